@@ -8,6 +8,9 @@ use std::path::PathBuf;
 /// Default HD derivation path for Ethereum (BIP44)
 pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0";
 
+/// SLIP-44 coin type of Ethereum, used unless another is configured
+pub const ETHEREUM_COIN_TYPE: u32 = 60;
+
 /// Default network name
 pub const DEFAULT_NETWORK: &str = "mainnet";
 
@@ -19,9 +22,23 @@ pub const SUPPORTED_NETWORKS: &[&str] = &[
     "holesky",
 ];
 
+/// Chain IDs for the supported network names
+pub const NETWORK_CHAIN_IDS: &[(&str, u64)] = &[
+    ("mainnet", 1),
+    ("sepolia", 11_155_111),
+    ("goerli", 5),
+    ("holesky", 17_000),
+];
+
 /// Default wallet directory name
 pub const DEFAULT_WALLET_DIR: &str = ".web3wallet";
 
+/// Configuration file name inside the default wallet directory
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Environment variable selecting a config profile when `--profile` is not given
+pub const PROFILE_ENV_VAR: &str = "WEB3WALLET_PROFILE";
+
 /// Keystore file extension
 pub const KEYSTORE_EXTENSION: &str = "json";
 
@@ -43,27 +60,57 @@ pub mod performance {
 pub mod crypto {
     /// Default Argon2id configuration (OWASP 2024 compliant)
     pub const DEFAULT_ARGON2_MEMORY: u32 = 47_104; // 46 MiB
+    /// Default Argon2id iteration count
     pub const DEFAULT_ARGON2_ITERATIONS: u32 = 1;
+    /// Default Argon2id parallelism
     pub const DEFAULT_ARGON2_PARALLELISM: u32 = 1;
 
     /// Alternative Argon2id configuration for lower memory systems
     pub const LOW_MEMORY_ARGON2_MEMORY: u32 = 19_456; // 19 MiB
+    /// Low-memory Argon2id iteration count
     pub const LOW_MEMORY_ARGON2_ITERATIONS: u32 = 2;
 
+    /// Argon2id memory for the paranoid KDF profile
+    pub const PARANOID_ARGON2_MEMORY: u32 = 262_144; // 256 MiB
+    /// Paranoid Argon2id iteration count
+    pub const PARANOID_ARGON2_ITERATIONS: u32 = 3;
+
+    /// Most Argon2id memory accepted from a file header (1 GiB, the largest `kdf-tune` candidate)
+    pub const MAX_ARGON2_MEMORY: u32 = 1_048_576;
+    /// Most Argon2id passes accepted from a file header
+    pub const MAX_ARGON2_ITERATIONS: u32 = 10;
+    /// Most Argon2id lanes accepted from a file header
+    pub const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+    /// Minimum PBKDF2-HMAC-SHA256 iterations accepted for mainnet keystores
+    pub const MIN_PBKDF2_ITERATIONS: u32 = 100_000;
+
     /// Salt length for key derivation
     pub const SALT_LENGTH: usize = 32;
 
     /// AES-GCM nonce length
     pub const NONCE_LENGTH: usize = 12;
 
+    /// AES-GCM authentication tag length, appended to the ciphertext
+    pub const TAG_LENGTH: usize = 16;
+
     /// Key length for AES-256
     pub const KEY_LENGTH: usize = 32;
 
+    /// Most keystores decrypted at once by batch unlocks (each Argon2id run holds its own memory)
+    pub const MAX_CONCURRENT_UNLOCKS: usize = 4;
+
     /// Minimum password length
     pub const MIN_PASSWORD_LENGTH: usize = 8;
 
     /// Maximum password length
     pub const MAX_PASSWORD_LENGTH: usize = 1024;
+
+    /// Prompts for a new password before giving up on a weak or mistyped one
+    pub const MAX_NEW_PASSWORD_ATTEMPTS: u32 = 3;
+
+    /// Most passphrase holders a threshold keystore can have
+    pub const MAX_KEY_SHARES: usize = 16;
 }
 
 /// File system configuration
@@ -76,6 +123,15 @@ pub mod fs {
 
     /// Maximum keystore file size (to prevent DoS)
     pub const MAX_KEYSTORE_SIZE: u64 = 1024 * 1024; // 1 MB
+
+    /// Maximum length of a keystore tag
+    pub const MAX_TAG_LENGTH: usize = 32;
+
+    /// Most wallets `create-batch` writes in one run
+    pub const MAX_BATCH_WALLETS: usize = 10_000;
+
+    /// Overwrite passes when shredding files
+    pub const SHRED_PASSES: u32 = 3;
 }
 
 /// BIP39 configuration
@@ -88,19 +144,158 @@ pub mod bip39 {
 
     /// Entropy bits for different word counts
     pub const ENTROPY_BITS_12: usize = 128;
+    /// Entropy bits for a 24-word mnemonic
     pub const ENTROPY_BITS_24: usize = 256;
 }
 
+/// Block explorer configuration
+pub mod explorer {
+    /// Etherscan's multichain API, used when `[explorer]` sets no `url`
+    pub const DEFAULT_API_URL: &str = "https://api.etherscan.io/v2/api";
+
+    /// Maximum accepted explorer response size
+    pub const MAX_RESPONSE_SIZE: usize = 8 * 1024 * 1024; // 8 MB
+
+    /// Transactions `wallet history` shows by default
+    pub const DEFAULT_HISTORY_LIMIT: usize = 25;
+}
+
+/// Chain registry configuration
+pub mod registry {
+    /// Registry override file written by `network update-registry` (inside the wallet directory)
+    pub const REGISTRY_FILE_NAME: &str = "chains.json";
+
+    /// Upstream chain metadata source (the dataset behind chainlist.org)
+    pub const DEFAULT_REGISTRY_URL: &str = "https://chainid.network/chains.json";
+
+    /// Maximum accepted registry download size
+    pub const MAX_REGISTRY_SIZE: usize = 16 * 1024 * 1024; // 16 MB
+}
+
+/// Low-resource mode for memory-constrained signing devices
+///
+/// Commands normally size Argon2id and worker pools for a desktop. In
+/// low-resource mode new keystores use the 19 MiB Argon2id profile, searches
+/// and batch unlocks run on one thread, and caches shrink, keeping the
+/// process within about 50 MB. The mode is the `low_resource` setting of
+/// [`WalletConfig`](crate::WalletConfig), passed down to the code it sizes.
+pub mod resources {
+    /// Machines with less total memory than this (in KiB) switch to low-resource mode automatically
+    pub const AUTO_DETECT_MEMORY_KB: u64 = 1024 * 1024; // 1 GiB
+
+    /// SQLite page cache in low-resource mode (in KiB)
+    pub const LOW_RESOURCE_SQLITE_CACHE_KB: u32 = 256;
+
+    /// Worker threads for CPU- or memory-heavy batches, at most `max`, and one in low-resource mode
+    pub fn parallelism(max: usize, low_resource: bool) -> usize {
+        if low_resource {
+            return 1;
+        }
+        std::thread::available_parallelism().map_or(1, usize::from).min(max).max(1)
+    }
+
+    /// Total physical memory in KiB, when the platform reports it
+    pub fn total_memory_kb() -> Option<u64> {
+        if cfg!(target_os = "linux") {
+            std::fs::read_to_string("/proc/meminfo").ok().as_deref().and_then(parse_mem_total)
+        } else {
+            None
+        }
+    }
+
+    /// Check if this machine looks like a low-spec device
+    pub fn detect_low_resource() -> bool {
+        total_memory_kb().is_some_and(|kb| kb < AUTO_DETECT_MEMORY_KB)
+    }
+
+    /// `MemTotal` of a `/proc/meminfo` listing, in KiB
+    pub(crate) fn parse_mem_total(meminfo: &str) -> Option<u64> {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix("MemTotal:"))
+            .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse().ok())
+    }
+}
+
+/// Backup archive configuration
+pub mod backup {
+    /// First line of every backup archive
+    pub const MAGIC: &str = "web3wallet-backup/v1";
+
+    /// Conventional archive file extension
+    pub const BACKUP_EXTENSION: &str = "w3w";
+
+    /// Maximum accepted archive size
+    pub const MAX_ARCHIVE_SIZE: u64 = 64 * 1024 * 1024; // 64 MB
+
+    /// Backup log file name inside the wallet directory
+    pub const LOG_FILE_NAME: &str = "backups.json";
+
+    /// Checksum manifest kept next to keystores and backup archives
+    pub const MANIFEST_FILE_NAME: &str = "MANIFEST.json";
+
+    /// Days after which a mainnet keystore's backup is considered stale
+    pub const DEFAULT_REMINDER_DAYS: u32 = 90;
+
+    /// Words asked for by `verify-backup` unless `--words` says otherwise
+    pub const QUIZ_WORDS: usize = 3;
+}
+
+/// Failed-unlock rate limiting
+pub mod unlock {
+    /// Wrong passwords allowed before delays start
+    pub const FREE_ATTEMPTS: u32 = 3;
+
+    /// Wait after the first counted failure; doubles with each further one
+    pub const BASE_DELAY_SECS: u64 = 30;
+
+    /// Longest wait between attempts
+    pub const MAX_DELAY_SECS: u64 = 3600;
+
+    /// Failures after which only `--reset-lockout` may try again
+    pub const HARD_LOCK_ATTEMPTS: u32 = 10;
+
+    /// Suffix of the hidden sidecar file next to each keystore
+    pub const SIDECAR_SUFFIX: &str = "lockout";
+}
+
+/// Breached password filter
+pub mod breach {
+    /// Installed filter inside the wallet directory
+    pub const FILTER_FILE_NAME: &str = "breached-passwords.bloom";
+
+    /// False positive rate of filters built by `breach-filter build`
+    pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+    /// Largest filter accepted, in bytes after decompression (a 0.1% filter of 100M passwords)
+    pub const MAX_FILTER_SIZE: u64 = 192 * 1024 * 1024;
+}
+
+/// Address book
+pub mod address_book {
+    /// Address book file inside the wallet directory
+    pub const FILE_NAME: &str = "address-book.json";
+
+    /// Longest label accepted for an entry
+    pub const MAX_LABEL_LENGTH: usize = 64;
+}
+
 /// CLI output configuration
 pub mod output {
     /// Table column widths
     pub const ADDRESS_COLUMN_WIDTH: usize = 43; // 0x + 40 hex chars + padding
+    /// Alias column width
     pub const ALIAS_COLUMN_WIDTH: usize = 20;
+    /// Network column width
     pub const NETWORK_COLUMN_WIDTH: usize = 15;
+    /// Date column width
     pub const DATE_COLUMN_WIDTH: usize = 20;
 
     /// JSON indentation
     pub const JSON_INDENT: usize = 2;
+
+    /// Addresses derived per batch when streaming NDJSON
+    pub const NDJSON_CHUNK: u32 = 256;
 }
 
 /// Application metadata
@@ -122,17 +317,34 @@ pub mod app {
 }
 
 /// Get default wallet directory path
+#[cfg(feature = "native")]
 pub fn default_wallet_dir() -> PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| std::path::PathBuf::from("."))
         .join(DEFAULT_WALLET_DIR)
 }
 
+/// Get default wallet directory path (relative without a home directory to look up)
+#[cfg(not(feature = "native"))]
+pub fn default_wallet_dir() -> PathBuf {
+    PathBuf::from(DEFAULT_WALLET_DIR)
+}
+
 /// Validate network name
+#[deprecated(since = "1.1.0", note = "parse a `models::Network` instead")]
 pub fn is_supported_network(network: &str) -> bool {
     SUPPORTED_NETWORKS.contains(&network)
 }
 
+/// Get the chain ID for a supported network name
+#[deprecated(since = "1.1.0", note = "use `models::Network::chain_id` instead")]
+pub fn chain_id_for_network(network: &str) -> Option<u64> {
+    NETWORK_CHAIN_IDS
+        .iter()
+        .find(|(name, _)| *name == network)
+        .map(|(_, id)| *id)
+}
+
 /// Get Argon2 configuration based on available memory
 pub fn get_argon2_config(use_low_memory: bool) -> (u32, u32, u32) {
     if use_low_memory {
@@ -150,6 +362,22 @@ pub fn get_argon2_config(use_low_memory: bool) -> (u32, u32, u32) {
     }
 }
 
+/// Check Argon2id cost against the OWASP minimum: 46 MiB at one pass,
+/// or 19 MiB with at least two passes
+pub fn is_strong_argon2(memory: u32, iterations: u32) -> bool {
+    memory >= crypto::DEFAULT_ARGON2_MEMORY
+        || (memory >= crypto::LOW_MEMORY_ARGON2_MEMORY
+            && iterations >= crypto::LOW_MEMORY_ARGON2_ITERATIONS)
+}
+
+/// Check Argon2id parameters read from a file before running them, so a
+/// crafted header cannot demand unbounded memory or time
+pub fn is_argon2_within_bounds(memory: u32, iterations: u32, parallelism: u32) -> bool {
+    (crypto::LOW_MEMORY_ARGON2_MEMORY..=crypto::MAX_ARGON2_MEMORY).contains(&memory)
+        && (1..=crypto::MAX_ARGON2_ITERATIONS).contains(&iterations)
+        && (1..=crypto::MAX_ARGON2_PARALLELISM).contains(&parallelism)
+}
+
 /// Validate word count for mnemonic generation
 pub fn is_supported_word_count(count: u8) -> bool {
     bip39::SUPPORTED_WORD_COUNTS.contains(&count)
@@ -169,12 +397,37 @@ mod tests {
     use super::*;
 
     #[test]
+    fn test_mem_total_parsing() {
+        let meminfo = "MemTotal:         443980 kB\nMemFree:           20112 kB\n";
+        assert_eq!(resources::parse_mem_total(meminfo), Some(443_980));
+        assert!(resources::parse_mem_total(meminfo).unwrap() < resources::AUTO_DETECT_MEMORY_KB);
+        assert_eq!(resources::parse_mem_total("MemFree: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_low_resource_config() {
+        let config: crate::WalletConfig = toml::from_str("low_resource = true\n").unwrap();
+        assert_eq!(config.low_resource, Some(true));
+        assert!(config.is_low_resource());
+        assert_eq!(crate::WalletConfig::default().low_resource, None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn test_supported_networks() {
         assert!(is_supported_network("mainnet"));
         assert!(is_supported_network("sepolia"));
         assert!(!is_supported_network("invalid"));
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_network_chain_ids() {
+        assert_eq!(chain_id_for_network("mainnet"), Some(1));
+        assert_eq!(chain_id_for_network("sepolia"), Some(11_155_111));
+        assert_eq!(chain_id_for_network("invalid"), None);
+    }
+
     #[test]
     fn test_supported_word_counts() {
         assert!(is_supported_word_count(12));
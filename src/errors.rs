@@ -130,6 +130,24 @@ pub enum CryptographicError {
         /// Error details
         details: String,
     },
+
+    /// KDF parameters too weak for the target network
+    #[error("CRYPTO_011: Key derivation parameters are too weak")]
+    WeakKdfParameters {
+        /// Parameters that were rejected
+        details: String,
+        /// Minimum accepted parameters
+        minimum: String,
+    },
+
+    /// The wallet does not hold the key material an operation needs
+    #[error("CRYPTO_012: Key material not available in this wallet")]
+    KeyUnavailable {
+        /// What is missing
+        details: String,
+        /// Suggestion for resolution
+        suggestion: String,
+    },
 }
 
 /// File system operation errors (FS_xxx)
@@ -281,6 +299,13 @@ pub enum UserInputError {
         /// Timeout duration
         duration: std::time::Duration,
     },
+
+    /// Operation needs private keys but the wallet is watch-only
+    #[error("INPUT_009: Watch-only wallet cannot sign or reveal keys")]
+    WatchOnlyWallet {
+        /// Wallet file path
+        path: String,
+    },
 }
 
 /// Authentication errors (AUTH_xxx)
@@ -289,10 +314,10 @@ pub enum AuthenticationError {
     /// Wrong password for wallet decryption
     #[error("AUTH_001: Incorrect password for wallet decryption")]
     WrongPassword {
-        /// Wallet file
+        /// Wallet file, empty when the caller only had its contents
         wallet_file: String,
-        /// Remaining attempts
-        attempts_remaining: u32,
+        /// Wrong passwords left before the keystore locks, when rate limited
+        attempts_remaining: Option<u32>,
     },
 
     /// Password too weak
@@ -316,6 +341,38 @@ pub enum AuthenticationError {
     /// User canceled authentication
     #[error("AUTH_005: User canceled authentication")]
     UserCanceled,
+
+    /// Daily signing quota exhausted for a keystore
+    #[error("AUTH_006: Signing quota exceeded")]
+    QuotaExceeded {
+        /// Keystore identifier
+        keystore: String,
+        /// Configured hard daily limit
+        limit: u32,
+    },
+
+    /// OS credential store could not be used
+    #[error("AUTH_007: Credential store unavailable")]
+    CredentialStore {
+        /// Error details
+        details: String,
+    },
+
+    /// One-time code missing, wrong, or already used
+    #[error("AUTH_008: Second factor verification failed")]
+    SecondFactorFailed {
+        /// Keystore identifier
+        keystore: String,
+        /// Error details
+        details: String,
+    },
+
+    /// Command needs a feature the config disables
+    #[error("AUTH_009: Feature disabled by configuration: {feature}")]
+    FeatureDisabled {
+        /// Disabled feature class
+        feature: String,
+    },
 }
 
 /// Network operation errors (NETWORK_xxx)
@@ -417,6 +474,29 @@ pub enum ValidationError {
 /// Convenient result type alias
 pub type WalletResult<T> = Result<T, WalletError>;
 
+/// Process exit codes, one per error category
+///
+/// Scripts can tell a wrong password from a missing file without parsing
+/// stderr. The values are stable; new categories get new numbers.
+pub mod exit_code {
+    /// Success
+    pub const SUCCESS: i32 = 0;
+    /// Error outside the categories below
+    pub const GENERAL: i32 = 1;
+    /// Invalid input or usage (INPUT_xxx; also clap's usage errors)
+    pub const INPUT: i32 = 2;
+    /// Authentication failed or was refused (AUTH_xxx)
+    pub const AUTH: i32 = 3;
+    /// File system error (FS_xxx, IO_ERROR)
+    pub const FILESYSTEM: i32 = 4;
+    /// Cryptographic failure (CRYPTO_xxx)
+    pub const CRYPTO: i32 = 5;
+    /// Network failure (NETWORK_xxx)
+    pub const NETWORK: i32 = 6;
+    /// Data validation failure (VALIDATION_xxx)
+    pub const VALIDATION: i32 = 7;
+}
+
 impl WalletError {
     /// Get error code for programmatic handling
     pub fn code(&self) -> &'static str {
@@ -433,6 +513,21 @@ impl WalletError {
         }
     }
 
+    /// Process exit code for this error's category, from its `code()` prefix
+    pub fn exit_code(&self) -> i32 {
+        let code = self.code();
+        let category = code.split('_').next().unwrap_or(code);
+        match category {
+            "INPUT" => exit_code::INPUT,
+            "AUTH" => exit_code::AUTH,
+            "FS" | "IO" => exit_code::FILESYSTEM,
+            "CRYPTO" => exit_code::CRYPTO,
+            "NETWORK" => exit_code::NETWORK,
+            "VALIDATION" => exit_code::VALIDATION,
+            _ => exit_code::GENERAL,
+        }
+    }
+
     /// Get user-friendly suggestion for error resolution
     pub fn suggestion(&self) -> Option<String> {
         match self {
@@ -463,6 +558,8 @@ impl CryptographicError {
             CryptographicError::KdfFailed { .. } => "CRYPTO_008",
             CryptographicError::SignatureFailed { .. } => "CRYPTO_009",
             CryptographicError::AddressGenerationFailed { .. } => "CRYPTO_010",
+            CryptographicError::WeakKdfParameters { .. } => "CRYPTO_011",
+            CryptographicError::KeyUnavailable { .. } => "CRYPTO_012",
         }
     }
 
@@ -473,6 +570,52 @@ impl CryptographicError {
             CryptographicError::InvalidPrivateKey { expected, .. } => {
                 Some(format!("Expected format: {}", expected))
             }
+            CryptographicError::WeakKdfParameters { minimum, .. } => Some(format!(
+                "Mainnet keystores require at least {}. Pass --allow-weak-kdf only for throwaway test wallets.",
+                minimum
+            )),
+            CryptographicError::KeyUnavailable { suggestion, .. } => Some(suggestion.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl AuthenticationError {
+    fn code(&self) -> &'static str {
+        match self {
+            AuthenticationError::WrongPassword { .. } => "AUTH_001",
+            AuthenticationError::WeakPassword { .. } => "AUTH_002",
+            AuthenticationError::MaxAttemptsExceeded { .. } => "AUTH_003",
+            AuthenticationError::SessionTimeout => "AUTH_004",
+            AuthenticationError::UserCanceled => "AUTH_005",
+            AuthenticationError::QuotaExceeded { .. } => "AUTH_006",
+            AuthenticationError::CredentialStore { .. } => "AUTH_007",
+            AuthenticationError::SecondFactorFailed { .. } => "AUTH_008",
+            AuthenticationError::FeatureDisabled { .. } => "AUTH_009",
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        match self {
+            AuthenticationError::WrongPassword { attempts_remaining: Some(0), .. } => Some(
+                "The keystore is now locked; retry with --reset-lockout and the correct password".to_string(),
+            ),
+            AuthenticationError::WrongPassword { attempts_remaining: Some(remaining), .. } => Some(format!(
+                "Check the password; {} more wrong attempt(s) before the keystore locks",
+                remaining
+            )),
+            AuthenticationError::WrongPassword { attempts_remaining: None, .. } => {
+                Some("Check the password".to_string())
+            }
+            AuthenticationError::WeakPassword { requirements } => {
+                Some(format!("Password needs: {}", requirements.join(", ")))
+            }
+            AuthenticationError::MaxAttemptsExceeded { lockout_duration } if lockout_duration.is_zero() => {
+                Some("Retry with --reset-lockout and the correct password".to_string())
+            }
+            AuthenticationError::MaxAttemptsExceeded { lockout_duration } => {
+                Some(format!("Try again in {} s", lockout_duration.as_secs().max(1)))
+            }
             _ => None,
         }
     }
@@ -495,7 +638,6 @@ macro_rules! impl_error_traits {
 
 impl_error_traits!(FileSystemError, "FS");
 impl_error_traits!(UserInputError, "INPUT");
-impl_error_traits!(AuthenticationError, "AUTH");
 impl_error_traits!(NetworkError, "NETWORK");
 impl_error_traits!(ValidationError, "VALIDATION");
 
@@ -12,10 +12,20 @@
 //! - **Error Handling**: Comprehensive Result-based error handling
 //! - **Performance**: <1s response time with <50MB memory usage
 //!
+//! ## WebAssembly
+//!
+//! Filesystem, async runtime and network support sit behind the default
+//! `native` feature. Built with `default-features = false` for
+//! `wasm32-unknown-unknown`, the core (mnemonics, derivation, keystore
+//! encryption and decryption) remains, with randomness from the browser
+//! and keystores kept in a [`services::storage::KeystoreStorage`].
+//!
 //! ## Example Usage
 //!
+//! Library consumers import the stable API from [`prelude`]:
+//!
 //! ```rust,no_run
-//! use web3wallet_cli::{WalletManager, WalletConfig};
+//! use web3wallet_cli::prelude::*;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -51,26 +61,30 @@
     while_true
 )]
 
-pub mod cli;
+pub(crate) mod cli;
 pub mod config;
 pub mod errors;
 pub mod models;
+pub mod prelude;
+pub mod security;
 pub mod services;
 pub mod utils;
 
 // Re-export main types for convenience
 pub use errors::{WalletError, WalletResult};
 pub use models::{Address, Keystore, Wallet};
-pub use services::WalletManager;
+#[cfg(feature = "native")]
+pub use services::{WalletManager, WalletOps};
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Default wallet configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct WalletConfig {
     /// Default network to use
-    pub network: String,
+    pub network: models::Network,
     /// Default wallet directory
     pub wallet_dir: std::path::PathBuf,
     /// KDF iteration count for Argon2id
@@ -79,22 +93,241 @@ pub struct WalletConfig {
     pub kdf_memory: u32,
     /// Parallelism for Argon2id
     pub kdf_parallelism: u32,
+    /// Argon2id preset for new keystores; `custom` uses the three values above, as does `default` once they are changed
+    pub kdf_profile: services::crypto::KdfProfile,
+    /// Keystore filename template (e.g. "{network}-{alias}-{fingerprint}")
+    pub save_template: Option<String>,
+    /// Directory for decrypted exports (defaults to a tmpfs location when available)
+    pub export_dir: Option<std::path::PathBuf>,
+    /// Allow saving mainnet keystores with KDF parameters below the minimum
+    pub allow_weak_kdf: bool,
+    /// Let keystores past the wrong-password hard limit be tried again (`--reset-lockout`, never read from the file)
+    #[serde(skip)]
+    pub reset_lockout: bool,
+    /// Accept breached passwords for new keystores with a warning (`--allow-weak`, never read from the file)
+    #[serde(skip)]
+    pub allow_breached: bool,
+    /// Where passwords come from instead of a prompt (`--password-file`, `--password-stdin` or `WALLET_PASSWORD`, never read from the file)
+    #[serde(skip)]
+    pub password_source: Option<security::PasswordSource>,
+    /// Low-resource mode for low-spec devices (unset: enabled automatically below 1 GiB of RAM)
+    pub low_resource: Option<bool>,
+    /// Daily signing quotas
+    pub quotas: services::usage::QuotaConfig,
+    /// When TOTP-enrolled keystores need a one-time code to sign
+    pub totp: services::totp::TotpConfig,
+    /// Feature classes refused for every command (e.g. `["export-secret", "sign"]`)
+    pub disable: Vec<security::Feature>,
+    /// Process-level protections for secrets in memory
+    pub security: security::SecurityConfig,
+    /// Session agent settings
+    #[cfg(feature = "native")]
+    pub agent: services::agent::AgentConfig,
+    /// Warn when a mainnet keystore has not been backed up for this many days (0 disables)
+    pub backup_reminder_days: u32,
+    /// RPC endpoint URLs keyed by network name
+    pub rpc_endpoints: std::collections::BTreeMap<String, String>,
+    /// How addresses are shown in JSON output (`checksum` or `lowercase`); files always store lowercase
+    pub address_case: models::AddressCase,
+    /// SLIP-44 coin types keyed by network name, for networks that do not derive with 60
+    pub coin_types: std::collections::BTreeMap<String, u32>,
+    /// PKCS#11 token holding the signing key
+    pub pkcs11: Option<services::signer::Pkcs11Config>,
+    /// Etherscan-compatible explorer for contract ABIs, names and address history
+    #[cfg(feature = "native")]
+    pub explorer: Option<services::explorer::ExplorerConfig>,
+    /// Named profiles (`[profile.work]`) overriding the settings above
+    pub profile: std::collections::BTreeMap<String, ProfileConfig>,
+}
+
+/// Settings a named profile may override
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// Default network to use
+    pub network: Option<models::Network>,
+    /// Wallet directory
+    pub wallet_dir: Option<std::path::PathBuf>,
+    /// RPC endpoint URLs keyed by network name, merged over the top-level table
+    pub rpc_endpoints: std::collections::BTreeMap<String, String>,
+    /// KDF iteration count for Argon2id
+    pub kdf_iterations: Option<u32>,
+    /// Memory usage for Argon2id (in KB)
+    pub kdf_memory: Option<u32>,
+    /// Parallelism for Argon2id
+    pub kdf_parallelism: Option<u32>,
+    /// Argon2id preset for new keystores
+    pub kdf_profile: Option<services::crypto::KdfProfile>,
 }
 
 impl Default for WalletConfig {
     fn default() -> Self {
         Self {
-            network: "mainnet".to_string(),
-            wallet_dir: dirs::home_dir()
-                .unwrap_or_else(|| std::path::PathBuf::from("."))
-                .join(".web3wallet"),
+            network: models::Network::Mainnet,
+            wallet_dir: config::default_wallet_dir(),
             kdf_iterations: 1,
             kdf_memory: 47_104, // 46 MiB
             kdf_parallelism: 1,
+            kdf_profile: services::crypto::KdfProfile::Default,
+            save_template: None,
+            export_dir: None,
+            allow_weak_kdf: false,
+            reset_lockout: false,
+            allow_breached: false,
+            password_source: None,
+            low_resource: None,
+            quotas: services::usage::QuotaConfig::default(),
+            totp: services::totp::TotpConfig::default(),
+            disable: Vec::new(),
+            security: security::SecurityConfig::default(),
+            #[cfg(feature = "native")]
+            agent: services::agent::AgentConfig::default(),
+            backup_reminder_days: config::backup::DEFAULT_REMINDER_DAYS,
+            rpc_endpoints: std::collections::BTreeMap::new(),
+            address_case: models::AddressCase::Checksum,
+            coin_types: std::collections::BTreeMap::new(),
+            pkcs11: None,
+            #[cfg(feature = "native")]
+            explorer: None,
+            profile: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+impl WalletConfig {
+    /// Load configuration from a TOML file; missing keys keep their defaults
+    pub fn load(path: &std::path::Path) -> WalletResult<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            errors::FileSystemError::PermissionDenied {
+                path: path.display().to_string(),
+                operation: format!("read config: {}", e),
+            }
+        })?;
+
+        let config: Self = toml::from_str(&contents).map_err(|e| {
+            errors::FileSystemError::InvalidFormat {
+                path: path.display().to_string(),
+                details: e.to_string(),
+            }
+        })?;
+
+        if let Some(template) = &config.save_template {
+            services::naming::NamingService::validate_template(template)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Apply the overrides of the named profile
+    pub fn with_profile(mut self, name: &str) -> WalletResult<Self> {
+        let profile = self.profile.get(name).cloned().ok_or_else(|| {
+            let known: Vec<&str> = self.profile.keys().map(String::as_str).collect();
+            errors::UserInputError::InvalidParameters {
+                parameter: "profile".to_string(),
+                value: name.to_string(),
+                expected: if known.is_empty() {
+                    "a [profile.<name>] section in the config file".to_string()
+                } else {
+                    format!("one of: {}", known.join(", "))
+                },
+            }
+        })?;
+
+        if let Some(network) = profile.network {
+            self.network = network;
+        }
+        if let Some(wallet_dir) = profile.wallet_dir {
+            self.wallet_dir = wallet_dir;
+        }
+        self.rpc_endpoints.extend(profile.rpc_endpoints);
+        if let Some(iterations) = profile.kdf_iterations {
+            self.kdf_iterations = iterations;
+        }
+        if let Some(memory) = profile.kdf_memory {
+            self.kdf_memory = memory;
         }
+        if let Some(parallelism) = profile.kdf_parallelism {
+            self.kdf_parallelism = parallelism;
+        }
+        if let Some(kdf_profile) = profile.kdf_profile {
+            self.kdf_profile = kdf_profile;
+        }
+
+        Ok(self)
+    }
+
+    /// Check if low-resource mode is on: as configured, else when the machine has little memory
+    pub fn is_low_resource(&self) -> bool {
+        self.low_resource.unwrap_or_else(config::resources::detect_low_resource)
+    }
+
+    /// Argon2id cost new keystores are encrypted with
+    pub fn kdf_settings(&self) -> services::crypto::KdfSettings {
+        self.kdf_profile.settings(self)
+    }
+
+    /// Configured RPC endpoint for a network
+    pub fn rpc_endpoint(&self, network: &str) -> Option<&str> {
+        self.rpc_endpoints.get(network).map(String::as_str)
+    }
+
+    /// SLIP-44 coin type new wallets on a network derive with
+    pub fn coin_type(&self, network: &str) -> u32 {
+        self.coin_types.get(network).copied().unwrap_or(config::ETHEREUM_COIN_TYPE)
     }
 }
 
 
 /// Result type alias for convenience
-pub type Result<T> = std::result::Result<T, WalletError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, WalletError>;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_loading() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+
+        std::fs::write(&path, "network = \"sepolia\"\nsave_template = \"{network}-{alias}\"\n").unwrap();
+        let config = WalletConfig::load(&path).unwrap();
+        assert_eq!(config.network, "sepolia");
+        assert_eq!(config.save_template.as_deref(), Some("{network}-{alias}"));
+        assert_eq!(config.kdf_memory, WalletConfig::default().kdf_memory);
+
+        std::fs::write(&path, "save_template = \"{nope}\"\n").unwrap();
+        assert!(WalletConfig::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_config_coin_types() {
+        let config: WalletConfig = toml::from_str("[coin_types]\nholesky = 1\n").unwrap();
+        assert_eq!(config.coin_type("holesky"), 1);
+        assert_eq!(config.coin_type("mainnet"), config::ETHEREUM_COIN_TYPE);
+    }
+
+    #[test]
+    fn test_config_profiles() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+
+        std::fs::write(
+            &path,
+            "network = \"mainnet\"\n\
+             [rpc_endpoints]\nmainnet = \"https://eth.example\"\n\n\
+             [profile.work]\nnetwork = \"sepolia\"\nwallet_dir = \"/srv/wallets\"\nkdf_memory = 65536\n\n\
+             [profile.work.rpc_endpoints]\nsepolia = \"https://sepolia.example\"\n",
+        )
+        .unwrap();
+
+        let config = WalletConfig::load(&path).unwrap().with_profile("work").unwrap();
+        assert_eq!(config.network, "sepolia");
+        assert_eq!(config.wallet_dir, std::path::PathBuf::from("/srv/wallets"));
+        assert_eq!(config.kdf_memory, 65536);
+        assert_eq!(config.kdf_iterations, WalletConfig::default().kdf_iterations);
+        assert_eq!(config.rpc_endpoint("sepolia"), Some("https://sepolia.example"));
+        assert_eq!(config.rpc_endpoint("mainnet"), Some("https://eth.example"));
+
+        assert!(WalletConfig::load(&path).unwrap().with_profile("personal").is_err());
+    }
+}
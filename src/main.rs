@@ -3,12 +3,48 @@
 //! Main entry point for the Web3 wallet CLI tool.
 //! Provides secure Ethereum wallet management with BIP39/BIP44 compliance.
 
+use bip39::Language;
 use clap::{Args, Parser, Subcommand};
-use rpassword::prompt_password;
 use std::path::PathBuf;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use web3wallet_cli::{WalletConfig, WalletError, WalletManager, WalletResult};
-use web3wallet_cli::errors::{UserInputError, FileSystemError};
+use web3wallet_cli::models::{Address, AddressBook, AddressCase, CommandResult, DerivationPath, EthereumAddress, Keystore, Network, PathPreset, WatchOnlyWallet};
+use web3wallet_cli::security::{keychain, lockdown, password, permissions, shred, tpm, workspace, yubikey, ExportWorkspace, Feature, PasswordSource, PermissionStatus};
+use web3wallet_cli::services::address_check::{AddressCheckService, CheckStatus};
+use web3wallet_cli::services::audit::AuditRecord;
+use web3wallet_cli::services::crypto::{HardwareBinding, KdfProfile};
+use web3wallet_cli::services::agent::{AgentClient, AgentServer};
+use web3wallet_cli::services::breach::BreachedPasswords;
+use web3wallet_cli::services::ur;
+use web3wallet_cli::services::{AirgapService, AuditAction, AuditLog, BackupService, Bip85Service, ChainRegistry, ChecksumManifest, ConfigCheckService, CryptoService, KdfBenchService, ExportContext, ExportRegistry, GapScanService, IdentityService, NamingService, OwnershipService, RecoveryService, ThresholdService, TotpStore, UsageTracker};
+#[cfg(feature = "btc")]
+use web3wallet_cli::services::btc::{BtcNetwork, BtcScheme, BtcService};
+#[cfg(feature = "solana")]
+use web3wallet_cli::services::SolanaService;
+use web3wallet_cli::services::identity::IdentityBundle;
+use web3wallet_cli::services::ownership::{OwnershipProof, VerifyOptions};
+use web3wallet_cli::services::signer::{KeySigner, LocalKeySigner, Pkcs11Signer};
+use web3wallet_cli::services::approvals::{Approval, ApprovalService};
+use web3wallet_cli::services::contract_call::{self, CallOutcome, ContractCallService};
+use web3wallet_cli::services::explorer::{ExplorerClient, ExplorerTransaction};
+use web3wallet_cli::services::nft::{NftService, NftStandard};
+use web3wallet_cli::services::psbt::{PartiallySignedTransaction, PsbtPayload, PsbtService, SignerPolicy};
+use web3wallet_cli::services::safe::{SafeOperation, SafeProposal, SafeService, SafeTransaction};
+use web3wallet_cli::services::signer_daemon::{self, ApprovalRequest, Approver, SignerDaemon};
+use web3wallet_cli::services::eth_encryption::{EncryptedMessage, EncryptionKey, EthEncryptionService};
+use web3wallet_cli::services::stealth::{self, Announcement, StealthKeys, StealthMetaAddress, StealthService};
+use web3wallet_cli::services::siwe::{SiweMessage, SiweService, SiweSignature, SiweVerifyOptions};
+use web3wallet_cli::services::totp::TotpSecret;
+use web3wallet_cli::services::usage::QuotaStatus;
+use web3wallet_cli::services::recovery::RecoveryPattern;
+use web3wallet_cli::services::config_check::Severity;
+use web3wallet_cli::services::mnemonic::{MnemonicService, SecureMnemonic};
+use web3wallet_cli::services::staking::StakingService;
+use web3wallet_cli::services::validator_keystore::{ValidatorKdf, ValidatorKeystoreService};
+use web3wallet_cli::services::vanity::{VanityPattern, VanityProgress, VanityService};
+use web3wallet_cli::services::backup::{BackupLog, BackupManifest, BackupStatus};
+use web3wallet_cli::services::keystore_index::{self, KeystoreFilter, KeystoreQuery, KeystoreSort};
+use web3wallet_cli::errors::{AuthenticationError, UserInputError, FileSystemError, NetworkError, ValidationError};
 
 /// Web3 Wallet CLI - Secure Ethereum wallet management
 #[derive(Parser)]
@@ -23,6 +59,11 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Log TRACE-level spans with timings, optionally only for comma-separated
+    /// module targets such as `services::crypto,models::wallet`
+    #[arg(long, global = true, value_name = "TARGETS", num_args = 0..=1, require_equals = true, default_missing_value = "")]
+    trace: Option<String>,
+
     /// Output format
     #[arg(short, long, value_enum, default_value = "table", global = true)]
     output: OutputFormat,
@@ -31,17 +72,51 @@ struct Cli {
     #[arg(short, long, global = true)]
     config: Option<std::path::PathBuf>,
 
+    /// Config profile to apply (defaults to $WEB3WALLET_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Use the low-memory KDF profile and single-threaded work for low-spec devices
+    #[arg(long, global = true)]
+    low_resource: bool,
+
+    /// Try a keystore locked after too many wrong passwords; the right one clears the lock
+    #[arg(long, global = true)]
+    reset_lockout: bool,
+
+    /// Accept a new password found in a breach list, with a warning
+    #[arg(long, global = true)]
+    allow_weak: bool,
+
+    /// Argon2id cost for new keystores (default, low-memory, paranoid, custom)
+    #[arg(long, global = true, value_parser = parse_kdf_profile)]
+    kdf_profile: Option<KdfProfile>,
+
+    /// Read the wallet password from the first line of this file
+    #[arg(long, global = true, conflicts_with = "password_stdin")]
+    password_file: Option<PathBuf>,
+
+    /// Read the wallet password from the first line of standard input
+    #[arg(long, global = true)]
+    password_stdin: bool,
+
+    /// Fail instead of prompting (supply the password with a flag above or $WALLET_PASSWORD)
+    #[arg(long, global = true)]
+    non_interactive: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 /// Output format options
-#[derive(clap::ValueEnum, Clone, Debug)]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
 enum OutputFormat {
     /// Human-readable table format
     Table,
     /// Machine-readable JSON format
     Json,
+    /// Newline-delimited JSON; `derive` streams one line per address
+    Ndjson,
 }
 
 /// Available wallet commands
@@ -49,14 +124,121 @@ enum OutputFormat {
 enum Commands {
     /// Create a new wallet
     Create(CreateArgs),
+    /// Create many independent wallets and write a manifest of their addresses
+    CreateBatch(CreateBatchArgs),
     /// Import an existing wallet
     Import(ImportArgs),
     /// Load and display wallet information
     Load(LoadArgs),
+    /// Describe a keystore file without decrypting it
+    Info(InfoArgs),
     /// List all stored wallets
     List(ListArgs),
     /// Derive addresses from HD wallet
     Derive(DeriveArgs),
+    /// Inspect the offline chain registry
+    Network(NetworkArgs),
+    /// Check wallet storage for insecure file permissions
+    Doctor(DoctorArgs),
+    /// Write an encrypted archive of the whole wallet directory
+    Backup(BackupArgs),
+    /// Unpack a backup archive into a fresh wallet directory
+    Restore(RestoreArgs),
+    /// Quiz yourself on random mnemonic words to confirm a paper backup
+    VerifyBackup(VerifyBackupArgs),
+    /// Check keystores and archives against their MANIFEST.json checksums
+    VerifyManifest(VerifyManifestArgs),
+    /// Show or reset per-keystore signing counters
+    Usage(UsageArgs),
+    /// Show or verify the audit log of sensitive operations
+    Audit(AuditArgs),
+    /// Manage keystore passwords stored in the OS keychain
+    Keychain(KeychainArgs),
+    /// Keep unlocked wallets in a background agent so later commands do not prompt
+    Agent(AgentArgs),
+    /// Enroll keystores for one-time codes on risky signing requests
+    Totp(TotpArgs),
+    /// Require a YubiKey, besides the password, to decrypt a keystore
+    Yubikey(YubikeyArgs),
+    /// Inspect the loaded configuration
+    Config(ConfigArgs),
+    /// Time Argon2id on this machine and store the strongest parameters within a target
+    KdfBench(KdfBenchArgs),
+    /// Check passwords against leaked ones offline, and install larger breach lists
+    BreachFilter(BreachFilterArgs),
+    /// Manage derived accounts stored inside a keystore file
+    Accounts(AccountsArgs),
+    /// Edit keystore tags and notes
+    Tag(TagArgs),
+    /// Keep labelled addresses that other commands accept in place of hex
+    Book(BookArgs),
+    /// Inspect and re-encrypt EIP-2335 validator keystores
+    Validator(ValidatorArgs),
+    /// Export a wallet in another format
+    Export(ExportArgs),
+    /// Render a printable PDF backup sheet, generated offline
+    Paper(PaperArgs),
+    /// Derive a BIP85 child mnemonic from an HD wallet
+    Bip85(Bip85Args),
+    /// Recover unreadable words of a mnemonic backup
+    Recover(RecoverArgs),
+    /// Sign a statement proving control of a wallet address
+    ProveOwnership(ProveOwnershipArgs),
+    /// Verify an ownership statement signed by someone else
+    ProveVerify(ProveVerifyArgs),
+    /// Build and sign a Sign-In with Ethereum (EIP-4361) message
+    Siwe(SiweArgs),
+    /// Verify a signed Sign-In with Ethereum message
+    SiweVerify(SiweVerifyArgs),
+    /// Show the public key others encrypt notes to (eth_getEncryptionPublicKey)
+    EncryptionKey(EncryptionKeyArgs),
+    /// Encrypt a note to an encryption public key (x25519-xsalsa20-poly1305)
+    EncryptMsg(EncryptMsgArgs),
+    /// Decrypt a note sealed to one of this wallet's accounts (eth_decrypt)
+    DecryptMsg(DecryptMsgArgs),
+    /// Receive to ERC-5564 stealth addresses
+    Stealth(StealthArgs),
+    /// Export or verify signed identity bundles
+    Identity(IdentityArgs),
+    /// Show the account extended public key for watch-only address generation
+    Xpub(XpubArgs),
+    /// Track addresses from an xpub or a bare address without private keys
+    Watch(WatchArgs),
+    /// Find used addresses of a wallet by scanning up to the gap limit
+    Scan(ScanArgs),
+    /// Re-encode a keystore canonically after checking it still decrypts
+    Compact(CompactArgs),
+    /// Overwrite files before deleting them (see the limits on SSDs)
+    ///
+    /// Overwriting reaches the original blocks only on filesystems that
+    /// rewrite files in place. SSD wear leveling, copy-on-write filesystems
+    /// (btrfs, ZFS, APFS), snapshots and backups can keep old copies; only
+    /// full-disk encryption protects those.
+    Shred(ShredArgs),
+    /// Search for an address with a chosen hex prefix and/or suffix
+    Vanity(VanityArgs),
+    /// Sign through an offline device with animated UR QR codes (ERC-4527)
+    Airgap(AirgapArgs),
+    /// Serve a wallet to geth, reth and dapp tooling as a clef-compatible external signer
+    Daemon(DaemonArgs),
+    /// Prepare, sign and combine Safe multisig transactions offline
+    Safe(SafeArgs),
+    /// Pass a multisig transaction between signers in a partially signed envelope
+    PsbtEth(PsbtEthArgs),
+    /// Review the token approvals an address has granted and build revoke transactions
+    Approvals(ApprovalsArgs),
+    /// Build, check and sign ERC-721 and ERC-1155 token transfers
+    Nft(NftArgs),
+    /// Encode a contract call from its ABI, and run it with eth_call
+    Call(CallArgs),
+    /// List an address's transactions from the configured block explorer
+    History(HistoryArgs),
+    /// Show the Bitcoin addresses controlled by a wallet's seed
+    #[cfg(feature = "btc")]
+    Btc(BtcArgs),
+    /// Show the Solana addresses controlled by a wallet's seed
+    #[cfg(feature = "solana")]
+    Solana(SolanaArgs),
 }
 
 /// Arguments for wallet creation
@@ -70,29 +252,127 @@ struct CreateArgs {
     #[arg(short, long)]
     save: Option<String>,
 
+    /// Keystore filename template, e.g. "{network}-{alias}-{fingerprint}" (implies saving)
+    #[arg(long)]
+    save_template: Option<String>,
+
     /// Target network
     #[arg(short, long, default_value = "mainnet")]
-    network: String,
+    network: Network,
+    /// Save mainnet keystores even if the configured KDF is below the minimum
+    #[arg(long)]
+    allow_weak_kdf: bool,
+    /// BIP39 wordlist (english, japanese, korean, spanish, chinese-simplified,
+    /// chinese-traditional, french, italian, czech, portuguese)
+    #[arg(short, long, value_parser = parse_language, default_value = "english")]
+    language: Language,
+    /// BIP44 account index (m/44'/60'/N'/0)
+    #[arg(long)]
+    account: Option<u32>,
+    /// SLIP-44 coin type to derive with (e.g. 61), or a registry chain that has one (e.g. gno)
+    #[arg(long)]
+    coin_type: Option<String>,
+    /// Also bind the saved keystore to this machine's TPM, so a copy is useless elsewhere
+    #[arg(long)]
+    bind_hardware: bool,
+    /// Split the saved keystore among --share holders so that any N of them can open it
+    #[arg(long, requires = "shares")]
+    threshold: Option<u8>,
+    /// Passphrase holder of a threshold keystore; repeat for each (e.g. --share personal --share spouse)
+    #[arg(long = "share", value_name = "LABEL", requires = "threshold")]
+    shares: Vec<String>,
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for bulk wallet creation
+#[derive(Args)]
+struct CreateBatchArgs {
+    /// Number of wallets to create
+    #[arg(short = 'n', long)]
+    count: usize,
+
+    /// Directory for the keystores and manifest (defaults to the wallet directory)
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// Manifest file (CSV, or JSON with a .json extension; defaults to manifest.csv in the output directory)
+    #[arg(long)]
+    manifest: Option<PathBuf>,
+
+    /// Number of mnemonic words (12 or 24)
+    #[arg(short, long, value_parser = validate_word_count, default_value = "12")]
+    words: u8,
+
+    /// BIP39 wordlist
+    #[arg(short, long, value_parser = parse_language, default_value = "english")]
+    language: Language,
+
+    /// Target network (defaults to the configured network)
+    #[arg(long)]
+    network: Option<Network>,
+
+    /// Save mainnet keystores even if the configured KDF is below the minimum
+    #[arg(long)]
+    allow_weak_kdf: bool,
 }
 
 /// Arguments for wallet import
 #[derive(Args)]
 struct ImportArgs {
     /// BIP39 mnemonic phrase
-    #[arg(short, long, conflicts_with = "private_key")]
+    #[arg(short, long, conflicts_with_all = ["private_key", "seed", "entropy"])]
     mnemonic: Option<String>,
 
-    /// Private key (hex format)
-    #[arg(short, long, conflicts_with = "mnemonic")]
+    /// Private key (hex, base64, WIF, or a [byte, ...] array)
+    #[arg(short, long, conflicts_with_all = ["seed", "entropy"])]
     private_key: Option<String>,
 
+    /// Raw BIP39 seed as hex (64 bytes), for tools that exported only the seed
+    #[arg(long, conflicts_with = "entropy")]
+    seed: Option<String>,
+
+    /// BIP39 entropy as hex (16-32 bytes); the mnemonic is regenerated from it
+    #[arg(long)]
+    entropy: Option<String>,
+
     /// Save wallet to file
     #[arg(short, long)]
     save: Option<String>,
 
+    /// Keystore filename template, e.g. "{network}-{alias}-{fingerprint}" (implies saving)
+    #[arg(long)]
+    save_template: Option<String>,
+
     /// Target network
     #[arg(short, long, default_value = "mainnet")]
-    network: String,
+    network: Network,
+    /// Save mainnet keystores even if the configured KDF is below the minimum
+    #[arg(long)]
+    allow_weak_kdf: bool,
+    /// BIP39 wordlist the mnemonic must come from (detected when omitted)
+    #[arg(short, long, value_parser = parse_language)]
+    language: Option<Language>,
+    /// BIP44 account index (m/44'/60'/N'/0)
+    #[arg(long)]
+    account: Option<u32>,
+    /// Account layout of the wallet the seed comes from (metamask, mew, ledger-live, ledger-legacy)
+    #[arg(long, value_parser = parse_path_preset)]
+    path_preset: Option<PathPreset>,
+    /// SLIP-44 coin type to derive with (e.g. 61), or a registry chain that has one (e.g. gno)
+    #[arg(long)]
+    coin_type: Option<String>,
+    /// Also bind the saved keystore to this machine's TPM, so a copy is useless elsewhere
+    #[arg(long)]
+    bind_hardware: bool,
+    /// Split the saved keystore among --share holders so that any N of them can open it
+    #[arg(long, requires = "shares")]
+    threshold: Option<u8>,
+    /// Passphrase holder of a threshold keystore; repeat for each (e.g. --share personal --share spouse)
+    #[arg(long = "share", value_name = "LABEL", requires = "threshold")]
+    shares: Vec<String>,
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
 }
 
 /// Arguments for wallet loading
@@ -108,6 +388,45 @@ struct LoadArgs {
     /// Derive specific address index
     #[arg(short, long)]
     derive: Option<u32>,
+
+    /// Write the decrypted secrets to a temporary file (removed on exit)
+    #[arg(long, conflicts_with = "address_only")]
+    export: bool,
+
+    /// Read the password from the OS keychain, storing it there after the first prompt
+    #[arg(long, conflicts_with = "address_only")]
+    use_keychain: bool,
+    /// BIP44 account index (m/44'/60'/N'/0)
+    #[arg(long, conflicts_with = "address_only")]
+    account: Option<u32>,
+    /// SLIP-44 coin type to derive with, or a registry chain that has one
+    #[arg(long, conflicts_with = "address_only")]
+    coin_type: Option<String>,
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for keystore inspection
+#[derive(Args)]
+struct InfoArgs {
+    /// Wallet file path
+    filename: String,
+
+    /// Also decrypt the keystore to confirm the password is right
+    #[arg(long)]
+    check_password: bool,
+}
+
+/// BIP39 passphrase options
+#[derive(Args, Default)]
+struct PassphraseArgs {
+    /// BIP39 passphrase ("25th word") selecting a hidden wallet
+    #[arg(long, conflicts_with = "passphrase_prompt")]
+    passphrase: Option<String>,
+
+    /// Prompt for the BIP39 passphrase instead of passing it as an argument
+    #[arg(long)]
+    passphrase_prompt: bool,
 }
 
 /// Arguments for wallet listing
@@ -116,12 +435,44 @@ struct ListArgs {
     /// Custom wallet directory
     #[arg(short, long)]
     path: Option<std::path::PathBuf>,
+
+    /// Only list keystores with this tag
+    #[arg(short, long)]
+    tag: Option<String>,
+
+    /// Only list keystores for this network
+    #[arg(short, long)]
+    network: Option<String>,
+
+    /// Only list keystores whose alias contains this text
+    #[arg(long)]
+    alias_contains: Option<String>,
+
+    /// Only list keystores whose address contains this hex (e.g. a prefix)
+    #[arg(long)]
+    address: Option<String>,
+
+    /// Only list keystores created on or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = parse_time_bound)]
+    created_after: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only list keystores created before this date (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = parse_time_bound)]
+    created_before: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Sort order (created, alias, address); file name when omitted
+    #[arg(long, value_parser = parse_keystore_sort)]
+    sort: Option<KeystoreSort>,
+
+    /// Show at most this many keystores
+    #[arg(long)]
+    limit: Option<usize>,
 }
 
 /// Arguments for address derivation
 #[derive(Args)]
 struct DeriveArgs {
-    /// HD derivation path or index
+    /// Starting index, or a BIP32 base path such as m/44'/60'/1'/0
     path: String,
 
     /// Source wallet file
@@ -129,560 +480,7354 @@ struct DeriveArgs {
     from_file: Option<String>,
 
     /// Number of addresses to derive
-    #[arg(short, long, default_value = "1")]
+    #[arg(short = 'n', long, default_value = "1")]
     count: u32,
 
-    /// Starting index for derivation
+    /// Starting index below a base path
     #[arg(short, long, default_value = "0")]
     start_index: u32,
+
+    /// CSV of expected addresses (`index,address` or one address per line) to diff against
+    #[arg(long)]
+    expect_file: Option<PathBuf>,
+
+    /// BIP44 account index (m/44'/60'/N'/0) for index arguments
+    #[arg(long)]
+    account: Option<u32>,
+
+    /// Account layout to derive with (metamask, mew, ledger-live, ledger-legacy)
+    #[arg(long, value_parser = parse_path_preset)]
+    path_preset: Option<PathPreset>,
+    /// SLIP-44 coin type for index arguments, or a registry chain that has one
+    #[arg(long)]
+    coin_type: Option<String>,
+    /// Derive change addresses (.../1/i) instead of receive addresses (.../0/i)
+    #[arg(long)]
+    change: bool,
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
 }
 
-/// Validate mnemonic word count
-fn validate_word_count(s: &str) -> Result<u8, String> {
-    match s.parse::<u8>() {
-        Ok(12) | Ok(24) => Ok(s.parse().unwrap()),
-        Ok(n) => Err(format!("Word count must be 12 or 24, got {}", n)),
-        Err(_) => Err(format!("Invalid number: {}", s)),
-    }
+/// Arguments for chain registry commands
+#[derive(Args)]
+struct NetworkArgs {
+    #[command(subcommand)]
+    command: NetworkCommands,
 }
 
-/// Initialize logging based on verbosity level
-fn init_logging(verbose: bool) {
-    let level = if verbose {
-        tracing::Level::DEBUG
-    } else {
-        tracing::Level::INFO
-    };
+/// Chain registry subcommands
+#[derive(Subcommand)]
+enum NetworkCommands {
+    /// List chains in the registry
+    List,
+    /// Show details for a chain (network name, short name, or chain ID)
+    Show {
+        /// Chain to look up
+        chain: String,
+    },
+    /// Refresh the registry from chainlist (the only command that goes online)
+    UpdateRegistry {
+        /// Registry source URL
+        #[arg(long, default_value = web3wallet_cli::config::registry::DEFAULT_REGISTRY_URL)]
+        url: String,
+    },
+}
 
-    tracing_subscriber::fmt()
-        .with_max_level(level)
-        .with_target(false)
-        .without_time()
-        .init();
+/// Arguments for storage health checks
+#[derive(Args)]
+struct DoctorArgs {
+    /// Custom wallet directory
+    #[arg(short, long)]
+    path: Option<std::path::PathBuf>,
+
+    /// Restrict insecure files and directories to the current user
+    #[arg(long)]
+    fix: bool,
 }
 
-#[tokio::main]
-async fn main() -> WalletResult<()> {
-    let cli = Cli::parse();
+/// Arguments for wallet directory backup
+#[derive(Args)]
+struct BackupArgs {
+    /// Archive file to write (e.g. backup.w3w)
+    #[arg(long)]
+    out: PathBuf,
 
-    // Initialize logging
-    init_logging(cli.verbose);
+    /// Custom wallet directory
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+}
 
-    // Load configuration
-    let config = load_config(cli.config).await?;
+/// Arguments for backup restore
+#[derive(Args)]
+struct RestoreArgs {
+    /// Archive file to restore
+    archive: PathBuf,
 
-    if cli.verbose {
-        info!("Starting Web3 Wallet CLI v{}", env!("CARGO_PKG_VERSION"));
-    }
+    /// Target wallet directory (must be empty or missing)
+    #[arg(short, long)]
+    path: Option<PathBuf>,
+}
 
-    // Execute command
-    let result = match cli.command {
-        Commands::Create(args) => {
-            info!("Creating new wallet...");
-            execute_create(args, &config, cli.output).await
-        }
-        Commands::Import(args) => {
-            info!("Importing wallet...");
-            execute_import(args, &config, cli.output).await
-        }
-        Commands::Load(args) => {
-            info!("Loading wallet...");
-            execute_load(args, &config, cli.output).await
-        }
-        Commands::List(args) => {
-            info!("Listing wallets...");
-            execute_list(args, &config, cli.output).await
-        }
-        Commands::Derive(args) => {
-            info!("Deriving addresses...");
-            execute_derive(args, &config, cli.output).await
-        }
-    };
+/// Arguments for the mnemonic backup quiz
+#[derive(Args)]
+struct VerifyBackupArgs {
+    /// Wallet file path
+    filename: String,
 
-    if let Err(ref err) = result {
-        error!("Command failed: {}", err);
-        std::process::exit(1);
-    }
+    /// Number of words to ask for
+    #[arg(short = 'n', long, default_value_t = web3wallet_cli::config::backup::QUIZ_WORDS)]
+    words: usize,
+}
 
-    result
+/// Arguments for checksum manifest verification
+#[derive(Args)]
+struct VerifyManifestArgs {
+    /// Directory holding MANIFEST.json, or the manifest itself (defaults to the wallet directory)
+    path: Option<PathBuf>,
 }
 
-/// Load configuration from file or use defaults
-async fn load_config(config_path: Option<std::path::PathBuf>) -> WalletResult<WalletConfig> {
-    match config_path {
-        Some(path) => {
-            // TODO: Implement config file loading
-            info!("Loading config from: {}", path.display());
-            Ok(WalletConfig::default())
-        }
-        None => Ok(WalletConfig::default()),
-    }
+/// Arguments for signing usage commands
+#[derive(Args)]
+struct UsageArgs {
+    #[command(subcommand)]
+    command: UsageCommands,
 }
 
-/// Execute wallet creation command
-async fn execute_create(
-    args: CreateArgs,
-    config: &WalletConfig,
-    output: OutputFormat,
-) -> WalletResult<()> {
-    let manager = WalletManager::new(config.clone());
+/// Signing usage subcommands
+#[derive(Subcommand)]
+enum UsageCommands {
+    /// Show signing counts and quotas
+    Show,
+    /// Clear today's count and unlock a keystore that hit its hard quota
+    Reset {
+        /// Keystore file name
+        keystore: String,
+    },
+}
 
-    info!("Generating new {}-word mnemonic wallet...", args.words);
-    let wallet = manager.create_wallet(args.words).await?;
+/// Arguments for audit log commands
+#[derive(Args)]
+struct AuditArgs {
+    #[command(subcommand)]
+    command: AuditCommands,
+}
 
-    // Display wallet information
-    match output {
-        OutputFormat::Table => {
-            println!("\n🎉 Wallet created successfully!");
-            println!("Address:  {}", wallet.address());
-            println!("Network:  {}", wallet.network());
-            println!("Mnemonic: {}", wallet.mnemonic());
-            println!("\n⚠️  IMPORTANT: Store your mnemonic phrase safely!");
-            println!("   Anyone with access to this phrase can access your wallet.");
-        }
-        OutputFormat::Json => {
-            let output = serde_json::json!({
-                "success": true,
-                "address": wallet.address(),
-                "network": wallet.network(),
-                "mnemonic": wallet.mnemonic(),
-                "derivation_path": wallet.derivation_path(),
-                "created_at": wallet.created_at()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        }
-    }
+/// Audit log subcommands
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// List recorded operations, oldest first
+    Show {
+        /// Only the most recent N entries
+        #[arg(short = 'n', long)]
+        last: Option<usize>,
 
-    // Save wallet if requested
-    if let Some(filename) = args.save {
-        let password = prompt_password("Enter password to encrypt wallet: ")?;
-        let confirm = prompt_password("Confirm password: ")?;
+        /// Only one kind of operation (create, import, export, sign, delete)
+        #[arg(long, value_parser = parse_audit_action)]
+        action: Option<AuditAction>,
+    },
+    /// Check the hash chain for edited, removed or reordered entries
+    Verify,
+}
 
-        if password != confirm {
-            return Err(WalletError::UserInput(
-                UserInputError::PasswordMismatch
-            ));
-        }
+/// Arguments for keychain commands
+#[derive(Args)]
+struct KeychainArgs {
+    #[command(subcommand)]
+    command: KeychainCommands,
+}
 
-        let wallet_dir = &config.wallet_dir;
-        tokio::fs::create_dir_all(wallet_dir).await.map_err(|e| {
-            WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
-                path: wallet_dir.display().to_string(),
-                details: e.to_string(),
-            })
-        })?;
+/// Keychain subcommands
+#[derive(Subcommand)]
+enum KeychainCommands {
+    /// Remove the stored password for a keystore
+    Forget {
+        /// Wallet file path
+        filename: String,
+    },
+}
 
-        let file_path = wallet_dir.join(format!("{}.json", filename));
-        manager.save_wallet(&wallet, &file_path, &password).await?;
+/// Arguments for the session agent
+#[derive(Args)]
+struct AgentArgs {
+    #[command(subcommand)]
+    command: AgentCommands,
+}
 
-        println!("\n💾 Wallet saved to: {}", file_path.display());
-    }
+/// Session agent subcommands
+#[derive(Subcommand)]
+enum AgentCommands {
+    /// Start the agent in the background
+    Start {
+        /// Seconds an added wallet stays unlocked (default: `[agent] ttl_secs`)
+        #[arg(long)]
+        ttl: Option<u64>,
+        /// Run in this process instead of the background
+        #[arg(long)]
+        foreground: bool,
+    },
+    /// Unlock a keystore and hand it to the agent
+    Add {
+        /// Wallet file path
+        filename: String,
+        /// Seconds to keep this wallet, instead of the agent's default
+        #[arg(long)]
+        ttl: Option<u64>,
+    },
+    /// Show the wallets the agent holds
+    List,
+    /// Make the agent forget every wallet
+    Lock,
+    /// Stop the agent
+    Stop,
+}
 
-    Ok(())
+/// Arguments for TOTP commands
+#[derive(Args)]
+struct TotpArgs {
+    #[command(subcommand)]
+    command: TotpCommands,
 }
 
-/// Execute wallet import command
-async fn execute_import(
-    args: ImportArgs,
-    config: &WalletConfig,
-    output: OutputFormat,
-) -> WalletResult<()> {
-    let manager = WalletManager::new(config.clone());
+/// TOTP subcommands
+#[derive(Subcommand)]
+enum TotpCommands {
+    /// Enroll a keystore with an authenticator app
+    Enroll {
+        /// Wallet file path
+        filename: String,
+    },
+    /// Remove a keystore's enrollment (needs a current code)
+    Remove {
+        /// Wallet file path
+        filename: String,
+    },
+    /// List enrolled keystores and the signing threshold
+    Status,
+}
 
-    let wallet = if let Some(mnemonic) = args.mnemonic {
-        info!("Importing wallet from mnemonic...");
-        manager.import_from_mnemonic(&mnemonic).await?
-    } else if let Some(private_key) = args.private_key {
-        info!("Importing wallet from private key...");
-        manager.import_from_private_key(&private_key).await?
-    } else {
-        // Prompt for mnemonic if no input provided
-        let mnemonic = prompt_password("Enter mnemonic phrase: ")?;
-        manager.import_from_mnemonic(&mnemonic).await?
-    };
+/// Arguments for YubiKey commands
+#[derive(Args)]
+struct YubikeyArgs {
+    #[command(subcommand)]
+    command: YubikeyCommands,
+}
 
-    // Display wallet information
-    match output {
-        OutputFormat::Table => {
-            println!("\n✅ Wallet imported successfully!");
-            println!("Address:  {}", wallet.address());
-            println!("Network:  {}", wallet.network());
-            if wallet.has_mnemonic() {
-                println!("Type:     HD Wallet (BIP44)");
-            } else {
-                println!("Type:     Private Key Only");
-            }
-        }
-        OutputFormat::Json => {
-            let output = serde_json::json!({
-                "success": true,
-                "address": wallet.address(),
-                "network": wallet.network(),
-                "has_mnemonic": wallet.has_mnemonic(),
-                "derivation_path": wallet.derivation_path(),
-                "created_at": wallet.created_at()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        }
-    }
+/// YubiKey subcommands
+#[derive(Subcommand)]
+enum YubikeyCommands {
+    /// Re-encrypt a keystore so it also needs the YubiKey's HMAC-SHA1 response
+    Enroll {
+        /// Wallet file path
+        filename: String,
+        /// OTP slot programmed for challenge-response (1 or 2)
+        #[arg(long, default_value_t = yubikey::DEFAULT_SLOT, value_parser = clap::value_parser!(u8).range(1..=2))]
+        slot: u8,
+    },
+    /// Re-encrypt a keystore with the password alone (needs the YubiKey once more)
+    Remove {
+        /// Wallet file path
+        filename: String,
+    },
+}
 
-    // Save wallet if requested
-    if let Some(filename) = args.save {
-        let password = prompt_password("Enter password to encrypt wallet: ")?;
-        let confirm = prompt_password("Confirm password: ")?;
+/// Arguments for config commands
+#[derive(Args)]
+struct ConfigArgs {
+    #[command(subcommand)]
+    command: ConfigCommands,
+}
 
-        if password != confirm {
-            return Err(WalletError::UserInput(
-                UserInputError::PasswordMismatch
-            ));
-        }
+/// Arguments for the Argon2id benchmark
+#[derive(Args)]
+struct KdfBenchArgs {
+    /// Time one key derivation may take, in milliseconds
+    #[arg(long, default_value = "500")]
+    target_ms: u64,
 
-        let wallet_dir = &config.wallet_dir;
-        tokio::fs::create_dir_all(wallet_dir).await.map_err(|e| {
-            WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
-                path: wallet_dir.display().to_string(),
-                details: e.to_string(),
-            })
-        })?;
+    /// Largest Argon2id memory to try, in MiB (default: half the RAM)
+    #[arg(long)]
+    max_memory_mib: Option<u32>,
 
-        let file_path = wallet_dir.join(format!("{}.json", filename));
-        manager.save_wallet(&wallet, &file_path, &password).await?;
+    /// Print the recommendation without writing the config file
+    #[arg(long)]
+    dry_run: bool,
+}
 
-        println!("\n💾 Wallet saved to: {}", file_path.display());
-    }
+/// Arguments for the breached password filter
+#[derive(Args)]
+struct BreachFilterArgs {
+    #[command(subcommand)]
+    command: BreachFilterCommands,
+}
 
-    Ok(())
+/// Breached password filter subcommands
+#[derive(Subcommand)]
+enum BreachFilterCommands {
+    /// Build a filter from a list with one password per line and install it
+    Build {
+        /// Password list (e.g. the most common passwords of a breach corpus)
+        list: PathBuf,
+        /// Where to write the filter (default: the wallet directory)
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Share of unlisted passwords the filter may wrongly report
+        #[arg(long, default_value_t = web3wallet_cli::config::breach::DEFAULT_FALSE_POSITIVE_RATE)]
+        false_positive_rate: f64,
+    },
+    /// Check whether a password appears in the installed lists
+    Check,
 }
 
-/// Execute wallet load command
-async fn execute_load(
-    args: LoadArgs,
-    config: &WalletConfig,
+/// Config subcommands
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Check the effective configuration for dangerous settings
+    Validate,
+}
+
+/// Arguments for account commands
+#[derive(Args)]
+struct AccountsArgs {
+    #[command(subcommand)]
+    command: AccountsCommands,
+}
+
+/// Account subcommands
+#[derive(Subcommand)]
+enum AccountsCommands {
+    /// List accounts stored in a keystore
+    List {
+        /// Wallet file path
+        filename: String,
+    },
+    /// Derive an account and store it in the keystore
+    Add {
+        /// Wallet file path
+        filename: String,
+
+        /// Derivation index (defaults to the next unused index)
+        #[arg(short, long)]
+        index: Option<u32>,
+
+        /// Account label
+        #[arg(short, long)]
+        label: Option<String>,
+    },
+    /// Remove a stored account from the keystore
+    Remove {
+        /// Wallet file path
+        filename: String,
+
+        /// Derivation index to remove
+        index: u32,
+    },
+}
+
+/// Arguments for tag commands
+#[derive(Args)]
+struct TagArgs {
+    #[command(subcommand)]
+    command: TagCommands,
+}
+
+/// Tag subcommands
+#[derive(Subcommand)]
+enum TagCommands {
+    /// Add tags to a keystore
+    Add {
+        /// Wallet file path
+        filename: String,
+
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from a keystore
+    Remove {
+        /// Wallet file path
+        filename: String,
+
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Set or clear the keystore notes
+    Note {
+        /// Wallet file path
+        filename: String,
+
+        /// Notes text (omit to clear)
+        text: Option<String>,
+    },
+}
+
+/// Arguments for address book commands
+#[derive(Args)]
+struct BookArgs {
+    #[command(subcommand)]
+    command: BookCommands,
+}
+
+/// Address book subcommands
+#[derive(Subcommand)]
+enum BookCommands {
+    /// Add an address to the book
+    Add {
+        /// Address to add
+        address: String,
+
+        /// Name to refer to the address by
+        #[arg(short, long)]
+        label: Option<String>,
+
+        /// Network the address is used on
+        #[arg(short, long, default_value = "mainnet")]
+        network: Network,
+    },
+    /// Remove an address, given as hex or by its label
+    Remove {
+        /// Address or label
+        address: String,
+    },
+    /// List the addresses in the book
+    List {
+        /// Only list addresses on this network
+        #[arg(short, long)]
+        network: Option<Network>,
+    },
+    /// Find addresses whose label or hex contains the text
+    Find {
+        /// Text to look for
+        query: String,
+    },
+}
+
+/// Arguments for validator keystore commands
+#[derive(Args)]
+struct ValidatorArgs {
+    #[command(subcommand)]
+    command: ValidatorCommands,
+}
+
+/// Validator keystore subcommands
+#[derive(Subcommand)]
+enum ValidatorCommands {
+    /// Show public metadata of an EIP-2335 keystore
+    Inspect {
+        /// Keystore file path
+        file: PathBuf,
+    },
+    /// Check the password of an EIP-2335 keystore
+    Verify {
+        /// Keystore file path
+        file: PathBuf,
+    },
+    /// Write a copy of an EIP-2335 keystore under a new password
+    Reencrypt {
+        /// Keystore file path
+        file: PathBuf,
+
+        /// Output keystore path
+        #[arg(long)]
+        out: PathBuf,
+
+        /// KDF for the new keystore (scrypt or pbkdf2)
+        #[arg(long, default_value = "scrypt")]
+        kdf: ValidatorKdf,
+    },
+    /// Derive validator keys from a wallet's mnemonic (EIP-2333), with launchpad deposit data
+    Derive {
+        /// Wallet file path
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Number of validators
+        #[arg(long, default_value = "1")]
+        count: u32,
+
+        /// Index of the first validator
+        #[arg(long, default_value = "0")]
+        start_index: u32,
+
+        /// Launchpad network (mainnet, sepolia, holesky, hoodi)
+        #[arg(long, default_value = "mainnet")]
+        chain: String,
+
+        /// Execution address for withdrawals (0x01 credentials) instead of a BLS withdrawal key
+        #[arg(long)]
+        withdrawal_address: Option<String>,
+
+        /// Directory for the keystores and the deposit data file
+        #[arg(long)]
+        out: PathBuf,
+
+        /// KDF for the keystores (scrypt or pbkdf2)
+        #[arg(long, default_value = "scrypt")]
+        kdf: ValidatorKdf,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+}
+
+/// Arguments for wallet export
+#[derive(Args)]
+struct ExportArgs {
+    /// Wallet file path
+    filename: String,
+
+    /// Export format (v3, mew, xpub, addresses, mnemonic, private-key, paper)
+    #[arg(short, long)]
+    format: String,
+
+    /// Write the export to this file instead of stdout or the export workspace
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Account index for single-key formats
+    #[arg(long, default_value = "0")]
+    index: u32,
+
+    /// Number of addresses for the addresses format
+    #[arg(long, default_value = "10")]
+    count: u32,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for paper wallet generation
+#[derive(Args)]
+struct PaperArgs {
+    /// Wallet file path
+    filename: String,
+
+    /// PDF file to write
+    #[arg(long)]
+    out: PathBuf,
+
+    /// Account whose address and derivation path are printed
+    #[arg(long, default_value = "0")]
+    index: u32,
+
+    /// Also print the mnemonic in numbered word boxes
+    #[arg(long)]
+    include_mnemonic: bool,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for BIP85 child mnemonic derivation
+#[derive(Args)]
+struct Bip85Args {
+    /// Wallet file path of the master wallet
+    filename: String,
+
+    /// Child index
+    #[arg(short, long, default_value = "0")]
+    index: u32,
+
+    /// Number of words in the child mnemonic (12 or 24)
+    #[arg(short, long, value_parser = validate_word_count, default_value = "12")]
+    words: u8,
+
+    /// BIP39 wordlist of the child mnemonic
+    #[arg(short, long, value_parser = parse_language, default_value = "english")]
+    language: Language,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for Bitcoin address derivation
+#[cfg(feature = "btc")]
+#[derive(Args)]
+struct BtcArgs {
+    /// Wallet file path
+    filename: String,
+
+    /// Address layout (bip84 native segwit, bip44 legacy)
+    #[arg(long, value_parser = parse_btc_scheme, default_value = "bip84")]
+    scheme: BtcScheme,
+
+    /// Bitcoin account index
+    #[arg(long, default_value = "0")]
+    account: u32,
+
+    /// Number of addresses to derive
+    #[arg(short = 'n', long, default_value = "5")]
+    count: u32,
+
+    /// First address index
+    #[arg(short, long, default_value = "0")]
+    start_index: u32,
+
+    /// Derive change addresses instead of receive addresses
+    #[arg(long)]
+    change: bool,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for Solana address derivation
+#[cfg(feature = "solana")]
+#[derive(Args)]
+struct SolanaArgs {
+    /// Wallet file path
+    filename: String,
+
+    /// Number of accounts to derive
+    #[arg(short = 'n', long, default_value = "5")]
+    count: u32,
+
+    /// First account index
+    #[arg(short, long, default_value = "0")]
+    start_index: u32,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for vanity address search
+#[derive(Args)]
+struct VanityArgs {
+    /// Hex characters the address must start with (after 0x)
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Hex characters the address must end with
+    #[arg(long)]
+    suffix: Option<String>,
+
+    /// Match letter case against the EIP-55 checksummed address
+    #[arg(long)]
+    case_sensitive: bool,
+
+    /// Worker threads (defaults to the number of CPUs)
+    #[arg(short, long)]
+    workers: Option<usize>,
+
+    /// Walk the derivation indices of this wallet instead of generating random keys
+    #[arg(short, long)]
+    from_file: Option<String>,
+
+    /// First derivation index to try with --from-file
+    #[arg(short, long, default_value = "0")]
+    start_index: u32,
+
+    /// Save the found random key to this file
+    #[arg(long, conflicts_with = "from_file")]
+    save: Option<String>,
+
+    /// Keystore filename template for the found random key
+    #[arg(long, conflicts_with = "from_file")]
+    save_template: Option<String>,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for air-gapped signing
+#[derive(Args)]
+struct AirgapArgs {
+    #[command(subcommand)]
+    command: AirgapCommands,
+}
+
+/// Air-gapped signing subcommands
+#[derive(Subcommand)]
+enum AirgapCommands {
+    /// Online side: show an unsigned transaction or message as an eth-sign-request
+    Request {
+        /// Watch-only wallet of the signing account
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Receive address index
+        #[arg(short, long, default_value = "0")]
+        index: u32,
+
+        /// Full path of the signing key (needed when the watch-only wallet has no account origin)
+        #[arg(long)]
+        path: Option<DerivationPath>,
+
+        /// Unsigned transaction as hex RLP (typed transactions start with their type byte)
+        #[arg(long, required_unless_present_any = ["message", "typed_data"], conflicts_with_all = ["message", "typed_data"])]
+        tx: Option<String>,
+
+        /// Personal message to sign
+        #[arg(long, conflicts_with = "typed_data")]
+        message: Option<String>,
+
+        /// EIP-712 typed data JSON file to sign
+        #[arg(long)]
+        typed_data: Option<PathBuf>,
+
+        /// Chain id the transaction must be for (defaults to its own)
+        #[arg(long, requires = "tx")]
+        chain_id: Option<u64>,
+
+        /// Keep the request in this file for `airgap finalize`
+        #[arg(long)]
+        save: PathBuf,
+
+        #[command(flatten)]
+        display: UrDisplayArgs,
+    },
+    /// Offline side: sign an eth-sign-request and show the eth-signature
+    Sign {
+        /// Keystore file
+        #[arg(short, long)]
+        from_file: String,
+
+        /// UR parts of the request (read from standard input, one per line, when omitted)
+        parts: Vec<String>,
+
+        /// Sign without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+
+        #[command(flatten)]
+        display: UrDisplayArgs,
+    },
+    /// Online side: check an eth-signature and print the signed transaction or signature
+    Finalize {
+        /// Request file written by `airgap request --save`
+        #[arg(long)]
+        request: PathBuf,
+
+        /// UR parts of the signature (read from standard input, one per line, when omitted)
+        parts: Vec<String>,
+    },
+}
+
+/// How a UR is shown to the other device
+#[derive(Args)]
+struct UrDisplayArgs {
+    /// Print the UR parts as lines of text instead of an animated QR code
+    #[arg(long)]
+    text: bool,
+
+    /// Longest payload fragment per part, in bytes
+    #[arg(long, default_value_t = web3wallet_cli::services::ur::DEFAULT_MAX_FRAGMENT_LENGTH)]
+    fragment_len: usize,
+
+    /// Time each frame of an animated QR code is shown, in milliseconds
+    #[arg(long, default_value = "250")]
+    interval_ms: u64,
+}
+
+/// Arguments for missing-word mnemonic recovery
+#[derive(Args)]
+struct RecoverArgs {
+    /// Mnemonic with ? for each unknown word (prompted if omitted)
+    #[arg(short, long)]
+    mnemonic: Option<String>,
+
+    /// BIP39 wordlist of the mnemonic
+    #[arg(short, long, value_parser = parse_language, default_value = "english")]
+    language: Language,
+
+    /// Known address of the wallet (or its address book label); stops at the first match
+    #[arg(short, long)]
+    address: Option<String>,
+
+    /// Look up balance and nonce of each candidate on the configured network
+    #[arg(long, conflicts_with = "address")]
+    check_balance: bool,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for address ownership proofs
+#[derive(Args)]
+struct ProveOwnershipArgs {
+    /// Wallet file path
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Challenge (nonce) issued by the counterparty
+    #[arg(long)]
+    challenge: String,
+
+    /// Account index to prove
+    #[arg(short, long, default_value = "0")]
+    index: u32,
+
+    /// Write the proof JSON to this file
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for the external signer daemon
+#[derive(Args)]
+struct DaemonArgs {
+    /// Wallet file path
+    #[arg(short, long, required_unless_present = "pkcs11")]
+    from_file: Option<String>,
+
+    /// Serve the key on the `[pkcs11]` token instead of a keystore
+    #[arg(long, conflicts_with_all = ["from_file", "accounts"])]
+    pkcs11: bool,
+
+    /// Address to listen on; point clients at http://<address>
+    #[arg(long, default_value = signer_daemon::DEFAULT_LISTEN)]
+    listen: std::net::SocketAddr,
+
+    /// Number of accounts to serve, from index 0
+    #[arg(long, default_value = "1")]
+    accounts: u32,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for Safe multisig transactions
+#[derive(Args)]
+struct SafeArgs {
+    #[command(subcommand)]
+    command: SafeCommands,
+}
+
+/// Fields of a Safe transaction
+#[derive(Args)]
+struct SafeTxArgs {
+    /// Safe contract address or address book label
+    #[arg(long)]
+    safe: String,
+
+    /// Network the Safe is deployed on
+    #[arg(short, long, default_value = "mainnet")]
+    network: Network,
+
+    /// Target of the call (address or address book label)
+    #[arg(long)]
+    to: String,
+
+    /// Wei to send, in decimal
+    #[arg(long, value_parser = parse_wei, default_value = "0")]
+    value: ethers::types::U256,
+
+    /// Calldata as hex
+    #[arg(long, value_parser = parse_calldata, default_value = "0x")]
+    data: ethers::types::Bytes,
+
+    /// call or delegatecall
+    #[arg(long, default_value = "call")]
+    operation: SafeOperation,
+
+    /// Safe nonce the transaction is for
+    #[arg(long)]
+    nonce: u64,
+
+    /// Gas reserved for the inner call (0 for all remaining gas)
+    #[arg(long, default_value = "0")]
+    safe_tx_gas: u64,
+
+    /// Gas refunded on top of the inner call
+    #[arg(long, default_value = "0")]
+    base_gas: u64,
+
+    /// Gas price for the refund, in wei (0 for no refund)
+    #[arg(long, value_parser = parse_wei, default_value = "0")]
+    gas_price: ethers::types::U256,
+
+    /// Token the refund is paid in (default: ETH)
+    #[arg(long)]
+    gas_token: Option<String>,
+
+    /// Receiver of the refund (default: the submitter)
+    #[arg(long)]
+    refund_receiver: Option<String>,
+}
+
+/// Safe multisig subcommands
+#[derive(Subcommand)]
+enum SafeCommands {
+    /// Write an unsigned proposal file for a Safe transaction
+    Propose {
+        #[command(flatten)]
+        transaction: SafeTxArgs,
+
+        /// Proposal file to write
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Sign a proposal with an owner key and write the signed copy
+    Sign {
+        /// Keystore of the owner
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Account index of the owner key
+        #[arg(short, long, default_value = "0")]
+        index: u32,
+
+        /// Proposal file
+        proposal: PathBuf,
+
+        /// Signed copy to write (default: the proposal file)
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Sign without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Combine signed copies of one proposal
+    Merge {
+        /// Signed proposal files
+        #[arg(required = true)]
+        proposals: Vec<PathBuf>,
+
+        /// Merged proposal file to write
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Check a proposal and show its signatures and execTransaction calldata
+    Show {
+        /// Proposal file
+        proposal: PathBuf,
+    },
+}
+
+/// Arguments for partially signed transactions
+#[derive(Args)]
+struct PsbtEthArgs {
+    #[command(subcommand)]
+    command: PsbtEthCommands,
+}
+
+/// Partially signed transaction subcommands
+#[derive(Subcommand)]
+enum PsbtEthCommands {
+    /// Write an unsigned envelope for a Safe transaction and who may sign it
+    Create {
+        #[command(flatten)]
+        transaction: Box<SafeTxArgs>,
+
+        /// Signatures needed to finalize
+        #[arg(long)]
+        threshold: usize,
+
+        /// Address (or address book label) allowed to sign; repeat for each signer
+        #[arg(long = "signer", required = true)]
+        signers: Vec<String>,
+
+        /// Note shown to every signer
+        #[arg(long)]
+        description: Option<String>,
+
+        /// Envelope file to write
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Sign an envelope with one holder's key and write the signed copy
+    Sign {
+        /// Keystore of the signer
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Account index of the signing key
+        #[arg(short, long, default_value = "0")]
+        index: u32,
+
+        /// Envelope file
+        psbt: PathBuf,
+
+        /// Signed copy to write (default: the envelope file)
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Name to record next to your signature
+        #[arg(long)]
+        label: Option<String>,
+
+        /// Sign without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Combine signed copies of one envelope
+    Combine {
+        /// Signed envelope files
+        #[arg(required = true)]
+        psbts: Vec<PathBuf>,
+
+        /// Combined envelope file to write
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Check the threshold is met and show the call that executes the transaction
+    Finalize {
+        /// Envelope file
+        psbt: PathBuf,
+
+        /// Write the call (chainId, to, value, data) as JSON to this file
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Check an envelope and show who has signed
+    Show {
+        /// Envelope file
+        psbt: PathBuf,
+    },
+}
+
+/// Arguments for token approval review
+#[derive(Args)]
+struct ApprovalsArgs {
+    /// Owner address (or its address book label)
+    address: String,
+
+    /// First block to scan for approval events
+    #[arg(long, default_value = "0")]
+    from_block: u64,
+
+    /// Last block to scan for approval events (default: latest)
+    #[arg(long)]
+    to_block: Option<u64>,
+
+    /// Network to query (default: the configured network)
+    #[arg(short, long)]
+    network: Option<Network>,
+
+    /// Build revoke transactions for approvals to this spender or on this token (repeatable; `all` for every approval)
+    #[arg(long)]
+    revoke: Vec<String>,
+
+    /// Write the revoke transactions to this JSON file
+    #[arg(long, requires = "revoke")]
+    out: Option<PathBuf>,
+
+    /// Name spenders missing from the address book after their verified source on the configured explorer
+    #[arg(long)]
+    names: bool,
+}
+
+/// Arguments for NFT commands
+#[derive(Args)]
+struct NftArgs {
+    #[command(subcommand)]
+    command: NftCommands,
+}
+
+/// NFT subcommands
+#[derive(Subcommand)]
+enum NftCommands {
+    /// Transfer a token with `safeTransferFrom`
+    ///
+    /// Without --from-file the unsigned transaction is printed for
+    /// `wallet airgap request --tx`; with it, the transaction is signed
+    /// locally and printed raw. Nothing is broadcast.
+    Transfer {
+        /// Token contract (or its address book label)
+        #[arg(long)]
+        contract: String,
+
+        /// Token ID, decimal or 0x-prefixed hex
+        #[arg(long, value_parser = parse_uint)]
+        token_id: ethers::types::U256,
+
+        /// Recipient (or its address book label)
+        #[arg(long)]
+        to: String,
+
+        /// Number of tokens to send (ERC-1155 only)
+        #[arg(long, value_parser = parse_uint, default_value = "1")]
+        amount: ethers::types::U256,
+
+        /// Token standard: erc721 or erc1155 (default: asked from the contract via ERC-165)
+        #[arg(long, value_parser = parse_nft_standard)]
+        standard: Option<NftStandard>,
+
+        /// Current holder (or its address book label), when signing elsewhere
+        #[arg(long, required_unless_present = "from_file", conflicts_with = "from_file")]
+        from: Option<String>,
+
+        /// Keystore to sign the transfer with
+        #[arg(short, long)]
+        from_file: Option<String>,
+
+        /// Account index of the holder in the keystore
+        #[arg(short, long, default_value = "0", requires = "from_file")]
+        index: u32,
+
+        /// Data passed to the recipient's receive hook, as hex
+        #[arg(long, value_parser = parse_calldata)]
+        data: Option<ethers::types::Bytes>,
+
+        /// Network to build the transfer for (default: the configured network)
+        #[arg(short, long)]
+        network: Option<Network>,
+
+        /// Skip the ownership check and the simulation
+        #[arg(long)]
+        skip_checks: bool,
+
+        /// Sign without asking for confirmation
+        #[arg(long, requires = "from_file")]
+        yes: bool,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+}
+
+/// Arguments for contract calls
+#[derive(Args)]
+struct CallArgs {
+    /// Contract ABI JSON, or a compiler artifact with an `abi` field (default: the verified ABI of --to from the configured explorer)
+    #[arg(long, required_unless_present = "to")]
+    abi: Option<PathBuf>,
+
+    /// Function name, or its full signature when overloaded (e.g. `transfer(address,uint256)`)
+    #[arg(short, long)]
+    method: String,
+
+    /// Comma-separated arguments: `[1,2]` for arrays, `(a,b)` for tuples, `1.5 ether` for amounts
+    #[arg(long, default_value = "", allow_hyphen_values = true)]
+    args: String,
+
+    /// Contract to run the call against with eth_call (only encodes when omitted)
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Caller (or its address book label) the eth_call runs as
+    #[arg(long, requires = "to")]
+    from: Option<String>,
+
+    /// Network to query (default: the configured network)
+    #[arg(short, long, requires = "to")]
+    network: Option<Network>,
+}
+
+/// Arguments for `wallet history`
+#[derive(Args)]
+struct HistoryArgs {
+    /// Address (or its address book label)
+    address: String,
+
+    /// Transactions per page
+    #[arg(short, long, default_value_t = web3wallet_cli::config::explorer::DEFAULT_HISTORY_LIMIT)]
+    limit: usize,
+
+    /// Page to show, newest first
+    #[arg(long, default_value = "1")]
+    page: usize,
+
+    /// Network to query (default: the configured network)
+    #[arg(short, long)]
+    network: Option<Network>,
+
+    /// Name counterparties missing from the address book after their verified source
+    #[arg(long)]
+    names: bool,
+}
+
+/// Arguments for ownership proof verification
+#[derive(Args)]
+struct ProveVerifyArgs {
+    /// Proof JSON written by prove-ownership
+    #[arg(long, conflicts_with_all = ["message", "signature"])]
+    proof: Option<PathBuf>,
+
+    /// Signed statement text
+    #[arg(long, requires = "signature")]
+    message: Option<String>,
+
+    /// Hex signature over the statement
+    #[arg(long, requires = "message")]
+    signature: Option<String>,
+
+    /// Address the proof must be for (or its address book label)
+    #[arg(short, long)]
+    address: Option<String>,
+
+    /// Challenge you issued
+    #[arg(long)]
+    challenge: Option<String>,
+
+    /// Reject proofs older than this many seconds
+    #[arg(long)]
+    max_age: Option<u64>,
+
+    /// Also accept signatures of a smart-contract wallet at the address (ERC-1271), asked over RPC
+    #[arg(long)]
+    erc1271: bool,
+
+    /// Network of the contract wallet (default: the configured network)
+    #[arg(short, long, requires = "erc1271")]
+    network: Option<Network>,
+}
+
+/// Arguments for Sign-In with Ethereum
+#[derive(Args)]
+struct SiweArgs {
+    /// Wallet file path
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Domain (RFC 3986 authority) requesting the sign-in, e.g. app.example
+    #[arg(long)]
+    domain: String,
+
+    /// URI the sign-in is for, e.g. https://app.example/login
+    #[arg(long)]
+    uri: String,
+
+    /// Nonce issued by the server (default: a random one)
+    #[arg(long)]
+    nonce: Option<String>,
+
+    /// Statement shown to the user
+    #[arg(long)]
+    statement: Option<String>,
+
+    /// Network whose chain ID goes into the message (default: the configured network)
+    #[arg(short, long)]
+    network: Option<Network>,
+
+    /// Make the sign-in expire this many seconds after it is issued
+    #[arg(long)]
+    expires_in: Option<u32>,
+
+    /// Time before which the sign-in is not valid (RFC 3339)
+    #[arg(long)]
+    not_before: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Request ID to include
+    #[arg(long)]
+    request_id: Option<String>,
+
+    /// Resource URI to include (repeatable)
+    #[arg(long = "resource")]
+    resources: Vec<String>,
+
+    /// Account index to sign in with
+    #[arg(short, long, default_value = "0")]
+    index: u32,
+
+    /// Write the message and signature as JSON to this file
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for Sign-In with Ethereum verification
+#[derive(Args)]
+struct SiweVerifyArgs {
+    /// JSON written by `wallet siwe --out`
+    #[arg(long, conflicts_with_all = ["message", "signature"])]
+    signed: Option<PathBuf>,
+
+    /// File holding the signed message text
+    #[arg(long, requires = "signature")]
+    message: Option<PathBuf>,
+
+    /// Hex signature over the message
+    #[arg(long, requires = "message")]
+    signature: Option<String>,
+
+    /// Domain the sign-in must be for
+    #[arg(long)]
+    domain: Option<String>,
+
+    /// Nonce you issued
+    #[arg(long)]
+    nonce: Option<String>,
+
+    /// Chain ID the sign-in must be for
+    #[arg(long)]
+    chain_id: Option<u64>,
+}
+
+/// Arguments for showing an encryption public key
+#[derive(Args)]
+struct EncryptionKeyArgs {
+    /// Wallet file path
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Account index
+    #[arg(short, long, default_value = "0")]
+    index: u32,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for note encryption
+#[derive(Args)]
+struct EncryptMsgArgs {
+    /// Recipient's base64 encryption public key (from `wallet encryption-key`)
+    #[arg(long)]
+    to: String,
+
+    /// Note to encrypt
+    #[arg(short, long, conflicts_with = "input", required_unless_present = "input")]
+    message: Option<String>,
+
+    /// Read the note from this file instead
+    #[arg(long = "in")]
+    input: Option<PathBuf>,
+
+    /// Write the encrypted note JSON to this file
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Arguments for note decryption
+#[derive(Args)]
+struct DecryptMsgArgs {
+    /// Wallet file path
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Encrypted note JSON (as written by `wallet encrypt-msg` or eth-sig-util)
+    #[arg(long = "in")]
+    input: PathBuf,
+
+    /// Account index the note was encrypted to
+    #[arg(short, long, default_value = "0")]
+    index: u32,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for stealth addresses
+#[derive(Args)]
+struct StealthArgs {
+    #[command(subcommand)]
+    command: StealthCommands,
+}
+
+#[derive(Subcommand)]
+enum StealthCommands {
+    /// Show the stealth meta-address senders pay to, and its registry calldata
+    MetaAddress {
+        /// Wallet file path
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Account index of the stealth keys
+        #[arg(short, long, default_value = "0")]
+        index: u32,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Generate a one-time address for a recipient's stealth meta-address
+    Generate {
+        /// Recipient's meta-address (st:eth:0x...)
+        meta_address: String,
+
+        /// Write the stealth address and announcement as JSON to this file
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Find announcements that pay this wallet
+    Scan {
+        /// Wallet file path
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Account index of the stealth keys
+        #[arg(short, long, default_value = "0")]
+        index: u32,
+
+        /// JSON array of announcements to check instead of querying the chain
+        #[arg(long, conflicts_with_all = ["from_block", "to_block", "network"], required_unless_present = "from_block")]
+        announcements: Option<PathBuf>,
+
+        /// First block to fetch announcements from
+        #[arg(long)]
+        from_block: Option<u64>,
+
+        /// Last block to fetch announcements from (default: latest)
+        #[arg(long, requires = "from_block")]
+        to_block: Option<u64>,
+
+        /// Network to query (default: the configured network)
+        #[arg(short, long)]
+        network: Option<Network>,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Save the private key of a stealth address as a new keystore
+    Claim {
+        /// Wallet file path
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Account index of the stealth keys
+        #[arg(short, long, default_value = "0")]
+        index: u32,
+
+        /// Stealth address to claim
+        #[arg(long)]
+        address: String,
+
+        /// Ephemeral public key from its announcement
+        #[arg(long, value_parser = parse_calldata)]
+        ephemeral_key: ethers::types::Bytes,
+
+        /// Name or path of the keystore to write
+        #[arg(long)]
+        save: String,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+}
+
+/// Arguments for identity bundles
+#[derive(Args)]
+struct IdentityArgs {
+    #[command(subcommand)]
+    command: IdentityCommands,
+}
+
+/// Identity bundle subcommands
+#[derive(Subcommand)]
+enum IdentityCommands {
+    /// Write address, public key and an ownership proof as one JSON bundle
+    Export {
+        /// Wallet file path
+        #[arg(short, long)]
+        from_file: String,
+
+        /// Challenge issued by the verifier (random when omitted)
+        #[arg(long)]
+        challenge: Option<String>,
+
+        /// Account index
+        #[arg(short, long, default_value = "0")]
+        index: u32,
+
+        /// Write the bundle to this file
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        #[command(flatten)]
+        passphrase: PassphraseArgs,
+    },
+    /// Check a bundle written by `identity export`
+    Verify {
+        /// Bundle JSON file
+        bundle: PathBuf,
+
+        /// Challenge you issued
+        #[arg(long)]
+        challenge: Option<String>,
+
+        /// Reject bundles older than this many seconds
+        #[arg(long)]
+        max_age: Option<u64>,
+    },
+}
+
+/// Arguments for account xpub export
+#[derive(Args)]
+struct XpubArgs {
+    /// Wallet file path
+    filename: String,
+
+    /// BIP44 account index (defaults to the wallet's own account)
+    #[arg(long)]
+    account: Option<u32>,
+
+    /// SLIP-44 coin type (defaults to the wallet's own), or a registry chain that has one
+    #[arg(long)]
+    coin_type: Option<String>,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for watch-only wallet commands
+#[derive(Args)]
+struct WatchArgs {
+    #[command(subcommand)]
+    command: WatchCommands,
+}
+
+/// Watch-only wallet subcommands
+#[derive(Subcommand)]
+enum WatchCommands {
+    /// Save a watch-only wallet from an account xpub or a single address
+    Add {
+        /// Name of the watch-only file in the wallet directory
+        name: String,
+
+        /// Account extended public key (see `wallet xpub`)
+        #[arg(long, required_unless_present = "address", conflicts_with = "address")]
+        xpub: Option<String>,
+
+        /// Single address to watch (or its address book label)
+        #[arg(long)]
+        address: Option<String>,
+
+        /// Account layout of the xpub (metamask, mew, ledger-legacy)
+        #[arg(long, value_parser = parse_path_preset, default_value = "metamask", requires = "xpub")]
+        path_preset: PathPreset,
+
+        /// Path of the xpub's account node, e.g. m/44'/60'/0' (for display only)
+        #[arg(long, requires = "xpub")]
+        origin: Option<DerivationPath>,
+
+        /// Target network
+        #[arg(short, long, default_value = "mainnet")]
+        network: Network,
+
+        /// Wallet alias
+        #[arg(long)]
+        alias: Option<String>,
+    },
+    /// Show receive addresses of a watch-only wallet
+    Derive {
+        /// Watch-only file path
+        filename: String,
+
+        /// First address index
+        #[arg(short, long, default_value = "0")]
+        start_index: u32,
+
+        /// Number of addresses
+        #[arg(short = 'n', long, default_value = "5")]
+        count: u32,
+    },
+    /// Show balances of the first receive addresses
+    Balance {
+        /// Watch-only file path
+        filename: String,
+
+        /// Number of addresses to query
+        #[arg(short = 'n', long, default_value = "1")]
+        count: u32,
+    },
+}
+
+/// Arguments for gap-limit address scanning
+#[derive(Args)]
+struct ScanArgs {
+    /// Wallet file (keystore or watch-only)
+    #[arg(short, long)]
+    from_file: String,
+
+    /// Stop after this many consecutive unused addresses
+    #[arg(long, default_value_t = web3wallet_cli::services::gap_scan::DEFAULT_GAP_LIMIT)]
+    gap: u32,
+
+    /// First index to check
+    #[arg(short, long, default_value = "0")]
+    start_index: u32,
+
+    /// BIP44 account index to scan (keystores only)
+    #[arg(long)]
+    account: Option<u32>,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
+}
+
+/// Arguments for keystore compaction
+#[derive(Args)]
+struct CompactArgs {
+    /// Wallet file path
+    filename: String,
+}
+
+/// Arguments for secure deletion
+#[derive(Args)]
+struct ShredArgs {
+    /// Files to overwrite and remove
+    #[arg(required = true)]
+    files: Vec<String>,
+
+    /// Overwrite passes; the last one writes zeros
+    #[arg(long, default_value_t = web3wallet_cli::config::fs::SHRED_PASSES, value_parser = clap::value_parser!(u32).range(1..=35))]
+    passes: u32,
+
+    /// Delete without asking for confirmation
+    #[arg(long)]
+    yes: bool,
+}
+
+/// Validate mnemonic word count
+fn validate_word_count(s: &str) -> Result<u8, String> {
+    match s.parse::<u8>() {
+        Ok(12) | Ok(24) => Ok(s.parse().unwrap()),
+        Ok(n) => Err(format!("Word count must be 12 or 24, got {}", n)),
+        Err(_) => Err(format!("Invalid number: {}", s)),
+    }
+}
+
+/// Parse a BIP39 wordlist name
+fn parse_language(s: &str) -> Result<Language, String> {
+    MnemonicService::parse_language(s).map_err(|_| format!("Unknown BIP39 wordlist: {}", s))
+}
+
+/// Parse a derivation path preset name
+fn parse_path_preset(s: &str) -> Result<PathPreset, String> {
+    s.parse().map_err(|_| {
+        let names: Vec<_> = PathPreset::ALL.iter().map(PathPreset::name).collect();
+        format!("Unknown path preset: {} (expected one of {})", s, names.join(", "))
+    })
+}
+
+/// Parse a KDF profile name
+fn parse_kdf_profile(s: &str) -> Result<KdfProfile, String> {
+    s.parse().map_err(|_| {
+        let names: Vec<_> = KdfProfile::ALL.iter().map(|profile| profile.name()).collect();
+        format!("Unknown KDF profile: {} (expected one of {})", s, names.join(", "))
+    })
+}
+
+/// Parse a `--sort` order for keystore listings
+fn parse_keystore_sort(s: &str) -> Result<KeystoreSort, String> {
+    s.parse().map_err(|_| {
+        let names: Vec<_> = KeystoreSort::ALL.iter().map(|sort| sort.name()).collect();
+        format!("Unknown sort order: {} (expected one of {})", s, names.join(", "))
+    })
+}
+
+/// Parse a date (midnight UTC) or RFC 3339 timestamp
+fn parse_time_bound(s: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|time| time.with_timezone(&chrono::Utc))
+        .map_err(|_| format!("Invalid date: {} (expected YYYY-MM-DD or an RFC 3339 timestamp)", s))
+}
+
+/// Parse an audit action name
+fn parse_audit_action(s: &str) -> Result<AuditAction, String> {
+    s.parse().map_err(|_| {
+        let names: Vec<_> = AuditAction::ALL.iter().map(|action| action.name()).collect();
+        format!("Unknown audit action: {} (expected one of {})", s, names.join(", "))
+    })
+}
+
+/// Parse a decimal amount of wei
+fn parse_wei(s: &str) -> Result<ethers::types::U256, String> {
+    ethers::types::U256::from_dec_str(s).map_err(|_| format!("Invalid amount: {} (expected decimal wei)", s))
+}
+
+/// Parse a token ID or amount, decimal or `0x`-prefixed hex
+fn parse_uint(s: &str) -> Result<ethers::types::U256, String> {
+    let parsed = match s.strip_prefix("0x") {
+        Some(digits) => ethers::types::U256::from_str_radix(digits, 16).ok(),
+        None => ethers::types::U256::from_dec_str(s).ok(),
+    };
+    parsed.ok_or_else(|| format!("Invalid number: {} (expected decimal or 0x-prefixed hex)", s))
+}
+
+/// Parse an NFT standard name
+fn parse_nft_standard(s: &str) -> Result<NftStandard, String> {
+    s.parse().map_err(|_| {
+        let names: Vec<_> = NftStandard::ALL.iter().map(|standard| standard.name()).collect();
+        format!("Unknown token standard: {} (expected one of {})", s, names.join(", "))
+    })
+}
+
+/// Parse hex calldata, with or without `0x`
+fn parse_calldata(s: &str) -> Result<ethers::types::Bytes, String> {
+    hex::decode(s.trim().trim_start_matches("0x"))
+        .map(Into::into)
+        .map_err(|e| format!("Invalid calldata: {}", e))
+}
+
+#[cfg(feature = "btc")]
+fn parse_btc_scheme(s: &str) -> Result<BtcScheme, String> {
+    s.parse().map_err(|_| {
+        let names: Vec<_> = BtcScheme::ALL.iter().map(BtcScheme::name).collect();
+        format!("Unknown Bitcoin scheme: {} (expected one of {})", s, names.join(", "))
+    })
+}
+
+/// Initialize logging based on verbosity level; logs go to stderr so stdout stays parseable
+fn init_logging(verbose: bool, trace: Option<&str>) {
+    if let Some(targets) = trace {
+        tracing_subscriber::fmt()
+            .with_env_filter(trace_filter(targets))
+            .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+            .with_target(true)
+            .with_timer(tracing_subscriber::fmt::time::uptime())
+            .with_writer(std::io::stderr)
+            .init();
+        return;
+    }
+
+    let level = if verbose {
+        tracing::Level::DEBUG
+    } else {
+        tracing::Level::INFO
+    };
+
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .without_time()
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Build the `--trace` filter: TRACE for the requested targets, INFO elsewhere
+fn trace_filter(targets: &str) -> tracing_subscriber::EnvFilter {
+    let mut directives = vec!["info".to_string()];
+
+    if targets.trim().is_empty() {
+        directives.push("web3wallet_cli=trace".to_string());
+        directives.push("wallet=trace".to_string());
+    }
+
+    for target in targets.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let (target, level) = target.split_once('=').unwrap_or((target, "trace"));
+        let target = if target.starts_with("web3wallet_cli") || target == "wallet" {
+            target.to_string()
+        } else {
+            format!("web3wallet_cli::{}", target)
+        };
+        directives.push(format!("{}={}", target, level));
+    }
+
+    tracing_subscriber::EnvFilter::new(directives.join(","))
+}
+
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    let cli = Cli::parse();
+
+    // Initialize logging
+    init_logging(cli.verbose, cli.trace.as_deref());
+
+    // Load configuration
+    let mut config = match load_config(cli.config.clone(), cli.profile).await {
+        Ok(config) => config,
+        Err(err) => return report_error(err, cli.output),
+    };
+    if let Some(kdf_profile) = cli.kdf_profile {
+        config.kdf_profile = kdf_profile;
+    }
+    config.reset_lockout = cli.reset_lockout;
+    config.allow_breached = cli.allow_weak;
+    config.password_source = PasswordSource::select(cli.password_file, cli.password_stdin);
+
+    if cli.low_resource {
+        config.low_resource = Some(true);
+    } else if config.low_resource.is_none() {
+        let detected = web3wallet_cli::config::resources::detect_low_resource();
+        if detected {
+            info!("Less than 1 GiB of memory detected; running in low-resource mode");
+        }
+        config.low_resource = Some(detected);
+    }
+
+    if config.security.hardened_memory {
+        web3wallet_cli::security::memory::harden_process();
+    }
+
+    if cli.verbose {
+        info!("Starting Web3 Wallet CLI v{}", env!("CARGO_PKG_VERSION"));
+    }
+
+    password::set_non_interactive(cli.non_interactive);
+
+    // Remove decrypted exports even when interrupted
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            workspace::cleanup_registered();
+            // The command may be blocked on a terminal prompt, so it cannot be asked to return
+            #[allow(clippy::disallowed_methods)]
+            std::process::exit(130);
+        }
+    });
+
+    // Refuse commands needing features the config disables
+    if let Err(err) = lockdown::ensure_enabled(&config.disable, &required_features(&cli.command)) {
+        return report_error(err, cli.output);
+    }
+
+    // Execute command
+    let result = match cli.command {
+        Commands::Create(args) => {
+            info!("Creating new wallet...");
+            execute_create(args, &config, cli.output).await
+        }
+        Commands::CreateBatch(args) => {
+            info!("Creating {} wallets...", args.count);
+            execute_create_batch(args, &config, cli.output).await
+        }
+        Commands::Import(args) => {
+            info!("Importing wallet...");
+            execute_import(args, &config, cli.output).await
+        }
+        Commands::Load(args) => {
+            info!("Loading wallet...");
+            execute_load(args, &config, cli.output).await
+        }
+        Commands::Info(args) => execute_info(args, &config, cli.output).await,
+        Commands::List(args) => {
+            info!("Listing wallets...");
+            execute_list(args, &config, cli.output).await
+        }
+        Commands::Derive(args) => {
+            info!("Deriving addresses...");
+            execute_derive(args, &config, cli.output).await
+        }
+        Commands::Network(args) => execute_network(args, &config, cli.output).await,
+        Commands::Doctor(args) => {
+            info!("Checking wallet storage...");
+            execute_doctor(args, &config, cli.output).await
+        }
+        Commands::Backup(args) => {
+            info!("Backing up wallet directory...");
+            execute_backup(args, &config, cli.output).await
+        }
+        Commands::Restore(args) => {
+            info!("Restoring wallet directory...");
+            execute_restore(args, &config, cli.output).await
+        }
+        Commands::VerifyBackup(args) => execute_verify_backup(args, &config, cli.output).await,
+        Commands::VerifyManifest(args) => execute_verify_manifest(args, &config, cli.output),
+        Commands::Usage(args) => execute_usage(args, &config, cli.output).await,
+        Commands::Audit(args) => execute_audit(args, &config, cli.output).await,
+        Commands::Keychain(args) => execute_keychain(args, &config, cli.output).await,
+        Commands::Agent(args) => execute_agent(args, &config, cli.output).await,
+        Commands::Totp(args) => execute_totp(args, &config, cli.output).await,
+        Commands::Yubikey(args) => execute_yubikey(args, &config, cli.output).await,
+        Commands::Config(args) => execute_config(args, &config, cli.output).await,
+        Commands::KdfBench(args) => execute_kdf_bench(args, &config, cli.config.as_deref(), cli.output).await,
+        Commands::BreachFilter(args) => execute_breach_filter(args, &config, cli.output).await,
+        Commands::Accounts(args) => execute_accounts(args, &config, cli.output).await,
+        Commands::Tag(args) => execute_tag(args, &config, cli.output).await,
+        Commands::Book(args) => execute_book(args, &config, cli.output),
+        Commands::Validator(args) => execute_validator(args, &config, cli.output).await,
+        Commands::Export(args) => execute_export(args, &config, cli.output).await,
+        Commands::Paper(args) => execute_paper(args, &config, cli.output).await,
+        Commands::Bip85(args) => execute_bip85(args, &config, cli.output).await,
+        Commands::Recover(args) => execute_recover(args, &config, cli.output).await,
+        Commands::ProveOwnership(args) => execute_prove_ownership(args, &config, cli.output).await,
+        Commands::Daemon(args) => execute_daemon(args, &config).await,
+        Commands::Safe(args) => execute_safe(args, &config, cli.output).await,
+        Commands::PsbtEth(args) => execute_psbt_eth(args, &config, cli.output).await,
+        Commands::ProveVerify(args) => execute_prove_verify(args, &config, cli.output).await,
+        Commands::Siwe(args) => execute_siwe(args, &config, cli.output).await,
+        Commands::SiweVerify(args) => execute_siwe_verify(args, &config, cli.output),
+        Commands::EncryptionKey(args) => execute_encryption_key(args, &config, cli.output).await,
+        Commands::EncryptMsg(args) => execute_encrypt_msg(args, &config, cli.output),
+        Commands::DecryptMsg(args) => execute_decrypt_msg(args, &config, cli.output).await,
+        Commands::Stealth(args) => execute_stealth(args, &config, cli.output).await,
+        Commands::Approvals(args) => execute_approvals(args, &config, cli.output).await,
+        Commands::Nft(args) => execute_nft(args, &config, cli.output).await,
+        Commands::Call(args) => execute_call(args, &config, cli.output).await,
+        Commands::History(args) => execute_history(args, &config, cli.output).await,
+        Commands::Identity(args) => execute_identity(args, &config, cli.output).await,
+        Commands::Xpub(args) => execute_xpub(args, &config, cli.output).await,
+        Commands::Watch(args) => execute_watch(args, &config, cli.output).await,
+        Commands::Scan(args) => execute_scan(args, &config, cli.output).await,
+        Commands::Compact(args) => execute_compact(args, &config, cli.output).await,
+        Commands::Shred(args) => execute_shred(args, &config, cli.output).await,
+        Commands::Vanity(args) => execute_vanity(args, &config, cli.output).await,
+        Commands::Airgap(args) => execute_airgap(args, &config, cli.output).await,
+        #[cfg(feature = "btc")]
+        Commands::Btc(args) => execute_btc(args, &config, cli.output).await,
+        #[cfg(feature = "solana")]
+        Commands::Solana(args) => execute_solana(args, &config, cli.output).await,
+    };
+
+    workspace::cleanup_registered();
+
+    match result {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => report_error(err, cli.output),
+    }
+}
+
+/// Report a failed command on stderr and return its category's exit code
+///
+/// With JSON output the report is a `CommandResult` object carrying the
+/// error code and suggestion, so scripts need not parse log text.
+fn report_error(err: WalletError, output: OutputFormat) -> std::process::ExitCode {
+    let code = err.exit_code();
+    match output {
+        OutputFormat::Table => error!("Command failed: {}", err),
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let result = CommandResult::<()>::from_error(err);
+            let report = match json {
+                OutputFormat::Ndjson => serde_json::to_string(&result),
+                _ => serde_json::to_string_pretty(&result),
+            };
+            match report {
+                Ok(report) => eprintln!("{}", report),
+                Err(_) => error!("Command failed: {}", result.error.map(|e| e.message).unwrap_or_default()),
+            }
+        }
+    }
+
+    std::process::ExitCode::from(u8::try_from(code).unwrap_or(1))
+}
+
+/// Feature classes a command needs, checked against the config's `disable` list
+fn required_features(command: &Commands) -> Vec<Feature> {
+    match command {
+        Commands::Load(args) => {
+            let mut features = Vec::new();
+            if args.export {
+                features.push(Feature::ExportSecret);
+            }
+            if args.use_keychain {
+                features.push(Feature::Keychain);
+            }
+            features
+        }
+        Commands::Export(args) => {
+            // Unknown formats are treated as secret until the command rejects them
+            let secret = ExportRegistry::default()
+                .get(&args.format)
+                .map_or(true, |format| format.contains_secrets());
+            if secret { vec![Feature::ExportSecret] } else { Vec::new() }
+        }
+        Commands::Paper(args) if args.include_mnemonic => vec![Feature::ExportSecret],
+        Commands::Bip85(_) => vec![Feature::ExportSecret],
+        Commands::Recover(args) if args.check_balance => vec![Feature::ExportSecret, Feature::Network],
+        Commands::Recover(_) => vec![Feature::ExportSecret],
+        Commands::Network(NetworkArgs { command: NetworkCommands::UpdateRegistry { .. } }) => vec![Feature::Network],
+        Commands::Keychain(_) => vec![Feature::Keychain],
+        Commands::Agent(AgentArgs { command: AgentCommands::Start { .. } | AgentCommands::Add { .. } }) => vec![Feature::Serve],
+        Commands::Watch(WatchArgs { command: WatchCommands::Balance { .. } }) => vec![Feature::Network],
+        Commands::Scan(_) => vec![Feature::Network],
+        Commands::ProveOwnership(_) => vec![Feature::Sign],
+        Commands::Siwe(_) => vec![Feature::Sign],
+        Commands::DecryptMsg(_) => vec![Feature::Sign],
+        Commands::Validator(ValidatorArgs { command: ValidatorCommands::Derive { .. } }) => vec![Feature::ExportSecret],
+        Commands::Stealth(StealthArgs { command: StealthCommands::Scan { from_block: Some(_), .. } }) => vec![Feature::Network],
+        Commands::Stealth(StealthArgs { command: StealthCommands::Claim { .. } }) => vec![Feature::ExportSecret],
+        Commands::ProveVerify(args) if args.erc1271 => vec![Feature::Network],
+        Commands::Approvals(_) => vec![Feature::Network],
+        Commands::Nft(NftArgs { command: NftCommands::Transfer { from_file: Some(_), .. } }) => vec![Feature::Sign, Feature::Network],
+        Commands::Nft(_) => vec![Feature::Network],
+        Commands::Call(args) if args.to.is_some() => vec![Feature::Network],
+        Commands::History(_) => vec![Feature::Network],
+        Commands::Daemon(_) => vec![Feature::Sign, Feature::Serve],
+        Commands::Safe(SafeArgs { command: SafeCommands::Sign { .. } }) => vec![Feature::Sign],
+        Commands::PsbtEth(PsbtEthArgs { command: PsbtEthCommands::Sign { .. } }) => vec![Feature::Sign],
+        Commands::Airgap(AirgapArgs { command: AirgapCommands::Sign { .. } }) => vec![Feature::Sign],
+        Commands::Identity(IdentityArgs { command: IdentityCommands::Export { .. } }) => vec![Feature::Sign],
+        _ => Vec::new(),
+    }
+}
+
+/// Load configuration from file or use defaults
+async fn load_config(
+    config_path: Option<std::path::PathBuf>,
+    profile: Option<String>,
+) -> WalletResult<WalletConfig> {
+    let config = match config_path {
+        Some(path) => {
+            info!("Loading config from: {}", path.display());
+            WalletConfig::load(&path)?
+        }
+        None => {
+            let default_path = web3wallet_cli::config::default_wallet_dir()
+                .join(web3wallet_cli::config::CONFIG_FILE_NAME);
+            if default_path.exists() {
+                WalletConfig::load(&default_path)?
+            } else {
+                WalletConfig::default()
+            }
+        }
+    };
+
+    let profile = profile.or_else(|| {
+        std::env::var_os(web3wallet_cli::config::PROFILE_ENV_VAR)
+            .map(|value| value.to_string_lossy().into_owned())
+            .filter(|value| !value.is_empty())
+    });
+
+    match profile {
+        Some(name) => {
+            info!("Using config profile: {}", name);
+            config.with_profile(&name)
+        }
+        None => Ok(config),
+    }
+}
+
+/// Prompt for a secret on the terminal, unless prompts are disabled
+fn prompt_password(prompt: impl ToString) -> WalletResult<String> {
+    let prompt = prompt.to_string();
+    if password::is_non_interactive() {
+        return Err(password::prompt_refused(&prompt).into());
+    }
+    Ok(rpassword::prompt_password(prompt)?)
+}
+
+/// Wallet password from `--password-file`, `--password-stdin` or $WALLET_PASSWORD, else a prompt
+fn wallet_password(config: &WalletConfig) -> WalletResult<zeroize::Zeroizing<String>> {
+    match &config.password_source {
+        Some(source) => Ok(zeroize::Zeroizing::new(source.read()?.as_str().to_string())),
+        None => Ok(zeroize::Zeroizing::new(prompt_password("Enter wallet password: ")?)),
+    }
+}
+
+/// Secret that opens the keystore at `file_path`
+///
+/// For a threshold keystore, share holders are asked for their passphrases
+/// in turn (Enter skips one) until enough shares open; otherwise this is
+/// the wallet password.
+fn keystore_password(file_path: &std::path::Path, config: &WalletConfig) -> WalletResult<zeroize::Zeroizing<String>> {
+    let keystore = match std::fs::read_to_string(file_path).map_err(WalletError::from).and_then(|json| Keystore::from_json(&json)) {
+        Ok(keystore) => keystore,
+        // Let the load report a missing or broken file with its usual error
+        Err(_) => return wallet_password(config),
+    };
+    let set = match &keystore.crypto.shares {
+        Some(set) => set,
+        None => return wallet_password(config),
+    };
+
+    eprintln!(
+        "🔑 This keystore needs {} of {} share passphrases ({})",
+        set.threshold,
+        set.shares.len(),
+        set.shares.iter().map(|share| share.label.as_str()).collect::<Vec<_>>().join(", ")
+    );
+    ThresholdService::assemble(file_path, set, config.reset_lockout, |share| {
+        let passphrase = zeroize::Zeroizing::new(prompt_password(format!("Passphrase for {} (Enter to skip): ", share.label))?);
+        Ok((!passphrase.is_empty()).then_some(passphrase))
+    })
+}
+
+/// Label and passphrase of each holder of a threshold keystore
+type ShareHolders = Vec<(String, zeroize::Zeroizing<String>)>;
+
+/// Labels and passphrases of `--share` holders when `--threshold` is given
+///
+/// Every holder types their own passphrase, so a configured password is refused.
+fn share_holders(
+    threshold: Option<u8>,
+    labels: &[String],
+    config: &WalletConfig,
+) -> WalletResult<Option<(u8, ShareHolders)>> {
+    let threshold = match threshold {
+        Some(threshold) => threshold,
+        None => return Ok(None),
+    };
+    if config.password_source.is_some() {
+        return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "threshold".to_string(),
+            value: threshold.to_string(),
+            expected: "each share holder typing a passphrase at the prompt, not a configured password".to_string(),
+        }));
+    }
+
+    let mut holders = Vec::with_capacity(labels.len());
+    for label in labels {
+        let passphrase = new_wallet_password(&format!("Passphrase for {}: ", label), config)?;
+        holders.push((label.clone(), passphrase));
+    }
+    Ok(Some((threshold, holders)))
+}
+
+/// Wallet held by the session agent, or the keystore decrypted with the wallet password
+async fn unlock_wallet(
+    manager: &WalletManager,
+    file_path: &std::path::Path,
+    config: &WalletConfig,
+) -> WalletResult<web3wallet_cli::Wallet> {
+    if let Some(wallet) = AgentClient::new(&config.wallet_dir).get(file_path).await {
+        info!("Using the wallet unlocked in the agent");
+        return Ok(wallet);
+    }
+    let password = keystore_password(file_path, config)?;
+    manager.load_wallet(file_path, &password).await
+}
+
+/// Password for new keystores: the configured one, or prompted twice
+///
+/// A weak password or a mistyped confirmation asks again, up to
+/// `MAX_NEW_PASSWORD_ATTEMPTS` times, so an already generated wallet is not
+/// lost to a typo. A configured password must meet the same requirements
+/// but fails at once. Passwords known from breaches are refused unless
+/// `--allow-weak` is given.
+fn new_wallet_password(prompt: &str, config: &WalletConfig) -> WalletResult<zeroize::Zeroizing<String>> {
+    let breached = BreachedPasswords::load(&config.wallet_dir)?;
+    let check_breached = |password: &str| match breached.check(password) {
+        Err(_) if config.allow_breached => {
+            warn!("This password appears in a list of leaked passwords; continuing because of --allow-weak");
+            Ok(())
+        }
+        Err(e) => {
+            warn!("This password appears in a list of leaked passwords; choose another or pass --allow-weak");
+            Err(e)
+        }
+        Ok(()) => Ok(()),
+    };
+
+    if let Some(source) = &config.password_source {
+        let password = zeroize::Zeroizing::new(source.read()?.as_str().to_string());
+        CryptoService::validate_password(&password)?;
+        check_breached(&password)?;
+        return Ok(password);
+    }
+
+    let attempts = web3wallet_cli::config::crypto::MAX_NEW_PASSWORD_ATTEMPTS;
+    let mut attempt = 1;
+    loop {
+        let password = zeroize::Zeroizing::new(prompt_password(prompt)?);
+        let error = match CryptoService::validate_password(&password) {
+            Err(e) => {
+                if let WalletError::Authentication(AuthenticationError::WeakPassword { requirements }) = &e {
+                    warn!("Password is too weak; it needs: {}", requirements.join(", "));
+                }
+                e
+            }
+            Ok(()) => match check_breached(&password) {
+                Err(e) => e,
+                Ok(()) => {
+                    let confirm = zeroize::Zeroizing::new(prompt_password("Confirm password: ")?);
+                    if password == confirm {
+                        return Ok(password);
+                    }
+                    warn!("Passwords do not match");
+                    WalletError::UserInput(UserInputError::PasswordMismatch)
+                }
+            },
+        };
+
+        if attempt >= attempts {
+            return Err(error);
+        }
+        attempt += 1;
+        eprintln!("Please try again (attempt {} of {})", attempt, attempts);
+    }
+}
+
+/// Read the BIP39 passphrase from the arguments or a prompt
+fn read_passphrase(args: &PassphraseArgs, confirm: bool) -> WalletResult<Option<zeroize::Zeroizing<String>>> {
+    if let Some(passphrase) = &args.passphrase {
+        return Ok(Some(zeroize::Zeroizing::new(passphrase.clone())));
+    }
+    if !args.passphrase_prompt {
+        return Ok(None);
+    }
+
+    let passphrase = zeroize::Zeroizing::new(prompt_password("Enter BIP39 passphrase: ")?);
+    if confirm {
+        let again = zeroize::Zeroizing::new(prompt_password("Confirm BIP39 passphrase: ")?);
+        if *passphrase != *again {
+            return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+        }
+    }
+
+    Ok(Some(passphrase))
+}
+
+/// Unlock a loaded wallet's BIP39 passphrase, prompting if it was not given
+fn unlock_passphrase(wallet: &mut web3wallet_cli::Wallet, args: &PassphraseArgs) -> WalletResult<()> {
+    if !wallet.uses_passphrase() {
+        if args.passphrase.is_some() || args.passphrase_prompt {
+            warn!("This wallet does not use a BIP39 passphrase; ignoring it");
+        }
+        return Ok(());
+    }
+
+    let passphrase = match read_passphrase(args, false)? {
+        Some(passphrase) => passphrase,
+        None => zeroize::Zeroizing::new(prompt_password("Enter BIP39 passphrase: ")?),
+    };
+    wallet.unlock_passphrase(&passphrase)
+}
+
+/// SLIP-44 coin type from `--coin-type`: a number, or a registry chain with a `slip44` entry
+async fn resolve_coin_type(value: &str, config: &WalletConfig) -> WalletResult<u32> {
+    if let Ok(coin_type) = value.parse::<u32>() {
+        return Ok(coin_type);
+    }
+
+    let registry = ChainRegistry::load(&config.wallet_dir).await?;
+    registry.find(value).and_then(|chain| chain.slip44).ok_or_else(|| {
+        UserInputError::InvalidParameters {
+            parameter: "coin-type".to_string(),
+            value: value.to_string(),
+            expected: "a SLIP-44 number or a registry chain with a coin type (see `wallet network list`)".to_string(),
+        }
+        .into()
+    })
+}
+
+/// Move a new HD wallet to `--coin-type`, or to the coin type configured for its network
+async fn apply_coin_type(wallet: &mut web3wallet_cli::Wallet, value: Option<&str>, config: &WalletConfig) -> WalletResult<()> {
+    let coin_type = match value {
+        Some(value) => resolve_coin_type(value, config).await?,
+        None if wallet.is_hd() => config.coin_type(wallet.network().name()),
+        None => return Ok(()),
+    };
+    if coin_type != wallet.coin_type() {
+        wallet.set_coin_type(coin_type)?;
+    }
+    Ok(())
+}
+
+/// Print a JSON document: indented for `json`, on one line for `ndjson`
+fn print_json<T: serde::Serialize + ?Sized>(value: &T, output: OutputFormat, case: AddressCase) -> WalletResult<()> {
+    let json = case.scope(|| match output {
+        OutputFormat::Ndjson => serde_json::to_string(value),
+        _ => serde_json::to_string_pretty(value),
+    })?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Resolve a wallet file argument against the wallet directory
+fn wallet_file_path(filename: &str, config: &WalletConfig) -> PathBuf {
+    if filename.contains('/') || filename.contains('\\') {
+        PathBuf::from(filename)
+    } else {
+        config.wallet_dir.join(filename)
+    }
+}
+
+/// Resolve the keystore path for a saved wallet
+fn keystore_save_path(
+    wallet: &web3wallet_cli::Wallet,
+    name: Option<&str>,
+    template: Option<&str>,
+    config: &WalletConfig,
+) -> WalletResult<PathBuf> {
+    let filename = match template.or(config.save_template.as_deref()) {
+        Some(template) => NamingService::render(template, wallet, name)?,
+        None => name.unwrap_or_default().to_string(),
+    };
+
+    Ok(config.wallet_dir.join(format!("{}.json", filename)))
+}
+
+/// Execute wallet creation command
+async fn execute_create(
+    args: CreateArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(WalletConfig {
+        allow_weak_kdf: config.allow_weak_kdf || args.allow_weak_kdf,
+        ..config.clone()
+    });
+
+    info!(
+        "Generating new {}-word {} mnemonic wallet...",
+        args.words,
+        MnemonicService::language_name(args.language)
+    );
+    let mut wallet = manager.create_wallet_in(args.words, args.language).await?;
+    apply_coin_type(&mut wallet, args.coin_type.as_deref(), config).await?;
+    if let Some(account) = args.account {
+        wallet.set_account(account)?;
+    }
+    if let Some(passphrase) = read_passphrase(&args.passphrase, true)? {
+        wallet.protect_with_passphrase(&passphrase)?;
+    }
+
+    // Display wallet information
+    match output {
+        OutputFormat::Table => {
+            println!("\n🎉 Wallet created successfully!");
+            println!("Address:  {}", wallet.address());
+            println!("Network:  {}", wallet.network());
+            if wallet.account() != 0 || wallet.coin_type() != web3wallet_cli::config::ETHEREUM_COIN_TYPE {
+                println!("Account:  {} ({})", wallet.account(), wallet.derivation_path());
+            }
+            println!("Mnemonic: {}", wallet.mnemonic());
+            println!("\n⚠️  IMPORTANT: Store your mnemonic phrase safely!");
+            println!("   Anyone with access to this phrase can access your wallet.");
+            if wallet.uses_passphrase() {
+                println!("   This wallet also needs its BIP39 passphrase, which is never saved.");
+                println!("   Without it the mnemonic recovers a different, empty wallet.");
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "address": wallet.address(),
+                "network": wallet.network(),
+                "mnemonic": wallet.mnemonic(),
+                "bip39_passphrase": wallet.uses_passphrase(),
+                "derivation_path": wallet.derivation_path(),
+                "coin_type": wallet.coin_type(),
+                "created_at": wallet.created_at()
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    // Save wallet if requested
+    if args.save.is_some() || args.save_template.is_some() {
+        let file_path = keystore_save_path(
+            &wallet,
+            args.save.as_deref(),
+            args.save_template.as_deref(),
+            config,
+        )?;
+
+        if args.bind_hardware {
+            warn_hardware_binding()?;
+        }
+        let shared = share_holders(args.threshold, &args.shares, config)?;
+        let password = match shared {
+            Some(_) => zeroize::Zeroizing::default(),
+            None => new_wallet_password("Enter password to encrypt wallet: ", config)?,
+        };
+
+        let wallet_dir = &config.wallet_dir;
+        tokio::fs::create_dir_all(wallet_dir).await.map_err(|e| {
+            WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
+                path: wallet_dir.display().to_string(),
+                details: e.to_string(),
+            })
+        })?;
+
+        let binding = args.bind_hardware.then_some(HardwareBinding::Tpm);
+        match &shared {
+            Some((threshold, holders)) => manager.save_wallet_shared(&wallet, &file_path, *threshold, holders, binding).await?,
+            None => manager.save_wallet_bound(&wallet, &file_path, &password, binding).await?,
+        }
+        audit(config, AuditAction::Create, "create", Some(&file_path), Some(wallet.address()), None)?;
+
+        println!("\n💾 Wallet saved to: {}", file_path.display());
+    }
+
+    Ok(())
+}
+
+/// Execute bulk wallet creation
+async fn execute_create_batch(
+    args: CreateBatchArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let network = args.network.unwrap_or_else(|| config.network.clone());
+    let manager = WalletManager::new(WalletConfig {
+        network: network.clone(),
+        allow_weak_kdf: config.allow_weak_kdf || args.allow_weak_kdf,
+        ..config.clone()
+    });
+    let out_dir = args.out_dir.unwrap_or_else(|| config.wallet_dir.clone());
+    let manifest_path = args.manifest.unwrap_or_else(|| out_dir.join("manifest.csv"));
+
+    let password = new_wallet_password("Enter password to encrypt the wallets: ", config)?;
+
+    let created = manager
+        .create_wallets(args.count, args.words, args.language, &out_dir, &password)
+        .await?;
+
+    // Keystores are listed by file name, relative to the manifest's directory
+    let entries: Vec<_> = created
+        .iter()
+        .map(|keystore| {
+            let file = keystore.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            (keystore.index, keystore.address.to_string(), file)
+        })
+        .collect();
+    let manifest = if manifest_path.extension().is_some_and(|ext| ext == "json") {
+        let rows: Vec<_> = entries
+            .iter()
+            .map(|(index, address, file)| serde_json::json!({ "index": index, "address": address, "keystore": file }))
+            .collect();
+        serde_json::to_string_pretty(&rows)?
+    } else {
+        let mut csv = String::from("index,address,keystore\n");
+        for (index, address, file) in &entries {
+            csv.push_str(&format!("{},{},{}\n", index, address, file));
+        }
+        csv
+    };
+    web3wallet_cli::utils::write_atomic(&manifest_path, manifest.as_bytes(), 0o644)?;
+    for keystore in &created {
+        audit(config, AuditAction::Create, "create-batch", Some(&keystore.path), Some(&keystore.address), None)?;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n✅ Created {} wallets in {}", created.len(), out_dir.display());
+            println!("Network:  {}", network);
+            println!("Manifest: {}", manifest_path.display());
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "count": created.len(),
+                "network": network,
+                "out_dir": out_dir,
+                "manifest": manifest_path,
+                "wallets": created
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute wallet import command
+async fn execute_import(
+    args: ImportArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(WalletConfig {
+        allow_weak_kdf: config.allow_weak_kdf || args.allow_weak_kdf,
+        ..config.clone()
+    });
+
+    let check_language = |mnemonic: &str| match args.language {
+        Some(language) => MnemonicService::validate_in(language, mnemonic).map(|_| ()),
+        None => Ok(()),
+    };
+
+    let mut wallet = if let Some(mnemonic) = &args.mnemonic {
+        info!("Importing wallet from mnemonic...");
+        check_language(mnemonic)?;
+        manager.import_from_mnemonic(mnemonic).await?
+    } else if let Some(private_key) = args.private_key {
+        info!("Importing wallet from private key...");
+        manager.import_from_private_key(&private_key).await?
+    } else if let Some(seed) = &args.seed {
+        info!("Importing wallet from BIP39 seed...");
+        manager.import_from_seed(seed).await?
+    } else if let Some(entropy) = &args.entropy {
+        info!("Importing wallet from BIP39 entropy...");
+        manager.import_from_entropy(entropy, args.language.unwrap_or(Language::English)).await?
+    } else {
+        // Prompt for mnemonic if no input provided
+        let mnemonic = prompt_password("Enter mnemonic phrase: ")?;
+        check_language(&mnemonic)?;
+        manager.import_from_mnemonic(&mnemonic).await?
+    };
+    if let Some(preset) = args.path_preset {
+        wallet.set_path_preset(preset)?;
+    }
+    apply_coin_type(&mut wallet, args.coin_type.as_deref(), config).await?;
+    if let Some(account) = args.account {
+        wallet.set_account(account)?;
+    }
+    if let Some(passphrase) = read_passphrase(&args.passphrase, true)? {
+        wallet.protect_with_passphrase(&passphrase)?;
+    }
+
+    // Display wallet information
+    match output {
+        OutputFormat::Table => {
+            println!("\n✅ Wallet imported successfully!");
+            println!("Address:  {}", wallet.address());
+            println!("Network:  {}", wallet.network());
+            if wallet.account() != 0 || wallet.coin_type() != web3wallet_cli::config::ETHEREUM_COIN_TYPE {
+                println!("Account:  {} ({})", wallet.account(), wallet.derivation_path());
+            }
+            if wallet.has_mnemonic() {
+                println!("Type:     HD Wallet (BIP44)");
+            } else if wallet.is_hd() {
+                println!("Type:     HD Wallet (BIP44, seed only)");
+            } else {
+                println!("Type:     Private Key Only");
+            }
+            if wallet.uses_passphrase() {
+                println!("Passphrase: yes (not saved)");
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "address": wallet.address(),
+                "network": wallet.network(),
+                "has_mnemonic": wallet.has_mnemonic(),
+                "bip39_passphrase": wallet.uses_passphrase(),
+                "derivation_path": wallet.derivation_path(),
+                "coin_type": wallet.coin_type(),
+                "path_preset": wallet.path_preset(),
+                "created_at": wallet.created_at()
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    // Save wallet if requested
+    if args.save.is_some() || args.save_template.is_some() {
+        let file_path = keystore_save_path(
+            &wallet,
+            args.save.as_deref(),
+            args.save_template.as_deref(),
+            config,
+        )?;
+
+        if args.bind_hardware {
+            warn_hardware_binding()?;
+        }
+        let shared = share_holders(args.threshold, &args.shares, config)?;
+        let password = match shared {
+            Some(_) => zeroize::Zeroizing::default(),
+            None => new_wallet_password("Enter password to encrypt wallet: ", config)?,
+        };
+
+        let wallet_dir = &config.wallet_dir;
+        tokio::fs::create_dir_all(wallet_dir).await.map_err(|e| {
+            WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
+                path: wallet_dir.display().to_string(),
+                details: e.to_string(),
+            })
+        })?;
+
+        let binding = args.bind_hardware.then_some(HardwareBinding::Tpm);
+        match &shared {
+            Some((threshold, holders)) => manager.save_wallet_shared(&wallet, &file_path, *threshold, holders, binding).await?,
+            None => manager.save_wallet_bound(&wallet, &file_path, &password, binding).await?,
+        }
+        audit(config, AuditAction::Import, "import", Some(&file_path), Some(wallet.address()), None)?;
+
+        println!("\n💾 Wallet saved to: {}", file_path.display());
+    }
+
+    Ok(())
+}
+
+/// Execute wallet load command
+async fn execute_load(
+    args: LoadArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let file_path = wallet_file_path(&args.filename, config);
+
+    info!("Loading wallet from: {}", file_path.display());
+
+    let mut wallet = if args.address_only {
+        // Load keystore without decryption for address only
+        let keystore = web3wallet_cli::services::CryptoService::load_keystore(&file_path).await?;
+
+        match output {
+            OutputFormat::Table => {
+                println!("\n📁 Wallet file: {}", file_path.display());
+                println!("Address:  {}", keystore.metadata.address);
+                println!("Network:  {}", keystore.metadata.network);
+                println!("Created:  {}", keystore.metadata.created_at);
+                if let Some(alias) = &keystore.metadata.alias {
+                    println!("Alias:    {}", alias);
+                }
+                if keystore.metadata.bip39_passphrase {
+                    println!("Passphrase: required");
+                }
+                if let Some(path) = &keystore.metadata.derivation_path {
+                    println!("Path:     {}", path);
+                }
+            }
+            json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                let output = config.address_case.scope(|| serde_json::json!({
+                    "file": file_path.display().to_string(),
+                    "address": keystore.metadata.address,
+                    "network": keystore.metadata.network,
+                    "created_at": keystore.metadata.created_at,
+                    "alias": keystore.metadata.alias,
+                    "bip39_passphrase": keystore.metadata.bip39_passphrase,
+                    "derivation_path": keystore.metadata.derivation_path
+                }));
+                print_json(&output, json, config.address_case)?;
+            }
+        }
+        warn_backup_reminder(&file_path, &keystore.metadata.network, config);
+        return Ok(());
+    } else {
+        // Load and decrypt wallet
+        let stored = if args.use_keychain {
+            keychain::get_password(&file_path)?
+        } else {
+            None
+        };
+
+        match stored {
+            Some(password) => manager.load_wallet(&file_path, &password).await?,
+            None => {
+                let password = keystore_password(&file_path, config)?;
+                let wallet = manager.load_wallet(&file_path, &password).await?;
+                if args.use_keychain {
+                    keychain::store_password(&file_path, &password)?;
+                    info!("Password stored in the OS keychain");
+                }
+                wallet
+            }
+        }
+    };
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+    if let Some(coin_type) = &args.coin_type {
+        let coin_type = resolve_coin_type(coin_type, config).await?;
+        if coin_type != wallet.coin_type() {
+            wallet.set_coin_type(coin_type)?;
+        }
+    }
+    if let Some(account) = args.account.filter(|&account| account != wallet.account()) {
+        // Stored accounts belong to the keystore's own account and are not shown
+        wallet.set_account(account)?;
+    }
+    warn_backup_reminder(&file_path, wallet.network(), config);
+
+    // Display wallet information
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔓 Wallet loaded successfully!");
+            println!("Address:  {}", wallet.address());
+            println!("Network:  {}", wallet.network());
+            if wallet.account() != 0 || wallet.coin_type() != web3wallet_cli::config::ETHEREUM_COIN_TYPE {
+                println!("Account:  {} ({})", wallet.account(), wallet.derivation_path());
+            }
+            if wallet.has_mnemonic() {
+                println!("Type:     HD Wallet (BIP44)");
+            } else if wallet.is_hd() {
+                println!("Type:     HD Wallet (BIP44, seed only)");
+            } else {
+                println!("Type:     Private Key Only");
+            }
+            if let Some(alias) = wallet.alias() {
+                println!("Alias:    {}", alias);
+            }
+            println!("Created:  {}", wallet.created_at().format("%Y-%m-%d %H:%M:%S UTC"));
+            if !wallet.accounts().is_empty() {
+                println!("Accounts: {} stored (see `wallet accounts list`)", wallet.accounts().len());
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "address": wallet.address(),
+                "network": wallet.network(),
+                "has_mnemonic": wallet.has_mnemonic(),
+                "derivation_path": wallet.derivation_path(),
+                "coin_type": wallet.coin_type(),
+                "alias": wallet.alias(),
+                "created_at": wallet.created_at(),
+                "accounts": wallet.accounts()
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    if args.export {
+        audit(config, AuditAction::Export, "load --export", Some(&file_path), Some(wallet.address()), None)?;
+        export_secrets(&wallet, config)?;
+    }
+
+    // Derive specific address if requested
+    if let Some(index) = args.derive {
+        if !wallet.is_hd() {
+            return Err(WalletError::UserInput(
+                UserInputError::InvalidParameters {
+                    parameter: "derive".to_string(),
+                    value: index.to_string(),
+                    expected: "HD wallet with mnemonic".to_string(),
+                }
+            ));
+        }
+
+        let derived = wallet.derive_address(index)?;
+
+        match output {
+            OutputFormat::Table => {
+                println!("\n🔗 Derived address [{}]:", index);
+                println!("Address:  {}", derived.address());
+                println!("Path:     {}", derived.derivation_path());
+            }
+            json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                let output = config.address_case.scope(|| serde_json::json!({
+                    "derived": {
+                        "index": index,
+                        "address": derived.address(),
+                        "derivation_path": derived.derivation_path()
+                    }
+                }));
+                print_json(&output, json, config.address_case)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute keystore inspection
+async fn execute_info(
+    args: InfoArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    use web3wallet_cli::models::keystore::KdfParams;
+    use web3wallet_cli::services::keystore_info::KeystoreInfoService;
+
+    let file_path = wallet_file_path(&args.filename, config);
+    let info = KeystoreInfoService::inspect(&file_path)?;
+
+    // Only a keystore that passed validation can be decrypted
+    let password_check = match (&info.keystore, args.check_password) {
+        (Some(_), true) if info.is_loadable() => {
+            let password = keystore_password(&file_path, config)?;
+            // Through the manager, so wrong passwords count toward the lockout
+            Some(WalletManager::new(config.clone()).load_wallet(&file_path, &password).await.map(|_| ()))
+        }
+        (_, true) => {
+            warn!("Not checking the password: the file has problems that stop it from opening");
+            None
+        }
+        (_, false) => None,
+    };
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n📄 {}", info.path.display());
+            println!("Kind:        {}", info.kind.name());
+            println!("Size:        {} bytes", info.file_size);
+            if let Some(modified) = info.modified {
+                println!("Modified:    {}", modified.format("%Y-%m-%d %H:%M:%S UTC"));
+            }
+            println!("Permissions: {}", info.permissions);
+
+            if let Some(keystore) = &info.keystore {
+                let metadata = &keystore.metadata;
+                println!("\nVersion:     {} ({})", keystore.version, metadata.keystore_type);
+                println!("Address:     {}", metadata.address);
+                println!("Network:     {}", metadata.network);
+                println!("Created:     {}", metadata.created_at);
+                if let Some(alias) = &metadata.alias {
+                    println!("Alias:       {}", alias);
+                }
+                if let Some(path) = &metadata.derivation_path {
+                    println!("Path:        {}", path);
+                }
+                if metadata.bip39_passphrase {
+                    println!("Passphrase:  required");
+                }
+                if !metadata.tags.is_empty() {
+                    println!("Tags:        {}", metadata.tags.join(", "));
+                }
+                if let Some(notes) = &metadata.notes {
+                    println!("Notes:       {}", notes);
+                }
+
+                println!("\nCipher:      {}", keystore.crypto.cipher);
+                match &keystore.crypto.kdfparams {
+                    KdfParams::Argon2 { dklen, memory, time, parallelism, .. } => println!(
+                        "KDF:         {} (memory {} KiB, time {}, parallelism {}, key {} bytes)",
+                        keystore.crypto.kdf, memory, time, parallelism, dklen
+                    ),
+                    KdfParams::Pbkdf2 { dklen, c, prf, .. } => println!(
+                        "KDF:         {} ({}, {} iterations, key {} bytes)",
+                        keystore.crypto.kdf, prf, c, dklen
+                    ),
+                }
+                if let Some(factor) = &keystore.crypto.hardware {
+                    println!("Hardware:    {}", factor.describe());
+                }
+                if let Some(set) = &keystore.crypto.shares {
+                    let labels: Vec<&str> = set.shares.iter().map(|share| share.label.as_str()).collect();
+                    println!("Shares:      {} of {} ({})", set.threshold, labels.len(), labels.join(", "));
+                }
+            }
+
+            if info.problems.is_empty() {
+                println!("\n✅ No problems found");
+            } else {
+                println!("\n⚠️  Problems:");
+                for problem in &info.problems {
+                    println!("   - {}", problem);
+                }
+            }
+            match &password_check {
+                Some(Ok(())) => println!("🔓 Password is correct"),
+                Some(Err(_)) => println!("🔒 Password does not decrypt this keystore"),
+                None => {}
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let keystore = info.keystore.as_ref();
+            let output = config.address_case.scope(|| serde_json::json!({
+                "file": info.path.display().to_string(),
+                "kind": info.kind.name(),
+                "file_size": info.file_size,
+                "modified": info.modified.map(|modified| modified.to_rfc3339()),
+                "permissions": info.permissions.to_string(),
+                "permissions_secure": info.permissions.is_secure(),
+                "version": keystore.map(|k| &k.version),
+                "metadata": keystore.map(|k| &k.metadata),
+                "cipher": keystore.map(|k| &k.crypto.cipher),
+                "kdf": keystore.map(|k| &k.crypto.kdf),
+                "kdfparams": keystore.map(|k| &k.crypto.kdfparams),
+                "hardware": keystore.and_then(|k| k.crypto.hardware.as_ref()),
+                "shares": keystore.and_then(|k| k.crypto.shares.as_ref()).map(|set| serde_json::json!({
+                    "threshold": set.threshold,
+                    "holders": set.shares.iter().map(|share| &share.label).collect::<Vec<_>>()
+                })),
+                "problems": info.problems,
+                "password_ok": password_check.as_ref().map(Result::is_ok)
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    match password_check {
+        Some(Err(e)) => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Execute wallet list command
+async fn execute_list(
+    args: ListArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let wallet_dir = args.path.unwrap_or_else(|| config.wallet_dir.clone());
+
+    info!("Scanning wallet directory: {}", wallet_dir.display());
+
+    // Create directory if it doesn't exist
+    if !wallet_dir.exists() {
+        tokio::fs::create_dir_all(&wallet_dir).await.map_err(|e| {
+            WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
+                path: wallet_dir.display().to_string(),
+                details: e.to_string(),
+            })
+        })?;
+
+        match output {
+            OutputFormat::Table => {
+                println!("\n📂 Wallet directory: {}", wallet_dir.display());
+                println!("No wallets found. Directory created.");
+            }
+            json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                let output = config.address_case.scope(|| serde_json::json!({
+                    "directory": wallet_dir.display().to_string(),
+                    "wallets": []
+                }));
+                print_json(&output, json, config.address_case)?;
+            }
+        }
+        return Ok(());
+    }
+
+    // Read keystore metadata (served from the index when enabled)
+    let query = KeystoreQuery {
+        filter: KeystoreFilter {
+            tag: args.tag,
+            network: args.network,
+            alias_contains: args.alias_contains,
+            address: args.address,
+            created_after: args.created_after,
+            created_before: args.created_before,
+        },
+        sort: args.sort,
+        limit: args.limit,
+    };
+    let wallets = query.apply(keystore_index::list_keystores(&wallet_dir, config.is_low_resource())?);
+
+    let backup_log = BackupLog::load(&wallet_dir).unwrap_or_default();
+    let reminders: Vec<String> = wallets
+        .iter()
+        .filter(|wallet| !wallet.watch_only)
+        .filter_map(|wallet| {
+            let network = wallet.network.parse().ok()?;
+            backup_reminder(&backup_log, &wallet.filename, &network, config)
+        })
+        .collect();
+
+    // Display results
+    match output {
+        OutputFormat::Table => {
+            println!("\n📂 Wallet directory: {}", wallet_dir.display());
+            println!("Found {} wallet(s):\n", wallets.len());
+
+            if wallets.is_empty() {
+                println!("No wallets found.");
+            } else {
+                println!("{:<20} {:<44} {:<12} {:<9} {:<20} {:<20}",
+                    "FILENAME", "ADDRESS", "NETWORK", "TYPE", "CREATED", "TAGS");
+                println!("{}", "─".repeat(130));
+
+                for wallet in wallets {
+                    let short_addr = if wallet.address.len() >= 42 {
+                        format!("{}...{}",
+                            &wallet.address[..6],
+                            &wallet.address[38..])
+                    } else {
+                        wallet.address.clone()
+                    };
+
+                    println!("{:<20} {:<44} {:<12} {:<9} {:<20} {:<20}",
+                        wallet.filename,
+                        short_addr,
+                        wallet.network,
+                        if wallet.watch_only { "watch" } else { "keystore" },
+                        wallet.created_at.get(..19).unwrap_or(&wallet.created_at).replace('T', " "),
+                        wallet.tags.join(",")
+                    );
+                }
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let wallet_list: Vec<_> = wallets.into_iter().map(|wallet| {
+                config.address_case.scope(|| serde_json::json!({
+                    "filename": wallet.filename,
+                    "path": wallet.path.display().to_string(),
+                    "address": wallet.address,
+                    "network": wallet.network,
+                    "created_at": wallet.created_at,
+                    "alias": wallet.alias,
+                    "tags": wallet.tags,
+                    "notes": wallet.notes,
+                    "watch_only": wallet.watch_only
+                }))
+            }).collect();
+
+            let output = config.address_case.scope(|| serde_json::json!({
+                "directory": wallet_dir.display().to_string(),
+                "count": wallet_list.len(),
+                "wallets": wallet_list
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    for reminder in reminders {
+        warn!("{}", reminder);
+    }
+
+    Ok(())
+}
+
+/// Reminder text when a mainnet keystore has no recent backup
+fn backup_reminder(log: &BackupLog, filename: &str, network: &Network, config: &WalletConfig) -> Option<String> {
+    if !network.is_mainnet() || config.backup_reminder_days == 0 {
+        return None;
+    }
+
+    let now = chrono::Utc::now();
+    match log.status(filename, config.backup_reminder_days, now) {
+        BackupStatus::Current => None,
+        BackupStatus::Never => Some(format!(
+            "💾 {} has never been backed up; consider running `wallet backup`",
+            filename
+        )),
+        BackupStatus::Stale { last } => Some(format!(
+            "💾 {} was last backed up {} days ago; consider running `wallet backup`",
+            filename,
+            (now - last).num_days()
+        )),
+    }
+}
+
+/// Print a backup reminder for a single keystore file
+fn warn_backup_reminder(file_path: &std::path::Path, network: &Network, config: &WalletConfig) {
+    let (Some(dir), Some(filename)) = (file_path.parent(), file_path.file_name()) else {
+        return;
+    };
+    let log = BackupLog::load(dir).unwrap_or_default();
+    if let Some(reminder) = backup_reminder(&log, &filename.to_string_lossy(), network, config) {
+        warn!("{}", reminder);
+    }
+}
+
+/// Execute address derivation command
+async fn execute_derive(
+    args: DeriveArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    // An index derives below the wallet's base path; a full path replaces it
+    let custom_base = match args.path.parse::<u32>() {
+        Ok(_) => None,
+        Err(_) => Some(args.path.parse::<DerivationPath>()?),
+    };
+
+    // Load wallet if file is specified
+    let wallet = if let Some(filename) = args.from_file {
+        let file_path = wallet_file_path(&filename, config);
+
+        let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+        unlock_passphrase(&mut wallet, &args.passphrase)?;
+        wallet
+    } else {
+        // Prompt for mnemonic
+        let mnemonic = prompt_password("Enter mnemonic phrase: ")?;
+        let mut wallet = manager.import_from_mnemonic(&mnemonic).await?;
+        if let Some(passphrase) = read_passphrase(&args.passphrase, false)? {
+            wallet.protect_with_passphrase(&passphrase)?;
+        }
+        apply_coin_type(&mut wallet, None, config).await?;
+        wallet
+    };
+    let coin_type = match &args.coin_type {
+        Some(coin_type) => resolve_coin_type(coin_type, config).await?,
+        None => wallet.coin_type(),
+    };
+
+    if !wallet.is_hd() {
+        return Err(WalletError::UserInput(
+            UserInputError::InvalidParameters {
+                parameter: "wallet".to_string(),
+                value: "private key only".to_string(),
+                expected: "HD wallet".to_string(),
+            }
+        ));
+    }
+
+    // A preset picks both the base path and how the index is placed below it
+    let layout = args.path_preset.unwrap_or(wallet.path_preset());
+    let index = args.path.parse::<u32>().unwrap_or(args.start_index);
+    if let (Some(_), Some(value)) = (&custom_base, &args.coin_type) {
+        return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "coin-type".to_string(),
+            value: value.clone(),
+            expected: "an index argument; a full path already names the coin type".to_string(),
+        }));
+    }
+    if custom_base.is_some() && args.change {
+        return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "change".to_string(),
+            value: args.path.clone(),
+            expected: "an index argument; a full path already names the chain".to_string(),
+        }));
+    }
+    let relayout = args.path_preset.is_some() || args.account.is_some() || coin_type != wallet.coin_type();
+    let (base_path, start_index) = match (custom_base, args.account) {
+        (Some(_), Some(account)) => {
+            return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                parameter: "account".to_string(),
+                value: account.to_string(),
+                expected: "an index argument; a full path already names the account".to_string(),
+            }))
+        }
+        (Some(base), None) => (base, args.start_index),
+        (None, account) if relayout => (layout.account_base(coin_type, account.unwrap_or(0))?, index),
+        (None, _) => (wallet.derivation_path().clone(), index),
+    };
+    let base_path = if args.change { layout.change_base(&base_path)? } else { base_path };
+
+    if let Some(expect_file) = args.expect_file {
+        return check_expected_addresses(&wallet, layout, &base_path, &expect_file, args.start_index, config, output);
+    }
+
+    let derive_range = |indices: std::ops::Range<u32>| -> WalletResult<Vec<_>> {
+        let paths = indices
+            .clone()
+            .map(|index| layout.account_path(&base_path, index))
+            .collect::<WalletResult<Vec<_>>>()?;
+        Ok(indices.zip(wallet.derive_many(&paths, config.is_low_resource())?).collect())
+    };
+    let end_index = start_index.checked_add(args.count).ok_or_else(|| UserInputError::ValueOutOfRange {
+        parameter: "count".to_string(),
+        value: args.count.to_string(),
+        range: format!("0-{}", u32::MAX - start_index),
+    })?;
+
+    // Stream one line per address, a chunk at a time, so memory stays flat
+    if output == OutputFormat::Ndjson {
+        use std::io::Write;
+
+        let mut stdout = std::io::stdout().lock();
+        let mut chunk_start = start_index;
+        while chunk_start < end_index {
+            let chunk_end = end_index.min(chunk_start.saturating_add(web3wallet_cli::config::output::NDJSON_CHUNK));
+            let mut lines = String::new();
+            for (index, derived) in derive_range(chunk_start..chunk_end)? {
+                let line = config.address_case.scope(|| serde_json::json!({
+                    "index": index,
+                    "address": derived.address(),
+                    "derivation_path": derived.derivation_path()
+                }));
+                lines.push_str(&format!("{}\n", line));
+            }
+            match stdout.write_all(lines.as_bytes()).and_then(|_| stdout.flush()) {
+                // The reader has gone (`| head`); nothing left to derive for
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => return Ok(()),
+                result => result?,
+            }
+            chunk_start = chunk_end;
+        }
+        return Ok(());
+    }
+
+    // Derive addresses
+    let derived_addresses = derive_range(start_index..end_index)?;
+
+    // Display results
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔗 Derived addresses from HD wallet:");
+            println!("Base address: {}", wallet.address());
+            println!("Base path:    {}", base_path);
+            if args.change {
+                println!("Chain:        change");
+            }
+            println!("Layout:       {}\n", layout);
+
+            println!("{:<6} {:<44} {:<30}",
+                "INDEX", "ADDRESS", "DERIVATION PATH");
+            println!("{}", "─".repeat(85));
+
+            for (index, derived) in derived_addresses {
+                println!("{:<6} {:<44} {:<30}",
+                    index,
+                    derived.address(),
+                    derived.derivation_path()
+                );
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let addresses: Vec<_> = derived_addresses.into_iter().map(|(index, derived)| {
+                config.address_case.scope(|| serde_json::json!({
+                    "index": index,
+                    "address": derived.address(),
+                    "derivation_path": derived.derivation_path()
+                }))
+            }).collect();
+
+            let output = config.address_case.scope(|| serde_json::json!({
+                "base_address": wallet.address(),
+                "base_path": base_path,
+                "path_preset": layout,
+                "change": args.change,
+                "count": args.count,
+                "start_index": start_index,
+                "addresses": addresses
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write decrypted wallet secrets into the export workspace until the user confirms
+fn export_secrets(wallet: &web3wallet_cli::Wallet, config: &WalletConfig) -> WalletResult<()> {
+    if !wallet.has_mnemonic() {
+        return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "export".to_string(),
+            value: wallet.address().to_string(),
+            expected: "HD wallet with mnemonic".to_string(),
+        }));
+    }
+
+    let secrets = zeroize::Zeroizing::new(serde_json::to_string_pretty(&serde_json::json!({
+        "address": wallet.address(),
+        "network": wallet.network(),
+        "mnemonic": wallet.mnemonic(),
+        "derivation_path": wallet.derivation_path()
+    }))?);
+    let name = format!("{}-secrets.json", wallet.address().to_lowercase());
+    hold_in_workspace(&name, secrets.as_bytes(), config)
+}
+
+/// Write secret export data into the export workspace until the user confirms
+fn hold_in_workspace(name: &str, data: &[u8], config: &WalletConfig) -> WalletResult<()> {
+    let workspace = ExportWorkspace::create(config.export_dir.as_deref())?;
+    if !workspace.is_memory_backed() {
+        warn!(
+            "No memory-backed filesystem available; secrets are being written to disk at {}",
+            workspace.dir().display()
+        );
+    }
+
+    let path = workspace.write_secret(name, data)?;
+
+    eprintln!("\n🔐 Secrets exported to: {}", path.display());
+    eprintln!("   The file is deleted when this command exits.");
+    eprint!("   Press Enter when you are done with it... ");
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+
+    workspace::cleanup_registered();
+    Ok(())
+}
+
+/// Derive the indexes listed in an expected-address file and report differences
+fn check_expected_addresses(
+    wallet: &web3wallet_cli::Wallet,
+    layout: PathPreset,
+    base_path: &DerivationPath,
+    expect_file: &std::path::Path,
+    start_index: u32,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let content = std::fs::read_to_string(expect_file).map_err(|e| {
+        WalletError::FileSystem(FileSystemError::PermissionDenied {
+            path: expect_file.display().to_string(),
+            operation: format!("read: {}", e),
+        })
+    })?;
+
+    let expected = AddressCheckService::parse_csv(&content, start_index)?;
+    let report = AddressCheckService::compare(&expected, |index| {
+        wallet.derive_at(&layout.account_path(base_path, index)?).map(|d| *d.address())
+    })?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔍 Cross-checking {} expected address(es):", report.checks.len());
+            println!("{:<8} {:<44} {:<44} {:<8}",
+                "INDEX", "EXPECTED", "DERIVED", "STATUS");
+            println!("{}", "─".repeat(106));
+
+            for check in &report.checks {
+                let status = match check.status {
+                    CheckStatus::Match => "OK",
+                    CheckStatus::Mismatch => "MISMATCH",
+                };
+                println!("{:<8} {:<44} {:<44} {:<8}",
+                    check.index, check.expected, check.actual, status);
+            }
+
+            println!("\nMatched: {}/{}", report.matched(), report.checks.len());
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let checks: Vec<_> = report.checks.iter().map(|check| {
+                config.address_case.scope(|| serde_json::json!({
+                    "index": check.index,
+                    "expected": check.expected,
+                    "derived": check.actual,
+                    "match": check.status == CheckStatus::Match
+                }))
+            }).collect();
+
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": report.is_clean(),
+                "matched": report.matched(),
+                "total": report.checks.len(),
+                "mismatched_indexes": report.mismatched_indexes(),
+                "checks": checks
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    if !report.is_clean() {
+        let indexes = report.mismatched_indexes();
+        return Err(WalletError::Validation(ValidationError::IntegrityCheckFailed {
+            data_type: "derived_addresses".to_string(),
+            details: format!(
+                "{} of {} addresses differ at indexes {:?}",
+                indexes.len(),
+                report.checks.len(),
+                indexes
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Execute chain registry commands
+async fn execute_network(
+    args: NetworkArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    match args.command {
+        NetworkCommands::List => {
+            let registry = ChainRegistry::load(&config.wallet_dir).await?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n⛓️  Chain registry ({})", registry.source());
+                    println!("{:<12} {:<32} {:<12} {:<8}",
+                        "CHAIN ID", "NAME", "SHORT NAME", "SYMBOL");
+                    println!("{}", "─".repeat(68));
+
+                    for chain in registry.chains() {
+                        println!("{:<12} {:<32} {:<12} {:<8}",
+                            chain.chain_id,
+                            chain.name,
+                            chain.short_name,
+                            chain.symbol().unwrap_or("-")
+                        );
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "source": registry.source().to_string(),
+                        "count": registry.chains().len(),
+                        "chains": registry.chains()
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        NetworkCommands::Show { chain } => {
+            let registry = ChainRegistry::load(&config.wallet_dir).await?;
+            let info = registry.find(&chain).ok_or_else(|| {
+                WalletError::UserInput(UserInputError::InvalidNetwork {
+                    network: chain.clone(),
+                    supported: registry.chains().iter().map(|c| c.short_name.clone()).collect(),
+                })
+            })?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n⛓️  {}", info.name);
+                    println!("Chain ID:   {}", info.chain_id);
+                    println!("Short name: {}", info.short_name);
+                    println!("Currency:   {}", info.symbol().unwrap_or("-"));
+                    if let Some(slip44) = info.slip44 {
+                        println!("SLIP-44:    {}", slip44);
+                    }
+                    if let Some(explorer) = info.explorer_url() {
+                        println!("Explorer:   {}", explorer);
+                    }
+                    if let Some(rpc) = config.rpc_endpoint(&info.short_name) {
+                        println!("RPC:        {} (configured)", rpc);
+                    }
+                    for rpc in &info.rpc {
+                        println!("RPC:        {}", rpc);
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    print_json(info, json, config.address_case)?;
+                }
+            }
+        }
+        NetworkCommands::UpdateRegistry { url } => {
+            info!("Fetching chain registry from: {}", url);
+            let registry = ChainRegistry::update(&config.wallet_dir, &url).await?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🌐 Chain registry updated!");
+                    println!("Chains:   {}", registry.chains().len());
+                    println!("Saved to: {}", registry.source());
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "count": registry.chains().len(),
+                        "source": registry.source().to_string()
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute storage health checks
+async fn execute_doctor(
+    args: DoctorArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let wallet_dir = args.path.unwrap_or_else(|| config.wallet_dir.clone());
+
+    if !wallet_dir.exists() {
+        return Err(WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
+            path: wallet_dir.display().to_string(),
+            details: "Wallet directory does not exist".to_string(),
+        }));
+    }
+
+    let mut findings = permissions::audit_directory(&wallet_dir)?;
+    let mut fixed = Vec::new();
+
+    if args.fix {
+        for finding in findings.iter_mut().filter(|f| !f.status.is_secure()) {
+            permissions::restrict_to_owner(&finding.path)?;
+            finding.status = permissions::check(&finding.path)?;
+            fixed.push(finding.path.clone());
+        }
+    }
+
+    let insecure = findings.iter().filter(|f| !f.status.is_secure()).count();
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🩺 Wallet directory: {}", wallet_dir.display());
+            println!("{:<8} {:<40} {:<40}", "STATUS", "PATH", "DETAILS");
+            println!("{}", "─".repeat(90));
+
+            for finding in &findings {
+                let status = match finding.status {
+                    PermissionStatus::Secure => "OK",
+                    PermissionStatus::Insecure { .. } => "WARN",
+                    PermissionStatus::Unsupported => "SKIP",
+                };
+                let name = if finding.path == wallet_dir {
+                    ".".to_string()
+                } else {
+                    finding.path.file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown")
+                        .to_string()
+                };
+                let details = if fixed.contains(&finding.path) {
+                    format!("fixed: {}", finding.status)
+                } else {
+                    finding.status.to_string()
+                };
+                println!("{:<8} {:<40} {:<40}", status, name, details);
+            }
+
+            if insecure > 0 {
+                println!("\n⚠️  {} path(s) are accessible by other users.", insecure);
+                println!("   Run `wallet doctor --fix` to restrict them to your account.");
+            } else {
+                println!("\n✅ All wallet files are restricted to their owner.");
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let checks: Vec<_> = findings.iter().map(|finding| {
+                config.address_case.scope(|| serde_json::json!({
+                    "path": finding.path.display().to_string(),
+                    "secure": finding.status.is_secure(),
+                    "details": finding.status.to_string(),
+                    "fixed": fixed.contains(&finding.path)
+                }))
+            }).collect();
+
+            let output = config.address_case.scope(|| serde_json::json!({
+                "directory": wallet_dir.display().to_string(),
+                "insecure": insecure,
+                "checks": checks
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute wallet directory backup
+async fn execute_backup(
+    args: BackupArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let wallet_dir = args.path.unwrap_or_else(|| config.wallet_dir.clone());
+
+    let password = prompt_password("Enter password to encrypt backup: ")?;
+    CryptoService::validate_password(&password)?;
+    let confirm = prompt_password("Confirm password: ")?;
+
+    if password != confirm {
+        return Err(WalletError::UserInput(
+            UserInputError::PasswordMismatch
+        ));
+    }
+
+    let manifest = BackupService::backup(&wallet_dir, &args.out, &password, config.is_low_resource())?;
+    audit(
+        config,
+        AuditAction::Export,
+        "backup",
+        None,
+        None,
+        Some(format!("{} files to {}", manifest.files.len(), args.out.display())),
+    )?;
+    print_backup_manifest("💾 Backup written to", &args.out, &manifest, config, output)
+}
+
+/// Execute backup restore
+async fn execute_restore(
+    args: RestoreArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let wallet_dir = args.path.unwrap_or_else(|| config.wallet_dir.clone());
+
+    let password = prompt_password("Enter backup password: ")?;
+    let manifest = BackupService::restore(&args.archive, &wallet_dir, &password)?;
+    audit(
+        config,
+        AuditAction::Import,
+        "restore",
+        None,
+        None,
+        Some(format!("{} files from {} into {}", manifest.files.len(), args.archive.display(), wallet_dir.display())),
+    )?;
+    print_backup_manifest("📦 Restored into", &wallet_dir, &manifest, config, output)
+}
+
+/// Execute the mnemonic backup quiz
+async fn execute_verify_backup(
+    args: VerifyBackupArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.filename, config);
+
+    let password = keystore_password(&file_path, config)?;
+    let wallet = manager.load_wallet(&file_path, &password).await?;
+    if !wallet.has_mnemonic() {
+        return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "wallet".to_string(),
+            value: "private key only".to_string(),
+            expected: "HD wallet with mnemonic".to_string(),
+        }));
+    }
+
+    let mnemonic = SecureMnemonic::new(wallet.mnemonic().to_string());
+    let positions = MnemonicService::quiz_positions(mnemonic.word_count(), args.words)?;
+
+    // Answers are read hidden and the stored words are never shown, right or wrong
+    let mut mismatched = Vec::new();
+    for &position in &positions {
+        let answer = zeroize::Zeroizing::new(prompt_password(format!("Enter word #{}: ", position + 1))?);
+        if !MnemonicService::quiz_answer_matches(&mnemonic, position, &answer) {
+            mismatched.push(position + 1);
+        }
+    }
+
+    match output {
+        OutputFormat::Table => {
+            if mismatched.is_empty() {
+                println!("\n✅ Backup verified: all {} word(s) match", positions.len());
+            } else {
+                let words: Vec<String> = mismatched.iter().map(|word| format!("#{}", word)).collect();
+                println!("\n❌ Backup does not match at word {}", words.join(", "));
+                println!("   Check those words against your written backup before relying on it.");
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "verified": mismatched.is_empty(),
+                "checked": positions.iter().map(|position| position + 1).collect::<Vec<_>>(),
+                "mismatched": mismatched
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    if !mismatched.is_empty() {
+        return Err(WalletError::Validation(ValidationError::IntegrityCheckFailed {
+            data_type: "mnemonic backup".to_string(),
+            details: format!("{} of {} word(s) do not match", mismatched.len(), positions.len()),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Execute checksum manifest verification
+fn execute_verify_manifest(
+    args: VerifyManifestArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    use sha2::Digest;
+    use web3wallet_cli::services::manifest::ManifestStatus;
+
+    let dir = match args.path {
+        Some(path) if path.is_file() => path.parent().map(std::path::Path::to_path_buf).unwrap_or_default(),
+        Some(path) => path,
+        None => config.wallet_dir.clone(),
+    };
+    let manifest = ChecksumManifest::load(&dir)?;
+    let manifest_path = manifest.path();
+    if !manifest_path.exists() {
+        return Err(WalletError::UserInput(UserInputError::MissingParameter {
+            parameter: "MANIFEST.json".to_string(),
+            hint: format!("{} has no manifest yet; saving a keystore or running `wallet backup` writes one", dir.display()),
+        }));
+    }
+    let checks = manifest.verify()?;
+    let failures = checks.iter().filter(|check| check.is_failure()).count();
+    let manifest_hash = hex::encode(sha2::Sha256::digest(std::fs::read(&manifest_path)?));
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n📋 Manifest: {}", manifest_path.display());
+            println!("{:<40} {:<44} {:<10} STATUS", "FILE", "ADDRESS", "SIZE");
+            println!("{}", "─".repeat(104));
+            for check in &checks {
+                let entry = check.expected.as_ref().or(check.actual.as_ref());
+                let address = entry.and_then(|entry| entry.address).map_or("-".to_string(), |address| address.to_string());
+                let size = entry.map_or("-".to_string(), |entry| web3wallet_cli::utils::format_bytes(entry.size));
+                let status = match check.status {
+                    ManifestStatus::Intact => "✅ intact",
+                    ManifestStatus::Modified => "❌ MODIFIED",
+                    ManifestStatus::Missing => "❌ MISSING",
+                    ManifestStatus::Unlisted => "⚠️  not in manifest",
+                };
+                println!("{:<40} {:<44} {:<10} {}", check.name, address, size, status);
+            }
+            println!("\nManifest SHA-256: {}", manifest_hash);
+            println!("Compare it with the copy kept alongside your offline backup.");
+            if failures > 0 {
+                println!("\n❌ {} of {} recorded file(s) are damaged or gone; restore them from a backup", failures, manifest.files.len());
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "verified": failures == 0,
+                "manifest": manifest_path.display().to_string(),
+                "manifest_sha256": manifest_hash,
+                "updated_at": manifest.updated_at,
+                "files": checks
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    if failures > 0 {
+        return Err(WalletError::Validation(ValidationError::IntegrityCheckFailed {
+            data_type: "manifest".to_string(),
+            details: format!("{} of {} file(s) do not match their recorded checksums", failures, manifest.files.len()),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Print the contents of a backup archive
+fn print_backup_manifest(
+    title: &str,
+    location: &std::path::Path,
+    manifest: &BackupManifest,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    match output {
+        OutputFormat::Table => {
+            println!("\n{}: {}", title, location.display());
+            println!("{:<40} {:<12}", "FILE", "SIZE");
+            println!("{}", "─".repeat(52));
+
+            for file in &manifest.files {
+                println!("{:<40} {:<12}", file.name, web3wallet_cli::utils::format_bytes(file.size));
+            }
+
+            println!("\n{} file(s), created {} by v{}",
+                manifest.files.len(),
+                manifest.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                manifest.version);
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let files: Vec<_> = manifest.files.iter().map(|file| {
+                config.address_case.scope(|| serde_json::json!({
+                    "name": file.name,
+                    "size": file.size
+                }))
+            }).collect();
+
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "location": location.display().to_string(),
+                "version": manifest.version,
+                "created_at": manifest.created_at,
+                "files": files
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute signing usage commands
+async fn execute_usage(
+    args: UsageArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let mut tracker = UsageTracker::load(&config.wallet_dir)?;
+
+    match args.command {
+        UsageCommands::Show => match output {
+            OutputFormat::Table => {
+                println!("\n📊 Signing usage:");
+                println!("{:<30} {:<8} {:<10} {:<8} {:<8} {:<8}",
+                    "KEYSTORE", "TODAY", "TOTAL", "SOFT", "HARD", "STATUS");
+                println!("{}", "─".repeat(76));
+
+                let today = chrono::Utc::now().date_naive();
+                for (keystore, usage) in tracker.entries() {
+                    let limits = config.quotas.limits_for(keystore);
+                    let count = if usage.day == today { usage.today } else { 0 };
+                    let limit = |l: Option<u32>| l.map(|l| l.to_string()).unwrap_or_else(|| "-".to_string());
+                    println!("{:<30} {:<8} {:<10} {:<8} {:<8} {:<8}",
+                        keystore,
+                        count,
+                        usage.total,
+                        limit(limits.soft_daily),
+                        limit(limits.hard_daily),
+                        if usage.locked { "LOCKED" } else { "OK" });
+                }
+            }
+            json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                let keystores: Vec<_> = tracker.entries().map(|(keystore, usage)| {
+                    let limits = config.quotas.limits_for(keystore);
+                    config.address_case.scope(|| serde_json::json!({
+                        "keystore": keystore,
+                        "day": usage.day,
+                        "today": usage.today,
+                        "total": usage.total,
+                        "last_used": usage.last_used,
+                        "locked": usage.locked,
+                        "soft_daily": limits.soft_daily,
+                        "hard_daily": limits.hard_daily
+                    }))
+                }).collect();
+
+                let output = config.address_case.scope(|| serde_json::json!({
+                    "keystores": keystores
+                }));
+                print_json(&output, json, config.address_case)?;
+            }
+        },
+        UsageCommands::Reset { keystore } => {
+            if !tracker.reset(&keystore) {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "keystore".to_string(),
+                    value: keystore,
+                    expected: "a keystore with recorded usage".to_string(),
+                }));
+            }
+            tracker.save()?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🔄 Signing quota reset for: {}", keystore);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "keystore": keystore
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute audit log commands
+async fn execute_audit(
+    args: AuditArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let log = AuditLog::new(&config.wallet_dir);
+
+    match args.command {
+        AuditCommands::Show { last, action } => {
+            let mut entries = log.entries()?;
+            if let Some(action) = action {
+                entries.retain(|entry| entry.action == action);
+            }
+            if let Some(last) = last {
+                entries.drain(..entries.len().saturating_sub(last));
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n📜 Audit log: {}", log.path().display());
+                    println!("{:<6} {:<20} {:<8} {:<18} {:<24} {:<44} DETAILS",
+                        "SEQ", "TIME", "ACTION", "OPERATION", "FILE", "ADDRESS");
+                    println!("{}", "─".repeat(130));
+
+                    for entry in &entries {
+                        println!("{:<6} {:<20} {:<8} {:<18} {:<24} {:<44} {}",
+                            entry.seq,
+                            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                            entry.action.name(),
+                            entry.operation,
+                            entry.file.as_deref().unwrap_or("-"),
+                            entry.address.as_deref().unwrap_or("-"),
+                            entry.details.as_deref().unwrap_or(""));
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "path": log.path(),
+                        "entries": entries
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        AuditCommands::Verify => {
+            let verification = log.verify()?;
+
+            match output {
+                OutputFormat::Table => {
+                    if let Some((line, problem)) = &verification.broken_at {
+                        println!("\n❌ Audit log broken at line {}: {}", line, problem);
+                        println!("   Entries before it: {}", verification.entries);
+                    } else {
+                        println!("\n✅ Audit log intact: {} entries", verification.entries);
+                    }
+                    println!("   Head hash: {}", verification.head);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "path": log.path(),
+                        "intact": verification.is_intact(),
+                        "entries": verification.entries,
+                        "head": verification.head,
+                        "broken_at": verification.broken_at.as_ref().map(|(line, _)| line),
+                        "problem": verification.broken_at.as_ref().map(|(_, problem)| problem)
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+
+            if let Some((line, problem)) = verification.broken_at {
+                return Err(WalletError::Validation(ValidationError::IntegrityCheckFailed {
+                    data_type: "audit log".to_string(),
+                    details: format!("line {}: {}", line, problem),
+                }));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute session agent commands
+async fn execute_agent(
+    args: AgentArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let client = AgentClient::new(&config.wallet_dir);
+    let not_running = || {
+        warn!("No agent is running; start one with `wallet agent start`");
+        WalletError::Network(web3wallet_cli::errors::NetworkError::ConnectivityFailure {
+            endpoint: client.socket().display().to_string(),
+            details: "no agent is running".to_string(),
+        })
+    };
+    let locking = matches!(args.command, AgentCommands::Lock);
+    let listing = matches!(args.command, AgentCommands::List | AgentCommands::Add { .. });
+
+    let entries = match args.command {
+        AgentCommands::Start { ttl, foreground: true } => {
+            let ttl = std::time::Duration::from_secs(ttl.unwrap_or(config.agent.ttl_secs));
+            return AgentServer::new(&config.wallet_dir, ttl).serve().await;
+        }
+        AgentCommands::Start { .. } => {
+            if client.is_running().await {
+                info!("An agent is already running on {}", client.socket().display());
+            } else {
+                // Same command line in the foreground, detached from this terminal
+                let mut command = std::process::Command::new(std::env::current_exe()?);
+                command
+                    .args(std::env::args_os().skip(1))
+                    .arg("--foreground")
+                    .stdin(std::process::Stdio::null())
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null());
+                #[cfg(unix)]
+                std::os::unix::process::CommandExt::process_group(&mut command, 0);
+                let child = command.spawn()?;
+
+                let started = std::time::Instant::now();
+                while !client.is_running().await {
+                    if started.elapsed() > std::time::Duration::from_secs(5) {
+                        return Err(not_running());
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+                info!("Agent started (pid {}) on {}", child.id(), client.socket().display());
+            }
+            Vec::new()
+        }
+        AgentCommands::Add { filename, ttl } => {
+            if !client.is_running().await {
+                return Err(not_running());
+            }
+            let file_path = wallet_file_path(&filename, config);
+            let password = keystore_password(&file_path, config)?;
+            let wallet = WalletManager::new(config.clone()).load_wallet(&file_path, &password).await?;
+            vec![client.add(&file_path, &wallet, ttl.map(std::time::Duration::from_secs)).await?]
+        }
+        AgentCommands::List => client.list().await.map_err(|_| not_running())?,
+        AgentCommands::Lock => client.lock().await.map_err(|_| not_running())?,
+        AgentCommands::Stop => {
+            client.lock().await.map_err(|_| not_running())?;
+            client.stop().await?;
+            info!("Agent stopped");
+            Vec::new()
+        }
+    };
+
+    match output {
+        OutputFormat::Table if locking => println!("🔒 Locked {} wallet(s)", entries.len()),
+        OutputFormat::Table if !listing => {}
+        OutputFormat::Table => {
+            if entries.is_empty() {
+                println!("No wallets unlocked in the agent");
+            }
+            for entry in &entries {
+                println!("🔓 {}  {}  until {}",
+                    entry.address,
+                    entry.path.display(),
+                    entry.expires_at.format("%Y-%m-%d %H:%M:%S UTC"));
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "socket": client.socket().display().to_string(),
+                "wallets": entries
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute keychain commands
+async fn execute_keychain(
+    args: KeychainArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    match args.command {
+        KeychainCommands::Forget { filename } => {
+            let file_path = wallet_file_path(&filename, config);
+
+            let removed = keychain::delete_password(&file_path)?;
+            if removed {
+                audit(config, AuditAction::Delete, "keychain forget", Some(&file_path), None, Some("stored password".to_string()))?;
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    if removed {
+                        println!("\n🔑 Removed stored password for: {}", file_path.display());
+                    } else {
+                        println!("\nNo stored password for: {}", file_path.display());
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "file": file_path.display().to_string(),
+                        "removed": removed
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute TOTP commands
+async fn execute_totp(
+    args: TotpArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let mut store = TotpStore::load(&config.wallet_dir)?;
+
+    match args.command {
+        TotpCommands::Enroll { filename } => {
+            let file_path = wallet_file_path(&filename, config);
+            let keystore = keystore_name(&file_path);
+
+            // Only the keystore owner may enroll it
+            let password = keystore_password(&file_path, config)?;
+            WalletManager::new(config.clone()).load_wallet(&file_path, &password).await?;
+
+            let secret = TotpSecret::generate();
+            let now = chrono::Utc::now();
+            store.enroll(&keystore, &secret, &password, now, config.is_low_resource())?;
+
+            eprintln!("\n📱 Add this key to your authenticator app:");
+            eprintln!("Secret: {}", secret.to_base32().as_str());
+            eprintln!("URI:    {}", secret.provisioning_uri(&keystore, "Web3 Wallet").as_str());
+            let code = prompt_one_time_code(&keystore)?;
+            store.verify(&keystore, &code, &password, chrono::Utc::now())?;
+            store.save()?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n✅ TOTP enrolled for: {}", keystore);
+                    if config.totp.after_daily.is_none() {
+                        println!("Set [totp] after_daily in the config to require codes when signing.");
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "keystore": keystore,
+                        "enrolled_at": now
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        TotpCommands::Remove { filename } => {
+            let file_path = wallet_file_path(&filename, config);
+            let keystore = keystore_name(&file_path);
+            // The secret is sealed under the keystore password
+            let password = keystore_password(&file_path, config)?;
+            let code = prompt_one_time_code(&keystore)?;
+            store.verify(&keystore, &code, &password, chrono::Utc::now())?;
+            store.remove(&keystore);
+            store.save()?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🗑️  TOTP enrollment removed for: {}", keystore);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "keystore": keystore
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        TotpCommands::Status => match output {
+            OutputFormat::Table => {
+                println!("\n📱 TOTP enrollments:");
+                match config.totp.after_daily {
+                    Some(limit) => println!("Codes required after {} signature(s) per day", limit),
+                    None => println!("Codes not required (no [totp] after_daily configured)"),
+                }
+                println!("{}", "─".repeat(60));
+                for (keystore, enrolled_at) in store.entries() {
+                    println!("{:<30} enrolled {}", keystore, enrolled_at.format("%Y-%m-%d %H:%M UTC"));
+                }
+            }
+            json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                let keystores: Vec<_> = store.entries().map(|(keystore, enrolled_at)| {
+                    config.address_case.scope(|| serde_json::json!({
+                        "keystore": keystore,
+                        "enrolled_at": enrolled_at
+                    }))
+                }).collect();
+
+                let output = config.address_case.scope(|| serde_json::json!({
+                    "after_daily": config.totp.after_daily,
+                    "keystores": keystores
+                }));
+                print_json(&output, json, config.address_case)?;
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// Check a TPM is present and explain what is lost along with it
+fn warn_hardware_binding() -> WalletResult<()> {
+    if !tpm::is_available() {
+        warn!("--bind-hardware needs a TPM 2.0, and none was found on this machine");
+        return Err(AuthenticationError::SecondFactorFailed {
+            keystore: String::new(),
+            details: "no TPM 2.0 found on this machine".to_string(),
+        }
+        .into());
+    }
+    eprintln!("\n⚠️  This keystore will only open on this machine, through its TPM.");
+    eprintln!("   Clearing the TPM, replacing the mainboard or moving the disk to another");
+    eprintln!("   machine makes it undecryptable for good, even with the password.");
+    eprintln!("   Keep the mnemonic (or private key) backed up offline to recover the wallet.");
+    Ok(())
+}
+
+/// Execute YubiKey commands
+async fn execute_yubikey(
+    args: YubikeyArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let (filename, slot) = match args.command {
+        YubikeyCommands::Enroll { filename, slot } => (filename, Some(slot)),
+        YubikeyCommands::Remove { filename } => (filename, None),
+    };
+    let file_path = wallet_file_path(&filename, config);
+
+    let password = keystore_password(&file_path, config)?;
+    let wallet = manager.load_wallet(&file_path, &password).await?;
+    // Enrolling asks the token once more, which also proves the slot answers
+    let binding = slot.map(|slot| HardwareBinding::YubiKey { slot });
+    manager.set_hardware_binding(&wallet, &file_path, &password, binding).await?;
+    if slot.is_none() {
+        audit(config, AuditAction::Delete, "yubikey remove", Some(&file_path), Some(wallet.address()), Some("YubiKey factor".to_string()))?;
+    }
+
+    match output {
+        OutputFormat::Table => match slot {
+            Some(slot) => {
+                println!("\n🔑 {} now needs the password and the YubiKey in slot {}", file_path.display(), slot);
+                println!("Keep a second YubiKey programmed with the same secret, or the keystore is lost with the token.");
+            }
+            None => println!("\n🔓 {} now opens with the password alone", file_path.display()),
+        },
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "file": file_path.display().to_string(),
+                "yubikey_slot": slot
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a one-time code from the terminal
+fn prompt_one_time_code(keystore: &str) -> WalletResult<String> {
+    eprint!("Enter one-time code for {}: ", keystore);
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+/// Execute config commands
+async fn execute_config(
+    args: ConfigArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    match args.command {
+        ConfigCommands::Validate => {
+            let findings = ConfigCheckService::check(config);
+            let errors = findings.iter().filter(|f| f.severity == Severity::Error).count();
+            let warnings = findings.iter().filter(|f| f.severity == Severity::Warning).count();
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🛡️  Configuration checklist");
+                    println!("{:<8} {:<24} {:<60}", "STATUS", "SETTING", "DETAILS");
+                    println!("{}", "─".repeat(92));
+
+                    for finding in &findings {
+                        let status = match finding.severity {
+                            Severity::Ok => "OK",
+                            Severity::Warning => "WARN",
+                            Severity::Error => "FAIL",
+                        };
+                        println!("{:<8} {:<24} {:<60}", status, finding.setting, finding.message);
+                    }
+
+                    if errors == 0 && warnings == 0 {
+                        println!("\n✅ No dangerous settings found.");
+                    } else if errors == 0 {
+                        println!("\n⚠️  {} warning(s); review them before storing real funds.", warnings);
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "valid": errors == 0,
+                        "errors": errors,
+                        "warnings": warnings,
+                        "checks": findings
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+
+            if errors > 0 {
+                return Err(WalletError::Validation(ValidationError::IntegrityCheckFailed {
+                    data_type: "config".to_string(),
+                    details: format!("{} setting(s) are unsafe", errors),
+                }));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute the Argon2id benchmark
+async fn execute_kdf_bench(
+    args: KdfBenchArgs,
+    config: &WalletConfig,
+    config_path: Option<&std::path::Path>,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    if args.target_ms == 0 {
+        return Err(WalletError::UserInput(UserInputError::ValueOutOfRange {
+            parameter: "target-ms".to_string(),
+            value: "0".to_string(),
+            range: "at least 1".to_string(),
+        }));
+    }
+
+    info!("Timing Argon2id; this takes a few seconds...");
+    let target = std::time::Duration::from_millis(args.target_ms);
+    let max_memory = args.max_memory_mib.map(|mib| mib.saturating_mul(1024));
+    let report = tokio::task::spawn_blocking(move || KdfBenchService::benchmark(target, max_memory))
+        .await
+        .map_err(|e| WalletError::Cryptographic(web3wallet_cli::errors::CryptographicError::KdfFailed {
+            details: format!("Benchmark task failed: {}", e),
+        }))??;
+
+    let recommended = report.recommended.settings;
+    let config_path = config_path.map(std::path::Path::to_path_buf).unwrap_or_else(|| {
+        web3wallet_cli::config::default_wallet_dir().join(web3wallet_cli::config::CONFIG_FILE_NAME)
+    });
+    if !args.dry_run {
+        KdfBenchService::save_to_config(&config_path, recommended)?;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n⏱️  Argon2id timings (one pass):");
+            println!("{:<12} {:<10}", "MEMORY", "TIME");
+            println!("{}", "─".repeat(22));
+            for sample in &report.samples {
+                println!("{:<12} {:<10}",
+                    format!("{} MiB", sample.settings.memory / 1024),
+                    format!("{} ms", sample.elapsed.as_millis()));
+            }
+
+            println!("\n✅ Recommended: m={} MiB, t={}, p={} ({} ms, target {} ms)",
+                recommended.memory / 1024,
+                recommended.iterations,
+                recommended.parallelism,
+                report.recommended.elapsed.as_millis(),
+                args.target_ms);
+            if !report.within_target {
+                println!("⚠️  This machine cannot reach the mainnet minimum within the target; using the minimum anyway.");
+            }
+            if args.dry_run {
+                println!("Dry run: {} was not changed.", config_path.display());
+            } else {
+                println!("💾 Saved as the custom KDF profile in: {}", config_path.display());
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let samples: Vec<_> = report.samples.iter().map(|sample| {
+                config.address_case.scope(|| serde_json::json!({
+                    "memory_kib": sample.settings.memory,
+                    "iterations": sample.settings.iterations,
+                    "elapsed_ms": sample.elapsed.as_millis() as u64
+                }))
+            }).collect();
+
+            let output = config.address_case.scope(|| serde_json::json!({
+                "target_ms": args.target_ms,
+                "samples": samples,
+                "recommended": {
+                    "memory_kib": recommended.memory,
+                    "iterations": recommended.iterations,
+                    "parallelism": recommended.parallelism,
+                    "elapsed_ms": report.recommended.elapsed.as_millis() as u64
+                },
+                "within_target": report.within_target,
+                "config_file": config_path.display().to_string(),
+                "saved": !args.dry_run
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute breached password filter commands
+async fn execute_breach_filter(
+    args: BreachFilterArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    match args.command {
+        BreachFilterCommands::Build { list, out, false_positive_rate } => {
+            if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+                return Err(WalletError::UserInput(UserInputError::ValueOutOfRange {
+                    parameter: "false-positive-rate".to_string(),
+                    value: false_positive_rate.to_string(),
+                    range: "between 0 and 1, exclusive".to_string(),
+                }));
+            }
+
+            info!("Building breached password filter from {}...", list.display());
+            let path = out.unwrap_or_else(|| BreachedPasswords::installed_path(&config.wallet_dir));
+            let report = tokio::task::spawn_blocking(move || BreachedPasswords::build(&list, &path, false_positive_rate))
+                .await
+                .map_err(|e| WalletError::Io(format!("Filter build task failed: {}", e)))??;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n✅ Added {} passwords to the breach filter ({} bytes)", report.entries, report.file_size);
+                    println!("💾 Saved to: {}", report.path.display());
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "path": report.path.display().to_string(),
+                        "entries": report.entries,
+                        "file_size": report.file_size,
+                        "false_positive_rate": false_positive_rate
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        BreachFilterCommands::Check => {
+            let breached = BreachedPasswords::load(&config.wallet_dir)?;
+            let password = wallet_password(config)?;
+            let found = breached.contains(&password);
+
+            match output {
+                OutputFormat::Table => {
+                    if found {
+                        println!("⚠️  This password appears in a list of leaked passwords; do not use it for a wallet.");
+                    } else {
+                        println!("✅ Not found in the bundled list{}", if breached.has_installed() { " or the installed filter" } else { "" });
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "breached": found,
+                        "installed_filter": breached.has_installed()
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute account commands
+async fn execute_accounts(
+    args: AccountsArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+
+    let filename = match &args.command {
+        AccountsCommands::List { filename }
+        | AccountsCommands::Add { filename, .. }
+        | AccountsCommands::Remove { filename, .. } => filename.clone(),
+    };
+    let file_path = wallet_file_path(&filename, config);
+
+    let password = keystore_password(&file_path, config)?;
+    let mut wallet = manager.load_wallet(&file_path, &password).await?;
+    unlock_passphrase(&mut wallet, &PassphraseArgs::default())?;
+
+    match args.command {
+        AccountsCommands::List { .. } => {}
+        AccountsCommands::Add { index, label, .. } => {
+            // Index 0 is the wallet's primary address
+            let index = index.unwrap_or_else(|| {
+                wallet.accounts().iter().map(|a| a.index() + 1).max().unwrap_or(1)
+            });
+            let account = wallet.add_account(index, label)?;
+            info!("Added account {} ({})", account.index(), account.address());
+            manager.update_wallet(&wallet, &file_path, &password).await?;
+        }
+        AccountsCommands::Remove { index, .. } => {
+            let address = wallet
+                .accounts()
+                .iter()
+                .find(|account| account.index() == index)
+                .map(|account| *account.address());
+            if !wallet.remove_account(index) {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "index".to_string(),
+                    value: index.to_string(),
+                    expected: "an account index stored in this wallet".to_string(),
+                }));
+            }
+            info!("Removed account {}", index);
+            manager.update_wallet(&wallet, &file_path, &password).await?;
+            audit(
+                config,
+                AuditAction::Delete,
+                "accounts remove",
+                Some(&file_path),
+                address.as_ref(),
+                Some(format!("account {}", index)),
+            )?;
+        }
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n👥 Accounts in {}", file_path.display());
+            println!("{:<6} {:<44} {:<24} {:<20}", "INDEX", "ADDRESS", "PATH", "LABEL");
+            println!("{}", "─".repeat(94));
+            println!("{:<6} {:<44} {:<24} {:<20}", 0, wallet.address(), wallet.derivation_path().child(0)?.to_string(), "(primary)");
+            for account in wallet.accounts() {
+                println!(
+                    "{:<6} {:<44} {:<24} {:<20}",
+                    account.index(),
+                    account.address(),
+                    account.derivation_path(),
+                    account.label().unwrap_or("-")
+                );
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "file": file_path.display().to_string(),
+                "address": wallet.address(),
+                "accounts": wallet.accounts()
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute tag commands
+async fn execute_tag(
+    args: TagArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let filename = match &args.command {
+        TagCommands::Add { filename, .. }
+        | TagCommands::Remove { filename, .. }
+        | TagCommands::Note { filename, .. } => filename.clone(),
+    };
+    let file_path = wallet_file_path(&filename, config);
+
+    // Tags and notes are plaintext metadata; no password is needed
+    let mut keystore = CryptoService::load_keystore(&file_path).await?;
+
+    match args.command {
+        TagCommands::Add { tags, .. } => {
+            let added = keystore.metadata.add_tags(&tags)?;
+            info!("Added {} tag(s)", added);
+        }
+        TagCommands::Remove { tags, .. } => {
+            let removed = keystore.metadata.remove_tags(&tags);
+            info!("Removed {} tag(s)", removed);
+        }
+        TagCommands::Note { text, .. } => {
+            keystore.metadata.notes = text.filter(|t| !t.trim().is_empty());
+        }
+    }
+
+    CryptoService::replace_keystore(&keystore, &file_path).await?;
+    ChecksumManifest::record_file(&file_path)?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🏷️  {}", file_path.display());
+            println!("Tags:     {}", if keystore.metadata.tags.is_empty() {
+                "-".to_string()
+            } else {
+                keystore.metadata.tags.join(", ")
+            });
+            println!("Notes:    {}", keystore.metadata.notes.as_deref().unwrap_or("-"));
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "file": file_path.display().to_string(),
+                "tags": keystore.metadata.tags,
+                "notes": keystore.metadata.notes
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute address book commands
+fn execute_book(args: BookArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let path = config.wallet_dir.join(web3wallet_cli::config::address_book::FILE_NAME);
+    let mut book = AddressBook::load(&path)?;
+
+    let entries: Vec<Address> = match args.command {
+        BookCommands::Add { address, label, network } => {
+            let mut entry = Address::new(address, network, None, None)?;
+            entry.set_label(label);
+            book.add(entry.clone())?;
+            book.save(&path)?;
+            info!("Added {} to the address book", entry);
+            vec![entry]
+        }
+        BookCommands::Remove { address } => {
+            let removed: Vec<Address> = book.addresses()
+                .iter()
+                .filter(|entry| entry.equals(&address) || entry.label().is_some_and(|label| label.eq_ignore_ascii_case(&address)))
+                .cloned()
+                .collect();
+            if !book.remove(&address) {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "address".to_string(),
+                    value: address,
+                    expected: "an address or label in the address book".to_string(),
+                }));
+            }
+            book.save(&path)?;
+            info!("Removed {} address(es) from the address book", removed.len());
+            removed
+        }
+        BookCommands::List { network } => book.addresses()
+            .iter()
+            .filter(|entry| network.as_ref().map_or(true, |network| entry.network() == network))
+            .cloned()
+            .collect(),
+        BookCommands::Find { query } => book.search(&query).into_iter().cloned().collect(),
+    };
+
+    match output {
+        OutputFormat::Table => {
+            if entries.is_empty() {
+                println!("No addresses");
+            }
+            for entry in &entries {
+                println!(
+                    "{:<width$} {:<43} {}",
+                    entry.label().unwrap_or("-"),
+                    entry.address(),
+                    entry.network(),
+                    width = web3wallet_cli::config::output::ALIAS_COLUMN_WIDTH
+                );
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let entries: Vec<_> = entries.iter()
+                .map(|entry| config.address_case.scope(|| serde_json::json!({
+                    "label": entry.label(),
+                    "address": entry.address(),
+                    "network": entry.network()
+                })))
+                .collect();
+            print_json(&config.address_case.scope(|| serde_json::json!({ "addresses": entries })), json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Address named by a command-line argument: hex, or an address book label
+fn resolve_address(name: &str, config: &WalletConfig) -> WalletResult<EthereumAddress> {
+    if AddressBook::is_hex_address(name) {
+        return name.parse();
+    }
+    AddressBook::load(&config.wallet_dir.join(web3wallet_cli::config::address_book::FILE_NAME))?.resolve(name)
+}
+
+/// Execute validator keystore commands
+async fn execute_validator(args: ValidatorArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    match args.command {
+        ValidatorCommands::Inspect { file } => {
+            let keystore = ValidatorKeystoreService::load(&file).await?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🪪 Validator keystore: {}", file.display());
+                    println!("Pubkey:   0x{}", keystore.pubkey);
+                    println!("Path:     {}", if keystore.path.is_empty() { "-" } else { &keystore.path });
+                    println!("KDF:      {}", keystore.kdf_name());
+                    println!("UUID:     {}", keystore.uuid);
+                    if let Some(description) = &keystore.description {
+                        println!("Note:     {}", description);
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "file": file.display().to_string(),
+                        "pubkey": keystore.pubkey,
+                        "path": keystore.path,
+                        "kdf": keystore.kdf_name(),
+                        "uuid": keystore.uuid,
+                        "description": keystore.description
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        ValidatorCommands::Verify { file } => {
+            let keystore = ValidatorKeystoreService::load(&file).await?;
+            let password = zeroize::Zeroizing::new(prompt_password("Enter keystore password: ")?);
+            ValidatorKeystoreService::decrypt(&keystore, &password)?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n✅ Password is correct for 0x{}", keystore.pubkey);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "file": file.display().to_string(),
+                        "pubkey": keystore.pubkey
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        ValidatorCommands::Reencrypt { file, out, kdf } => {
+            let keystore = ValidatorKeystoreService::load(&file).await?;
+            let password = zeroize::Zeroizing::new(prompt_password("Enter current keystore password: ")?);
+            let secret = ValidatorKeystoreService::decrypt(&keystore, &password)?;
+
+            let new_password = zeroize::Zeroizing::new(prompt_password("Enter new keystore password: ")?);
+            CryptoService::validate_password(&new_password)?;
+            let confirm = zeroize::Zeroizing::new(prompt_password("Confirm new password: ")?);
+            if *new_password != *confirm {
+                return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+            }
+
+            let mut reencrypted = ValidatorKeystoreService::encrypt(
+                &secret,
+                &keystore.pubkey,
+                &keystore.path,
+                &new_password,
+                kdf,
+            )?;
+            reencrypted.description = keystore.description.clone();
+            ValidatorKeystoreService::save(&reencrypted, &out).await?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🔐 Re-encrypted keystore written to: {}", out.display());
+                    println!("Pubkey:   0x{}", reencrypted.pubkey);
+                    println!("KDF:      {}", reencrypted.kdf_name());
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "file": out.display().to_string(),
+                        "pubkey": reencrypted.pubkey,
+                        "kdf": reencrypted.kdf_name(),
+                        "uuid": reencrypted.uuid
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        ValidatorCommands::Derive {
+            from_file, count, start_index, chain, withdrawal_address, out, kdf, passphrase,
+        } => {
+            StakingService::fork_version(&chain)?;
+            let manager = WalletManager::new(config.clone());
+            let file_path = wallet_file_path(&from_file, config);
+            let withdrawal_address = withdrawal_address.map(|name| resolve_address(&name, config)).transpose()?;
+            let end_index = start_index.checked_add(count).filter(|_| count > 0).ok_or_else(|| {
+                UserInputError::ValueOutOfRange {
+                    parameter: "count".to_string(),
+                    value: count.to_string(),
+                    range: format!("1-{}", u32::MAX - start_index),
+                }
+            })?;
+
+            let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &passphrase)?;
+            let password = new_wallet_password("Enter password for the validator keystores: ", config)?;
+            std::fs::create_dir_all(&out)?;
+
+            let timestamp = chrono::Utc::now().timestamp();
+            let mut deposits = Vec::new();
+            let mut keystores = Vec::new();
+            for index in start_index..end_index {
+                let key = StakingService::validator_key(&wallet, index)?;
+                let credentials = StakingService::withdrawal_credentials(&wallet, index, withdrawal_address.as_ref())?;
+                deposits.push(StakingService::deposit_data(&key, &credentials, &chain)?);
+
+                let keystore = ValidatorKeystoreService::encrypt(&key.secret, &hex::encode(key.pubkey), &key.path, &password, kdf)?;
+                let keystore_path = out.join(format!("keystore-{}-{}.json", key.path.replace('/', "_"), timestamp));
+                ValidatorKeystoreService::save(&keystore, &keystore_path).await?;
+                info!("Keystore written to: {}", keystore_path.display());
+                keystores.push(keystore_path);
+            }
+
+            let deposit_path = out.join(format!("deposit_data-{}.json", timestamp));
+            std::fs::write(&deposit_path, serde_json::to_string(&deposits)?)?;
+            audit(
+                config,
+                AuditAction::Export,
+                "validator derive",
+                Some(&file_path),
+                Some(wallet.address()),
+                Some(format!("validators {}..{} on {}", start_index, end_index, chain)),
+            )?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🪪 {} validator key(s) for {}", deposits.len(), chain);
+                    for deposit in &deposits {
+                        println!("   0x{}", deposit.pubkey);
+                    }
+                    println!("\nKeystores:    {}", out.display());
+                    println!("Deposit data: {}", deposit_path.display());
+                    println!("Upload the deposit data to the staking launchpad and import the keystores into your validator client.");
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "network": chain,
+                        "pubkeys": deposits.iter().map(|deposit| &deposit.pubkey).collect::<Vec<_>>(),
+                        "keystores": keystores,
+                        "deposit_data": deposit_path
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute wallet export
+async fn execute_export(
+    args: ExportArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let registry = ExportRegistry::default();
+    let format = registry.get(&args.format)?;
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.filename, config);
+    let password = keystore_password(&file_path, config)?;
+    let mut wallet = manager.load_wallet(&file_path, &password).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+
+    let export_password = if format.needs_password() {
+        let export_password = zeroize::Zeroizing::new(prompt_password("Enter export password: ")?);
+        CryptoService::validate_password(&export_password)?;
+        let confirm = zeroize::Zeroizing::new(prompt_password("Confirm export password: ")?);
+        if *export_password != *confirm {
+            return Err(WalletError::UserInput(UserInputError::PasswordMismatch));
+        }
+        Some(export_password)
+    } else {
+        None
+    };
+
+    let context = ExportContext {
+        index: args.index,
+        count: args.count,
+        password: export_password.as_deref().map(String::as_str),
+        low_resource: config.is_low_resource(),
+    };
+    let data = format.render(&wallet, &context)?;
+    let destination = args.out.as_ref().map_or("terminal".to_string(), |out| out.display().to_string());
+    audit(
+        config,
+        AuditAction::Export,
+        "export",
+        Some(&file_path),
+        Some(wallet.address()),
+        Some(format!("{} to {}", format.name(), destination)),
+    )?;
+
+    match (&args.out, format.contains_secrets()) {
+        (Some(out), secret) => {
+            web3wallet_cli::utils::write_atomic(out, &data, if secret { 0o600 } else { 0o644 })?;
+            match output {
+                OutputFormat::Table => {
+                    println!("\n📦 Exported {} ({}) to: {}", wallet.address(), format.name(), out.display());
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "address": wallet.address(),
+                        "format": format.name(),
+                        "file": out.display().to_string()
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        (None, true) => hold_in_workspace(&format.file_name(&wallet), &data, config)?,
+        (None, false) => {
+            let content = String::from_utf8_lossy(&data);
+            match output {
+                OutputFormat::Table => print!("{}", content),
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "address": wallet.address(),
+                        "format": format.name(),
+                        "content": content
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute paper wallet generation
+async fn execute_paper(
+    args: PaperArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    use web3wallet_cli::services::paper::PaperOptions;
+    use web3wallet_cli::services::PaperWalletService;
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.filename, config);
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+
+    let options = PaperOptions {
+        index: args.index,
+        include_mnemonic: args.include_mnemonic,
+    };
+    let pdf = PaperWalletService::render_pdf(&wallet, &options)?;
+    audit(
+        config,
+        AuditAction::Export,
+        "paper",
+        Some(&file_path),
+        Some(wallet.address()),
+        Some(format!(
+            "{} to {}",
+            if args.include_mnemonic { "sheet with mnemonic" } else { "public sheet" },
+            args.out.display()
+        )),
+    )?;
+    web3wallet_cli::utils::write_atomic(&args.out, &pdf, if args.include_mnemonic { 0o600 } else { 0o644 })?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🖨️  Backup sheet for {} written to: {}", wallet.address(), args.out.display());
+            if args.include_mnemonic {
+                println!("⚠️  It contains the mnemonic: print it offline, then shred the file");
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "address": wallet.address(),
+                "file": args.out.display().to_string(),
+                "includes_mnemonic": args.include_mnemonic
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute BIP85 child mnemonic derivation
+async fn execute_bip85(
+    args: Bip85Args,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.filename, config);
+
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+
+    let child = Bip85Service::derive_mnemonic(&wallet, args.language, args.words, args.index)?;
+    let child_wallet = web3wallet_cli::Wallet::from_mnemonic(child.mnemonic.phrase(), wallet.network(), None)?;
+    audit(
+        config,
+        AuditAction::Export,
+        "bip85",
+        Some(&file_path),
+        Some(wallet.address()),
+        Some(format!("child mnemonic #{} ({})", args.index, child.derivation_path)),
+    )?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🌱 BIP85 child wallet #{}", args.index);
+            println!("Address:  {}", child_wallet.address());
+            println!("Path:     {}", child.derivation_path);
+            println!("Mnemonic: {}", child.mnemonic.phrase());
+            println!("\n⚠️  The master wallet and index {} always reproduce this mnemonic.", args.index);
+            println!("   Anyone with the master mnemonic can derive it.");
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "index": args.index,
+                "words": args.words,
+                "language": MnemonicService::language_name(args.language),
+                "derivation_path": child.derivation_path,
+                "mnemonic": child.mnemonic.phrase(),
+                "address": child_wallet.address()
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute missing-word mnemonic recovery
+async fn execute_recover(
+    args: RecoverArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let phrase = match &args.mnemonic {
+        Some(phrase) => zeroize::Zeroizing::new(phrase.clone()),
+        None => zeroize::Zeroizing::new(prompt_password("Enter mnemonic phrase (? for unknown words): ")?),
+    };
+    let passphrase = read_passphrase(&args.passphrase, false)?;
+    let passphrase = passphrase.as_deref().map(String::as_str).unwrap_or("");
+
+    let pattern = RecoveryPattern::parse(&phrase, args.language)?;
+    info!(
+        "Searching {} phrases for {} unknown word(s)",
+        pattern.search_space(),
+        pattern.unknown_count()
+    );
+
+    if let Some(address) = &args.address {
+        let address = resolve_address(address, config)?.to_string();
+        let found = RecoveryService::find_address(&pattern, &address, passphrase, config.is_low_resource())?;
+        match output {
+            OutputFormat::Table => match &found {
+                Some(mnemonic) => {
+                    println!("\n✅ Recovered mnemonic for {}", address);
+                    println!("Mnemonic: {}", mnemonic);
+                }
+                None => println!("\n❌ No completion derives {}", address),
+            },
+            json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                let output = config.address_case.scope(|| serde_json::json!({
+                    "address": address,
+                    "searched": pattern.search_space(),
+                    "mnemonic": found
+                }));
+                print_json(&output, json, config.address_case)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let candidates = RecoveryService::checksum_candidates(&pattern, config.is_low_resource());
+
+    if args.check_balance {
+        let rpc_url = config.rpc_endpoint(config.network.name()).ok_or_else(|| NetworkError::InvalidConfiguration {
+            key: format!("rpc_endpoints.{}", config.network),
+            details: "No RPC endpoint configured for the balance scan".to_string(),
+        })?;
+        let active = RecoveryService::scan_balances(&candidates, passphrase, rpc_url).await?;
+
+        match output {
+            OutputFormat::Table => {
+                println!("\n🔎 {} of {} checksum-valid candidates have on-chain activity", active.len(), candidates.len());
+                for candidate in &active {
+                    println!("\nAddress:      {}", candidate.address);
+                    println!("Balance:      {} ETH", ethers::utils::format_ether(candidate.balance));
+                    println!("Transactions: {}", candidate.transactions);
+                    println!("Mnemonic:     {}", candidate.mnemonic);
+                }
+            }
+            json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                let active: Vec<_> = active
+                    .iter()
+                    .map(|candidate| {
+                        config.address_case.scope(|| serde_json::json!({
+                            "mnemonic": candidate.mnemonic,
+                            "address": candidate.address,
+                            "balance_wei": candidate.balance.to_string(),
+                            "transactions": candidate.transactions.to_string()
+                        }))
+                    })
+                    .collect();
+                let output = config.address_case.scope(|| serde_json::json!({
+                    "network": config.network,
+                    "candidates": candidates.len(),
+                    "active": active
+                }));
+                print_json(&output, json, config.address_case)?;
+            }
+        }
+        return Ok(());
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔎 {} checksum-valid candidates", candidates.len());
+            for candidate in &candidates {
+                println!("{}", candidate);
+            }
+            if candidates.len() > 1 {
+                println!("\n💡 Pass --address or --check-balance to find the right one.");
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "searched": pattern.search_space(),
+                "candidates": candidates
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Record a sensitive operation in the wallet directory's audit log
+fn audit(
+    config: &WalletConfig,
+    action: AuditAction,
+    operation: &str,
+    file: Option<&std::path::Path>,
+    address: Option<&EthereumAddress>,
+    details: Option<String>,
+) -> WalletResult<()> {
+    let record = AuditRecord {
+        operation: operation.to_string(),
+        file: file.map(keystore_name),
+        address: address.map(EthereumAddress::to_string),
+        details,
+    };
+    AuditLog::new(&config.wallet_dir).append(action, record, chrono::Utc::now())?;
+    Ok(())
+}
+
+/// Keystore identifier used by the usage and TOTP indexes
+fn keystore_name(file_path: &std::path::Path) -> String {
+    file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| file_path.display().to_string())
+}
+
+/// Count a signature against the keystore's signing quota, asking for a
+/// one-time code first when an enrolled keystore is past the TOTP threshold
+fn record_signature(file_path: &std::path::Path, config: &WalletConfig) -> WalletResult<()> {
+    let keystore = keystore_name(file_path);
+    let now = chrono::Utc::now();
+    let mut tracker = UsageTracker::load(&config.wallet_dir)?;
+
+    if config.totp.required(tracker.count_today(&keystore, now) + 1) {
+        let mut store = TotpStore::load(&config.wallet_dir)?;
+        if store.is_enrolled(&keystore) {
+            let password = keystore_password(file_path, config)?;
+            let code = prompt_one_time_code(&keystore)?;
+            store.verify(&keystore, &code, &password, now)?;
+            store.save()?;
+        }
+    }
+
+    let status = tracker.record_signature(&keystore, config.quotas.limits_for(&keystore), now);
+    // Save even when refused so a newly reached hard limit stays locked
+    tracker.save()?;
+    if let QuotaStatus::SoftLimitExceeded { count, limit } = status? {
+        warn!("{} has signed {} times today (soft limit {})", keystore, count, limit);
+    }
+    Ok(())
+}
+
+/// Asks on the terminal before the signer daemon lists accounts or signs
+struct TerminalApprover {
+    file_path: PathBuf,
+    config: WalletConfig,
+}
+
+impl Approver for TerminalApprover {
+    fn approve(&self, request: &ApprovalRequest) -> bool {
+        eprintln!("\n📥 {}", request);
+        eprint!("Approve? [y/N] ");
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).is_err() || !answer.trim().eq_ignore_ascii_case("y") {
+            eprintln!("❌ Denied");
+            return false;
+        }
+        if !request.is_signature() {
+            return true;
+        }
+
+        let recorded = record_signature(&self.file_path, &self.config).and_then(|_| {
+            let details = match request {
+                ApprovalRequest::SignData { content_type, .. } => content_type.clone(),
+                _ => "transaction".to_string(),
+            };
+            audit(&self.config, AuditAction::Sign, "daemon sign", Some(&self.file_path), request.signer(), Some(details))
+        });
+        match recorded {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Not signing: {}", e);
+                false
+            }
+        }
+    }
+}
+
+/// Execute the external signer daemon
+async fn execute_daemon(args: DaemonArgs, config: &WalletConfig) -> WalletResult<()> {
+    // Every request is approved on this terminal
+    if password::is_non_interactive() {
+        return Err(password::prompt_refused("Approve signer requests? [y/N]").into());
+    }
+    if args.accounts == 0 {
+        return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "accounts".to_string(),
+            value: "0".to_string(),
+            expected: "at least one account".to_string(),
+        }));
+    }
+
+    let (network, file_path, signers) = match &args.from_file {
+        Some(from_file) => {
+            let manager = WalletManager::new(config.clone());
+            let file_path = wallet_file_path(from_file, config);
+            let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &args.passphrase)?;
+            let signers = (0..args.accounts)
+                .map(|index| Ok(Box::new(LocalKeySigner::from_wallet(&wallet, index)?) as Box<dyn KeySigner>))
+                .collect::<WalletResult<Vec<_>>>()?;
+            (wallet.network().clone(), file_path, signers)
+        }
+        None => {
+            let token = config.pkcs11.as_ref().ok_or_else(|| UserInputError::MissingParameter {
+                parameter: "pkcs11".to_string(),
+                hint: "Add a [pkcs11] section with the module and key_label to the config".to_string(),
+            })?;
+            let pin = match &config.password_source {
+                Some(source) => zeroize::Zeroizing::new(source.read()?.as_str().to_string()),
+                None => zeroize::Zeroizing::new(prompt_password("Enter token PIN: ")?),
+            };
+            let signer = Pkcs11Signer::open(token, &pin)?;
+            // Quotas, second factors and audit records are kept under the key's label
+            let file_path = PathBuf::from(format!("pkcs11:{}", token.key_label));
+            (config.network.clone(), file_path, vec![Box::new(signer) as Box<dyn KeySigner>])
+        }
+    };
+
+    let approver = TerminalApprover { file_path, config: config.clone() };
+    let mut daemon = SignerDaemon::new(network.chain_id(), std::sync::Arc::new(approver));
+    for signer in signers {
+        daemon.add_account(signer)?;
+    }
+
+    if !args.listen.ip().is_loopback() {
+        warn!("{} is reachable from other machines; anyone who can connect may send signing requests", args.listen);
+    }
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    eprintln!("🔏 Signing on {} (chain {}) with:", network, network.chain_id());
+    for account in daemon.accounts() {
+        eprintln!("   {}", account);
+    }
+    eprintln!("Point clients at http://{} (geth: --signer http://{}); stop with Ctrl-C", args.listen, args.listen);
+
+    tokio::select! {
+        result = daemon.serve(listener) => result,
+        _ = tokio::signal::ctrl_c() => {
+            info!("Signer daemon stopped");
+            Ok(())
+        }
+    }
+}
+
+/// Execute address ownership proof signing
+async fn execute_prove_ownership(
+    args: ProveOwnershipArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.from_file, config);
+
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+    let signer = LocalKeySigner::from_wallet(&wallet, args.index)?;
+
+    record_signature(&file_path, config)?;
+
+    let proof = OwnershipService::prove(&manager, &signer, &args.challenge, chrono::Utc::now())?;
+    audit(config, AuditAction::Sign, "prove-ownership", Some(&file_path), Some(&proof.address.parse()?), None)?;
+    let json = serde_json::to_string_pretty(&proof)?;
+    if let Some(out) = &args.out {
+        std::fs::write(out, format!("{}\n", json))?;
+        info!("Proof written to: {}", out.display());
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n✍️  Ownership proof");
+            println!("Message:   {}", proof.message);
+            println!("Signature: {}", proof.signature);
+        }
+        OutputFormat::Json => println!("{}", json),
+        OutputFormat::Ndjson => print_json(&proof, output, config.address_case)?,
+    }
+
+    Ok(())
+}
+
+/// Execute Safe multisig commands
+async fn execute_safe(args: SafeArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let load = |path: &std::path::Path| -> WalletResult<SafeProposal> {
+        let proposal: SafeProposal = serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "Safe proposal".to_string(),
+                details: format!("{}: {}", path.display(), e),
+            }
+        })?;
+        SafeService::verify(&proposal)?;
+        Ok(proposal)
+    };
+    let save = |path: &std::path::Path, proposal: &SafeProposal| -> WalletResult<()> {
+        std::fs::write(path, format!("{}\n", serde_json::to_string_pretty(proposal)?))?;
+        info!("Proposal written to: {}", path.display());
+        Ok(())
+    };
+
+    let proposal = match args.command {
+        SafeCommands::Propose { transaction, out } => {
+            let (safe, chain_id, transaction) = safe_transaction(transaction, config)?;
+            let proposal = SafeService::propose(safe, chain_id, transaction);
+            save(&out, &proposal)?;
+            proposal
+        }
+        SafeCommands::Sign { from_file, index, proposal: path, out, yes, passphrase } => {
+            let mut proposal = load(&path)?;
+            eprintln!("\n📥 Safe transaction");
+            describe_safe_transaction(&proposal);
+
+            if !yes {
+                if password::is_non_interactive() {
+                    return Err(password::prompt_refused("Sign this Safe transaction? [y/N]").into());
+                }
+                eprint!("\nSign this Safe transaction? [y/N] ");
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                        parameter: "confirmation".to_string(),
+                        value: answer.trim().to_string(),
+                        expected: "y to sign".to_string(),
+                    }));
+                }
+            }
+
+            let manager = WalletManager::new(config.clone());
+            let file_path = wallet_file_path(&from_file, config);
+            let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &passphrase)?;
+            let signer = LocalKeySigner::from_wallet(&wallet, index)?;
+
+            record_signature(&file_path, config)?;
+            let owner = SafeService::sign(&mut proposal, &signer)?;
+            audit(
+                config,
+                AuditAction::Sign,
+                "safe sign",
+                Some(&file_path),
+                Some(&owner),
+                Some(format!("safeTxHash {:?} for {}", proposal.safe_tx_hash, proposal.safe)),
+            )?;
+            save(out.as_deref().unwrap_or(&path), &proposal)?;
+            proposal
+        }
+        SafeCommands::Merge { proposals, out } => {
+            let proposals = proposals.iter().map(|path| load(path)).collect::<WalletResult<Vec<_>>>()?;
+            let merged = SafeService::merge(&proposals)?;
+            save(&out, &merged)?;
+            merged
+        }
+        SafeCommands::Show { proposal } => load(&proposal)?,
+    };
+
+    let signatures = SafeService::packed_signatures(&proposal)?;
+    let calldata = SafeService::exec_calldata(&proposal)?;
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔐 Safe transaction");
+            println!("Safe:        {}", proposal.safe);
+            println!("Chain:       {}", proposal.chain_id);
+            println!("safeTxHash:  {:?}", proposal.safe_tx_hash);
+            println!("Signed by:   {} owner(s)", proposal.signatures.len());
+            for owner in proposal.signers() {
+                println!("   {}", owner);
+            }
+            if !proposal.signatures.is_empty() {
+                println!("Signatures:  {}", signatures);
+                println!("execTransaction calldata (send to the Safe once the threshold is met):");
+                println!("{}", calldata);
+            }
+        }
+        _ => print_json(
+            &config.address_case.scope(|| serde_json::json!({
+                "safe": proposal.safe,
+                "chainId": proposal.chain_id,
+                "safeTxHash": proposal.safe_tx_hash,
+                "signers": proposal.signers(),
+                "signatures": signatures,
+                "execTransactionCalldata": calldata,
+            })),
+            output,
+            config.address_case,
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Execute partially signed transaction commands
+async fn execute_psbt_eth(args: PsbtEthArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let load = |path: &std::path::Path| -> WalletResult<PartiallySignedTransaction> {
+        let psbt: PartiallySignedTransaction = serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "partially signed transaction".to_string(),
+                details: format!("{}: {}", path.display(), e),
+            }
+        })?;
+        PsbtService::verify(&psbt)?;
+        Ok(psbt)
+    };
+    let save = |path: &std::path::Path, psbt: &PartiallySignedTransaction| -> WalletResult<()> {
+        std::fs::write(path, format!("{}\n", serde_json::to_string_pretty(psbt)?))?;
+        info!("Envelope written to: {}", path.display());
+        Ok(())
+    };
+
+    let psbt = match args.command {
+        PsbtEthCommands::Create { transaction, threshold, signers, description, out } => {
+            let (safe, chain_id, transaction) = safe_transaction(*transaction, config)?;
+            let policy = SignerPolicy {
+                threshold,
+                signers: signers.iter().map(|signer| resolve_address(signer, config)).collect::<WalletResult<_>>()?,
+            };
+            let psbt = PsbtService::create(chain_id, PsbtPayload::Safe { safe, transaction }, policy, description)?;
+            save(&out, &psbt)?;
+            psbt
+        }
+        PsbtEthCommands::Sign { from_file, index, psbt: path, out, label, yes, passphrase } => {
+            let mut psbt = load(&path)?;
+            eprintln!("\n📥 Partially signed transaction");
+            if let Some(description) = &psbt.description {
+                eprintln!("Note:       {}", description);
+            }
+            let PsbtPayload::Safe { safe, transaction } = &psbt.payload;
+            describe_safe_transaction(&SafeService::propose(*safe, psbt.chain_id, transaction.clone()));
+            eprintln!("Policy:     {} of {} signers, {} signed", psbt.policy.threshold, psbt.policy.signers.len(), psbt.signatures.len());
+
+            if !yes {
+                if password::is_non_interactive() {
+                    return Err(password::prompt_refused("Sign this transaction? [y/N]").into());
+                }
+                eprint!("\nSign this transaction? [y/N] ");
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                        parameter: "confirmation".to_string(),
+                        value: answer.trim().to_string(),
+                        expected: "y to sign".to_string(),
+                    }));
+                }
+            }
+
+            let manager = WalletManager::new(config.clone());
+            let file_path = wallet_file_path(&from_file, config);
+            let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &passphrase)?;
+            let signer = LocalKeySigner::from_wallet(&wallet, index)?;
+
+            record_signature(&file_path, config)?;
+            let address = PsbtService::sign(&mut psbt, &signer, label)?;
+            audit(
+                config,
+                AuditAction::Sign,
+                "psbt-eth sign",
+                Some(&file_path),
+                Some(&address),
+                Some(format!("digest {:?} on chain {}", psbt.digest, psbt.chain_id)),
+            )?;
+            save(out.as_deref().unwrap_or(&path), &psbt)?;
+            psbt
+        }
+        PsbtEthCommands::Combine { psbts, out } => {
+            let copies = psbts.iter().map(|path| load(path)).collect::<WalletResult<Vec<_>>>()?;
+            let combined = PsbtService::combine(&copies)?;
+            save(&out, &combined)?;
+            combined
+        }
+        PsbtEthCommands::Finalize { psbt, out } => {
+            let finalized = PsbtService::finalize(&load(&psbt)?)?;
+            if let Some(out) = &out {
+                std::fs::write(out, format!("{}\n", serde_json::to_string_pretty(&finalized)?))?;
+                info!("Call written to: {}", out.display());
+            }
+            match output {
+                OutputFormat::Table => {
+                    println!("\n✅ Threshold met: {} signature(s)", finalized.signers.len());
+                    println!("Chain:  {}", finalized.chain_id);
+                    println!("To:     {}", finalized.to);
+                    println!("Value:  {} wei", finalized.value);
+                    println!("Data (send from any account to execute):");
+                    println!("{}", finalized.data);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(&finalized, json, config.address_case)?,
+            }
+            return Ok(());
+        }
+        PsbtEthCommands::Show { psbt } => load(&psbt)?,
+    };
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🧾 Partially signed transaction");
+            if let Some(description) = &psbt.description {
+                println!("Note:       {}", description);
+            }
+            println!("Chain:      {}", psbt.chain_id);
+            println!("Digest:     {:?}", psbt.digest);
+            println!("Threshold:  {} of {}", psbt.policy.threshold, psbt.policy.signers.len());
+            for signer in &psbt.policy.signers {
+                match psbt.signatures.get(signer) {
+                    Some(entry) => println!(
+                        "   ✅ {} {}({})",
+                        signer,
+                        entry.label.as_ref().map_or(String::new(), |label| format!("{} ", label)),
+                        entry.signed_at.format("%Y-%m-%d %H:%M UTC")
+                    ),
+                    None => println!("   ⏳ {}", signer),
+                }
+            }
+            if psbt.is_complete() {
+                println!("\nReady: run `wallet psbt-eth finalize` to get the executing call");
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "chainId": psbt.chain_id,
+                "digest": psbt.digest,
+                "threshold": psbt.policy.threshold,
+                "signed": psbt.signatures.keys().collect::<Vec<_>>(),
+                "pending": psbt.pending_signers(),
+                "complete": psbt.is_complete(),
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute token approval review
+async fn execute_approvals(args: ApprovalsArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    use ethers::providers::{Http, Middleware, Provider};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use ethers::types::BlockNumber;
+
+    let owner = resolve_address(&args.address, config)?;
+    let network = args.network.as_ref().unwrap_or(&config.network);
+    let rpc_url = config.rpc_endpoint(network.name()).ok_or_else(|| NetworkError::InvalidConfiguration {
+        key: format!("rpc_endpoints.{}", network),
+        details: "No RPC endpoint configured for approval lookups".to_string(),
+    })?;
+    let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+        key: "rpc_endpoints".to_string(),
+        details: e.to_string(),
+    })?;
+    let connectivity = |e: ethers::providers::ProviderError| NetworkError::ConnectivityFailure {
+        endpoint: rpc_url.to_string(),
+        details: e.to_string(),
+    };
+
+    let mut approvals = ApprovalService::fetch(&provider, rpc_url, owner, args.from_block, args.to_block).await?;
+    let book = AddressBook::load(&config.wallet_dir.join(web3wallet_cli::config::address_book::FILE_NAME))?;
+    ApprovalService::label_spenders(&mut approvals, &book);
+    if args.names {
+        let unlabeled: Vec<EthereumAddress> =
+            approvals.iter().filter(|approval| approval.spender_label.is_none()).map(|approval| approval.spender).collect();
+        // Names are a convenience; an unreachable explorer leaves the spenders unnamed
+        match explorer_client(config, network)?.contract_names(unlabeled).await {
+            Ok(names) => {
+                for approval in approvals.iter_mut().filter(|approval| approval.spender_label.is_none()) {
+                    approval.spender_label = names.get(&approval.spender).cloned();
+                }
+            }
+            Err(e) => warn!("Could not look up spender names: {}", e),
+        }
+    }
+
+    // Spenders and tokens whose approvals to revoke
+    let revoke_all = args.revoke.iter().any(|name| name.eq_ignore_ascii_case("all"));
+    let targets = args
+        .revoke
+        .iter()
+        .filter(|name| !name.eq_ignore_ascii_case("all"))
+        .map(|name| resolve_address(name, config))
+        .collect::<WalletResult<Vec<_>>>()?;
+    let selected: Vec<&Approval> = approvals
+        .iter()
+        .filter(|approval| revoke_all || targets.iter().any(|target| *target == approval.spender || *target == approval.token))
+        .collect();
+    if !args.revoke.is_empty() && selected.is_empty() {
+        warn!("No current approval matches --revoke; nothing to build");
+    }
+
+    let mut revokes = Vec::with_capacity(selected.len());
+    if !selected.is_empty() {
+        let nonce = provider
+            .get_transaction_count(ethers::types::Address::from(owner), Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(connectivity)?;
+        for (offset, approval) in selected.iter().enumerate() {
+            let mut transaction: TypedTransaction =
+                approval.revoke_transaction(owner, network.chain_id()).nonce(nonce + offset).into();
+            provider.fill_transaction(&mut transaction, None).await.map_err(connectivity)?;
+            revokes.push(config.address_case.scope(|| serde_json::json!({
+                "token": approval.token,
+                "kind": approval.kind,
+                "spender": approval.spender,
+                "nonce": transaction.nonce(),
+                "gas": transaction.gas(),
+                "tx": format!("0x{}", hex::encode(transaction.rlp())),
+            })));
+        }
+    }
+
+    if let Some(out) = &args.out {
+        std::fs::write(out, serde_json::to_string_pretty(&revokes)?)?;
+        info!("Revoke transactions written to: {}", out.display());
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔓 {} current approval(s) granted by {} on {}\n", approvals.len(), owner, network);
+            if !approvals.is_empty() {
+                println!("{:<42} {:<8} {:<42} {:<26} NAME", "TOKEN", "KIND", "SPENDER", "AMOUNT");
+            }
+            for approval in &approvals {
+                let amount = match (approval.allowance, approval.token_id) {
+                    _ if approval.is_unlimited() => "unlimited".to_string(),
+                    (Some(allowance), _) => allowance.to_string(),
+                    (_, Some(token_id)) => format!("token #{}", token_id),
+                    _ => "all tokens".to_string(),
+                };
+                let amount = if approval.confirmed { amount } else { format!("{}?", amount) };
+                println!(
+                    "{:<42} {:<8} {:<42} {:<26} {}",
+                    approval.token,
+                    approval.kind,
+                    approval.spender,
+                    amount,
+                    approval.spender_label.as_deref().unwrap_or("-")
+                );
+            }
+            if approvals.iter().any(|approval| !approval.confirmed) {
+                println!("\n? The token did not answer the check; the amount is taken from its logs");
+            }
+            if !revokes.is_empty() && args.out.is_none() {
+                println!("\n📝 Unsigned revoke transactions (sign with `wallet airgap request --tx <hex>`):");
+                for revoke in &revokes {
+                    println!("\n   {} on {}", revoke["spender"].as_str().unwrap_or_default(), revoke["token"].as_str().unwrap_or_default());
+                    println!("   {}", revoke["tx"].as_str().unwrap_or_default());
+                }
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+            &config.address_case.scope(|| serde_json::json!({ "owner": owner, "chainId": network.chain_id(), "approvals": approvals, "revokes": revokes })),
+            json,
+            config.address_case,
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Execute NFT commands
+async fn execute_nft(args: NftArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    use ethers::providers::{Http, Middleware, Provider};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+
+    let NftCommands::Transfer {
+        contract,
+        token_id,
+        to,
+        amount,
+        standard,
+        from,
+        from_file,
+        index,
+        data,
+        network,
+        skip_checks,
+        yes,
+        passphrase,
+    } = args.command;
+
+    let network = network.as_ref().unwrap_or(&config.network);
+    let rpc_url = config.rpc_endpoint(network.name()).ok_or_else(|| NetworkError::InvalidConfiguration {
+        key: format!("rpc_endpoints.{}", network),
+        details: "No RPC endpoint configured for building transfers".to_string(),
+    })?;
+    let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+        key: "rpc_endpoints".to_string(),
+        details: e.to_string(),
+    })?;
+
+    let contract = resolve_address(&contract, config)?;
+    let to = resolve_address(&to, config)?;
+    let (signer, from) = match (&from_file, from) {
+        (Some(from_file), _) => {
+            let manager = WalletManager::new(config.clone());
+            let file_path = wallet_file_path(from_file, config);
+            let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &passphrase)?;
+            let signer = LocalKeySigner::from_wallet(&wallet, index)?;
+            let address: EthereumAddress = signer.address().parse()?;
+            (Some((signer, file_path)), address)
+        }
+        (None, Some(from)) => (None, resolve_address(&from, config)?),
+        (None, None) => unreachable!("clap requires --from or --from-file"),
+    };
+
+    let standard = match standard {
+        Some(standard) => standard,
+        None => NftService::detect_standard(&provider, rpc_url, contract).await?,
+    };
+    let transfer = NftService::transfer(contract, standard, from, to, token_id, amount, data.unwrap_or_default())?;
+    let mut transaction: TypedTransaction = transfer.transaction(network.chain_id()).into();
+    if !skip_checks {
+        let problem = match NftService::check_ownership(&provider, rpc_url, &transfer).await? {
+            Some(problem) => Some(problem),
+            None => NftService::simulate(&provider, rpc_url, &transaction)
+                .await?
+                .map(|reason| format!("The transfer would revert: {}", reason)),
+        };
+        if let Some(problem) = problem {
+            error!("{}", problem);
+            return Err(WalletError::Validation(ValidationError::IntegrityCheckFailed {
+                data_type: "nft transfer".to_string(),
+                details: problem,
+            }));
+        }
+    }
+
+    let connectivity = |e: ethers::providers::ProviderError| NetworkError::ConnectivityFailure {
+        endpoint: rpc_url.to_string(),
+        details: e.to_string(),
+    };
+    let nonce = provider
+        .get_transaction_count(ethers::types::Address::from(from), Some(ethers::types::BlockNumber::Pending.into()))
+        .await
+        .map_err(connectivity)?;
+    transaction.set_nonce(nonce);
+    provider.fill_transaction(&mut transaction, None).await.map_err(connectivity)?;
+
+    eprintln!("\n🖼️  {} transfer on chain {}", standard, network.chain_id());
+    eprintln!("Contract:  {}", contract);
+    eprintln!("Token ID:  {}", token_id);
+    if standard == NftStandard::Erc1155 {
+        eprintln!("Amount:    {}", amount);
+    }
+    eprintln!("From:      {}", from);
+    eprintln!("To:        {}", to);
+    eprintln!("Nonce:     {}", transaction.nonce().copied().unwrap_or_default());
+    eprintln!("Gas limit: {}", transaction.gas().copied().unwrap_or_default());
+    if let TypedTransaction::Eip1559(request) = &transaction {
+        let max_fee = request.max_fee_per_gas.unwrap_or_default();
+        eprintln!("Max fee:   {} gwei", ethers::utils::format_units(max_fee, "gwei").unwrap_or_else(|_| max_fee.to_string()));
+    }
+    if skip_checks {
+        warn!("Ownership check and simulation skipped");
+    }
+
+    let signed = match signer {
+        Some((signer, file_path)) => {
+            if !yes {
+                if password::is_non_interactive() {
+                    return Err(password::prompt_refused("Sign this transfer? [y/N]").into());
+                }
+                eprint!("\nSign this transfer? [y/N] ");
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                        parameter: "confirmation".to_string(),
+                        value: answer.trim().to_string(),
+                        expected: "y to sign".to_string(),
+                    }));
+                }
+            }
+
+            record_signature(&file_path, config)?;
+            let signature = signer.sign_transaction(&transaction)?;
+            audit(
+                config,
+                AuditAction::Sign,
+                "nft transfer",
+                Some(&file_path),
+                Some(&from),
+                Some(format!("{} token {} on {} to {}", standard, token_id, contract, to)),
+            )?;
+            Some(transaction.rlp_signed(&signature))
+        }
+        None => None,
+    };
+
+    let raw = signed.as_ref().map_or_else(|| transaction.rlp(), Clone::clone);
+    match output {
+        OutputFormat::Table => match &signed {
+            Some(raw) => {
+                println!("\n✅ Signed by {}", from);
+                println!("Transaction hash: 0x{}", hex::encode(ethers::utils::keccak256(raw)));
+                println!("Raw transaction:  0x{}", hex::encode(raw));
+            }
+            None => {
+                println!("\n📝 Unsigned transaction (sign with `wallet airgap request --tx <hex>`):");
+                println!("   0x{}", hex::encode(&raw));
+            }
+        },
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+            &config.address_case.scope(|| serde_json::json!({
+                "transfer": transfer,
+                "chainId": network.chain_id(),
+                "nonce": transaction.nonce(),
+                "gas": transaction.gas(),
+                "signed": signed.is_some(),
+                "hash": signed.as_ref().map(|raw| format!("0x{}", hex::encode(ethers::utils::keccak256(raw)))),
+                "tx": format!("0x{}", hex::encode(&raw)),
+            })),
+            json,
+            config.address_case,
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Execute contract call encoding
+async fn execute_call(args: CallArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    use ethers::providers::{Http, Provider};
+
+    let network = args.network.as_ref().unwrap_or(&config.network);
+    let to = args.to.as_deref().map(|to| resolve_address(to, config)).transpose()?;
+    let (abi, contract_name) = match (&args.abi, to) {
+        (Some(path), _) => (ContractCallService::parse_abi(&std::fs::read_to_string(path)?)?, None),
+        (None, Some(to)) => {
+            let Some(contract) = explorer_client(config, network)?.callable_contract(to).await? else {
+                error!("{} has no verified source on the explorer; pass its ABI with --abi", to);
+                return Err(UserInputError::MissingParameter {
+                    parameter: "abi".to_string(),
+                    hint: "Pass the contract ABI with --abi".to_string(),
+                }
+                .into());
+            };
+            (contract.abi, Some(contract.name))
+        }
+        (None, None) => unreachable!("clap requires --abi without --to"),
+    };
+    let call_args = ContractCallService::split_args(&args.args);
+    let function = ContractCallService::function(&abi, &args.method, call_args.len())?;
+    let book = AddressBook::load(&config.wallet_dir.join(web3wallet_cli::config::address_book::FILE_NAME))?;
+    let calldata = ContractCallService::encode(function, &call_args, &book)?;
+    let signature = contract_call::signature(function);
+
+    let returned = match to {
+        Some(to) => {
+            let from = args.from.as_deref().map(|from| resolve_address(from, config)).transpose()?;
+            if !ContractCallService::is_read_only(function) {
+                warn!("{} can change state; eth_call only simulates it and nothing is sent", signature);
+            }
+
+            let rpc_url = config.rpc_endpoint(network.name()).ok_or_else(|| NetworkError::InvalidConfiguration {
+                key: format!("rpc_endpoints.{}", network),
+                details: "No RPC endpoint configured for contract calls".to_string(),
+            })?;
+            let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+                key: "rpc_endpoints".to_string(),
+                details: e.to_string(),
+            })?;
+            match ContractCallService::call(&provider, rpc_url, to, from, calldata.clone()).await? {
+                CallOutcome::Returned(data) => Some(ContractCallService::decode_output(function, &data)?),
+                CallOutcome::Reverted(reason) => {
+                    error!("The call reverted: {}", reason);
+                    return Err(WalletError::Validation(ValidationError::IntegrityCheckFailed {
+                        data_type: "contract call".to_string(),
+                        details: reason,
+                    }));
+                }
+            }
+        }
+        None => None,
+    };
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n📦 {}", signature);
+            if let Some(name) = &contract_name {
+                println!("Contract: {} (verified source)", name);
+            }
+            println!("Selector: 0x{}", hex::encode(function.short_signature()));
+            println!("Calldata: {}", calldata);
+            if let Some(values) = &returned {
+                println!("\nReturned:");
+                if values.is_empty() {
+                    println!("   (nothing)");
+                }
+                for value in values {
+                    match value.name.as_str() {
+                        "" => println!("   {}: {}", value.kind, value.value),
+                        name => println!("   {} ({}): {}", name, value.kind, value.value),
+                    }
+                }
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+            &config.address_case.scope(|| serde_json::json!({
+                "function": signature,
+                "contract": contract_name,
+                "selector": format!("0x{}", hex::encode(function.short_signature())),
+                "calldata": calldata,
+                "returned": returned,
+            })),
+            json,
+            config.address_case,
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Explorer client for `network` from the `[explorer]` config section
+fn explorer_client(config: &WalletConfig, network: &Network) -> WalletResult<ExplorerClient> {
+    let explorer = config.explorer.as_ref().ok_or_else(|| NetworkError::InvalidConfiguration {
+        key: "explorer.api_key".to_string(),
+        details: "No block explorer configured; add an [explorer] section with an api_key".to_string(),
+    })?;
+    ExplorerClient::new(explorer, network.chain_id())
+}
+
+/// Execute the history command
+async fn execute_history(args: HistoryArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    if !(1..=10_000).contains(&args.limit) {
+        return Err(UserInputError::ValueOutOfRange {
+            parameter: "limit".to_string(),
+            value: args.limit.to_string(),
+            range: "1-10000".to_string(),
+        }
+        .into());
+    }
+
+    let address = resolve_address(&args.address, config)?;
+    let network = args.network.as_ref().unwrap_or(&config.network);
+    let explorer = explorer_client(config, network)?;
+    let transactions = explorer.transactions(address, args.page, args.limit).await?;
+
+    // Counterparties by address book label, then by verified contract name
+    let book = AddressBook::load(&config.wallet_dir.join(web3wallet_cli::config::address_book::FILE_NAME))?;
+    let counterparty = |tx: &ExplorerTransaction| if tx.from == address { tx.to.or(tx.contract_address) } else { Some(tx.from) };
+    let mut names: std::collections::BTreeMap<EthereumAddress, String> = transactions
+        .iter()
+        .filter_map(counterparty)
+        .filter_map(|other| book.find(&other.to_string()).and_then(|entry| entry.label().map(|label| (other, label.to_string()))))
+        .collect();
+    if args.names {
+        let unnamed: Vec<EthereumAddress> = transactions.iter().filter_map(counterparty).filter(|other| !names.contains_key(other)).collect();
+        match explorer.contract_names(unnamed).await {
+            Ok(verified) => names.extend(verified),
+            Err(e) => warn!("Could not look up contract names: {}", e),
+        }
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n📜 Transactions of {} on {} (page {})\n", address, network, args.page.max(1));
+            if transactions.is_empty() {
+                println!("   (none)");
+                return Ok(());
+            }
+            println!("{:<19} {:<12} {:<4} {:<42} {:>14} {:<20} STATUS", "TIME (UTC)", "HASH", "DIR", "COUNTERPARTY", "ETH", "METHOD");
+            for tx in &transactions {
+                let direction = match (tx.from == address, tx.to == Some(address)) {
+                    (true, true) => "self",
+                    (true, false) => "out",
+                    _ => "in",
+                };
+                let other = counterparty(tx);
+                let other = match other.and_then(|other| names.get(&other)) {
+                    Some(name) => name.clone(),
+                    None => other.map_or_else(|| "-".to_string(), |other| other.to_string()),
+                };
+                let hash = format!("{:?}", tx.hash)[..10].to_string() + "…";
+                println!(
+                    "{:<19} {:<12} {:<4} {:<42} {:>14} {:<20} {}",
+                    tx.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    hash,
+                    direction,
+                    other,
+                    ethers::utils::format_ether(tx.value).trim_end_matches('0').trim_end_matches('.'),
+                    tx.method().unwrap_or(if tx.to.is_none() { "(create)" } else { "-" }),
+                    if tx.failed { "failed" } else { "ok" }
+                );
+            }
+            if transactions.len() == args.limit {
+                println!("\nMore with --page {}", args.page.max(1) + 1);
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+            &config.address_case.scope(|| serde_json::json!({ "address": address, "chainId": network.chain_id(), "names": names, "transactions": transactions })),
+            json,
+            config.address_case,
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Safe address, chain ID and `SafeTx` fields from the command line
+fn safe_transaction(args: SafeTxArgs, config: &WalletConfig) -> WalletResult<(EthereumAddress, u64, SafeTransaction)> {
+    let resolve = |name: Option<String>| name.map(|name| resolve_address(&name, config)).transpose();
+    let transaction = SafeTransaction {
+        to: resolve_address(&args.to, config)?,
+        value: args.value,
+        data: args.data,
+        operation: args.operation,
+        safe_tx_gas: args.safe_tx_gas.into(),
+        base_gas: args.base_gas.into(),
+        gas_price: args.gas_price,
+        gas_token: resolve(args.gas_token)?.unwrap_or_default(),
+        refund_receiver: resolve(args.refund_receiver)?.unwrap_or_default(),
+        nonce: args.nonce.into(),
+    };
+    Ok((resolve_address(&args.safe, config)?, args.network.chain_id(), transaction))
+}
+
+/// Show the fields of a Safe transaction on stderr, ahead of the confirmation
+fn describe_safe_transaction(proposal: &SafeProposal) {
+    let transaction = &proposal.transaction;
+    eprintln!("Safe:       {} (chain {})", proposal.safe, proposal.chain_id);
+    eprintln!("To:         {}", transaction.to);
+    eprintln!("Value:      {} ETH", ethers::utils::format_ether(transaction.value));
+    match transaction.data.len() {
+        0 => {}
+        1..=3 => eprintln!("Data:       {}", transaction.data),
+        len => eprintln!("Data:       {} bytes, selector 0x{}", len, hex::encode(&transaction.data[..4])),
+    }
+    if transaction.operation == SafeOperation::DelegateCall {
+        warn!("This is a delegate call: the target's code runs with full control of the Safe");
+    }
+    eprintln!("Operation:  {}", transaction.operation.name());
+    eprintln!("Nonce:      {}", transaction.nonce);
+    if !transaction.gas_price.is_zero() {
+        eprintln!(
+            "Refund:     safeTxGas {}, baseGas {}, gas price {} wei in {} to {}",
+            transaction.safe_tx_gas, transaction.base_gas, transaction.gas_price, transaction.gas_token, transaction.refund_receiver
+        );
+    }
+    eprintln!("safeTxHash: {:?}", proposal.safe_tx_hash);
+    if !proposal.signatures.is_empty() {
+        eprintln!("Signed by:  {}", proposal.signers().iter().map(ToString::to_string).collect::<Vec<_>>().join(", "));
+    }
+}
+
+/// Execute ownership proof verification
+async fn execute_prove_verify(args: ProveVerifyArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    use ethers::providers::{Http, Provider};
+    use web3wallet_cli::services::ownership::SignatureKind;
+
+    let address = args.address.as_deref().map(|address| resolve_address(address, config)).transpose()?.map(|address| address.to_string());
+    let options = VerifyOptions {
+        address: address.as_deref(),
+        challenge: args.challenge.as_deref(),
+        max_age: args.max_age.map(|secs| chrono::Duration::seconds(secs.min(i64::MAX as u64) as i64)),
+    };
+    let now = chrono::Utc::now();
+
+    let proof = match &args.proof {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)?;
+            let proof: OwnershipProof = serde_json::from_str(&json).map_err(|e| ValidationError::IntegrityCheckFailed {
+                data_type: "ownership proof".to_string(),
+                details: e.to_string(),
+            })?;
+            Some(proof)
+        }
+        None => None,
+    };
+    let (message, signature) = match (&proof, &args.message, &args.signature) {
+        (Some(proof), _, _) => (&proof.message, &proof.signature),
+        (None, Some(message), Some(signature)) => (message, signature),
+        _ => {
+            return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                parameter: "proof".to_string(),
+                value: String::new(),
+                expected: "--proof <file>, or --message with --signature".to_string(),
+            }))
+        }
+    };
+
+    let (statement, kind) = if args.erc1271 {
+        let network = args.network.as_ref().unwrap_or(&config.network);
+        let rpc_url = config.rpc_endpoint(network.name()).ok_or_else(|| NetworkError::InvalidConfiguration {
+            key: format!("rpc_endpoints.{}", network),
+            details: "No RPC endpoint configured for ERC-1271 checks".to_string(),
+        })?;
+        let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+            key: "rpc_endpoints".to_string(),
+            details: e.to_string(),
+        })?;
+        OwnershipService::verify_with_contracts(&provider, rpc_url, message, signature, &options, now).await?
+    } else {
+        (OwnershipService::verify(message, signature, &options, now)?, SignatureKind::Ecdsa)
+    };
+    if let Some(proof) = &proof {
+        OwnershipService::check_proof_fields(proof, &statement)?;
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n✅ Valid ownership proof");
+            println!("Address:   {}", statement.address);
+            if kind == SignatureKind::Erc1271 {
+                println!("Signer:    contract wallet (ERC-1271)");
+            }
+            println!("Signed at: {}", statement.timestamp.to_rfc3339());
+            println!("Challenge: {}", statement.challenge);
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "valid": true,
+                "address": statement.address,
+                "timestamp": statement.timestamp.to_rfc3339(),
+                "challenge": statement.challenge,
+                "signatureType": kind.name()
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute Sign-In with Ethereum message signing
+async fn execute_siwe(args: SiweArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.from_file, config);
+
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+    let signer = LocalKeySigner::from_wallet(&wallet, args.index)?;
+
+    let now = chrono::Utc::now();
+    let issued_at = chrono::DateTime::from_timestamp(now.timestamp(), 0).unwrap_or(now);
+    let nonce = args.nonce.unwrap_or_else(SiweMessage::random_nonce);
+    let chain_id = args.network.as_ref().unwrap_or(&config.network).chain_id();
+    let mut message = SiweMessage::new(&args.domain, signer.address().parse()?, &args.uri, chain_id, &nonce, issued_at)?;
+    message.statement = args.statement;
+    message.expiration_time = args.expires_in.map(|secs| issued_at + chrono::Duration::seconds(i64::from(secs)));
+    message.not_before = args.not_before;
+    message.request_id = args.request_id;
+    message.resources = args.resources;
+
+    record_signature(&file_path, config)?;
+    let signed = SiweService::sign(&manager, &signer, &message)?;
+    audit(
+        config,
+        AuditAction::Sign,
+        "siwe",
+        Some(&file_path),
+        Some(&message.address),
+        Some(format!("domain {}, nonce {}", message.domain, message.nonce)),
+    )?;
+    let json = serde_json::to_string_pretty(&signed)?;
+    if let Some(out) = &args.out {
+        std::fs::write(out, format!("{}\n", json))?;
+        info!("Signed message written to: {}", out.display());
+    }
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n✍️  Sign-In with Ethereum\n");
+            println!("{}\n", signed.message);
+            println!("Signature: {}", signed.signature);
+        }
+        OutputFormat::Json => println!("{}", json),
+        OutputFormat::Ndjson => print_json(&signed, output, config.address_case)?,
+    }
+
+    Ok(())
+}
+
+/// Execute encryption public key display
+async fn execute_encryption_key(args: EncryptionKeyArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.from_file, config);
+
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+    let address = *wallet.derive_address(args.index)?.address();
+    let public_key = EncryptionKey::from_wallet(&wallet, args.index)?.public_key();
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔐 Encryption public key\n");
+            println!("Address:    {}", address);
+            println!("Public key: {}", public_key);
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+            &config.address_case.scope(|| serde_json::json!({ "address": address, "index": args.index, "publicKey": public_key })),
+            json,
+            config.address_case,
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Execute note encryption
+fn execute_encrypt_msg(args: EncryptMsgArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let plaintext = match (args.message, &args.input) {
+        (Some(message), _) => message,
+        (None, Some(path)) => std::fs::read_to_string(path)?,
+        (None, None) => {
+            return Err(WalletError::UserInput(UserInputError::MissingParameter {
+                parameter: "message".to_string(),
+                hint: "Pass --message <text> or --in <file>".to_string(),
+            }))
+        }
+    };
+
+    let encrypted = EthEncryptionService::encrypt(&args.to, &plaintext)?;
+    let json = serde_json::to_string_pretty(&encrypted)?;
+    if let Some(out) = &args.out {
+        std::fs::write(out, format!("{}\n", json))?;
+        info!("Encrypted note written to: {}", out.display());
+    }
+
+    match output {
+        OutputFormat::Table | OutputFormat::Json => println!("{}", json),
+        OutputFormat::Ndjson => print_json(&encrypted, output, config.address_case)?,
+    }
+
+    Ok(())
+}
+
+/// Execute note decryption
+async fn execute_decrypt_msg(args: DecryptMsgArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let encrypted: EncryptedMessage = serde_json::from_str(&std::fs::read_to_string(&args.input)?).map_err(|e| {
+        ValidationError::IntegrityCheckFailed {
+            data_type: "encrypted message".to_string(),
+            details: format!("{}: {}", args.input.display(), e),
+        }
+    })?;
+
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.from_file, config);
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+    let address = *wallet.derive_address(args.index)?.address();
+    let plaintext = EncryptionKey::from_wallet(&wallet, args.index)?.decrypt(&encrypted)?;
+    audit(config, AuditAction::Sign, "decrypt-msg", Some(&file_path), Some(&address), None)?;
+
+    match output {
+        OutputFormat::Table => println!("{}", plaintext.as_str()),
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+            &config.address_case.scope(|| serde_json::json!({ "address": address, "message": plaintext.as_str() })),
+            json,
+            config.address_case,
+        )?,
+    }
+
+    Ok(())
+}
+
+/// Execute stealth address commands
+async fn execute_stealth(args: StealthArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let unlock_keys = |from_file: String, index: u32, passphrase: PassphraseArgs| {
+        let manager = &manager;
+        async move {
+            let file_path = wallet_file_path(&from_file, config);
+            let mut wallet = unlock_wallet(manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &passphrase)?;
+            StealthKeys::from_wallet(&wallet, index).map(|keys| (keys, file_path))
+        }
+    };
+
+    match args.command {
+        StealthCommands::MetaAddress { from_file, index, passphrase } => {
+            let (keys, _) = unlock_keys(from_file, index, passphrase).await?;
+            let meta_address = keys.meta_address();
+            let calldata = StealthService::register_calldata(&meta_address);
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🕶️  Stealth meta-address\n");
+                    println!("{}\n", meta_address);
+                    println!("To publish it, send this calldata to the ERC-6538 registry at {}:", stealth::REGISTRY_ADDRESS);
+                    println!("{}", calldata);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+                    &config.address_case.scope(|| serde_json::json!({
+                        "metaAddress": meta_address.to_string(),
+                        "registry": stealth::REGISTRY_ADDRESS,
+                        "registerKeysCalldata": calldata,
+                    })),
+                    json,
+                    config.address_case,
+                )?,
+            }
+        }
+        StealthCommands::Generate { meta_address, out } => {
+            let stealth = StealthService::generate(&meta_address.parse::<StealthMetaAddress>()?)?;
+            let calldata = StealthService::announce_calldata(&stealth);
+            let document = serde_json::json!({
+                "stealthAddress": stealth.stealth_address,
+                "ephemeralPublicKey": stealth.ephemeral_public_key,
+                "viewTag": stealth.view_tag,
+                "announcer": stealth::ANNOUNCER_ADDRESS,
+                "announceCalldata": calldata,
+            });
+            if let Some(out) = &out {
+                std::fs::write(out, format!("{}\n", serde_json::to_string_pretty(&document)?))?;
+                info!("Stealth address written to: {}", out.display());
+            }
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🕶️  Stealth address\n");
+                    println!("Pay to:        {}", stealth.stealth_address);
+                    println!("Ephemeral key: {}", stealth.ephemeral_public_key);
+                    println!("View tag:      0x{:02x}", stealth.view_tag);
+                    println!("\nSo the recipient can find the payment, send this calldata to the announcer at {}:", stealth::ANNOUNCER_ADDRESS);
+                    println!("{}", calldata);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(&document, json, config.address_case)?,
+            }
+        }
+        StealthCommands::Scan { from_file, index, announcements, from_block, to_block, network, passphrase } => {
+            let announcements: Vec<Announcement> = match (&announcements, from_block) {
+                (Some(path), _) => serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+                    ValidationError::IntegrityCheckFailed {
+                        data_type: "stealth announcements".to_string(),
+                        details: format!("{}: {}", path.display(), e),
+                    }
+                })?,
+                (None, Some(from_block)) => {
+                    use ethers::providers::{Http, Provider};
+
+                    let network = network.as_ref().unwrap_or(&config.network);
+                    let rpc_url = config.rpc_endpoint(network.name()).ok_or_else(|| NetworkError::InvalidConfiguration {
+                        key: format!("rpc_endpoints.{}", network),
+                        details: "No RPC endpoint configured for announcement lookups".to_string(),
+                    })?;
+                    let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+                        key: "rpc_endpoints".to_string(),
+                        details: e.to_string(),
+                    })?;
+                    StealthService::fetch_announcements(&provider, rpc_url, from_block, to_block).await?
+                }
+                (None, None) => {
+                    return Err(WalletError::UserInput(UserInputError::MissingParameter {
+                        parameter: "from-block".to_string(),
+                        hint: "Pass --from-block <N>, or --announcements <file>".to_string(),
+                    }))
+                }
+            };
+
+            let (keys, _) = unlock_keys(from_file, index, passphrase).await?;
+            let found = StealthService::scan(&keys, &announcements);
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🕶️  {} of {} announcement(s) pay this wallet", found.len(), announcements.len());
+                    for announcement in &found {
+                        println!("\n   {}", announcement.stealth_address);
+                        println!("   Ephemeral key: {}", announcement.ephemeral_public_key);
+                        if let Some(block) = announcement.block_number {
+                            println!("   Block:         {}", block);
+                        }
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+                    &config.address_case.scope(|| serde_json::json!({ "scanned": announcements.len(), "found": found })),
+                    json,
+                    config.address_case,
+                )?,
+            }
+        }
+        StealthCommands::Claim { from_file, index, address, ephemeral_key, save, passphrase } => {
+            let announcement = Announcement {
+                stealth_address: resolve_address(&address, config)?,
+                ephemeral_public_key: ephemeral_key,
+                metadata: Default::default(),
+                block_number: None,
+                transaction_hash: None,
+            };
+            let (keys, source) = unlock_keys(from_file, index, passphrase).await?;
+            let private_key = keys.claim(&announcement)?;
+
+            let wallet = manager.import_from_private_key(&zeroize::Zeroizing::new(hex::encode(*private_key))).await?;
+            let file_path = keystore_save_path(&wallet, Some(&save), None, config)?;
+            let password = new_wallet_password("Enter password to encrypt the stealth keystore: ", config)?;
+            tokio::fs::create_dir_all(&config.wallet_dir).await.map_err(|e| {
+                WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
+                    path: config.wallet_dir.display().to_string(),
+                    details: e.to_string(),
+                })
+            })?;
+            manager.save_wallet(&wallet, &file_path, &password).await?;
+            audit(
+                config,
+                AuditAction::Create,
+                "stealth claim",
+                Some(&file_path),
+                Some(wallet.address()),
+                Some(format!("from {}", source.display())),
+            )?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n✅ Claimed {}", wallet.address());
+                    println!("💾 Keystore saved to: {}", file_path.display());
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => print_json(
+                    &config.address_case.scope(|| serde_json::json!({ "address": wallet.address(), "file": file_path })),
+                    json,
+                    config.address_case,
+                )?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute Sign-In with Ethereum verification
+fn execute_siwe_verify(args: SiweVerifyArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    let signed = match (&args.signed, &args.message, args.signature) {
+        (Some(path), _, _) => serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "SIWE signature".to_string(),
+                details: format!("{}: {}", path.display(), e),
+            }
+        })?,
+        (None, Some(path), Some(signature)) => SiweSignature {
+            message: std::fs::read_to_string(path)?.trim_end_matches(['\n', '\r']).to_string(),
+            signature,
+        },
+        _ => {
+            return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                parameter: "signed".to_string(),
+                value: String::new(),
+                expected: "--signed <file>, or --message <file> with --signature".to_string(),
+            }))
+        }
+    };
+
+    let options = SiweVerifyOptions {
+        domain: args.domain.as_deref(),
+        nonce: args.nonce.as_deref(),
+        chain_id: args.chain_id,
+    };
+    let message = SiweService::verify(&signed.message, &signed.signature, &options, chrono::Utc::now())?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n✅ Valid sign-in");
+            println!("Address:   {}", message.address);
+            println!("Domain:    {}", message.domain);
+            println!("URI:       {}", message.uri);
+            println!("Chain ID:  {}", message.chain_id);
+            println!("Nonce:     {}", message.nonce);
+            println!("Issued at: {}", message.issued_at.to_rfc3339());
+            if let Some(expiry) = message.expiration_time {
+                println!("Expires:   {}", expiry.to_rfc3339());
+            }
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "valid": true,
+                "address": message.address,
+                "domain": message.domain,
+                "uri": message.uri,
+                "chainId": message.chain_id,
+                "nonce": message.nonce,
+                "issuedAt": message.issued_at.to_rfc3339(),
+                "expirationTime": message.expiration_time.map(|time| time.to_rfc3339()),
+                "notBefore": message.not_before.map(|time| time.to_rfc3339()),
+                "requestId": message.request_id,
+                "resources": message.resources
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute identity bundle commands
+async fn execute_identity(
+    args: IdentityArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    match args.command {
+        IdentityCommands::Export { from_file, challenge, index, out, passphrase } => {
+            let manager = WalletManager::new(config.clone());
+            let file_path = wallet_file_path(&from_file, config);
+
+            let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &passphrase)?;
+            let signer = LocalKeySigner::from_wallet(&wallet, index)?;
+
+            record_signature(&file_path, config)?;
+            let challenge = challenge.unwrap_or_else(web3wallet_cli::utils::random_uuid);
+            let bundle = IdentityService::export(&manager, &signer, wallet.network(), &challenge, chrono::Utc::now())?;
+            audit(config, AuditAction::Sign, "identity export", Some(&file_path), Some(&bundle.checksum_address.parse()?), None)?;
+            let json = serde_json::to_string_pretty(&bundle)?;
+            if let Some(out) = &out {
+                std::fs::write(out, format!("{}\n", json))?;
+                info!("Identity bundle written to: {}", out.display());
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n🪪 Identity bundle");
+                    println!("Address:    {}", bundle.checksum_address);
+                    println!("Public key: {}", bundle.public_key);
+                    println!("Network:    {}", bundle.network);
+                    println!("Challenge:  {}", bundle.proof.challenge);
+                    if out.is_none() {
+                        println!("\n{}", json);
+                    }
+                }
+                OutputFormat::Json => println!("{}", json),
+                OutputFormat::Ndjson => print_json(&bundle, output, config.address_case)?,
+            }
+        }
+        IdentityCommands::Verify { bundle, challenge, max_age } => {
+            let json = std::fs::read_to_string(&bundle)?;
+            let bundle: IdentityBundle = serde_json::from_str(&json).map_err(|e| ValidationError::IntegrityCheckFailed {
+                data_type: "identity bundle".to_string(),
+                details: e.to_string(),
+            })?;
+            let options = VerifyOptions {
+                address: None,
+                challenge: challenge.as_deref(),
+                max_age: max_age.map(|secs| chrono::Duration::seconds(secs.min(i64::MAX as u64) as i64)),
+            };
+            let statement = IdentityService::verify(&bundle, &options, chrono::Utc::now())?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n✅ Valid identity bundle");
+                    println!("Address:    {}", bundle.checksum_address);
+                    println!("Public key: {}", bundle.public_key);
+                    println!("Network:    {}", bundle.network);
+                    println!("Signed at:  {}", statement.timestamp.to_rfc3339());
+                    println!("Challenge:  {}", statement.challenge);
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "valid": true,
+                        "address": bundle.checksum_address,
+                        "network": bundle.network,
+                        "timestamp": statement.timestamp.to_rfc3339(),
+                        "challenge": statement.challenge
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute account xpub export
+async fn execute_xpub(
+    args: XpubArgs,
+    config: &WalletConfig,
     output: OutputFormat,
 ) -> WalletResult<()> {
     let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.filename, config);
+
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
+    if let Some(coin_type) = &args.coin_type {
+        let coin_type = resolve_coin_type(coin_type, config).await?;
+        if coin_type != wallet.coin_type() {
+            wallet.set_coin_type(coin_type)?;
+        }
+    }
+    if let Some(account) = args.account.filter(|&account| account != wallet.account()) {
+        wallet.set_account(account)?;
+    }
+
+    let xpub = wallet.account_xpub()?;
+    let node = wallet.account_node_path()?;
+    let fingerprint = wallet.fingerprint()?;
+    let origin = format!("[{}{}]{}", fingerprint, node.to_string().trim_start_matches('m'), xpub);
+
+    // Levels below the account node; the one that changes between addresses 0 and 1 is the index
+    let (first, second) = (wallet.account_path(0)?, wallet.account_path(1)?);
+    let suffix: Vec<String> = first
+        .components()
+        .iter()
+        .zip(second.components())
+        .skip(node.depth())
+        .map(|(a, b)| if a == b { a.to_string() } else { "*".to_string() })
+        .collect();
+    let addresses = format!("{}/{}", node, suffix.join("/"));
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔑 Account extended public key");
+            println!("xpub:      {}", xpub);
+            println!("Path:      {}", node);
+            println!("Origin:    {}", origin);
+            println!("Addresses: {}", addresses);
+            println!("\nThe xpub reveals every address of this account but cannot sign.");
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "xpub": xpub,
+                "derivation_path": node,
+                "fingerprint": fingerprint,
+                "key_origin": origin,
+                "address_path": addresses
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute watch-only wallet commands
+async fn execute_watch(
+    args: WatchArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    match args.command {
+        WatchCommands::Add { name, xpub, address, path_preset, origin, network, alias } => {
+            let mut wallet = match (xpub, address) {
+                (Some(xpub), _) => WatchOnlyWallet::from_xpub(&xpub, path_preset, origin, &network)?,
+                (None, Some(address)) => WatchOnlyWallet::from_address(&resolve_address(&address, config)?.to_string(), &network)?,
+                (None, None) => {
+                    return Err(WalletError::UserInput(UserInputError::MissingParameter {
+                        parameter: "xpub or address".to_string(),
+                        hint: "Pass --xpub <XPUB> or --address <ADDRESS>".to_string(),
+                    }));
+                }
+            };
+            wallet.set_alias(alias);
+
+            let file_path = config.wallet_dir.join(format!("{}.json", name));
+            wallet.save(&file_path)?;
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n👁️  Watch-only wallet saved to: {}", file_path.display());
+                    println!("Address: {}", wallet.address());
+                    println!("Network: {}", wallet.network());
+                    println!("\nThis wallet holds no private keys and cannot sign.");
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "success": true,
+                        "file": file_path.display().to_string(),
+                        "address": wallet.address(),
+                        "network": wallet.network(),
+                        "source": wallet.source()
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        WatchCommands::Derive { filename, start_index, count } => {
+            let wallet = WatchOnlyWallet::load(&wallet_file_path(&filename, config))?;
+            let end = start_index.checked_add(count).ok_or_else(|| UserInputError::ValueOutOfRange {
+                parameter: "count".to_string(),
+                value: count.to_string(),
+                range: format!("at most {} from index {}", u32::MAX - start_index, start_index),
+            })?;
+
+            let mut addresses = Vec::new();
+            for index in start_index..end {
+                addresses.push((index, wallet.derive_address(index)?, wallet.address_path(index)?));
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n👁️  Receive addresses ({}):", wallet.network());
+                    for (index, address, path) in &addresses {
+                        match path {
+                            Some(path) => println!("{:>4}  {}  {}", index, address, path),
+                            None => println!("{:>4}  {}", index, address),
+                        }
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let list: Vec<_> = addresses.iter().map(|(index, address, path)| {
+                        config.address_case.scope(|| serde_json::json!({
+                            "index": index,
+                            "address": address,
+                            "derivation_path": path
+                        }))
+                    }).collect();
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "network": wallet.network(),
+                        "addresses": list
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+        WatchCommands::Balance { filename, count } => {
+            use ethers::providers::{Http, Middleware, Provider};
+
+            let wallet = WatchOnlyWallet::load(&wallet_file_path(&filename, config))?;
+            let rpc_url = config.rpc_endpoint(wallet.network().name()).ok_or_else(|| NetworkError::InvalidConfiguration {
+                key: format!("rpc_endpoints.{}", wallet.network()),
+                details: "No RPC endpoint configured for balance lookups".to_string(),
+            })?;
+            let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+                key: "rpc_endpoints".to_string(),
+                details: e.to_string(),
+            })?;
+
+            let mut balances = Vec::new();
+            for index in 0..count.max(1) {
+                let address = wallet.derive_address(index)?;
+                let parsed: ethers::types::Address = address.parse().map_err(|_| ValidationError::InvalidAddressFormat {
+                    address: address.clone(),
+                    expected: "0x-prefixed 20-byte hex".to_string(),
+                })?;
+                let balance = provider.get_balance(parsed, None).await.map_err(|e| NetworkError::ConnectivityFailure {
+                    endpoint: rpc_url.to_string(),
+                    details: e.to_string(),
+                })?;
+                balances.push((index, address, balance));
+            }
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n💰 Balances ({}):", wallet.network());
+                    for (index, address, balance) in &balances {
+                        println!("{:>4}  {}  {} ETH", index, address, ethers::utils::format_ether(*balance));
+                    }
+                }
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let list: Vec<_> = balances.iter().map(|(index, address, balance)| {
+                        config.address_case.scope(|| serde_json::json!({
+                            "index": index,
+                            "address": address,
+                            "balance_wei": balance.to_string()
+                        }))
+                    }).collect();
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "network": wallet.network(),
+                        "balances": list
+                    }));
+                    print_json(&output, json, config.address_case)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
 
-    // Construct file path
-    let file_path = if args.filename.contains('/') || args.filename.contains('\\') {
-        PathBuf::from(&args.filename)
+/// Execute gap-limit address scanning
+async fn execute_scan(
+    args: ScanArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    use ethers::providers::{Http, Provider};
+
+    let file_path = wallet_file_path(&args.from_file, config);
+
+    // Watch-only files scan without a password
+    let contents = std::fs::read_to_string(&file_path).unwrap_or_default();
+    let (network, derive): (String, Box<dyn Fn(u32) -> WalletResult<String>>) = if WatchOnlyWallet::is_watch_only(&contents) {
+        let wallet = WatchOnlyWallet::from_json(&contents)?;
+        (wallet.network().to_string(), Box::new(move |index| wallet.derive_address(index)))
     } else {
-        config.wallet_dir.join(&args.filename)
+        let manager = WalletManager::new(config.clone());
+        let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+        unlock_passphrase(&mut wallet, &args.passphrase)?;
+        if let Some(account) = args.account.filter(|&account| account != wallet.account()) {
+            wallet.set_account(account)?;
+        }
+        (wallet.network().to_string(), Box::new(move |index| Ok(wallet.derive_address(index)?.address().to_string())))
     };
 
-    info!("Loading wallet from: {}", file_path.display());
+    let rpc_url = config.rpc_endpoint(&network).ok_or_else(|| NetworkError::InvalidConfiguration {
+        key: format!("rpc_endpoints.{}", network),
+        details: "No RPC endpoint configured for the address scan".to_string(),
+    })?;
+    let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+        key: "rpc_endpoints".to_string(),
+        details: e.to_string(),
+    })?;
 
-    let wallet = if args.address_only {
-        // Load keystore without decryption for address only
-        let keystore = web3wallet_cli::services::CryptoService::load_keystore(&file_path).await?;
+    info!("Scanning from index {} with gap limit {}", args.start_index, args.gap);
+    let scan = GapScanService::scan(&provider, rpc_url, derive, args.start_index, args.gap).await?;
 
-        match output {
-            OutputFormat::Table => {
-                println!("\n📁 Wallet file: {}", file_path.display());
-                println!("Address:  {}", keystore.metadata.address);
-                println!("Network:  {}", keystore.metadata.network);
-                println!("Created:  {}", keystore.metadata.created_at);
-                if let Some(alias) = &keystore.metadata.alias {
-                    println!("Alias:    {}", alias);
+    match output {
+        OutputFormat::Table => {
+            println!("\n🔎 Scanned {} address(es) on {}, {} used", scan.scanned, network, scan.used.len());
+            if !scan.used.is_empty() {
+                println!("{:>6}  {:<44} {:<24} {:<6}", "INDEX", "ADDRESS", "BALANCE (ETH)", "SENT");
+                for activity in &scan.used {
+                    println!("{:>6}  {:<44} {:<24} {:<6}",
+                        activity.index,
+                        activity.address,
+                        ethers::utils::format_ether(activity.balance),
+                        activity.transactions);
+                }
+            }
+            println!("\nNext unused index: {}", scan.next_index);
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let used: Vec<_> = scan.used.iter().map(|activity| {
+                config.address_case.scope(|| serde_json::json!({
+                    "index": activity.index,
+                    "address": activity.address,
+                    "balance_wei": activity.balance.to_string(),
+                    "transactions": activity.transactions.to_string()
+                }))
+            }).collect();
+            let output = config.address_case.scope(|| serde_json::json!({
+                "network": network,
+                "gap": args.gap,
+                "scanned": scan.scanned,
+                "next_index": scan.next_index,
+                "used": used
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute keystore compaction
+async fn execute_compact(
+    args: CompactArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let file_path = wallet_file_path(&args.filename, config);
+
+    let password = keystore_password(&file_path, config)?;
+    let report = CryptoService::compact_keystore(&file_path, &password).await?;
+    if report.rewritten {
+        ChecksumManifest::record_file(&file_path)?;
+    }
+    let saved = report.size_before as i64 - report.size_after as i64;
+
+    match output {
+        OutputFormat::Table => {
+            if report.rewritten {
+                println!("\n🗜️  Keystore compacted: {}", file_path.display());
+                println!("Size:  {} → {} bytes ({} saved)", report.size_before, report.size_after, saved);
+                if report.hex_normalized {
+                    println!("Hex fields normalized to lowercase without 0x");
                 }
+                println!("Verified: decrypts before and after");
+            } else {
+                println!("\n✅ Keystore already canonical: {}", file_path.display());
             }
-            OutputFormat::Json => {
-                let output = serde_json::json!({
-                    "file": file_path.display().to_string(),
-                    "address": keystore.metadata.address,
-                    "network": keystore.metadata.network,
-                    "created_at": keystore.metadata.created_at,
-                    "alias": keystore.metadata.alias
-                });
-                println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "success": true,
+                "file": file_path.display().to_string(),
+                "rewritten": report.rewritten,
+                "hex_normalized": report.hex_normalized,
+                "size_before": report.size_before,
+                "size_after": report.size_after,
+                "bytes_saved": saved
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute secure deletion
+async fn execute_shred(
+    args: ShredArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let paths: Vec<PathBuf> = args.files.iter().map(|file| wallet_file_path(file, config)).collect();
+    for path in &paths {
+        if !path.is_file() {
+            return Err(FileSystemError::FileNotFound {
+                path: path.display().to_string(),
+                directory: path.parent().map(|p| p.display().to_string()).unwrap_or_else(|| ".".to_string()),
             }
+            .into());
         }
-        return Ok(());
-    } else {
-        // Load and decrypt wallet
-        let password = prompt_password("Enter wallet password: ")?;
-        manager.load_wallet(&file_path, &password).await?
-    };
+    }
+
+    if !args.yes {
+        if password::is_non_interactive() {
+            return Err(password::prompt_refused("Shred these files? [y/N]").into());
+        }
+        eprintln!("\n🗑️  These files will be overwritten and deleted for good:");
+        for path in &paths {
+            eprintln!("   {}", path.display());
+        }
+        eprint!("\nShred these files? [y/N] ");
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                parameter: "confirmation".to_string(),
+                value: answer.trim().to_string(),
+                expected: "y to shred".to_string(),
+            }));
+        }
+    }
+
+    let mut reports = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let report = shred::shred_file(path, args.passes)?;
+        ChecksumManifest::forget_file(path)?;
+        audit(config, AuditAction::Delete, "shred", Some(path), None, Some(format!("{} passes", report.passes)))?;
+        reports.push(report);
+    }
 
-    // Display wallet information
     match output {
         OutputFormat::Table => {
-            println!("\n🔓 Wallet loaded successfully!");
-            println!("Address:  {}", wallet.address());
-            println!("Network:  {}", wallet.network());
-            if wallet.has_mnemonic() {
-                println!("Type:     HD Wallet (BIP44)");
-            } else {
-                println!("Type:     Private Key Only");
-            }
-            if let Some(alias) = wallet.alias() {
-                println!("Alias:    {}", alias);
+            for report in &reports {
+                println!("🗑️  Shredded {} ({} bytes, {} passes)", report.path.display(), report.size, report.passes);
             }
-            println!("Created:  {}", wallet.created_at().format("%Y-%m-%d %H:%M:%S UTC"));
+            println!("\nOn SSDs and copy-on-write filesystems old copies may survive; see `wallet shred --help`.");
         }
-        OutputFormat::Json => {
-            let output = serde_json::json!({
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let files: Vec<_> = reports.iter().map(|report| {
+                config.address_case.scope(|| serde_json::json!({
+                    "file": report.path.display().to_string(),
+                    "size": report.size,
+                    "passes": report.passes
+                }))
+            }).collect();
+            let output = config.address_case.scope(|| serde_json::json!({
                 "success": true,
-                "address": wallet.address(),
-                "network": wallet.network(),
-                "has_mnemonic": wallet.has_mnemonic(),
-                "derivation_path": wallet.derivation_path(),
-                "alias": wallet.alias(),
-                "created_at": wallet.created_at()
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+                "files": files
+            }));
+            print_json(&output, json, config.address_case)?;
         }
     }
 
-    // Derive specific address if requested
-    if let Some(index) = args.derive {
-        if !wallet.has_mnemonic() {
-            return Err(WalletError::UserInput(
-                UserInputError::InvalidParameters {
-                    parameter: "derive".to_string(),
-                    value: index.to_string(),
-                    expected: "HD wallet with mnemonic".to_string(),
-                }
-            ));
+    Ok(())
+}
+
+/// Execute vanity address search
+async fn execute_vanity(
+    args: VanityArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let pattern = VanityPattern::new(args.prefix.as_deref(), args.suffix.as_deref(), args.case_sensitive)?;
+    let workers = args
+        .workers
+        .unwrap_or_else(|| web3wallet_cli::config::resources::parallelism(usize::MAX, config.is_low_resource()));
+
+    // A random key only exists in memory until saved, so take the password before searching
+    let (wallet, password) = match &args.from_file {
+        Some(filename) => {
+            let file_path = wallet_file_path(filename, config);
+            let password = keystore_password(&file_path, config)?;
+            let mut wallet = manager.load_wallet(&file_path, &password).await?;
+            unlock_passphrase(&mut wallet, &args.passphrase)?;
+            (Some(wallet), None)
         }
+        None => {
+            if args.save.is_none() && args.save_template.is_none() && config.save_template.is_none() {
+                return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                    parameter: "save".to_string(),
+                    value: String::new(),
+                    expected: "--save or --save-template for the key a random search finds".to_string(),
+                }));
+            }
+            let password = new_wallet_password("Enter password to encrypt wallet: ", config)?;
+            (None, Some(password))
+        }
+    };
 
-        let derived = wallet.derive_address(index)?;
+    eprintln!(
+        "🎯 Searching with {} worker(s), about {:.0} attempts expected",
+        workers.max(1),
+        pattern.difficulty()
+    );
+    let report = |progress: &VanityProgress| {
+        eprint!("\r   {} tried, {:.0}/s, {}s elapsed   ", progress.attempts, progress.rate(), progress.elapsed.as_secs());
+    };
+    let found = match &wallet {
+        Some(wallet) => VanityService::search_seed(
+            wallet,
+            wallet.path_preset(),
+            wallet.derivation_path(),
+            args.start_index,
+            &pattern,
+            workers,
+            report,
+        )?,
+        None => VanityService::search_random(&pattern, workers, report)?,
+    };
+    eprintln!();
 
-        match output {
-            OutputFormat::Table => {
-                println!("\n🔗 Derived address [{}]:", index);
-                println!("Address:  {}", derived.address());
-                println!("Path:     {}", derived.derivation_path());
+    let saved = match (password, &found.private_key) {
+        (Some(password), Some(private_key)) => {
+            let wallet = manager
+                .import_from_private_key(&zeroize::Zeroizing::new(hex::encode(private_key.as_ref())))
+                .await?;
+            let file_path = keystore_save_path(&wallet, args.save.as_deref(), args.save_template.as_deref(), config)?;
+            tokio::fs::create_dir_all(&config.wallet_dir).await.map_err(|e| {
+                WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
+                    path: config.wallet_dir.display().to_string(),
+                    details: e.to_string(),
+                })
+            })?;
+            manager.save_wallet(&wallet, &file_path, &password).await?;
+            audit(config, AuditAction::Create, "vanity", Some(&file_path), Some(wallet.address()), None)?;
+            Some(file_path)
+        }
+        _ => None,
+    };
+
+    match output {
+        OutputFormat::Table => {
+            println!("\n✅ Found {}", found.address);
+            if let Some(path) = &found.derivation_path {
+                println!("Path:     {}", path);
             }
-            OutputFormat::Json => {
-                let output = serde_json::json!({
-                    "derived": {
-                        "index": index,
-                        "address": derived.address(),
-                        "derivation_path": derived.derivation_path()
-                    }
-                });
-                println!("{}", serde_json::to_string_pretty(&output)?);
+            println!("Attempts: {} in {:.1}s ({:.0}/s)", found.progress.attempts, found.progress.elapsed.as_secs_f64(), found.progress.rate());
+            if let Some(file_path) = &saved {
+                println!("\n💾 Wallet saved to: {}", file_path.display());
             }
         }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "address": found.address,
+                "index": found.index,
+                "derivation_path": found.derivation_path,
+                "attempts": found.progress.attempts,
+                "elapsed_seconds": found.progress.elapsed.as_secs_f64(),
+                "saved_to": saved
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
     }
 
     Ok(())
 }
 
-/// Execute wallet list command
-async fn execute_list(
-    args: ListArgs,
+/// Execute air-gapped signing commands
+async fn execute_airgap(
+    args: AirgapArgs,
     config: &WalletConfig,
     output: OutputFormat,
 ) -> WalletResult<()> {
-    let wallet_dir = args.path.unwrap_or_else(|| config.wallet_dir.clone());
+    use web3wallet_cli::services::airgap::{self, EthSignRequest, EthSignature, SignDataType};
 
-    info!("Scanning wallet directory: {}", wallet_dir.display());
+    match args.command {
+        AirgapCommands::Request { from_file, index, path, tx, message, typed_data, chain_id, save, display } => {
+            let wallet = WatchOnlyWallet::load(&wallet_file_path(&from_file, config))?;
+            let address = wallet.derive_address(index)?;
+            let derivation_path = match path {
+                Some(path) => path,
+                None => wallet.address_path(index)?.ok_or_else(|| UserInputError::MissingParameter {
+                    parameter: "path".to_string(),
+                    hint: "This watch-only wallet has no account origin; pass the key's full path with --path".to_string(),
+                })?,
+            };
+            let signer = Some(parse_address(&address)?);
 
-    // Create directory if it doesn't exist
-    if !wallet_dir.exists() {
-        tokio::fs::create_dir_all(&wallet_dir).await.map_err(|e| {
-            WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
-                path: wallet_dir.display().to_string(),
-                details: e.to_string(),
-            })
-        })?;
+            let request = match (tx, message, typed_data) {
+                (Some(tx), _, _) => {
+                    let unsigned = hex::decode(tx.trim().trim_start_matches("0x")).map_err(|e| {
+                        UserInputError::InvalidParameters {
+                            parameter: "tx".to_string(),
+                            value: e.to_string(),
+                            expected: "hex-encoded unsigned transaction".to_string(),
+                        }
+                    })?;
+                    AirgapService::transaction_request(&unsigned, chain_id, derivation_path, signer, None)?
+                }
+                (None, Some(message), _) => AirgapService::message_request(
+                    message.as_bytes(),
+                    SignDataType::PersonalMessage,
+                    derivation_path,
+                    signer,
+                    None,
+                )?,
+                (None, None, Some(file)) => {
+                    let json = std::fs::read(&file)?;
+                    AirgapService::message_request(&json, SignDataType::TypedData, derivation_path, signer, None)?
+                }
+                (None, None, None) => {
+                    return Err(WalletError::UserInput(UserInputError::MissingParameter {
+                        parameter: "tx, message or typed-data".to_string(),
+                        hint: "Pass --tx <HEX>, --message <TEXT> or --typed-data <FILE>".to_string(),
+                    }));
+                }
+            };
 
-        match output {
-            OutputFormat::Table => {
-                println!("\n📂 Wallet directory: {}", wallet_dir.display());
-                println!("No wallets found. Directory created.");
+            let cbor = request.to_cbor().to_bytes();
+            std::fs::write(&save, format!("ur:{}/{}\n", airgap::SIGN_REQUEST_UR_TYPE, ur::bytewords_encode(&cbor)))?;
+            info!("Request saved to: {}", save.display());
+
+            eprintln!("📤 Sign request for {} ({}) at {}", address, request.data_type.name(), request.derivation_path);
+            show_ur(airgap::SIGN_REQUEST_UR_TYPE, cbor, &display, config, output)?;
+        }
+        AirgapCommands::Sign { from_file, parts, yes, passphrase, display } => {
+            let cbor = receive_ur(&parts, airgap::SIGN_REQUEST_UR_TYPE)?;
+            let request = EthSignRequest::from_cbor(&cbor)?;
+
+            eprintln!("\n📥 Sign request{}", request.origin.as_deref().map(|o| format!(" from {}", o)).unwrap_or_default());
+            eprintln!("Type:    {}", request.data_type.name());
+            eprintln!("Path:    {}", request.derivation_path);
+            if let Some(address) = request.address {
+                eprintln!("Address: {}", ethers::utils::to_checksum(&address, None));
             }
-            OutputFormat::Json => {
-                let output = serde_json::json!({
-                    "directory": wallet_dir.display().to_string(),
-                    "wallets": []
-                });
-                println!("{}", serde_json::to_string_pretty(&output)?);
+            describe_sign_data(&request)?;
+
+            if !yes {
+                if password::is_non_interactive() {
+                    return Err(password::prompt_refused("Sign this request? [y/N]").into());
+                }
+                eprint!("\nSign this request? [y/N] ");
+                let mut answer = String::new();
+                std::io::stdin().read_line(&mut answer)?;
+                if !answer.trim().eq_ignore_ascii_case("y") {
+                    return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+                        parameter: "confirmation".to_string(),
+                        value: answer.trim().to_string(),
+                        expected: "y to sign".to_string(),
+                    }));
+                }
             }
-        }
-        return Ok(());
-    }
 
-    // Read directory and find wallet files
-    let mut entries = tokio::fs::read_dir(&wallet_dir).await.map_err(|e| {
-        WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
-            path: wallet_dir.display().to_string(),
-            details: e.to_string(),
-        })
-    })?;
+            let manager = WalletManager::new(config.clone());
+            let file_path = wallet_file_path(&from_file, config);
+            let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+            unlock_passphrase(&mut wallet, &passphrase)?;
 
-    let mut wallets = Vec::new();
+            record_signature(&file_path, config)?;
+            let response = AirgapService::sign(&wallet, &request)?;
+            let signer = wallet.derive_at(&request.derivation_path)?;
+            audit(
+                config,
+                AuditAction::Sign,
+                "airgap sign",
+                Some(&file_path),
+                Some(signer.address()),
+                Some(format!("{} at {}", request.data_type.name(), request.derivation_path)),
+            )?;
 
-    while let Some(entry) = entries.next_entry().await.map_err(|e| {
-        WalletError::FileSystem(FileSystemError::DirectoryNotAccessible {
-            path: wallet_dir.display().to_string(),
-            details: e.to_string(),
-        })
-    })? {
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            // Try to load keystore metadata
-            match web3wallet_cli::services::CryptoService::load_keystore(&path).await {
-                Ok(keystore) => {
-                    wallets.push((path.clone(), keystore));
+            eprintln!("\n✍️  Signed; show this to the online device");
+            show_ur(airgap::SIGNATURE_UR_TYPE, response.to_cbor().to_bytes(), &display, config, output)?;
+        }
+        AirgapCommands::Finalize { request, parts } => {
+            let saved = std::fs::read_to_string(&request)?;
+            let cbor = receive_ur(&[saved.trim().to_string()], airgap::SIGN_REQUEST_UR_TYPE)?;
+            let request = EthSignRequest::from_cbor(&cbor)?;
+            let cbor = receive_ur(&parts, airgap::SIGNATURE_UR_TYPE)?;
+            let response = EthSignature::from_cbor(&cbor)?;
+
+            let signer = ethers::utils::to_checksum(&AirgapService::verify(&request, &response)?, None);
+            let is_transaction = matches!(request.data_type, SignDataType::Transaction | SignDataType::TypedTransaction);
+            let raw = match is_transaction {
+                true => Some(AirgapService::signed_transaction(&request, &response)?),
+                false => None,
+            };
+            let signature = format!("0x{}", hex::encode(response.signature.to_vec()));
+
+            match output {
+                OutputFormat::Table => {
+                    println!("\n✅ Signed by {}", signer);
+                    match &raw {
+                        Some(raw) => {
+                            println!("Transaction hash: 0x{}", hex::encode(ethers::utils::keccak256(raw)));
+                            println!("Raw transaction:  0x{}", hex::encode(raw));
+                        }
+                        None => println!("Signature: {}", signature),
+                    }
                 }
-                Err(_) => {
-                    // Skip invalid files
-                    continue;
+                json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+                    let output = config.address_case.scope(|| serde_json::json!({
+                        "signer": signer,
+                        "data_type": request.data_type.name(),
+                        "signature": signature,
+                        "transaction_hash": raw.as_ref().map(|raw| format!("0x{}", hex::encode(ethers::utils::keccak256(raw)))),
+                        "raw_transaction": raw.as_ref().map(|raw| format!("0x{}", hex::encode(raw)))
+                    }));
+                    print_json(&output, json, config.address_case)?;
                 }
             }
         }
     }
 
-    // Display results
-    match output {
-        OutputFormat::Table => {
-            println!("\n📂 Wallet directory: {}", wallet_dir.display());
-            println!("Found {} wallet(s):\n", wallets.len());
+    Ok(())
+}
 
-            if wallets.is_empty() {
-                println!("No wallets found.");
-            } else {
-                println!("{:<20} {:<44} {:<12} {:<20}",
-                    "FILENAME", "ADDRESS", "NETWORK", "CREATED");
-                println!("{}", "─".repeat(100));
+/// Parse a 0x-prefixed hex address
+fn parse_address(address: &str) -> WalletResult<ethers::types::H160> {
+    address.parse().map_err(|_| {
+        WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "address".to_string(),
+            value: address.to_string(),
+            expected: "a 20-byte hex address".to_string(),
+        })
+    })
+}
 
-                for (path, keystore) in wallets {
-                    let filename = path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unknown");
-                    let short_addr = if keystore.metadata.address.len() >= 42 {
-                        format!("{}...{}",
-                            &keystore.metadata.address[..6],
-                            &keystore.metadata.address[38..])
-                    } else {
-                        keystore.metadata.address.clone()
-                    };
+/// Show what a sign request asks for on stderr, ahead of the confirmation
+fn describe_sign_data(request: &web3wallet_cli::services::airgap::EthSignRequest) -> WalletResult<()> {
+    use web3wallet_cli::services::airgap::SignDataType;
 
-                    println!("{:<20} {:<44} {:<12} {:<20}",
-                        filename,
-                        short_addr,
-                        keystore.metadata.network,
-                        keystore.metadata.created_at[..19].replace('T', " ")
-                    );
-                }
+    match request.data_type {
+        SignDataType::Transaction | SignDataType::TypedTransaction => {
+            let tx = request.transaction()?;
+            match tx.chain_id() {
+                Some(chain_id) => eprintln!("Chain:   {}", chain_id),
+                None => eprintln!("Chain:   none (⚠️  replayable on every chain)"),
+            }
+            match tx.to_addr() {
+                Some(to) => eprintln!("To:      {}", ethers::utils::to_checksum(to, None)),
+                None => eprintln!("To:      (contract creation)"),
+            }
+            let value = tx.value().copied().unwrap_or_default();
+            eprintln!("Value:   {} ETH", ethers::utils::format_ether(value));
+            if let Some(nonce) = tx.nonce() {
+                eprintln!("Nonce:   {}", nonce);
+            }
+            if let Some(gas) = tx.gas() {
+                eprintln!("Gas:     {}", gas);
+            }
+            if let Some(data) = tx.data().filter(|data| !data.is_empty()) {
+                eprintln!("Data:    {} bytes, selector 0x{}", data.len(), hex::encode(data.get(..4).unwrap_or(data)));
             }
         }
-        OutputFormat::Json => {
-            let wallet_list: Vec<_> = wallets.into_iter().map(|(path, keystore)| {
-                serde_json::json!({
-                    "filename": path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown"),
-                    "path": path.display().to_string(),
-                    "address": keystore.metadata.address,
-                    "network": keystore.metadata.network,
-                    "created_at": keystore.metadata.created_at,
-                    "alias": keystore.metadata.alias
-                })
-            }).collect();
+        SignDataType::PersonalMessage => match std::str::from_utf8(&request.sign_data) {
+            Ok(text) => eprintln!("Message: {}", text),
+            Err(_) => eprintln!("Message: 0x{}", hex::encode(&request.sign_data)),
+        },
+        SignDataType::TypedData => {
+            let typed_data = request.typed_data()?;
+            eprintln!("Domain:  {}", typed_data.domain.name.as_deref().unwrap_or("(unnamed)"));
+            eprintln!("Primary: {}", typed_data.primary_type);
+            eprintln!("Message: {}", serde_json::to_string_pretty(&typed_data.message)?);
+        }
+    }
+    Ok(())
+}
 
-            let output = serde_json::json!({
-                "directory": wallet_dir.display().to_string(),
-                "count": wallet_list.len(),
-                "wallets": wallet_list
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+/// Collect UR parts from the arguments, or from standard input lines, until the UR is complete
+fn receive_ur(parts: &[String], expected_type: &str) -> WalletResult<ur::Cbor> {
+    let mut decoder = ur::UrDecoder::new();
+    if parts.is_empty() {
+        eprintln!("Paste the {} parts, one per line:", expected_type);
+        let mut line = String::new();
+        let mut complete = false;
+        while !complete && std::io::stdin().read_line(&mut line)? > 0 {
+            if !line.trim().is_empty() {
+                complete = decoder.receive(&line)?;
+                eprint!("\r   {:.0}% received ", decoder.progress() * 100.0);
+            }
+            line.clear();
+        }
+        eprintln!();
+    } else {
+        for part in parts {
+            decoder.receive(part)?;
         }
     }
 
+    let (ur_type, payload) = decoder.result().ok_or_else(|| UserInputError::MissingParameter {
+        parameter: "UR parts".to_string(),
+        hint: format!("{:.0}% of the {} was received; scan more parts", decoder.progress() * 100.0, expected_type),
+    })?;
+    if ur_type != expected_type {
+        return Err(WalletError::UserInput(UserInputError::InvalidParameters {
+            parameter: "UR type".to_string(),
+            value: ur_type.to_string(),
+            expected: expected_type.to_string(),
+        }));
+    }
+    ur::Cbor::from_bytes(payload)
+}
+
+/// Show a UR as text parts, a JSON list of parts, or an animated QR code
+fn show_ur(ur_type: &str, cbor: Vec<u8>, display: &UrDisplayArgs, config: &WalletConfig, output: OutputFormat) -> WalletResult<()> {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use web3wallet_cli::services::qr::{QrCode, QrEcc};
+
+    let mut encoder = ur::UrEncoder::new(ur_type, cbor, display.fragment_len)?;
+    // The pure fragments alone are enough when nothing gets lost, as with copied text
+    let parts: Vec<String> = (0..encoder.fragment_count()).map(|_| encoder.next_part()).collect();
+
+    match output {
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "ur_type": ur_type,
+                "parts": parts
+            }));
+            return print_json(&output, json, config.address_case);
+        }
+        OutputFormat::Table if display.text || password::is_non_interactive() => {
+            for part in &parts {
+                println!("{}", part);
+            }
+            return Ok(());
+        }
+        OutputFormat::Table => {}
+    }
+
+    if encoder.is_single_part() {
+        print!("{}", QrCode::encode(&parts[0].to_uppercase(), QrEcc::Medium)?.render_terminal(2));
+        return Ok(());
+    }
+
+    // Cycle frames until Enter; the fountain parts after the first round cover frames the scanner missed
+    static DONE: AtomicBool = AtomicBool::new(false);
+    std::thread::spawn(|| {
+        let mut line = String::new();
+        let _ = std::io::stdin().read_line(&mut line);
+        DONE.store(true, Ordering::Relaxed);
+    });
+    eprintln!("Scan the animated QR code ({} fragments), then press Enter", encoder.fragment_count());
+
+    let mut frames = parts.into_iter();
+    let mut height = 0;
+    while !DONE.load(Ordering::Relaxed) {
+        let part = frames.next().unwrap_or_else(|| encoder.next_part());
+        let frame = QrCode::encode(&part.to_uppercase(), QrEcc::Low)?.render_terminal(2);
+        let mut stdout = std::io::stdout().lock();
+        if height > 0 {
+            // Back to the top of the previous frame and clear it, in case this one is smaller
+            write!(stdout, "\x1b[{}A\x1b[J", height)?;
+        }
+        write!(stdout, "{}", frame)?;
+        stdout.flush()?;
+        height = frame.lines().count();
+        std::thread::sleep(std::time::Duration::from_millis(display.interval_ms));
+    }
     Ok(())
 }
 
-/// Execute address derivation command
-async fn execute_derive(
-    args: DeriveArgs,
+/// Execute Bitcoin address derivation
+#[cfg(feature = "btc")]
+async fn execute_btc(
+    args: BtcArgs,
     config: &WalletConfig,
     output: OutputFormat,
 ) -> WalletResult<()> {
     let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.filename, config);
 
-    // Load wallet if file is specified
-    let wallet = if let Some(filename) = args.from_file {
-        let file_path = if filename.contains('/') || filename.contains('\\') {
-            PathBuf::from(&filename)
-        } else {
-            config.wallet_dir.join(&filename)
-        };
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
 
-        let password = prompt_password("Enter wallet password: ")?;
-        manager.load_wallet(&file_path, &password).await?
-    } else {
-        // Prompt for mnemonic
-        let mnemonic = prompt_password("Enter mnemonic phrase: ")?;
-        manager.import_from_mnemonic(&mnemonic).await?
-    };
+    let network = BtcNetwork::for_wallet_network(wallet.network());
+    let account_path = BtcService::account_path(args.scheme, network, args.account)?;
+    let addresses = BtcService::derive_addresses(
+        &wallet,
+        args.scheme,
+        network,
+        args.account,
+        args.change,
+        args.start_index,
+        args.count,
+    )?;
 
-    if !wallet.has_mnemonic() {
-        return Err(WalletError::UserInput(
-            UserInputError::InvalidParameters {
-                parameter: "wallet".to_string(),
-                value: "private key only".to_string(),
-                expected: "HD wallet with mnemonic".to_string(),
+    match output {
+        OutputFormat::Table => {
+            println!("\n₿ Bitcoin {} addresses ({})", if args.change { "change" } else { "receive" }, network);
+            println!("Scheme:   {}", args.scheme);
+            println!("Account:  {}\n", account_path);
+
+            println!("{:<6} {:<44} {:<30}", "INDEX", "ADDRESS", "DERIVATION PATH");
+            println!("{}", "─".repeat(85));
+            for address in &addresses {
+                println!("{:<6} {:<44} {:<30}", address.index, address.address, address.derivation_path);
             }
-        ));
+        }
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
+                "network": network,
+                "scheme": args.scheme,
+                "account_path": account_path,
+                "change": args.change,
+                "addresses": addresses
+            }));
+            print_json(&output, json, config.address_case)?;
+        }
     }
 
-    // Parse derivation path or index
-    let start_index = if args.path.parse::<u32>().is_ok() {
-        // Path is a simple index
-        args.path.parse::<u32>().unwrap()
-    } else {
-        // TODO: Parse full derivation path - for now just use start_index
-        args.start_index
-    };
+    Ok(())
+}
+
+/// Execute Solana address derivation
+#[cfg(feature = "solana")]
+async fn execute_solana(
+    args: SolanaArgs,
+    config: &WalletConfig,
+    output: OutputFormat,
+) -> WalletResult<()> {
+    let manager = WalletManager::new(config.clone());
+    let file_path = wallet_file_path(&args.filename, config);
 
-    let mut derived_addresses = Vec::new();
+    let mut wallet = unlock_wallet(&manager, &file_path, config).await?;
+    unlock_passphrase(&mut wallet, &args.passphrase)?;
 
-    // Derive addresses
-    for i in 0..args.count {
-        let index = start_index + i;
-        let derived = wallet.derive_address(index)?;
-        derived_addresses.push((index, derived));
-    }
+    let addresses = SolanaService::derive_addresses(&wallet, args.start_index, args.count)?;
 
-    // Display results
     match output {
         OutputFormat::Table => {
-            println!("\n🔗 Derived addresses from HD wallet:");
-            println!("Base address: {}", wallet.address());
-            println!("Base path:    {}\n", wallet.derivation_path());
-
-            println!("{:<6} {:<44} {:<30}",
-                "INDEX", "ADDRESS", "DERIVATION PATH");
-            println!("{}", "─".repeat(85));
+            println!("\n◎ Solana addresses");
+            println!("Path:     m/44'/501'/<account>'/0'\n");
 
-            for (index, derived) in derived_addresses {
-                println!("{:<6} {:<44} {:<30}",
-                    index,
-                    derived.address(),
-                    derived.derivation_path()
-                );
+            println!("{:<8} {:<44} {:<24}", "ACCOUNT", "ADDRESS", "DERIVATION PATH");
+            println!("{}", "─".repeat(78));
+            for address in &addresses {
+                println!("{:<8} {:<44} {:<24}", address.index, address.address, address.derivation_path);
             }
         }
-        OutputFormat::Json => {
-            let addresses: Vec<_> = derived_addresses.into_iter().map(|(index, derived)| {
-                serde_json::json!({
-                    "index": index,
-                    "address": derived.address(),
-                    "derivation_path": derived.derivation_path()
-                })
-            }).collect();
-
-            let output = serde_json::json!({
-                "base_address": wallet.address(),
-                "base_path": wallet.derivation_path(),
-                "count": args.count,
-                "start_index": start_index,
+        json @ (OutputFormat::Json | OutputFormat::Ndjson) => {
+            let output = config.address_case.scope(|| serde_json::json!({
                 "addresses": addresses
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            }));
+            print_json(&output, json, config.address_case)?;
         }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
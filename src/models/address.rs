@@ -1,22 +1,180 @@
 //! # Address Model
 //!
 //! Ethereum address representation with validation and metadata.
+//!
+//! [`EthereumAddress`] is parsed and validated once, at the edge; every
+//! model holds the 20 bytes rather than a string. It displays with its
+//! EIP-55 checksum and serializes as lowercase hex, the form keystores
+//! have always been written in. Output meant for people can be serialized
+//! inside [`AddressCase::scope`] to write its addresses in another case;
+//! files never are.
+
+use crate::errors::{UserInputError, ValidationError, WalletResult};
+use crate::models::{DerivationPath, Network};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+use std::path::Path;
+use std::str::FromStr;
+
+thread_local! {
+    /// Case addresses serialize in on this thread, set only inside [`AddressCase::scope`]
+    static SERIALIZE_CASE: Cell<AddressCase> = const { Cell::new(AddressCase::Lowercase) };
+}
+
+/// How addresses are shown in output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressCase {
+    /// All-lowercase hex
+    Lowercase,
+    /// EIP-55 mixed-case checksum
+    #[default]
+    Checksum,
+}
+
+impl AddressCase {
+    /// `address` written in this case
+    pub fn format(self, address: &EthereumAddress) -> String {
+        match self {
+            Self::Lowercase => address.to_lowercase(),
+            Self::Checksum => address.to_checksum(),
+        }
+    }
+
+    /// Run `serialize` with every [`EthereumAddress`] it serializes written in this case
+    ///
+    /// The case applies to this thread until `serialize` returns, so wrap
+    /// only the serialization of output, never code that writes files.
+    pub fn scope<R>(self, serialize: impl FnOnce() -> R) -> R {
+        struct Restore(AddressCase);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SERIALIZE_CASE.with(|case| case.set(self.0));
+            }
+        }
+
+        let _restore = Restore(SERIALIZE_CASE.with(|case| case.replace(self)));
+        serialize()
+    }
+}
+
+/// A 20-byte Ethereum account address
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EthereumAddress([u8; 20]);
+
+impl EthereumAddress {
+    /// Address with the given bytes
+    pub const fn from_bytes(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    /// The address bytes
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// EIP-55 mixed-case form, as shown by `Display`
+    pub fn to_checksum(&self) -> String {
+        ethers::utils::to_checksum(&ethers::types::H160(self.0), None)
+    }
+
+    /// Lowercase `0x`-prefixed form, as serialized
+    pub fn to_lowercase(&self) -> String {
+        format!("0x{}", hex::encode(self.0))
+    }
+
+    fn invalid(address: &str, expected: &str) -> crate::WalletError {
+        ValidationError::InvalidAddressFormat {
+            address: address.to_string(),
+            expected: expected.to_string(),
+        }
+        .into()
+    }
+}
+
+impl FromStr for EthereumAddress {
+    type Err = crate::WalletError;
+
+    /// Parse 40 hex digits with or without `0x`; mixed case must be a valid EIP-55 checksum
+    fn from_str(address: &str) -> WalletResult<Self> {
+        crate::utils::validate_ethereum_address(address)?;
+        let digits = address.strip_prefix("0x").unwrap_or(address);
+        let mut bytes = [0u8; 20];
+        hex::decode_to_slice(digits, &mut bytes)
+            .map_err(|_| Self::invalid(address, "hexadecimal characters only"))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl std::fmt::Display for EthereumAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&self.to_checksum())
+    }
+}
+
+impl std::fmt::Debug for EthereumAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EthereumAddress({})", self.to_checksum())
+    }
+}
+
+impl From<ethers::types::H160> for EthereumAddress {
+    fn from(address: ethers::types::H160) -> Self {
+        Self(address.0)
+    }
+}
+
+impl From<EthereumAddress> for ethers::types::H160 {
+    fn from(address: EthereumAddress) -> Self {
+        Self(address.0)
+    }
+}
+
+/// Compares with a hex string regardless of case; malformed strings are never equal
+impl PartialEq<str> for EthereumAddress {
+    fn eq(&self, other: &str) -> bool {
+        let digits = other.strip_prefix("0x").or_else(|| other.strip_prefix("0X")).unwrap_or(other);
+        digits.len() == 40 && digits.eq_ignore_ascii_case(&hex::encode(self.0))
+    }
+}
+
+impl PartialEq<&str> for EthereumAddress {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
 
-use crate::config;
-use crate::errors::{ValidationError, WalletResult};
-use serde::{Deserialize, Serialize};
+impl PartialEq<String> for EthereumAddress {
+    fn eq(&self, other: &String) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl Serialize for EthereumAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&SERIALIZE_CASE.with(Cell::get).format(self))
+    }
+}
+
+impl<'de> Deserialize<'de> for EthereumAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        address.parse().map_err(serde::de::Error::custom)
+    }
+}
 
 /// Ethereum address with metadata
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Address {
-    /// Ethereum address (42 characters with 0x prefix)
-    address: String,
+    /// Ethereum address
+    address: EthereumAddress,
 
     /// Derivation index (for HD wallets)
     index: Option<u32>,
 
     /// Full HD derivation path
-    derivation_path: Option<String>,
+    derivation_path: Option<DerivationPath>,
 
     /// Optional cached balance (in wei)
     balance: Option<String>,
@@ -28,36 +186,21 @@ pub struct Address {
     label: Option<String>,
 
     /// Network this address belongs to
-    network: String,
+    network: Network,
 }
 
 impl Address {
     /// Create a new address
     pub fn new(
         address: String,
-        network: String,
+        network: Network,
         index: Option<u32>,
-        derivation_path: Option<String>,
+        derivation_path: Option<DerivationPath>,
     ) -> WalletResult<Self> {
-        // Validate address format
-        crate::utils::validate_ethereum_address(&address)?;
-
-        // Validate network
-        if !config::is_supported_network(&network) {
-            return Err(ValidationError::InvalidAddressFormat {
-                address: network.clone(),
-                expected: format!("one of: {:?}", config::SUPPORTED_NETWORKS),
-            }
-            .into());
-        }
-
-        // Validate derivation path if provided
-        if let Some(ref path) = derivation_path {
-            crate::utils::validate_derivation_path(path)?;
-        }
+        let address: EthereumAddress = address.parse()?;
 
         Ok(Self {
-            address: address.to_lowercase(),
+            address,
             index,
             derivation_path,
             balance: None,
@@ -69,15 +212,15 @@ impl Address {
 
     /// Create from string address
     pub fn from_string(address: &str, network: &str) -> WalletResult<Self> {
-        Self::new(address.to_string(), network.to_string(), None, None)
+        Self::new(address.to_string(), network.parse()?, None, None)
     }
 
     /// Create derived address
     pub fn derived(
         address: String,
-        network: String,
+        network: Network,
         index: u32,
-        derivation_path: String,
+        derivation_path: DerivationPath,
     ) -> WalletResult<Self> {
         Self::new(
             address,
@@ -88,16 +231,13 @@ impl Address {
     }
 
     /// Get the Ethereum address
-    pub fn address(&self) -> &str {
+    pub fn address(&self) -> &EthereumAddress {
         &self.address
     }
 
-    /// Get the checksummed address (proper capitalization)
+    /// Get the EIP-55 checksummed address
     pub fn checksummed_address(&self) -> String {
-        // Simple checksum implementation
-        // In production, use ethers::utils::to_checksum for proper EIP-55
-        let addr = self.address.strip_prefix("0x").unwrap_or(&self.address);
-        format!("0x{}", addr)
+        self.address.to_checksum()
     }
 
     /// Get derivation index
@@ -106,12 +246,12 @@ impl Address {
     }
 
     /// Get derivation path
-    pub fn derivation_path(&self) -> Option<&str> {
-        self.derivation_path.as_deref()
+    pub fn derivation_path(&self) -> Option<&DerivationPath> {
+        self.derivation_path.as_ref()
     }
 
     /// Get network
-    pub fn network(&self) -> &str {
+    pub fn network(&self) -> &Network {
         &self.network
     }
 
@@ -150,33 +290,16 @@ impl Address {
         self.index.is_some() && self.derivation_path.is_some()
     }
 
-    /// Get short address for display (first 6 + last 4 chars)
+    /// Get short address for display (first 6 + last 4 chars of the checksummed form)
     pub fn short_address(&self) -> String {
-        if self.address.len() >= 42 {
-            format!("{}...{}", &self.address[..6], &self.address[38..])
-        } else {
-            self.address.clone()
-        }
+        let address = self.address.to_checksum();
+        format!("{}...{}", &address[..6], &address[38..])
     }
 
     /// Validate address format and consistency
     pub fn validate(&self) -> WalletResult<()> {
-        // Validate address format
-        crate::utils::validate_ethereum_address(&self.address)?;
-
-        // Validate network
-        if !config::is_supported_network(&self.network) {
-            return Err(ValidationError::InvalidAddressFormat {
-                address: self.network.clone(),
-                expected: format!("one of: {:?}", config::SUPPORTED_NETWORKS),
-            }
-            .into());
-        }
-
         // Validate derivation path consistency
         if let Some(ref path) = self.derivation_path {
-            crate::utils::validate_derivation_path(path)?;
-
             // If we have a path, we should have an index
             if self.index.is_none() {
                 return Err(ValidationError::IntegrityCheckFailed {
@@ -188,7 +311,7 @@ impl Address {
 
             // Verify index matches path
             if let Some(index) = self.index {
-                if !path.ends_with(&format!("/{}", index)) {
+                if path.last().map(|child| child.index()) != Some(index) {
                     return Err(ValidationError::IntegrityCheckFailed {
                         data_type: "address".to_string(),
                         details: format!(
@@ -199,6 +322,20 @@ impl Address {
                     .into());
                 }
             }
+
+            // BIP44 paths sit on the external (0) or internal change (1) chain
+            if let Some(chain) = path.chain() {
+                if chain.is_hardened() || chain.index() > 1 {
+                    return Err(ValidationError::IntegrityCheckFailed {
+                        data_type: "address".to_string(),
+                        details: format!(
+                            "Chain {} of derivation path {} is neither external (0) nor change (1)",
+                            chain, path
+                        ),
+                    }
+                    .into());
+                }
+            }
         }
 
         Ok(())
@@ -206,7 +343,7 @@ impl Address {
 
     /// Compare addresses (case-insensitive)
     pub fn equals(&self, other: &str) -> bool {
-        self.address.to_lowercase() == other.to_lowercase()
+        self.address == other
     }
 }
 
@@ -224,22 +361,57 @@ impl std::fmt::Display for Address {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddressBook {
     addresses: Vec<Address>,
-    default_network: String,
+    default_network: Network,
 }
 
 impl AddressBook {
     /// Create a new address book
-    pub fn new(default_network: String) -> Self {
+    pub fn new(default_network: Network) -> Self {
         Self {
             addresses: Vec::new(),
             default_network,
         }
     }
 
+    /// Load the book at `path`, starting empty when none exists
+    pub fn load(path: &Path) -> WalletResult<Self> {
+        if !path.exists() {
+            return Ok(Self::new(Network::Mainnet));
+        }
+
+        let book: Self = serde_json::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "address_book".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        book.validate()?;
+        Ok(book)
+    }
+
+    /// Write the book to `path`
+    pub fn save(&self, path: &Path) -> WalletResult<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(path, json.as_bytes(), crate::config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+
     /// Add an address
     pub fn add(&mut self, address: Address) -> WalletResult<()> {
         // Validate before adding
         address.validate()?;
+        if let Some(label) = address.label() {
+            Self::validate_label(label)?;
+            if self.find_by_label(label).is_some() {
+                return Err(ValidationError::IntegrityCheckFailed {
+                    data_type: "address_book".to_string(),
+                    details: format!("Label '{}' already exists", label),
+                }
+                .into());
+            }
+        }
 
         // Check for duplicates
         if self.addresses.iter().any(|a| a.address == address.address) {
@@ -254,11 +426,11 @@ impl AddressBook {
         Ok(())
     }
 
-    /// Remove an address
+    /// Remove an address, given as hex or by its label
     pub fn remove(&mut self, address: &str) -> bool {
         let initial_len = self.addresses.len();
         self.addresses
-            .retain(|a| !a.equals(address));
+            .retain(|a| !a.equals(address) && !a.label.as_deref().is_some_and(|label| label.eq_ignore_ascii_case(address)));
         self.addresses.len() < initial_len
     }
 
@@ -267,11 +439,66 @@ impl AddressBook {
         self.addresses.iter().find(|a| a.equals(address))
     }
 
-    /// Find address by label
+    /// Find address by label (case-insensitive)
     pub fn find_by_label(&self, label: &str) -> Option<&Address> {
         self.addresses
             .iter()
-            .find(|a| a.label.as_deref() == Some(label))
+            .find(|a| a.label.as_deref().is_some_and(|own| own.eq_ignore_ascii_case(label)))
+    }
+
+    /// Addresses whose label or hex contains `query` (case-insensitive)
+    pub fn search(&self, query: &str) -> Vec<&Address> {
+        let query = query.to_lowercase();
+        self.addresses
+            .iter()
+            .filter(|a| {
+                a.label.as_deref().is_some_and(|label| label.to_lowercase().contains(&query))
+                    || a.address.to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+
+    /// Address meant by `name`: a hex address as given, otherwise the one labelled `name`
+    pub fn resolve(&self, name: &str) -> WalletResult<EthereumAddress> {
+        if Self::is_hex_address(name) {
+            return name.parse();
+        }
+        self.find_by_label(name)
+            .map(|entry| entry.address)
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "address".to_string(),
+                    value: name.to_string(),
+                    expected: "a 0x-prefixed hex address or an address book label (see `wallet book list`)".to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Whether `name` is meant as a hex address rather than a label
+    pub fn is_hex_address(name: &str) -> bool {
+        name.starts_with("0x") || name.starts_with("0X") || name.parse::<EthereumAddress>().is_ok()
+    }
+
+    /// Labels are short names that cannot be mistaken for an address
+    fn validate_label(label: &str) -> WalletResult<()> {
+        let max = crate::config::address_book::MAX_LABEL_LENGTH;
+        let valid = !label.is_empty()
+            && label.chars().count() <= max
+            && label.trim() == label
+            && !label.chars().any(char::is_control)
+            && !Self::is_hex_address(label);
+
+        if valid {
+            Ok(())
+        } else {
+            Err(UserInputError::InvalidParameters {
+                parameter: "label".to_string(),
+                value: label.to_string(),
+                expected: format!("1-{} characters, not starting with 0x or padded with spaces", max),
+            }
+            .into())
+        }
     }
 
     /// Get all addresses
@@ -280,10 +507,10 @@ impl AddressBook {
     }
 
     /// Get addresses for specific network
-    pub fn addresses_for_network(&self, network: &str) -> Vec<&Address> {
+    pub fn addresses_for_network(&self, network: &Network) -> Vec<&Address> {
         self.addresses
             .iter()
-            .filter(|a| a.network == network)
+            .filter(|a| &a.network == network)
             .collect()
     }
 
@@ -294,8 +521,19 @@ impl AddressBook {
 
     /// Validate all addresses
     pub fn validate(&self) -> WalletResult<()> {
+        let mut labels = std::collections::BTreeSet::new();
         for address in &self.addresses {
             address.validate()?;
+            if let Some(label) = address.label() {
+                Self::validate_label(label)?;
+                if !labels.insert(label.to_lowercase()) {
+                    return Err(ValidationError::IntegrityCheckFailed {
+                        data_type: "address_book".to_string(),
+                        details: format!("Label '{}' is used twice", label),
+                    }
+                    .into());
+                }
+            }
         }
         Ok(())
     }
@@ -305,22 +543,57 @@ impl AddressBook {
 mod tests {
     use super::*;
 
-    const TEST_ADDRESS: &str = "0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99";
+    const TEST_ADDRESS: &str = "0x742d35Cc6634C0532925a3b844Bc454e4438f44e";
     const TEST_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
 
+    #[test]
+    fn test_ethereum_address_checksum() {
+        let address: EthereumAddress = TEST_ADDRESS.to_lowercase().parse().unwrap();
+        assert_eq!(address.to_string(), TEST_ADDRESS);
+        assert_eq!(address, TEST_ADDRESS.parse::<EthereumAddress>().unwrap());
+        assert_eq!(address, TEST_ADDRESS[2..].to_uppercase().parse::<EthereumAddress>().unwrap());
+
+        // Mixed case is a checksum and must be right
+        assert!("0x742d35Cc6634C0532925a3b844Bc454e4438F44e".parse::<EthereumAddress>().is_err());
+        assert!("0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99".parse::<EthereumAddress>().is_err());
+
+        // Serialized lowercase, as keystores store it; any valid form reads back
+        let json = serde_json::to_string(&address).unwrap();
+        assert_eq!(json, format!("\"{}\"", TEST_ADDRESS.to_lowercase()));
+        assert_eq!(serde_json::from_str::<EthereumAddress>(&json).unwrap(), address);
+        assert!(serde_json::from_str::<EthereumAddress>("\"0x742d35cc\"").is_err());
+        assert_eq!(address, TEST_ADDRESS.to_uppercase());
+    }
+
+    #[test]
+    fn test_address_case_scope() {
+        let address: EthereumAddress = TEST_ADDRESS.parse().unwrap();
+        let data = format!("0x{}", "ab".repeat(20));
+        let document = || serde_json::json!({ "accounts": [{ "address": address }], "calldata": data });
+
+        let checksummed = AddressCase::Checksum.scope(document);
+        assert_eq!(checksummed["accounts"][0]["address"], TEST_ADDRESS);
+        // Only addresses change; other hex of the same length is data
+        assert_eq!(checksummed["calldata"], data.as_str());
+
+        // Outside the scope, and after a nested one, the stored form is back
+        assert_eq!(AddressCase::Lowercase.scope(document)["accounts"][0]["address"], TEST_ADDRESS.to_lowercase());
+        assert_eq!(document()["accounts"][0]["address"], TEST_ADDRESS.to_lowercase());
+    }
+
     #[test]
     fn test_address_creation() {
         let addr = Address::new(
             TEST_ADDRESS.to_string(),
-            "mainnet".to_string(),
+            Network::Mainnet,
             Some(0),
-            Some(TEST_DERIVATION_PATH.to_string()),
+            Some(TEST_DERIVATION_PATH.parse().unwrap()),
         )
         .unwrap();
 
-        assert_eq!(addr.address(), TEST_ADDRESS.to_lowercase());
+        assert_eq!(*addr.address(), TEST_ADDRESS.to_lowercase());
         assert_eq!(addr.index(), Some(0));
-        assert_eq!(addr.derivation_path(), Some(TEST_DERIVATION_PATH));
+        assert_eq!(addr.derivation_path().unwrap().to_string(), TEST_DERIVATION_PATH);
         assert!(addr.is_derived());
     }
 
@@ -328,7 +601,7 @@ mod tests {
     fn test_address_from_string() {
         let addr = Address::from_string(TEST_ADDRESS, "mainnet").unwrap();
 
-        assert_eq!(addr.address(), TEST_ADDRESS.to_lowercase());
+        assert_eq!(*addr.address(), TEST_ADDRESS.to_lowercase());
         assert_eq!(addr.network(), "mainnet");
         assert!(!addr.is_derived());
     }
@@ -363,13 +636,13 @@ mod tests {
         let short = addr.short_address();
 
         assert!(short.starts_with("0x742d"));
-        assert!(short.ends_with("9a99"));
+        assert!(short.ends_with("f44e"));
         assert!(short.contains("..."));
     }
 
     #[test]
     fn test_address_book() {
-        let mut book = AddressBook::new("mainnet".to_string());
+        let mut book = AddressBook::new(Network::Mainnet);
 
         let addr1 = Address::from_string(TEST_ADDRESS, "mainnet").unwrap();
         let addr2 = Address::from_string("0x1234567890123456789012345678901234567890", "mainnet").unwrap();
@@ -383,14 +656,49 @@ mod tests {
         assert_eq!(book.addresses().len(), 1);
     }
 
+    #[test]
+    fn test_address_book_labels() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join(crate::config::address_book::FILE_NAME);
+        let mut book = AddressBook::load(&path).unwrap();
+        assert!(book.addresses().is_empty());
+
+        let mut alice = Address::from_string(TEST_ADDRESS, "mainnet").unwrap();
+        alice.set_label(Some("Alice".to_string()));
+        book.add(alice).unwrap();
+
+        // Labels are unique regardless of case and never look like an address
+        for label in ["alice", "0xabc", " padded", ""] {
+            let mut other = Address::from_string("0x1234567890123456789012345678901234567890", "mainnet").unwrap();
+            other.set_label(Some(label.to_string()));
+            assert!(book.add(other).is_err(), "{:?}", label);
+        }
+
+        book.save(&path).unwrap();
+        let mut book = AddressBook::load(&path).unwrap();
+        assert_eq!(book.resolve("alice").unwrap(), TEST_ADDRESS);
+        assert_eq!(book.resolve(&TEST_ADDRESS.to_lowercase()).unwrap(), TEST_ADDRESS);
+        assert!(book.resolve("bob").is_err());
+        assert!(book.resolve("0x742d35Cc6634C0532925a3b844Bc454e4438F44e").is_err());
+        assert_eq!(book.search("LIC").len(), 1);
+        assert_eq!(book.search("0x742D35").len(), 1);
+        assert!(book.search("carol").is_empty());
+
+        assert!(book.remove("ALICE"));
+        assert!(book.addresses().is_empty());
+
+        std::fs::write(&path, r#"{"addresses": [], "default_network": 5}"#).unwrap();
+        assert!(AddressBook::load(&path).is_err());
+    }
+
     #[test]
     fn test_derived_address_validation() {
         // Valid derived address
         let addr = Address::derived(
             TEST_ADDRESS.to_string(),
-            "mainnet".to_string(),
+            Network::Mainnet,
             0,
-            TEST_DERIVATION_PATH.to_string(),
+            TEST_DERIVATION_PATH.parse().unwrap(),
         )
         .unwrap();
         assert!(addr.validate().is_ok());
@@ -398,11 +706,17 @@ mod tests {
         // Invalid: index doesn't match path
         let addr = Address::derived(
             TEST_ADDRESS.to_string(),
-            "mainnet".to_string(),
+            Network::Mainnet,
             5,
-            TEST_DERIVATION_PATH.to_string(),
+            TEST_DERIVATION_PATH.parse().unwrap(),
         )
         .unwrap();
         assert!(addr.validate().is_err());
+
+        // Change chain is valid, any other chain is not
+        for (path, valid) in [("m/44'/60'/0'/1/3", true), ("m/44'/60'/0'/2/3", false), ("m/44'/60'/0'/1'/3", false)] {
+            let addr = Address::derived(TEST_ADDRESS.to_string(), Network::Mainnet, 3, path.parse().unwrap()).unwrap();
+            assert_eq!(addr.validate().is_ok(), valid, "{}", path);
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+//! # Chain Model
+//!
+//! Chain metadata entries as published by the chainlist registry.
+//! Only the fields the wallet uses are kept; unknown fields are ignored.
+
+use serde::{Deserialize, Serialize};
+
+/// Metadata for a single EVM chain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfo {
+    /// Human-readable chain name
+    pub name: String,
+
+    /// Chain family identifier (e.g. "ETH")
+    #[serde(default)]
+    pub chain: String,
+
+    /// Short name used in EIP-3770 addresses
+    #[serde(default)]
+    pub short_name: String,
+
+    /// EIP-155 chain ID
+    pub chain_id: u64,
+
+    /// Network ID
+    #[serde(default)]
+    pub network_id: Option<u64>,
+
+    /// SLIP-44 coin type, when registered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slip44: Option<u32>,
+
+    /// Native currency details
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native_currency: Option<NativeCurrency>,
+
+    /// Public RPC endpoints
+    #[serde(default)]
+    pub rpc: Vec<String>,
+
+    /// Project information URL
+    #[serde(default, rename = "infoURL", skip_serializing_if = "Option::is_none")]
+    pub info_url: Option<String>,
+
+    /// Block explorers
+    #[serde(default)]
+    pub explorers: Vec<Explorer>,
+}
+
+/// Native currency of a chain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NativeCurrency {
+    /// Currency name
+    pub name: String,
+    /// Ticker symbol
+    pub symbol: String,
+    /// Decimal places
+    pub decimals: u8,
+}
+
+/// Block explorer entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Explorer {
+    /// Explorer name
+    pub name: String,
+    /// Base URL
+    pub url: String,
+    /// Supported standard (usually "EIP3091")
+    #[serde(default)]
+    pub standard: Option<String>,
+}
+
+impl ChainInfo {
+    /// Native currency symbol, if known
+    pub fn symbol(&self) -> Option<&str> {
+        self.native_currency.as_ref().map(|c| c.symbol.as_str())
+    }
+
+    /// First listed block explorer URL
+    pub fn explorer_url(&self) -> Option<&str> {
+        self.explorers.first().map(|e| e.url.as_str())
+    }
+
+    /// Check whether a query matches this chain's name, short name or chain ID
+    pub fn matches(&self, query: &str) -> bool {
+        if let Ok(id) = query.parse::<u64>() {
+            return id == self.chain_id;
+        }
+
+        self.name.eq_ignore_ascii_case(query) || self.short_name.eq_ignore_ascii_case(query)
+    }
+}
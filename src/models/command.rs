@@ -4,6 +4,7 @@
 
 use crate::config;
 use crate::errors::{UserInputError, WalletResult};
+use crate::models::{DerivationPath, Network};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -14,6 +15,8 @@ pub enum OutputFormat {
     Table,
     /// Machine-readable JSON format
     Json,
+    /// Newline-delimited JSON, one record per line
+    Ndjson,
 }
 
 impl std::str::FromStr for OutputFormat {
@@ -23,9 +26,10 @@ impl std::str::FromStr for OutputFormat {
         match s.to_lowercase().as_str() {
             "table" => Ok(OutputFormat::Table),
             "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
             _ => Err(UserInputError::UnsupportedFormat {
                 format: s.to_string(),
-                supported: vec!["table".to_string(), "json".to_string()],
+                supported: vec!["table".to_string(), "json".to_string(), "ndjson".to_string()],
             }),
         }
     }
@@ -36,6 +40,7 @@ impl std::fmt::Display for OutputFormat {
         match self {
             OutputFormat::Table => write!(f, "table"),
             OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Ndjson => write!(f, "ndjson"),
         }
     }
 }
@@ -74,6 +79,12 @@ pub struct CreateCommand {
     pub network: String,
 }
 
+impl Default for CreateCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CreateCommand {
     /// Create with defaults
     pub fn new() -> Self {
@@ -98,13 +109,7 @@ impl CreateCommand {
         }
 
         // Validate network
-        if !config::is_supported_network(&self.network) {
-            return Err(UserInputError::InvalidNetwork {
-                network: self.network.clone(),
-                supported: config::SUPPORTED_NETWORKS.iter().map(|s| s.to_string()).collect(),
-            }
-            .into());
-        }
+        self.network.parse::<Network>()?;
 
         // Validate save path if provided
         if let Some(ref save_path) = self.save {
@@ -130,6 +135,12 @@ pub struct ImportCommand {
     pub network: String,
 }
 
+impl Default for ImportCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ImportCommand {
     /// Create with defaults
     pub fn new() -> Self {
@@ -183,13 +194,7 @@ impl ImportCommand {
         }
 
         // Validate network
-        if !config::is_supported_network(&self.network) {
-            return Err(UserInputError::InvalidNetwork {
-                network: self.network.clone(),
-                supported: config::SUPPORTED_NETWORKS.iter().map(|s| s.to_string()).collect(),
-            }
-            .into());
-        }
+        self.network.parse::<Network>()?;
 
         // Validate save path if provided
         if let Some(ref save_path) = self.save {
@@ -256,6 +261,12 @@ pub struct ListCommand {
     pub path: Option<PathBuf>,
 }
 
+impl Default for ListCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ListCommand {
     /// Create with defaults
     pub fn new() -> Self {
@@ -369,18 +380,16 @@ impl DeriveCommand {
     }
 
     /// Get the full derivation path for a specific index
-    pub fn derivation_path_for_index(&self, index: u32) -> String {
-        if self.path.starts_with("m/") {
+    pub fn derivation_path_for_index(&self, index: u32) -> WalletResult<DerivationPath> {
+        let base = if self.path.starts_with("m/") {
             // Replace the last component with the new index
-            if let Some(last_slash) = self.path.rfind('/') {
-                format!("{}/{}", &self.path[..last_slash], index)
-            } else {
-                format!("{}/{}", self.path, index)
-            }
+            let path: DerivationPath = self.path.parse()?;
+            path.parent().unwrap_or_else(DerivationPath::root)
         } else {
             // Use default path with index
-            format!("{}/{}", config::DEFAULT_DERIVATION_PATH, index)
-        }
+            DerivationPath::default()
+        };
+        base.child(index)
     }
 }
 
@@ -404,6 +413,8 @@ pub struct CommandError {
     pub message: String,
     /// Additional error details
     pub details: Option<serde_json::Value>,
+    /// How to resolve the error
+    pub suggestion: Option<String>,
 }
 
 impl<T> CommandResult<T> {
@@ -425,17 +436,18 @@ impl<T> CommandResult<T> {
                 code,
                 message,
                 details,
+                suggestion: None,
             }),
         }
     }
 
     /// Create error result from WalletError
     pub fn from_error(error: crate::WalletError) -> Self {
-        Self::error(
-            error.code().to_string(),
-            error.to_string(),
-            error.suggestion().map(|s| serde_json::Value::String(s)),
-        )
+        let mut result = Self::error(error.code().to_string(), error.to_string(), None);
+        if let Some(command_error) = result.error.as_mut() {
+            command_error.suggestion = error.suggestion();
+        }
+        result
     }
 }
 
@@ -447,6 +459,7 @@ mod tests {
     fn test_output_format_parsing() {
         assert_eq!("table".parse::<OutputFormat>().unwrap(), OutputFormat::Table);
         assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("NDJSON".parse::<OutputFormat>().unwrap(), OutputFormat::Ndjson);
         assert!("invalid".parse::<OutputFormat>().is_err());
     }
 
@@ -494,10 +507,10 @@ mod tests {
     #[test]
     fn test_derive_path_generation() {
         let cmd = DeriveCommand::new("m/44'/60'/0'/0/0".to_string());
-        assert_eq!(cmd.derivation_path_for_index(5), "m/44'/60'/0'/0/5");
+        assert_eq!(cmd.derivation_path_for_index(5).unwrap().to_string(), "m/44'/60'/0'/0/5");
 
         let cmd = DeriveCommand::new("5".to_string());
-        assert_eq!(cmd.derivation_path_for_index(5), "m/44'/60'/0'/0/5");
+        assert_eq!(cmd.derivation_path_for_index(5).unwrap().to_string(), "m/44'/60'/0'/0/5");
     }
 
     #[test]
@@ -513,5 +526,17 @@ mod tests {
         );
         assert!(!error.success);
         assert_eq!(error.error.unwrap().code, "TEST_001");
+
+        let error = CommandResult::<()>::from_error(
+            crate::errors::CryptographicError::InvalidMnemonic {
+                details: "bad checksum".to_string(),
+                suggestion: "Check the word order".to_string(),
+            }
+            .into(),
+        );
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["error"]["code"], "CRYPTO_002");
+        assert_eq!(json["error"]["suggestion"], "Check the word order");
     }
 }
\ No newline at end of file
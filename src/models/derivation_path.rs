@@ -0,0 +1,417 @@
+//! # Derivation Path Model
+//!
+//! BIP32 derivation paths such as `m/44'/60'/0'/0/5`. Paths are validated once
+//! when parsed and manipulated structurally afterwards, so callers never splice
+//! path strings by hand.
+
+use crate::errors::{CryptographicError, UserInputError, WalletError, WalletResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// First hardened BIP32 index
+const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// BIP44 chain holding change addresses
+const INTERNAL_CHAIN: u32 = 1;
+
+/// One level of a derivation path
+///
+/// Orders like the BIP32 index it encodes: all normal children before all
+/// hardened ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChildNumber {
+    hardened: bool,
+    index: u32,
+}
+
+impl ChildNumber {
+    /// Normal (non-hardened) child
+    pub fn normal(index: u32) -> WalletResult<Self> {
+        Self::new(index, false)
+    }
+
+    /// Hardened child, written with a trailing `'`
+    pub fn hardened(index: u32) -> WalletResult<Self> {
+        Self::new(index, true)
+    }
+
+    fn new(index: u32, hardened: bool) -> WalletResult<Self> {
+        if index >= HARDENED_OFFSET {
+            return Err(CryptographicError::IndexOutOfRange {
+                index,
+                max_index: HARDENED_OFFSET - 1,
+            }
+            .into());
+        }
+        Ok(Self { hardened, index })
+    }
+
+    /// Index without the hardened offset
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Check if this is a hardened child
+    pub fn is_hardened(&self) -> bool {
+        self.hardened
+    }
+
+    /// Raw BIP32 index, with the hardened offset applied
+    pub fn to_bip32(&self) -> u32 {
+        if self.hardened {
+            self.index | HARDENED_OFFSET
+        } else {
+            self.index
+        }
+    }
+}
+
+impl fmt::Display for ChildNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.hardened {
+            write!(f, "{}'", self.index)
+        } else {
+            write!(f, "{}", self.index)
+        }
+    }
+}
+
+/// BIP32 derivation path
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DerivationPath(Vec<ChildNumber>);
+
+impl DerivationPath {
+    /// The master key path `m`
+    pub fn root() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Path levels below the master key
+    pub fn components(&self) -> &[ChildNumber] {
+        &self.0
+    }
+
+    /// Number of levels below the master key
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Last level, `None` for the master key
+    pub fn last(&self) -> Option<ChildNumber> {
+        self.0.last().copied()
+    }
+
+    /// Path with `child` appended
+    pub fn join(&self, child: ChildNumber) -> Self {
+        let mut components = self.0.clone();
+        components.push(child);
+        Self(components)
+    }
+
+    /// Path to the normal child at `index`
+    pub fn child(&self, index: u32) -> WalletResult<Self> {
+        Ok(self.join(ChildNumber::normal(index)?))
+    }
+
+    /// Path to the hardened child at `index`
+    pub fn hardened_child(&self, index: u32) -> WalletResult<Self> {
+        Ok(self.join(ChildNumber::hardened(index)?))
+    }
+
+    /// Path one level up, `None` for the master key
+    pub fn parent(&self) -> Option<Self> {
+        self.0.split_last().map(|(_, parent)| Self(parent.to_vec()))
+    }
+
+    /// SLIP-44 coin type of a BIP44 path (`m/44'/coin'/...`)
+    pub fn coin_type(&self) -> Option<u32> {
+        match self.0.as_slice() {
+            [purpose, coin, ..] if purpose.is_hardened() && purpose.index() == 44 && coin.is_hardened() => {
+                Some(coin.index())
+            }
+            _ => None,
+        }
+    }
+
+    /// BIP44 chain of an address path (`m/44'/coin'/account'/chain/index`): 0 external, 1 internal
+    pub fn chain(&self) -> Option<ChildNumber> {
+        match self.0.as_slice() {
+            [purpose, _, _, chain, _] if purpose.is_hardened() && purpose.index() == 44 => Some(*chain),
+            _ => None,
+        }
+    }
+
+    /// Check if `self` is `ancestor` or lies below it
+    pub fn starts_with(&self, ancestor: &DerivationPath) -> bool {
+        self.0.starts_with(&ancestor.0)
+    }
+
+    /// Raw BIP32 indices for key derivation
+    pub(crate) fn to_bip32(&self) -> Vec<u32> {
+        self.0.iter().map(ChildNumber::to_bip32).collect()
+    }
+}
+
+impl Default for DerivationPath {
+    /// Base path of Ethereum accounts, [`crate::config::DEFAULT_DERIVATION_PATH`]
+    fn default() -> Self {
+        Self(vec![
+            ChildNumber { hardened: true, index: 44 },
+            ChildNumber { hardened: true, index: 60 },
+            ChildNumber { hardened: true, index: 0 },
+            ChildNumber { hardened: false, index: 0 },
+        ])
+    }
+}
+
+/// Account layouts used by popular wallets for the same seed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathPreset {
+    /// `m/44'/60'/0'/0/x`: MetaMask, Trezor, most software wallets
+    #[default]
+    Metamask,
+    /// `m/44'/60'/0'/0/x`: MyEtherWallet's default, same layout as MetaMask
+    Mew,
+    /// `m/44'/60'/x'/0/0`: Ledger Live, one hardened account per index
+    LedgerLive,
+    /// `m/44'/60'/0'/x`: the original Ledger Chrome app and MEW's "Ledger (ETH)"
+    LedgerLegacy,
+}
+
+impl PathPreset {
+    /// All presets, in display order
+    pub const ALL: [PathPreset; 4] = [Self::Metamask, Self::Mew, Self::LedgerLive, Self::LedgerLegacy];
+
+    /// Command-line name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Metamask => "metamask",
+            Self::Mew => "mew",
+            Self::LedgerLive => "ledger-live",
+            Self::LedgerLegacy => "ledger-legacy",
+        }
+    }
+
+    /// Fixed part of the path, above the varying index
+    pub fn base_path(&self) -> DerivationPath {
+        let ethereum = DerivationPath(vec![
+            ChildNumber { hardened: true, index: 44 },
+            ChildNumber { hardened: true, index: 60 },
+        ]);
+        match self {
+            Self::Metamask | Self::Mew => DerivationPath::default(),
+            Self::LedgerLive => ethereum,
+            Self::LedgerLegacy => ethereum.join(ChildNumber { hardened: true, index: 0 }),
+        }
+    }
+
+    /// Base path for BIP44 account `account` of SLIP-44 coin `coin_type` (`m/44'/coin'/account'/...`)
+    ///
+    /// Ledger Live has no separate account level: its index already is the account.
+    pub fn account_base(&self, coin_type: u32, account: u32) -> WalletResult<DerivationPath> {
+        let coin = DerivationPath::root().hardened_child(44)?.hardened_child(coin_type)?;
+        match self {
+            Self::Metamask | Self::Mew => coin.hardened_child(account)?.child(0),
+            Self::LedgerLegacy => coin.hardened_child(account),
+            Self::LedgerLive if account == 0 => Ok(coin),
+            Self::LedgerLive => Err(UserInputError::InvalidParameters {
+                parameter: "account".to_string(),
+                value: account.to_string(),
+                expected: "0; ledger-live selects the account with the address index".to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Full path of account `index` below `base`
+    pub fn account_path(&self, base: &DerivationPath, index: u32) -> WalletResult<DerivationPath> {
+        match self {
+            Self::LedgerLive => base.hardened_child(index)?.child(0)?.child(0),
+            Self::Metamask | Self::Mew | Self::LedgerLegacy => base.child(index),
+        }
+    }
+
+    /// Check if the layout has a BIP44 change branch next to the external one
+    pub fn has_change_branch(&self) -> bool {
+        matches!(self, Self::Metamask | Self::Mew)
+    }
+
+    /// Internal (change) chain next to the external chain `base`
+    pub fn change_base(&self, base: &DerivationPath) -> WalletResult<DerivationPath> {
+        if !self.has_change_branch() {
+            return Err(CryptographicError::InvalidDerivationPath {
+                path: base.to_string(),
+                expected: format!("a layout with a change branch ({} has none)", self),
+            }
+            .into());
+        }
+        let account = base.parent().ok_or_else(|| CryptographicError::InvalidDerivationPath {
+            path: base.to_string(),
+            expected: "a base path with an account level above the external branch".to_string(),
+        })?;
+        account.child(INTERNAL_CHAIN)
+    }
+}
+
+impl FromStr for PathPreset {
+    type Err = WalletError;
+
+    fn from_str(name: &str) -> WalletResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|preset| preset.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "path-preset".to_string(),
+                    value: name.to_string(),
+                    expected: Self::ALL.map(|preset| preset.name()).join(", "),
+                }
+                .into()
+            })
+    }
+}
+
+impl fmt::Display for PathPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = WalletError;
+
+    fn from_str(path: &str) -> WalletResult<Self> {
+        let invalid = |expected: &str| CryptographicError::InvalidDerivationPath {
+            path: path.to_string(),
+            expected: expected.to_string(),
+        };
+
+        let rest = match path.strip_prefix('m') {
+            Some("") => return Ok(Self::root()),
+            Some(rest) => rest.strip_prefix('/').ok_or_else(|| invalid("path starting with 'm/'"))?,
+            None => return Err(invalid("path starting with 'm/'").into()),
+        };
+
+        rest.split('/')
+            .map(|component| {
+                if component.is_empty() {
+                    return Err(invalid("non-empty path components").into());
+                }
+                let (digits, hardened) = match component.strip_suffix(['\'', 'h', 'H']) {
+                    Some(digits) => (digits, true),
+                    None => (component, false),
+                };
+                let index = digits
+                    .parse::<u32>()
+                    .map_err(|_| invalid("numeric path components, optionally hardened with '"))?;
+                ChildNumber::new(index, hardened)
+                    .map_err(|_| invalid("path components below 2147483648").into())
+            })
+            .collect::<WalletResult<Vec<_>>>()
+            .map(Self)
+    }
+}
+
+impl TryFrom<String> for DerivationPath {
+    type Error = WalletError;
+
+    fn try_from(path: String) -> WalletResult<Self> {
+        path.parse()
+    }
+}
+
+impl From<DerivationPath> for String {
+    fn from(path: DerivationPath) -> Self {
+        path.to_string()
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut path = String::from("m");
+        for component in &self.0 {
+            path.push_str(&format!("/{}", component));
+        }
+        f.pad(&path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+
+    #[test]
+    fn test_parse_and_display() {
+        let path: DerivationPath = "m/44'/60'/0'/0/5".parse().unwrap();
+        assert_eq!(path.depth(), 5);
+        assert!(path.components()[0].is_hardened());
+        assert_eq!(path.last(), Some(ChildNumber::normal(5).unwrap()));
+        assert_eq!(path.to_string(), "m/44'/60'/0'/0/5");
+
+        assert_eq!("m/44h/60H/0'".parse::<DerivationPath>().unwrap().to_string(), "m/44'/60'/0'");
+        assert_eq!("m".parse::<DerivationPath>().unwrap(), DerivationPath::root());
+        assert_eq!(DerivationPath::default().to_string(), config::DEFAULT_DERIVATION_PATH);
+
+        for invalid in ["", "44'/60'", "m/", "m//0", "m/a", "m/0''", "m/2147483648", "n/0"] {
+            let err = invalid.parse::<DerivationPath>().unwrap_err();
+            assert_eq!(err.code(), "CRYPTO_006", "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_structural_operations() {
+        let base = DerivationPath::default();
+        let child = base.child(7).unwrap();
+        assert_eq!(child.to_string(), "m/44'/60'/0'/0/7");
+        assert_eq!(child.parent(), Some(base.clone()));
+        assert!(child.starts_with(&base));
+        assert!(!base.starts_with(&child));
+        assert_eq!(DerivationPath::root().parent(), None);
+        assert_eq!(child.chain(), Some(ChildNumber::normal(0).unwrap()));
+        assert_eq!(base.chain(), None);
+        assert!(base.child(HARDENED_OFFSET).is_err());
+
+        let hardened = base.hardened_child(1).unwrap();
+        assert_eq!(hardened.last().unwrap().to_bip32(), HARDENED_OFFSET + 1);
+        assert!(base.child(1).unwrap() < hardened);
+        assert!(base.child(2).unwrap() > base.child(1).unwrap());
+    }
+
+    #[test]
+    fn test_path_presets() {
+        let paths: Vec<String> = PathPreset::ALL
+            .iter()
+            .map(|preset| preset.account_path(&preset.base_path(), 2).unwrap().to_string())
+            .collect();
+        assert_eq!(paths, ["m/44'/60'/0'/0/2", "m/44'/60'/0'/0/2", "m/44'/60'/2'/0/0", "m/44'/60'/0'/2"]);
+
+        for preset in PathPreset::ALL {
+            assert_eq!(preset.account_base(60, 0).unwrap(), preset.base_path());
+            assert_eq!(preset.account_base(61, 0).unwrap().coin_type(), Some(61));
+        }
+        assert_eq!(PathPreset::Metamask.account_base(60, 3).unwrap().to_string(), "m/44'/60'/3'/0");
+        assert_eq!(PathPreset::LedgerLegacy.account_base(60, 3).unwrap().to_string(), "m/44'/60'/3'");
+        assert_eq!(PathPreset::Metamask.account_base(700, 0).unwrap().to_string(), "m/44'/700'/0'/0");
+        assert!(PathPreset::LedgerLive.account_base(60, 3).is_err());
+        assert_eq!(PathPreset::Metamask.change_base(&PathPreset::Metamask.base_path()).unwrap().to_string(), "m/44'/60'/0'/1");
+        assert!(PathPreset::LedgerLegacy.change_base(&PathPreset::LedgerLegacy.base_path()).is_err());
+        assert_eq!("m/0'/1".parse::<DerivationPath>().unwrap().coin_type(), None);
+
+        assert_eq!("Ledger-Live".parse::<PathPreset>().unwrap(), PathPreset::LedgerLive);
+        assert!("trezor".parse::<PathPreset>().is_err());
+        assert_eq!(PathPreset::default(), PathPreset::Metamask);
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        let path = DerivationPath::default().child(3).unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, "\"m/44'/60'/0'/0/3\"");
+        assert_eq!(serde_json::from_str::<DerivationPath>(&json).unwrap(), path);
+        assert!(serde_json::from_str::<DerivationPath>("\"m/x\"").is_err());
+    }
+}
@@ -4,7 +4,8 @@
 //! Compatible with MetaMask and other standard wallet formats.
 
 use crate::config;
-use crate::errors::{CryptographicError, ValidationError, WalletResult};
+use crate::errors::{CryptographicError, UserInputError, ValidationError, WalletResult};
+use crate::models::{DerivationPath, EthereumAddress, Network};
 use serde::{Deserialize, Serialize};
 
 /// UTC/JSON Keystore format (MetaMask compatible)
@@ -27,20 +28,88 @@ pub struct KeystoreMetadata {
     pub alias: Option<String>,
 
     /// Primary Ethereum address
-    pub address: String,
+    pub address: EthereumAddress,
 
     /// Creation timestamp (ISO 8601)
     pub created_at: String,
 
     /// Target network
-    pub network: String,
+    pub network: Network,
 
     /// Keystore format identifier
     pub keystore_type: String,
+
+    /// Free-form tags for grouping and filtering
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Free-form notes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+
+    /// Whether the seed uses a BIP39 passphrase (never the passphrase itself)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bip39_passphrase: bool,
+
+    /// Base derivation path of the wallet's account (HD wallets only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<DerivationPath>,
+}
+
+impl KeystoreMetadata {
+    /// Add tags, ignoring ones already present; returns how many were added
+    pub fn add_tags(&mut self, tags: &[String]) -> WalletResult<usize> {
+        for tag in tags {
+            Self::validate_tag(tag)?;
+        }
+
+        let before = self.tags.len();
+        for tag in tags {
+            if !self.has_tag(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+        self.tags.sort();
+        Ok(self.tags.len() - before)
+    }
+
+    /// Remove tags; returns how many were removed
+    pub fn remove_tags(&mut self, tags: &[String]) -> usize {
+        let before = self.tags.len();
+        self.tags.retain(|existing| !tags.iter().any(|tag| tag.eq_ignore_ascii_case(existing)));
+        before - self.tags.len()
+    }
+
+    /// Check for a tag (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag))
+    }
+
+    /// Tags are short identifiers: letters, digits, `-`, `_`, `.`
+    fn validate_tag(tag: &str) -> WalletResult<()> {
+        let valid = !tag.is_empty()
+            && tag.len() <= config::fs::MAX_TAG_LENGTH
+            && tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+        if valid {
+            Ok(())
+        } else {
+            Err(UserInputError::InvalidParameters {
+                parameter: "tag".to_string(),
+                value: tag.to_string(),
+                expected: format!(
+                    "1-{} characters from letters, digits, '-', '_' and '.'",
+                    config::fs::MAX_TAG_LENGTH
+                ),
+            }
+            .into())
+        }
+    }
 }
 
 /// Cryptographic parameters for encrypted data
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawCryptoParams")]
 pub struct CryptoParams {
     /// Encryption algorithm ("aes-256-gcm")
     pub cipher: String,
@@ -59,6 +128,115 @@ pub struct CryptoParams {
 
     /// Message authentication code (hex encoded)
     pub mac: String,
+
+    /// Hardware token mixed into the derived key, if enrolled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware: Option<HardwareFactor>,
+
+    /// Passphrase shares of the data key, for threshold keystores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shares: Option<ShareSet>,
+}
+
+/// [`CryptoParams`] as read, before `kdfparams` is parsed for its `kdf`
+#[derive(Deserialize)]
+struct RawCryptoParams {
+    cipher: String,
+    ciphertext: String,
+    cipherparams: CipherParams,
+    kdf: String,
+    kdfparams: serde_json::Value,
+    mac: String,
+    #[serde(default)]
+    hardware: Option<HardwareFactor>,
+    #[serde(default)]
+    shares: Option<ShareSet>,
+}
+
+impl TryFrom<RawCryptoParams> for CryptoParams {
+    type Error = String;
+
+    fn try_from(raw: RawCryptoParams) -> Result<Self, String> {
+        Ok(Self {
+            kdfparams: KdfParams::from_tagged(&raw.kdf, raw.kdfparams)?,
+            cipher: raw.cipher,
+            ciphertext: raw.ciphertext,
+            cipherparams: raw.cipherparams,
+            kdf: raw.kdf,
+            mac: raw.mac,
+            hardware: raw.hardware,
+            shares: raw.shares,
+        })
+    }
+}
+
+/// Data key of a threshold keystore, split among passphrase holders
+///
+/// The keystore is encrypted as usual with the hex-encoded data key as its
+/// password; any `threshold` holders together recover that key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareSet {
+    /// Shares needed to recover the data key
+    pub threshold: u8,
+    /// One wrapped share per holder
+    pub shares: Vec<WrappedShare>,
+}
+
+/// One holder's Shamir share, encrypted under their passphrase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedShare {
+    /// Holder name shown when prompting
+    pub label: String,
+    /// Argon2id parameters for the holder's passphrase
+    pub kdfparams: KdfParams,
+    /// AES-GCM nonce (hex encoded)
+    pub iv: String,
+    /// Encrypted share with its tag (hex encoded)
+    pub ciphertext: String,
+}
+
+/// Hardware a keystore is bound to, stored with it
+///
+/// Only what the hardware needs to reproduce its secret is kept; the
+/// secret itself is needed again at every unlock and never written to
+/// disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum HardwareFactor {
+    /// YubiKey HMAC-SHA1 challenge-response
+    #[serde(rename = "yubikey-hmac-sha1")]
+    YubiKey {
+        /// OTP slot holding the HMAC-SHA1 secret (1 or 2)
+        slot: u8,
+        /// Challenge sent to the token (hex encoded)
+        challenge: String,
+    },
+    /// Secret sealed to this machine's TPM 2.0
+    #[serde(rename = "tpm2-sealed")]
+    Tpm {
+        /// Public area of the sealed object (hex encoded)
+        public: String,
+        /// Private area, encrypted by the TPM's storage key (hex encoded)
+        private: String,
+    },
+}
+
+impl HardwareFactor {
+    /// Stored kind name
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::YubiKey { .. } => "yubikey-hmac-sha1",
+            Self::Tpm { .. } => "tpm2-sealed",
+        }
+    }
+
+    /// Short description for display
+    pub fn describe(&self) -> String {
+        match self {
+            Self::YubiKey { slot, .. } => format!("YubiKey slot {}", slot),
+            Self::Tpm { .. } => "TPM 2.0 of this machine".to_string(),
+        }
+    }
 }
 
 /// AES-GCM cipher parameters
@@ -69,7 +247,12 @@ pub struct CipherParams {
 }
 
 /// Key derivation function parameters
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Written as a bare object next to the keystore's `kdf` field, which is
+/// what decides the variant on reading: parameters that do not exactly
+/// match the named KDF are rejected instead of being read as whichever
+/// variant happens to fit.
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum KdfParams {
     /// Argon2id parameters (preferred)
@@ -98,12 +281,71 @@ pub enum KdfParams {
     },
 }
 
+/// Argon2id fields of [`KdfParams`], read strictly
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Argon2Fields {
+    dklen: u32,
+    memory: u32,
+    time: u32,
+    parallelism: u32,
+    salt: String,
+}
+
+/// PBKDF2 fields of [`KdfParams`], read strictly
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Pbkdf2Fields {
+    dklen: u32,
+    c: u32,
+    prf: String,
+    salt: String,
+}
+
+impl KdfParams {
+    /// Name stored in the keystore's `kdf` field
+    pub fn kdf_name(&self) -> &'static str {
+        match self {
+            Self::Argon2 { .. } => "argon2id",
+            Self::Pbkdf2 { .. } => "pbkdf2",
+        }
+    }
+
+    /// Parse the parameters of the KDF named `kdf`
+    pub fn from_tagged(kdf: &str, params: serde_json::Value) -> Result<Self, String> {
+        let invalid = |e: serde_json::Error| format!("invalid {} parameters: {}", kdf, e);
+        match kdf {
+            "argon2id" => {
+                let Argon2Fields { dklen, memory, time, parallelism, salt } =
+                    serde_json::from_value(params).map_err(invalid)?;
+                Ok(Self::Argon2 { dklen, memory, time, parallelism, salt })
+            }
+            "pbkdf2" => {
+                let Pbkdf2Fields { dklen, c, prf, salt } = serde_json::from_value(params).map_err(invalid)?;
+                Ok(Self::Pbkdf2 { dklen, c, prf, salt })
+            }
+            other => Err(format!("Unsupported KDF: {}", other)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for KdfParams {
+    /// Parameters without a `kdf` field beside them (passphrase shares):
+    /// an iteration count `c` marks PBKDF2, anything else must be Argon2id
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let params = serde_json::Value::deserialize(deserializer)?;
+        let kdf = if params.get("c").is_some() { "pbkdf2" } else { "argon2id" };
+        Self::from_tagged(kdf, params).map_err(serde::de::Error::custom)
+    }
+}
+
 impl Keystore {
     /// Create a new keystore structure
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         alias: Option<String>,
-        address: String,
-        network: String,
+        address: EthereumAddress,
+        network: Network,
         encrypted_data: Vec<u8>,
         _salt: Vec<u8>,
         nonce: Vec<u8>,
@@ -116,6 +358,10 @@ impl Keystore {
             created_at: chrono::Utc::now().to_rfc3339(),
             network,
             keystore_type: "web3wallet-cli".to_string(),
+            tags: Vec::new(),
+            notes: None,
+            bip39_passphrase: false,
+            derivation_path: None,
         };
 
         let crypto = CryptoParams {
@@ -124,12 +370,11 @@ impl Keystore {
             cipherparams: CipherParams {
                 iv: hex::encode(nonce),
             },
-            kdf: match kdf_params {
-                KdfParams::Argon2 { .. } => "argon2id".to_string(),
-                KdfParams::Pbkdf2 { .. } => "pbkdf2".to_string(),
-            },
+            kdf: kdf_params.kdf_name().to_string(),
             kdfparams: kdf_params,
             mac: hex::encode(mac),
+            hardware: None,
+            shares: None,
         };
 
         Self {
@@ -140,10 +385,11 @@ impl Keystore {
     }
 
     /// Create Argon2id keystore
+    #[allow(clippy::too_many_arguments)]
     pub fn with_argon2(
         alias: Option<String>,
-        address: String,
-        network: String,
+        address: EthereumAddress,
+        network: Network,
         encrypted_data: Vec<u8>,
         salt: Vec<u8>,
         nonce: Vec<u8>,
@@ -173,10 +419,11 @@ impl Keystore {
     }
 
     /// Create PBKDF2 keystore (legacy compatibility)
+    #[allow(clippy::too_many_arguments)]
     pub fn with_pbkdf2(
         alias: Option<String>,
-        address: String,
-        network: String,
+        address: EthereumAddress,
+        network: Network,
         encrypted_data: Vec<u8>,
         salt: Vec<u8>,
         nonce: Vec<u8>,
@@ -204,47 +451,60 @@ impl Keystore {
 
     /// Get encrypted data as bytes
     pub fn encrypted_data(&self) -> WalletResult<Vec<u8>> {
-        hex::decode(&self.crypto.ciphertext).map_err(|e| {
-            CryptographicError::DataCorruption {
-                details: format!("Invalid ciphertext hex: {}", e),
-            }
-            .into()
-        })
+        let mut data = Vec::new();
+        self.encrypted_data_into(&mut data)?;
+        Ok(data)
+    }
+
+    /// Decode the ciphertext into `out`, reusing its allocation
+    pub fn encrypted_data_into(&self, out: &mut Vec<u8>) -> WalletResult<()> {
+        decode_hex_into(&self.crypto.ciphertext, out, "ciphertext")
     }
 
     /// Get salt as bytes
     pub fn salt(&self) -> WalletResult<Vec<u8>> {
-        let salt_hex = match &self.crypto.kdfparams {
-            KdfParams::Argon2 { salt, .. } => salt,
-            KdfParams::Pbkdf2 { salt, .. } => salt,
-        };
-
-        hex::decode(salt_hex).map_err(|e| {
-            CryptographicError::DataCorruption {
-                details: format!("Invalid salt hex: {}", e),
-            }
-            .into()
-        })
+        let mut salt = Vec::new();
+        decode_hex_into(self.salt_hex(), &mut salt, "salt")?;
+        Ok(salt)
     }
 
     /// Get nonce/IV as bytes
     pub fn nonce(&self) -> WalletResult<Vec<u8>> {
-        hex::decode(&self.crypto.cipherparams.iv).map_err(|e| {
-            CryptographicError::DataCorruption {
-                details: format!("Invalid nonce hex: {}", e),
-            }
-            .into()
-        })
+        let mut nonce = Vec::new();
+        decode_hex_into(&self.crypto.cipherparams.iv, &mut nonce, "nonce")?;
+        Ok(nonce)
     }
 
     /// Get MAC as bytes
     pub fn mac(&self) -> WalletResult<Vec<u8>> {
-        hex::decode(&self.crypto.mac).map_err(|e| {
-            CryptographicError::DataCorruption {
-                details: format!("Invalid MAC hex: {}", e),
+        let mut mac = Vec::new();
+        decode_hex_into(&self.crypto.mac, &mut mac, "MAC")?;
+        Ok(mac)
+    }
+
+    fn salt_hex(&self) -> &str {
+        match &self.crypto.kdfparams {
+            KdfParams::Argon2 { salt, .. } => salt,
+            KdfParams::Pbkdf2 { salt, .. } => salt,
+        }
+    }
+
+    /// Lowercase every hex field and drop `0x` prefixes; returns whether anything changed
+    pub fn normalize_hex(&mut self) -> bool {
+        let salt = match &mut self.crypto.kdfparams {
+            KdfParams::Argon2 { salt, .. } => salt,
+            KdfParams::Pbkdf2 { salt, .. } => salt,
+        };
+
+        let mut changed = false;
+        for field in [&mut self.crypto.ciphertext, &mut self.crypto.cipherparams.iv, &mut self.crypto.mac, salt] {
+            let normalized = field.trim().trim_start_matches("0x").to_ascii_lowercase();
+            if *field != normalized {
+                *field = normalized;
+                changed = true;
             }
-            .into()
-        })
+        }
+        changed
     }
 
     /// Get KDF parameters
@@ -263,45 +523,90 @@ impl Keystore {
             .into());
         }
 
-        // Validate address format
-        crate::utils::validate_ethereum_address(&self.metadata.address)?;
-
-        // Validate network
-        if !config::is_supported_network(&self.metadata.network) {
+        // Validate cipher
+        if self.crypto.cipher != "aes-256-gcm" {
             return Err(ValidationError::InvalidKeystoreSchema {
-                error: format!("Unsupported network: {}", self.metadata.network),
+                error: format!("Unsupported cipher: {}", self.crypto.cipher),
                 file_path: "unknown".to_string(),
             }
             .into());
         }
 
-        // Validate cipher
-        if self.crypto.cipher != "aes-256-gcm" {
+        // Validate KDF: the name must match the parameters it is derived with
+        if self.crypto.kdf != self.crypto.kdfparams.kdf_name() {
+            let error = match self.crypto.kdf.as_str() {
+                "argon2id" | "pbkdf2" => format!(
+                    "KDF '{}' does not match its {} parameters",
+                    self.crypto.kdf,
+                    self.crypto.kdfparams.kdf_name()
+                ),
+                _ => format!("Unsupported KDF: {}", self.crypto.kdf),
+            };
             return Err(ValidationError::InvalidKeystoreSchema {
-                error: format!("Unsupported cipher: {}", self.crypto.cipher),
+                error,
                 file_path: "unknown".to_string(),
             }
             .into());
         }
 
-        // Validate KDF
-        match self.crypto.kdf.as_str() {
-            "argon2id" | "pbkdf2" => {}
-            _ => {
+        // Validate hex fields without decoding them
+        check_hex(&self.crypto.ciphertext, "ciphertext")?;
+        check_hex(self.salt_hex(), "salt")?;
+        check_hex(&self.crypto.cipherparams.iv, "nonce")?;
+        check_hex(&self.crypto.mac, "MAC")?;
+
+        // Validate the hardware factor
+        match &self.crypto.hardware {
+            Some(HardwareFactor::YubiKey { slot, challenge }) => {
+                if !(1..=2).contains(slot) {
+                    return Err(ValidationError::InvalidKeystoreSchema {
+                        error: format!("Invalid YubiKey slot: {}", slot),
+                        file_path: "unknown".to_string(),
+                    }
+                    .into());
+                }
+                if challenge.is_empty() || challenge.len() > 2 * crate::security::yubikey::MAX_CHALLENGE_LENGTH {
+                    return Err(ValidationError::InvalidKeystoreSchema {
+                        error: "Invalid YubiKey challenge length".to_string(),
+                        file_path: "unknown".to_string(),
+                    }
+                    .into());
+                }
+                check_hex(challenge, "YubiKey challenge")?;
+            }
+            Some(HardwareFactor::Tpm { public, private }) => {
+                check_hex(public, "TPM public area")?;
+                check_hex(private, "TPM private area")?;
+            }
+            None => {}
+        }
+
+        // Validate passphrase shares
+        if let Some(set) = &self.crypto.shares {
+            let count = set.shares.len();
+            let labels: std::collections::BTreeSet<_> = set.shares.iter().map(|share| share.label.as_str()).collect();
+            let problem = if count > config::crypto::MAX_KEY_SHARES || set.threshold == 0 || usize::from(set.threshold) > count {
+                Some(format!("Invalid share threshold: {} of {}", set.threshold, count))
+            } else if labels.len() != count {
+                Some("Share labels must be unique".to_string())
+            } else if set.shares.iter().any(|share| !matches!(share.kdfparams, KdfParams::Argon2 { .. })) {
+                Some("Shares must use Argon2id".to_string())
+            } else {
+                None
+            };
+            if let Some(error) = problem {
                 return Err(ValidationError::InvalidKeystoreSchema {
-                    error: format!("Unsupported KDF: {}", self.crypto.kdf),
+                    error,
                     file_path: "unknown".to_string(),
                 }
-                .into())
+                .into());
+            }
+            for share in &set.shares {
+                check_hex(&share.iv, "share nonce")?;
+                check_hex(&share.ciphertext, "share ciphertext")?;
             }
         }
 
-        // Validate hex fields
-        self.encrypted_data()?;
-        self.salt()?;
-        self.nonce()?;
-        self.mac()?;
-
         // Validate KDF parameters
         match &self.crypto.kdfparams {
             KdfParams::Argon2 {
@@ -354,9 +659,9 @@ impl Keystore {
         Ok(())
     }
 
-    /// Serialize to JSON string
+    /// Serialize to canonical JSON (sorted keys, compact) so output is byte-stable
     pub fn to_json(&self) -> WalletResult<String> {
-        serde_json::to_string_pretty(self).map_err(|e| {
+        crate::utils::to_canonical_json(self).map_err(|e| {
             ValidationError::InvalidKeystoreSchema {
                 error: format!("JSON serialization failed: {}", e),
                 file_path: "unknown".to_string(),
@@ -381,6 +686,34 @@ impl Keystore {
     }
 }
 
+/// Decode a hex keystore field (optionally `0x`-prefixed) into `out`, reusing its allocation
+fn decode_hex_into(field: &str, out: &mut Vec<u8>, name: &str) -> WalletResult<()> {
+    let digits = field.trim_start_matches("0x");
+    out.clear();
+    out.resize(digits.len() / 2, 0);
+    hex::decode_to_slice(digits, out).map_err(|e| hex_error(name, e))
+}
+
+/// Check that a hex keystore field decodes, without allocating
+fn check_hex(field: &str, name: &str) -> WalletResult<()> {
+    let digits = field.trim_start_matches("0x");
+    if let Some(index) = digits.bytes().position(|b| !b.is_ascii_hexdigit()) {
+        let c = digits[index..].chars().next().unwrap_or_default();
+        return Err(hex_error(name, hex::FromHexError::InvalidHexCharacter { c, index }));
+    }
+    if digits.len() % 2 != 0 {
+        return Err(hex_error(name, hex::FromHexError::OddLength));
+    }
+    Ok(())
+}
+
+fn hex_error(name: &str, e: hex::FromHexError) -> crate::errors::WalletError {
+    CryptographicError::DataCorruption {
+        details: format!("Invalid {} hex: {}", name, e),
+    }
+    .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -389,8 +722,8 @@ mod tests {
     fn test_keystore_creation() {
         let keystore = Keystore::with_argon2(
             Some("test".to_string()),
-            "0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99".to_string(),
-            "mainnet".to_string(),
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
             vec![1, 2, 3, 4],
             vec![5, 6, 7, 8],
             vec![9, 10, 11, 12],
@@ -410,8 +743,8 @@ mod tests {
     fn test_keystore_validation() {
         let keystore = Keystore::with_argon2(
             None,
-            "0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99".to_string(),
-            "mainnet".to_string(),
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
             vec![1, 2, 3, 4],
             vec![5, 6, 7, 8],
             vec![9, 10, 11, 12],
@@ -422,14 +755,56 @@ mod tests {
         );
 
         assert!(keystore.validate().is_ok());
+
+        // The non-decoding check agrees with the decoder
+        for ciphertext in ["0x01020304", "0102030", "01zz", ""] {
+            let mut edited = keystore.clone();
+            edited.crypto.ciphertext = ciphertext.to_string();
+            let decodes = hex::decode(ciphertext.trim_start_matches("0x")).is_ok();
+            match edited.validate() {
+                Ok(()) => assert!(decodes, "{}", ciphertext),
+                Err(err) => assert!(!decodes && err.code() == "CRYPTO_005", "{}", ciphertext),
+            }
+        }
+    }
+
+    #[test]
+    fn test_keystore_tags() {
+        let mut keystore = Keystore::with_argon2(
+            None,
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
+            vec![1, 2, 3, 4],
+            vec![5, 6, 7, 8],
+            vec![9, 10, 11, 12],
+            vec![13, 14, 15, 16],
+            47104,
+            1,
+            1,
+        );
+
+        // Untagged keystores serialize exactly as before
+        assert!(!keystore.to_json().unwrap().contains("tags"));
+
+        let added = keystore.metadata.add_tags(&["treasury".to_string(), "ops".to_string()]).unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(keystore.metadata.add_tags(&["Treasury".to_string()]).unwrap(), 0);
+        assert!(keystore.metadata.add_tags(&["bad tag".to_string()]).is_err());
+        assert_eq!(keystore.metadata.tags, vec!["ops", "treasury"]);
+
+        let reloaded = Keystore::from_json(&keystore.to_json().unwrap()).unwrap();
+        assert!(reloaded.metadata.has_tag("TREASURY"));
+
+        assert_eq!(keystore.metadata.remove_tags(&["OPS".to_string()]), 1);
+        assert_eq!(keystore.metadata.tags, vec!["treasury"]);
     }
 
     #[test]
     fn test_keystore_serialization() {
         let keystore = Keystore::with_argon2(
             None,
-            "0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99".to_string(),
-            "mainnet".to_string(),
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
             vec![1, 2, 3, 4],
             vec![5, 6, 7, 8],
             vec![9, 10, 11, 12],
@@ -446,12 +821,36 @@ mod tests {
         assert_eq!(keystore.metadata.address, restored.metadata.address);
     }
 
+    #[test]
+    fn test_keystore_canonical_json() {
+        let keystore = Keystore::with_argon2(
+            None,
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
+            vec![1, 2, 3, 4],
+            vec![5, 6, 7, 8],
+            vec![9, 10, 11, 12],
+            vec![13, 14, 15, 16],
+            47104,
+            1,
+            1,
+        );
+
+        let json = keystore.to_json().unwrap();
+        let restored = Keystore::from_json(&json).unwrap();
+
+        // Re-serializing a parsed keystore yields identical bytes
+        assert_eq!(restored.to_json().unwrap(), json);
+        assert!(json.starts_with(r#"{"crypto":{"cipher":"aes-256-gcm","cipherparams":"#));
+        assert!(!json.contains('\n'));
+    }
+
     #[test]
     fn test_data_extraction() {
         let keystore = Keystore::with_argon2(
             None,
-            "0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99".to_string(),
-            "mainnet".to_string(),
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
             vec![1, 2, 3, 4],
             vec![5, 6, 7, 8],
             vec![9, 10, 11, 12],
@@ -465,5 +864,94 @@ mod tests {
         assert_eq!(keystore.salt().unwrap(), vec![5, 6, 7, 8]);
         assert_eq!(keystore.nonce().unwrap(), vec![9, 10, 11, 12]);
         assert_eq!(keystore.mac().unwrap(), vec![13, 14, 15, 16]);
+
+        let mut buffer = Vec::with_capacity(64);
+        let capacity = buffer.capacity();
+        keystore.encrypted_data_into(&mut buffer).unwrap();
+        assert_eq!(buffer, [1, 2, 3, 4]);
+        assert_eq!(buffer.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_kdf_params_follow_kdf_field() {
+        let keystore = Keystore::with_argon2(
+            None,
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
+            vec![1, 2, 3, 4],
+            vec![5, 6, 7, 8],
+            vec![9, 10, 11, 12],
+            vec![13, 14, 15, 16],
+            47104,
+            1,
+            1,
+        );
+        let json: serde_json::Value = serde_json::from_str(&keystore.to_json().unwrap()).unwrap();
+        let edit = |pointer: &str, value: serde_json::Value| {
+            let mut edited = json.clone();
+            *edited.pointer_mut(pointer).unwrap() = value;
+            Keystore::from_json(&edited.to_string())
+        };
+
+        // Existing PBKDF2 keystores still read
+        let pbkdf2 = serde_json::json!({"dklen": 32, "c": 600000, "prf": "hmac-sha256", "salt": "0506"});
+        let mut crypto = json["crypto"].clone();
+        crypto["kdf"] = "pbkdf2".into();
+        crypto["kdfparams"] = pbkdf2.clone();
+        let legacy = edit("/crypto", crypto).unwrap();
+        assert!(matches!(legacy.crypto.kdfparams, KdfParams::Pbkdf2 { c: 600000, .. }));
+
+        // Parameters that do not match the named KDF are rejected
+        assert!(edit("/crypto/kdf", "pbkdf2".into()).is_err());
+        assert!(edit("/crypto/kdfparams", pbkdf2).is_err());
+        assert!(edit("/crypto/kdf", "scrypt".into()).is_err());
+        let mut extra = json["crypto"]["kdfparams"].clone();
+        extra["c"] = 1.into();
+        assert!(edit("/crypto/kdfparams", extra).is_err());
+        assert!(edit("/crypto/kdfparams/memory", "47104".into()).is_err());
+        assert!(edit("/crypto/kdfparams", serde_json::json!({"dklen": 32, "salt": "0506"})).is_err());
+
+        let mut mismatched = keystore.clone();
+        mismatched.crypto.kdf = "pbkdf2".to_string();
+        assert!(mismatched.validate().is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_corrupted_keystores_are_rejected() {
+        use rand::{Rng, SeedableRng};
+
+        let keystore = Keystore::with_argon2(
+            Some("test".to_string()),
+            "0x742d35Cc6634C0532925a3b844Bc454e4438f44e".parse().unwrap(),
+            Network::Mainnet,
+            vec![1, 2, 3, 4],
+            vec![5, 6, 7, 8],
+            vec![9, 10, 11, 12],
+            vec![13, 14, 15, 16],
+            47104,
+            1,
+            1,
+        );
+        let json = keystore.to_json().unwrap();
+
+        // Every truncation fails cleanly
+        for end in 0..json.len() {
+            assert!(Keystore::from_json(&json[..end]).is_err(), "{}", &json[..end]);
+        }
+
+        // Random byte flips never panic, and whatever still parses is valid
+        // with matching KDF name and parameters
+        let mut rng = rand::rngs::StdRng::seed_from_u64(4625);
+        for _ in 0..2000 {
+            let mut bytes = json.clone().into_bytes();
+            for _ in 0..rng.gen_range(1..4) {
+                let index = rng.gen_range(0..bytes.len());
+                bytes[index] = rng.gen_range(0x20..0x7f);
+            }
+            if let Ok(parsed) = Keystore::from_json(&String::from_utf8(bytes).unwrap()) {
+                assert!(parsed.validate().is_ok());
+                assert_eq!(parsed.crypto.kdf, parsed.crypto.kdfparams.kdf_name());
+            }
+        }
+    }
+}
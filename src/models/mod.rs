@@ -4,12 +4,24 @@
 //! All models follow the constitutional requirements for type safety and validation.
 
 pub mod address;
+pub mod chain;
 pub mod command;
+pub mod derivation_path;
 pub mod keystore;
+pub mod network;
+pub mod secret;
+pub mod validator_keystore;
 pub mod wallet;
+pub mod watch_only;
 
 // Re-export main types
-pub use address::Address;
+pub use address::{Address, AddressBook, AddressCase, EthereumAddress};
+pub use chain::ChainInfo;
 pub use command::{CommandResult, OutputFormat};
+pub use derivation_path::{ChildNumber, DerivationPath, PathPreset};
 pub use keystore::Keystore;
-pub use wallet::Wallet;
\ No newline at end of file
+pub use network::Network;
+pub use secret::{SecretString, SecureSeed};
+pub use validator_keystore::ValidatorKeystore;
+pub use wallet::{Wallet, WalletAccount};
+pub use watch_only::WatchOnlyWallet;
\ No newline at end of file
@@ -0,0 +1,224 @@
+//! # Network Model
+//!
+//! The chain a wallet, keystore or address belongs to. Known networks are
+//! variants; any other EVM chain is a [`Network::Custom`] written as
+//! `name:chain_id` (e.g. `devnet:1337`). Every network carries its EIP-155
+//! chain ID, so signing code never has to look one up by name.
+//!
+//! Networks serialize as the same string they parse from, so keystores and
+//! config files written with plain network names read back unchanged.
+
+use crate::config;
+use crate::errors::{UserInputError, WalletResult};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+/// Longest accepted custom network name
+const MAX_CUSTOM_NAME_LENGTH: usize = 32;
+
+/// An EVM network
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Network {
+    /// Ethereum mainnet (chain 1)
+    #[default]
+    Mainnet,
+    /// Sepolia testnet (chain 11155111)
+    Sepolia,
+    /// Goerli testnet (chain 5), deprecated upstream
+    Goerli,
+    /// Holesky testnet (chain 17000)
+    Holesky,
+    /// Any other chain, by name and EIP-155 chain ID
+    Custom {
+        /// Lowercase name, also the key for per-network config tables
+        name: String,
+        /// EIP-155 chain ID
+        chain_id: u64,
+    },
+}
+
+impl Network {
+    /// Networks known by name
+    pub const KNOWN: [Network; 4] = [Network::Mainnet, Network::Sepolia, Network::Goerli, Network::Holesky];
+
+    /// Custom network, checking the name and chain ID
+    pub fn custom(name: &str, chain_id: u64) -> WalletResult<Self> {
+        let valid_name = !name.is_empty()
+            && name.len() <= MAX_CUSTOM_NAME_LENGTH
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_name || Self::known(name).is_some() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "network".to_string(),
+                value: name.to_string(),
+                expected: format!(
+                    "a custom network name of up to {} lowercase letters, digits, '-' or '_', other than a known network",
+                    MAX_CUSTOM_NAME_LENGTH
+                ),
+            }
+            .into());
+        }
+        if chain_id == 0 {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "chain_id".to_string(),
+                value: chain_id.to_string(),
+                expected: "a non-zero EIP-155 chain ID".to_string(),
+            }
+            .into());
+        }
+        Ok(Network::Custom {
+            name: name.to_string(),
+            chain_id,
+        })
+    }
+
+    /// Known network with this chain ID
+    pub fn from_chain_id(chain_id: u64) -> Option<Self> {
+        Self::KNOWN.into_iter().find(|network| network.chain_id() == chain_id)
+    }
+
+    /// Network name, without the chain ID of a custom network
+    pub fn name(&self) -> &str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Sepolia => "sepolia",
+            Network::Goerli => "goerli",
+            Network::Holesky => "holesky",
+            Network::Custom { name, .. } => name,
+        }
+    }
+
+    /// EIP-155 chain ID
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Network::Mainnet => 1,
+            Network::Sepolia => 11_155_111,
+            Network::Goerli => 5,
+            Network::Holesky => 17_000,
+            Network::Custom { chain_id, .. } => *chain_id,
+        }
+    }
+
+    /// Check if this is Ethereum mainnet, where funds are real
+    pub fn is_mainnet(&self) -> bool {
+        matches!(self, Network::Mainnet)
+    }
+
+    /// Check if this is a known testnet; custom networks may hold real funds and are not
+    pub fn is_testnet(&self) -> bool {
+        matches!(self, Network::Sepolia | Network::Goerli | Network::Holesky)
+    }
+
+    /// Check if this is a known network rather than a custom one
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Network::Custom { .. })
+    }
+
+    fn known(name: &str) -> Option<Self> {
+        Self::KNOWN.into_iter().find(|network| network.name() == name)
+    }
+}
+
+impl FromStr for Network {
+    type Err = crate::WalletError;
+
+    /// Parse a known network name or `name:chain_id`
+    fn from_str(network: &str) -> WalletResult<Self> {
+        if let Some(known) = Self::known(network) {
+            return Ok(known);
+        }
+        if let Some((name, chain_id)) = network.split_once(':') {
+            if let Ok(chain_id) = chain_id.parse::<u64>() {
+                return Self::custom(name, chain_id);
+            }
+        }
+
+        let mut supported: Vec<String> = config::SUPPORTED_NETWORKS.iter().map(|s| s.to_string()).collect();
+        supported.push("<name>:<chain id>".to_string());
+        Err(UserInputError::InvalidNetwork {
+            network: network.to_string(),
+            supported,
+        }
+        .into())
+    }
+}
+
+/// The form networks parse from: the name, plus `:chain_id` for custom networks
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Network::Custom { name, chain_id } => f.pad(&format!("{}:{}", name, chain_id)),
+            known => f.pad(known.name()),
+        }
+    }
+}
+
+/// Compares with the string form shown by `Display`
+impl PartialEq<str> for Network {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Network::Custom { name, chain_id } => other.split_once(':') == Some((name, &chain_id.to_string())),
+            known => known.name() == other,
+        }
+    }
+}
+
+impl PartialEq<&str> for Network {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<String> for Network {
+    fn eq(&self, other: &String) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl Serialize for Network {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Network {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let network = String::deserialize(deserializer)?;
+        network.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_parsing() {
+        for network in Network::KNOWN {
+            assert_eq!(network.name().parse::<Network>().unwrap(), network);
+            assert_eq!(Network::from_chain_id(network.chain_id()), Some(network.clone()));
+            assert!(network.is_known());
+        }
+        assert_eq!("sepolia".parse::<Network>().unwrap().chain_id(), 11_155_111);
+
+        let devnet: Network = "devnet:1337".parse().unwrap();
+        assert_eq!((devnet.name(), devnet.chain_id()), ("devnet", 1337));
+        assert_eq!(devnet.to_string(), "devnet:1337");
+        assert_eq!(devnet, "devnet:1337");
+        assert!(!devnet.is_known() && !devnet.is_mainnet() && !devnet.is_testnet());
+        assert!(Network::Holesky.is_testnet());
+
+        // Known names cannot be reused with another chain ID
+        assert!("mainnet:5".parse::<Network>().is_err());
+        assert!("devnet:0".parse::<Network>().is_err());
+        assert!("Devnet:1337".parse::<Network>().is_err());
+        assert!(matches!(
+            "invalid".parse::<Network>(),
+            Err(crate::WalletError::UserInput(UserInputError::InvalidNetwork { .. }))
+        ));
+
+        // Serialized as parsed, so existing keystores and configs read back
+        assert_eq!(serde_json::to_string(&Network::Mainnet).unwrap(), "\"mainnet\"");
+        assert_eq!(serde_json::from_str::<Network>("\"devnet:1337\"").unwrap(), devnet);
+        assert!(serde_json::from_str::<Network>("\"invalid\"").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! # Secret Values
+//!
+//! Wrappers for the secrets a wallet holds in memory. Both are wiped when
+//! dropped, locked into RAM under hardened memory, and redacted from
+//! `Debug` output. [`SecretString`] serializes as a plain string, so a
+//! wallet's mnemonic passes through keystore encryption without being
+//! copied into an ordinary `String` on the way.
+
+use crate::security::memory;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// String secret such as a mnemonic or BIP39 passphrase
+#[derive(Default, Zeroize, ZeroizeOnDrop)]
+pub struct SecretString {
+    data: String,
+    // Dropped after `data` is zeroized
+    #[zeroize(skip)]
+    locked: memory::LockedRegion,
+}
+
+impl SecretString {
+    /// Take ownership of `data`
+    pub fn new(data: String) -> Self {
+        let locked = memory::lock(data.as_bytes());
+        Self { data, locked }
+    }
+
+    /// The secret itself
+    pub fn expose(&self) -> &str {
+        &self.data
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl Clone for SecretString {
+    fn clone(&self) -> Self {
+        Self::new(self.data.clone())
+    }
+}
+
+impl PartialEq for SecretString {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl Eq for SecretString {}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(data: String) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(data: &str) -> Self {
+        Self::new(data.to_string())
+    }
+}
+
+impl Serialize for SecretString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.data)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Seed or key bytes
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct SecureSeed {
+    bytes: Vec<u8>,
+    // Dropped after `bytes` is zeroized
+    #[zeroize(skip)]
+    locked: memory::LockedRegion,
+}
+
+impl SecureSeed {
+    /// Create new secure seed
+    pub fn new(bytes: Vec<u8>) -> Self {
+        let locked = memory::lock(&bytes);
+        Self { bytes, locked }
+    }
+
+    /// Get seed bytes (use carefully)
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get seed length
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Check if seed is empty
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+impl Clone for SecureSeed {
+    fn clone(&self) -> Self {
+        Self::new(self.bytes.clone())
+    }
+}
+
+impl std::fmt::Debug for SecureSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SecureSeed([REDACTED; {}])", self.bytes.len())
+    }
+}
@@ -0,0 +1,211 @@
+//! # Validator Keystore Model
+//!
+//! EIP-2335 keystore format used by Ethereum consensus-layer validators.
+//! The secret is a BLS12-381 private key protected by scrypt or PBKDF2,
+//! AES-128-CTR, and a SHA-256 checksum.
+
+use crate::errors::{CryptographicError, ValidationError, WalletResult};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// EIP-2335 keystore version
+pub const EIP2335_VERSION: u32 = 4;
+
+/// BLS12-381 subgroup order r (big-endian)
+const BLS_CURVE_ORDER: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// EIP-2335 keystore file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorKeystore {
+    /// Encryption modules
+    pub crypto: ValidatorCrypto,
+
+    /// Optional human-readable description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// BLS public key (hex encoded, 48 bytes)
+    pub pubkey: String,
+
+    /// EIP-2334 derivation path, empty if the key was not derived
+    pub path: String,
+
+    /// Keystore UUID
+    pub uuid: String,
+
+    /// Keystore format version (always 4)
+    pub version: u32,
+}
+
+/// KDF, checksum, and cipher modules
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorCrypto {
+    /// Key derivation module
+    pub kdf: CryptoModule<ValidatorKdfParams>,
+    /// Checksum module
+    pub checksum: CryptoModule<EmptyParams>,
+    /// Cipher module
+    pub cipher: CryptoModule<ValidatorCipherParams>,
+}
+
+/// One EIP-2335 crypto module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CryptoModule<P> {
+    /// Function name (e.g. "scrypt", "sha256", "aes-128-ctr")
+    pub function: String,
+    /// Function parameters
+    pub params: P,
+    /// Module output (hex encoded)
+    pub message: String,
+}
+
+/// EIP-2335 KDF parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ValidatorKdfParams {
+    /// scrypt parameters
+    Scrypt {
+        /// Derived key length
+        dklen: u32,
+        /// CPU/memory cost
+        n: u32,
+        /// Block size
+        r: u32,
+        /// Parallelism
+        p: u32,
+        /// Salt (hex encoded)
+        salt: String,
+    },
+    /// PBKDF2 parameters
+    Pbkdf2 {
+        /// Derived key length
+        dklen: u32,
+        /// Iteration count
+        c: u32,
+        /// Pseudo-random function ("hmac-sha256")
+        prf: String,
+        /// Salt (hex encoded)
+        salt: String,
+    },
+}
+
+/// Checksum module parameters (always empty)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmptyParams {}
+
+/// AES-128-CTR parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorCipherParams {
+    /// Initial counter block (hex encoded, 16 bytes)
+    pub iv: String,
+}
+
+impl ValidatorKeystore {
+    /// Parse a keystore from JSON and check its structure
+    pub fn from_json(json: &str) -> WalletResult<Self> {
+        let keystore: Self = serde_json::from_str(json).map_err(|e| {
+            ValidationError::InvalidKeystoreSchema {
+                error: e.to_string(),
+                file_path: String::new(),
+            }
+        })?;
+        keystore.validate()?;
+        Ok(keystore)
+    }
+
+    /// Serialize to pretty-printed JSON
+    pub fn to_json(&self) -> WalletResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// KDF function name
+    pub fn kdf_name(&self) -> &str {
+        &self.crypto.kdf.function
+    }
+
+    /// Check version, module names, and field encodings
+    pub fn validate(&self) -> WalletResult<()> {
+        let invalid = |error: String| ValidationError::InvalidKeystoreSchema {
+            error,
+            file_path: String::new(),
+        };
+
+        if self.version != EIP2335_VERSION {
+            return Err(invalid(format!("unsupported version {}", self.version)).into());
+        }
+
+        let kdf_matches = matches!(
+            (&self.crypto.kdf.params, self.crypto.kdf.function.as_str()),
+            (ValidatorKdfParams::Scrypt { .. }, "scrypt") | (ValidatorKdfParams::Pbkdf2 { .. }, "pbkdf2")
+        );
+        if !kdf_matches {
+            return Err(invalid(format!("unsupported kdf '{}'", self.crypto.kdf.function)).into());
+        }
+        if self.crypto.checksum.function != "sha256" {
+            return Err(invalid(format!("unsupported checksum '{}'", self.crypto.checksum.function)).into());
+        }
+        if self.crypto.cipher.function != "aes-128-ctr" {
+            return Err(invalid(format!("unsupported cipher '{}'", self.crypto.cipher.function)).into());
+        }
+
+        let pubkey = hex::decode(self.pubkey.trim_start_matches("0x")).unwrap_or_default();
+        if !self.pubkey.is_empty() && pubkey.len() != 48 {
+            return Err(invalid("pubkey must be 48 hex-encoded bytes".to_string()).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// BLS12-381 secret key
+pub struct BlsSecretKey(Zeroizing<[u8; 32]>);
+
+impl BlsSecretKey {
+    /// Wrap a big-endian scalar, rejecting zero and values >= r
+    pub fn from_bytes(bytes: &[u8]) -> WalletResult<Self> {
+        let scalar: [u8; 32] = bytes.try_into().map_err(|_| CryptographicError::InvalidPrivateKey {
+            details: format!("Expected 32 bytes, got {}", bytes.len()),
+            expected: "32-byte BLS12-381 secret key".to_string(),
+        })?;
+
+        if scalar.iter().all(|b| *b == 0) || scalar >= BLS_CURVE_ORDER {
+            return Err(CryptographicError::InvalidPrivateKey {
+                details: "Scalar is zero or not below the BLS12-381 group order".to_string(),
+                expected: "32-byte BLS12-381 secret key".to_string(),
+            }
+            .into());
+        }
+
+        Ok(Self(Zeroizing::new(scalar)))
+    }
+
+    /// Raw big-endian scalar
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for BlsSecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("BlsSecretKey(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_key_range() {
+        assert!(BlsSecretKey::from_bytes(&[0u8; 32]).is_err());
+        assert!(BlsSecretKey::from_bytes(&BLS_CURVE_ORDER).is_err());
+        assert!(BlsSecretKey::from_bytes(&[1u8; 31]).is_err());
+
+        let mut below = BLS_CURVE_ORDER;
+        below[31] = 0;
+        assert!(BlsSecretKey::from_bytes(&below).is_ok());
+    }
+}
@@ -4,35 +4,48 @@
 //! Implements secure patterns with zeroize for memory cleanup.
 
 use crate::config;
-use crate::errors::{CryptographicError, WalletResult};
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::{DerivationPath, EthereumAddress, Network, PathPreset, SecretString, SecureSeed};
+use coins_bip32::xkeys::Parent;
 use ethers::prelude::*;
-use ethers::signers::coins_bip39::English;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
+/// Shortest seed BIP32 accepts
+const MIN_SEED_LENGTH: usize = 16;
+
+/// Longest seed BIP32 accepts
+const MAX_SEED_LENGTH: usize = 64;
+
+/// Fewest paths worth a thread of their own in [`Wallet::derive_many`]
+const MIN_PATHS_PER_THREAD: usize = 32;
+
 /// HD Wallet with BIP39/BIP44 support
 #[derive(Debug, Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
 pub struct Wallet {
     /// BIP39 mnemonic phrase
-    #[zeroize(skip)]
-    mnemonic: String,
+    mnemonic: SecretString,
 
     /// Master private key derived from mnemonic
     #[serde(skip)]
-    master_private_key: Option<Vec<u8>>,
+    master_private_key: Option<SecureSeed>,
 
     /// Primary Ethereum address (index 0)
     #[zeroize(skip)]
-    address: String,
+    address: EthereumAddress,
 
     /// Base HD derivation path
     #[zeroize(skip)]
-    derivation_path: String,
+    derivation_path: DerivationPath,
+
+    /// Account layout below the base path
+    #[zeroize(skip)]
+    #[serde(default)]
+    path_preset: PathPreset,
 
     /// Target network
     #[zeroize(skip)]
-    network: String,
+    network: Network,
 
     /// Wallet creation timestamp
     #[zeroize(skip)]
@@ -41,91 +54,159 @@ pub struct Wallet {
     /// Optional wallet alias
     #[zeroize(skip)]
     alias: Option<String>,
+
+    /// Additional derived accounts stored in the same keystore
+    #[zeroize(skip)]
+    #[serde(default)]
+    accounts: Vec<WalletAccount>,
+
+    /// Whether the seed uses a BIP39 passphrase (the passphrase itself is never stored)
+    #[zeroize(skip)]
+    #[serde(default)]
+    bip39_passphrase: bool,
+
+    /// BIP39 passphrase, held in memory only
+    #[serde(skip)]
+    passphrase: Option<SecretString>,
+
+    /// Hex BIP39 seed of wallets imported without their mnemonic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<SecretString>,
+
+    /// Hex secp256k1 key of wallets imported from a single private key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    private_key: Option<SecretString>,
 }
 
 impl Wallet {
     /// Create a new wallet from mnemonic
+    #[tracing::instrument(level = "debug", skip_all, fields(network = %network))]
     pub fn from_mnemonic(
         mnemonic: &str,
-        network: &str,
+        network: &Network,
         alias: Option<String>,
     ) -> WalletResult<Self> {
         // Validate mnemonic
-        let bip39_mnemonic = bip39::Mnemonic::from_str(mnemonic).map_err(|e| {
-            CryptographicError::InvalidMnemonic {
-                details: e.to_string(),
-                suggestion: "Verify the mnemonic phrase has the correct number of words (12 or 24) and all words are from the BIP39 wordlist.".to_string(),
-            }
-        })?;
+        let bip39_mnemonic = Self::parse_mnemonic(mnemonic)?;
 
         // Generate seed from mnemonic
-        let seed = bip39_mnemonic.to_seed("");
+        let mut seed = bip39_mnemonic.to_seed("");
+        let master_private_key = SecureSeed::new(seed.to_vec());
+        seed.zeroize();
 
-        // Create HD wallet
-        let wallet = MnemonicBuilder::<English>::default()
-            .phrase(mnemonic)
-            .build()
-            .map_err(|e| CryptographicError::AddressGenerationFailed {
-                details: e.to_string(),
-            })?;
-
-        let address = format!("{:?}", wallet.address());
-        let derivation_path = config::DEFAULT_DERIVATION_PATH.to_string();
+        let address = Self::primary_address(mnemonic, "")?;
+        let derivation_path = DerivationPath::default();
 
         Ok(Self {
-            mnemonic: mnemonic.to_string(),
-            master_private_key: Some(seed.to_vec()),
+            mnemonic: SecretString::from(mnemonic),
+            master_private_key: Some(master_private_key),
             address,
             derivation_path,
-            network: network.to_string(),
+            path_preset: PathPreset::default(),
+            network: network.clone(),
             created_at: chrono::Utc::now(),
             alias,
+            accounts: Vec::new(),
+            bip39_passphrase: false,
+            passphrase: None,
+            seed: None,
+            private_key: None,
         })
     }
 
     /// Create wallet from private key
+    #[tracing::instrument(level = "debug", skip_all, fields(network = %network))]
     pub fn from_private_key(
         private_key: &str,
-        network: &str,
+        network: &Network,
         alias: Option<String>,
     ) -> WalletResult<Self> {
-        // Remove 0x prefix if present
-        let key_str = private_key.strip_prefix("0x").unwrap_or(private_key);
+        // Accept hex, base64, WIF and byte-array encodings
+        let key = crate::utils::decode_private_key(private_key)?;
 
-        // Validate private key format
-        if key_str.len() != 64 {
-            return Err(CryptographicError::InvalidPrivateKey {
-                details: format!("Expected 64 hex characters, got {}", key_str.len()),
-                expected: "64 hex characters (with or without 0x prefix)".to_string(),
-            }
-            .into());
-        }
-
-        // Parse private key
         // Parse private key into wallet
-        let wallet = key_str.parse::<LocalWallet>().map_err(|e| {
+        let wallet = LocalWallet::from_bytes(key.as_slice()).map_err(|e| {
             CryptographicError::InvalidPrivateKey {
                 details: e.to_string(),
                 expected: "valid secp256k1 private key".to_string(),
             }
         })?;
-        let address = format!("{:?}", wallet.address());
+        let address = EthereumAddress::from(wallet.address());
 
         Ok(Self {
-            mnemonic: String::new(), // No mnemonic for private key import
-            master_private_key: Some(vec![]), // Placeholder for now
+            mnemonic: SecretString::default(), // No mnemonic for private key import
+            master_private_key: None,
             address,
-            derivation_path: config::DEFAULT_DERIVATION_PATH.to_string(),
-            network: network.to_string(),
+            derivation_path: DerivationPath::default(),
+            path_preset: PathPreset::default(),
+            network: network.clone(),
+            created_at: chrono::Utc::now(),
+            alias,
+            accounts: Vec::new(),
+            bip39_passphrase: false,
+            passphrase: None,
+            seed: None,
+            private_key: Some(SecretString::new(hex::encode(key.as_slice()))),
+        })
+    }
+
+    /// Create an HD wallet from a raw BIP39 seed whose mnemonic is not available
+    ///
+    /// Addresses derive exactly as they would from the mnemonic, but a passphrase
+    /// cannot be added since it is already part of the seed.
+    #[tracing::instrument(level = "debug", skip_all, fields(network = %network))]
+    pub fn from_seed(seed: &[u8], network: &Network, alias: Option<String>) -> WalletResult<Self> {
+        if !(MIN_SEED_LENGTH..=MAX_SEED_LENGTH).contains(&seed.len()) {
+            return Err(CryptographicError::InvalidPrivateKey {
+                details: format!("{}-byte seed", seed.len()),
+                expected: format!("a BIP32 seed of {} to {} bytes (BIP39 seeds are 64)", MIN_SEED_LENGTH, MAX_SEED_LENGTH),
+            }
+            .into());
+        }
+
+        let derivation_path = DerivationPath::default();
+        let address = Self::key_from_seed(seed, &derivation_path.child(0)?)?;
+
+        Ok(Self {
+            mnemonic: SecretString::default(),
+            master_private_key: Some(SecureSeed::new(seed.to_vec())),
+            address: EthereumAddress::from(address.address()),
+            derivation_path,
+            path_preset: PathPreset::default(),
+            network: network.clone(),
             created_at: chrono::Utc::now(),
             alias,
+            accounts: Vec::new(),
+            bip39_passphrase: false,
+            passphrase: None,
+            seed: Some(SecretString::new(hex::encode(seed))),
+            private_key: None,
         })
     }
 
+    /// Create a wallet from BIP39 entropy, regenerating its mnemonic in `language`
+    pub fn from_entropy(
+        entropy: &[u8],
+        language: bip39::Language,
+        network: &Network,
+        alias: Option<String>,
+    ) -> WalletResult<Self> {
+        let mnemonic = zeroize::Zeroizing::new(
+            bip39::Mnemonic::from_entropy_in(language, entropy)
+                .map_err(|e| CryptographicError::InvalidMnemonic {
+                    details: e.to_string(),
+                    suggestion: "BIP39 entropy is 16, 20, 24, 28 or 32 bytes".to_string(),
+                })?
+                .to_string(),
+        );
+        Self::from_mnemonic(&mnemonic, network, alias)
+    }
+
     /// Generate a new random wallet
+    #[tracing::instrument(level = "debug", skip_all, fields(word_count = word_count, network = %network))]
     pub fn generate(
         word_count: u8,
-        network: &str,
+        network: &Network,
         alias: Option<String>,
     ) -> WalletResult<Self> {
         // Validate word count
@@ -141,7 +222,7 @@ impl Wallet {
         let entropy_bits = config::entropy_bits_for_word_count(word_count).unwrap();
 
         // Generate random entropy
-        let mut entropy = vec![0u8; entropy_bits / 8];
+        let mut entropy = zeroize::Zeroizing::new(vec![0u8; entropy_bits / 8]);
         use rand::RngCore;
         rand::thread_rng().fill_bytes(&mut entropy);
 
@@ -153,26 +234,272 @@ impl Wallet {
             }
         })?;
 
-        Self::from_mnemonic(&mnemonic.to_string(), network, alias)
+        Self::from_mnemonic(&zeroize::Zeroizing::new(mnemonic.to_string()), network, alias)
+    }
+
+    /// Switch a new HD wallet to the hidden wallet for `passphrase` (the "25th word")
+    pub fn protect_with_passphrase(&mut self, passphrase: &str) -> WalletResult<()> {
+        if !self.has_mnemonic() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "passphrase".to_string(),
+                value: String::new(),
+                expected: "an HD wallet with a mnemonic".to_string(),
+            }
+            .into());
+        }
+
+        self.address = self.address_with(passphrase)?;
+        self.master_private_key = Some(SecureSeed::new(self.seed_with(passphrase)?.to_vec()));
+        self.bip39_passphrase = true;
+        self.passphrase = Some(SecretString::from(passphrase));
+        self.accounts.clear();
+
+        Ok(())
+    }
+
+    /// Supply the passphrase for a loaded wallet, checking it reproduces the stored address
+    pub fn unlock_passphrase(&mut self, passphrase: &str) -> WalletResult<()> {
+        if !self.bip39_passphrase {
+            return Ok(());
+        }
+
+        let address = self.address_with(passphrase)?;
+        if address != self.address {
+            return Err(CryptographicError::DecryptionFailed {
+                context: "BIP39 passphrase does not match this wallet".to_string(),
+            }
+            .into());
+        }
+
+        self.master_private_key = Some(SecureSeed::new(self.seed_with(passphrase)?.to_vec()));
+        self.passphrase = Some(SecretString::from(passphrase));
+        Ok(())
+    }
+
+    /// Switch an HD wallet to the account layout of another wallet application
+    pub fn set_path_preset(&mut self, preset: PathPreset) -> WalletResult<()> {
+        if !self.is_hd() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "path-preset".to_string(),
+                value: preset.to_string(),
+                expected: "an HD wallet".to_string(),
+            }
+            .into());
+        }
+
+        self.relayout(preset, self.coin_type(), self.account())
+    }
+
+    /// Move an HD wallet to BIP44 account `account` (`m/44'/60'/account'/0`)
+    pub fn set_account(&mut self, account: u32) -> WalletResult<()> {
+        if !self.is_hd() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "account".to_string(),
+                value: account.to_string(),
+                expected: "an HD wallet".to_string(),
+            }
+            .into());
+        }
+
+        self.relayout(self.path_preset, self.coin_type(), account)
+    }
+
+    /// Derive from SLIP-44 coin type `coin_type` (`m/44'/coin_type'/...`) instead of Ethereum's 60
+    pub fn set_coin_type(&mut self, coin_type: u32) -> WalletResult<()> {
+        if !self.is_hd() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "coin-type".to_string(),
+                value: coin_type.to_string(),
+                expected: "an HD wallet".to_string(),
+            }
+            .into());
+        }
+
+        self.relayout(self.path_preset, coin_type, self.account())
+    }
+
+    /// SLIP-44 coin type of the base path
+    pub fn coin_type(&self) -> u32 {
+        self.derivation_path.coin_type().unwrap_or(config::ETHEREUM_COIN_TYPE)
+    }
+
+    /// BIP44 account index of the base path
+    pub fn account(&self) -> u32 {
+        match self.derivation_path.components() {
+            [_, _, account, ..] if account.is_hardened() => account.index(),
+            _ => 0,
+        }
+    }
+
+    /// Rebase the wallet; stored accounts belong to the old layout and are dropped
+    fn relayout(&mut self, preset: PathPreset, coin_type: u32, account: u32) -> WalletResult<()> {
+        let derivation_path = preset.account_base(coin_type, account)?;
+        let address = self.signing_key_at(&preset.account_path(&derivation_path, 0)?)?;
+
+        self.path_preset = preset;
+        self.derivation_path = derivation_path;
+        self.address = EthereumAddress::from(address.address());
+        self.accounts.clear();
+
+        Ok(())
+    }
+
+    /// Account layout below the base path
+    pub fn path_preset(&self) -> PathPreset {
+        self.path_preset
+    }
+
+    /// Check if the seed uses a BIP39 passphrase
+    pub fn uses_passphrase(&self) -> bool {
+        self.bip39_passphrase
+    }
+
+    /// Passphrase for seed derivation, failing if a required one was not supplied
+    pub(crate) fn seed_passphrase(&self) -> WalletResult<&str> {
+        match (&self.passphrase, self.bip39_passphrase) {
+            (Some(passphrase), _) => Ok(passphrase.expose()),
+            (None, false) => Ok(""),
+            (None, true) => Err(UserInputError::InvalidParameters {
+                parameter: "passphrase".to_string(),
+                value: String::new(),
+                expected: "the BIP39 passphrase this wallet was created with".to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Parse a mnemonic in any supported BIP39 language.
+    ///
+    /// English is tried first so phrases made only of words shared with
+    /// another list (e.g. French) are not rejected as ambiguous.
+    pub(crate) fn parse_mnemonic(phrase: &str) -> WalletResult<bip39::Mnemonic> {
+        bip39::Mnemonic::parse_in(bip39::Language::English, phrase)
+            .or_else(|_| bip39::Mnemonic::parse(phrase))
+            .map_err(|e| {
+                CryptographicError::InvalidMnemonic {
+                    details: e.to_string(),
+                    suggestion: "Verify the mnemonic phrase has the correct number of words (12 or 24) and all words are from the BIP39 wordlist.".to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Signing key at `path` for a mnemonic and passphrase
+    fn key_at(mnemonic: &str, passphrase: &str, path: &DerivationPath) -> WalletResult<LocalWallet> {
+        let mut seed = Self::parse_mnemonic(mnemonic)?.to_seed(passphrase);
+        let key = Self::key_from_seed(&seed, path);
+        seed.zeroize();
+        key
+    }
+
+    /// Signing key at `path` below a BIP39 seed
+    fn key_from_seed(seed: &[u8], path: &DerivationPath) -> WalletResult<LocalWallet> {
+        let master = coins_bip32::xkeys::XPriv::root_from_seed(seed, None);
+        let master = master.map_err(|e| CryptographicError::AddressGenerationFailed {
+            details: e.to_string(),
+        })?;
+        let key = master.derive_path(path.to_bip32()).map_err(|_e| CryptographicError::InvalidDerivationPath {
+            path: path.to_string(),
+            expected: "valid BIP44 derivation path".to_string(),
+        })?;
+
+        let signing_key: &ethers::core::k256::ecdsa::SigningKey = key.as_ref();
+        Ok(LocalWallet::from(signing_key.clone()))
+    }
+
+    /// Signing key for the account at `index`
+    ///
+    /// A wallet imported from a private key has only account 0, whose key is
+    /// the imported one.
+    pub(crate) fn signing_key(&self, index: u32) -> WalletResult<LocalWallet> {
+        if self.is_hd() {
+            return self.signing_key_at(&self.account_path(index)?);
+        }
+        if index != 0 {
+            return Err(Self::no_hd_seed(&format!("derive account {}", index)).into());
+        }
+
+        let key = self.private_key.as_ref().ok_or_else(|| CryptographicError::KeyUnavailable {
+            details: "The keystore was saved without its private key by an older version".to_string(),
+            suggestion: "Import the private key again and save it over this keystore".to_string(),
+        })?;
+        let bytes = zeroize::Zeroizing::new(hex::decode(key.expose()).map_err(|e| CryptographicError::DataCorruption {
+            details: format!("stored private key: {}", e),
+        })?);
+        LocalWallet::from_bytes(&bytes).map_err(|e| {
+            CryptographicError::DataCorruption {
+                details: format!("stored private key: {}", e),
+            }
+            .into()
+        })
+    }
+
+    /// Error for HD operations on a wallet imported from a private key
+    fn no_hd_seed(operation: &str) -> CryptographicError {
+        CryptographicError::KeyUnavailable {
+            details: format!("Cannot {}: a wallet imported from a private key has no HD seed", operation),
+            suggestion: "Import the mnemonic or seed to use more than the imported account".to_string(),
+        }
+    }
+
+    /// Full path of the account at `index`
+    pub fn account_path(&self, index: u32) -> WalletResult<DerivationPath> {
+        self.path_preset.account_path(&self.derivation_path, index)
+    }
+
+    /// Primary address of this wallet's mnemonic and layout under `passphrase`
+    fn address_with(&self, passphrase: &str) -> WalletResult<EthereumAddress> {
+        let wallet = Self::key_at(self.mnemonic.expose(), passphrase, &self.account_path(0)?)?;
+        Ok(EthereumAddress::from(wallet.address()))
+    }
+
+    /// Signing key at an arbitrary path of an HD wallet
+    pub(crate) fn signing_key_at(&self, path: &DerivationPath) -> WalletResult<LocalWallet> {
+        Self::key_from_seed(&self.seed()?, path)
+    }
+
+    /// Address at the default path for a mnemonic and passphrase
+    pub(crate) fn primary_address(mnemonic: &str, passphrase: &str) -> WalletResult<EthereumAddress> {
+        let path = DerivationPath::default().child(0)?;
+        let wallet = Self::key_at(mnemonic, passphrase, &path)?;
+        Ok(EthereumAddress::from(wallet.address()))
+    }
+
+    /// BIP39 seed for the stored mnemonic and `passphrase`, or the imported seed
+    fn seed_with(&self, passphrase: &str) -> WalletResult<zeroize::Zeroizing<Vec<u8>>> {
+        match &self.seed {
+            Some(seed) => hex::decode(seed.expose()).map(zeroize::Zeroizing::new).map_err(|e| {
+                CryptographicError::DataCorruption {
+                    details: format!("stored seed: {}", e),
+                }
+                .into()
+            }),
+            None => {
+                let mut seed = Self::parse_mnemonic(self.mnemonic.expose())?.to_seed(passphrase);
+                let bytes = zeroize::Zeroizing::new(seed.to_vec());
+                seed.zeroize();
+                Ok(bytes)
+            }
+        }
     }
 
     /// Get wallet address
-    pub fn address(&self) -> &str {
+    pub fn address(&self) -> &EthereumAddress {
         &self.address
     }
 
     /// Get mnemonic phrase (empty for private key imports)
     pub fn mnemonic(&self) -> &str {
-        &self.mnemonic
+        self.mnemonic.expose()
     }
 
     /// Get network
-    pub fn network(&self) -> &str {
+    pub fn network(&self) -> &Network {
         &self.network
     }
 
     /// Get derivation path
-    pub fn derivation_path(&self) -> &str {
+    pub fn derivation_path(&self) -> &DerivationPath {
         &self.derivation_path
     }
 
@@ -196,60 +523,221 @@ impl Wallet {
         !self.mnemonic.is_empty()
     }
 
-    /// Get private key (for internal use only)
-    pub(crate) fn private_key_bytes(&self) -> Option<&[u8]> {
-        self.master_private_key.as_deref()
+    /// Check if wallet can derive HD addresses, from a mnemonic or an imported seed
+    pub fn is_hd(&self) -> bool {
+        self.has_mnemonic() || self.seed.is_some()
     }
 
-    /// Derive address at specific index
-    pub fn derive_address(&self, index: u32) -> WalletResult<DerivedAddress> {
-        if self.mnemonic.is_empty() {
-            return Err(CryptographicError::KdfFailed {
-                details: "Cannot derive addresses from private key only wallet".to_string(),
-            }
-            .into());
+    /// Short identifier for the wallet's key material.
+    ///
+    /// HD wallets use the BIP32 master key fingerprint; private-key-only
+    /// wallets use the first four bytes of their address.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn fingerprint(&self) -> WalletResult<String> {
+        if !self.is_hd() {
+            return Ok(hex::encode(&self.address.as_bytes()[..4]));
         }
 
-        let derivation_path = format!("{}/{}", self.derivation_path, index);
+        Ok(hex::encode(self.master_key()?.fingerprint().0))
+    }
 
-        // Create wallet from mnemonic with specific derivation path
-        let wallet = MnemonicBuilder::<English>::default()
-            .phrase(self.mnemonic.as_str())
-            .derivation_path(&derivation_path)
-            .map_err(|_e| CryptographicError::InvalidDerivationPath {
-                path: derivation_path.clone(),
-                expected: "valid BIP44 derivation path".to_string(),
-            })?
-            .build()
-            .map_err(|e| CryptographicError::AddressGenerationFailed {
+    /// BIP39 seed of an HD wallet, including its passphrase
+    pub(crate) fn seed(&self) -> WalletResult<zeroize::Zeroizing<Vec<u8>>> {
+        if !self.is_hd() {
+            return Err(Self::no_hd_seed("use a BIP32 root key").into());
+        }
+
+        self.seed_with(self.seed_passphrase()?)
+    }
+
+    /// BIP32 root key of an HD wallet
+    pub(crate) fn master_key(&self) -> WalletResult<coins_bip32::xkeys::XPriv> {
+        let seed = self.seed()?;
+        let master = coins_bip32::xkeys::XPriv::root_from_seed(&seed, Some(coins_bip32::prelude::Hint::Legacy));
+
+        master.map_err(|e| {
+            CryptographicError::AddressGenerationFailed {
                 details: e.to_string(),
-            })?;
+            }
+            .into()
+        })
+    }
+
+    /// Derive address at specific index
+    #[tracing::instrument(level = "trace", skip_all, fields(index = index))]
+    pub fn derive_address(&self, index: u32) -> WalletResult<DerivedAddress> {
+        let mut derived = self.derive_at(&self.account_path(index)?)?;
+        derived.index = index;
+        Ok(derived)
+    }
+
+    /// Derive the change address at `index` on the internal branch (`.../1/index`)
+    #[tracing::instrument(level = "trace", skip_all, fields(index = index))]
+    pub fn derive_change_address(&self, index: u32) -> WalletResult<DerivedAddress> {
+        self.derive_at(&self.change_path()?.child(index)?)
+    }
+
+    /// Derive the address at an arbitrary path of any depth
+    #[tracing::instrument(level = "trace", skip_all, fields(path = %path))]
+    pub fn derive_at(&self, path: &DerivationPath) -> WalletResult<DerivedAddress> {
+        if !self.is_hd() {
+            return Err(Self::no_hd_seed(&format!("derive {}", path)).into());
+        }
 
-        let address = format!("{:?}", wallet.address());
+        let wallet = self.signing_key_at(path)?;
+        let address = EthereumAddress::from(wallet.address());
 
         Ok(DerivedAddress {
             address,
-            index,
-            derivation_path,
+            index: path.last().map(|child| child.index()).unwrap_or(0),
+            derivation_path: path.clone(),
         })
     }
 
-    /// Validate wallet consistency
-    pub fn validate(&self) -> WalletResult<()> {
-        // Validate address format
-        crate::utils::validate_ethereum_address(&self.address)?;
+    /// Derive the addresses at many paths, in order
+    ///
+    /// The seed and the paths' common ancestor are derived once, then the
+    /// remaining levels are spread over worker threads, so large batches cost
+    /// a few child derivations per address instead of a full seed stretch.
+    /// `low_resource` keeps the work on one thread.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = paths.len()))]
+    pub fn derive_many(&self, paths: &[DerivationPath], low_resource: bool) -> WalletResult<Vec<DerivedAddress>> {
+        if !self.is_hd() {
+            return Err(Self::no_hd_seed("derive addresses").into());
+        }
+        let first = match paths.first() {
+            Some(first) => first.components(),
+            None => return Ok(Vec::new()),
+        };
+
+        let shared = paths.iter().fold(first.len(), |shared, path| {
+            first.iter().zip(path.components()).take(shared).take_while(|(a, b)| a == b).count()
+        });
+        let ancestor = DerivationPath::root();
+        let ancestor = first[..shared].iter().fold(ancestor, |path, child| path.join(*child));
+        let ancestor_key = self.master_key()?.derive_path(ancestor.to_bip32()).map_err(|_| {
+            CryptographicError::InvalidDerivationPath {
+                path: ancestor.to_string(),
+                expected: "valid BIP44 derivation path".to_string(),
+            }
+        })?;
+
+        let derive = |path: &DerivationPath| -> WalletResult<DerivedAddress> {
+            let key = path.components()[shared..]
+                .iter()
+                .try_fold(ancestor_key.clone(), |key, child| key.derive_child(child.to_bip32()))
+                .map_err(|_| CryptographicError::InvalidDerivationPath {
+                    path: path.to_string(),
+                    expected: "valid BIP44 derivation path".to_string(),
+                })?;
+            let signing_key: &ethers::core::k256::ecdsa::SigningKey = key.as_ref();
+            Ok(DerivedAddress {
+                address: EthereumAddress::from(LocalWallet::from(signing_key.clone()).address()),
+                index: path.last().map(|child| child.index()).unwrap_or(0),
+                derivation_path: path.clone(),
+            })
+        };
+
+        let threads = config::resources::parallelism(usize::MAX, low_resource).min(paths.len() / MIN_PATHS_PER_THREAD).max(1);
+        let chunk = (paths.len() + threads - 1) / threads;
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = paths
+                .chunks(chunk)
+                .map(|chunk| {
+                    let derive = &derive;
+                    scope.spawn(move || chunk.iter().map(derive).collect::<WalletResult<Vec<_>>>())
+                })
+                .collect();
+
+            let mut derived = Vec::with_capacity(paths.len());
+            for worker in workers {
+                derived.extend(worker.join().expect("derivation worker panicked")?);
+            }
+            Ok(derived)
+        })
+    }
+
+    /// Internal (change) branch next to the external branch of the base path
+    pub fn change_path(&self) -> WalletResult<DerivationPath> {
+        self.path_preset.change_base(&self.derivation_path)
+    }
+
+    /// Private key of the account at `index` (for exports only)
+    pub(crate) fn derive_private_key(&self, index: u32) -> WalletResult<zeroize::Zeroizing<[u8; 32]>> {
+        let wallet = self.signing_key(index)?;
+
+        let mut key = zeroize::Zeroizing::new([0u8; 32]);
+        key.copy_from_slice(&wallet.signer().to_bytes());
+        Ok(key)
+    }
+
+    /// BIP44 account node (`m/44'/60'/N'`) that the receive addresses descend from
+    pub fn account_node_path(&self) -> WalletResult<DerivationPath> {
+        match self.path_preset {
+            PathPreset::Metamask | PathPreset::Mew => Ok(self.derivation_path.parent().unwrap_or_else(DerivationPath::root)),
+            PathPreset::LedgerLegacy => Ok(self.derivation_path.clone()),
+            // Every Ledger Live address is its own account; this is account 0
+            PathPreset::LedgerLive => self.derivation_path.hardened_child(0),
+        }
+    }
+
+    /// Extended public key of the account node
+    pub fn account_xpub(&self) -> WalletResult<String> {
+        use coins_bip32::enc::{MainnetEncoder, XKeyEncoder};
+
+        let account_path = self.account_node_path()?;
+        let key_error = |e: coins_bip32::Bip32Error| CryptographicError::AddressGenerationFailed {
+            details: e.to_string(),
+        };
 
-        // Validate network
-        if !config::is_supported_network(&self.network) {
-            return Err(CryptographicError::KdfFailed {
-                details: format!("Unsupported network: {}", self.network),
+        let account = self.master_key()?.derive_path(account_path.to_bip32()).map_err(key_error)?;
+        MainnetEncoder::xpub_to_base58(&account.verify_key()).map_err(|e| key_error(e).into())
+    }
+
+    /// Accounts stored in this wallet, sorted by index
+    pub fn accounts(&self) -> &[WalletAccount] {
+        &self.accounts
+    }
+
+    /// Derive the account at `index` and store it in the wallet
+    pub fn add_account(&mut self, index: u32, label: Option<String>) -> WalletResult<&WalletAccount> {
+        if self.accounts.iter().any(|account| account.index == index) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "index".to_string(),
+                value: index.to_string(),
+                expected: "an index not already stored in this wallet".to_string(),
             }
             .into());
         }
 
-        // Validate derivation path
-        crate::utils::validate_derivation_path(&self.derivation_path)?;
+        let derived = self.derive_address(index)?;
+        let position = self.accounts.partition_point(|account| account.index < index);
+        self.accounts.insert(position, WalletAccount {
+            index,
+            address: derived.address,
+            derivation_path: derived.derivation_path,
+            label,
+        });
+
+        Ok(&self.accounts[position])
+    }
+
+    /// Remove the stored account at `index`; returns false if there was none
+    pub fn remove_account(&mut self, index: u32) -> bool {
+        let before = self.accounts.len();
+        self.accounts.retain(|account| account.index != index);
+        self.accounts.len() != before
+    }
 
+    /// Validate wallet consistency
+    pub fn validate(&self) -> WalletResult<()> {
+        // Address and network were validated when parsed; a stored key must still match the address
+        if self.private_key.is_some() && EthereumAddress::from(self.signing_key(0)?.address()) != self.address {
+            return Err(CryptographicError::DataCorruption {
+                details: "stored private key does not match the wallet address".to_string(),
+            }
+            .into());
+        }
         Ok(())
     }
 }
@@ -258,16 +746,16 @@ impl Wallet {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DerivedAddress {
     /// Ethereum address
-    address: String,
+    address: EthereumAddress,
     /// Derivation index
     index: u32,
     /// Full derivation path
-    derivation_path: String,
+    derivation_path: DerivationPath,
 }
 
 impl DerivedAddress {
     /// Get address
-    pub fn address(&self) -> &str {
+    pub fn address(&self) -> &EthereumAddress {
         &self.address
     }
 
@@ -277,11 +765,46 @@ impl DerivedAddress {
     }
 
     /// Get derivation path
-    pub fn derivation_path(&self) -> &str {
+    pub fn derivation_path(&self) -> &DerivationPath {
         &self.derivation_path
     }
 }
 
+/// Derived account stored inside a wallet keystore
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletAccount {
+    /// Derivation index
+    index: u32,
+    /// Ethereum address
+    address: EthereumAddress,
+    /// Full derivation path
+    derivation_path: DerivationPath,
+    /// Optional account label
+    label: Option<String>,
+}
+
+impl WalletAccount {
+    /// Get derivation index
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Get address
+    pub fn address(&self) -> &EthereumAddress {
+        &self.address
+    }
+
+    /// Get derivation path
+    pub fn derivation_path(&self) -> &DerivationPath {
+        &self.derivation_path
+    }
+
+    /// Get account label
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,7 +814,7 @@ mod tests {
 
     #[test]
     fn test_wallet_from_mnemonic() {
-        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, "mainnet", None).unwrap();
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
 
         assert_eq!(wallet.address(), EXPECTED_ADDRESS);
         assert_eq!(wallet.mnemonic(), TEST_MNEMONIC);
@@ -301,39 +824,267 @@ mod tests {
 
     #[test]
     fn test_wallet_generation() {
-        let wallet = Wallet::generate(12, "mainnet", Some("test".to_string())).unwrap();
+        let wallet = Wallet::generate(12, &Network::Mainnet, Some("test".to_string())).unwrap();
 
-        assert!(wallet.address().starts_with("0x"));
-        assert_eq!(wallet.address().len(), 42);
+        assert!(wallet.address().to_string().starts_with("0x"));
+        assert_eq!(wallet.address().to_string().len(), 42);
         assert_eq!(wallet.mnemonic().split_whitespace().count(), 12);
         assert_eq!(wallet.alias(), Some("test"));
     }
 
     #[test]
     fn test_address_derivation() {
-        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, "mainnet", None).unwrap();
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
 
         let derived = wallet.derive_address(1).unwrap();
-        assert!(derived.address().starts_with("0x"));
+        assert!(derived.address().to_string().starts_with("0x"));
         assert_eq!(derived.index(), 1);
-        assert!(derived.derivation_path().ends_with("/1"));
+        assert_eq!(derived.derivation_path().to_string(), "m/44'/60'/0'/0/1");
+    }
+
+    #[test]
+    fn test_path_preset() {
+        let mut wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        wallet.add_account(1, None).unwrap();
+        wallet.set_path_preset(PathPreset::LedgerLive).unwrap();
+
+        assert!(wallet.accounts().is_empty());
+        assert_eq!(wallet.derivation_path().to_string(), "m/44'/60'");
+        let derived = wallet.derive_address(3).unwrap();
+        assert_eq!(derived.index(), 3);
+        assert_eq!(derived.derivation_path().to_string(), "m/44'/60'/3'/0/0");
+
+        // Index 0 of every layout is the same key
+        assert_eq!(wallet.address(), EXPECTED_ADDRESS);
+        assert!(wallet.derive_change_address(0).is_err());
+
+        wallet.set_path_preset(PathPreset::LedgerLegacy).unwrap();
+        assert_ne!(wallet.address(), EXPECTED_ADDRESS);
+        assert_eq!(wallet.derive_address(0).unwrap().address(), wallet.address());
+
+        let json = serde_json::to_string(&wallet).unwrap();
+        let reloaded: Wallet = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.path_preset(), PathPreset::LedgerLegacy);
+        assert_eq!(reloaded.derive_address(2).unwrap().address(), wallet.derive_address(2).unwrap().address());
+    }
+
+    #[test]
+    fn test_account_selection() {
+        let mut wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        assert_eq!(wallet.account(), 0);
+
+        wallet.set_account(2).unwrap();
+        assert_eq!(wallet.account(), 2);
+        assert_eq!(wallet.derivation_path().to_string(), "m/44'/60'/2'/0");
+        let expected = wallet.derive_at(&"m/44'/60'/2'/0/0".parse().unwrap()).unwrap();
+        assert_eq!(wallet.address(), expected.address());
+        assert_eq!(wallet.derive_change_address(1).unwrap().derivation_path().to_string(), "m/44'/60'/2'/1/1");
+
+        // Switching layout keeps the account where the layout has one
+        wallet.set_path_preset(PathPreset::LedgerLegacy).unwrap();
+        assert_eq!(wallet.derivation_path().to_string(), "m/44'/60'/2'");
+        assert!(wallet.set_path_preset(PathPreset::LedgerLive).is_err());
+
+        wallet.set_account(0).unwrap();
+        assert_eq!(wallet.derive_address(0).unwrap().address(), wallet.address());
+    }
+
+    #[test]
+    fn test_coin_type_selection() {
+        let mut wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        assert_eq!(wallet.coin_type(), 60);
+
+        wallet.set_account(1).unwrap();
+        wallet.set_coin_type(61).unwrap();
+        assert_eq!(wallet.coin_type(), 61);
+        assert_eq!(wallet.account(), 1);
+        assert_eq!(wallet.derivation_path().to_string(), "m/44'/61'/1'/0");
+        let expected = wallet.derive_at(&"m/44'/61'/1'/0/0".parse().unwrap()).unwrap();
+        assert_eq!(wallet.address(), expected.address());
+
+        // The coin type survives a layout switch and a reload
+        wallet.set_path_preset(PathPreset::LedgerLegacy).unwrap();
+        assert_eq!(wallet.derivation_path().to_string(), "m/44'/61'/1'");
+        let reloaded: Wallet = serde_json::from_str(&serde_json::to_string(&wallet).unwrap()).unwrap();
+        assert_eq!(reloaded.coin_type(), 61);
+
+        let key_only = Wallet::from_private_key(&"11".repeat(32), &Network::Mainnet, None).unwrap();
+        assert!(key_only.clone().set_coin_type(61).is_err());
+    }
+
+    #[test]
+    fn test_account_node_path() {
+        let mut wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        assert_eq!(wallet.account_node_path().unwrap().to_string(), "m/44'/60'/0'");
+
+        wallet.set_account(3).unwrap();
+        assert_eq!(wallet.account_node_path().unwrap().to_string(), "m/44'/60'/3'");
+
+        wallet.set_path_preset(PathPreset::LedgerLegacy).unwrap();
+        assert_eq!(wallet.account_node_path().unwrap().to_string(), "m/44'/60'/3'");
+        assert!(wallet.account_xpub().unwrap().starts_with("xpub"));
+    }
+
+    #[test]
+    fn test_seed_and_entropy_import() {
+        let from_mnemonic = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+
+        let entropy = Wallet::from_entropy(&[0u8; 16], bip39::Language::English, &Network::Mainnet, None).unwrap();
+        assert_eq!(entropy.mnemonic(), TEST_MNEMONIC);
+        assert!(Wallet::from_entropy(&[0u8; 15], bip39::Language::English, &Network::Mainnet, None).is_err());
+
+        let seed = from_mnemonic.seed().unwrap();
+        let mut wallet = Wallet::from_seed(&seed, &Network::Mainnet, None).unwrap();
+        assert!(wallet.is_hd() && !wallet.has_mnemonic());
+        assert_eq!(wallet.address(), from_mnemonic.address());
+        assert_eq!(wallet.fingerprint().unwrap(), from_mnemonic.fingerprint().unwrap());
+        assert_eq!(wallet.derive_address(3).unwrap().address(), from_mnemonic.derive_address(3).unwrap().address());
+        assert!(wallet.protect_with_passphrase("extra").is_err());
+
+        wallet.set_account(2).unwrap();
+        assert_eq!(wallet.derivation_path().to_string(), "m/44'/60'/2'/0");
+
+        let restored: Wallet = serde_json::from_str(&serde_json::to_string(&wallet).unwrap()).unwrap();
+        assert_eq!(restored.derive_address(1).unwrap().address(), wallet.derive_address(1).unwrap().address());
+        assert!(Wallet::from_seed(&[0u8; 8], &Network::Mainnet, None).is_err());
+    }
+
+    #[test]
+    fn test_private_key_import() {
+        let key = "11".repeat(32);
+        let wallet = Wallet::from_private_key(&key, &Network::Mainnet, None).unwrap();
+        assert!(!wallet.is_hd());
+
+        // The key survives a save and reload, so the wallet can still sign and export
+        let json = serde_json::to_string(&wallet).unwrap();
+        let restored: Wallet = serde_json::from_str(&json).unwrap();
+        restored.validate().unwrap();
+        assert_eq!(EthereumAddress::from(restored.signing_key(0).unwrap().address()), *wallet.address());
+        assert_eq!(hex::encode(restored.derive_private_key(0).unwrap().as_slice()), key);
+
+        // There is only the imported account
+        assert_eq!(restored.signing_key(1).unwrap_err().code(), "CRYPTO_012");
+        assert_eq!(restored.derive_address(0).unwrap_err().code(), "CRYPTO_012");
+
+        // Keystores written before the key was stored still open, but cannot sign
+        let mut legacy: serde_json::Value = serde_json::from_str(&json).unwrap();
+        legacy.as_object_mut().unwrap().remove("private_key");
+        let legacy: Wallet = serde_json::from_value(legacy).unwrap();
+        legacy.validate().unwrap();
+        assert_eq!(legacy.signing_key(0).unwrap_err().code(), "CRYPTO_012");
+
+        // A stored key must belong to the wallet's address
+        let other = json.replace(&key, &"22".repeat(32));
+        let other: Wallet = serde_json::from_str(&other).unwrap();
+        assert_eq!(other.validate().unwrap_err().code(), "CRYPTO_005");
+    }
+
+    #[test]
+    fn test_derive_many() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+
+        // Enough paths for several threads, plus ones that diverge above the base
+        let mut paths: Vec<DerivationPath> = (0..100).map(|index| wallet.account_path(index).unwrap()).collect();
+        paths.push("m/44'/60'/1'/0/0".parse().unwrap());
+        paths.push(wallet.change_path().unwrap().child(2).unwrap());
+
+        let derived = wallet.derive_many(&paths, false).unwrap();
+        assert_eq!(derived.len(), paths.len());
+        for (path, derived) in paths.iter().zip(&derived) {
+            assert_eq!(derived.address(), wallet.derive_at(path).unwrap().address(), "{}", path);
+            assert_eq!(derived.derivation_path(), path);
+        }
+        assert_eq!(derived[0].address(), wallet.address());
+        assert!(wallet.derive_many(&[], false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_change_and_custom_paths() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+
+        let change = wallet.derive_change_address(0).unwrap();
+        assert_eq!(change.derivation_path().to_string(), "m/44'/60'/0'/1/0");
+        assert_ne!(change.address(), wallet.address());
+
+        let path: DerivationPath = "m/44'/60'/0'/0/0".parse().unwrap();
+        assert_eq!(wallet.derive_at(&path).unwrap().address(), wallet.address());
+
+        let deep: DerivationPath = "m/44'/60'/1'/0/3/7'".parse().unwrap();
+        let derived = wallet.derive_at(&deep).unwrap();
+        assert_eq!(derived.index(), 7);
+        assert_eq!(derived.derivation_path(), &deep);
+        assert_ne!(derived.address(), wallet.derive_address(7).unwrap().address());
     }
 
     #[test]
     fn test_wallet_validation() {
-        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, "mainnet", None).unwrap();
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
         assert!(wallet.validate().is_ok());
     }
 
     #[test]
     fn test_invalid_mnemonic() {
-        let result = Wallet::from_mnemonic("invalid mnemonic", "mainnet", None);
+        let result = Wallet::from_mnemonic("invalid mnemonic", &Network::Mainnet, None);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_invalid_word_count() {
-        let result = Wallet::generate(16, "mainnet", None);
+        let result = Wallet::generate(16, &Network::Mainnet, None);
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_wallet_accounts() {
+        let mut wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+
+        wallet.add_account(2, Some("savings".to_string())).unwrap();
+        wallet.add_account(1, None).unwrap();
+        assert!(wallet.add_account(1, None).is_err());
+
+        let indices: Vec<u32> = wallet.accounts().iter().map(|a| a.index()).collect();
+        assert_eq!(indices, vec![1, 2]);
+        assert_eq!(wallet.accounts()[1].label(), Some("savings"));
+        assert_eq!(wallet.accounts()[0].address(), wallet.derive_address(1).unwrap().address());
+
+        assert!(wallet.remove_account(1));
+        assert!(!wallet.remove_account(1));
+        assert_eq!(wallet.accounts().len(), 1);
+    }
+
+    #[test]
+    fn test_bip39_passphrase() {
+        let mut wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        wallet.protect_with_passphrase("TREZOR").unwrap();
+
+        assert!(wallet.uses_passphrase());
+        assert_ne!(wallet.address(), EXPECTED_ADDRESS);
+        let derived = wallet.derive_address(0).unwrap();
+        assert_eq!(derived.address(), wallet.address());
+
+        // The passphrase is not serialized; a reloaded wallet must be unlocked again
+        let json = serde_json::to_string(&wallet).unwrap();
+        assert!(!json.contains("TREZOR"));
+        let mut reloaded: Wallet = serde_json::from_str(&json).unwrap();
+        assert!(reloaded.derive_address(0).is_err());
+        assert!(reloaded.unlock_passphrase("wrong").is_err());
+        reloaded.unlock_passphrase("TREZOR").unwrap();
+        assert_eq!(reloaded.derive_address(1).unwrap().address(), wallet.derive_address(1).unwrap().address());
+    }
+
+    #[test]
+    fn test_secrets_redacted_and_wiped() {
+        let mut wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        wallet.protect_with_passphrase("TREZOR").unwrap();
+        let debug = format!("{:?}", wallet);
+        assert!(!debug.contains("abandon") && !debug.contains("TREZOR"));
+
+        // The mnemonic still serializes as a plain string for keystore encryption
+        let json = serde_json::to_string(&wallet).unwrap();
+        assert!(json.contains(&format!(r#""mnemonic":"{}""#, TEST_MNEMONIC)));
+
+        wallet.zeroize();
+        assert!(wallet.mnemonic().is_empty() && wallet.master_private_key.is_none());
+        assert!(wallet.seed_passphrase().is_err());
+    }
+}
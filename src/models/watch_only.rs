@@ -0,0 +1,304 @@
+//! # Watch-Only Wallet Model
+//!
+//! Public-only wallets built from an account xpub or a single address. They
+//! sit in the wallet directory next to the keystores as plain JSON, since
+//! there is nothing secret to encrypt. A SHA-256 checksum over the wallet
+//! body catches corruption and hand edits. It does not stop deliberate
+//! tampering, because anyone can recompute it. Watch-only wallets derive
+//! receive addresses and report balances, but hold no key that could sign.
+
+use crate::errors::{CryptographicError, UserInputError, ValidationError, WalletResult};
+use crate::models::{DerivationPath, Network, PathPreset};
+use chrono::{DateTime, Utc};
+use coins_bip32::enc::{MainnetEncoder, XKeyEncoder};
+use coins_bip32::xkeys::{Parent, XPub};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// File format version
+pub const WATCH_ONLY_VERSION: u32 = 1;
+
+/// Value of the `type` field marking a watch-only file
+pub const WATCH_ONLY_TYPE: &str = "watch-only";
+
+/// What a watch-only wallet was created from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "kebab-case")]
+pub enum WatchSource {
+    /// Account-level extended public key
+    Xpub {
+        /// Base58 `xpub...`
+        xpub: String,
+        /// Path of the account node, when known (only used for display)
+        origin: Option<DerivationPath>,
+        /// Branch below the account node holding receive addresses (`None`: addresses are direct children)
+        receive_branch: Option<u32>,
+    },
+    /// One address and nothing else
+    Address,
+}
+
+/// Address-only wallet that cannot sign
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchOnlyWallet {
+    /// Primary address (EIP-55)
+    address: String,
+    /// Target network
+    network: Network,
+    /// Optional alias
+    alias: Option<String>,
+    /// Creation timestamp
+    created_at: DateTime<Utc>,
+    /// Origin of the addresses
+    #[serde(flatten)]
+    source: WatchSource,
+}
+
+/// On-disk envelope
+#[derive(Serialize, Deserialize)]
+struct WatchOnlyFile {
+    version: u32,
+    #[serde(rename = "type")]
+    kind: String,
+    wallet: WatchOnlyWallet,
+    /// SHA-256 of the serialized `wallet`, hex
+    checksum: String,
+}
+
+impl WatchOnlyWallet {
+    /// Watch the receive addresses of an account xpub laid out like `layout`
+    pub fn from_xpub(xpub: &str, layout: PathPreset, origin: Option<DerivationPath>, network: &Network) -> WalletResult<Self> {
+        let receive_branch = match layout {
+            PathPreset::Metamask | PathPreset::Mew => Some(0),
+            PathPreset::LedgerLegacy => None,
+            PathPreset::LedgerLive => {
+                return Err(CryptographicError::InvalidDerivationPath {
+                    path: layout.base_path().to_string(),
+                    expected: "a layout with several addresses per account; watch Ledger Live accounts by address".to_string(),
+                }
+                .into())
+            }
+        };
+
+        let mut wallet = Self {
+            address: String::new(),
+            network: network.clone(),
+            alias: None,
+            created_at: Utc::now(),
+            source: WatchSource::Xpub { xpub: xpub.trim().to_string(), origin, receive_branch },
+        };
+        wallet.address = wallet.derive_address(0)?;
+        Ok(wallet)
+    }
+
+    /// Watch a single address
+    pub fn from_address(address: &str, network: &Network) -> WalletResult<Self> {
+        Ok(Self {
+            address: Self::checksummed(address)?,
+            network: network.clone(),
+            alias: None,
+            created_at: Utc::now(),
+            source: WatchSource::Address,
+        })
+    }
+
+    /// Whether a keystore-directory JSON document is a watch-only wallet
+    pub fn is_watch_only(json: &str) -> bool {
+        serde_json::from_str::<serde_json::Value>(json)
+            .ok()
+            .and_then(|value| value.get("type").and_then(|kind| kind.as_str()).map(|kind| kind == WATCH_ONLY_TYPE))
+            .unwrap_or(false)
+    }
+
+    /// Primary address (index 0)
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Target network
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// Optional alias
+    pub fn alias(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+
+    /// Set or clear the alias
+    pub fn set_alias(&mut self, alias: Option<String>) {
+        self.alias = alias;
+    }
+
+    /// Creation timestamp
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// Origin of the addresses
+    pub fn source(&self) -> &WatchSource {
+        &self.source
+    }
+
+    /// Receive address at `index`; address-only wallets have index 0 alone
+    pub fn derive_address(&self, index: u32) -> WalletResult<String> {
+        let (xpub, receive_branch) = match &self.source {
+            WatchSource::Address if index == 0 => return Ok(self.address.clone()),
+            WatchSource::Address => {
+                return Err(CryptographicError::IndexOutOfRange { index, max_index: 0 }.into());
+            }
+            WatchSource::Xpub { xpub, receive_branch, .. } => (xpub, receive_branch),
+        };
+
+        let key_error = |e: coins_bip32::Bip32Error| CryptographicError::AddressGenerationFailed {
+            details: e.to_string(),
+        };
+        let account: XPub = MainnetEncoder::xpub_from_base58(xpub).map_err(key_error)?;
+        let branch = match receive_branch {
+            Some(branch) => account.derive_child(*branch).map_err(key_error)?,
+            None => account,
+        };
+        let child = branch.derive_child(index).map_err(key_error)?;
+
+        let address = ethers::utils::public_key_to_address(child.as_ref());
+        Ok(ethers::utils::to_checksum(&address, None))
+    }
+
+    /// Full path of the receive address at `index`, when the account origin is known
+    pub fn address_path(&self, index: u32) -> WalletResult<Option<DerivationPath>> {
+        match &self.source {
+            WatchSource::Xpub { origin: Some(origin), receive_branch, .. } => {
+                let branch = match receive_branch {
+                    Some(branch) => origin.child(*branch)?,
+                    None => origin.clone(),
+                };
+                branch.child(index).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Serialize with the integrity checksum
+    pub fn to_json(&self) -> WalletResult<String> {
+        let file = WatchOnlyFile {
+            version: WATCH_ONLY_VERSION,
+            kind: WATCH_ONLY_TYPE.to_string(),
+            wallet: self.clone(),
+            checksum: Self::checksum(self)?,
+        };
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    /// Parse and check a watch-only file
+    pub fn from_json(json: &str) -> WalletResult<Self> {
+        let invalid = |details: String| ValidationError::IntegrityCheckFailed {
+            data_type: "watch-only wallet".to_string(),
+            details,
+        };
+
+        let file: WatchOnlyFile = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        if file.kind != WATCH_ONLY_TYPE {
+            return Err(invalid(format!("Not a watch-only wallet (type {})", file.kind)).into());
+        }
+        if file.version != WATCH_ONLY_VERSION {
+            return Err(ValidationError::VersionIncompatible {
+                current: file.version.to_string(),
+                required: WATCH_ONLY_VERSION.to_string(),
+            }
+            .into());
+        }
+        if Self::checksum(&file.wallet)? != file.checksum {
+            return Err(invalid("Checksum mismatch; the file was modified or corrupted".to_string()).into());
+        }
+
+        let wallet = file.wallet;
+        if wallet.derive_address(0)? != wallet.address {
+            return Err(invalid("Primary address does not match the watched key".to_string()).into());
+        }
+        Ok(wallet)
+    }
+
+    /// Write to `path`, refusing to overwrite
+    pub fn save(&self, path: &Path) -> WalletResult<()> {
+        if path.exists() {
+            return Err(crate::errors::FileSystemError::FileExists {
+                path: path.display().to_string(),
+                suggestion: "Choose a different name".to_string(),
+            }
+            .into());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        crate::utils::write_atomic(path, self.to_json()?.as_bytes(), crate::config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+
+    /// Read and check a watch-only file
+    pub fn load(path: &Path) -> WalletResult<Self> {
+        if !path.exists() {
+            return Err(crate::errors::FileSystemError::FileNotFound {
+                path: path.display().to_string(),
+                directory: path.parent().map(|p| p.display().to_string()).unwrap_or_else(|| ".".to_string()),
+            }
+            .into());
+        }
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    fn checksum(wallet: &Self) -> WalletResult<String> {
+        Ok(hex::encode(Sha256::digest(serde_json::to_vec(wallet)?)))
+    }
+
+    fn checksummed(address: &str) -> WalletResult<String> {
+        crate::utils::validate_ethereum_address(address)?;
+        let parsed: ethers::types::Address = address.parse().map_err(|_| UserInputError::InvalidParameters {
+            parameter: "address".to_string(),
+            value: address.to_string(),
+            expected: "a 0x-prefixed 20-byte hex address".to_string(),
+        })?;
+        Ok(ethers::utils::to_checksum(&parsed, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Wallet;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_xpub_matches_wallet() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let xpub = wallet.account_xpub().unwrap();
+        let watch = WatchOnlyWallet::from_xpub(&xpub, PathPreset::Metamask, Some(wallet.account_node_path().unwrap()), &Network::Mainnet).unwrap();
+
+        assert_eq!(watch.address(), "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+        assert_eq!(*wallet.derive_address(3).unwrap().address(), watch.derive_address(3).unwrap());
+        assert_eq!(watch.address_path(3).unwrap().unwrap().to_string(), "m/44'/60'/0'/0/3");
+
+        let mut legacy = wallet.clone();
+        legacy.set_path_preset(PathPreset::LedgerLegacy).unwrap();
+        let watch = WatchOnlyWallet::from_xpub(&legacy.account_xpub().unwrap(), PathPreset::LedgerLegacy, None, &Network::Mainnet).unwrap();
+        assert_eq!(*legacy.derive_address(2).unwrap().address(), watch.derive_address(2).unwrap());
+
+        assert!(WatchOnlyWallet::from_xpub(&xpub, PathPreset::LedgerLive, None, &Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_file_round_trip_and_integrity() {
+        let mut watch = WatchOnlyWallet::from_address("0x9858effd232b4033e47d90003d41ec34ecaeda94", &Network::Mainnet).unwrap();
+        watch.set_alias(Some("cold".to_string()));
+        assert_eq!(watch.address(), "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+        assert!(watch.derive_address(1).is_err());
+
+        let json = watch.to_json().unwrap();
+        assert!(WatchOnlyWallet::is_watch_only(&json));
+        assert_eq!(WatchOnlyWallet::from_json(&json).unwrap(), watch);
+
+        let edited = json.replace("cold", "hot");
+        assert!(WatchOnlyWallet::from_json(&edited).is_err());
+        assert!(!WatchOnlyWallet::is_watch_only("{\"version\": \"3\"}"));
+    }
+}
@@ -0,0 +1,23 @@
+//! # Prelude
+//!
+//! The supported public API in one import:
+//!
+//! ```rust
+//! use web3wallet_cli::prelude::*;
+//! ```
+//!
+//! Everything re-exported here follows semantic versioning: a name is only
+//! removed or changed incompatibly in a major release. The module paths
+//! behind it (`services::...`, `models::...`) also serve the `wallet`
+//! binary and may be reorganised in any release, so library consumers
+//! should import through the prelude.
+
+pub use crate::errors::{
+    AuthenticationError, CryptographicError, FileSystemError, NetworkError, UserInputError,
+    ValidationError, WalletError, WalletResult,
+};
+pub use crate::models::{Address, ChildNumber, DerivationPath, Keystore, PathPreset, Wallet};
+pub use crate::services::signer::{KeySigner, LocalKeySigner};
+#[cfg(feature = "native")]
+pub use crate::services::WalletManager;
+pub use crate::WalletConfig;
@@ -0,0 +1,128 @@
+//! # OS Keychain
+//!
+//! Optional storage of keystore passwords in the platform credential store
+//! (macOS Keychain, Windows Credential Manager, Secret Service on Linux).
+//! Entries are keyed by the keystore's canonical path. Support is compiled
+//! in with the `keyring` feature; without it every call reports that the
+//! credential store is unavailable.
+
+use crate::errors::WalletResult;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Service name under which passwords are stored
+pub const SERVICE_NAME: &str = "web3wallet-cli";
+
+/// Credential store account name for a keystore
+pub fn account_for(keystore: &Path) -> String {
+    keystore
+        .canonicalize()
+        .unwrap_or_else(|_| keystore.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Check if keychain support was compiled in
+pub fn is_supported() -> bool {
+    cfg!(feature = "keyring")
+}
+
+/// Store the password for a keystore
+pub fn store_password(keystore: &Path, password: &str) -> WalletResult<()> {
+    imp::store(&account_for(keystore), password)
+}
+
+/// Fetch the stored password for a keystore, if any
+pub fn get_password(keystore: &Path) -> WalletResult<Option<Zeroizing<String>>> {
+    imp::get(&account_for(keystore))
+}
+
+/// Remove the stored password for a keystore; returns false if none was stored
+pub fn delete_password(keystore: &Path) -> WalletResult<bool> {
+    imp::delete(&account_for(keystore))
+}
+
+#[cfg(feature = "keyring")]
+mod imp {
+    use super::*;
+    use crate::errors::{AuthenticationError, WalletError};
+
+    fn store_error(e: keyring::Error) -> WalletError {
+        AuthenticationError::CredentialStore {
+            details: e.to_string(),
+        }
+        .into()
+    }
+
+    fn entry(account: &str) -> WalletResult<keyring::Entry> {
+        keyring::Entry::new(SERVICE_NAME, account).map_err(store_error)
+    }
+
+    pub(super) fn store(account: &str, password: &str) -> WalletResult<()> {
+        entry(account)?.set_password(password).map_err(store_error)
+    }
+
+    pub(super) fn get(account: &str) -> WalletResult<Option<Zeroizing<String>>> {
+        match entry(account)?.get_password() {
+            Ok(password) => Ok(Some(Zeroizing::new(password))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(store_error(e)),
+        }
+    }
+
+    pub(super) fn delete(account: &str) -> WalletResult<bool> {
+        match entry(account)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(store_error(e)),
+        }
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+mod imp {
+    use super::*;
+    use crate::errors::{AuthenticationError, WalletError};
+
+    fn unsupported() -> WalletError {
+        AuthenticationError::CredentialStore {
+            details: "Keychain support is not compiled in; rebuild with `--features keyring`".to_string(),
+        }
+        .into()
+    }
+
+    pub(super) fn store(_account: &str, _password: &str) -> WalletResult<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn get(_account: &str) -> WalletResult<Option<Zeroizing<String>>> {
+        Err(unsupported())
+    }
+
+    pub(super) fn delete(_account: &str) -> WalletResult<bool> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_account_is_canonical_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let keystore = temp_dir.path().join("wallet.json");
+        std::fs::write(&keystore, "{}").unwrap();
+
+        let relative = temp_dir.path().join(".").join("wallet.json");
+        assert_eq!(account_for(&keystore), account_for(&relative));
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    #[test]
+    fn test_unsupported_without_feature() {
+        assert!(!is_supported());
+        assert!(get_password(Path::new("wallet.json")).is_err());
+    }
+}
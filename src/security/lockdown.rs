@@ -0,0 +1,79 @@
+//! # Feature Lockdown
+//!
+//! Classes of functionality an administrator can switch off for a whole
+//! install with `disable = [...]` in the config file. Every command declares
+//! the classes it needs and is refused before it runs if any is disabled.
+//! Unknown class names make the config fail to load, so a typo never leaves
+//! a feature silently enabled.
+
+use crate::errors::{AuthenticationError, WalletResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A class of functionality that can be disabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Feature {
+    /// Writing or showing private keys and mnemonics after creation
+    ExportSecret,
+    /// Producing signatures with a keystore
+    Sign,
+    /// Contacting RPC endpoints or registries
+    Network,
+    /// Storing or reading passwords in the OS keychain
+    Keychain,
+    /// Long-running processes holding decrypted keys (`agent`)
+    Serve,
+    /// Sending transactions (reserved; no command uses it yet)
+    Broadcast,
+}
+
+impl Feature {
+    /// Config name of the class
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::ExportSecret => "export-secret",
+            Feature::Sign => "sign",
+            Feature::Network => "network",
+            Feature::Keychain => "keychain",
+            Feature::Serve => "serve",
+            Feature::Broadcast => "broadcast",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// Refuse if any of `required` is in `disabled`
+pub fn ensure_enabled(disabled: &[Feature], required: &[Feature]) -> WalletResult<()> {
+    match required.iter().find(|feature| disabled.contains(feature)) {
+        Some(feature) => Err(AuthenticationError::FeatureDisabled { feature: feature.name().to_string() }.into()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_enabled() {
+        let disabled = [Feature::ExportSecret, Feature::Serve];
+        assert!(ensure_enabled(&disabled, &[]).is_ok());
+        assert!(ensure_enabled(&disabled, &[Feature::Sign, Feature::Network]).is_ok());
+
+        let err = ensure_enabled(&disabled, &[Feature::Network, Feature::ExportSecret]).unwrap_err();
+        assert!(err.to_string().contains("export-secret"));
+    }
+
+    #[test]
+    fn test_disable_config() {
+        let config: crate::WalletConfig = toml::from_str("disable = [\"export-secret\", \"serve\"]\n").unwrap();
+        assert_eq!(config.disable, [Feature::ExportSecret, Feature::Serve]);
+        assert!(toml::from_str::<crate::WalletConfig>("disable = [\"exports\"]\n").is_err());
+    }
+}
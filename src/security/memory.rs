@@ -0,0 +1,243 @@
+//! # Hardened Memory
+//!
+//! Opt-in protection (`security.hardened_memory`) against secrets leaving
+//! the process through the operating system: buffers holding passwords,
+//! derived keys and decrypted wallet data are locked into RAM so they are
+//! never written to swap, core dumps are disabled, and on Linux the
+//! process is marked non-dumpable so other processes of the same user
+//! cannot attach to it or read its memory through `/proc`.
+//!
+//! Locking is best effort. The kernel caps locked memory per process
+//! (`ulimit -l`); when a lock is refused a warning is logged once and the
+//! buffer is used unlocked. Hibernation images are outside a process's
+//! control and need encrypted swap.
+//!
+//! Locks do not nest in the kernel, so small buffers sharing a page are
+//! counted per page, and a page is unlocked only when the last region on
+//! it is dropped.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use tracing::warn;
+
+static HARDENED: AtomicBool = AtomicBool::new(false);
+static LOCK_FAILURE_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Number of live [`LockedRegion`]s on each locked page, keyed by page address
+static LOCKED_PAGES: OnceLock<Mutex<HashMap<usize, usize>>> = OnceLock::new();
+
+fn locked_pages() -> MutexGuard<'static, HashMap<usize, usize>> {
+    LOCKED_PAGES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start address of every page under `addr..addr + len`
+fn pages(addr: usize, len: usize) -> impl Iterator<Item = usize> {
+    let size = imp::page_size();
+    let first = addr / size;
+    let last = (addr + len - 1) / size;
+    (first..=last).map(move |page| page * size)
+}
+
+/// Disable core dumps and start locking secret buffers for the rest of the process
+pub fn harden_process() {
+    if let Err(e) = imp::disable_core_dumps() {
+        warn!("Could not disable core dumps: {}", e);
+    }
+    HARDENED.store(true, Ordering::Relaxed);
+}
+
+/// Check if [`harden_process`] was called
+pub fn is_hardened() -> bool {
+    HARDENED.load(Ordering::Relaxed)
+}
+
+/// Lock the pages under `bytes` into RAM while hardened memory is on
+///
+/// The returned guard unlocks them when dropped, so it must not outlive the
+/// buffer and should be dropped after the buffer is zeroized.
+#[must_use = "the pages are unlocked again when the guard is dropped"]
+pub fn lock(bytes: &[u8]) -> LockedRegion {
+    if !is_hardened() {
+        return LockedRegion::default();
+    }
+    LockedRegion::new(bytes)
+}
+
+/// Pages locked by [`lock`]; unlocked on drop
+#[derive(Debug, Default)]
+pub struct LockedRegion {
+    // Address and length only: the guard must not borrow the buffer it protects
+    addr: usize,
+    len: usize,
+}
+
+impl LockedRegion {
+    fn new(bytes: &[u8]) -> Self {
+        if bytes.is_empty() {
+            return Self::default();
+        }
+        let (addr, len) = (bytes.as_ptr() as usize, bytes.len());
+        let mut locked = locked_pages();
+        match imp::lock(addr, len) {
+            Ok(()) => {
+                for page in pages(addr, len) {
+                    *locked.entry(page).or_insert(0) += 1;
+                }
+                Self { addr, len }
+            }
+            Err(e) => {
+                if !LOCK_FAILURE_REPORTED.swap(true, Ordering::Relaxed) {
+                    warn!("Could not lock secret memory ({}); raise `ulimit -l` to keep secrets out of swap", e);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Check if any pages are locked
+    pub fn is_locked(&self) -> bool {
+        self.len > 0
+    }
+}
+
+impl Drop for LockedRegion {
+    fn drop(&mut self) {
+        if !self.is_locked() {
+            return;
+        }
+        let mut locked = locked_pages();
+        for page in pages(self.addr, self.len) {
+            let count = locked.entry(page).or_insert(1);
+            *count -= 1;
+            if *count == 0 {
+                locked.remove(&page);
+                // Failing to unlock only keeps the page resident
+                let _ = imp::unlock(page, imp::page_size());
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+mod imp {
+    use std::io;
+
+    pub(super) fn lock(addr: usize, len: usize) -> io::Result<()> {
+        // SAFETY: mlock only changes paging of the given range; it does not
+        // read or write it, and the kernel rounds the range to whole pages.
+        check(unsafe { libc::mlock(addr as *const libc::c_void, len) })
+    }
+
+    pub(super) fn unlock(addr: usize, len: usize) -> io::Result<()> {
+        // SAFETY: as for mlock
+        check(unsafe { libc::munlock(addr as *const libc::c_void, len) })
+    }
+
+    pub(super) fn page_size() -> usize {
+        // SAFETY: sysconf only reads a system constant
+        match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            size if size > 0 => size as usize,
+            _ => 4096,
+        }
+    }
+
+    pub(super) fn disable_core_dumps() -> io::Result<()> {
+        let limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        // SAFETY: setrlimit reads the fully initialized struct above
+        check(unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) })?;
+
+        // SAFETY: PR_SET_DUMPABLE takes a plain integer argument
+        #[cfg(target_os = "linux")]
+        check(unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0) })?;
+
+        Ok(())
+    }
+
+    fn check(result: libc::c_int) -> io::Result<()> {
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform")
+    }
+
+    pub(super) fn lock(_addr: usize, _len: usize) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn unlock(_addr: usize, _len: usize) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub(super) fn page_size() -> usize {
+        4096
+    }
+
+    pub(super) fn disable_core_dumps() -> io::Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hardened_memory_config() {
+        let config: crate::WalletConfig = toml::from_str("[security]\nhardened_memory = true\n").unwrap();
+        assert!(config.security.hardened_memory);
+        assert!(!crate::WalletConfig::default().security.hardened_memory);
+    }
+
+    #[test]
+    fn test_lock_is_opt_in() {
+        let secret = vec![7u8; 4096];
+        assert!(!lock(&secret).is_locked());
+
+        // Locking itself may be refused by a zero `ulimit -l`; when it works, it is undone on drop
+        let region = LockedRegion::new(&secret);
+        if region.is_locked() {
+            assert_eq!((region.addr, region.len), (secret.as_ptr() as usize, secret.len()));
+        }
+        drop(region);
+        assert!(!LockedRegion::new(&[]).is_locked());
+    }
+
+    #[test]
+    fn test_regions_sharing_a_page() {
+        // A page no other test's buffer can touch
+        let size = imp::page_size();
+        let buffer = vec![7u8; 3 * size];
+        let start = (buffer.as_ptr() as usize / size + 1) * size - buffer.as_ptr() as usize;
+        let page = &buffer[start..start + size];
+        let page_addr = page.as_ptr() as usize;
+        let count = || locked_pages().get(&page_addr).copied();
+
+        let first = LockedRegion::new(&page[..32]);
+        let second = LockedRegion::new(&page[64..96]);
+        if !(first.is_locked() && second.is_locked()) {
+            // Locking refused by a zero `ulimit -l`
+            return;
+        }
+        assert_eq!(count(), Some(2));
+
+        drop(first);
+        assert_eq!(count(), Some(1));
+        drop(second);
+        assert_eq!(count(), None);
+    }
+}
@@ -0,0 +1,31 @@
+//! # Platform Security
+//!
+//! Operating-system level protections for wallet data at rest, the
+//! install-wide switches that lock down risky features, password
+//! sources for unattended runs, rate limiting of wrong passwords,
+//! keeping secrets out of swap and core dumps, hardware-bound keystore
+//! encryption, and overwriting files before deleting them.
+
+pub mod keychain;
+pub mod lockdown;
+pub mod memory;
+pub mod password;
+pub mod permissions;
+pub mod shred;
+pub mod tpm;
+pub mod unlock_limit;
+pub mod workspace;
+pub mod yubikey;
+
+pub use lockdown::Feature;
+pub use password::PasswordSource;
+pub use permissions::PermissionStatus;
+pub use workspace::ExportWorkspace;
+
+/// Process-level protections (`[security]` in the config file)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityConfig {
+    /// Lock secret buffers into RAM and disable core dumps
+    pub hardened_memory: bool,
+}
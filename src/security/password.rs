@@ -0,0 +1,152 @@
+//! # Non-Interactive Passwords
+//!
+//! Where the wallet password comes from when nobody is at a terminal: the
+//! first line of a file (`--password-file`), the first line of standard
+//! input (`--password-stdin`) or the `WALLET_PASSWORD` environment
+//! variable. The source travels in
+//! [`WalletConfig::password_source`](crate::WalletConfig::password_source)
+//! and is read each time a password is needed, standard input giving one
+//! line per read. In non-interactive mode anything that would still prompt
+//! fails instead, so scripts never hang on a hidden prompt.
+
+use crate::errors::{FileSystemError, UserInputError, WalletResult};
+use crate::security::permissions;
+use crate::services::crypto::SecureString;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::warn;
+use zeroize::Zeroizing;
+
+/// Environment variable holding the wallet password
+pub const PASSWORD_ENV_VAR: &str = "WALLET_PASSWORD";
+
+/// Where the wallet password is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordSource {
+    /// First line of a file
+    File(PathBuf),
+    /// First line of standard input
+    Stdin,
+    /// The `WALLET_PASSWORD` environment variable
+    Env,
+}
+
+impl PasswordSource {
+    /// Source chosen by the command-line flags, falling back to `WALLET_PASSWORD` when it is set
+    pub fn select(file: Option<PathBuf>, stdin: bool) -> Option<Self> {
+        match (file, stdin) {
+            (Some(path), _) => Some(Self::File(path)),
+            (None, true) => Some(Self::Stdin),
+            (None, false) => std::env::var_os(PASSWORD_ENV_VAR).map(|_| Self::Env),
+        }
+    }
+
+    /// Read the password from this source
+    pub fn read(&self) -> WalletResult<SecureString> {
+        let password = match self {
+            Self::File(path) => {
+                if let Ok(status) = permissions::check(path) {
+                    if !status.is_secure() && status != permissions::PermissionStatus::Unsupported {
+                        warn!("Password file {} is readable by other users ({})", path.display(), status);
+                    }
+                }
+                let contents = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| read_error(path, e))?);
+                first_line(&contents)
+            }
+            Self::Stdin => {
+                let mut line = Zeroizing::new(String::new());
+                std::io::stdin()
+                    .lock()
+                    .read_line(&mut line)
+                    .map_err(|e| read_error(Path::new("<stdin>"), e))?;
+                first_line(&line)
+            }
+            Self::Env => {
+                warn!(
+                    "Reading the password from {}; environment variables can leak to child processes and process listings",
+                    PASSWORD_ENV_VAR
+                );
+                let value = std::env::var_os(PASSWORD_ENV_VAR)
+                    .unwrap_or_default()
+                    .into_string()
+                    .map_err(|_| UserInputError::InvalidParameters {
+                        parameter: PASSWORD_ENV_VAR.to_string(),
+                        value: "<not valid UTF-8>".to_string(),
+                        expected: "a UTF-8 password".to_string(),
+                    })?;
+                SecureString::new(value)
+            }
+        };
+
+        if password.is_empty() {
+            return Err(UserInputError::MissingParameter {
+                parameter: "password".to_string(),
+                hint: format!("the password source ({}) is empty", self.describe()),
+            }
+            .into());
+        }
+        Ok(password)
+    }
+
+    /// Short description for messages
+    pub fn describe(&self) -> String {
+        match self {
+            Self::File(path) => format!("file {}", path.display()),
+            Self::Stdin => "standard input".to_string(),
+            Self::Env => PASSWORD_ENV_VAR.to_string(),
+        }
+    }
+}
+
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set whether prompting is allowed for the rest of the process
+pub fn set_non_interactive(non_interactive: bool) {
+    NON_INTERACTIVE.store(non_interactive, Ordering::Relaxed);
+}
+
+/// Check if prompts must fail instead of waiting for input
+pub fn is_non_interactive() -> bool {
+    NON_INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// Error for a prompt refused in non-interactive mode
+pub fn prompt_refused(prompt: &str) -> UserInputError {
+    UserInputError::MissingParameter {
+        parameter: prompt.trim().trim_end_matches(':').to_string(),
+        hint: "--non-interactive forbids prompts; pass the password with --password-file, --password-stdin or WALLET_PASSWORD"
+            .to_string(),
+    }
+}
+
+fn first_line(contents: &str) -> SecureString {
+    SecureString::new(contents.lines().next().unwrap_or_default().to_string())
+}
+
+fn read_error(path: &Path, e: std::io::Error) -> FileSystemError {
+    FileSystemError::PermissionDenied {
+        path: path.display().to_string(),
+        operation: format!("read password: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_file_first_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("password.txt");
+        std::fs::write(&path, "Passw0rd!Str0ng#1\r\nignored\n").unwrap();
+
+        let source = PasswordSource::select(Some(path.clone()), true).unwrap();
+        assert_eq!(source, PasswordSource::File(path.clone()));
+        assert_eq!(source.read().unwrap().as_str(), "Passw0rd!Str0ng#1");
+
+        std::fs::write(&path, "\nsecond line\n").unwrap();
+        assert!(source.read().is_err());
+        assert!(PasswordSource::File(dir.path().join("missing")).read().is_err());
+    }
+}
@@ -0,0 +1,371 @@
+//! # File Permissions
+//!
+//! Owner-only access control for keystores and the wallet directory.
+//! Unix uses POSIX modes; Windows uses a protected DACL that grants access
+//! to the current user only.
+
+use crate::errors::{FileSystemError, WalletResult};
+use std::path::{Path, PathBuf};
+
+/// Result of a permission check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionStatus {
+    /// Only the owner can access the path
+    Secure,
+    /// Other principals can access the path
+    Insecure {
+        /// Description of the problem
+        details: String,
+    },
+    /// Permissions cannot be inspected on this platform
+    Unsupported,
+}
+
+impl PermissionStatus {
+    /// Check if the path is restricted to its owner
+    pub fn is_secure(&self) -> bool {
+        matches!(self, PermissionStatus::Secure)
+    }
+}
+
+impl std::fmt::Display for PermissionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PermissionStatus::Secure => write!(f, "owner only"),
+            PermissionStatus::Insecure { details } => write!(f, "{}", details),
+            PermissionStatus::Unsupported => write!(f, "not checked on this platform"),
+        }
+    }
+}
+
+/// Permission check result for a single path
+#[derive(Debug, Clone)]
+pub struct PermissionFinding {
+    /// Checked path
+    pub path: PathBuf,
+    /// Check result
+    pub status: PermissionStatus,
+}
+
+/// Restrict a file or directory so only the current user can access it
+pub fn restrict_to_owner(path: &Path) -> WalletResult<()> {
+    imp::restrict_to_owner(path).map_err(|e| {
+        FileSystemError::PermissionDenied {
+            path: path.display().to_string(),
+            operation: format!("restrict permissions: {}", e),
+        }
+        .into()
+    })
+}
+
+/// Check whether a file or directory is accessible only by its owner
+pub fn check(path: &Path) -> WalletResult<PermissionStatus> {
+    imp::check(path).map_err(|e| {
+        FileSystemError::PermissionDenied {
+            path: path.display().to_string(),
+            operation: format!("inspect permissions: {}", e),
+        }
+        .into()
+    })
+}
+
+/// Check a wallet directory and every keystore (`*.json`) inside it
+pub fn audit_directory(dir: &Path) -> WalletResult<Vec<PermissionFinding>> {
+    let mut findings = vec![PermissionFinding {
+        path: dir.to_path_buf(),
+        status: check(dir)?,
+    }];
+
+    let entries = std::fs::read_dir(dir).map_err(|e| FileSystemError::DirectoryNotAccessible {
+        path: dir.display().to_string(),
+        details: e.to_string(),
+    })?;
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().and_then(|s| s.to_str())
+                    == Some(crate::config::KEYSTORE_EXTENSION)
+        })
+        .collect();
+    files.sort();
+
+    for path in files {
+        let status = check(&path)?;
+        findings.push(PermissionFinding { path, status });
+    }
+
+    Ok(findings)
+}
+
+#[cfg(unix)]
+mod imp {
+    use super::PermissionStatus;
+    use crate::config;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    pub(super) fn restrict_to_owner(path: &Path) -> std::io::Result<()> {
+        let mode = if path.is_dir() {
+            config::fs::WALLET_DIR_PERMISSIONS
+        } else {
+            config::fs::KEYSTORE_FILE_PERMISSIONS
+        };
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+    }
+
+    pub(super) fn check(path: &Path) -> std::io::Result<PermissionStatus> {
+        let mode = std::fs::metadata(path)?.permissions().mode() & 0o777;
+        if mode & 0o077 == 0 {
+            Ok(PermissionStatus::Secure)
+        } else {
+            Ok(PermissionStatus::Insecure {
+                details: format!("mode {:04o} allows group/other access", mode),
+            })
+        }
+    }
+}
+
+#[cfg(windows)]
+#[allow(unsafe_code)]
+mod imp {
+    use super::PermissionStatus;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr::{null, null_mut};
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, LocalFree, ERROR_SUCCESS, GENERIC_ALL, HANDLE, PSID,
+    };
+    use windows_sys::Win32::Security::Authorization::{
+        GetNamedSecurityInfoW, SetEntriesInAclW, SetNamedSecurityInfoW, EXPLICIT_ACCESS_W,
+        NO_MULTIPLE_TRUSTEE, SET_ACCESS, SE_FILE_OBJECT, TRUSTEE_IS_SID, TRUSTEE_IS_USER,
+        TRUSTEE_W,
+    };
+    use windows_sys::Win32::Security::{
+        EqualSid, GetAce, GetSecurityDescriptorControl, GetTokenInformation, TokenUser,
+        ACCESS_ALLOWED_ACE, ACE_HEADER, ACL, DACL_SECURITY_INFORMATION, NO_INHERITANCE,
+        PROTECTED_DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, SE_DACL_PROTECTED,
+        SUB_CONTAINERS_AND_OBJECTS_INHERIT, TOKEN_QUERY, TOKEN_USER,
+    };
+    use windows_sys::Win32::System::SystemServices::ACCESS_ALLOWED_ACE_TYPE;
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    /// Current user's SID, kept alive inside its `TOKEN_USER` buffer
+    struct CurrentUser {
+        buffer: Vec<u64>,
+    }
+
+    impl CurrentUser {
+        fn query() -> std::io::Result<Self> {
+            // SAFETY: the token handle is closed on every path and the buffer
+            // is sized from the length reported by the first call.
+            unsafe {
+                let mut token: HANDLE = 0;
+                if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                let mut len = 0u32;
+                GetTokenInformation(token, TokenUser, null_mut(), 0, &mut len);
+                let mut buffer = vec![0u64; (len as usize + 7) / 8];
+                let ok = GetTokenInformation(
+                    token,
+                    TokenUser,
+                    buffer.as_mut_ptr().cast(),
+                    len,
+                    &mut len,
+                );
+                CloseHandle(token);
+
+                if ok == 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(Self { buffer })
+            }
+        }
+
+        fn sid(&self) -> PSID {
+            // SAFETY: the buffer was filled by GetTokenInformation(TokenUser)
+            unsafe { (*(self.buffer.as_ptr() as *const TOKEN_USER)).User.Sid }
+        }
+    }
+
+    fn wide(path: &Path) -> Vec<u16> {
+        path.as_os_str().encode_wide().chain(Some(0)).collect()
+    }
+
+    pub(super) fn restrict_to_owner(path: &Path) -> std::io::Result<()> {
+        let user = CurrentUser::query()?;
+        let inheritance = if path.is_dir() {
+            SUB_CONTAINERS_AND_OBJECTS_INHERIT
+        } else {
+            NO_INHERITANCE
+        };
+
+        let access = EXPLICIT_ACCESS_W {
+            grfAccessPermissions: GENERIC_ALL,
+            grfAccessMode: SET_ACCESS,
+            grfInheritance: inheritance,
+            Trustee: TRUSTEE_W {
+                pMultipleTrustee: null_mut(),
+                MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+                TrusteeForm: TRUSTEE_IS_SID,
+                TrusteeType: TRUSTEE_IS_USER,
+                ptstrName: user.sid().cast(),
+            },
+        };
+
+        // SAFETY: the ACL allocated by SetEntriesInAclW is freed with LocalFree
+        // and the SID it references outlives both calls.
+        unsafe {
+            let mut acl: *mut ACL = null_mut();
+            let err = SetEntriesInAclW(1, &access, null(), &mut acl);
+            if err != ERROR_SUCCESS {
+                return Err(std::io::Error::from_raw_os_error(err as i32));
+            }
+
+            let name = wide(path);
+            let err = SetNamedSecurityInfoW(
+                name.as_ptr(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                null_mut(),
+                null_mut(),
+                acl,
+                null(),
+            );
+            LocalFree(acl.cast());
+
+            if err != ERROR_SUCCESS {
+                return Err(std::io::Error::from_raw_os_error(err as i32));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn check(path: &Path) -> std::io::Result<PermissionStatus> {
+        let user = CurrentUser::query()?;
+        let name = wide(path);
+
+        // SAFETY: the security descriptor returned by GetNamedSecurityInfoW owns
+        // the DACL and is freed with LocalFree after inspection.
+        unsafe {
+            let mut dacl: *mut ACL = null_mut();
+            let mut descriptor: PSECURITY_DESCRIPTOR = null_mut();
+            let err = GetNamedSecurityInfoW(
+                name.as_ptr(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION,
+                null_mut(),
+                null_mut(),
+                &mut dacl,
+                null_mut(),
+                &mut descriptor,
+            );
+            if err != ERROR_SUCCESS {
+                return Err(std::io::Error::from_raw_os_error(err as i32));
+            }
+
+            let status = inspect(descriptor, dacl, user.sid());
+            LocalFree(descriptor);
+            Ok(status)
+        }
+    }
+
+    unsafe fn inspect(descriptor: PSECURITY_DESCRIPTOR, dacl: *mut ACL, user: PSID) -> PermissionStatus {
+        if dacl.is_null() {
+            return PermissionStatus::Insecure {
+                details: "no DACL (everyone has full access)".to_string(),
+            };
+        }
+
+        let mut control = 0u16;
+        let mut revision = 0u32;
+        if GetSecurityDescriptorControl(descriptor, &mut control, &mut revision) != 0
+            && control & SE_DACL_PROTECTED == 0
+        {
+            return PermissionStatus::Insecure {
+                details: "DACL inherits entries from the parent directory".to_string(),
+            };
+        }
+
+        for index in 0..u32::from((*dacl).AceCount) {
+            let mut ace = null_mut();
+            if GetAce(dacl, index, &mut ace) == 0 {
+                continue;
+            }
+
+            let header = &*(ace as *const ACE_HEADER);
+            if u32::from(header.AceType) != ACCESS_ALLOWED_ACE_TYPE {
+                continue;
+            }
+
+            let allowed = ace as *const ACCESS_ALLOWED_ACE;
+            let sid = std::ptr::addr_of!((*allowed).SidStart) as PSID;
+            if EqualSid(sid, user) == 0 {
+                return PermissionStatus::Insecure {
+                    details: "access granted to other accounts".to_string(),
+                };
+            }
+        }
+
+        PermissionStatus::Secure
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod imp {
+    use super::PermissionStatus;
+    use std::path::Path;
+
+    pub(super) fn restrict_to_owner(_path: &Path) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    pub(super) fn check(_path: &Path) -> std::io::Result<PermissionStatus> {
+        Ok(PermissionStatus::Unsupported)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_restrict_and_check_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("wallet.json");
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(!check(&path).unwrap().is_secure());
+
+        restrict_to_owner(&path).unwrap();
+        assert_eq!(check(&path).unwrap(), PermissionStatus::Secure);
+    }
+
+    #[test]
+    fn test_audit_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::set_permissions(temp_dir.path(), std::fs::Permissions::from_mode(0o700)).unwrap();
+
+        let secure = temp_dir.path().join("a.json");
+        let open = temp_dir.path().join("b.json");
+        let ignored = temp_dir.path().join("notes.txt");
+        for path in [&secure, &open, &ignored] {
+            std::fs::write(path, "{}").unwrap();
+        }
+        std::fs::set_permissions(&secure, std::fs::Permissions::from_mode(0o600)).unwrap();
+        std::fs::set_permissions(&open, std::fs::Permissions::from_mode(0o666)).unwrap();
+
+        let findings = audit_directory(temp_dir.path()).unwrap();
+        assert_eq!(findings.len(), 3);
+        assert!(findings[0].status.is_secure());
+        assert!(findings[1].status.is_secure());
+        assert!(!findings[2].status.is_secure());
+    }
+}
@@ -0,0 +1,136 @@
+//! # Secure Deletion
+//!
+//! Overwrites a file's contents before unlinking it, renames it to a
+//! random name first so the directory entry no longer names the keystore,
+//! and syncs the directory so the removal reaches the disk.
+//!
+//! Overwriting in place only reaches the original blocks on filesystems
+//! that rewrite files in place (ext4, NTFS, HFS+ without snapshots). SSDs
+//! remap writes through their wear-leveling layer, copy-on-write and
+//! log-structured filesystems (btrfs, ZFS, APFS, F2FS) write new blocks, and
+//! snapshots or backups keep their own copies; on those the old contents
+//! may survive until the device reuses the space. Full-disk encryption is
+//! the only reliable protection there, and keystores are encrypted
+//! anyway, so shredding is defence in depth rather than a guarantee.
+
+use crate::config;
+use crate::errors::{FileSystemError, WalletResult};
+use rand::RngCore;
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of each overwrite write
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Result of shredding one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShredReport {
+    /// File that was removed
+    pub path: PathBuf,
+    /// Bytes overwritten per pass
+    pub size: u64,
+    /// Overwrite passes made
+    pub passes: u32,
+}
+
+/// Overwrite `path` with `passes` rounds (random data, zeros on the last) and remove it
+#[tracing::instrument(level = "debug", skip_all, fields(path = %path.display(), passes = passes))]
+pub fn shred_file(path: &Path, passes: u32) -> WalletResult<ShredReport> {
+    let metadata = std::fs::symlink_metadata(path).map_err(|_| FileSystemError::FileNotFound {
+        path: path.display().to_string(),
+        directory: path.parent().map(|p| p.display().to_string()).unwrap_or_else(|| ".".to_string()),
+    })?;
+    if !metadata.is_file() {
+        // Following a symlink would overwrite whatever it points to
+        return Err(FileSystemError::InvalidFormat {
+            path: path.display().to_string(),
+            details: "only regular files can be shredded".to_string(),
+        }
+        .into());
+    }
+
+    let size = metadata.len();
+    let passes = passes.max(1);
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    for pass in 0..passes {
+        let last = pass + 1 == passes;
+        file.seek(SeekFrom::Start(0))?;
+        let mut remaining = size;
+        while remaining > 0 {
+            let len = usize::try_from(remaining).unwrap_or(CHUNK_SIZE).min(CHUNK_SIZE);
+            if last {
+                chunk[..len].fill(0);
+            } else {
+                rand::thread_rng().fill_bytes(&mut chunk[..len]);
+            }
+            file.write_all(&chunk[..len])?;
+            remaining -= len as u64;
+        }
+        file.sync_data()?;
+    }
+    drop(file);
+
+    // Leave no directory entry naming the file behind
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let anonymous = dir.join(format!(".shred-{:016x}", rand::random::<u64>()));
+    std::fs::rename(path, &anonymous)?;
+    std::fs::remove_file(&anonymous)?;
+    #[cfg(unix)]
+    std::fs::File::open(dir)?.sync_all()?;
+
+    Ok(ShredReport {
+        path: path.to_path_buf(),
+        size,
+        passes,
+    })
+}
+
+/// Keep the current contents of `path` reachable under a hidden name before it is replaced
+///
+/// An atomic replace only unlinks the old file, leaving its blocks intact;
+/// holding a hard link lets [`shred_retained`] overwrite them once the new
+/// file is in place. Returns `None` when there is nothing to keep or the
+/// filesystem does not support hard links.
+pub fn retain_previous(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let link = path.with_file_name(format!(".{}.old-{:016x}", name, rand::random::<u64>()));
+    std::fs::hard_link(path, &link).ok().map(|()| link)
+}
+
+/// Shred a link made by [`retain_previous`], logging rather than failing
+pub fn shred_retained(link: &Path) {
+    if let Err(e) = shred_file(link, config::fs::SHRED_PASSES) {
+        tracing::warn!("Could not overwrite the previous contents of a replaced file: {}", e);
+        let _ = std::fs::remove_file(link);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shred_overwrites_and_removes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.json");
+        std::fs::write(&path, vec![0xAA; CHUNK_SIZE + 10]).unwrap();
+
+        // A second link sees the overwritten contents, proving the blocks were rewritten in place
+        let witness = dir.path().join("witness");
+        std::fs::hard_link(&path, &witness).unwrap();
+        let report = shred_file(&path, 3).unwrap();
+        assert_eq!((report.size, report.passes), (CHUNK_SIZE as u64 + 10, 3));
+        assert!(!path.exists());
+        assert_eq!(std::fs::read(&witness).unwrap(), vec![0; CHUNK_SIZE + 10]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let retained = retain_previous(&witness).unwrap();
+        crate::utils::write_atomic(&witness, b"replaced", config::fs::KEYSTORE_FILE_PERMISSIONS).unwrap();
+        shred_retained(&retained);
+        assert!(!retained.exists());
+        assert_eq!(std::fs::read(&witness).unwrap(), b"replaced");
+        assert!(shred_file(dir.path(), 1).is_err());
+    }
+}
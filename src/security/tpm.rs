@@ -0,0 +1,187 @@
+//! # TPM Key Binding
+//!
+//! Optional binding of a keystore to the machine it was saved on. A random
+//! secret is sealed to the TPM 2.0 under its owner storage key and mixed
+//! into the key derived from the password; the keystore keeps only the
+//! sealed blobs, which no other TPM can load. A copied keystore is then
+//! useless without this machine, even with the password.
+//!
+//! The binding is lost along with the TPM: clearing it, replacing the
+//! mainboard or moving the disk to another machine makes the keystore
+//! undecryptable for good, so the mnemonic must be backed up elsewhere.
+//!
+//! The TPM is driven through tpm2-tools (`tpm2_createprimary`,
+//! `tpm2_create`, `tpm2_load`, `tpm2_unseal`), so no TSS library is linked
+//! into the wallet. Apple's Secure Enclave is not supported yet.
+
+use crate::errors::{AuthenticationError, WalletResult};
+use crate::security::ExportWorkspace;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+/// Device nodes of a TPM 2.0, resource-managed first
+const DEVICES: [&str; 2] = ["/dev/tpmrm0", "/dev/tpm0"];
+
+/// Something that seals secrets so only one machine can unseal them
+pub trait KeySealer: Send + Sync {
+    /// Seal `secret`, returning the public and private areas to store
+    fn seal(&self, secret: &[u8]) -> WalletResult<(Vec<u8>, Vec<u8>)>;
+
+    /// Recover a secret from its stored areas
+    fn unseal(&self, public: &[u8], private: &[u8]) -> WalletResult<Zeroizing<Vec<u8>>>;
+}
+
+/// The local TPM 2.0, reached through tpm2-tools
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tpm2Tools;
+
+impl Tpm2Tools {
+    fn run(dir: &Path, program: &str, args: &[&str], stdin: Option<&[u8]>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(dir)
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    tracing::warn!("Install tpm2-tools to use keystores bound to this machine's TPM");
+                }
+                failed(&format!("could not run {}: {}", program, e))
+            })?;
+        if let (Some(data), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(data)?;
+        }
+
+        let output = child.wait_with_output()?;
+        let stdout = Zeroizing::new(output.stdout);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            tracing::warn!("{} failed: {}", program, stderr.trim());
+            return Err(failed(&format!("{} failed: {}", program, stderr.trim())).into());
+        }
+        Ok(stdout)
+    }
+
+    /// Recreate the owner storage primary key; the same template always gives the same key
+    fn create_primary(dir: &Path) -> WalletResult<()> {
+        Self::run(dir, "tpm2_createprimary", &["-Q", "-C", "o", "-g", "sha256", "-G", "ecc", "-c", "primary.ctx"], None)?;
+        Ok(())
+    }
+
+    fn workspace() -> WalletResult<ExportWorkspace> {
+        if cfg!(target_os = "macos") {
+            return Err(failed("binding to the Secure Enclave is not supported yet").into());
+        }
+        if !is_available() {
+            return Err(failed("no TPM 2.0 found on this machine").into());
+        }
+        ExportWorkspace::create(None)
+    }
+}
+
+impl KeySealer for Tpm2Tools {
+    fn seal(&self, secret: &[u8]) -> WalletResult<(Vec<u8>, Vec<u8>)> {
+        let workspace = Self::workspace()?;
+        let dir = workspace.dir();
+        let sealed = Self::create_primary(dir)
+            .and_then(|()| {
+                Self::run(
+                    dir,
+                    "tpm2_create",
+                    &["-Q", "-C", "primary.ctx", "-g", "sha256", "-i", "-", "-u", "seal.pub", "-r", "seal.priv"],
+                    Some(secret),
+                )
+            })
+            .and_then(|_| Ok((std::fs::read(dir.join("seal.pub"))?, std::fs::read(dir.join("seal.priv"))?)));
+        let _ = std::fs::remove_dir_all(dir);
+        sealed
+    }
+
+    fn unseal(&self, public: &[u8], private: &[u8]) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let workspace = Self::workspace()?;
+        let dir = workspace.dir();
+        workspace.write_secret("seal.pub", public)?;
+        workspace.write_secret("seal.priv", private)?;
+        let secret = Self::create_primary(dir)
+            .and_then(|()| {
+                Self::run(dir, "tpm2_load", &["-Q", "-C", "primary.ctx", "-u", "seal.pub", "-r", "seal.priv", "-c", "seal.ctx"], None)
+            })
+            .and_then(|_| Self::run(dir, "tpm2_unseal", &["-Q", "-c", "seal.ctx"], None));
+        let _ = std::fs::remove_dir_all(dir);
+        secret
+    }
+}
+
+fn failed(details: &str) -> AuthenticationError {
+    AuthenticationError::SecondFactorFailed {
+        keystore: String::new(),
+        details: details.to_string(),
+    }
+}
+
+/// Check if this machine exposes a TPM 2.0 device
+pub fn is_available() -> bool {
+    DEVICES.iter().any(|device| Path::new(device).exists())
+}
+
+/// Seal `secret` with `sealer`, returning the hex-encoded public and private areas
+pub fn seal(sealer: &dyn KeySealer, secret: &[u8]) -> WalletResult<(String, String)> {
+    let (public, private) = sealer.seal(secret)?;
+    Ok((hex::encode(public), hex::encode(private)))
+}
+
+/// Recover a secret with `sealer` from hex-encoded areas written by [`seal`]
+pub fn unseal(sealer: &dyn KeySealer, public: &str, private: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+    let decode = |area: &str| hex::decode(area).map_err(|_| failed("stored TPM area is not hex"));
+    sealer.unseal(&decode(public)?, &decode(private)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Network, Wallet};
+    use crate::services::crypto::{CryptoService, HardwareBinding, HardwareTokens, KdfSettings, SecureString};
+    use std::sync::Arc;
+
+    /// Machine emulation: sealed blobs only unseal under the same machine id
+    struct SoftwareTpm(u8);
+
+    impl KeySealer for SoftwareTpm {
+        fn seal(&self, secret: &[u8]) -> WalletResult<(Vec<u8>, Vec<u8>)> {
+            Ok((vec![self.0], secret.iter().map(|b| b ^ self.0).collect()))
+        }
+
+        fn unseal(&self, public: &[u8], private: &[u8]) -> WalletResult<Zeroizing<Vec<u8>>> {
+            if public != [self.0] {
+                return Err(failed("object was sealed by another TPM").into());
+            }
+            Ok(Zeroizing::new(private.iter().map(|b| b ^ self.0).collect()))
+        }
+    }
+
+    #[test]
+    fn test_keystore_bound_to_machine() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let settings = KdfSettings { memory: 1024, iterations: 1, parallelism: 1 };
+        let password = &SecureString::from("Passw0rd!Str0ng#1");
+
+        let tokens = HardwareTokens { tpm: Arc::new(SoftwareTpm(0x5a)), ..HardwareTokens::default() };
+        let keystore = CryptoService::encrypt_wallet_bound(&wallet, password, settings, HardwareBinding::Tpm, &tokens).unwrap();
+        assert!(keystore.to_json().unwrap().contains(r#""kind":"tpm2-sealed""#));
+        keystore.validate().unwrap();
+        assert_eq!(CryptoService::decrypt_wallet_bound(&keystore, password, &tokens).unwrap().address(), wallet.address());
+
+        // A copy on another machine does not open, even with the password
+        let other = HardwareTokens { tpm: Arc::new(SoftwareTpm(0x33)), ..HardwareTokens::default() };
+        assert_eq!(CryptoService::decrypt_wallet_bound(&keystore, password, &other).unwrap_err().code(), "AUTH_008");
+    }
+}
@@ -0,0 +1,158 @@
+//! # Unlock Rate Limiting
+//!
+//! Counts wrong passwords per keystore in a hidden sidecar file next to it
+//! (`.wallet.json.lockout`). After a few free attempts each further failure
+//! doubles the wait before the next try, and past a hard limit the keystore
+//! only opens again when the caller explicitly asks to reset the lockout
+//! and then supplies the right password. A successful unlock clears the
+//! count. The sidecar slows guessing through this tool; it cannot stop
+//! someone working on a copy of the keystore, which is what the KDF cost
+//! is for.
+
+use crate::config::{self, unlock};
+use crate::errors::{AuthenticationError, WalletResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Wait required after `failures` consecutive wrong passwords
+pub fn delay_after(failures: u32) -> Duration {
+    if failures < unlock::FREE_ATTEMPTS {
+        return Duration::ZERO;
+    }
+    let doublings = (failures - unlock::FREE_ATTEMPTS).min(16);
+    Duration::from_secs((unlock::BASE_DELAY_SECS << doublings).min(unlock::MAX_DELAY_SECS))
+}
+
+/// Failed unlock state of one keystore
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockAttempts {
+    /// Consecutive wrong passwords
+    pub failures: u32,
+    /// Time of the most recent one
+    pub last_failure: Option<DateTime<Utc>>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl UnlockAttempts {
+    /// Sidecar file for a keystore
+    pub fn sidecar_path(keystore: &Path) -> PathBuf {
+        let name = keystore.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        keystore.with_file_name(format!(".{}.{}", name, unlock::SIDECAR_SUFFIX))
+    }
+
+    /// Load the state of a keystore; no sidecar means no failures
+    ///
+    /// An unreadable sidecar counts as a hard lock rather than a clean
+    /// slate, so deleting its contents does not reset the count.
+    pub fn load(keystore: &Path) -> WalletResult<Self> {
+        let path = Self::sidecar_path(keystore);
+        if !path.exists() {
+            return Ok(Self { path, ..Self::default() });
+        }
+
+        let json = std::fs::read_to_string(&path)?;
+        let mut attempts = serde_json::from_str(&json).unwrap_or_else(|_| Self {
+            failures: unlock::HARD_LOCK_ATTEMPTS,
+            last_failure: None,
+            path: PathBuf::new(),
+        });
+        attempts.path = path;
+        Ok(attempts)
+    }
+
+    /// When the next attempt is allowed, if it is not allowed yet at `now`
+    pub fn locked_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let last = self.last_failure?;
+        let until = last + chrono::Duration::from_std(delay_after(self.failures)).ok()?;
+        (until > now).then_some(until)
+    }
+
+    /// Check if the hard limit was reached
+    pub fn is_hard_locked(&self) -> bool {
+        self.failures >= unlock::HARD_LOCK_ATTEMPTS
+    }
+
+    /// Refuse an attempt while a wait is pending, or past the hard limit unless `reset` (`--reset-lockout`)
+    pub fn check(&self, now: DateTime<Utc>, reset: bool) -> WalletResult<()> {
+        let wait = self.locked_until(now).and_then(|until| (until - now).to_std().ok());
+
+        if self.is_hard_locked() && !reset {
+            tracing::warn!(
+                "{} wrong passwords for this keystore; retry with --reset-lockout and the correct password",
+                self.failures
+            );
+            // No duration: waiting alone never lifts a hard lock
+            return Err(AuthenticationError::MaxAttemptsExceeded { lockout_duration: Duration::ZERO }.into());
+        }
+        if let Some(wait) = wait {
+            tracing::warn!("Too many wrong passwords; try again in {} s", wait.as_secs().max(1));
+            return Err(AuthenticationError::MaxAttemptsExceeded { lockout_duration: wait }.into());
+        }
+        Ok(())
+    }
+
+    /// Wrong passwords left before the hard limit
+    pub fn remaining(&self) -> u32 {
+        unlock::HARD_LOCK_ATTEMPTS.saturating_sub(self.failures)
+    }
+
+    /// Count a wrong password
+    pub fn record_failure(&mut self, now: DateTime<Utc>) -> WalletResult<()> {
+        self.failures = self.failures.saturating_add(1);
+        self.last_failure = Some(now);
+        let json = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(&self.path, json.as_bytes(), config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+
+    /// Forget recorded failures after a successful unlock
+    pub fn record_success(&mut self) -> WalletResult<()> {
+        if self.path.exists() {
+            std::fs::remove_file(&self.path)?;
+        }
+        *self = Self { path: std::mem::take(&mut self.path), ..Self::default() };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_escalating_lockout() {
+        assert_eq!(delay_after(2), Duration::ZERO);
+        assert_eq!(delay_after(3), Duration::from_secs(30));
+        assert_eq!(delay_after(5), Duration::from_secs(120));
+        assert_eq!(delay_after(40), Duration::from_secs(unlock::MAX_DELAY_SECS));
+
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = dir.path().join("wallet.json");
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+
+        let mut attempts = UnlockAttempts::load(&keystore).unwrap();
+        for _ in 0..3 {
+            attempts.check(now, false).unwrap();
+            attempts.record_failure(now).unwrap();
+        }
+        assert!(dir.path().join(".wallet.json.lockout").exists());
+        assert_eq!(attempts.remaining(), unlock::HARD_LOCK_ATTEMPTS - 3);
+
+        // The fourth try waits, and the count survives a reload
+        let attempts = UnlockAttempts::load(&keystore).unwrap();
+        assert!(attempts.check(now + chrono::Duration::seconds(10), false).is_err());
+        attempts.check(now + chrono::Duration::seconds(30), false).unwrap();
+
+        let mut attempts = UnlockAttempts { failures: unlock::HARD_LOCK_ATTEMPTS, ..attempts };
+        let later = now + chrono::Duration::days(1);
+        assert!(attempts.check(later, false).is_err());
+        attempts.check(later, true).unwrap();
+
+        attempts.record_success().unwrap();
+        assert!(!dir.path().join(".wallet.json.lockout").exists());
+        assert_eq!(attempts.failures, 0);
+    }
+}
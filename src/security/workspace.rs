@@ -0,0 +1,223 @@
+//! # Export Workspace
+//!
+//! Scratch location for decrypted exports. A memory-backed filesystem
+//! (tmpfs/ramfs) is preferred so secrets never reach persistent storage;
+//! when none is available the workspace falls back to the system temp
+//! directory and reports it so callers can warn. Every file written is
+//! registered for deletion when the process exits, and overwritten before
+//! it is unlinked.
+
+use crate::config;
+use crate::errors::{FileSystemError, WalletResult};
+use rand::RngCore;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Paths to remove on process exit
+static CLEANUP_REGISTRY: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Storage backing an export workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceBacking {
+    /// RAM-backed filesystem; contents never touch disk
+    Memory,
+    /// Regular disk storage
+    Disk,
+}
+
+/// Per-process directory for decrypted exports
+#[derive(Debug)]
+pub struct ExportWorkspace {
+    dir: PathBuf,
+    backing: WorkspaceBacking,
+}
+
+impl ExportWorkspace {
+    /// Create a private workspace, preferring `configured`, then tmpfs locations, then the temp dir
+    pub fn create(configured: Option<&Path>) -> WalletResult<Self> {
+        let base = match configured {
+            Some(dir) => dir.to_path_buf(),
+            None => Self::memory_candidates()
+                .into_iter()
+                .find(|dir| dir.is_dir() && is_memory_backed(dir))
+                .unwrap_or_else(std::env::temp_dir),
+        };
+
+        let backing = if is_memory_backed(&base) {
+            WorkspaceBacking::Memory
+        } else {
+            WorkspaceBacking::Disk
+        };
+
+        let dir = base.join(format!(
+            "web3wallet-export-{}-{:08x}",
+            std::process::id(),
+            rand::thread_rng().next_u32()
+        ));
+        create_private_dir(&dir)?;
+        register_for_cleanup(dir.clone());
+
+        Ok(Self { dir, backing })
+    }
+
+    /// Workspace directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Storage backing the workspace
+    pub fn backing(&self) -> WorkspaceBacking {
+        self.backing
+    }
+
+    /// Check if the workspace lives in memory
+    pub fn is_memory_backed(&self) -> bool {
+        self.backing == WorkspaceBacking::Memory
+    }
+
+    /// Write a secret file into the workspace and register it for deletion
+    pub fn write_secret(&self, name: &str, data: &[u8]) -> WalletResult<PathBuf> {
+        let name = crate::utils::sanitize_filename(name);
+        if name.is_empty() {
+            return Err(FileSystemError::PathTraversal {
+                path: name,
+            }
+            .into());
+        }
+
+        let path = self.dir.join(name);
+        register_for_cleanup(path.clone());
+        crate::utils::write_atomic(&path, data, config::fs::KEYSTORE_FILE_PERMISSIONS)?;
+
+        Ok(path)
+    }
+
+    /// Well-known RAM-backed locations, most private first
+    fn memory_candidates() -> Vec<PathBuf> {
+        let mut candidates = Vec::new();
+        if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+            candidates.push(PathBuf::from(runtime_dir));
+        }
+        candidates.push(PathBuf::from("/dev/shm"));
+        candidates
+    }
+}
+
+/// Register a file or directory for removal by [`cleanup_registered`]
+pub fn register_for_cleanup(path: PathBuf) {
+    if let Ok(mut registry) = CLEANUP_REGISTRY.lock() {
+        if !registry.contains(&path) {
+            registry.push(path);
+        }
+    }
+}
+
+/// Remove every registered path; returns how many existed and were removed
+pub fn cleanup_registered() -> usize {
+    let paths = match CLEANUP_REGISTRY.lock() {
+        Ok(mut registry) => std::mem::take(&mut *registry),
+        Err(_) => return 0,
+    };
+
+    // Files were registered before their directories, so remove in reverse
+    paths
+        .iter()
+        .rev()
+        .filter(|path| {
+            if path.is_dir() {
+                std::fs::remove_dir_all(path).is_ok()
+            } else {
+                crate::security::shred::shred_file(path, config::fs::SHRED_PASSES).is_ok()
+                    || std::fs::remove_file(path).is_ok()
+            }
+        })
+        .count()
+}
+
+/// Check whether `path` is on a tmpfs/ramfs mount
+pub fn is_memory_backed(path: &Path) -> bool {
+    match std::fs::read_to_string("/proc/self/mounts") {
+        Ok(mounts) => {
+            let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            mount_is_memory_backed(&path, &mounts)
+        }
+        Err(_) => false,
+    }
+}
+
+/// Find the mount containing `path` in a /proc/mounts table and check its type
+fn mount_is_memory_backed(path: &Path, mounts: &str) -> bool {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())
+        .map(|(_, fs_type)| matches!(fs_type, "tmpfs" | "ramfs"))
+        .unwrap_or(false)
+}
+
+/// Create a directory readable only by the current user
+fn create_private_dir(dir: &Path) -> WalletResult<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(config::fs::WALLET_DIR_PERMISSIONS);
+    }
+
+    builder.create(dir).map_err(|e| FileSystemError::DirectoryNotAccessible {
+        path: dir.display().to_string(),
+        details: e.to_string(),
+    })?;
+
+    crate::security::permissions::restrict_to_owner(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mount_detection() {
+        let mounts = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+tmpfs /dev/shm tmpfs rw,nosuid,nodev 0 0
+tmpfs /run/user/1000 tmpfs rw,nosuid,nodev,mode=700 0 0
+/dev/sdb1 /run/user/1000/disk ext4 rw 0 0
+";
+
+        assert!(mount_is_memory_backed(Path::new("/dev/shm/x"), mounts));
+        assert!(mount_is_memory_backed(Path::new("/run/user/1000"), mounts));
+        assert!(!mount_is_memory_backed(Path::new("/run/user/1000/disk/x"), mounts));
+        assert!(!mount_is_memory_backed(Path::new("/home/user"), mounts));
+    }
+
+    #[test]
+    fn test_workspace_files_removed_on_cleanup() {
+        let base = TempDir::new().unwrap();
+        let workspace = ExportWorkspace::create(Some(base.path())).unwrap();
+        assert!(workspace.dir().starts_with(base.path()));
+
+        let path = workspace.write_secret("wallet-secrets.json", b"secret").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"secret");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(workspace.dir()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        assert!(cleanup_registered() >= 1);
+        assert!(!path.exists());
+        assert!(!workspace.dir().exists());
+    }
+}
@@ -0,0 +1,152 @@
+//! # YubiKey Challenge-Response
+//!
+//! Optional second factor baked into keystore encryption. A keystore
+//! enrolled with a YubiKey stores a random challenge; at every unlock the
+//! challenge goes to the token's HMAC-SHA1 slot and the 20-byte response
+//! is mixed into the key derived from the password. Without the physical
+//! token the password alone decrypts nothing, and no response is ever
+//! written to disk. A fresh challenge is drawn whenever the keystore is
+//! re-encrypted.
+//!
+//! The token is reached through the YubiKey command-line tools
+//! (`ykchalresp` from yubikey-personalization, or `ykman`), so no USB
+//! stack is linked into the wallet. A slot is programmed once with, for
+//! example, `ykman otp chalresp --generate --touch 2`; programming a second
+//! key with the same secret gives a spare.
+
+use crate::errors::{AuthenticationError, WalletResult};
+use crate::models::keystore::HardwareFactor;
+use rand::RngCore;
+use std::process::Command;
+use zeroize::Zeroizing;
+
+/// Slot used when none is given (slot 1 usually holds the Yubico OTP)
+pub const DEFAULT_SLOT: u8 = 2;
+
+/// Challenge drawn for new enrollments
+pub const CHALLENGE_LENGTH: usize = 32;
+
+/// Longest challenge the HMAC-SHA1 slot accepts
+pub const MAX_CHALLENGE_LENGTH: usize = 64;
+
+/// HMAC-SHA1 response length
+const RESPONSE_LENGTH: usize = 20;
+
+/// Something that answers HMAC-SHA1 challenges like a YubiKey slot
+pub trait ChallengeResponder: Send + Sync {
+    /// Response of `slot` to `challenge`
+    fn respond(&self, slot: u8, challenge: &[u8]) -> WalletResult<Zeroizing<Vec<u8>>>;
+}
+
+/// A YubiKey reached through `ykchalresp` or `ykman`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct YubiKeyCli;
+
+impl YubiKeyCli {
+    fn run(program: &str, args: &[String]) -> std::io::Result<std::process::Output> {
+        Command::new(program).args(args).stdin(std::process::Stdio::null()).output()
+    }
+}
+
+impl ChallengeResponder for YubiKeyCli {
+    fn respond(&self, slot: u8, challenge: &[u8]) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let challenge = hex::encode(challenge);
+        tracing::info!("Touch your YubiKey if it blinks");
+
+        let output = match Self::run("ykchalresp", &[format!("-{}", slot), "-x".to_string(), challenge.clone()]) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Self::run("ykman", &["otp".to_string(), "calculate".to_string(), slot.to_string(), challenge])
+            }
+            result => result,
+        };
+        let output = output.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                tracing::warn!("Install ykchalresp (yubikey-personalization) or ykman to unlock YubiKey-protected keystores");
+            }
+            failed(&format!("could not run the YubiKey tools: {}", e))
+        })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            tracing::warn!("YubiKey did not answer: {}", stderr.trim());
+            return Err(failed(&format!("no response from YubiKey slot {}: {}", slot, stderr.trim())).into());
+        }
+
+        let stdout = Zeroizing::new(String::from_utf8_lossy(&output.stdout).into_owned());
+        let response = Zeroizing::new(hex::decode(stdout.trim()).map_err(|_| failed("YubiKey returned a malformed response"))?);
+        if response.len() != RESPONSE_LENGTH {
+            return Err(failed("YubiKey returned a malformed response").into());
+        }
+        Ok(response)
+    }
+}
+
+fn failed(details: &str) -> AuthenticationError {
+    AuthenticationError::SecondFactorFailed {
+        keystore: String::new(),
+        details: details.to_string(),
+    }
+}
+
+/// Fresh factor for `slot` with a random challenge
+pub fn new_factor(slot: u8) -> HardwareFactor {
+    let mut challenge = [0u8; CHALLENGE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut challenge);
+    HardwareFactor::YubiKey {
+        slot,
+        challenge: hex::encode(challenge),
+    }
+}
+
+/// Ask `responder` for the response of `slot` to a hex-encoded stored challenge
+pub fn respond(responder: &dyn ChallengeResponder, slot: u8, challenge: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+    let challenge = hex::decode(challenge).map_err(|_| failed("stored YubiKey challenge is not hex"))?;
+    responder.respond(slot, &challenge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Keystore, Network, Wallet};
+    use crate::services::crypto::{CryptoService, HardwareBinding, HardwareTokens, KdfSettings, SecureString};
+    use hmac::{Hmac, Mac};
+    use sha1::Sha1;
+    use std::sync::Arc;
+
+    /// Token emulation with a known HMAC-SHA1 secret
+    struct SoftwareToken(Vec<u8>);
+
+    impl ChallengeResponder for SoftwareToken {
+        fn respond(&self, _slot: u8, challenge: &[u8]) -> WalletResult<Zeroizing<Vec<u8>>> {
+            let mut mac = <Hmac<Sha1> as Mac>::new_from_slice(&self.0).unwrap();
+            mac.update(challenge);
+            Ok(Zeroizing::new(mac.finalize().into_bytes().to_vec()))
+        }
+    }
+
+    #[test]
+    fn test_keystore_needs_token() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let settings = KdfSettings { memory: 1024, iterations: 1, parallelism: 1 };
+        let password = &SecureString::from("Passw0rd!Str0ng#1");
+
+        let tokens = HardwareTokens { yubikey: Arc::new(SoftwareToken(vec![1; 20])), ..HardwareTokens::default() };
+        let binding = HardwareBinding::YubiKey { slot: DEFAULT_SLOT };
+        let keystore = CryptoService::encrypt_wallet_bound(&wallet, password, settings, binding, &tokens).unwrap();
+        let factor = keystore.crypto.hardware.clone().unwrap();
+        assert_eq!(HardwareBinding::of(&factor), binding);
+        assert!(keystore.to_json().unwrap().contains(r#""kind":"yubikey-hmac-sha1""#));
+        let reloaded = Keystore::from_json(&keystore.to_json().unwrap()).unwrap();
+        let restored = CryptoService::decrypt_wallet_bound(&reloaded, password, &tokens).unwrap();
+        assert_eq!(restored.address(), wallet.address());
+
+        // The right password with a different token is rejected like a wrong password
+        let other = HardwareTokens { yubikey: Arc::new(SoftwareToken(vec![2; 20])), ..HardwareTokens::default() };
+        let err = CryptoService::decrypt_wallet_bound(&keystore, password, &other).unwrap_err();
+        assert_eq!(err.code(), "AUTH_001");
+    }
+}
@@ -0,0 +1,218 @@
+//! # Address Cross-Check Service
+//!
+//! Compares derived addresses against an expected list exported by another
+//! wallet or tool, to validate migrations and derivation-scheme assumptions.
+
+use crate::errors::{UserInputError, WalletResult};
+use crate::models::EthereumAddress;
+
+/// An expected address at a derivation index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedAddress {
+    /// Derivation index
+    pub index: u32,
+    /// Expected Ethereum address
+    pub address: EthereumAddress,
+}
+
+/// Outcome of checking one index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Derived address equals the expected one
+    Match,
+    /// Derived address differs from the expected one
+    Mismatch,
+}
+
+/// Comparison result for one index
+#[derive(Debug, Clone)]
+pub struct IndexCheck {
+    /// Derivation index
+    pub index: u32,
+    /// Expected address
+    pub expected: EthereumAddress,
+    /// Derived address
+    pub actual: EthereumAddress,
+    /// Comparison outcome
+    pub status: CheckStatus,
+}
+
+/// Full comparison report
+#[derive(Debug, Clone, Default)]
+pub struct AddressCheckReport {
+    /// Per-index results, in file order
+    pub checks: Vec<IndexCheck>,
+}
+
+impl AddressCheckReport {
+    /// Number of matching indexes
+    pub fn matched(&self) -> usize {
+        self.checks.iter().filter(|c| c.status == CheckStatus::Match).count()
+    }
+
+    /// Indexes whose derived address differs
+    pub fn mismatched_indexes(&self) -> Vec<u32> {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Mismatch)
+            .map(|c| c.index)
+            .collect()
+    }
+
+    /// Check if every expected address matched
+    pub fn is_clean(&self) -> bool {
+        self.checks.iter().all(|c| c.status == CheckStatus::Match)
+    }
+}
+
+/// Address cross-check service
+pub struct AddressCheckService;
+
+impl AddressCheckService {
+    /// Parse an expected-address CSV.
+    ///
+    /// Each line is either `index,address` or a bare `address`; bare addresses
+    /// are numbered consecutively from `start_index`. Blank lines, `#` comments
+    /// and a leading header row are ignored.
+    pub fn parse_csv(content: &str, start_index: u32) -> WalletResult<Vec<ExpectedAddress>> {
+        let mut expected = Vec::new();
+        let mut next_index = start_index;
+
+        for (line_no, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+
+            // Header row such as "index,address"
+            let is_header = expected.is_empty()
+                && fields.iter().all(|f| {
+                    f.parse::<u32>().is_err() && crate::utils::validate_ethereum_address(f).is_err()
+                });
+            if is_header {
+                continue;
+            }
+
+            let invalid_row = || UserInputError::InvalidParameters {
+                parameter: format!("expect-file line {}", line_no + 1),
+                value: line.to_string(),
+                expected: "index,address or address".to_string(),
+            };
+            let (index, address) = match fields.as_slice() {
+                [address] => (next_index, *address),
+                [index, address] => (index.parse::<u32>().map_err(|_| invalid_row())?, *address),
+                _ => return Err(invalid_row().into()),
+            };
+
+            expected.push(ExpectedAddress { index, address: address.parse()? });
+            next_index = index.saturating_add(1);
+        }
+
+        if expected.is_empty() {
+            return Err(UserInputError::MissingParameter {
+                parameter: "expect-file".to_string(),
+                hint: "The file contains no addresses".to_string(),
+            }
+            .into());
+        }
+
+        Ok(expected)
+    }
+
+    /// Compare expected addresses against a derivation function
+    ///
+    /// Addresses compare by their bytes, so `0x`-prefixed, bare, checksummed
+    /// and lowercase forms of the same address all match.
+    pub fn compare<F>(expected: &[ExpectedAddress], mut derive: F) -> WalletResult<AddressCheckReport>
+    where
+        F: FnMut(u32) -> WalletResult<EthereumAddress>,
+    {
+        let mut report = AddressCheckReport::default();
+
+        for entry in expected {
+            let actual = derive(entry.index)?;
+            let status = if actual == entry.address {
+                CheckStatus::Match
+            } else {
+                CheckStatus::Mismatch
+            };
+
+            report.checks.push(IndexCheck {
+                index: entry.index,
+                expected: entry.address,
+                actual,
+                status,
+            });
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDR_0: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+    const ADDR_1: &str = "0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0";
+
+    #[test]
+    fn test_parse_indexed_csv_with_header() {
+        let csv = format!("index,address\n0,{}\n\n# comment\n5,{}\n", ADDR_0, ADDR_1);
+        let expected = AddressCheckService::parse_csv(&csv, 0).unwrap();
+
+        assert_eq!(expected.len(), 2);
+        assert_eq!(expected[1].index, 5);
+        assert_eq!(expected[1].address, ADDR_1);
+    }
+
+    #[test]
+    fn test_parse_bare_addresses() {
+        let csv = format!("address\n{}\n{}\n", ADDR_0, ADDR_1);
+        let expected = AddressCheckService::parse_csv(&csv, 10).unwrap();
+
+        assert_eq!(expected[0].index, 10);
+        assert_eq!(expected[1].index, 11);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_rows() {
+        assert!(AddressCheckService::parse_csv("0,0x1234", 0).is_err());
+        assert!(AddressCheckService::parse_csv("", 0).is_err());
+        assert!(AddressCheckService::parse_csv(&format!("0,{}\nx,{}", ADDR_0, ADDR_1), 0).is_err());
+    }
+
+    #[test]
+    fn test_compare_reports_mismatches() {
+        let expected = vec![
+            ExpectedAddress { index: 0, address: ADDR_0.parse().unwrap() },
+            ExpectedAddress { index: 1, address: ADDR_0.parse().unwrap() },
+        ];
+
+        let report = AddressCheckService::compare(&expected, |index| {
+            if index == 0 { ADDR_0 } else { ADDR_1 }.parse()
+        })
+        .unwrap();
+
+        assert_eq!(report.matched(), 1);
+        assert_eq!(report.mismatched_indexes(), vec![1]);
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_compare_bare_hex_rows() {
+        // Exports without the 0x prefix, in any case, still match
+        let csv = format!("0,{}\n1,{}\n", &ADDR_0[2..], ADDR_1[2..].to_lowercase());
+        let expected = AddressCheckService::parse_csv(&csv, 0).unwrap();
+
+        let report = AddressCheckService::compare(&expected, |index| {
+            if index == 0 { ADDR_0 } else { ADDR_1 }.parse()
+        })
+        .unwrap();
+
+        assert!(report.is_clean());
+        assert_eq!(report.checks[0].expected.to_string(), ADDR_0);
+    }
+}
@@ -0,0 +1,439 @@
+//! # Session Agent
+//!
+//! Background process that keeps decrypted wallets in memory so commands
+//! run shortly after one another do not ask for the password again, in
+//! the manner of `ssh-agent`. Wallets are added explicitly (`wallet agent
+//! add`), after being decrypted by the client with the usual password and
+//! lockout checks, and each one is forgotten when its time to live runs
+//! out, when the machine was suspended, or on `wallet agent lock`.
+//!
+//! Clients talk to the agent over a Unix socket in the wallet directory,
+//! one JSON request and one JSON response per line. The socket is only
+//! accessible to its owner, which is the only authentication: anyone who
+//! can open it can read the wallets it holds. Named pipes on Windows are
+//! not supported yet.
+
+use crate::errors::{NetworkError, UserInputError, WalletResult};
+use crate::models::Wallet;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Agent socket file name inside the wallet directory
+pub const SOCKET_FILE_NAME: &str = "agent.sock";
+
+/// Time a wallet stays unlocked when the config does not say otherwise
+pub const DEFAULT_TTL_SECS: u64 = 900;
+
+/// How often expiry and suspend are checked
+const TICK: Duration = Duration::from_secs(5);
+
+/// Wall-clock time passing beyond the monotonic clock that counts as a suspend
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Agent settings (`[agent]` in the config file)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Seconds an added wallet stays unlocked
+    pub ttl_secs: u64,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self { ttl_secs: DEFAULT_TTL_SECS }
+    }
+}
+
+/// A wallet held by the agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentEntry {
+    /// Keystore file the wallet was decrypted from
+    pub path: PathBuf,
+    /// Primary address
+    pub address: String,
+    /// When the agent forgets it
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Request sent to the agent
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum AgentRequest {
+    /// Hold a decrypted wallet
+    Add {
+        /// Keystore file
+        path: PathBuf,
+        /// Decrypted wallet
+        wallet: Box<Wallet>,
+        /// Seconds to hold it, instead of the agent's default
+        ttl_secs: Option<u64>,
+    },
+    /// Hand out a held wallet
+    Get {
+        /// Keystore file
+        path: PathBuf,
+    },
+    /// Describe the held wallets
+    List,
+    /// Forget every wallet
+    Lock,
+    /// Forget every wallet and exit
+    Stop,
+}
+
+/// Agent reply
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AgentResponse {
+    /// Why the request failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Wallet asked for with `get`, if held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet: Option<Box<Wallet>>,
+    /// Held wallets (`add` and `list`) or the ones forgotten (`lock`)
+    #[serde(default)]
+    pub entries: Vec<AgentEntry>,
+}
+
+/// Connection to a running agent
+#[derive(Debug, Clone)]
+pub struct AgentClient {
+    socket: PathBuf,
+}
+
+impl AgentClient {
+    /// Client for the agent of `wallet_dir`
+    pub fn new(wallet_dir: &Path) -> Self {
+        Self {
+            socket: Self::socket_path(wallet_dir),
+        }
+    }
+
+    /// Agent socket of `wallet_dir`
+    pub fn socket_path(wallet_dir: &Path) -> PathBuf {
+        wallet_dir.join(SOCKET_FILE_NAME)
+    }
+
+    /// Socket this client connects to
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Check if an agent answers on the socket
+    pub async fn is_running(&self) -> bool {
+        self.request(&AgentRequest::List).await.is_ok()
+    }
+
+    /// Hand a decrypted wallet to the agent
+    pub async fn add(&self, path: &Path, wallet: &Wallet, ttl: Option<Duration>) -> WalletResult<AgentEntry> {
+        let request = AgentRequest::Add {
+            path: Self::key(path),
+            wallet: Box::new(wallet.clone()),
+            ttl_secs: ttl.map(|ttl| ttl.as_secs()),
+        };
+        self.request(&request).await?.entries.pop().ok_or_else(|| self.error("agent did not confirm the wallet"))
+    }
+
+    /// Wallet held for a keystore; `None` when no agent runs or it does not hold one
+    pub async fn get(&self, path: &Path) -> Option<Wallet> {
+        let request = AgentRequest::Get { path: Self::key(path) };
+        let wallet = self.request(&request).await.ok()?.wallet?;
+        wallet.validate().ok()?;
+        Some(*wallet)
+    }
+
+    /// Wallets the agent holds
+    pub async fn list(&self) -> WalletResult<Vec<AgentEntry>> {
+        Ok(self.request(&AgentRequest::List).await?.entries)
+    }
+
+    /// Make the agent forget every wallet, returning the ones it held
+    pub async fn lock(&self) -> WalletResult<Vec<AgentEntry>> {
+        Ok(self.request(&AgentRequest::Lock).await?.entries)
+    }
+
+    /// Stop the agent
+    pub async fn stop(&self) -> WalletResult<()> {
+        self.request(&AgentRequest::Stop).await.map(|_| ())
+    }
+
+    /// Keystores are keyed by canonical path, so any spelling of one finds it
+    fn key(path: &Path) -> PathBuf {
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    async fn request(&self, request: &AgentRequest) -> WalletResult<AgentResponse> {
+        let line = zeroize::Zeroizing::new(serde_json::to_string(request)?);
+        let reply = transport::exchange(&self.socket, &line).await.map_err(|e| self.error(&e.to_string()))?;
+        let response: AgentResponse = serde_json::from_str(&reply)?;
+        match response.error {
+            Some(error) => Err(self.error(&error)),
+            None => Ok(response),
+        }
+    }
+
+    fn error(&self, details: &str) -> crate::WalletError {
+        NetworkError::ConnectivityFailure {
+            endpoint: self.socket.display().to_string(),
+            details: details.to_string(),
+        }
+        .into()
+    }
+}
+
+/// A wallet held by the server
+struct Held {
+    wallet: Wallet,
+    entry: AgentEntry,
+}
+
+/// Wallets held by a running agent
+pub struct AgentServer {
+    client: AgentClient,
+    ttl: Duration,
+    held: std::collections::BTreeMap<PathBuf, Held>,
+}
+
+impl AgentServer {
+    /// Agent for `wallet_dir` holding wallets for `ttl` unless told otherwise
+    pub fn new(wallet_dir: &Path, ttl: Duration) -> Self {
+        Self {
+            client: AgentClient::new(wallet_dir),
+            ttl,
+            held: std::collections::BTreeMap::new(),
+        }
+    }
+
+    /// Answer requests until told to stop
+    ///
+    /// Fails if another agent already answers on the socket; a socket left
+    /// behind by one that died is replaced.
+    #[tracing::instrument(level = "debug", skip_all, fields(socket = %self.client.socket.display()))]
+    pub async fn serve(mut self) -> WalletResult<()> {
+        if self.client.is_running().await {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "agent".to_string(),
+                value: self.client.socket.display().to_string(),
+                expected: "no agent already running for this wallet directory".to_string(),
+            }
+            .into());
+        }
+        let listener = transport::bind(&self.client.socket)?;
+        tracing::info!("Agent listening on {}", self.client.socket.display());
+
+        let mut ticker = tokio::time::interval(TICK);
+        let mut last_tick = (std::time::Instant::now(), std::time::SystemTime::now());
+        loop {
+            tokio::select! {
+                connection = listener.accept() => {
+                    if let Ok((stream, _)) = connection {
+                        if self.answer(stream).await {
+                            break;
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    let now = (std::time::Instant::now(), std::time::SystemTime::now());
+                    if Self::was_suspended(last_tick, now) && !self.held.is_empty() {
+                        tracing::info!("Suspend detected; locking {} wallet(s)", self.held.len());
+                        self.held.clear();
+                    }
+                    last_tick = now;
+                    self.expire(Utc::now());
+                }
+            }
+        }
+
+        self.held.clear();
+        let _ = std::fs::remove_file(&self.client.socket);
+        Ok(())
+    }
+
+    /// Check if the wall clock moved further than the monotonic clock, which stops while suspended
+    fn was_suspended(
+        (last_instant, last_wall): (std::time::Instant, std::time::SystemTime),
+        (now_instant, now_wall): (std::time::Instant, std::time::SystemTime),
+    ) -> bool {
+        let monotonic = now_instant.saturating_duration_since(last_instant);
+        let wall = now_wall.duration_since(last_wall).unwrap_or_default();
+        wall > monotonic + SUSPEND_THRESHOLD
+    }
+
+    /// Forget wallets whose time ran out
+    fn expire(&mut self, now: DateTime<Utc>) {
+        self.held.retain(|_, held| held.entry.expires_at > now);
+    }
+
+    /// Serve one connection; returns true when asked to stop
+    async fn answer(&mut self, stream: transport::Stream) -> bool {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut line = zeroize::Zeroizing::new(String::new());
+        let read = tokio::time::timeout(TICK, BufReader::new(reader).read_line(&mut line)).await;
+        if !matches!(read, Ok(Ok(n)) if n > 0) {
+            return false;
+        }
+
+        let (response, stop) = match serde_json::from_str::<AgentRequest>(&line) {
+            Ok(request) => {
+                let stop = matches!(request, AgentRequest::Stop);
+                (self.handle(request, Utc::now()), stop)
+            }
+            Err(e) => (AgentResponse { error: Some(format!("invalid request: {}", e)), ..AgentResponse::default() }, false),
+        };
+        if let Ok(reply) = serde_json::to_string(&response).map(zeroize::Zeroizing::new) {
+            let _ = writer.write_all(reply.as_bytes()).await;
+            let _ = writer.write_all(b"\n").await;
+            let _ = writer.shutdown().await;
+        }
+        stop
+    }
+
+    fn handle(&mut self, request: AgentRequest, now: DateTime<Utc>) -> AgentResponse {
+        self.expire(now);
+        match request {
+            AgentRequest::Add { path, wallet, ttl_secs } => {
+                let ttl = ttl_secs.map(Duration::from_secs).unwrap_or(self.ttl);
+                let expires_at = now + chrono::Duration::from_std(ttl).unwrap_or_else(|_| chrono::Duration::days(365));
+                let entry = AgentEntry { path: path.clone(), address: wallet.address().to_string(), expires_at };
+                self.held.insert(path, Held { wallet: *wallet, entry: entry.clone() });
+                AgentResponse { entries: vec![entry], ..AgentResponse::default() }
+            }
+            AgentRequest::Get { path } => AgentResponse {
+                wallet: self.held.get(&path).map(|held| Box::new(held.wallet.clone())),
+                ..AgentResponse::default()
+            },
+            AgentRequest::List => AgentResponse { entries: self.entries(), ..AgentResponse::default() },
+            AgentRequest::Lock | AgentRequest::Stop => {
+                let entries = self.entries();
+                self.held.clear();
+                AgentResponse { entries, ..AgentResponse::default() }
+            }
+        }
+    }
+
+    fn entries(&self) -> Vec<AgentEntry> {
+        self.held.values().map(|held| held.entry.clone()).collect()
+    }
+}
+
+#[cfg(unix)]
+mod transport {
+    use crate::errors::WalletResult;
+    use std::path::Path;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    pub(super) type Stream = tokio::net::UnixStream;
+
+    /// Send one request line and read the reply line
+    pub(super) async fn exchange(socket: &Path, request: &str) -> std::io::Result<zeroize::Zeroizing<String>> {
+        let stream = tokio::net::UnixStream::connect(socket).await?;
+        let (reader, mut writer) = tokio::io::split(stream);
+        writer.write_all(request.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+
+        let mut reply = zeroize::Zeroizing::new(String::new());
+        BufReader::new(reader).read_line(&mut reply).await?;
+        if reply.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "agent closed the connection"));
+        }
+        Ok(reply)
+    }
+
+    /// Listen on `socket`, replacing a stale one, readable only by the owner
+    pub(super) fn bind(socket: &Path) -> WalletResult<tokio::net::UnixListener> {
+        if let Some(dir) = socket.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        if socket.exists() {
+            std::fs::remove_file(socket)?;
+        }
+        let listener = tokio::net::UnixListener::bind(socket)?;
+        crate::security::permissions::restrict_to_owner(socket)?;
+        Ok(listener)
+    }
+}
+
+#[cfg(not(unix))]
+mod transport {
+    use crate::errors::{NetworkError, WalletResult};
+    use std::path::Path;
+
+    pub(super) type Stream = tokio::io::DuplexStream;
+
+    pub(super) struct Listener;
+
+    impl Listener {
+        pub(super) async fn accept(&self) -> std::io::Result<(Stream, ())> {
+            std::future::pending().await
+        }
+    }
+
+    pub(super) async fn exchange(_socket: &Path, _request: &str) -> std::io::Result<zeroize::Zeroizing<String>> {
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "the agent needs Unix sockets"))
+    }
+
+    pub(super) fn bind(socket: &Path) -> WalletResult<Listener> {
+        Err(NetworkError::UnsupportedProtocol {
+            protocol: format!("agent socket {}", socket.display()),
+            supported: vec!["Unix domain sockets".to_string()],
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_agent_holds_wallets_until_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let keystore = dir.path().join("wallet.json");
+        std::fs::write(&keystore, "{}").unwrap();
+
+        let client = AgentClient::new(dir.path());
+        assert!(client.get(&keystore).await.is_none());
+        let server = tokio::spawn(AgentServer::new(dir.path(), Duration::from_secs(60)).serve());
+        while !client.is_running().await {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let entry = client.add(&keystore, &wallet, None).await.unwrap();
+        assert_eq!(*wallet.address(), entry.address);
+        let relative = dir.path().join(".").join("wallet.json");
+        assert_eq!(client.get(&relative).await.unwrap().address(), wallet.address());
+
+        // Expired entries are dropped on the next request
+        client.add(&keystore, &wallet, Some(Duration::ZERO)).await.unwrap();
+        assert!(client.list().await.unwrap().is_empty());
+
+        client.add(&keystore, &wallet, None).await.unwrap();
+        assert_eq!(client.lock().await.unwrap().len(), 1);
+        assert!(client.get(&keystore).await.is_none());
+
+        client.stop().await.unwrap();
+        server.await.unwrap().unwrap();
+        assert!(!AgentClient::socket_path(dir.path()).exists());
+    }
+
+    #[test]
+    fn test_suspend_detection() {
+        let instant = std::time::Instant::now();
+        let wall = std::time::SystemTime::now();
+        let after = |secs| (instant + TICK, wall + Duration::from_secs(secs));
+        assert!(!AgentServer::was_suspended((instant, wall), after(5)));
+        assert!(AgentServer::was_suspended((instant, wall), after(600)));
+    }
+}
@@ -0,0 +1,590 @@
+//! # Air-Gapped Signing
+//!
+//! The Ethereum UR registry types of ERC-4527, as used by Keystone and
+//! AirGap Vault. The online ("hot") side wraps an unsigned transaction or
+//! message in an `eth-sign-request` and shows it as an animated QR code;
+//! the offline ("cold") side decodes it, signs with the key at the
+//! requested path and answers with an `eth-signature`, which the hot side
+//! turns back into a broadcastable transaction. Either side can be this
+//! CLI. The cold side only signs the exact bytes it decoded: transactions
+//! that do not re-encode to the same bytes are refused, so what is shown
+//! for confirmation is what gets signed.
+
+use crate::errors::{CryptographicError, UserInputError, ValidationError, WalletResult};
+use crate::models::{ChildNumber, DerivationPath, EthereumAddress, Wallet};
+use crate::services::ur::Cbor;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Signature, H160, H256, U256};
+use ethers::utils::rlp;
+
+/// UR type of a signing request
+pub const SIGN_REQUEST_UR_TYPE: &str = "eth-sign-request";
+
+/// UR type of a signing response
+pub const SIGNATURE_UR_TYPE: &str = "eth-signature";
+
+/// CBOR tag of a UUID
+const UUID_TAG: u64 = 37;
+
+/// CBOR tag of a `crypto-keypath`
+const KEYPATH_TAG: u64 = 304;
+
+/// What the sign data of a request is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignDataType {
+    /// RLP of a legacy transaction (EIP-155 fields included when it has a chain id)
+    Transaction,
+    /// EIP-712 typed data as JSON
+    TypedData,
+    /// Raw bytes of an EIP-191 personal message
+    PersonalMessage,
+    /// EIP-2718 typed transaction (`type || rlp`)
+    TypedTransaction,
+}
+
+impl SignDataType {
+    fn code(self) -> u64 {
+        match self {
+            Self::Transaction => 1,
+            Self::TypedData => 2,
+            Self::PersonalMessage => 3,
+            Self::TypedTransaction => 4,
+        }
+    }
+
+    fn from_code(code: u64) -> Option<Self> {
+        match code {
+            1 => Some(Self::Transaction),
+            2 => Some(Self::TypedData),
+            3 => Some(Self::PersonalMessage),
+            4 => Some(Self::TypedTransaction),
+            _ => None,
+        }
+    }
+
+    /// Short name for display
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Transaction => "legacy transaction",
+            Self::TypedData => "typed data",
+            Self::PersonalMessage => "personal message",
+            Self::TypedTransaction => "typed transaction",
+        }
+    }
+}
+
+/// `eth-sign-request`: what the cold side is asked to sign
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthSignRequest {
+    /// Random id the response must echo
+    pub request_id: [u8; 16],
+    /// Bytes to sign, interpreted per `data_type`
+    pub sign_data: Vec<u8>,
+    /// Kind of `sign_data`
+    pub data_type: SignDataType,
+    /// Chain the transaction is for
+    pub chain_id: Option<u64>,
+    /// Path of the signing key
+    pub derivation_path: DerivationPath,
+    /// BIP32 fingerprint of the master key the path starts at
+    pub source_fingerprint: Option<u32>,
+    /// Address the signing key must have
+    pub address: Option<H160>,
+    /// Name of the requesting application
+    pub origin: Option<String>,
+}
+
+impl EthSignRequest {
+    /// CBOR encoding with the registry's integer map keys
+    pub fn to_cbor(&self) -> Cbor {
+        let components = self
+            .derivation_path
+            .components()
+            .iter()
+            .flat_map(|child| [Cbor::Unsigned(u64::from(child.index())), Cbor::Bool(child.is_hardened())])
+            .collect();
+        let mut keypath = vec![(Cbor::Unsigned(1), Cbor::Array(components))];
+        if let Some(fingerprint) = self.source_fingerprint {
+            keypath.push((Cbor::Unsigned(2), Cbor::Unsigned(u64::from(fingerprint))));
+        }
+
+        let mut entries = vec![
+            (Cbor::Unsigned(1), Cbor::Tag(UUID_TAG, Box::new(Cbor::Bytes(self.request_id.to_vec())))),
+            (Cbor::Unsigned(2), Cbor::Bytes(self.sign_data.clone())),
+            (Cbor::Unsigned(3), Cbor::Unsigned(self.data_type.code())),
+        ];
+        if let Some(chain_id) = self.chain_id {
+            entries.push((Cbor::Unsigned(4), Cbor::Unsigned(chain_id)));
+        }
+        entries.push((Cbor::Unsigned(5), Cbor::Tag(KEYPATH_TAG, Box::new(Cbor::Map(keypath)))));
+        if let Some(address) = self.address {
+            entries.push((Cbor::Unsigned(6), Cbor::Bytes(address.as_bytes().to_vec())));
+        }
+        if let Some(origin) = &self.origin {
+            entries.push((Cbor::Unsigned(7), Cbor::Text(origin.clone())));
+        }
+        Cbor::Map(entries)
+    }
+
+    /// Parse the CBOR of an `eth-sign-request`
+    pub fn from_cbor(item: &Cbor) -> WalletResult<Self> {
+        let request_id = uuid_field(item)?.ok_or_else(|| malformed(SIGN_REQUEST_UR_TYPE, "missing request id"))?;
+        let sign_data = item
+            .get(2)
+            .and_then(Cbor::as_bytes)
+            .ok_or_else(|| malformed(SIGN_REQUEST_UR_TYPE, "missing sign data"))?
+            .to_vec();
+        let data_type = item
+            .get(3)
+            .and_then(Cbor::as_u64)
+            .and_then(SignDataType::from_code)
+            .ok_or_else(|| malformed(SIGN_REQUEST_UR_TYPE, "unknown data type"))?;
+        let chain_id = match item.get(4) {
+            Some(value) => Some(value.as_u64().ok_or_else(|| malformed(SIGN_REQUEST_UR_TYPE, "chain id"))?),
+            None => None,
+        };
+
+        let keypath = item
+            .get(5)
+            .map(|keypath| keypath.untag(KEYPATH_TAG))
+            .ok_or_else(|| malformed(SIGN_REQUEST_UR_TYPE, "missing derivation path"))?;
+        let components = match keypath.get(1) {
+            Some(Cbor::Array(components)) if components.len() % 2 == 0 => components,
+            _ => return Err(malformed(SIGN_REQUEST_UR_TYPE, "derivation path components").into()),
+        };
+        let mut derivation_path = DerivationPath::root();
+        for pair in components.chunks(2) {
+            let child = match (&pair[0], &pair[1]) {
+                (Cbor::Unsigned(index), Cbor::Bool(true)) if *index < 1 << 31 => ChildNumber::hardened(*index as u32)?,
+                (Cbor::Unsigned(index), Cbor::Bool(false)) if *index < 1 << 31 => ChildNumber::normal(*index as u32)?,
+                _ => return Err(malformed(SIGN_REQUEST_UR_TYPE, "wildcard or out-of-range path component").into()),
+            };
+            derivation_path = derivation_path.join(child);
+        }
+        let source_fingerprint = match keypath.get(2) {
+            Some(value) => Some(
+                value
+                    .as_u64()
+                    .and_then(|fingerprint| u32::try_from(fingerprint).ok())
+                    .ok_or_else(|| malformed(SIGN_REQUEST_UR_TYPE, "source fingerprint"))?,
+            ),
+            None => None,
+        };
+
+        let address = match item.get(6) {
+            Some(Cbor::Bytes(bytes)) if bytes.len() == 20 => Some(H160::from_slice(bytes)),
+            Some(_) => return Err(malformed(SIGN_REQUEST_UR_TYPE, "address").into()),
+            None => None,
+        };
+        let origin = match item.get(7) {
+            Some(Cbor::Text(origin)) => Some(origin.clone()),
+            _ => None,
+        };
+
+        Ok(Self {
+            request_id,
+            sign_data,
+            data_type,
+            chain_id,
+            derivation_path,
+            source_fingerprint,
+            address,
+            origin,
+        })
+    }
+
+    /// The transaction inside a transaction request, checked to re-encode to the same bytes
+    pub fn transaction(&self) -> WalletResult<TypedTransaction> {
+        if !matches!(self.data_type, SignDataType::Transaction | SignDataType::TypedTransaction) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "data type".to_string(),
+                value: self.data_type.name().to_string(),
+                expected: "a transaction".to_string(),
+            }
+            .into());
+        }
+
+        let tx: TypedTransaction = rlp::decode(&self.sign_data)
+            .map_err(|e| malformed("transaction", &e.to_string()))?;
+        let is_typed = !matches!(tx, TypedTransaction::Legacy(_));
+        if is_typed != (self.data_type == SignDataType::TypedTransaction) || tx.rlp().as_ref() != self.sign_data {
+            return Err(malformed("transaction", "not a canonical encoding of its data type").into());
+        }
+        // A transaction without a chain id could be replayed on the chain the request names and any other
+        let tx_chain_id = tx.chain_id().map(|chain_id| chain_id.as_u64());
+        if self.chain_id.is_some() && self.chain_id != tx_chain_id {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: SIGN_REQUEST_UR_TYPE.to_string(),
+                details: format!(
+                    "request is for chain {} but the transaction for {}",
+                    self.chain_id.unwrap_or_default(),
+                    tx_chain_id.map_or("no chain".to_string(), |id| format!("chain {}", id))
+                ),
+            }
+            .into());
+        }
+        Ok(tx)
+    }
+
+    /// The EIP-712 document of a typed-data request
+    pub fn typed_data(&self) -> WalletResult<TypedData> {
+        serde_json::from_slice(&self.sign_data).map_err(|e| malformed("typed data", &e.to_string()).into())
+    }
+
+    /// Digest the signature is over
+    pub fn signing_hash(&self) -> WalletResult<[u8; 32]> {
+        match self.data_type {
+            SignDataType::Transaction | SignDataType::TypedTransaction => {
+                self.transaction()?;
+                Ok(ethers::utils::keccak256(&self.sign_data))
+            }
+            SignDataType::TypedData => self.typed_data()?.encode_eip712().map_err(|e| {
+                malformed("typed data", &e.to_string()).into()
+            }),
+            SignDataType::PersonalMessage => Ok(ethers::utils::hash_message(&self.sign_data).0),
+        }
+    }
+
+    /// Turn a recovery id into the `v` this data type carries
+    fn v_for(&self, recovery_id: u64) -> WalletResult<u64> {
+        match self.data_type {
+            SignDataType::TypedTransaction => Ok(recovery_id),
+            SignDataType::Transaction => Ok(match self.transaction()?.chain_id() {
+                Some(chain_id) => chain_id.as_u64() * 2 + 35 + recovery_id,
+                None => 27 + recovery_id,
+            }),
+            SignDataType::TypedData | SignDataType::PersonalMessage => Ok(27 + recovery_id),
+        }
+    }
+}
+
+/// `eth-signature`: the cold side's answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthSignature {
+    /// Id of the request this answers
+    pub request_id: Option<[u8; 16]>,
+    /// Signature
+    pub signature: Signature,
+    /// Name of the signing device
+    pub origin: Option<String>,
+}
+
+impl EthSignature {
+    /// CBOR encoding; the signature is `r || s || v` with `v` in as few bytes as it needs
+    pub fn to_cbor(&self) -> Cbor {
+        let mut bytes = [0u8; 64].to_vec();
+        self.signature.r.to_big_endian(&mut bytes[..32]);
+        self.signature.s.to_big_endian(&mut bytes[32..]);
+        let v = self.signature.v.to_be_bytes();
+        let skip = v.iter().take(7).take_while(|&&byte| byte == 0).count();
+        bytes.extend_from_slice(&v[skip..]);
+
+        let mut entries = Vec::new();
+        if let Some(request_id) = self.request_id {
+            entries.push((Cbor::Unsigned(1), Cbor::Tag(UUID_TAG, Box::new(Cbor::Bytes(request_id.to_vec())))));
+        }
+        entries.push((Cbor::Unsigned(2), Cbor::Bytes(bytes)));
+        if let Some(origin) = &self.origin {
+            entries.push((Cbor::Unsigned(3), Cbor::Text(origin.clone())));
+        }
+        Cbor::Map(entries)
+    }
+
+    /// Parse the CBOR of an `eth-signature`
+    pub fn from_cbor(item: &Cbor) -> WalletResult<Self> {
+        let bytes = item
+            .get(2)
+            .and_then(Cbor::as_bytes)
+            .filter(|bytes| (65..=72).contains(&bytes.len()))
+            .ok_or_else(|| malformed(SIGNATURE_UR_TYPE, "signature must be r || s || v"))?;
+        let v = bytes[64..].iter().fold(0u64, |v, &byte| v << 8 | u64::from(byte));
+
+        Ok(Self {
+            request_id: uuid_field(item)?,
+            signature: Signature {
+                r: U256::from_big_endian(&bytes[..32]),
+                s: U256::from_big_endian(&bytes[32..64]),
+                v,
+            },
+            origin: match item.get(3) {
+                Some(Cbor::Text(origin)) => Some(origin.clone()),
+                _ => None,
+            },
+        })
+    }
+}
+
+/// Builds, signs and completes air-gapped signing requests
+pub struct AirgapService;
+
+impl AirgapService {
+    /// Request to sign an unsigned transaction (`rlp` or `type || rlp`), taking the chain id from the transaction
+    pub fn transaction_request(
+        unsigned: &[u8],
+        chain_id: Option<u64>,
+        derivation_path: DerivationPath,
+        address: Option<H160>,
+        source_fingerprint: Option<u32>,
+    ) -> WalletResult<EthSignRequest> {
+        let data_type = match unsigned.first() {
+            Some(&byte) if byte >= 0xc0 => SignDataType::Transaction,
+            _ => SignDataType::TypedTransaction,
+        };
+        let mut request = EthSignRequest {
+            request_id: Self::new_request_id(),
+            sign_data: unsigned.to_vec(),
+            data_type,
+            chain_id,
+            derivation_path,
+            source_fingerprint,
+            address,
+            origin: Some(Self::origin()),
+        };
+        let tx = request.transaction()?;
+        if request.chain_id.is_none() {
+            request.chain_id = tx.chain_id().map(|chain_id| chain_id.as_u64());
+        }
+        Ok(request)
+    }
+
+    /// Request to sign a personal message or EIP-712 typed data
+    pub fn message_request(
+        data: &[u8],
+        data_type: SignDataType,
+        derivation_path: DerivationPath,
+        address: Option<H160>,
+        source_fingerprint: Option<u32>,
+    ) -> WalletResult<EthSignRequest> {
+        let request = EthSignRequest {
+            request_id: Self::new_request_id(),
+            sign_data: data.to_vec(),
+            data_type,
+            chain_id: None,
+            derivation_path,
+            source_fingerprint,
+            address,
+            origin: Some(Self::origin()),
+        };
+        if matches!(data_type, SignDataType::Transaction | SignDataType::TypedTransaction) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "data type".to_string(),
+                value: data_type.name().to_string(),
+                expected: "a personal message or typed data".to_string(),
+            }
+            .into());
+        }
+        request.signing_hash()?;
+        Ok(request)
+    }
+
+    /// Sign a request with the key of an HD wallet (the cold side)
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %request.derivation_path))]
+    pub fn sign(wallet: &Wallet, request: &EthSignRequest) -> WalletResult<EthSignature> {
+        if let Some(expected) = request.source_fingerprint {
+            let fingerprint = wallet.fingerprint()?;
+            if fingerprint != format!("{:08x}", expected) {
+                return Err(ValidationError::IntegrityCheckFailed {
+                    data_type: SIGN_REQUEST_UR_TYPE.to_string(),
+                    details: format!("request is for master key {:08x}, this wallet is {}", expected, fingerprint),
+                }
+                .into());
+            }
+        }
+
+        let key = wallet.signing_key_at(&request.derivation_path)?;
+        let signer = ethers::signers::Signer::address(&key);
+        if let Some(address) = request.address {
+            if address != signer {
+                return Err(ValidationError::IntegrityCheckFailed {
+                    data_type: SIGN_REQUEST_UR_TYPE.to_string(),
+                    details: format!(
+                        "{} derives {}, but the request is for {}",
+                        request.derivation_path,
+                        EthereumAddress::from(signer),
+                        EthereumAddress::from(address)
+                    ),
+                }
+                .into());
+            }
+        }
+
+        let hash = request.signing_hash()?;
+        let mut signature = key.sign_hash(H256::from(hash)).map_err(|e| CryptographicError::SignatureFailed {
+            details: e.to_string(),
+        })?;
+        signature.v = request.v_for(signature.v - 27)?;
+
+        Ok(EthSignature {
+            request_id: Some(request.request_id),
+            signature,
+            origin: Some(Self::origin()),
+        })
+    }
+
+    /// Check a response against its request and return the signer's address
+    pub fn verify(request: &EthSignRequest, response: &EthSignature) -> WalletResult<H160> {
+        if response.request_id.is_some_and(|id| id != request.request_id) {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: SIGNATURE_UR_TYPE.to_string(),
+                details: "signature answers a different request".to_string(),
+            }
+            .into());
+        }
+
+        let hash = request.signing_hash()?;
+        let signer = response
+            .signature
+            .recover(H256::from(hash))
+            .map_err(|e| CryptographicError::SignatureFailed { details: e.to_string() })?;
+        if request.address.is_some_and(|address| address != signer) {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: SIGNATURE_UR_TYPE.to_string(),
+                details: format!("signed by {} instead of the requested address", EthereumAddress::from(signer)),
+            }
+            .into());
+        }
+        Ok(signer)
+    }
+
+    /// Raw signed transaction for a transaction request and its verified response
+    pub fn signed_transaction(request: &EthSignRequest, response: &EthSignature) -> WalletResult<Vec<u8>> {
+        Self::verify(request, response)?;
+        let tx = request.transaction()?;
+        let mut signature = response.signature;
+        if request.data_type == SignDataType::Transaction {
+            // Devices may answer with the bare recovery id; legacy encoding needs the EIP-155 `v`
+            signature.v = request.v_for(signature.v.checked_sub(27).unwrap_or(signature.v) % 2)?;
+        }
+        Ok(tx.rlp_signed(&signature).to_vec())
+    }
+
+    fn new_request_id() -> [u8; 16] {
+        let mut id = [0u8; 16];
+        hex::decode_to_slice(crate::utils::random_uuid().replace('-', ""), &mut id).expect("UUID is 32 hex digits");
+        id
+    }
+
+    fn origin() -> String {
+        format!("web3wallet-cli {}", env!("CARGO_PKG_VERSION"))
+    }
+}
+
+/// Optional request id under map key 1
+fn uuid_field(item: &Cbor) -> WalletResult<Option<[u8; 16]>> {
+    match item.get(1).map(|id| id.untag(UUID_TAG).as_bytes()) {
+        Some(Some(bytes)) => Ok(Some(
+            bytes.try_into().map_err(|_| malformed("request id", "must be a 16-byte UUID"))?,
+        )),
+        Some(None) => Err(malformed("request id", "must be a 16-byte UUID").into()),
+        None => Ok(None),
+    }
+}
+
+fn malformed(data_type: &str, details: &str) -> ValidationError {
+    ValidationError::IntegrityCheckFailed {
+        data_type: data_type.to_string(),
+        details: details.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+    use crate::services::ur::{UrDecoder, UrEncoder};
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    fn wallet() -> Wallet {
+        Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Mainnet,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn through_ur(ur_type: &str, item: &Cbor) -> Cbor {
+        let mut encoder = UrEncoder::new(ur_type, item.to_bytes(), 40).unwrap();
+        let mut decoder = UrDecoder::new();
+        while !decoder.receive(&encoder.next_part()).unwrap() {}
+        let (decoded_type, payload) = decoder.result().unwrap();
+        assert_eq!(decoded_type, ur_type);
+        Cbor::from_bytes(payload).unwrap()
+    }
+
+    #[test]
+    fn test_transaction_round_trip() {
+        let wallet = wallet();
+        let path = wallet.account_path(0).unwrap();
+        let address = H160::from(*wallet.address());
+        let fingerprint = u32::from_str_radix(&wallet.fingerprint().unwrap(), 16).unwrap();
+        let to: H160 = "0x000000000000000000000000000000000000dEaD".parse().unwrap();
+
+        let legacy: TypedTransaction = TransactionRequest::new()
+            .nonce(3)
+            .gas_price(20_000_000_000u64)
+            .gas(21_000)
+            .to(to)
+            .value(1_000u64)
+            .chain_id(1)
+            .into();
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+            .nonce(4)
+            .max_fee_per_gas(30_000_000_000u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+            .gas(21_000)
+            .to(to)
+            .value(2_000u64)
+            .chain_id(11_155_111)
+            .into();
+
+        for tx in [legacy, eip1559] {
+            let request =
+                AirgapService::transaction_request(&tx.rlp(), None, path.clone(), Some(address), Some(fingerprint)).unwrap();
+            assert_eq!(request.chain_id, tx.chain_id().map(|id| id.as_u64()));
+
+            // Hot side -> cold side
+            let received = EthSignRequest::from_cbor(&through_ur(SIGN_REQUEST_UR_TYPE, &request.to_cbor())).unwrap();
+            assert_eq!(received, request);
+            let response = AirgapService::sign(&wallet, &received).unwrap();
+
+            // Cold side -> hot side
+            let response = EthSignature::from_cbor(&through_ur(SIGNATURE_UR_TYPE, &response.to_cbor())).unwrap();
+            let raw = AirgapService::signed_transaction(&request, &response).unwrap();
+
+            let (decoded, signature) = TypedTransaction::decode_signed(&rlp::Rlp::new(&raw)).unwrap();
+            assert_eq!(decoded.sighash(), tx.sighash());
+            assert_eq!(signature.recover(tx.sighash()).unwrap(), address);
+        }
+    }
+
+    #[test]
+    fn test_message_and_mismatches() {
+        let wallet = wallet();
+        let path = wallet.account_path(0).unwrap();
+        let address = H160::from(*wallet.address());
+
+        let request =
+            AirgapService::message_request(b"hello", SignDataType::PersonalMessage, path.clone(), Some(address), None)
+                .unwrap();
+        let response = AirgapService::sign(&wallet, &request).unwrap();
+        assert!(matches!(response.signature.v, 27 | 28));
+        assert_eq!(AirgapService::verify(&request, &response).unwrap(), address);
+
+        // Answers to another request are rejected
+        let other = AirgapService::message_request(b"hello", SignDataType::PersonalMessage, path.clone(), None, None).unwrap();
+        assert!(AirgapService::verify(&other, &response).is_err());
+
+        // Wrong master key or wrong address at the path
+        let mut wrong = request.clone();
+        wrong.source_fingerprint = Some(0xdeadbeef);
+        assert!(AirgapService::sign(&wallet, &wrong).is_err());
+        let mut wrong = request;
+        wrong.derivation_path = wallet.account_path(1).unwrap();
+        assert!(AirgapService::sign(&wallet, &wrong).is_err());
+
+        // Sign data that does not decode as claimed
+        assert!(AirgapService::transaction_request(b"\x02\xc0", None, path, None, None).is_err());
+    }
+}
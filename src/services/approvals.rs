@@ -0,0 +1,354 @@
+//! # Token Approvals
+//!
+//! Lists the ERC-20 allowances and ERC-721 approvals an address has granted,
+//! and builds the transactions that revoke them. Candidates come from the
+//! owner's `Approval` and `ApprovalForAll` logs; each one is then re-read
+//! from the token with `allowance`, `getApproved` or `isApprovedForAll`,
+//! because transfers spend and clear approvals without always logging it.
+//!
+//! Revokes are unsigned EIP-1559 transactions, to be signed offline with
+//! `wallet airgap request --tx`.
+
+use crate::errors::{NetworkError, WalletResult};
+use crate::models::{AddressBook, EthereumAddress};
+use ethers::abi::{self, Token};
+use ethers::providers::{Middleware, MiddlewareError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, Bytes, Eip1559TransactionRequest, Filter, Log, TransactionRequest, H256, U256};
+use ethers::utils::keccak256;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// ERC-20 and ERC-721 `Approval` event (ERC-721 indexes the token id)
+pub const APPROVAL_EVENT: &str = "Approval(address,address,uint256)";
+
+/// ERC-721 and ERC-1155 `ApprovalForAll` event
+pub const APPROVAL_FOR_ALL_EVENT: &str = "ApprovalForAll(address,address,bool)";
+
+const APPROVE: &str = "approve(address,uint256)";
+const SET_APPROVAL_FOR_ALL: &str = "setApprovalForAll(address,bool)";
+const ALLOWANCE: &str = "allowance(address,address)";
+const GET_APPROVED: &str = "getApproved(uint256)";
+const IS_APPROVED_FOR_ALL: &str = "isApprovedForAll(address,address)";
+
+/// Spenders named when the address book has no label for them (same address on every chain)
+pub const KNOWN_SPENDERS: &[(&str, &str)] = &[
+    ("0x000000000022D473030F116dDEE9F6B43aC78BA3", "Uniswap Permit2"),
+    ("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D", "Uniswap V2 Router"),
+    ("0xE592427A0AEce92De3Edee1F18E0157C05861564", "Uniswap V3 Router"),
+    ("0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45", "Uniswap SwapRouter02"),
+    ("0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD", "Uniswap Universal Router"),
+    ("0x1111111254EEB25477B68fb85Ed929f73A960582", "1inch Router v5"),
+    ("0x111111125421cA6dc452d289314280a0f8842A65", "1inch Router v6"),
+    ("0xDef1C0ded9bec7F1a1670819833240f027b25EfF", "0x Exchange Proxy"),
+    ("0x00000000000000ADc04C56Bf30aC9d3c0aAF14dC", "OpenSea Seaport 1.5"),
+    ("0x1E0049783F008A0085193E00003D00cd54003c71", "OpenSea Conduit"),
+];
+
+/// What an approval lets the spender take
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApprovalKind {
+    /// Up to an ERC-20 allowance
+    Erc20,
+    /// One ERC-721 token
+    Erc721,
+    /// Every ERC-721 or ERC-1155 token of the collection (`setApprovalForAll`)
+    Operator,
+}
+
+impl std::fmt::Display for ApprovalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Erc20 => "ERC-20",
+            Self::Erc721 => "ERC-721",
+            Self::Operator => "operator",
+        })
+    }
+}
+
+/// One approval the owner has granted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Approval {
+    /// Token contract
+    pub token: EthereumAddress,
+    /// Kind of approval
+    pub kind: ApprovalKind,
+    /// Address allowed to move the tokens
+    pub spender: EthereumAddress,
+    /// Name of the spender, from the address book or [`KNOWN_SPENDERS`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spender_label: Option<String>,
+    /// Remaining ERC-20 allowance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowance: Option<U256>,
+    /// Approved ERC-721 token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<U256>,
+    /// Block of the latest approval event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    /// Whether the token confirmed the approval, rather than it being taken from logs alone
+    pub confirmed: bool,
+}
+
+impl Approval {
+    /// Whether an ERC-20 allowance is the maximum `uint256`, as dapps usually request
+    pub fn is_unlimited(&self) -> bool {
+        self.allowance == Some(U256::MAX)
+    }
+
+    /// Calldata that revokes this approval on [`Approval::token`]
+    pub fn revoke_calldata(&self) -> Bytes {
+        let (signature, tokens) = match self.kind {
+            ApprovalKind::Erc20 => (APPROVE, vec![Token::Address(self.spender.into()), Token::Uint(U256::zero())]),
+            ApprovalKind::Erc721 => (
+                APPROVE,
+                vec![Token::Address(Address::zero()), Token::Uint(self.token_id.unwrap_or_default())],
+            ),
+            ApprovalKind::Operator => (SET_APPROVAL_FOR_ALL, vec![Token::Address(self.spender.into()), Token::Bool(false)]),
+        };
+        let mut calldata = keccak256(signature)[..4].to_vec();
+        calldata.extend(abi::encode(&tokens));
+        calldata.into()
+    }
+
+    /// Unsigned transaction from `owner` that revokes this approval; nonce, gas and fees are left to fill
+    pub fn revoke_transaction(&self, owner: EthereumAddress, chain_id: u64) -> Eip1559TransactionRequest {
+        Eip1559TransactionRequest::new()
+            .from(Address::from(owner))
+            .to(Address::from(self.token))
+            .value(0)
+            .data(self.revoke_calldata())
+            .chain_id(chain_id)
+    }
+
+    /// Key of the approval slot a log writes; later logs for the same slot replace earlier ones
+    fn slot(&self) -> (EthereumAddress, ApprovalKind, H256) {
+        let subject = match self.token_id {
+            Some(token_id) => H256::from(<[u8; 32]>::from(token_id)),
+            None => H256::from(Address::from(self.spender)),
+        };
+        (self.token, self.kind, subject)
+    }
+}
+
+/// Review and revoke token approvals
+pub struct ApprovalService;
+
+impl ApprovalService {
+    /// Approvals still in force after `logs`, oldest first; logs must be in chain order
+    ///
+    /// Logs that are not approval events of `owner` are skipped, as are
+    /// approvals later set to zero, to nobody or to `false`.
+    pub fn from_logs(owner: EthereumAddress, logs: &[Log]) -> Vec<Approval> {
+        let approval = H256::from(keccak256(APPROVAL_EVENT));
+        let approval_for_all = H256::from(keccak256(APPROVAL_FOR_ALL_EVENT));
+        let topic_address = |topic: &H256| EthereumAddress::from(Address::from(*topic));
+
+        let mut slots = BTreeMap::new();
+        for log in logs {
+            if log.topics.len() < 3 || topic_address(&log.topics[1]) != owner {
+                continue;
+            }
+            let spender = topic_address(&log.topics[2]);
+            let (kind, allowance, token_id, active) = match (log.topics[0], log.topics.len()) {
+                (topic, 3) if topic == approval && log.data.len() == 32 => {
+                    let amount = U256::from_big_endian(&log.data);
+                    (ApprovalKind::Erc20, Some(amount), None, !amount.is_zero())
+                }
+                (topic, 4) if topic == approval => {
+                    let token_id = U256::from_big_endian(log.topics[3].as_bytes());
+                    (ApprovalKind::Erc721, None, Some(token_id), spender != EthereumAddress::default())
+                }
+                (topic, 3) if topic == approval_for_all && log.data.len() == 32 => {
+                    (ApprovalKind::Operator, None, None, log.data[31] != 0)
+                }
+                _ => continue,
+            };
+
+            let entry = Approval {
+                token: log.address.into(),
+                kind,
+                spender,
+                spender_label: None,
+                allowance,
+                token_id,
+                block_number: log.block_number.map(|number| number.as_u64()),
+                confirmed: false,
+            };
+            slots.remove(&entry.slot());
+            if active {
+                slots.insert(entry.slot(), entry);
+            }
+        }
+
+        let mut approvals: Vec<Approval> = slots.into_values().collect();
+        approvals.sort_by_key(|approval| approval.block_number);
+        approvals
+    }
+
+    /// Name spenders after their address book label, or else a [`KNOWN_SPENDERS`] entry
+    pub fn label_spenders(approvals: &mut [Approval], book: &AddressBook) {
+        for approval in approvals {
+            let spender = approval.spender.to_checksum();
+            approval.spender_label = book
+                .find(&spender)
+                .and_then(|entry| entry.label())
+                .or_else(|| {
+                    KNOWN_SPENDERS
+                        .iter()
+                        .find(|(address, _)| address.eq_ignore_ascii_case(&spender))
+                        .map(|(_, name)| *name)
+                })
+                .map(str::to_string);
+        }
+    }
+
+    /// Approvals `owner` has granted between `from_block` and `to_block` (default: latest) that are still in force
+    ///
+    /// Each candidate from the logs is checked against the token. Tokens
+    /// that revert on the check keep the value from their logs, unconfirmed.
+    pub async fn fetch<M: Middleware>(
+        provider: &M,
+        endpoint: &str,
+        owner: EthereumAddress,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> WalletResult<Vec<Approval>> {
+        let connectivity = |e: M::Error| NetworkError::ConnectivityFailure {
+            endpoint: endpoint.to_string(),
+            details: e.to_string(),
+        };
+        let filter = Filter::new()
+            .topic0(vec![H256::from(keccak256(APPROVAL_EVENT)), H256::from(keccak256(APPROVAL_FOR_ALL_EVENT))])
+            .topic1(H256::from(Address::from(owner)))
+            .from_block(from_block)
+            .to_block(to_block.map_or(BlockNumber::Latest, BlockNumber::from));
+        let mut logs = provider.get_logs(&filter).await.map_err(connectivity)?;
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let mut approvals = Vec::new();
+        for mut approval in Self::from_logs(owner, &logs) {
+            let (signature, tokens) = match approval.kind {
+                ApprovalKind::Erc20 => (ALLOWANCE, vec![Token::Address(owner.into()), Token::Address(approval.spender.into())]),
+                ApprovalKind::Erc721 => (GET_APPROVED, vec![Token::Uint(approval.token_id.unwrap_or_default())]),
+                ApprovalKind::Operator => {
+                    (IS_APPROVED_FOR_ALL, vec![Token::Address(owner.into()), Token::Address(approval.spender.into())])
+                }
+            };
+            let mut data = keccak256(signature)[..4].to_vec();
+            data.extend(abi::encode(&tokens));
+            let call: TypedTransaction = TransactionRequest::new().to(Address::from(approval.token)).data(data).into();
+
+            let result = match provider.call(&call, None).await {
+                Ok(result) if result.len() >= 32 => result,
+                Ok(_) => {
+                    approvals.push(approval);
+                    continue;
+                }
+                Err(e) if e.as_error_response().is_some_and(|response| response.is_revert()) => {
+                    approvals.push(approval);
+                    continue;
+                }
+                Err(e) => return Err(connectivity(e).into()),
+            };
+            let word = U256::from_big_endian(&result[..32]);
+            let active = match approval.kind {
+                ApprovalKind::Erc20 => {
+                    approval.allowance = Some(word);
+                    !word.is_zero()
+                }
+                ApprovalKind::Erc721 => EthereumAddress::from(Address::from(H256::from_slice(&result[..32]))) == approval.spender,
+                ApprovalKind::Operator => !word.is_zero(),
+            };
+            if active {
+                approval.confirmed = true;
+                approvals.push(approval);
+            }
+        }
+        Ok(approvals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Address as BookAddress, Network};
+
+    const OWNER: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+    const TOKEN: &str = "0x6B175474E89094C44Da98b954EedeAC495271d0F";
+    const COLLECTION: &str = "0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D";
+    const PERMIT2: &str = "0x000000000022D473030F116dDEE9F6B43aC78BA3";
+    const FRIEND: &str = "0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0";
+
+    fn topic(address: &str) -> H256 {
+        H256::from(address.parse::<Address>().unwrap())
+    }
+
+    fn log(token: &str, block: u64, topics: Vec<H256>, data: Vec<u8>) -> Log {
+        Log {
+            address: token.parse().unwrap(),
+            topics,
+            data: data.into(),
+            block_number: Some(block.into()),
+            ..Default::default()
+        }
+    }
+
+    fn word(value: U256) -> Vec<u8> {
+        <[u8; 32]>::from(value).to_vec()
+    }
+
+    #[test]
+    fn test_approvals_from_logs_and_revoke() {
+        let approval = H256::from(keccak256(APPROVAL_EVENT));
+        let approval_for_all = H256::from(keccak256(APPROVAL_FOR_ALL_EVENT));
+        let owner: EthereumAddress = OWNER.parse().unwrap();
+        let logs = vec![
+            log(TOKEN, 1, vec![approval, topic(OWNER), topic(PERMIT2)], word(U256::MAX)),
+            log(TOKEN, 2, vec![approval, topic(OWNER), topic(FRIEND)], word(100.into())),
+            log(TOKEN, 3, vec![approval, topic(OWNER), topic(FRIEND)], word(U256::zero())),
+            log(COLLECTION, 4, vec![approval, topic(OWNER), topic(FRIEND), H256::from_low_u64_be(7)], vec![]),
+            log(COLLECTION, 5, vec![approval_for_all, topic(OWNER), topic(PERMIT2)], word(1.into())),
+            // Granted by someone else
+            log(TOKEN, 6, vec![approval, topic(FRIEND), topic(PERMIT2)], word(1.into())),
+        ];
+
+        let mut approvals = ApprovalService::from_logs(owner, &logs);
+        let kinds: Vec<_> = approvals.iter().map(|approval| (approval.kind, approval.block_number)).collect();
+        assert_eq!(
+            kinds,
+            vec![(ApprovalKind::Erc20, Some(1)), (ApprovalKind::Erc721, Some(4)), (ApprovalKind::Operator, Some(5))]
+        );
+        assert!(approvals[0].is_unlimited());
+        assert_eq!(approvals[1].token_id, Some(7.into()));
+
+        let mut book = AddressBook::new(Network::Mainnet);
+        let mut friend = BookAddress::from_string(FRIEND, "mainnet").unwrap();
+        friend.set_label(Some("alice".to_string()));
+        book.add(friend).unwrap();
+        ApprovalService::label_spenders(&mut approvals, &book);
+        assert_eq!(approvals[0].spender_label.as_deref(), Some("Uniswap Permit2"));
+        assert_eq!(approvals[1].spender_label.as_deref(), Some("alice"));
+
+        // approve(spender, 0), approve(address(0), tokenId) and setApprovalForAll(operator, false)
+        assert_eq!(
+            hex::encode(approvals[0].revoke_calldata()),
+            format!("095ea7b3{:0>64}{}", &PERMIT2[2..].to_lowercase(), "0".repeat(64))
+        );
+        assert_eq!(hex::encode(approvals[1].revoke_calldata()), format!("095ea7b3{}{:0>64}", "0".repeat(64), "7"));
+        assert_eq!(
+            hex::encode(approvals[2].revoke_calldata()),
+            format!("a22cb465{:0>64}{}", &PERMIT2[2..].to_lowercase(), "0".repeat(64))
+        );
+
+        let tx = approvals[2].revoke_transaction(owner, 1);
+        assert_eq!(tx.to, Some(COLLECTION.parse::<Address>().unwrap().into()));
+        assert_eq!(tx.from, Some(owner.into()));
+
+        for (address, _) in KNOWN_SPENDERS {
+            address.parse::<EthereumAddress>().unwrap();
+        }
+    }
+}
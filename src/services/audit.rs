@@ -0,0 +1,318 @@
+//! # Audit Log
+//!
+//! Append-only record of sensitive operations (creating, importing,
+//! exporting, signing and deleting) as one JSON object per line in the
+//! wallet directory. Entries carry file names and addresses, never secrets.
+//! Each entry stores the SHA-256 of the previous one and a hash over its
+//! own canonical JSON, so editing, reordering or removing any line breaks
+//! the chain from that point on. Cutting lines off the end leaves a valid
+//! shorter chain, so `verify` also reports the head hash to compare with
+//! one recorded elsewhere.
+
+use crate::errors::{FileSystemError, UserInputError, ValidationError, WalletError, WalletResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Audit log file name inside the wallet directory
+pub const AUDIT_FILE_NAME: &str = "audit.jsonl";
+
+/// `prev_hash` of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Kind of audited operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    /// New wallet generated and saved
+    Create,
+    /// Existing key material imported or restored
+    Import,
+    /// Key material or a wallet archive written out
+    Export,
+    /// Signature produced
+    Sign,
+    /// Key material or a stored credential removed
+    Delete,
+}
+
+impl AuditAction {
+    /// Every action, in display order
+    pub const ALL: [AuditAction; 5] = [Self::Create, Self::Import, Self::Export, Self::Sign, Self::Delete];
+
+    /// Lowercase name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Import => "import",
+            Self::Export => "export",
+            Self::Sign => "sign",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+impl std::str::FromStr for AuditAction {
+    type Err = WalletError;
+
+    fn from_str(name: &str) -> WalletResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "action".to_string(),
+                    value: name.to_string(),
+                    expected: Self::ALL.map(|action| action.name()).join(", "),
+                }
+                .into()
+            })
+    }
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, from 1
+    pub seq: u64,
+    /// When the operation happened
+    pub timestamp: DateTime<Utc>,
+    /// Operation
+    pub action: AuditAction,
+    /// Command that performed it
+    pub operation: String,
+    /// Wallet file involved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Address involved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Extra non-secret context (format, path, destination, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// `hash` of the previous entry
+    pub prev_hash: String,
+    /// SHA-256 over this entry's canonical JSON without `hash`
+    #[serde(default)]
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// Hash this entry should carry
+    pub fn compute_hash(&self) -> WalletResult<String> {
+        let mut body = serde_json::to_value(self)?;
+        if let Some(object) = body.as_object_mut() {
+            object.remove("hash");
+        }
+        let canonical = crate::utils::to_canonical_json(&body)?;
+        Ok(hex::encode(Sha256::digest(canonical.as_bytes())))
+    }
+}
+
+/// Details of an operation to record
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Command that performed the operation
+    pub operation: String,
+    /// Wallet file involved
+    pub file: Option<String>,
+    /// Address involved
+    pub address: Option<String>,
+    /// Extra non-secret context
+    pub details: Option<String>,
+}
+
+/// Result of checking the hash chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditVerification {
+    /// Entries checked
+    pub entries: u64,
+    /// Hash of the last valid entry
+    pub head: String,
+    /// First line that breaks the chain and why (1-based line number)
+    pub broken_at: Option<(u64, String)>,
+}
+
+impl AuditVerification {
+    /// Check if every entry chained correctly
+    pub fn is_intact(&self) -> bool {
+        self.broken_at.is_none()
+    }
+}
+
+/// Hash-chained audit log of a wallet directory
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Audit log of `wallet_dir`
+    pub fn new(wallet_dir: &Path) -> Self {
+        Self {
+            path: wallet_dir.join(AUDIT_FILE_NAME),
+        }
+    }
+
+    /// Log file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry chained to the current last one
+    #[tracing::instrument(level = "debug", skip_all, fields(action = action.name()))]
+    pub fn append(&self, action: AuditAction, record: AuditRecord, now: DateTime<Utc>) -> WalletResult<AuditEntry> {
+        let (seq, prev_hash) = match self.entries()?.last() {
+            Some(last) => (last.seq + 1, last.hash.clone()),
+            None => (1, GENESIS_HASH.to_string()),
+        };
+
+        let mut entry = AuditEntry {
+            seq,
+            timestamp: now,
+            action,
+            operation: record.operation,
+            file: record.file,
+            address: record.address,
+            details: record.details,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash()?;
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let is_new = !self.path.exists();
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| self.write_error(e))?;
+        if is_new {
+            crate::security::permissions::restrict_to_owner(&self.path)?;
+        }
+        // One write per line, so concurrent appenders never interleave within an entry
+        let line = format!("{}\n", serde_json::to_string(&entry)?);
+        file.write_all(line.as_bytes()).map_err(|e| self.write_error(e))?;
+        file.sync_all().map_err(|e| self.write_error(e))?;
+
+        Ok(entry)
+    }
+
+    /// Every entry, in order; empty when no log exists
+    pub fn entries(&self) -> WalletResult<Vec<AuditEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        std::fs::read_to_string(&self.path)?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    ValidationError::IntegrityCheckFailed {
+                        data_type: "audit log".to_string(),
+                        details: format!("line {}: {}", number + 1, e),
+                    }
+                    .into()
+                })
+            })
+            .collect()
+    }
+
+    /// Check every entry's hash and its link to the one before
+    pub fn verify(&self) -> WalletResult<AuditVerification> {
+        let mut verification = AuditVerification {
+            entries: 0,
+            head: GENESIS_HASH.to_string(),
+            broken_at: None,
+        };
+        if !self.path.exists() {
+            return Ok(verification);
+        }
+
+        for (number, line) in std::fs::read_to_string(&self.path)?.lines().enumerate() {
+            let line_number = number as u64 + 1;
+            let problem = match serde_json::from_str::<AuditEntry>(line) {
+                Err(e) => Some(format!("unreadable entry: {}", e)),
+                Ok(entry) if entry.prev_hash != verification.head => {
+                    Some("does not follow the previous entry (removed, reordered or inserted lines)".to_string())
+                }
+                Ok(entry) if entry.seq != verification.entries + 1 => {
+                    Some(format!("sequence number {} where {} was expected", entry.seq, verification.entries + 1))
+                }
+                Ok(entry) if entry.compute_hash()? != entry.hash => Some("contents were modified".to_string()),
+                Ok(entry) => {
+                    verification.entries += 1;
+                    verification.head = entry.hash;
+                    None
+                }
+            };
+            if let Some(problem) = problem {
+                verification.broken_at = Some((line_number, problem));
+                break;
+            }
+        }
+        Ok(verification)
+    }
+
+    fn write_error(&self, e: std::io::Error) -> FileSystemError {
+        FileSystemError::PermissionDenied {
+            path: self.path.display().to_string(),
+            operation: format!("append audit entry: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn record(operation: &str) -> AuditRecord {
+        AuditRecord {
+            operation: operation.to_string(),
+            file: Some("wallet.json".to_string()),
+            address: Some("0x9858EfFD232B4033E47d90003D41EC34EcaEda94".to_string()),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_hash_chain_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path());
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+
+        assert!(log.verify().unwrap().is_intact());
+        let first = log.append(AuditAction::Create, record("create"), now).unwrap();
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        log.append(AuditAction::Sign, record("prove-ownership"), now).unwrap();
+        let last = log.append(AuditAction::Export, record("export"), now).unwrap();
+
+        let verification = log.verify().unwrap();
+        assert!(verification.is_intact());
+        assert_eq!((verification.entries, verification.head), (3, last.hash));
+
+        let original = std::fs::read_to_string(log.path()).unwrap();
+        let lines: Vec<&str> = original.lines().collect();
+
+        // Edited entry
+        std::fs::write(log.path(), original.replace("prove-ownership", "create")).unwrap();
+        assert_eq!(log.verify().unwrap().broken_at.unwrap().0, 2);
+
+        // Removed entry
+        std::fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(log.verify().unwrap().broken_at.unwrap().0, 2);
+
+        // Truncation leaves a shorter valid chain with a different head
+        std::fs::write(log.path(), format!("{}\n", lines[0])).unwrap();
+        let verification = log.verify().unwrap();
+        assert!(verification.is_intact());
+        assert_eq!(verification.head, first.hash);
+    }
+}
@@ -0,0 +1,564 @@
+//! # Backup Service
+//!
+//! Bundles the wallet directory into a single password-encrypted archive.
+//!
+//! Archive layout:
+//!
+//! ```text
+//! web3wallet-backup/v1\n
+//! {"kdf":"argon2id",...,"nonce":"..."}\n
+//! <AES-256-GCM ciphertext>
+//! ```
+//!
+//! The two header lines are authenticated as associated data, so any change
+//! to the KDF parameters or the payload is detected before anything is
+//! written to disk.
+//!
+//! Each backup also stamps the archived files in the wallet directory's
+//! [`BackupLog`], which `list` and `load` consult to remind the user about
+//! mainnet keystores that were never backed up or not for a long time, and
+//! records the archive's hash in the [`ChecksumManifest`] of the directory
+//! it was written to.
+
+use crate::config;
+use crate::errors::{AuthenticationError, CryptographicError, FileSystemError, ValidationError, WalletResult};
+use crate::services::manifest::ChecksumManifest;
+use crate::services::CryptoService;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroize;
+
+/// Unencrypted archive header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupHeader {
+    /// Key derivation function (always "argon2id")
+    kdf: String,
+    /// Memory usage in KB
+    memory: u32,
+    /// Time cost (iterations)
+    time: u32,
+    /// Parallelism degree
+    parallelism: u32,
+    /// Salt (hex encoded)
+    salt: String,
+    /// AES-GCM nonce (hex encoded)
+    nonce: String,
+}
+
+/// Encrypted archive contents
+#[derive(Serialize, Deserialize)]
+struct BackupPayload {
+    version: String,
+    created_at: DateTime<Utc>,
+    files: Vec<ArchivedFile>,
+}
+
+/// A single file inside the encrypted payload
+#[derive(Serialize, Deserialize)]
+struct ArchivedFile {
+    name: String,
+    data: String,
+}
+
+impl Drop for BackupPayload {
+    fn drop(&mut self) {
+        for file in &mut self.files {
+            file.data.zeroize();
+        }
+    }
+}
+
+/// A file recorded in a backup archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupEntry {
+    /// File name inside the wallet directory
+    pub name: String,
+    /// File size in bytes
+    pub size: u64,
+}
+
+/// Summary of a backup archive
+#[derive(Debug, Clone)]
+pub struct BackupManifest {
+    /// Version of the tool that wrote the archive
+    pub version: String,
+    /// Archive creation time
+    pub created_at: DateTime<Utc>,
+    /// Files in the archive
+    pub files: Vec<BackupEntry>,
+}
+
+/// Whether a keystore has a recent backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupStatus {
+    /// Backed up within the reminder window
+    Current,
+    /// Last backup is older than the reminder window
+    Stale {
+        /// Time of the last backup
+        last: DateTime<Utc>,
+    },
+    /// Never included in a backup
+    Never,
+}
+
+/// When each file in a wallet directory was last included in a backup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupLog {
+    files: BTreeMap<String, DateTime<Utc>>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl BackupLog {
+    /// Load the backup log, starting empty when none exists
+    pub fn load(wallet_dir: &Path) -> WalletResult<Self> {
+        let path = wallet_dir.join(config::backup::LOG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self { files: BTreeMap::new(), path });
+        }
+
+        let json = std::fs::read_to_string(&path)?;
+        let mut log: Self = serde_json::from_str(&json).map_err(|e| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "backup_log".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        log.path = path;
+        Ok(log)
+    }
+
+    /// Persist the backup log
+    pub fn save(&self) -> WalletResult<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(&self.path, json.as_bytes(), config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+
+    /// Mark files as backed up at `at`
+    pub fn record<'a>(&mut self, names: impl IntoIterator<Item = &'a str>, at: DateTime<Utc>) {
+        for name in names {
+            self.files.insert(name.to_string(), at);
+        }
+    }
+
+    /// Time `name` was last backed up
+    pub fn last_backup(&self, name: &str) -> Option<DateTime<Utc>> {
+        self.files.get(name).copied()
+    }
+
+    /// Backup status of `name` for a reminder window of `max_age_days`
+    pub fn status(&self, name: &str, max_age_days: u32, now: DateTime<Utc>) -> BackupStatus {
+        match self.last_backup(name) {
+            None => BackupStatus::Never,
+            Some(last) if now - last > chrono::Duration::days(i64::from(max_age_days)) => {
+                BackupStatus::Stale { last }
+            }
+            Some(_) => BackupStatus::Current,
+        }
+    }
+}
+
+/// Backup service for whole-directory archives
+pub struct BackupService;
+
+impl BackupService {
+    /// Write an encrypted archive of `wallet_dir` to `out`, at the smaller Argon2id cost when `low_resource`
+    #[tracing::instrument(level = "debug", skip_all, fields(out = %out.display()))]
+    pub fn backup(wallet_dir: &Path, out: &Path, password: &str, low_resource: bool) -> WalletResult<BackupManifest> {
+        if out.exists() {
+            return Err(FileSystemError::FileExists {
+                path: out.display().to_string(),
+                suggestion: "Choose a different archive name".to_string(),
+            }
+            .into());
+        }
+
+        // List keystores saved before manifests existed, so the archive carries a complete one
+        let mut checksums = ChecksumManifest::load(wallet_dir)?;
+        if checksums.track_keystores()? > 0 {
+            checksums.save()?;
+        }
+
+        let payload = Self::collect(wallet_dir)?;
+        let manifest = Self::manifest(&payload);
+        let archive = Self::seal(&payload, password, low_resource)?;
+
+        crate::utils::write_atomic(out, &archive, config::fs::KEYSTORE_FILE_PERMISSIONS)?;
+        ChecksumManifest::record_file(out)?;
+
+        let mut log = BackupLog::load(wallet_dir)?;
+        log.record(manifest.files.iter().map(|f| f.name.as_str()), manifest.created_at);
+        log.save()?;
+
+        Ok(manifest)
+    }
+
+    /// Decrypt `archive` and unpack it into the empty or missing directory `target_dir`
+    #[tracing::instrument(level = "debug", skip_all, fields(archive = %archive.display()))]
+    pub fn restore(archive: &Path, target_dir: &Path, password: &str) -> WalletResult<BackupManifest> {
+        let size = std::fs::metadata(archive)
+            .map_err(|_| FileSystemError::FileNotFound {
+                path: archive.display().to_string(),
+                directory: archive.parent()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| ".".to_string()),
+            })?
+            .len();
+
+        if size > config::backup::MAX_ARCHIVE_SIZE {
+            return Err(FileSystemError::InvalidFormat {
+                path: archive.display().to_string(),
+                details: format!("Archive too large: {} bytes", size),
+            }
+            .into());
+        }
+
+        if target_dir.exists() && std::fs::read_dir(target_dir)?.next().is_some() {
+            return Err(FileSystemError::FileExists {
+                path: target_dir.display().to_string(),
+                suggestion: "Restore into a new or empty wallet directory".to_string(),
+            }
+            .into());
+        }
+
+        let bytes = std::fs::read(archive)?;
+        let payload = Self::open(&bytes, password)?;
+        let manifest = Self::manifest(&payload);
+
+        std::fs::create_dir_all(target_dir)?;
+        crate::security::permissions::restrict_to_owner(target_dir)?;
+
+        for file in &payload.files {
+            let mut data = hex::decode(&file.data).map_err(|e| CryptographicError::DataCorruption {
+                details: format!("Invalid archive entry {}: {}", file.name, e),
+            })?;
+            let result = crate::utils::write_atomic(
+                &target_dir.join(&file.name),
+                &data,
+                config::fs::KEYSTORE_FILE_PERMISSIONS,
+            );
+            data.zeroize();
+            result?;
+        }
+
+        Ok(manifest)
+    }
+
+    /// Decrypt an archive and list its contents without unpacking
+    pub fn inspect(archive: &[u8], password: &str) -> WalletResult<BackupManifest> {
+        let payload = Self::open(archive, password)?;
+        Ok(Self::manifest(&payload))
+    }
+
+    /// Read every top-level wallet file (hidden and temporary files are skipped)
+    fn collect(wallet_dir: &Path) -> WalletResult<BackupPayload> {
+        let entries = std::fs::read_dir(wallet_dir).map_err(|e| {
+            FileSystemError::DirectoryNotAccessible {
+                path: wallet_dir.display().to_string(),
+                details: e.to_string(),
+            }
+        })?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !entry.file_type()?.is_file() || !Self::is_valid_entry_name(&name) {
+                continue;
+            }
+
+            let mut data = std::fs::read(entry.path())?;
+            files.push(ArchivedFile {
+                name,
+                data: hex::encode(&data),
+            });
+            data.zeroize();
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(BackupPayload {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now(),
+            files,
+        })
+    }
+
+    /// Encrypt a payload into archive bytes
+    fn seal(payload: &BackupPayload, password: &str, low_resource: bool) -> WalletResult<Vec<u8>> {
+        let mut salt = vec![0u8; config::crypto::SALT_LENGTH];
+        let mut nonce = vec![0u8; config::crypto::NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let (memory, time, parallelism) = config::get_argon2_config(low_resource);
+        let header = BackupHeader {
+            kdf: "argon2id".to_string(),
+            memory,
+            time,
+            parallelism,
+            salt: hex::encode(&salt),
+            nonce: hex::encode(&nonce),
+        };
+        let preamble = format!("{}\n{}\n", config::backup::MAGIC, serde_json::to_string(&header)?);
+
+        let mut plaintext = serde_json::to_vec(payload)?;
+        let mut key_bytes = vec![0u8; config::crypto::KEY_LENGTH];
+        CryptoService::derive_key_argon2(
+            password.as_bytes(),
+            &salt,
+            memory,
+            time,
+            parallelism,
+            &mut key_bytes,
+        )?;
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key_bytes));
+        let ciphertext = cipher.encrypt(
+            Nonce::from_slice(&nonce),
+            Payload { msg: &plaintext, aad: preamble.as_bytes() },
+        );
+        key_bytes.zeroize();
+        plaintext.zeroize();
+
+        let ciphertext = ciphertext.map_err(|e| CryptographicError::KdfFailed {
+            details: format!("Encryption failed: {}", e),
+        })?;
+
+        let mut archive = preamble.into_bytes();
+        archive.extend_from_slice(&ciphertext);
+        Ok(archive)
+    }
+
+    /// Authenticate and decrypt archive bytes
+    fn open(archive: &[u8], password: &str) -> WalletResult<BackupPayload> {
+        let invalid = |details: &str| ValidationError::IntegrityCheckFailed {
+            data_type: "backup_archive".to_string(),
+            details: details.to_string(),
+        };
+
+        let magic_end = archive.iter().position(|&b| b == b'\n')
+            .ok_or_else(|| invalid("Missing archive header"))?;
+        if &archive[..magic_end] != config::backup::MAGIC.as_bytes() {
+            return Err(invalid("Not a web3wallet backup archive").into());
+        }
+
+        let header_end = archive[magic_end + 1..].iter().position(|&b| b == b'\n')
+            .map(|pos| magic_end + 1 + pos)
+            .ok_or_else(|| invalid("Missing archive header"))?;
+        let header: BackupHeader = serde_json::from_slice(&archive[magic_end + 1..header_end])
+            .map_err(|e| invalid(&format!("Invalid archive header: {}", e)))?;
+        if header.kdf != "argon2id" {
+            return Err(invalid(&format!("Unsupported KDF: {}", header.kdf)).into());
+        }
+
+        // The header is only authenticated after the key is derived, so bound the work it can ask for first
+        if !config::is_argon2_within_bounds(header.memory, header.time, header.parallelism) {
+            return Err(invalid(&format!(
+                "KDF parameters out of range: memory {} KiB, time {}, parallelism {}",
+                header.memory, header.time, header.parallelism
+            ))
+            .into());
+        }
+
+        let salt = hex::decode(&header.salt).map_err(|_| invalid("Invalid salt encoding"))?;
+        if salt.len() != config::crypto::SALT_LENGTH {
+            return Err(invalid("Invalid salt length").into());
+        }
+        let nonce = hex::decode(&header.nonce).map_err(|_| invalid("Invalid nonce encoding"))?;
+        if nonce.len() != config::crypto::NONCE_LENGTH {
+            return Err(invalid("Invalid nonce length").into());
+        }
+
+        let mut key_bytes = vec![0u8; config::crypto::KEY_LENGTH];
+        CryptoService::derive_key_argon2(
+            password.as_bytes(),
+            &salt,
+            header.memory,
+            header.time,
+            header.parallelism,
+            &mut key_bytes,
+        )?;
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key_bytes));
+        let plaintext = cipher.decrypt(
+            Nonce::from_slice(&nonce),
+            Payload { msg: &archive[header_end + 1..], aad: &archive[..=header_end] },
+        );
+        key_bytes.zeroize();
+
+        // GCM cannot tell a wrong key from a damaged archive; the password is the usual culprit
+        let mut plaintext = plaintext.map_err(|_| AuthenticationError::WrongPassword {
+            wallet_file: String::new(),
+            attempts_remaining: None,
+        })?;
+
+        let payload: Result<BackupPayload, _> = serde_json::from_slice(&plaintext);
+        plaintext.zeroize();
+        let payload = payload.map_err(|e| CryptographicError::DataCorruption {
+            details: format!("Invalid archive payload: {}", e),
+        })?;
+
+        if let Some(file) = payload.files.iter().find(|f| !Self::is_valid_entry_name(&f.name)) {
+            return Err(FileSystemError::PathTraversal {
+                path: file.name.clone(),
+            }
+            .into());
+        }
+
+        Ok(payload)
+    }
+
+    /// Build the public summary of a payload
+    fn manifest(payload: &BackupPayload) -> BackupManifest {
+        BackupManifest {
+            version: payload.version.clone(),
+            created_at: payload.created_at,
+            files: payload.files.iter().map(|f| BackupEntry {
+                name: f.name.clone(),
+                size: (f.data.len() / 2) as u64,
+            }).collect(),
+        }
+    }
+
+    /// Accept only plain, non-hidden file names
+    fn is_valid_entry_name(name: &str) -> bool {
+        !name.is_empty()
+            && !name.starts_with('.')
+            && Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PASSWORD: &str = "BackupPass123!";
+
+    fn wallet_dir_with_files() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("alpha.json"), r#"{"address":"a"}"#).unwrap();
+        std::fs::write(dir.path().join("beta.json"), r#"{"address":"b"}"#).unwrap();
+        std::fs::write(dir.path().join(".beta.json.tmp-1"), "partial").unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_backup_restore_roundtrip() {
+        let source = wallet_dir_with_files();
+        let out_dir = TempDir::new().unwrap();
+        let archive = out_dir.path().join("backup.w3w");
+
+        let manifest = BackupService::backup(source.path(), &archive, PASSWORD, false).unwrap();
+        let names: Vec<_> = manifest.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["alpha.json", "beta.json"]);
+        let checksums = ChecksumManifest::load(out_dir.path()).unwrap();
+        assert_eq!(checksums.files["backup.w3w"].size, std::fs::metadata(&archive).unwrap().len());
+
+        let target = out_dir.path().join("restored");
+        BackupService::restore(&archive, &target, PASSWORD).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(target.join("beta.json")).unwrap(),
+            r#"{"address":"b"}"#
+        );
+        assert!(!target.join(".beta.json.tmp-1").exists());
+    }
+
+    #[test]
+    fn test_restore_rejects_wrong_password_and_tampering() {
+        let source = wallet_dir_with_files();
+        let out_dir = TempDir::new().unwrap();
+        let archive = out_dir.path().join("backup.w3w");
+        BackupService::backup(source.path(), &archive, PASSWORD, false).unwrap();
+
+        let target = out_dir.path().join("restored");
+        assert!(BackupService::restore(&archive, &target, "WrongPass123!").is_err());
+        assert!(!target.exists());
+
+        // Weaken the KDF parameters in the (authenticated) header
+        let bytes = std::fs::read(&archive).unwrap();
+        let text = String::from_utf8_lossy(&bytes).replacen("\"time\":", "\"time\":1", 1);
+        assert!(BackupService::inspect(text.as_bytes(), PASSWORD).is_err());
+
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 0x01;
+        assert!(BackupService::inspect(&flipped, PASSWORD).is_err());
+
+        assert!(BackupService::inspect(b"not an archive\n{}\n", PASSWORD).is_err());
+    }
+
+    #[test]
+    fn test_open_rejects_unbounded_kdf_header() {
+        let source = wallet_dir_with_files();
+        let out_dir = TempDir::new().unwrap();
+        let archive = out_dir.path().join("backup.w3w");
+        BackupService::backup(source.path(), &archive, PASSWORD, false).unwrap();
+        let bytes = std::fs::read(&archive).unwrap();
+        let mut parts = bytes.splitn(3, |&b| b == b'\n');
+        let (_, header, ciphertext) = (parts.next().unwrap(), parts.next().unwrap(), parts.next().unwrap());
+        let header: BackupHeader = serde_json::from_slice(header).unwrap();
+
+        // Rejected before any key derivation, which would otherwise allocate 4 TiB
+        for crafted in [
+            BackupHeader { memory: u32::MAX, ..header.clone() },
+            BackupHeader { time: u32::MAX, ..header.clone() },
+            BackupHeader { parallelism: 0, ..header.clone() },
+            BackupHeader { salt: "00".to_string(), ..header },
+        ] {
+            let mut forged = format!("{}\n{}\n", config::backup::MAGIC, serde_json::to_string(&crafted).unwrap()).into_bytes();
+            forged.extend_from_slice(ciphertext);
+            assert!(matches!(
+                BackupService::inspect(&forged, PASSWORD),
+                Err(crate::WalletError::Validation(ValidationError::IntegrityCheckFailed { .. }))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_backup_updates_log() {
+        let source = wallet_dir_with_files();
+        let out_dir = TempDir::new().unwrap();
+        let now = Utc::now();
+
+        let log = BackupLog::load(source.path()).unwrap();
+        assert_eq!(log.status("alpha.json", 90, now), BackupStatus::Never);
+
+        let manifest = BackupService::backup(source.path(), &out_dir.path().join("backup.w3w"), PASSWORD, false).unwrap();
+        let log = BackupLog::load(source.path()).unwrap();
+        assert_eq!(log.last_backup("alpha.json"), Some(manifest.created_at));
+        assert_eq!(log.status("alpha.json", 90, now), BackupStatus::Current);
+        assert_eq!(
+            log.status("alpha.json", 90, now + chrono::Duration::days(91)),
+            BackupStatus::Stale { last: manifest.created_at }
+        );
+    }
+
+    #[test]
+    fn test_restore_refuses_non_empty_directory() {
+        let source = wallet_dir_with_files();
+        let out_dir = TempDir::new().unwrap();
+        let archive = out_dir.path().join("backup.w3w");
+        BackupService::backup(source.path(), &archive, PASSWORD, false).unwrap();
+
+        assert!(BackupService::restore(&archive, source.path(), PASSWORD).is_err());
+        assert!(BackupService::backup(source.path(), &archive, PASSWORD, false).is_err());
+    }
+
+    #[test]
+    fn test_entry_names() {
+        assert!(BackupService::is_valid_entry_name("wallet.json"));
+        assert!(!BackupService::is_valid_entry_name("../wallet.json"));
+        assert!(!BackupService::is_valid_entry_name("sub/wallet.json"));
+        assert!(!BackupService::is_valid_entry_name(".hidden"));
+        assert!(!BackupService::is_valid_entry_name(""));
+    }
+}
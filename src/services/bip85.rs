@@ -0,0 +1,159 @@
+//! # BIP85 Service
+//!
+//! Deterministic child mnemonics (BIP85 application 39). Each child is
+//! derived from the master key at `m/83696968'/39'/{language}'/{words}'/{index}'`,
+//! so a single backup of the master mnemonic recovers every child wallet.
+
+use crate::config;
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::{DerivationPath, Wallet};
+use crate::services::mnemonic::{MnemonicService, SecureMnemonic};
+use bip39::{Language, Mnemonic};
+use coins_bip32::xkeys::XPriv;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+/// BIP85 purpose (ASCII "BIPS" on a telephone keypad)
+const BIP85_PURPOSE: u32 = 83_696_968;
+
+/// Application number for BIP39 mnemonics
+const BIP39_APPLICATION: u32 = 39;
+
+/// HMAC key turning a derived private key into entropy
+const ENTROPY_HMAC_KEY: &[u8] = b"bip-entropy-from-k";
+
+/// A child mnemonic and where it came from
+#[derive(Debug, Clone)]
+pub struct Bip85Child {
+    /// Child mnemonic phrase
+    pub mnemonic: SecureMnemonic,
+    /// BIP85 derivation path of the child entropy
+    pub derivation_path: DerivationPath,
+}
+
+/// BIP85 child mnemonic derivation
+pub struct Bip85Service;
+
+impl Bip85Service {
+    /// Derive the child mnemonic at `index` from an HD wallet
+    pub fn derive_mnemonic(wallet: &Wallet, language: Language, word_count: u8, index: u32) -> WalletResult<Bip85Child> {
+        Self::derive_from_master(&wallet.master_key()?, language, word_count, index)
+    }
+
+    /// Derive the child mnemonic at `index` from a BIP32 root key
+    #[tracing::instrument(level = "debug", skip_all, fields(word_count = word_count, index = index))]
+    pub fn derive_from_master(master: &XPriv, language: Language, word_count: u8, index: u32) -> WalletResult<Bip85Child> {
+        if !config::is_supported_word_count(word_count) {
+            return Err(CryptographicError::InvalidMnemonic {
+                details: format!("Unsupported word count: {}", word_count),
+                suggestion: "Use 12 or 24 words".to_string(),
+            }
+            .into());
+        }
+
+        let derivation_path = DerivationPath::root()
+            .hardened_child(BIP85_PURPOSE)?
+            .hardened_child(BIP39_APPLICATION)?
+            .hardened_child(Self::language_code(language)?)?
+            .hardened_child(word_count.into())?
+            .hardened_child(index)?;
+        let entropy = Self::derive_entropy(master, &derivation_path)?;
+        let entropy_len = word_count as usize * 4 / 3;
+
+        let mnemonic = Mnemonic::from_entropy_in(language, &entropy[..entropy_len]).map_err(|e| {
+            CryptographicError::InvalidMnemonic {
+                details: e.to_string(),
+                suggestion: "Report this as a bug".to_string(),
+            }
+        })?;
+
+        Ok(Bip85Child {
+            mnemonic: SecureMnemonic::new(mnemonic.to_string()),
+            derivation_path,
+        })
+    }
+
+    /// HMAC-SHA512 of the private key at `path`
+    fn derive_entropy(master: &XPriv, path: &DerivationPath) -> WalletResult<Zeroizing<[u8; 64]>> {
+        let child = master.derive_path(path.to_bip32()).map_err(|_e| CryptographicError::InvalidDerivationPath {
+            path: path.to_string(),
+            expected: "BIP85 derivation path".to_string(),
+        })?;
+        let signing_key: &ethers::core::k256::ecdsa::SigningKey = child.as_ref();
+        let key = Zeroizing::new(signing_key.to_bytes());
+
+        let mut mac = Hmac::<Sha512>::new_from_slice(ENTROPY_HMAC_KEY).map_err(|e| CryptographicError::KdfFailed {
+            details: e.to_string(),
+        })?;
+        mac.update(&key);
+
+        let mut entropy = Zeroizing::new([0u8; 64]);
+        entropy.copy_from_slice(&mac.finalize().into_bytes());
+        Ok(entropy)
+    }
+
+    /// BIP85 language number
+    fn language_code(language: Language) -> WalletResult<u32> {
+        match language {
+            Language::English => Ok(0),
+            Language::Japanese => Ok(1),
+            Language::Korean => Ok(2),
+            Language::Spanish => Ok(3),
+            Language::SimplifiedChinese => Ok(4),
+            Language::TraditionalChinese => Ok(5),
+            Language::French => Ok(6),
+            Language::Italian => Ok(7),
+            Language::Czech => Ok(8),
+            other => Err(UserInputError::InvalidParameters {
+                parameter: "language".to_string(),
+                value: MnemonicService::language_name(other).to_string(),
+                expected: "a wordlist with a BIP85 language number".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+    use coins_bip32::enc::{MainnetEncoder, XKeyEncoder};
+
+    /// Master key from the BIP85 test vectors
+    const TEST_XPRV: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    #[test]
+    fn test_bip85_vectors() {
+        let master: XPriv = MainnetEncoder::xpriv_from_base58(TEST_XPRV).unwrap();
+
+        let child = Bip85Service::derive_from_master(&master, Language::English, 12, 0).unwrap();
+        assert_eq!(child.derivation_path.to_string(), "m/83696968'/39'/0'/12'/0'");
+        assert_eq!(
+            child.mnemonic.phrase(),
+            "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose"
+        );
+
+        let child = Bip85Service::derive_from_master(&master, Language::English, 24, 0).unwrap();
+        assert_eq!(
+            child.mnemonic.phrase(),
+            "puppy ocean match cereal symbol another shed magic wrap hammer bulb intact gadget divorce twin tonight reason outdoor destroy simple truth cigar social volcano"
+        );
+    }
+
+    #[test]
+    fn test_children_differ_by_index() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Mainnet,
+            None,
+        )
+        .unwrap();
+
+        let first = Bip85Service::derive_mnemonic(&wallet, Language::English, 12, 0).unwrap();
+        let second = Bip85Service::derive_mnemonic(&wallet, Language::English, 12, 1).unwrap();
+        assert_ne!(first.mnemonic.phrase(), second.mnemonic.phrase());
+        assert!(Bip85Service::derive_mnemonic(&wallet, Language::Portuguese, 12, 0).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! # Blocking Wallet API
+//!
+//! Synchronous facade over [`WalletManager`] for command-line tools and FFI
+//! layers that do not run an async executor. Each facade owns a
+//! single-threaded tokio runtime and drives the manager's futures to
+//! completion on the calling thread, so callers never see tokio.
+//!
+//! The facade must not be used from inside an async runtime: blocking on a
+//! future there panics. Async code should call [`WalletManager`] directly.
+
+use crate::errors::WalletResult;
+use crate::models::{Address, Wallet};
+use crate::services::crypto::{HardwareBinding, SecretPassword};
+use crate::services::wallet_manager::{CreatedKeystore, WalletManager};
+use std::path::{Path, PathBuf};
+use tokio::runtime::{Builder, Runtime};
+use zeroize::Zeroizing;
+
+/// [`WalletManager`] with blocking methods, made by [`WalletManager::blocking`]
+pub struct BlockingWalletManager {
+    manager: WalletManager,
+    runtime: Runtime,
+}
+
+impl BlockingWalletManager {
+    pub(crate) fn new(manager: WalletManager) -> WalletResult<Self> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { manager, runtime })
+    }
+
+    /// The wrapped manager, for its synchronous methods such as signing
+    pub fn manager(&self) -> &WalletManager {
+        &self.manager
+    }
+
+    /// Give back the async manager
+    pub fn into_inner(self) -> WalletManager {
+        self.manager
+    }
+
+    /// Create a new wallet with specified word count
+    pub fn create_wallet(&self, word_count: u8) -> WalletResult<Wallet> {
+        self.runtime.block_on(self.manager.create_wallet(word_count))
+    }
+
+    /// Create a new wallet with a mnemonic from the `language` wordlist
+    pub fn create_wallet_in(&self, word_count: u8, language: bip39::Language) -> WalletResult<Wallet> {
+        self.runtime.block_on(self.manager.create_wallet_in(word_count, language))
+    }
+
+    /// Import wallet from mnemonic
+    pub fn import_from_mnemonic(&self, mnemonic: &str) -> WalletResult<Wallet> {
+        self.runtime.block_on(self.manager.import_from_mnemonic(mnemonic))
+    }
+
+    /// Import an HD wallet from a raw BIP39 seed (hex)
+    pub fn import_from_seed(&self, seed_hex: &str) -> WalletResult<Wallet> {
+        self.runtime.block_on(self.manager.import_from_seed(seed_hex))
+    }
+
+    /// Import wallet from BIP39 entropy (hex), regenerating the mnemonic in `language`
+    pub fn import_from_entropy(&self, entropy_hex: &str, language: bip39::Language) -> WalletResult<Wallet> {
+        self.runtime.block_on(self.manager.import_from_entropy(entropy_hex, language))
+    }
+
+    /// Import wallet from private key
+    pub fn import_from_private_key(&self, private_key: &str) -> WalletResult<Wallet> {
+        self.runtime.block_on(self.manager.import_from_private_key(private_key))
+    }
+
+    /// Save wallet to encrypted file
+    pub fn save_wallet(&self, wallet: &Wallet, path: &Path, password: &impl SecretPassword) -> WalletResult<()> {
+        self.runtime.block_on(self.manager.save_wallet(wallet, path, password))
+    }
+
+    /// Save wallet to encrypted file that also needs `binding` to decrypt
+    pub fn save_wallet_bound(
+        &self,
+        wallet: &Wallet,
+        path: &Path,
+        password: &impl SecretPassword,
+        binding: Option<HardwareBinding>,
+    ) -> WalletResult<()> {
+        self.runtime.block_on(self.manager.save_wallet_bound(wallet, path, password, binding))
+    }
+
+    /// Re-encrypt a wallet over its existing keystore file
+    pub fn update_wallet(&self, wallet: &Wallet, path: &Path, password: &impl SecretPassword) -> WalletResult<()> {
+        self.runtime.block_on(self.manager.update_wallet(wallet, path, password))
+    }
+
+    /// Create `count` independent wallets, each saved to its own keystore in `dir`
+    pub fn create_wallets(
+        &self,
+        count: usize,
+        word_count: u8,
+        language: bip39::Language,
+        dir: &Path,
+        password: &impl SecretPassword,
+    ) -> WalletResult<Vec<CreatedKeystore>> {
+        self.runtime.block_on(self.manager.create_wallets(count, word_count, language, dir, password))
+    }
+
+    /// Load wallet from encrypted file
+    pub fn load_wallet(&self, path: &Path, password: &impl SecretPassword) -> WalletResult<Wallet> {
+        self.runtime.block_on(self.manager.load_wallet(path, password))
+    }
+
+    /// Load several keystores, asking `password_provider` for each password
+    pub fn load_wallets<F>(&self, paths: &[PathBuf], password_provider: F) -> Vec<(PathBuf, WalletResult<Wallet>)>
+    where
+        F: FnMut(&Path) -> WalletResult<Zeroizing<String>>,
+    {
+        self.runtime.block_on(self.manager.load_wallets(paths, password_provider))
+    }
+
+    /// Derive address from wallet
+    pub fn derive_address(&self, wallet: &Wallet, index: u32) -> WalletResult<Address> {
+        self.runtime.block_on(self.manager.derive_address(wallet, index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::SecureString;
+    use crate::WalletConfig;
+
+    #[test]
+    fn test_blocking_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = WalletConfig {
+            wallet_dir: dir.path().to_path_buf(),
+            kdf_iterations: 1,
+            kdf_memory: 1024,
+            kdf_parallelism: 1,
+            allow_weak_kdf: true,
+            ..WalletConfig::default()
+        };
+
+        // A plain #[test]: no runtime is running on this thread
+        let manager = WalletManager::new(config).blocking().unwrap();
+        let wallet = manager
+            .import_from_mnemonic("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about")
+            .unwrap();
+        let path = dir.path().join("blocking.json");
+        let password = SecureString::from("Correct-Horse-42");
+        manager.save_wallet(&wallet, &path, &password).unwrap();
+
+        let loaded = manager.load_wallet(&path, &password).unwrap();
+        assert_eq!(loaded.address(), wallet.address());
+        assert_eq!(manager.derive_address(&loaded, 0).unwrap().address(), "0x9858effd232b4033e47d90003d41ec34ecaeda94");
+    }
+}
@@ -0,0 +1,113 @@
+//! # BLS12-381
+//!
+//! The parts of BLS12-381 that validator keys need: public keys on G1 and
+//! signatures on G2 under the Ethereum proof-of-possession ciphersuite,
+//! with hashing to G2 as in RFC 9380. The curve arithmetic and pairing come
+//! from the pure-Rust `bls12_381` crate, so the core still builds for
+//! wasm32; [`verify`] checks a signature with the pairing the way a
+//! consensus client does.
+
+use crate::models::validator_keystore::BlsSecretKey;
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{pairing, G1Affine, G2Affine, G2Projective, Scalar};
+use zeroize::Zeroizing;
+
+/// Domain separation tag of the proof-of-possession ciphersuite Ethereum signs with
+pub const POP_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Compressed G1 public key length
+pub const PUBLIC_KEY_LENGTH: usize = 48;
+
+/// Compressed G2 signature length
+pub const SIGNATURE_LENGTH: usize = 96;
+
+/// Public key of a validator secret key: `sk * G1`, compressed
+pub fn public_key(secret: &BlsSecretKey) -> [u8; PUBLIC_KEY_LENGTH] {
+    let scalar = scalar(secret);
+    G1Affine::from(G1Affine::generator() * *scalar).to_compressed()
+}
+
+/// Signature over `message` under the proof-of-possession ciphersuite: `sk * H(message)`, compressed
+pub fn sign(secret: &BlsSecretKey, message: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+    let scalar = scalar(secret);
+    G2Affine::from(hash_to_g2(message, POP_DST) * *scalar).to_compressed()
+}
+
+/// Check `signature` over `message` under `public_key`: `e(pk, H(message)) == e(G1, sig)`
+///
+/// Both points must decode into their prime-order subgroups, and the
+/// identity is refused as a public key.
+pub fn verify(public_key: &[u8; PUBLIC_KEY_LENGTH], message: &[u8], signature: &[u8; SIGNATURE_LENGTH]) -> bool {
+    let public_key: Option<G1Affine> = G1Affine::from_compressed(public_key).into();
+    let signature: Option<G2Affine> = G2Affine::from_compressed(signature).into();
+    let (Some(public_key), Some(signature)) = (public_key, signature) else {
+        return false;
+    };
+    if bool::from(public_key.is_identity()) {
+        return false;
+    }
+
+    let message = G2Affine::from(hash_to_g2(message, POP_DST));
+    pairing(&public_key, &message) == pairing(&G1Affine::generator(), &signature)
+}
+
+/// Secret key as a field scalar, which the crate reads little-endian
+fn scalar(secret: &BlsSecretKey) -> Zeroizing<Scalar> {
+    let mut bytes = Zeroizing::new(*secret.as_bytes());
+    bytes.reverse();
+    // BlsSecretKey only holds scalars below the group order
+    Zeroizing::new(Scalar::from_bytes(&bytes).expect("BLS secret key is a canonical scalar"))
+}
+
+fn hash_to_g2(message: &[u8], dst: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(message, dst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors() {
+        // EIP-2335 test vector public key
+        let secret =
+            BlsSecretKey::from_bytes(&hex::decode("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f").unwrap())
+                .unwrap();
+        assert_eq!(
+            hex::encode(public_key(&secret)),
+            "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07"
+        );
+
+        // RFC 9380 hash_to_curve vector for BLS12381G2_XMD:SHA-256_SSWU_RO_, msg = ""
+        let point = G2Affine::from(hash_to_g2(b"", b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_"));
+        let expected = hex::decode(
+            "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d\
+             0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a\
+             12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6\
+             0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+        )
+        .unwrap();
+        // Uncompressed G2 is x.c1 || x.c0 || y.c1 || y.c0
+        assert_eq!(point.to_uncompressed().to_vec(), expected);
+
+        // Consensus spec BLS test vector (sign_case_142f678a8d05fcd1)
+        let secret =
+            BlsSecretKey::from_bytes(&hex::decode("47b8192d77bf871b62e87859d653922725724a5c031afeabc60bcef5ff665138").unwrap())
+                .unwrap();
+        let signature = sign(&secret, &[0; 32]);
+        assert_eq!(
+            hex::encode(signature),
+            "b23c46be3a001c63ca711f87a005c200cc550b9429d5f4eb38d74322144f1b63926da3388979e5321012fb1a0526bcd1\
+             00b5ef5fe72628ce4cd5e904aeaa3279527843fae5ca9ca675f4f51ed8f83bbf7155da9ecc9663100a885d5dc6df96d9"
+        );
+
+        // The pairing accepts the signature and nothing else
+        let pubkey = public_key(&secret);
+        assert!(verify(&pubkey, &[0; 32], &signature));
+        assert!(!verify(&pubkey, &[1; 32], &signature));
+        let mut tampered = signature;
+        tampered[95] ^= 1;
+        assert!(!verify(&pubkey, &[0; 32], &tampered));
+        assert!(!verify(&G1Affine::identity().to_compressed(), &[0; 32], &G2Affine::identity().to_compressed()));
+    }
+}
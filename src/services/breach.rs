@@ -0,0 +1,256 @@
+//! # Breached Password Filter
+//!
+//! Offline check of new keystore passwords against passwords known from
+//! public breaches. A short list of the most common ones ships with the
+//! binary; a full list (for example the top million from a breach corpus)
+//! can be turned into a gzip-compressed bloom filter in the wallet
+//! directory with `breach-filter build`. Nothing is ever sent over the
+//! network. A bloom filter can report a password that was never listed
+//! (at the rate it was built for) but never misses one that was.
+
+use crate::config;
+use crate::errors::{AuthenticationError, FileSystemError, WalletResult};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Passwords built into every check
+const BUNDLED_LIST: &str = include_str!("../../data/breached-passwords.txt");
+
+/// First bytes of a filter file, after decompression
+const MAGIC: &[u8; 8] = b"W3WBLOOM";
+
+/// Filter file format version
+const FORMAT_VERSION: u8 = 1;
+
+/// Bloom filter over SHA-256 digests, using double hashing for its probes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u8>,
+    bit_count: u64,
+    hashes: u8,
+}
+
+impl BloomFilter {
+    /// Empty filter sized for `items` entries at `false_positive_rate`
+    pub fn with_capacity(items: usize, false_positive_rate: f64) -> Self {
+        let items = items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = (-items * false_positive_rate.ln() / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let hashes = ((bit_count as f64 / items) * ln2).round().clamp(1.0, 32.0) as u8;
+        let bytes = usize::try_from((bit_count + 7) / 8).unwrap_or(usize::MAX);
+
+        Self {
+            bits: vec![0; bytes],
+            bit_count,
+            hashes,
+        }
+    }
+
+    /// Add an entry
+    pub fn insert(&mut self, item: &[u8]) {
+        for bit in self.positions(item) {
+            self.bits[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Check if an entry may have been added
+    pub fn contains(&self, item: &[u8]) -> bool {
+        self.positions(item).all(|bit| self.bits[(bit / 8) as usize] & (1 << (bit % 8)) != 0)
+    }
+
+    fn positions(&self, item: &[u8]) -> impl Iterator<Item = u64> {
+        let digest = Sha256::digest(item);
+        let h1 = u64::from_le_bytes(digest[..8].try_into().expect("SHA-256 digest is 32 bytes"));
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().expect("SHA-256 digest is 32 bytes")) | 1;
+        let bit_count = self.bit_count;
+        (0..u64::from(self.hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_count)
+    }
+
+    /// Gzip-compressed file contents
+    pub fn to_bytes(&self) -> WalletResult<Vec<u8>> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(MAGIC)?;
+        encoder.write_all(&[FORMAT_VERSION, self.hashes])?;
+        encoder.write_all(&self.bit_count.to_le_bytes())?;
+        encoder.write_all(&self.bits)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Parse file contents written by [`BloomFilter::to_bytes`]
+    pub fn from_bytes(bytes: &[u8], source: &Path) -> WalletResult<Self> {
+        let invalid = |details: &str| FileSystemError::InvalidFormat {
+            path: source.display().to_string(),
+            details: details.to_string(),
+        };
+
+        let mut contents = Vec::new();
+        flate2::read::GzDecoder::new(bytes)
+            .take(config::breach::MAX_FILTER_SIZE + 1)
+            .read_to_end(&mut contents)
+            .map_err(|e| invalid(&format!("not a gzip-compressed filter: {}", e)))?;
+        if contents.len() as u64 > config::breach::MAX_FILTER_SIZE {
+            return Err(invalid("filter is larger than the supported maximum").into());
+        }
+
+        let header_len = MAGIC.len() + 10;
+        if contents.len() < header_len || &contents[..MAGIC.len()] != MAGIC {
+            return Err(invalid("not a breached password filter").into());
+        }
+        let version = contents[MAGIC.len()];
+        if version != FORMAT_VERSION {
+            return Err(invalid(&format!("unsupported filter version {}", version)).into());
+        }
+        let hashes = contents[MAGIC.len() + 1];
+        let bit_count = u64::from_le_bytes(contents[MAGIC.len() + 2..header_len].try_into().expect("8-byte slice"));
+        let bits = contents.split_off(header_len);
+        if hashes == 0 || bit_count == 0 || (bits.len() as u64) * 8 < bit_count {
+            return Err(invalid("filter header does not match its size").into());
+        }
+
+        Ok(Self { bits, bit_count, hashes })
+    }
+}
+
+/// Result of building a filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterBuildReport {
+    /// Where the filter was written
+    pub path: PathBuf,
+    /// Passwords added
+    pub entries: usize,
+    /// Compressed file size
+    pub file_size: u64,
+}
+
+/// Bundled and installed breached password filters
+#[derive(Debug, Clone)]
+pub struct BreachedPasswords {
+    installed: Option<BloomFilter>,
+}
+
+impl BreachedPasswords {
+    /// Bundled list plus the filter installed in `wallet_dir`, if any
+    pub fn load(wallet_dir: &Path) -> WalletResult<Self> {
+        let path = Self::installed_path(wallet_dir);
+        let installed = match std::fs::read(&path) {
+            Ok(bytes) => Some(BloomFilter::from_bytes(&bytes, &path)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { installed })
+    }
+
+    /// Location of the installed filter
+    pub fn installed_path(wallet_dir: &Path) -> PathBuf {
+        wallet_dir.join(config::breach::FILTER_FILE_NAME)
+    }
+
+    /// Check if a filter beyond the bundled list is installed
+    pub fn has_installed(&self) -> bool {
+        self.installed.is_some()
+    }
+
+    /// Check if `password` appears in a breach list
+    pub fn contains(&self, password: &str) -> bool {
+        Self::bundled().contains(password.as_bytes())
+            || self.installed.as_ref().is_some_and(|filter| filter.contains(password.as_bytes()))
+    }
+
+    /// Refuse a breached password for a new keystore
+    pub fn check(&self, password: &str) -> WalletResult<()> {
+        if self.contains(password) {
+            return Err(AuthenticationError::WeakPassword {
+                requirements: vec!["Not a password known from public breaches".to_string()],
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Build a filter from a list with one password per line and install it at `output`
+    #[tracing::instrument(level = "debug", skip_all, fields(list = %list.display()))]
+    pub fn build(list: &Path, output: &Path, false_positive_rate: f64) -> WalletResult<FilterBuildReport> {
+        let open = || {
+            std::fs::File::open(list).map(std::io::BufReader::new).map_err(|_| FileSystemError::FileNotFound {
+                path: list.display().to_string(),
+                directory: list.parent().map(|p| p.display().to_string()).unwrap_or_else(|| ".".to_string()),
+            })
+        };
+
+        // Two passes, so a list of millions is never held in memory
+        let mut entries = 0;
+        for line in open()?.split(b'\n') {
+            if !Self::entry(&line?).is_empty() {
+                entries += 1;
+            }
+        }
+        let mut filter = BloomFilter::with_capacity(entries, false_positive_rate);
+        for line in open()?.split(b'\n') {
+            let line = line?;
+            let entry = Self::entry(&line);
+            if !entry.is_empty() {
+                filter.insert(entry);
+            }
+        }
+
+        let bytes = filter.to_bytes()?;
+        if let Some(dir) = output.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        crate::utils::write_atomic(output, &bytes, config::fs::KEYSTORE_FILE_PERMISSIONS)?;
+
+        Ok(FilterBuildReport {
+            path: output.to_path_buf(),
+            entries,
+            file_size: bytes.len() as u64,
+        })
+    }
+
+    /// Password on one list line, without the line ending
+    fn entry(line: &[u8]) -> &[u8] {
+        line.strip_suffix(b"\r").unwrap_or(line)
+    }
+
+    fn bundled() -> &'static BloomFilter {
+        static BUNDLED: OnceLock<BloomFilter> = OnceLock::new();
+        BUNDLED.get_or_init(|| {
+            let passwords: Vec<&str> = BUNDLED_LIST.lines().filter(|line| !line.is_empty() && !line.starts_with('#')).collect();
+            let mut filter = BloomFilter::with_capacity(passwords.len(), config::breach::DEFAULT_FALSE_POSITIVE_RATE);
+            for password in passwords {
+                filter.insert(password.as_bytes());
+            }
+            filter
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_and_installed_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let breached = BreachedPasswords::load(dir.path()).unwrap();
+        assert!(!breached.has_installed());
+        assert!(breached.contains("P@ssw0rd123!"));
+        assert!(breached.check("Passw0rd!Str0ng#1").is_ok());
+        assert_eq!(breached.check("Qwerty123!").unwrap_err().code(), "AUTH_002");
+
+        let list = dir.path().join("leaked.txt");
+        std::fs::write(&list, "Tr0ub4dor&3\r\nCorrectHorse9!\n\n").unwrap();
+        let output = BreachedPasswords::installed_path(dir.path());
+        let report = BreachedPasswords::build(&list, &output, 0.001).unwrap();
+        assert_eq!(report.entries, 2);
+
+        let breached = BreachedPasswords::load(dir.path()).unwrap();
+        assert!(breached.has_installed());
+        assert!(breached.contains("Tr0ub4dor&3") && breached.contains("CorrectHorse9!"));
+        assert!(!breached.contains("Passw0rd!Str0ng#1"));
+
+        std::fs::write(&output, b"not a filter").unwrap();
+        assert!(BreachedPasswords::load(dir.path()).is_err());
+    }
+}
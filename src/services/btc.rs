@@ -0,0 +1,279 @@
+//! # Bitcoin Service
+//!
+//! Bitcoin addresses controlled by a wallet's seed, for checking what a
+//! shared mnemonic holds and verifying backups against another wallet. Both
+//! common layouts are covered: BIP84 native segwit (`bc1q...`, `m/84'/0'/...`)
+//! and BIP44 legacy pay-to-pubkey-hash (`1...`, `m/44'/0'/...`). Only
+//! addresses are derived; nothing here signs Bitcoin transactions.
+//! Compiled in with the `btc` feature.
+
+use crate::errors::{CryptographicError, UserInputError, WalletError, WalletResult};
+use crate::models::{DerivationPath, Network, Wallet};
+use bech32::{ToBase32, Variant};
+use coins_bip32::xkeys::{Parent, XPriv};
+use ripemd::Ripemd160;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest number of addresses derived in one call
+pub const MAX_BTC_ADDRESSES: u32 = 1_000;
+
+/// Bitcoin address layout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BtcScheme {
+    /// BIP84 native segwit P2WPKH (`bc1q...`)
+    #[default]
+    Bip84,
+    /// BIP44 legacy P2PKH (`1...`)
+    Bip44,
+}
+
+impl BtcScheme {
+    /// All schemes, in display order
+    pub const ALL: [BtcScheme; 2] = [Self::Bip84, Self::Bip44];
+
+    /// Command-line name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bip84 => "bip84",
+            Self::Bip44 => "bip44",
+        }
+    }
+
+    /// BIP43 purpose level
+    pub fn purpose(&self) -> u32 {
+        match self {
+            Self::Bip84 => 84,
+            Self::Bip44 => 44,
+        }
+    }
+}
+
+impl FromStr for BtcScheme {
+    type Err = WalletError;
+
+    fn from_str(name: &str) -> WalletResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scheme| scheme.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "scheme".to_string(),
+                    value: name.to_string(),
+                    expected: Self::ALL.map(|scheme| scheme.name()).join(", "),
+                }
+                .into()
+            })
+    }
+}
+
+impl fmt::Display for BtcScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// Bitcoin network an address is encoded for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BtcNetwork {
+    /// Bitcoin mainnet (coin type 0)
+    Mainnet,
+    /// Bitcoin testnet and signet (coin type 1)
+    Testnet,
+}
+
+impl BtcNetwork {
+    /// Bitcoin network matching an Ethereum network: mainnet for mainnet, testnet otherwise
+    pub fn for_wallet_network(network: &Network) -> Self {
+        if network.is_mainnet() {
+            Self::Mainnet
+        } else {
+            Self::Testnet
+        }
+    }
+
+    /// SLIP-44 coin type
+    pub fn coin_type(&self) -> u32 {
+        match self {
+            Self::Mainnet => 0,
+            Self::Testnet => 1,
+        }
+    }
+
+    /// Bech32 human-readable part
+    fn hrp(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "bc",
+            Self::Testnet => "tb",
+        }
+    }
+
+    /// Base58 version byte of pay-to-pubkey-hash addresses
+    fn p2pkh_version(&self) -> u8 {
+        match self {
+            Self::Mainnet => 0x00,
+            Self::Testnet => 0x6f,
+        }
+    }
+}
+
+impl fmt::Display for BtcNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Mainnet => "bitcoin",
+            Self::Testnet => "testnet",
+        })
+    }
+}
+
+/// A derived Bitcoin address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BtcAddress {
+    /// Address index
+    pub index: u32,
+    /// Encoded address
+    pub address: String,
+    /// Full derivation path
+    pub derivation_path: DerivationPath,
+}
+
+/// Bitcoin address derivation
+pub struct BtcService;
+
+impl BtcService {
+    /// Account node `m/purpose'/coin'/account'`
+    pub fn account_path(scheme: BtcScheme, network: BtcNetwork, account: u32) -> WalletResult<DerivationPath> {
+        DerivationPath::root()
+            .hardened_child(scheme.purpose())?
+            .hardened_child(network.coin_type())?
+            .hardened_child(account)
+    }
+
+    /// Derive `count` receive (or change) addresses of `account` from an HD wallet
+    pub fn derive_addresses(
+        wallet: &Wallet,
+        scheme: BtcScheme,
+        network: BtcNetwork,
+        account: u32,
+        change: bool,
+        start: u32,
+        count: u32,
+    ) -> WalletResult<Vec<BtcAddress>> {
+        Self::derive_from_master(&wallet.master_key()?, scheme, network, account, change, start, count)
+    }
+
+    /// Derive `count` receive (or change) addresses of `account` from a BIP32 root key
+    #[tracing::instrument(level = "debug", skip_all, fields(scheme = %scheme, account = account, start = start, count = count))]
+    pub fn derive_from_master(
+        master: &XPriv,
+        scheme: BtcScheme,
+        network: BtcNetwork,
+        account: u32,
+        change: bool,
+        start: u32,
+        count: u32,
+    ) -> WalletResult<Vec<BtcAddress>> {
+        if count == 0 || count > MAX_BTC_ADDRESSES {
+            return Err(UserInputError::ValueOutOfRange {
+                parameter: "count".to_string(),
+                value: count.to_string(),
+                range: format!("1-{}", MAX_BTC_ADDRESSES),
+            }
+            .into());
+        }
+
+        let branch = Self::account_path(scheme, network, account)?.child(u32::from(change))?;
+        let key_error = |path: &DerivationPath| CryptographicError::InvalidDerivationPath {
+            path: path.to_string(),
+            expected: "a derivable BIP32 path".to_string(),
+        };
+        let branch_key = master.derive_path(branch.to_bip32()).map_err(|_| key_error(&branch))?;
+
+        (start..start.saturating_add(count))
+            .map(|index| {
+                let derivation_path = branch.child(index)?;
+                let child = branch_key.derive_child(index).map_err(|_| key_error(&derivation_path))?;
+                let xpub = child.verify_key();
+                let pubkey: &coins_bip32::ecdsa::VerifyingKey = xpub.as_ref();
+                let compressed = pubkey.to_encoded_point(true);
+
+                Ok(BtcAddress {
+                    index,
+                    address: Self::encode_address(compressed.as_bytes(), scheme, network)?,
+                    derivation_path,
+                })
+            })
+            .collect()
+    }
+
+    /// Encode a compressed public key as a `scheme` address
+    pub fn encode_address(pubkey: &[u8], scheme: BtcScheme, network: BtcNetwork) -> WalletResult<String> {
+        let hash = Self::hash160(pubkey);
+        match scheme {
+            BtcScheme::Bip84 => {
+                // Witness version 0 followed by the 20-byte program
+                let mut data = vec![bech32::u5::try_from_u8(0).expect("0 is a valid 5-bit value")];
+                data.extend(hash.to_base32());
+                bech32::encode(network.hrp(), data, Variant::Bech32).map_err(|e| {
+                    CryptographicError::AddressGenerationFailed {
+                        details: e.to_string(),
+                    }
+                    .into()
+                })
+            }
+            BtcScheme::Bip44 => {
+                let mut payload = Vec::with_capacity(21);
+                payload.push(network.p2pkh_version());
+                payload.extend_from_slice(&hash);
+                Ok(bs58::encode(payload).with_check().into_string())
+            }
+        }
+    }
+
+    /// RIPEMD-160 of SHA-256
+    fn hash160(data: &[u8]) -> [u8; 20] {
+        Ripemd160::digest(Sha256::digest(data)).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_reference_addresses() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let network = BtcNetwork::for_wallet_network(wallet.network());
+
+        // BIP84 test vectors
+        let segwit = BtcService::derive_addresses(&wallet, BtcScheme::Bip84, network, 0, false, 0, 2).unwrap();
+        assert_eq!(segwit[0].address, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        assert_eq!(segwit[1].address, "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g");
+        assert_eq!(segwit[1].derivation_path.to_string(), "m/84'/0'/0'/0/1");
+
+        let change = BtcService::derive_addresses(&wallet, BtcScheme::Bip84, network, 0, true, 0, 1).unwrap();
+        assert_eq!(change[0].address, "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el");
+
+        let legacy = BtcService::derive_addresses(&wallet, BtcScheme::Bip44, network, 0, false, 0, 1).unwrap();
+        assert_eq!(legacy[0].address, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+
+        let testnet = BtcService::derive_addresses(&wallet, BtcScheme::Bip84, BtcNetwork::Testnet, 0, false, 0, 1).unwrap();
+        assert!(testnet[0].address.starts_with("tb1q"));
+        assert_eq!(testnet[0].derivation_path.to_string(), "m/84'/1'/0'/0/0");
+
+        assert!(BtcService::derive_addresses(&wallet, BtcScheme::Bip84, network, 0, false, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_scheme_names() {
+        assert_eq!("BIP44".parse::<BtcScheme>().unwrap(), BtcScheme::Bip44);
+        assert!("taproot".parse::<BtcScheme>().is_err());
+        assert_eq!(BtcNetwork::for_wallet_network(&Network::Sepolia), BtcNetwork::Testnet);
+    }
+}
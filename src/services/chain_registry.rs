@@ -0,0 +1,254 @@
+//! # Chain Registry Service
+//!
+//! Offline registry of chain metadata. A snapshot is bundled into the binary;
+//! `network update-registry` can explicitly refresh it from chainlist, storing
+//! the result inside the wallet directory. Nothing is ever fetched implicitly.
+
+use crate::config;
+use crate::errors::{NetworkError, ValidationError, WalletError, WalletResult};
+use crate::models::{ChainInfo, Network};
+use std::path::{Path, PathBuf};
+
+/// Vendored chain metadata snapshot
+const BUNDLED_REGISTRY: &str = include_str!("../../data/chains.json");
+
+/// Where the active registry was loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistrySource {
+    /// Snapshot compiled into the binary
+    Bundled,
+    /// Refreshed registry file in the wallet directory
+    File(PathBuf),
+}
+
+impl std::fmt::Display for RegistrySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistrySource::Bundled => write!(f, "bundled"),
+            RegistrySource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Chain metadata registry
+#[derive(Debug, Clone)]
+pub struct ChainRegistry {
+    chains: Vec<ChainInfo>,
+    source: RegistrySource,
+}
+
+impl ChainRegistry {
+    /// Load the registry bundled with the binary
+    pub fn bundled() -> WalletResult<Self> {
+        Self::from_json(BUNDLED_REGISTRY, RegistrySource::Bundled)
+    }
+
+    /// Parse a registry from chainlist-formatted JSON
+    pub fn from_json(json: &str, source: RegistrySource) -> WalletResult<Self> {
+        let chains: Vec<ChainInfo> = serde_json::from_str(json).map_err(|e| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "chain_registry".to_string(),
+                details: format!("Invalid registry JSON: {}", e),
+            }
+        })?;
+
+        if chains.is_empty() {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: "chain_registry".to_string(),
+                details: "Registry contains no chains".to_string(),
+            }
+            .into());
+        }
+
+        Ok(Self { chains, source })
+    }
+
+    /// Load the registry, preferring a refreshed copy in the wallet directory
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn load(wallet_dir: &Path) -> WalletResult<Self> {
+        let path = Self::registry_path(wallet_dir);
+        if !path.exists() {
+            return Self::bundled();
+        }
+
+        let json = tokio::fs::read_to_string(&path).await?;
+        Self::from_json(&json, RegistrySource::File(path))
+    }
+
+    /// Fetch the registry from `url` and store it in the wallet directory
+    #[tracing::instrument(level = "debug", skip_all, fields(url = url))]
+    pub async fn update(wallet_dir: &Path, url: &str) -> WalletResult<Self> {
+        if !url.starts_with("https://") {
+            return Err(NetworkError::UnsupportedProtocol {
+                protocol: url.split("://").next().unwrap_or(url).to_string(),
+                supported: vec!["https".to_string()],
+            }
+            .into());
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(config::performance::BLOCKCHAIN_OPERATION_TIMEOUT)
+            .build()
+            .map_err(|e| NetworkError::InvalidConfiguration {
+                key: "http_client".to_string(),
+                details: e.to_string(),
+            })?;
+
+        let mut response = client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| NetworkError::ConnectivityFailure {
+                endpoint: url.to_string(),
+                details: e.to_string(),
+            })?;
+
+        // Refuse an oversized registry from its announced size, then by counting, so it is never held whole
+        let limit = config::registry::MAX_REGISTRY_SIZE;
+        if let Some(length) = response.content_length().filter(|&length| length > limit as u64) {
+            return Err(Self::too_large(length));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| NetworkError::ConnectivityFailure {
+            endpoint: url.to_string(),
+            details: e.to_string(),
+        })? {
+            if body.len() + chunk.len() > limit {
+                return Err(Self::too_large((body.len() + chunk.len()) as u64));
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        let json = String::from_utf8_lossy(&body);
+        let path = Self::registry_path(wallet_dir);
+        let registry = Self::from_json(&json, RegistrySource::File(path.clone()))?;
+        registry.save(wallet_dir)?;
+
+        Ok(registry)
+    }
+
+    /// Error for a registry download over `MAX_REGISTRY_SIZE`
+    fn too_large(bytes: u64) -> WalletError {
+        ValidationError::IntegrityCheckFailed {
+            data_type: "chain_registry".to_string(),
+            details: format!("Registry too large: at least {} bytes", bytes),
+        }
+        .into()
+    }
+
+    /// Write the registry into the wallet directory
+    pub fn save(&self, wallet_dir: &Path) -> WalletResult<PathBuf> {
+        std::fs::create_dir_all(wallet_dir)?;
+        let path = Self::registry_path(wallet_dir);
+        let json = serde_json::to_string_pretty(&self.chains)?;
+        crate::utils::write_atomic(&path, json.as_bytes(), 0o644)?;
+        Ok(path)
+    }
+
+    /// Path of the refreshed registry file
+    pub fn registry_path(wallet_dir: &Path) -> PathBuf {
+        wallet_dir.join(config::registry::REGISTRY_FILE_NAME)
+    }
+
+    /// All known chains
+    pub fn chains(&self) -> &[ChainInfo] {
+        &self.chains
+    }
+
+    /// Where this registry was loaded from
+    pub fn source(&self) -> &RegistrySource {
+        &self.source
+    }
+
+    /// Find a chain by EIP-155 chain ID
+    pub fn find_by_chain_id(&self, chain_id: u64) -> Option<&ChainInfo> {
+        self.chains.iter().find(|c| c.chain_id == chain_id)
+    }
+
+    /// Find a chain by wallet network name, chain name, short name, or chain ID
+    pub fn find(&self, query: &str) -> Option<&ChainInfo> {
+        if let Ok(network) = query.parse::<Network>() {
+            return self.find_by_chain_id(network.chain_id());
+        }
+
+        self.chains.iter().find(|c| c.matches(query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_bundled_registry() {
+        let registry = ChainRegistry::bundled().unwrap();
+        assert_eq!(registry.source(), &RegistrySource::Bundled);
+
+        // Every supported network must be resolvable offline
+        for network in config::SUPPORTED_NETWORKS {
+            assert!(registry.find(network).is_some(), "missing {}", network);
+        }
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = ChainRegistry::bundled().unwrap();
+
+        assert_eq!(registry.find("mainnet").unwrap().chain_id, 1);
+        assert_eq!(registry.find("137").unwrap().short_name, "pol");
+        assert_eq!(registry.find("arbitrum one").unwrap().chain_id, 42_161);
+        assert_eq!(registry.find("sep").unwrap().chain_id, 11_155_111);
+        assert!(registry.find("unknown-chain").is_none());
+    }
+
+    #[test]
+    fn test_chainlist_format_parsing() {
+        let json = r#"[{
+            "name": "Test Chain",
+            "chain": "TST",
+            "icon": "ignored",
+            "rpc": ["https://rpc.test"],
+            "faucets": [],
+            "nativeCurrency": {"name": "Test", "symbol": "TST", "decimals": 18},
+            "infoURL": "https://test.example",
+            "shortName": "tst",
+            "chainId": 9999,
+            "networkId": 9999
+        }]"#;
+
+        let registry = ChainRegistry::from_json(json, RegistrySource::Bundled).unwrap();
+        let chain = registry.find_by_chain_id(9999).unwrap();
+        assert_eq!(chain.symbol(), Some("TST"));
+        assert_eq!(chain.info_url.as_deref(), Some("https://test.example"));
+        assert!(chain.explorers.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_registry() {
+        assert!(ChainRegistry::from_json("[]", RegistrySource::Bundled).is_err());
+        assert!(ChainRegistry::from_json("{not json", RegistrySource::Bundled).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_prefers_refreshed_file() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let registry = ChainRegistry::load(temp_dir.path()).await.unwrap();
+        assert_eq!(registry.source(), &RegistrySource::Bundled);
+
+        registry.save(temp_dir.path()).unwrap();
+        let reloaded = ChainRegistry::load(temp_dir.path()).await.unwrap();
+        assert!(matches!(reloaded.source(), RegistrySource::File(_)));
+        assert_eq!(reloaded.chains().len(), registry.chains().len());
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_plain_http() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = ChainRegistry::update(temp_dir.path(), "http://chainid.network/chains.json").await;
+        assert!(result.is_err());
+        assert!(!ChainRegistry::registry_path(temp_dir.path()).exists());
+    }
+}
@@ -0,0 +1,278 @@
+//! # Config Check Service
+//!
+//! Safety checklist for a loaded configuration. Each check yields a finding
+//! with a severity: errors describe settings that put mainnet funds at risk
+//! and make `wallet config validate` fail, warnings are reported but
+//! tolerated (for example weak KDF parameters on a testnet profile).
+
+use crate::config;
+use crate::models::Network;
+use crate::security::permissions::{self, PermissionStatus};
+use crate::WalletConfig;
+use serde::Serialize;
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Check passed
+    Ok,
+    /// Risky but allowed
+    Warning,
+    /// Refused
+    Error,
+}
+
+/// Result of a single checklist item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigFinding {
+    /// Setting the check looked at
+    pub setting: String,
+    /// Check outcome
+    pub severity: Severity,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl ConfigFinding {
+    fn new(setting: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            setting: setting.to_string(),
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+/// Configuration safety checks
+pub struct ConfigCheckService;
+
+impl ConfigCheckService {
+    /// Run every check against the effective configuration
+    pub fn check(config: &WalletConfig) -> Vec<ConfigFinding> {
+        let mut findings = vec![
+            Self::check_network(config),
+            Self::check_kdf(config),
+            Self::check_wallet_dir(config),
+        ];
+        findings.extend(Self::check_rpc_endpoints(config));
+        findings
+    }
+
+    /// Check whether any finding is an error
+    pub fn has_errors(findings: &[ConfigFinding]) -> bool {
+        findings.iter().any(|f| f.severity == Severity::Error)
+    }
+
+    fn check_network(config: &WalletConfig) -> ConfigFinding {
+        // The network was validated when the config was parsed
+        let network = &config.network;
+        if network.is_known() {
+            ConfigFinding::new("network", Severity::Ok, format!("default network is {}", network))
+        } else {
+            ConfigFinding::new(
+                "network",
+                Severity::Ok,
+                format!("default network is custom network {} (chain {})", network.name(), network.chain_id()),
+            )
+        }
+    }
+
+    /// Argon2id cost of the selected profile must reach the minimum enforced for mainnet keystores
+    fn check_kdf(config: &WalletConfig) -> ConfigFinding {
+        let kdf = config.kdf_settings();
+        let minimum_parallel_memory = 8 * kdf.parallelism;
+        if kdf.iterations == 0 || kdf.parallelism == 0 || kdf.memory < minimum_parallel_memory {
+            return ConfigFinding::new(
+                "kdf",
+                Severity::Error,
+                format!(
+                    "invalid Argon2id parameters (t={}, m={} KiB, p={})",
+                    kdf.iterations, kdf.memory, kdf.parallelism
+                ),
+            );
+        }
+
+        if config::is_strong_argon2(kdf.memory, kdf.iterations) {
+            ConfigFinding::new(
+                "kdf",
+                Severity::Ok,
+                format!("Argon2id t={}, m={} KiB ({} profile)", kdf.iterations, kdf.memory, config.kdf_profile.name()),
+            )
+        } else {
+            // Testnets tolerate weaker settings than mainnet
+            let severity = if config.network.is_testnet() {
+                Severity::Warning
+            } else {
+                Severity::Error
+            };
+            ConfigFinding::new(
+                "kdf",
+                severity,
+                format!(
+                    "Argon2id t={}, m={} KiB is below the recommended minimum ({} KiB at t=1 or {} KiB at t={})",
+                    kdf.iterations,
+                    kdf.memory,
+                    config::crypto::DEFAULT_ARGON2_MEMORY,
+                    config::crypto::LOW_MEMORY_ARGON2_MEMORY,
+                    config::crypto::LOW_MEMORY_ARGON2_ITERATIONS
+                ),
+            )
+        }
+    }
+
+    fn check_wallet_dir(config: &WalletConfig) -> ConfigFinding {
+        let dir = &config.wallet_dir;
+        if !dir.exists() {
+            return ConfigFinding::new(
+                "wallet_dir",
+                Severity::Ok,
+                format!("{} will be created with owner-only permissions", dir.display()),
+            );
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Ok(metadata) = std::fs::metadata(dir) {
+                if metadata.permissions().mode() & 0o002 != 0 {
+                    return ConfigFinding::new(
+                        "wallet_dir",
+                        Severity::Error,
+                        format!("{} is world-writable", dir.display()),
+                    );
+                }
+            }
+        }
+
+        match permissions::check(dir) {
+            Ok(PermissionStatus::Insecure { details }) => ConfigFinding::new(
+                "wallet_dir",
+                Severity::Warning,
+                format!("{}: {} (run `wallet doctor --fix`)", dir.display(), details),
+            ),
+            Ok(status) => ConfigFinding::new("wallet_dir", Severity::Ok, format!("{}: {}", dir.display(), status)),
+            Err(e) => ConfigFinding::new("wallet_dir", Severity::Warning, e.to_string()),
+        }
+    }
+
+    fn check_rpc_endpoints(config: &WalletConfig) -> Vec<ConfigFinding> {
+        config
+            .rpc_endpoints
+            .iter()
+            .map(|(network, url)| {
+                let setting = format!("rpc_endpoints.{}", network);
+                let scheme = url.split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase());
+
+                match scheme.as_deref() {
+                    Some("https") | Some("wss") => ConfigFinding::new(&setting, Severity::Ok, url.clone()),
+                    Some("http") | Some("ws") if Self::is_local_url(url) => ConfigFinding::new(
+                        &setting,
+                        Severity::Ok,
+                        format!("{} (local node)", url),
+                    ),
+                    Some("http") | Some("ws") => {
+                        let testnet = network.parse::<Network>().is_ok_and(|network| network.is_testnet());
+                        let severity = if testnet {
+                            Severity::Warning
+                        } else {
+                            Severity::Error
+                        };
+                        ConfigFinding::new(&setting, severity, format!("{} is not encrypted in transit", url))
+                    }
+                    _ => ConfigFinding::new(&setting, Severity::Error, format!("'{}' is not an http(s) or ws(s) URL", url)),
+                }
+            })
+            .collect()
+    }
+
+    /// Plaintext is acceptable for a node on the same machine
+    pub(crate) fn is_local_url(url: &str) -> bool {
+        let host = url
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(url)
+            .split(['/', '?'])
+            .next()
+            .unwrap_or_default();
+        let host = host.rsplit_once(':').map(|(host, _)| host).unwrap_or(host);
+
+        matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::KdfProfile;
+
+    fn severity_of(findings: &[ConfigFinding], setting: &str) -> Severity {
+        findings.iter().find(|f| f.setting == setting).unwrap().severity
+    }
+
+    #[test]
+    fn test_default_config_passes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = WalletConfig {
+            wallet_dir: temp_dir.path().join("wallets"),
+            ..WalletConfig::default()
+        };
+
+        let findings = ConfigCheckService::check(&config);
+        assert!(!ConfigCheckService::has_errors(&findings));
+    }
+
+    #[test]
+    fn test_weak_kdf_refused_on_mainnet_only() {
+        let mut config = WalletConfig {
+            kdf_iterations: 1,
+            kdf_memory: 1024,
+            kdf_profile: KdfProfile::Custom,
+            ..WalletConfig::default()
+        };
+        assert_eq!(severity_of(&ConfigCheckService::check(&config), "kdf"), Severity::Error);
+
+        // The default profile reads changed kdf_* values too; the other presets ignore them
+        let default = WalletConfig { kdf_profile: KdfProfile::Default, ..config.clone() };
+        assert_eq!(severity_of(&ConfigCheckService::check(&default), "kdf"), Severity::Error);
+        for profile in [KdfProfile::LowMemory, KdfProfile::Paranoid] {
+            let preset = WalletConfig { kdf_profile: profile, ..config.clone() };
+            assert_eq!(severity_of(&ConfigCheckService::check(&preset), "kdf"), Severity::Ok);
+        }
+
+        config.network = Network::Sepolia;
+        assert_eq!(severity_of(&ConfigCheckService::check(&config), "kdf"), Severity::Warning);
+
+        config.kdf_memory = config::crypto::LOW_MEMORY_ARGON2_MEMORY;
+        config.kdf_iterations = config::crypto::LOW_MEMORY_ARGON2_ITERATIONS;
+        assert_eq!(severity_of(&ConfigCheckService::check(&config), "kdf"), Severity::Ok);
+    }
+
+    #[test]
+    fn test_plaintext_rpc() {
+        let mut config = WalletConfig::default();
+        config.rpc_endpoints.insert("mainnet".to_string(), "http://eth.example".to_string());
+        config.rpc_endpoints.insert("sepolia".to_string(), "http://sepolia.example".to_string());
+        config.rpc_endpoints.insert("holesky".to_string(), "http://localhost:8545".to_string());
+
+        let findings = ConfigCheckService::check(&config);
+        assert_eq!(severity_of(&findings, "rpc_endpoints.mainnet"), Severity::Error);
+        assert_eq!(severity_of(&findings, "rpc_endpoints.sepolia"), Severity::Warning);
+        assert_eq!(severity_of(&findings, "rpc_endpoints.holesky"), Severity::Ok);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_world_writable_wallet_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::set_permissions(temp_dir.path(), std::fs::Permissions::from_mode(0o777)).unwrap();
+        let config = WalletConfig {
+            wallet_dir: temp_dir.path().to_path_buf(),
+            ..WalletConfig::default()
+        };
+
+        assert_eq!(severity_of(&ConfigCheckService::check(&config), "wallet_dir"), Severity::Error);
+    }
+}
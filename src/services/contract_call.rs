@@ -0,0 +1,283 @@
+//! # Contract Calls
+//!
+//! Encodes calls to any contract from its JSON ABI (a bare ABI array, or a
+//! Hardhat or Foundry artifact with an `abi` field), and decodes what the
+//! contract returns. Arguments are given as text the way `cast` takes
+//! them: `[1,2]` for arrays, `(a,b)` for tuples, and `1.5 ether` or
+//! `30 gwei` for amounts. Address arguments also accept address book
+//! labels.
+
+use crate::errors::{NetworkError, UserInputError, ValidationError, WalletResult};
+use crate::models::{AddressBook, EthereumAddress};
+use ethers::abi::token::{LenientTokenizer, Tokenizer};
+use ethers::abi::{Abi, Function, ParamType, StateMutability, Token};
+use ethers::providers::{Middleware, MiddlewareError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, TransactionRequest, I256};
+use serde::Serialize;
+
+/// One decoded return value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedValue {
+    /// Output name from the ABI, empty when unnamed
+    pub name: String,
+    /// Solidity type
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Value as text: checksummed addresses, decimal numbers, 0x-prefixed bytes
+    pub value: String,
+}
+
+/// Result of an `eth_call`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallOutcome {
+    /// Raw return data
+    Returned(Bytes),
+    /// The node's revert message
+    Reverted(String),
+}
+
+/// Encode and decode contract calls from ABI files
+pub struct ContractCallService;
+
+impl ContractCallService {
+    /// Parse an ABI array, or the `abi` field of a compiler artifact
+    pub fn parse_abi(json: &str) -> WalletResult<Abi> {
+        let invalid = |details: String| ValidationError::IntegrityCheckFailed {
+            data_type: "abi".to_string(),
+            details,
+        };
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        let abi = match value {
+            serde_json::Value::Object(mut artifact) => artifact
+                .remove("abi")
+                .ok_or_else(|| invalid("Expected an ABI array or an artifact with an `abi` field".to_string()))?,
+            abi => abi,
+        };
+        serde_json::from_value(abi).map_err(|e| invalid(e.to_string()).into())
+    }
+
+    /// Function `method` of `abi`, by name or by full signature such as `transfer(address,uint256)`
+    ///
+    /// An overloaded name is resolved by the number of arguments given.
+    pub fn function<'a>(abi: &'a Abi, method: &str, arg_count: usize) -> WalletResult<&'a Function> {
+        let candidates: Vec<&Function> = match method.split_once('(') {
+            Some((name, _)) => abi
+                .functions_by_name(name)
+                .map(|functions| functions.iter().filter(|function| signature(function) == method.replace(' ', "")).collect())
+                .unwrap_or_default(),
+            None => abi
+                .functions_by_name(method)
+                .map(|functions| functions.iter().filter(|function| function.inputs.len() == arg_count).collect())
+                .unwrap_or_default(),
+        };
+
+        match candidates.as_slice() {
+            [function] => Ok(function),
+            _ => {
+                let mut known: Vec<String> = abi.functions().map(signature).collect();
+                known.sort();
+                let expected = match candidates.len() {
+                    0 => format!("one of {}", known.join(", ")),
+                    _ => format!("a full signature to choose between {}", candidates.iter().map(|f| signature(f)).collect::<Vec<_>>().join(", ")),
+                };
+                Err(UserInputError::InvalidParameters {
+                    parameter: "method".to_string(),
+                    value: method.to_string(),
+                    expected,
+                }
+                .into())
+            }
+        }
+    }
+
+    /// Split a comma-separated argument list, keeping commas inside brackets, parentheses and quotes
+    pub fn split_args(args: &str) -> Vec<String> {
+        let mut split = Vec::new();
+        let (mut depth, mut quoted, mut current) = (0usize, false, String::new());
+        for c in args.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                '[' | '(' if !quoted => depth += 1,
+                ']' | ')' if !quoted => depth = depth.saturating_sub(1),
+                ',' if !quoted && depth == 0 => {
+                    split.push(std::mem::take(&mut current).trim().to_string());
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        if !current.trim().is_empty() || !split.is_empty() {
+            split.push(current.trim().to_string());
+        }
+        split
+    }
+
+    /// Calldata of `function` with `args`; top-level address arguments may be address book labels
+    pub fn encode(function: &Function, args: &[String], book: &AddressBook) -> WalletResult<Bytes> {
+        if args.len() != function.inputs.len() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "args".to_string(),
+                value: format!("{} argument(s)", args.len()),
+                expected: format!("{} argument(s) for {}", function.inputs.len(), signature(function)),
+            }
+            .into());
+        }
+
+        let tokens = function
+            .inputs
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| {
+                if param.kind == ParamType::Address && !AddressBook::is_hex_address(arg) {
+                    return Ok(Token::Address(book.resolve(arg)?.into()));
+                }
+                let arg = arg.trim_matches('"');
+                LenientTokenizer::tokenize(&param.kind, arg).map_err(|_| {
+                    UserInputError::InvalidParameters {
+                        parameter: if param.name.is_empty() { "args".to_string() } else { param.name.clone() },
+                        value: arg.to_string(),
+                        expected: param.kind.to_string(),
+                    }
+                    .into()
+                })
+            })
+            .collect::<WalletResult<Vec<Token>>>()?;
+
+        let calldata = function.encode_input(&tokens).map_err(|e| UserInputError::InvalidParameters {
+            parameter: "args".to_string(),
+            value: args.join(","),
+            expected: e.to_string(),
+        })?;
+        Ok(calldata.into())
+    }
+
+    /// Return values of `function` decoded from `data`
+    pub fn decode_output(function: &Function, data: &[u8]) -> WalletResult<Vec<DecodedValue>> {
+        let tokens = function.decode_output(data).map_err(|e| ValidationError::IntegrityCheckFailed {
+            data_type: "return data".to_string(),
+            details: format!("{} does not match {}: {}", Bytes::from(data.to_vec()), signature(function), e),
+        })?;
+        Ok(function
+            .outputs
+            .iter()
+            .zip(&tokens)
+            .map(|(param, token)| DecodedValue {
+                name: param.name.clone(),
+                kind: param.kind.to_string(),
+                value: format_token(token),
+            })
+            .collect())
+    }
+
+    /// Whether `function` only reads state (`view` or `pure`)
+    pub fn is_read_only(function: &Function) -> bool {
+        #[allow(deprecated)]
+        let constant = function.constant.unwrap_or(false);
+        constant || matches!(function.state_mutability, StateMutability::View | StateMutability::Pure)
+    }
+
+    /// Run `data` against `to` with `eth_call` at the latest block, from `from` when given
+    pub async fn call<M: Middleware>(
+        provider: &M,
+        endpoint: &str,
+        to: EthereumAddress,
+        from: Option<EthereumAddress>,
+        data: Bytes,
+    ) -> WalletResult<CallOutcome> {
+        let mut request = TransactionRequest::new().to(Address::from(to)).data(data);
+        if let Some(from) = from {
+            request = request.from(Address::from(from));
+        }
+        let call: TypedTransaction = request.into();
+
+        match provider.call(&call, None).await {
+            Ok(result) => Ok(CallOutcome::Returned(result)),
+            Err(e) => match e.as_error_response() {
+                Some(response) if response.is_revert() => Ok(CallOutcome::Reverted(response.message.clone())),
+                _ => Err(NetworkError::ConnectivityFailure {
+                    endpoint: endpoint.to_string(),
+                    details: e.to_string(),
+                }
+                .into()),
+            },
+        }
+    }
+}
+
+/// `name(type,…)` of a function, the form its selector is hashed from
+pub fn signature(function: &Function) -> String {
+    let inputs: Vec<String> = function.inputs.iter().map(|param| param.kind.to_string()).collect();
+    format!("{}({})", function.name, inputs.join(","))
+}
+
+/// Text form of a decoded value
+fn format_token(token: &Token) -> String {
+    let list = |tokens: &[Token]| tokens.iter().map(format_token).collect::<Vec<_>>().join(", ");
+    match token {
+        Token::Address(address) => EthereumAddress::from(*address).to_string(),
+        Token::Uint(value) => value.to_string(),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => value.clone(),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Array(tokens) | Token::FixedArray(tokens) => format!("[{}]", list(tokens)),
+        Token::Tuple(tokens) => format!("({})", list(tokens)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Address as BookAddress, Network};
+
+    const ABI: &str = r#"{"abi": [
+        {"type":"function","name":"transfer","stateMutability":"nonpayable","inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],"outputs":[{"name":"","type":"bool"}]},
+        {"type":"function","name":"balanceOf","stateMutability":"view","inputs":[{"name":"owner","type":"address"}],"outputs":[{"name":"balance","type":"uint256"}]},
+        {"type":"function","name":"pick","stateMutability":"pure","inputs":[{"name":"ids","type":"uint256[]"},{"name":"note","type":"string"}],"outputs":[{"name":"delta","type":"int256"},{"name":"who","type":"address[]"}]},
+        {"type":"function","name":"pick","stateMutability":"pure","inputs":[{"name":"id","type":"uint256"}],"outputs":[]},
+        {"type":"event","name":"Transfer","anonymous":false,"inputs":[]}
+    ]}"#;
+    const FRIEND: &str = "0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0";
+
+    #[test]
+    fn test_encode_and_decode() {
+        let abi = ContractCallService::parse_abi(ABI).unwrap();
+        let mut book = AddressBook::new(Network::Mainnet);
+        let mut friend = BookAddress::from_string(FRIEND, "mainnet").unwrap();
+        friend.set_label(Some("alice".to_string()));
+        book.add(friend).unwrap();
+
+        let args = ContractCallService::split_args("alice, 1.5 ether");
+        let transfer = ContractCallService::function(&abi, "transfer", args.len()).unwrap();
+        assert!(!ContractCallService::is_read_only(transfer));
+        let calldata = ContractCallService::encode(transfer, &args, &book).unwrap();
+        assert_eq!(
+            hex::encode(&calldata),
+            format!("a9059cbb{:0>64}{:0>64}", FRIEND[2..].to_lowercase(), "14d1120d7b160000")
+        );
+
+        // Overloads resolve by argument count or full signature
+        let args = ContractCallService::split_args(r#"[1,2],"a, b""#);
+        assert_eq!(args, vec!["[1,2]".to_string(), r#""a, b""#.to_string()]);
+        let pick = ContractCallService::function(&abi, "pick", args.len()).unwrap();
+        assert_eq!(signature(pick), "pick(uint256[],string)");
+        assert!(ContractCallService::is_read_only(pick));
+        assert_eq!(ContractCallService::function(&abi, "pick(uint256)", 0).unwrap().inputs.len(), 1);
+        assert!(ContractCallService::function(&abi, "approve", 2).is_err());
+        assert!(ContractCallService::encode(transfer, &ContractCallService::split_args("alice"), &book).is_err());
+        assert!(ContractCallService::encode(transfer, &ContractCallService::split_args("bob,1"), &book).is_err());
+        assert!(ContractCallService::split_args("").is_empty());
+
+        let data = ethers::abi::encode(&[
+            Token::Int(I256::from(-5).into_raw()),
+            Token::Array(vec![Token::Address(FRIEND.parse().unwrap())]),
+        ]);
+        let decoded = ContractCallService::decode_output(pick, &data).unwrap();
+        assert_eq!(decoded[0].value, "-5");
+        assert_eq!(decoded[1].value, format!("[{}]", FRIEND));
+        assert_eq!(decoded[1].kind, "address[]");
+        assert!(ContractCallService::decode_output(pick, &[0u8; 4]).is_err());
+    }
+}
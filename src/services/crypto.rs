@@ -4,36 +4,229 @@
 //! Uses AES-256-GCM with Argon2id key derivation.
 
 use crate::config;
-use crate::errors::{CryptographicError, WalletResult};
+use crate::errors::{AuthenticationError, CryptographicError, UserInputError, WalletError, WalletResult};
 use crate::models::{Keystore, Wallet};
-use crate::models::keystore::KdfParams;
+use crate::models::keystore::{HardwareFactor, KdfParams};
+#[cfg(feature = "native")]
+use crate::security::shred;
+use crate::security::memory;
+use crate::security::tpm::{self, KeySealer, Tpm2Tools};
+use crate::security::yubikey::{self, ChallengeResponder, YubiKeyCli};
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{AeadInPlace, KeyInit},
     Aes256Gcm, Key, Nonce,
 };
 use argon2::{Algorithm, Argon2, Params, Version};
 use pbkdf2::pbkdf2_hmac;
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+#[cfg(feature = "native")]
 use std::path::Path;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use std::sync::Arc;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Outcome of compacting a keystore
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactReport {
+    /// File size before, in bytes
+    pub size_before: usize,
+    /// File size after, in bytes
+    pub size_after: usize,
+    /// Whether any hex field was re-cased or lost a `0x` prefix
+    pub hex_normalized: bool,
+    /// Whether the file was rewritten
+    pub rewritten: bool,
+}
+
+/// Argon2id cost parameters for new keystores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfSettings {
+    /// Memory in KiB
+    pub memory: u32,
+    /// Passes over memory
+    pub iterations: u32,
+    /// Lanes
+    pub parallelism: u32,
+}
+
+/// Hardware a new keystore is bound to, besides its password
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardwareBinding {
+    /// YubiKey HMAC-SHA1 challenge-response in an OTP slot
+    YubiKey {
+        /// Slot programmed for challenge-response (1 or 2)
+        slot: u8,
+    },
+    /// This machine's TPM 2.0
+    Tpm,
+}
+
+/// Devices that answer hardware factors when encrypting or decrypting
+///
+/// The default reaches the real tokens; tests and embedders substitute
+/// their own.
+#[derive(Clone)]
+pub struct HardwareTokens {
+    /// Answers YubiKey challenges
+    pub yubikey: Arc<dyn ChallengeResponder>,
+    /// Seals and unseals TPM-bound secrets
+    pub tpm: Arc<dyn KeySealer>,
+}
+
+impl Default for HardwareTokens {
+    fn default() -> Self {
+        Self { yubikey: Arc::new(YubiKeyCli), tpm: Arc::new(Tpm2Tools) }
+    }
+}
+
+impl HardwareBinding {
+    /// Binding a stored factor came from, to repeat it when re-encrypting
+    pub fn of(factor: &HardwareFactor) -> Self {
+        match factor {
+            HardwareFactor::YubiKey { slot, .. } => Self::YubiKey { slot: *slot },
+            HardwareFactor::Tpm { .. } => Self::Tpm,
+        }
+    }
+}
+
+/// Named Argon2id cost presets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KdfProfile {
+    /// The `kdf_*` config values when set; otherwise 46 MiB at one pass,
+    /// or the low-memory settings in low-resource mode
+    #[default]
+    Default,
+    /// 19 MiB at two passes, the OWASP alternative for small devices
+    LowMemory,
+    /// 256 MiB at three passes, for cold storage
+    Paranoid,
+    /// The `kdf_memory`, `kdf_iterations` and `kdf_parallelism` config values, even in low-resource mode
+    Custom,
+}
+
+impl KdfProfile {
+    /// Every profile
+    pub const ALL: [KdfProfile; 4] = [Self::Default, Self::LowMemory, Self::Paranoid, Self::Custom];
+
+    /// Name accepted by `--kdf-profile` and the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::LowMemory => "low-memory",
+            Self::Paranoid => "paranoid",
+            Self::Custom => "custom",
+        }
+    }
+
+    /// Settings for this profile; `custom`, and `default` when the values were changed, read them from `config`
+    pub fn settings(self, config: &crate::WalletConfig) -> KdfSettings {
+        let configured = (config.kdf_memory, config.kdf_iterations, config.kdf_parallelism);
+        let (memory, iterations, parallelism) = match self {
+            Self::Default if configured != config::get_argon2_config(false) => configured,
+            Self::Default => config::get_argon2_config(config.is_low_resource()),
+            Self::LowMemory => config::get_argon2_config(true),
+            Self::Paranoid => (
+                config::crypto::PARANOID_ARGON2_MEMORY,
+                config::crypto::PARANOID_ARGON2_ITERATIONS,
+                config::crypto::DEFAULT_ARGON2_PARALLELISM,
+            ),
+            Self::Custom => configured,
+        };
+        KdfSettings { memory, iterations, parallelism }
+    }
+}
+
+impl std::str::FromStr for KdfProfile {
+    type Err = WalletError;
+
+    fn from_str(name: &str) -> WalletResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "kdf-profile".to_string(),
+                    value: name.to_string(),
+                    expected: Self::ALL.map(|profile| profile.name()).join(", "),
+                }
+                .into()
+            })
+    }
+}
+
+/// Writer that only counts bytes, to size buffers before serializing into them
+struct ByteCount(usize);
+
+impl std::io::Write for ByteCount {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
 
 /// Cryptographic service for wallet encryption/decryption
 pub struct CryptoService;
 
 impl CryptoService {
     /// Encrypt wallet data and create keystore
+    ///
+    /// Argon2id uses the built-in default cost; `use_argon2 = false` selects
+    /// PBKDF2. Use [`CryptoService::encrypt_wallet_with`] with
+    /// `WalletConfig::kdf_settings` to honor a configuration.
     pub fn encrypt_wallet(
         wallet: &Wallet,
-        password: &str,
+        password: &impl SecretPassword,
         use_argon2: bool,
     ) -> WalletResult<Keystore> {
-        // Serialize wallet data
-        let wallet_data = serde_json::to_vec(wallet).map_err(|e| {
-            CryptographicError::KdfFailed {
-                details: format!("Wallet serialization failed: {}", e),
-            }
-        })?;
+        let settings = KdfProfile::Default.settings(&crate::WalletConfig::default());
+        Self::encrypt(wallet, password.expose_password(), use_argon2.then_some(settings), None)
+    }
+
+    /// Encrypt wallet data with a plain string password
+    #[deprecated(since = "1.1.0", note = "pass the password as a `SecureString` to `encrypt_wallet`")]
+    pub fn encrypt_wallet_str(wallet: &Wallet, password: &str, use_argon2: bool) -> WalletResult<Keystore> {
+        Self::encrypt_wallet(wallet, &SecureString::from(password), use_argon2)
+    }
+
+    /// Encrypt wallet data with Argon2id at the given cost
+    pub fn encrypt_wallet_with(wallet: &Wallet, password: &impl SecretPassword, settings: KdfSettings) -> WalletResult<Keystore> {
+        Self::encrypt(wallet, password.expose_password(), Some(settings), None)
+    }
+
+    /// Encrypt wallet data with Argon2id, also requiring `binding`, as answered by `tokens`, to decrypt
+    pub fn encrypt_wallet_bound(
+        wallet: &Wallet,
+        password: &impl SecretPassword,
+        settings: KdfSettings,
+        binding: HardwareBinding,
+        tokens: &HardwareTokens,
+    ) -> WalletResult<Keystore> {
+        Self::encrypt(wallet, password.expose_password(), Some(settings), Some((binding, tokens)))
+    }
+
+    /// Encrypt with Argon2id at `argon2`, or PBKDF2 when `None`, binding to hardware if given
+    #[tracing::instrument(level = "debug", skip_all, fields(memory = argon2.map(|s| s.memory), iterations = argon2.map(|s| s.iterations)))]
+    fn encrypt(
+        wallet: &Wallet,
+        password: &str,
+        argon2: Option<KdfSettings>,
+        hardware: Option<(HardwareBinding, &HardwareTokens)>,
+    ) -> WalletResult<Keystore> {
+        // Serialize into a buffer sized for the ciphertext, so encrypting in place never reallocates
+        let serialization_error = |e: serde_json::Error| CryptographicError::KdfFailed {
+            details: format!("Wallet serialization failed: {}", e),
+        };
+        let mut size = ByteCount(0);
+        serde_json::to_writer(&mut size, wallet).map_err(serialization_error)?;
+        let mut buffer = Zeroizing::new(Vec::with_capacity(size.0 + config::crypto::TAG_LENGTH));
+        serde_json::to_writer(&mut *buffer, wallet).map_err(serialization_error)?;
+        let _locked = memory::lock(&buffer);
 
         // Generate random salt and nonce
         let mut salt = vec![0u8; config::crypto::SALT_LENGTH];
@@ -42,18 +235,18 @@ impl CryptoService {
         rand::thread_rng().fill_bytes(&mut salt);
         rand::thread_rng().fill_bytes(&mut nonce_bytes);
 
-        // Derive encryption key
-        let mut key_bytes = vec![0u8; config::crypto::KEY_LENGTH];
-        let kdf_params = if use_argon2 {
-            let (memory, iterations, parallelism) = config::get_argon2_config(false);
-
+        // Derive encryption key; declared first so the lock outlives the zeroized key
+        let _locked_key;
+        let mut key_bytes = Zeroizing::new([0u8; config::crypto::KEY_LENGTH]);
+        _locked_key = memory::lock(&*key_bytes);
+        let kdf_params = if let Some(KdfSettings { memory, iterations, parallelism }) = argon2 {
             Self::derive_key_argon2(
                 password.as_bytes(),
                 &salt,
                 memory,
                 iterations,
                 parallelism,
-                &mut key_bytes,
+                &mut *key_bytes,
             )?;
 
             KdfParams::Argon2 {
@@ -70,7 +263,7 @@ impl CryptoService {
                 password.as_bytes(),
                 &salt,
                 PBKDF2_ITERATIONS,
-                &mut key_bytes,
+                &mut *key_bytes,
             );
 
             KdfParams::Pbkdf2 {
@@ -81,50 +274,69 @@ impl CryptoService {
             }
         };
 
-        // Create AES-GCM cipher
-        let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
-        let cipher = Aes256Gcm::new(key);
-        let nonce = Nonce::from_slice(&nonce_bytes);
+        let hardware = hardware.map(|(binding, tokens)| Self::bind_key(binding, tokens, &mut *key_bytes)).transpose()?;
 
-        // Encrypt wallet data
-        let ciphertext = cipher.encrypt(nonce, wallet_data.as_ref()).map_err(|e| {
-            CryptographicError::KdfFailed {
+        // Encrypt wallet data in place; the buffer now holds ciphertext and tag
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key_bytes));
+        cipher
+            .encrypt_in_place(Nonce::from_slice(&nonce_bytes), b"", &mut *buffer)
+            .map_err(|e| CryptographicError::KdfFailed {
                 details: format!("Encryption failed: {}", e),
-            }
-        })?;
+            })?;
 
         // Compute MAC over ciphertext + nonce
-        let mac = Self::compute_mac(&key_bytes, &ciphertext, &nonce_bytes)?;
-
-        // Clear sensitive data
-        key_bytes.zeroize();
+        let mac = Self::compute_mac(&*key_bytes, &buffer, &nonce_bytes)?;
 
         // Create keystore
-        Ok(Keystore::new(
+        let mut keystore = Keystore::new(
             wallet.alias().map(|s| s.to_string()),
-            wallet.address().to_string(),
-            wallet.network().to_string(),
-            ciphertext,
+            *wallet.address(),
+            wallet.network().clone(),
+            std::mem::take(&mut *buffer),
             salt,
             nonce_bytes,
             mac,
             kdf_params,
-        ))
+        );
+        keystore.crypto.hardware = hardware;
+        keystore.metadata.bip39_passphrase = wallet.uses_passphrase();
+        keystore.metadata.derivation_path = wallet.is_hd().then(|| wallet.derivation_path().clone());
+
+        Ok(keystore)
     }
 
-    /// Decrypt keystore and restore wallet
-    pub fn decrypt_wallet(keystore: &Keystore, password: &str) -> WalletResult<Wallet> {
+    /// Decrypt keystore with a plain string password
+    #[deprecated(since = "1.1.0", note = "pass the password as a `SecureString` to `decrypt_wallet`")]
+    pub fn decrypt_wallet_str(keystore: &Keystore, password: &str) -> WalletResult<Wallet> {
+        Self::decrypt_wallet(keystore, &SecureString::from(password))
+    }
+
+    /// Decrypt keystore and restore wallet, asking the real devices for any hardware factor
+    pub fn decrypt_wallet(keystore: &Keystore, password: &impl SecretPassword) -> WalletResult<Wallet> {
+        Self::decrypt_wallet_bound(keystore, password, &HardwareTokens::default())
+    }
+
+    /// Decrypt keystore and restore wallet, with `tokens` answering its hardware factor
+    #[tracing::instrument(level = "debug", skip_all, fields(kdf = %keystore.crypto.kdf))]
+    pub fn decrypt_wallet_bound(keystore: &Keystore, password: &impl SecretPassword, tokens: &HardwareTokens) -> WalletResult<Wallet> {
+        let password = password.expose_password();
+
         // Validate keystore
         keystore.validate()?;
 
-        // Extract cryptographic data
-        let ciphertext = keystore.encrypted_data()?;
+        // Extract cryptographic data; the ciphertext is decoded once and decrypted in place
+        let _locked_plaintext;
+        let mut buffer = Zeroizing::new(Vec::new());
+        keystore.encrypted_data_into(&mut buffer)?;
+        _locked_plaintext = memory::lock(&buffer);
         let salt = keystore.salt()?;
         let nonce = keystore.nonce()?;
         let stored_mac = keystore.mac()?;
 
         // Derive decryption key
-        let mut key_bytes = vec![0u8; config::crypto::KEY_LENGTH];
+        let _locked_key;
+        let mut key_bytes = Zeroizing::new([0u8; config::crypto::KEY_LENGTH]);
+        _locked_key = memory::lock(&*key_bytes);
 
         match keystore.kdf_params() {
             KdfParams::Argon2 { memory, time, parallelism, .. } => {
@@ -134,7 +346,7 @@ impl CryptoService {
                     *memory,
                     *time,
                     *parallelism,
-                    &mut key_bytes,
+                    &mut *key_bytes,
                 )?;
             }
             KdfParams::Pbkdf2 { c, .. } => {
@@ -142,36 +354,28 @@ impl CryptoService {
                     password.as_bytes(),
                     &salt,
                     *c,
-                    &mut key_bytes,
+                    &mut *key_bytes,
                 );
             }
         }
 
-        // Verify MAC
-        let computed_mac = Self::compute_mac(&key_bytes, &ciphertext, &nonce)?;
-        if computed_mac != stored_mac {
-            return Err(CryptographicError::DecryptionFailed {
-                context: "MAC verification failed - wrong password or corrupted data".to_string(),
-            }
-            .into());
+        if let Some(factor) = &keystore.crypto.hardware {
+            Self::mix_hardware_secret(&mut *key_bytes, factor, &Self::hardware_secret(factor, tokens)?);
         }
 
-        // Decrypt wallet data
-        let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
-        let cipher = Aes256Gcm::new(key);
-        let nonce_array = Nonce::from_slice(&nonce);
-
-        let plaintext = cipher.decrypt(nonce_array, ciphertext.as_ref()).map_err(|e| {
-            CryptographicError::DecryptionFailed {
-                context: format!("Decryption failed: {}", e),
-            }
-        })?;
-
-        // Clear sensitive data
-        key_bytes.zeroize();
+        // Verify MAC
+        Self::verify_mac(&*key_bytes, &buffer, &nonce, &stored_mac)?;
+
+        // Decrypt wallet data in place; the buffer now holds the plaintext
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key_bytes));
+        cipher
+            .decrypt_in_place(Nonce::from_slice(&nonce), b"", &mut *buffer)
+            .map_err(|e| CryptographicError::DataCorruption {
+                details: format!("Ciphertext failed authentication after the MAC matched: {}", e),
+            })?;
 
         // Deserialize wallet
-        let wallet: Wallet = serde_json::from_slice(&plaintext).map_err(|e| {
+        let wallet: Wallet = serde_json::from_slice(&buffer).map_err(|e| {
             CryptographicError::DataCorruption {
                 details: format!("Wallet deserialization failed: {}", e),
             }
@@ -183,7 +387,45 @@ impl CryptoService {
         Ok(wallet)
     }
 
+    /// Re-serialize a keystore canonically in place, checking it decrypts before and after
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    #[cfg(feature = "native")]
+    pub async fn compact_keystore(path: &Path, password: &impl SecretPassword) -> WalletResult<CompactReport> {
+        let original = tokio::fs::read_to_string(path).await?;
+        let mut keystore = Self::load_keystore(path).await?;
+        let before = Self::decrypt_wallet(&keystore, password)?;
+
+        let hex_normalized = keystore.normalize_hex();
+        let compacted = keystore.to_json()?;
+        let after = Self::decrypt_wallet(&Keystore::from_json(&compacted)?, password)?;
+        // Account 0's key also covers wallets imported without a mnemonic
+        let key = |wallet: &Wallet| wallet.derive_private_key(0).ok();
+        if after.address() != before.address()
+            || after.mnemonic() != before.mnemonic()
+            || key(&after) != key(&before)
+        {
+            return Err(CryptographicError::DataCorruption {
+                details: "Re-encoded keystore decrypts to a different wallet".to_string(),
+            }
+            .into());
+        }
+
+        let rewritten = compacted != original;
+        if rewritten {
+            crate::utils::write_atomic(path, compacted.as_bytes(), config::fs::KEYSTORE_FILE_PERMISSIONS)?;
+        }
+
+        Ok(CompactReport {
+            size_before: original.len(),
+            size_after: compacted.len(),
+            hex_normalized,
+            rewritten,
+        })
+    }
+
     /// Save encrypted keystore to file
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    #[cfg(feature = "native")]
     pub async fn save_keystore(keystore: &Keystore, path: &Path) -> WalletResult<()> {
         // Validate file path
         crate::utils::validate_file_path(path)?;
@@ -210,31 +452,75 @@ impl CryptoService {
         // Serialize keystore to JSON
         let json_data = keystore.to_json()?;
 
-        // Write to file with secure permissions
-        tokio::fs::write(path, json_data).await.map_err(|e| {
-            crate::errors::FileSystemError::PermissionDenied {
-                path: path.display().to_string(),
-                operation: format!("write: {}", e),
-            }
-        })?;
+        // Write via temp file + rename so a crash never leaves a truncated keystore
+        crate::utils::write_atomic(
+            path,
+            json_data.as_bytes(),
+            config::fs::KEYSTORE_FILE_PERMISSIONS,
+        )?;
 
-        // Set secure file permissions (Unix-like systems)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let permissions = std::fs::Permissions::from_mode(config::fs::KEYSTORE_FILE_PERMISSIONS);
-            std::fs::set_permissions(path, permissions).map_err(|e| {
-                crate::errors::FileSystemError::PermissionDenied {
-                    path: path.display().to_string(),
-                    operation: format!("set_permissions: {}", e),
-                }
-            })?;
+        Ok(())
+    }
+
+    /// Refuse mainnet keystores whose KDF cost is below the minimum
+    pub fn check_kdf_strength(keystore: &Keystore) -> WalletResult<()> {
+        if keystore.metadata.network.is_testnet() {
+            return Ok(());
         }
 
-        Ok(())
+        let (strong, details, minimum) = match &keystore.crypto.kdfparams {
+            KdfParams::Argon2 { memory, time, .. } => (
+                config::is_strong_argon2(*memory, *time),
+                format!("argon2id m={} KiB, t={}", memory, time),
+                format!(
+                    "argon2id m={} KiB at t=1, or m={} KiB at t={}",
+                    config::crypto::DEFAULT_ARGON2_MEMORY,
+                    config::crypto::LOW_MEMORY_ARGON2_MEMORY,
+                    config::crypto::LOW_MEMORY_ARGON2_ITERATIONS
+                ),
+            ),
+            KdfParams::Pbkdf2 { c, .. } => (
+                *c >= config::crypto::MIN_PBKDF2_ITERATIONS,
+                format!("pbkdf2 c={}", c),
+                format!("pbkdf2 c={}", config::crypto::MIN_PBKDF2_ITERATIONS),
+            ),
+        };
+
+        if strong {
+            Ok(())
+        } else {
+            Err(CryptographicError::WeakKdfParameters { details, minimum }.into())
+        }
+    }
+
+    /// Atomically overwrite an existing keystore file
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    #[cfg(feature = "native")]
+    pub async fn replace_keystore(keystore: &Keystore, path: &Path) -> WalletResult<()> {
+        crate::utils::validate_file_path(path)?;
+
+        let json_data = keystore.to_json()?;
+        // The old file is encrypted under the previous password; overwrite it once replaced
+        let previous = shred::retain_previous(path);
+        let written = crate::utils::write_atomic(
+            path,
+            json_data.as_bytes(),
+            config::fs::KEYSTORE_FILE_PERMISSIONS,
+        );
+        if let Some(previous) = previous {
+            match written {
+                Ok(()) => shred::shred_retained(&previous),
+                Err(_) => {
+                    let _ = std::fs::remove_file(&previous);
+                }
+            }
+        }
+        written
     }
 
     /// Load keystore from file
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    #[cfg(feature = "native")]
     pub async fn load_keystore(path: &Path) -> WalletResult<Keystore> {
         // Validate file path
         crate::utils::validate_file_path(path)?;
@@ -271,12 +557,21 @@ impl CryptoService {
             .into());
         }
 
+        // Watch-only wallets share the directory but have no keys to decrypt
+        if crate::models::WatchOnlyWallet::is_watch_only(&json_data) {
+            return Err(crate::errors::UserInputError::WatchOnlyWallet {
+                path: path.display().to_string(),
+            }
+            .into());
+        }
+
         // Parse and validate keystore
         Keystore::from_json(&json_data)
     }
 
     /// Derive key using Argon2id
-    fn derive_key_argon2(
+    #[tracing::instrument(level = "trace", skip_all, fields(memory = memory, iterations = iterations, parallelism = parallelism))]
+    pub(crate) fn derive_key_argon2(
         password: &[u8],
         salt: &[u8],
         memory: u32,
@@ -300,8 +595,66 @@ impl CryptoService {
         Ok(())
     }
 
+    /// Create a factor for `binding` and mix its secret into `key`
+    fn bind_key(binding: HardwareBinding, tokens: &HardwareTokens, key: &mut [u8]) -> WalletResult<HardwareFactor> {
+        let (factor, secret) = match binding {
+            HardwareBinding::YubiKey { slot } => {
+                let factor = yubikey::new_factor(slot);
+                let secret = Self::hardware_secret(&factor, tokens)?;
+                (factor, secret)
+            }
+            HardwareBinding::Tpm => {
+                let mut secret = Zeroizing::new(vec![0u8; config::crypto::KEY_LENGTH]);
+                rand::thread_rng().fill_bytes(&mut secret);
+                let (public, private) = tpm::seal(&*tokens.tpm, &secret)?;
+                (HardwareFactor::Tpm { public, private }, secret)
+            }
+        };
+        Self::mix_hardware_secret(key, &factor, &secret);
+        Ok(factor)
+    }
+
+    /// Secret the hardware behind a stored factor reproduces
+    fn hardware_secret(factor: &HardwareFactor, tokens: &HardwareTokens) -> WalletResult<Zeroizing<Vec<u8>>> {
+        match factor {
+            HardwareFactor::YubiKey { slot, challenge } => yubikey::respond(&*tokens.yubikey, *slot, challenge),
+            HardwareFactor::Tpm { public, private } => tpm::unseal(&*tokens.tpm, public, private),
+        }
+    }
+
+    /// Replace a password-derived key with one that also depends on a hardware secret
+    fn mix_hardware_secret(key: &mut [u8], factor: &HardwareFactor, secret: &[u8]) {
+        use hmac::{Hmac, Mac};
+
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(format!("web3wallet {} v1", factor.kind()).as_bytes());
+        mac.update(secret);
+        let mixed = Zeroizing::new(mac.finalize().into_bytes());
+        key.copy_from_slice(&mixed[..key.len()]);
+    }
+
     /// Compute MAC over ciphertext and nonce
     fn compute_mac(key: &[u8], ciphertext: &[u8], nonce: &[u8]) -> WalletResult<Vec<u8>> {
+        use hmac::Mac;
+
+        Ok(Self::mac_over(key, ciphertext, nonce)?.finalize().into_bytes().to_vec())
+    }
+
+    /// Check a stored MAC in constant time, without materializing the computed one
+    fn verify_mac(key: &[u8], ciphertext: &[u8], nonce: &[u8], expected: &[u8]) -> WalletResult<()> {
+        use hmac::Mac;
+
+        // The key comes from the password, so a mismatch almost always means the wrong one
+        Self::mac_over(key, ciphertext, nonce)?.verify_slice(expected).map_err(|_| {
+            AuthenticationError::WrongPassword {
+                wallet_file: String::new(),
+                attempts_remaining: None,
+            }
+            .into()
+        })
+    }
+
+    fn mac_over(key: &[u8], ciphertext: &[u8], nonce: &[u8]) -> WalletResult<hmac::Hmac<Sha256>> {
         use hmac::{Hmac, Mac};
 
         let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).map_err(|e| {
@@ -313,7 +666,7 @@ impl CryptoService {
         mac.update(ciphertext);
         mac.update(nonce);
 
-        Ok(mac.finalize().into_bytes().to_vec())
+        Ok(mac)
     }
 
     /// Validate password strength
@@ -345,7 +698,7 @@ impl CryptoService {
         }
 
         if !requirements.is_empty() {
-            return Err(crate::errors::AuthenticationError::WeakPassword {
+            return Err(AuthenticationError::WeakPassword {
                 requirements,
             }
             .into());
@@ -355,28 +708,44 @@ impl CryptoService {
     }
 
     /// Generate secure random password
-    pub fn generate_password(length: usize) -> String {
+    ///
+    /// The result always contains at least one character from every class
+    /// required by [`CryptoService::validate_password`].
+    pub fn generate_password(length: usize) -> SecureString {
         use rand::seq::SliceRandom;
 
+        const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+        const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        const DIGITS: &[u8] = b"0123456789";
+        const SPECIAL: &[u8] = b"!@#$%^&*";
         const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789!@#$%^&*";
         let mut rng = rand::thread_rng();
 
-        (0..length)
-            .map(|_| *CHARS.choose(&mut rng).unwrap() as char)
-            .collect()
+        let mut chars: Zeroizing<Vec<u8>> = Zeroizing::new(Vec::with_capacity(length));
+        chars.extend([LOWER, UPPER, DIGITS, SPECIAL].iter().take(length).map(|class| *class.choose(&mut rng).unwrap()));
+        while chars.len() < length {
+            chars.push(*CHARS.choose(&mut rng).unwrap());
+        }
+        chars.shuffle(&mut rng);
+
+        SecureString::new(chars.iter().copied().map(char::from).collect())
     }
 }
 
-/// Secure string that clears memory on drop
-#[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
+/// Secure string that clears memory on drop, locked into RAM under hardened memory
+#[derive(Debug, Zeroize, ZeroizeOnDrop)]
 pub struct SecureString {
     data: String,
+    // Dropped after `data` is zeroized
+    #[zeroize(skip)]
+    locked: memory::LockedRegion,
 }
 
 impl SecureString {
     /// Create new secure string
     pub fn new(data: String) -> Self {
-        Self { data }
+        let locked = memory::lock(data.as_bytes());
+        Self { data, locked }
     }
 
     /// Get string reference
@@ -395,6 +764,12 @@ impl SecureString {
     }
 }
 
+impl Clone for SecureString {
+    fn clone(&self) -> Self {
+        Self::new(self.data.clone())
+    }
+}
+
 impl From<String> for SecureString {
     fn from(data: String) -> Self {
         Self::new(data)
@@ -407,10 +782,111 @@ impl From<&str> for SecureString {
     }
 }
 
+/// Password held in memory that is wiped when dropped
+///
+/// Password-taking APIs accept any of these instead of `&str`, so callers
+/// never need an ordinary `String` copy of a secret. Code still holding a
+/// `&str` wraps it with `SecureString::from`.
+pub trait SecretPassword: Send + Sync {
+    /// The password itself
+    fn expose_password(&self) -> &str;
+}
+
+impl<T: SecretPassword + ?Sized> SecretPassword for &T {
+    fn expose_password(&self) -> &str {
+        (**self).expose_password()
+    }
+}
+
+impl SecretPassword for SecureString {
+    fn expose_password(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl SecretPassword for Zeroizing<String> {
+    fn expose_password(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl SecretPassword for crate::models::SecretString {
+    fn expose_password(&self) -> &str {
+        self.expose()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Wallet;
+    use crate::models::{Network, Wallet};
+
+    #[test]
+    fn test_weak_kdf_refused_for_mainnet() {
+        let keystore_for = |network: Network, memory: u32, time: u32| Keystore::with_argon2(
+            None,
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94".parse().unwrap(),
+            network,
+            vec![1u8; 32],
+            vec![2u8; 32],
+            vec![3u8; 12],
+            vec![4u8; 32],
+            memory,
+            time,
+            1,
+        );
+
+        assert!(CryptoService::check_kdf_strength(&keystore_for(Network::Mainnet, 1024, 1)).is_err());
+        assert!(CryptoService::check_kdf_strength(&keystore_for(Network::Sepolia, 1024, 1)).is_ok());
+        assert!(CryptoService::check_kdf_strength(&keystore_for(Network::Mainnet, 19_456, 2)).is_ok());
+        assert!(CryptoService::check_kdf_strength(&keystore_for(Network::Mainnet, 47_104, 1)).is_ok());
+        // Custom chains may hold real funds, so they get the mainnet minimum
+        let devnet = Network::custom("devnet", 1337).unwrap();
+        assert!(CryptoService::check_kdf_strength(&keystore_for(devnet, 1024, 1)).is_err());
+
+        let pbkdf2 = Keystore::with_pbkdf2(
+            None,
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94".parse().unwrap(),
+            Network::Mainnet,
+            vec![1u8; 32],
+            vec![2u8; 32],
+            vec![3u8; 12],
+            vec![4u8; 32],
+            1_000,
+        );
+        assert!(CryptoService::check_kdf_strength(&pbkdf2).is_err());
+    }
+
+    #[test]
+    fn test_kdf_profile_recorded_in_keystore() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let config = crate::WalletConfig {
+            kdf_memory: 1024,
+            kdf_iterations: 2,
+            kdf_parallelism: 1,
+            kdf_profile: KdfProfile::Custom,
+            ..crate::WalletConfig::default()
+        };
+
+        let keystore = CryptoService::encrypt_wallet_with(&wallet, &SecureString::from("TestPassword123!"), config.kdf_settings()).unwrap();
+        assert!(matches!(keystore.crypto.kdfparams, KdfParams::Argon2 { memory: 1024, time: 2, parallelism: 1, .. }));
+        assert_eq!(CryptoService::decrypt_wallet(&keystore, &SecureString::from("TestPassword123!")).unwrap().address(), wallet.address());
+
+        let paranoid = KdfProfile::Paranoid.settings(&config);
+        assert_eq!((paranoid.memory, paranoid.iterations), (262_144, 3));
+
+        // Changed kdf_* values apply under the default profile too
+        let default = crate::WalletConfig { kdf_profile: KdfProfile::Default, ..config.clone() };
+        assert_eq!(default.kdf_settings(), config.kdf_settings());
+        let untouched = KdfProfile::Default.settings(&crate::WalletConfig::default());
+        assert_eq!(untouched.memory, crate::config::crypto::DEFAULT_ARGON2_MEMORY);
+        assert_eq!("LOW-MEMORY".parse::<KdfProfile>().unwrap(), KdfProfile::LowMemory);
+    }
 
     #[test]
     fn test_password_validation() {
@@ -435,8 +911,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_wallet_encryption_decryption() {
-        let wallet = Wallet::generate(12, "mainnet", Some("test".to_string())).unwrap();
-        let password = "TestPassword123!";
+        let wallet = Wallet::generate(12, &Network::Mainnet, Some("test".to_string())).unwrap();
+        let password = &SecureString::from("TestPassword123!");
 
         // Encrypt wallet
         let keystore = CryptoService::encrypt_wallet(&wallet, password, true).unwrap();
@@ -452,27 +928,100 @@ mod tests {
         assert_eq!(wallet.mnemonic(), restored_wallet.mnemonic());
         assert_eq!(wallet.network(), restored_wallet.network());
         assert_eq!(wallet.alias(), restored_wallet.alias());
+
+        // The deprecated string form still works for existing callers
+        #[allow(deprecated)]
+        let legacy = CryptoService::decrypt_wallet_str(&keystore, "TestPassword123!").unwrap();
+        assert_eq!(legacy.address(), wallet.address());
+    }
+
+    #[tokio::test]
+    async fn test_account_path_round_trip() {
+        let mut wallet = Wallet::generate(12, &Network::Mainnet, None).unwrap();
+        wallet.set_account(4).unwrap();
+
+        let keystore = CryptoService::encrypt_wallet(&wallet, &SecureString::from("TestPassword123!"), true).unwrap();
+        assert_eq!(keystore.metadata.derivation_path.as_ref(), Some(wallet.derivation_path()));
+
+        let restored = CryptoService::decrypt_wallet(&keystore, &SecureString::from("TestPassword123!")).unwrap();
+        assert_eq!(restored.account(), 4);
+        assert_eq!(restored.address(), wallet.address());
     }
 
     #[tokio::test]
     async fn test_wrong_password_decryption() {
-        let wallet = Wallet::generate(12, "mainnet", None).unwrap();
-        let password = "TestPassword123!";
-        let wrong_password = "WrongPassword123!";
+        let wallet = Wallet::generate(12, &Network::Mainnet, None).unwrap();
+        let password = &SecureString::from("TestPassword123!");
+        let wrong_password = &SecureString::from("WrongPassword123!");
 
         // Encrypt with correct password
         let keystore = CryptoService::encrypt_wallet(&wallet, password, true).unwrap();
 
         // Try to decrypt with wrong password
         let result = CryptoService::decrypt_wallet(&keystore, wrong_password);
-        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().code(), "AUTH_001");
+    }
+
+    #[tokio::test]
+    async fn test_save_keystore_leaves_no_temp_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("wallet.json");
+        let wallet = Wallet::generate(12, &Network::Mainnet, None).unwrap();
+        let keystore = CryptoService::encrypt_wallet(&wallet, &SecureString::from("TestPassword123!"), true).unwrap();
+
+        CryptoService::save_keystore(&keystore, &path).await.unwrap();
+
+        let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+        let loaded = CryptoService::load_keystore(&path).await.unwrap();
+        assert_eq!(loaded.metadata.address, keystore.metadata.address);
+    }
+
+    #[tokio::test]
+    async fn test_compact_keystore() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("wallet.json");
+        let wallet = Wallet::generate(12, &Network::Mainnet, None).unwrap();
+        let keystore = CryptoService::encrypt_wallet(&wallet, &SecureString::from("TestPassword123!"), true).unwrap();
+
+        // A hand-edited copy: pretty-printed with an uppercase, 0x-prefixed MAC
+        let mut edited: serde_json::Value = serde_json::from_str(&keystore.to_json().unwrap()).unwrap();
+        edited["crypto"]["mac"] = format!("0x{}", keystore.crypto.mac.to_uppercase()).into();
+        std::fs::write(&path, serde_json::to_string_pretty(&edited).unwrap()).unwrap();
+
+        assert!(CryptoService::compact_keystore(&path, &SecureString::from("WrongPassword123!")).await.is_err());
+        let report = CryptoService::compact_keystore(&path, &SecureString::from("TestPassword123!")).await.unwrap();
+        assert!(report.rewritten && report.hex_normalized);
+        assert!(report.size_after < report.size_before);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), keystore.to_json().unwrap());
+
+        let again = CryptoService::compact_keystore(&path, &SecureString::from("TestPassword123!")).await.unwrap();
+        assert!(!again.rewritten);
     }
 
     #[test]
     fn test_password_generation() {
         let password = CryptoService::generate_password(16);
         assert_eq!(password.len(), 16);
-        assert!(CryptoService::validate_password(&password).is_ok());
+        assert!(CryptoService::validate_password(password.as_str()).is_ok());
+    }
+
+    #[test]
+    fn test_generated_password_has_every_class() {
+        let classes: [fn(&char) -> bool; 4] = [
+            char::is_ascii_lowercase,
+            char::is_ascii_uppercase,
+            char::is_ascii_digit,
+            |c| "!@#$%^&*".contains(*c),
+        ];
+        for _ in 0..200 {
+            let password = CryptoService::generate_password(12);
+            assert!(classes.iter().all(|class| password.as_str().chars().any(|c| class(&c))));
+        }
+        // Shorter than the number of classes: as many distinct classes as characters
+        let short = CryptoService::generate_password(2);
+        assert_eq!(short.len(), 2);
+        assert_eq!(classes.iter().filter(|class| short.as_str().chars().any(|c| class(&c))).count(), 2);
     }
 
     #[test]
@@ -0,0 +1,163 @@
+//! # Message Encryption
+//!
+//! The `x25519-xsalsa20-poly1305` scheme behind MetaMask's
+//! `eth_getEncryptionPublicKey` and `eth_decrypt`. An account's Ethereum
+//! private key doubles as its X25519 secret key; anyone holding the
+//! matching encryption public key can seal a note to it with a fresh
+//! ephemeral key, and only the account can open it. Encrypted notes are
+//! the same JSON documents MetaMask and eth-sig-util read and write.
+
+use crate::errors::{CryptographicError, UserInputError, ValidationError, WalletResult};
+use crate::models::Wallet;
+use crate::services::nacl;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// Version string of the only supported scheme
+pub const ENCRYPTION_VERSION: &str = "x25519-xsalsa20-poly1305";
+
+/// An encrypted note, as produced by eth-sig-util's `encrypt`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedMessage {
+    /// Scheme identifier ("x25519-xsalsa20-poly1305")
+    pub version: String,
+    /// 24-byte nonce (base64)
+    pub nonce: String,
+    /// Sender's one-time X25519 public key (base64)
+    pub ephem_public_key: String,
+    /// Poly1305 tag followed by the XSalsa20 ciphertext (base64)
+    pub ciphertext: String,
+}
+
+/// An account's encryption key pair
+pub struct EncryptionKey {
+    secret: Zeroizing<[u8; 32]>,
+}
+
+impl EncryptionKey {
+    /// Encryption key of account `index`
+    pub fn from_wallet(wallet: &Wallet, index: u32) -> WalletResult<Self> {
+        Ok(Self::from_private_key(wallet.signing_key(index)?.signer().to_bytes().into()))
+    }
+
+    /// Encryption key for a raw Ethereum private key
+    pub fn from_private_key(private_key: [u8; 32]) -> Self {
+        Self {
+            secret: Zeroizing::new(private_key),
+        }
+    }
+
+    /// Public key others encrypt to (base64, as `eth_getEncryptionPublicKey` returns it)
+    pub fn public_key(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(nacl::public_key(&self.secret))
+    }
+
+    /// Decrypt a note sealed to this key
+    pub fn decrypt(&self, message: &EncryptedMessage) -> WalletResult<Zeroizing<String>> {
+        if message.version != ENCRYPTION_VERSION {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: "encrypted message".to_string(),
+                details: format!("Unsupported version '{}', expected {}", message.version, ENCRYPTION_VERSION),
+            }
+            .into());
+        }
+
+        let nonce: [u8; nacl::NONCE_LENGTH] = decode_field(&message.nonce, "nonce")?;
+        let sender: [u8; 32] = decode_field(&message.ephem_public_key, "ephemPublicKey")?;
+        let ciphertext = decode(&message.ciphertext, "ciphertext")?;
+        let plaintext = nacl::open(&ciphertext, &nonce, &sender, &self.secret).ok_or_else(|| {
+            CryptographicError::DataCorruption {
+                details: "The message was not encrypted to this account or has been altered".to_string(),
+            }
+        })?;
+
+        let text = String::from_utf8(plaintext.to_vec()).map_err(|_| CryptographicError::DataCorruption {
+            details: "Decrypted message is not UTF-8 text".to_string(),
+        })?;
+        Ok(Zeroizing::new(text))
+    }
+}
+
+/// MetaMask-compatible note encryption
+pub struct EthEncryptionService;
+
+impl EthEncryptionService {
+    /// Encrypt `plaintext` to the holder of the base64 encryption `public_key`
+    pub fn encrypt(public_key: &str, plaintext: &str) -> WalletResult<EncryptedMessage> {
+        let recipient: [u8; 32] = decode_field(public_key, "public key").map_err(|_| UserInputError::InvalidParameters {
+            parameter: "public-key".to_string(),
+            value: public_key.to_string(),
+            expected: "a base64 X25519 encryption public key (see `wallet encryption-key`)".to_string(),
+        })?;
+
+        let mut ephemeral = Zeroizing::new([0u8; 32]);
+        let mut nonce = [0u8; nacl::NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut *ephemeral);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let sealed = nacl::seal(plaintext.as_bytes(), &nonce, &recipient, &ephemeral);
+
+        let engine = base64::engine::general_purpose::STANDARD;
+        Ok(EncryptedMessage {
+            version: ENCRYPTION_VERSION.to_string(),
+            nonce: engine.encode(nonce),
+            ephem_public_key: engine.encode(nacl::public_key(&ephemeral)),
+            ciphertext: engine.encode(sealed),
+        })
+    }
+}
+
+fn decode(value: &str, name: &str) -> WalletResult<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD.decode(value.trim()).map_err(|e| {
+        ValidationError::IntegrityCheckFailed {
+            data_type: "encrypted message".to_string(),
+            details: format!("Invalid {} base64: {}", name, e),
+        }
+        .into()
+    })
+}
+
+fn decode_field<const N: usize>(value: &str, name: &str) -> WalletResult<[u8; N]> {
+    decode(value, name)?.try_into().map_err(|bytes: Vec<u8>| {
+        ValidationError::IntegrityCheckFailed {
+            data_type: "encrypted message".to_string(),
+            details: format!("Invalid {} length: {} bytes, expected {}", name, bytes.len(), N),
+        }
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metamask_vector() {
+        // Vector from eth-sig-util's encryption tests
+        let bob = EncryptionKey::from_private_key(
+            hex::decode("7e5374ec2ef0d91761a6e72fdf8f6ac665519bfdf6da0a2329cf0d804514b816").unwrap().try_into().unwrap(),
+        );
+        assert_eq!(bob.public_key(), "C5YMNdqE4kLgxQhJO1MfuQcHP5hjVSXzamzd/TxlR0U=");
+
+        let message = EncryptedMessage {
+            version: ENCRYPTION_VERSION.to_string(),
+            nonce: "1dvWO7uOnBnO7iNDJ9kO9pTasLuKNlej".to_string(),
+            ephem_public_key: "FBH1/pAEHOOW14Lu3FWkgV3qOEcuL78Zy+qW1RwzMXQ=".to_string(),
+            ciphertext: "f8kBcl/NCyf3sybfbwAKk/np2Bzt9lRVkZejr6uh5FgnNlH/ic62DZzy".to_string(),
+        };
+        assert_eq!(bob.decrypt(&message).unwrap().as_str(), "My name is Satoshi Buterin");
+
+        // Round trip through a fresh encryption, which only bob can open
+        let sealed = EthEncryptionService::encrypt(&bob.public_key(), "meet at noon").unwrap();
+        assert_eq!(bob.decrypt(&sealed).unwrap().as_str(), "meet at noon");
+        let alice = EncryptionKey::from_private_key([1; 32]);
+        assert_eq!(alice.decrypt(&sealed).unwrap_err().code(), "CRYPTO_005");
+
+        let json = serde_json::to_value(&sealed).unwrap();
+        assert!(json.get("ephemPublicKey").is_some());
+        assert!(EthEncryptionService::encrypt("not-a-key", "x").is_err());
+        assert!(bob.decrypt(&EncryptedMessage { version: "x25519".to_string(), ..message }).is_err());
+    }
+}
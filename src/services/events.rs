@@ -0,0 +1,38 @@
+//! # Wallet Events
+//!
+//! Observer hooks for applications embedding [`WalletManager`](super::WalletManager).
+//! Register a [`WalletObserver`] to drive audit logs, metrics, or UI updates
+//! from manager operations. Observers only ever see public data: addresses,
+//! paths, error values, and signed digests.
+
+use crate::errors::WalletError;
+use crate::models::{EthereumAddress, Network};
+use std::path::Path;
+
+/// How a wallet came into existence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletOrigin {
+    /// Fresh mnemonic generated by the manager
+    Generated,
+    /// Imported from a mnemonic phrase, or the entropy it encodes
+    Mnemonic,
+    /// Imported from a raw BIP39 seed without its mnemonic
+    Seed,
+    /// Imported from a raw private key
+    PrivateKey,
+}
+
+/// Callbacks for wallet manager events; every method defaults to a no-op
+pub trait WalletObserver: Send + Sync {
+    /// A wallet was generated or imported
+    fn on_wallet_created(&self, _address: &EthereumAddress, _network: &Network, _origin: WalletOrigin) {}
+
+    /// A keystore was written to disk
+    fn on_keystore_saved(&self, _address: &EthereumAddress, _path: &Path) {}
+
+    /// Decrypting a keystore failed (wrong password, corruption, ...)
+    fn on_decrypt_failed(&self, _path: &Path, _error: &WalletError) {}
+
+    /// A signature was produced over `hash`
+    fn on_signature_produced(&self, _address: &str, _hash: &[u8; 32]) {}
+}
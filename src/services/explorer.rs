@@ -0,0 +1,397 @@
+//! # Block Explorer
+//!
+//! Client for the Etherscan API and the explorers that copy it (Blockscout,
+//! Routescan and the per-chain Etherscan forks). It is optional and only
+//! used when the config has an `[explorer]` section:
+//!
+//! ```toml
+//! [explorer]
+//! api_key = "..."
+//! # Etherscan's multichain API when unset
+//! url = "https://api.etherscan.io/v2/api"
+//! ```
+//!
+//! The explorer supplies what a node cannot: the ABIs and names of verified
+//! contracts, and the transaction history of an address. Each request
+//! tells the explorer which address is being looked at, so nothing is
+//! fetched unless a command asks for it.
+
+use crate::config;
+use crate::errors::{NetworkError, ValidationError, WalletResult};
+use crate::models::EthereumAddress;
+use chrono::{DateTime, Utc};
+use ethers::abi::Abi;
+use ethers::types::{H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Explorer settings (`[explorer]` in the config file)
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExplorerConfig {
+    /// API key of the explorer account
+    pub api_key: String,
+    /// Etherscan-compatible API endpoint; [`config::explorer::DEFAULT_API_URL`] when unset
+    pub url: Option<String>,
+}
+
+impl std::fmt::Debug for ExplorerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExplorerConfig")
+            .field("api_key", &"<redacted>")
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+/// A verified contract
+#[derive(Debug, Clone)]
+pub struct VerifiedContract {
+    /// Contract name from the verified source
+    pub name: String,
+    /// Contract ABI
+    pub abi: Abi,
+    /// Implementation behind the contract, when the explorer knows it is a proxy
+    pub implementation: Option<EthereumAddress>,
+}
+
+/// One transaction from an address's history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExplorerTransaction {
+    /// Transaction hash
+    pub hash: H256,
+    /// Block the transaction was included in
+    pub block_number: u64,
+    /// Block time
+    pub timestamp: DateTime<Utc>,
+    /// Sender
+    pub from: EthereumAddress,
+    /// Recipient; `None` for contract creations
+    pub to: Option<EthereumAddress>,
+    /// Contract created by the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract_address: Option<EthereumAddress>,
+    /// Value in wei
+    pub value: U256,
+    /// Gas used
+    pub gas_used: u64,
+    /// Whether execution failed
+    pub failed: bool,
+    /// Called function, from the explorer's signature database (e.g. `transfer(address _to, uint256 _value)`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+}
+
+impl ExplorerTransaction {
+    /// Name of the called function without its parameters, or the selector when the explorer has no name
+    pub fn method(&self) -> Option<&str> {
+        self.function.as_deref().map(|function| function.split('(').next().unwrap_or(function))
+    }
+}
+
+/// Transaction as the explorer lists it: every field a string
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTransaction {
+    hash: String,
+    block_number: String,
+    time_stamp: String,
+    from: String,
+    #[serde(default)]
+    to: String,
+    #[serde(default)]
+    contract_address: String,
+    value: String,
+    #[serde(default)]
+    gas_used: String,
+    #[serde(default)]
+    is_error: String,
+    #[serde(default)]
+    method_id: String,
+    #[serde(default)]
+    function_name: String,
+}
+
+/// Source code entry of `getsourcecode`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RawSource {
+    #[serde(default)]
+    contract_name: String,
+    #[serde(rename = "ABI", default)]
+    abi: String,
+    #[serde(default)]
+    implementation: String,
+}
+
+/// Client for an Etherscan-compatible explorer API on one chain
+pub struct ExplorerClient {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    chain_id: u64,
+}
+
+impl ExplorerClient {
+    /// Client for `chain_id`; plaintext URLs are refused except to this machine
+    pub fn new(explorer: &ExplorerConfig, chain_id: u64) -> WalletResult<Self> {
+        let url = explorer.url.clone().unwrap_or_else(|| config::explorer::DEFAULT_API_URL.to_string());
+        let scheme = url.split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase());
+        let local = crate::services::ConfigCheckService::is_local_url(&url);
+        if !(scheme.as_deref() == Some("https") || (scheme.as_deref() == Some("http") && local)) {
+            return Err(NetworkError::UnsupportedProtocol {
+                protocol: scheme.unwrap_or(url),
+                supported: vec!["https".to_string()],
+            }
+            .into());
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(config::performance::BLOCKCHAIN_OPERATION_TIMEOUT)
+            .build()
+            .map_err(|e| NetworkError::InvalidConfiguration {
+                key: "http_client".to_string(),
+                details: e.to_string(),
+            })?;
+        Ok(Self {
+            client,
+            url,
+            api_key: explorer.api_key.clone(),
+            chain_id,
+        })
+    }
+
+    /// Verified source of the contract at `address`, or `None` when it is not verified
+    pub async fn contract(&self, address: EthereumAddress) -> WalletResult<Option<VerifiedContract>> {
+        let result = self
+            .get(&[("module", "contract"), ("action", "getsourcecode"), ("address", &address.to_lowercase())])
+            .await?;
+        Self::parse_source(result)
+    }
+
+    /// Verified contract at `address` with the ABI to call it through: the implementation's when it is a proxy
+    pub async fn callable_contract(&self, address: EthereumAddress) -> WalletResult<Option<VerifiedContract>> {
+        let Some(mut contract) = self.contract(address).await? else {
+            return Ok(None);
+        };
+        if let Some(implementation) = contract.implementation.filter(|implementation| *implementation != address) {
+            if let Some(verified) = self.contract(implementation).await? {
+                contract.abi = verified.abi;
+            }
+        }
+        Ok(Some(contract))
+    }
+
+    /// Names of the verified contracts among `addresses`; unverified ones and externally owned accounts are left out
+    pub async fn contract_names(
+        &self,
+        addresses: impl IntoIterator<Item = EthereumAddress>,
+    ) -> WalletResult<BTreeMap<EthereumAddress, String>> {
+        let mut names = BTreeMap::new();
+        let mut seen = std::collections::BTreeSet::new();
+        for address in addresses {
+            if !seen.insert(address) {
+                continue;
+            }
+            if let Some(contract) = self.contract(address).await? {
+                names.insert(address, contract.name);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Normal transactions sent from or to `address`, newest first
+    pub async fn transactions(&self, address: EthereumAddress, page: usize, limit: usize) -> WalletResult<Vec<ExplorerTransaction>> {
+        let (page, limit) = (page.max(1).to_string(), limit.to_string());
+        let result = self
+            .get(&[
+                ("module", "account"),
+                ("action", "txlist"),
+                ("address", &address.to_lowercase()),
+                ("startblock", "0"),
+                ("endblock", "99999999"),
+                ("page", &page),
+                ("offset", &limit),
+                ("sort", "desc"),
+            ])
+            .await?;
+        Self::parse_transactions(result)
+    }
+
+    /// `result` of a request, after checking the explorer's status envelope
+    async fn get(&self, params: &[(&str, &str)]) -> WalletResult<serde_json::Value> {
+        let chain_id = self.chain_id.to_string();
+        let response = self
+            .client
+            .get(&self.url)
+            .query(&[("chainid", chain_id.as_str())])
+            .query(params)
+            .query(&[("apikey", self.api_key.as_str())])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            // The URL carries the API key; keep it out of error messages
+            .map_err(|e| self.connectivity(e.without_url().to_string()))?;
+        let body = response.bytes().await.map_err(|e| self.connectivity(e.without_url().to_string()))?;
+        if body.len() > config::explorer::MAX_RESPONSE_SIZE {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: "explorer response".to_string(),
+                details: format!("Response too large: {} bytes", body.len()),
+            }
+            .into());
+        }
+
+        let json: serde_json::Value = serde_json::from_slice(&body).map_err(|e| ValidationError::IntegrityCheckFailed {
+            data_type: "explorer response".to_string(),
+            details: e.to_string(),
+        })?;
+        Self::check_envelope(json, &self.url)
+    }
+
+    fn connectivity(&self, details: String) -> NetworkError {
+        NetworkError::ConnectivityFailure {
+            endpoint: self.url.clone(),
+            details,
+        }
+    }
+
+    /// Unwrap `{"status", "message", "result"}`; an empty list is reported as status 0 too
+    fn check_envelope(json: serde_json::Value, endpoint: &str) -> WalletResult<serde_json::Value> {
+        let status = json.get("status").and_then(serde_json::Value::as_str).unwrap_or("1");
+        let message = json.get("message").and_then(serde_json::Value::as_str).unwrap_or_default().to_string();
+        let result = json.get("result").cloned().unwrap_or(serde_json::Value::Null);
+        if status == "1" || message.starts_with("No transactions found") {
+            return Ok(result);
+        }
+
+        let details = result.as_str().map_or(message, str::to_string);
+        if details.to_ascii_lowercase().contains("rate limit") {
+            return Err(NetworkError::RateLimitExceeded {
+                retry_after: std::time::Duration::from_secs(1),
+            }
+            .into());
+        }
+        Err(NetworkError::ConnectivityFailure {
+            endpoint: endpoint.to_string(),
+            details,
+        }
+        .into())
+    }
+
+    fn parse_source(result: serde_json::Value) -> WalletResult<Option<VerifiedContract>> {
+        let malformed = |details: String| ValidationError::IntegrityCheckFailed {
+            data_type: "explorer source".to_string(),
+            details,
+        };
+        let sources: Vec<RawSource> = serde_json::from_value(result).map_err(|e| malformed(e.to_string()))?;
+        let Some(source) = sources.into_iter().next() else {
+            return Ok(None);
+        };
+        // Unverified contracts come back with an empty name and a message in place of the ABI
+        if source.contract_name.is_empty() || !source.abi.starts_with('[') {
+            return Ok(None);
+        }
+        Ok(Some(VerifiedContract {
+            name: source.contract_name,
+            abi: serde_json::from_str(&source.abi).map_err(|e| malformed(e.to_string()))?,
+            implementation: source.implementation.parse().ok(),
+        }))
+    }
+
+    fn parse_transactions(result: serde_json::Value) -> WalletResult<Vec<ExplorerTransaction>> {
+        let raw: Vec<RawTransaction> = serde_json::from_value(result).map_err(|e| ValidationError::IntegrityCheckFailed {
+            data_type: "explorer transactions".to_string(),
+            details: e.to_string(),
+        })?;
+        raw.into_iter()
+            .map(|tx| {
+                let malformed = |field: &str| ValidationError::IntegrityCheckFailed {
+                    data_type: "explorer transactions".to_string(),
+                    details: format!("Bad {} in transaction {}", field, tx.hash),
+                };
+                let timestamp = tx
+                    .time_stamp
+                    .parse()
+                    .ok()
+                    .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
+                    .ok_or_else(|| malformed("timeStamp"))?;
+                let function = match (tx.function_name.is_empty(), tx.method_id.as_str()) {
+                    (false, _) => Some(tx.function_name.clone()),
+                    (true, "" | "0x") => None,
+                    (true, selector) => Some(selector.to_string()),
+                };
+                Ok(ExplorerTransaction {
+                    hash: tx.hash.parse().map_err(|_| malformed("hash"))?,
+                    block_number: tx.block_number.parse().map_err(|_| malformed("blockNumber"))?,
+                    timestamp,
+                    from: tx.from.parse().map_err(|_| malformed("from"))?,
+                    to: tx.to.parse().ok(),
+                    contract_address: tx.contract_address.parse().ok(),
+                    value: U256::from_dec_str(&tx.value).map_err(|_| malformed("value"))?,
+                    gas_used: tx.gas_used.parse().unwrap_or_default(),
+                    failed: tx.is_error == "1",
+                    function,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_explorer_responses() {
+        let source = serde_json::json!({"status": "1", "message": "OK", "result": [{
+            "ContractName": "TransparentUpgradeableProxy",
+            "ABI": "[{\"type\":\"function\",\"name\":\"admin\",\"inputs\":[],\"outputs\":[{\"name\":\"\",\"type\":\"address\"}],\"stateMutability\":\"view\"}]",
+            "Proxy": "1",
+            "Implementation": "0x43506849d7c04f9138d1a2050bbf3a0c054402dd"
+        }]});
+        let result = ExplorerClient::check_envelope(source, "test").unwrap();
+        let contract = ExplorerClient::parse_source(result).unwrap().unwrap();
+        assert_eq!(contract.name, "TransparentUpgradeableProxy");
+        assert!(contract.abi.function("admin").is_ok());
+        assert_eq!(contract.implementation.unwrap().to_lowercase(), "0x43506849d7c04f9138d1a2050bbf3a0c054402dd");
+
+        let unverified = serde_json::json!([{"ContractName": "", "ABI": "Contract source code not verified", "Implementation": ""}]);
+        assert!(ExplorerClient::parse_source(unverified).unwrap().is_none());
+
+        let history = serde_json::json!({"status": "1", "message": "OK", "result": [{
+            "blockNumber": "14923678", "timeStamp": "1654646411",
+            "hash": "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060",
+            "from": "0x9858effd232b4033e47d90003d41ec34ecaeda94", "to": "0x6b175474e89094c44da98b954eedeac495271d0f",
+            "value": "0", "gasUsed": "34706", "isError": "0", "contractAddress": "",
+            "methodId": "0xa9059cbb", "functionName": "transfer(address dst, uint256 wad)"
+        }, {
+            "blockNumber": "14923600", "timeStamp": "1654645000",
+            "hash": "0x6c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060",
+            "from": "0x6fac4d18c912343bf86fa7049364dd4e424ab9c0", "to": "0x9858effd232b4033e47d90003d41ec34ecaeda94",
+            "value": "1000000000000000000", "gasUsed": "21000", "isError": "1", "contractAddress": "",
+            "methodId": "0x", "functionName": ""
+        }]});
+        let transactions = ExplorerClient::parse_transactions(ExplorerClient::check_envelope(history, "test").unwrap()).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].method(), Some("transfer"));
+        assert_eq!(transactions[0].timestamp.to_rfc3339(), "2022-06-08T00:00:11+00:00");
+        assert_eq!(transactions[1].value, U256::exp10(18));
+        assert!(transactions[1].failed && transactions[1].method().is_none());
+
+        let empty = serde_json::json!({"status": "0", "message": "No transactions found", "result": []});
+        assert!(ExplorerClient::parse_transactions(ExplorerClient::check_envelope(empty, "test").unwrap()).unwrap().is_empty());
+        let rate_limited = serde_json::json!({"status": "0", "message": "NOTOK", "result": "Max rate limit reached"});
+        assert!(matches!(
+            ExplorerClient::check_envelope(rate_limited, "test"),
+            Err(crate::WalletError::Network(NetworkError::RateLimitExceeded { .. }))
+        ));
+
+        let config = ExplorerConfig {
+            api_key: "secret".to_string(),
+            url: Some("http://explorer.example/api".to_string()),
+        };
+        assert!(!format!("{:?}", config).contains("secret"));
+        assert!(ExplorerClient::new(&config, 1).is_err());
+        assert!(ExplorerClient::new(&ExplorerConfig { url: None, ..config }, 1).is_ok());
+    }
+}
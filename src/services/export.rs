@@ -0,0 +1,495 @@
+//! # Export Service
+//!
+//! Every way of getting key material or public data out of a wallet goes
+//! through one [`ExportRegistry`]. Each format is an [`ExportFormat`] that
+//! renders a loaded wallet to bytes, so adding a format means registering
+//! one more implementation rather than adding another CLI command.
+
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::Wallet;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use ethers::signers::Signer;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Options shared by all export formats
+#[derive(Debug, Clone, Copy)]
+pub struct ExportContext<'a> {
+    /// Account index for single-key formats
+    pub index: u32,
+    /// Number of addresses for list formats
+    pub count: u32,
+    /// Password protecting the exported file, for formats that need one
+    pub password: Option<&'a str>,
+    /// Low-resource mode: derive list formats on one thread
+    pub low_resource: bool,
+}
+
+impl Default for ExportContext<'_> {
+    fn default() -> Self {
+        Self {
+            index: 0,
+            count: 10,
+            password: None,
+            low_resource: false,
+        }
+    }
+}
+
+/// A named way of rendering a wallet
+pub trait ExportFormat: Send + Sync {
+    /// Name used with `--format`
+    fn name(&self) -> &'static str;
+
+    /// One-line description for help output
+    fn description(&self) -> &'static str;
+
+    /// Whether the output contains secret key material
+    fn contains_secrets(&self) -> bool;
+
+    /// Whether the output is encrypted with an export password
+    fn needs_password(&self) -> bool {
+        false
+    }
+
+    /// Suggested file name for the output
+    fn file_name(&self, wallet: &Wallet) -> String;
+
+    /// Render the wallet
+    fn render(&self, wallet: &Wallet, context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>>;
+}
+
+/// Export formats by name
+pub struct ExportRegistry {
+    formats: Vec<Box<dyn ExportFormat>>,
+}
+
+impl Default for ExportRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(V3Format::default()));
+        registry.register(Box::new(MewFormat));
+        registry.register(Box::new(XpubFormat));
+        registry.register(Box::new(AddressesFormat));
+        registry.register(Box::new(MnemonicFormat));
+        registry.register(Box::new(PrivateKeyFormat));
+        registry.register(Box::new(PaperFormat));
+        registry
+    }
+}
+
+impl ExportRegistry {
+    /// Registry without any formats
+    pub fn empty() -> Self {
+        Self { formats: Vec::new() }
+    }
+
+    /// Add a format, replacing any existing format with the same name
+    pub fn register(&mut self, format: Box<dyn ExportFormat>) {
+        self.formats.retain(|existing| existing.name() != format.name());
+        self.formats.push(format);
+    }
+
+    /// Look up a format by name
+    pub fn get(&self, name: &str) -> WalletResult<&dyn ExportFormat> {
+        self.formats
+            .iter()
+            .find(|format| format.name().eq_ignore_ascii_case(name))
+            .map(|format| format.as_ref())
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "format".to_string(),
+                    value: name.to_string(),
+                    expected: self.names().join(", "),
+                }
+                .into()
+            })
+    }
+
+    /// Registered formats in registration order
+    pub fn formats(&self) -> impl Iterator<Item = &dyn ExportFormat> {
+        self.formats.iter().map(|format| format.as_ref())
+    }
+
+    /// Registered format names
+    pub fn names(&self) -> Vec<&'static str> {
+        self.formats.iter().map(|format| format.name()).collect()
+    }
+}
+
+fn require_mnemonic(wallet: &Wallet, format: &str) -> WalletResult<()> {
+    if wallet.has_mnemonic() {
+        return Ok(());
+    }
+
+    Err(UserInputError::InvalidParameters {
+        parameter: "format".to_string(),
+        value: format.to_string(),
+        expected: "an HD wallet with a mnemonic".to_string(),
+    }
+    .into())
+}
+
+/// Web3 Secret Storage (v3) keystore for one account, readable by geth and most wallets
+struct V3Format {
+    scrypt_log_n: u8,
+}
+
+impl Default for V3Format {
+    fn default() -> Self {
+        // n = 262144, the "standard" cost used by geth and MetaMask
+        Self { scrypt_log_n: 18 }
+    }
+}
+
+impl V3Format {
+    fn encrypt(&self, wallet: &Wallet, context: &ExportContext<'_>) -> WalletResult<serde_json::Value> {
+        let password = context.password.ok_or_else(|| UserInputError::InvalidParameters {
+            parameter: "password".to_string(),
+            value: String::new(),
+            expected: "an export password".to_string(),
+        })?;
+
+        let signing_key = wallet.signing_key(context.index)?;
+        let mut secret = Zeroizing::new([0u8; 32]);
+        secret.copy_from_slice(&signing_key.signer().to_bytes());
+
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let kdf_error = |details: String| CryptographicError::KdfFailed { details };
+        let params = scrypt::Params::new(self.scrypt_log_n, 8, 1).map_err(|e| kdf_error(e.to_string()))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        scrypt::scrypt(password.as_bytes(), &salt, &params, key.as_mut()).map_err(|e| kdf_error(e.to_string()))?;
+
+        let mut ciphertext = secret.to_vec();
+        let mut cipher = Aes128Ctr::new_from_slices(&key[..16], &iv).map_err(|e| kdf_error(e.to_string()))?;
+        cipher.apply_keystream(&mut ciphertext);
+
+        let mac = ethers::utils::keccak256([&key[16..], ciphertext.as_slice()].concat());
+
+        Ok(serde_json::json!({
+            "address": hex::encode(signing_key.address().as_bytes()),
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": { "iv": hex::encode(iv) },
+                "ciphertext": hex::encode(&ciphertext),
+                "kdf": "scrypt",
+                "kdfparams": {
+                    "dklen": 32,
+                    "n": 1u32 << self.scrypt_log_n,
+                    "p": 1,
+                    "r": 8,
+                    "salt": hex::encode(salt)
+                },
+                "mac": hex::encode(mac)
+            },
+            "id": crate::utils::random_uuid(),
+            "version": 3
+        }))
+    }
+}
+
+impl ExportFormat for V3Format {
+    fn name(&self) -> &'static str {
+        "v3"
+    }
+
+    fn description(&self) -> &'static str {
+        "Web3 Secret Storage v3 keystore (scrypt) for one account"
+    }
+
+    fn contains_secrets(&self) -> bool {
+        true
+    }
+
+    fn needs_password(&self) -> bool {
+        true
+    }
+
+    fn file_name(&self, wallet: &Wallet) -> String {
+        format!("{}.json", wallet.address().to_lowercase())
+    }
+
+    fn render(&self, wallet: &Wallet, context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let keystore = self.encrypt(wallet, context)?;
+        Ok(Zeroizing::new(serde_json::to_vec_pretty(&keystore)?))
+    }
+}
+
+/// v3 keystore under the `UTC--<timestamp>--<address>` name MyEtherWallet and geth expect
+struct MewFormat;
+
+impl ExportFormat for MewFormat {
+    fn name(&self) -> &'static str {
+        "mew"
+    }
+
+    fn description(&self) -> &'static str {
+        "v3 keystore named for MyEtherWallet / geth import"
+    }
+
+    fn contains_secrets(&self) -> bool {
+        true
+    }
+
+    fn needs_password(&self) -> bool {
+        true
+    }
+
+    fn file_name(&self, wallet: &Wallet) -> String {
+        format!(
+            "UTC--{}--{}",
+            chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S%.3fZ"),
+            hex::encode(wallet.address().as_bytes())
+        )
+    }
+
+    fn render(&self, wallet: &Wallet, context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        V3Format::default().render(wallet, context)
+    }
+}
+
+/// Account-level extended public key for watch-only tooling
+struct XpubFormat;
+
+impl ExportFormat for XpubFormat {
+    fn name(&self) -> &'static str {
+        "xpub"
+    }
+
+    fn description(&self) -> &'static str {
+        "Account extended public key (watch-only)"
+    }
+
+    fn contains_secrets(&self) -> bool {
+        false
+    }
+
+    fn file_name(&self, wallet: &Wallet) -> String {
+        format!("{}.xpub", wallet.address().to_lowercase())
+    }
+
+    fn render(&self, wallet: &Wallet, _context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        Ok(Zeroizing::new(format!("{}\n", wallet.account_xpub()?).into_bytes()))
+    }
+}
+
+/// `index,address` CSV, accepted back by `wallet derive --expect`
+struct AddressesFormat;
+
+impl ExportFormat for AddressesFormat {
+    fn name(&self) -> &'static str {
+        "addresses"
+    }
+
+    fn description(&self) -> &'static str {
+        "CSV of derived addresses (index,address)"
+    }
+
+    fn contains_secrets(&self) -> bool {
+        false
+    }
+
+    fn file_name(&self, wallet: &Wallet) -> String {
+        format!("{}-addresses.csv", wallet.address().to_lowercase())
+    }
+
+    fn render(&self, wallet: &Wallet, context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let mut csv = String::from("index,address\n");
+        if wallet.is_hd() {
+            let indices: Vec<u32> = (context.index..context.index.saturating_add(context.count)).collect();
+            let paths = indices
+                .iter()
+                .map(|&index| wallet.account_path(index))
+                .collect::<WalletResult<Vec<_>>>()?;
+            for (index, derived) in indices.into_iter().zip(wallet.derive_many(&paths, context.low_resource)?) {
+                csv.push_str(&format!("{},{}\n", index, derived.address()));
+            }
+        } else {
+            csv.push_str(&format!("0,{}\n", wallet.address()));
+        }
+        Ok(Zeroizing::new(csv.into_bytes()))
+    }
+}
+
+/// Bare BIP39 mnemonic
+struct MnemonicFormat;
+
+impl ExportFormat for MnemonicFormat {
+    fn name(&self) -> &'static str {
+        "mnemonic"
+    }
+
+    fn description(&self) -> &'static str {
+        "BIP39 mnemonic phrase"
+    }
+
+    fn contains_secrets(&self) -> bool {
+        true
+    }
+
+    fn file_name(&self, wallet: &Wallet) -> String {
+        format!("{}-mnemonic.txt", wallet.address().to_lowercase())
+    }
+
+    fn render(&self, wallet: &Wallet, _context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        require_mnemonic(wallet, self.name())?;
+        Ok(Zeroizing::new(format!("{}\n", wallet.mnemonic()).into_bytes()))
+    }
+}
+
+/// Hex private key for one account
+struct PrivateKeyFormat;
+
+impl ExportFormat for PrivateKeyFormat {
+    fn name(&self) -> &'static str {
+        "private-key"
+    }
+
+    fn description(&self) -> &'static str {
+        "Hex private key for one account"
+    }
+
+    fn contains_secrets(&self) -> bool {
+        true
+    }
+
+    fn file_name(&self, wallet: &Wallet) -> String {
+        format!("{}-private-key.txt", wallet.address().to_lowercase())
+    }
+
+    fn render(&self, wallet: &Wallet, context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let key = wallet.derive_private_key(context.index)?;
+        Ok(Zeroizing::new(format!("0x{}\n", hex::encode(key.as_slice())).into_bytes()))
+    }
+}
+
+/// Printable backup sheet
+struct PaperFormat;
+
+impl ExportFormat for PaperFormat {
+    fn name(&self) -> &'static str {
+        "paper"
+    }
+
+    fn description(&self) -> &'static str {
+        "Printable backup sheet with numbered mnemonic words"
+    }
+
+    fn contains_secrets(&self) -> bool {
+        true
+    }
+
+    fn file_name(&self, wallet: &Wallet) -> String {
+        format!("{}-paper.txt", wallet.address().to_lowercase())
+    }
+
+    fn render(&self, wallet: &Wallet, _context: &ExportContext<'_>) -> WalletResult<Zeroizing<Vec<u8>>> {
+        require_mnemonic(wallet, self.name())?;
+
+        let mut sheet = String::new();
+        sheet.push_str("WEB3 WALLET BACKUP\n");
+        sheet.push_str(&"=".repeat(60));
+        sheet.push('\n');
+        sheet.push_str(&format!("Address:         {}\n", wallet.address()));
+        sheet.push_str(&format!("Network:         {}\n", wallet.network()));
+        sheet.push_str(&format!("Derivation path: {}\n", wallet.derivation_path()));
+        sheet.push_str(&format!("Created:         {}\n", wallet.created_at().format("%Y-%m-%d")));
+        if wallet.uses_passphrase() {
+            sheet.push_str("Passphrase:      REQUIRED (not printed on this sheet)\n");
+        }
+        sheet.push('\n');
+
+        let words: Vec<&str> = wallet.mnemonic().split_whitespace().collect();
+        for (row, chunk) in words.chunks(4).enumerate() {
+            let cells: Vec<String> = chunk
+                .iter()
+                .enumerate()
+                .map(|(column, word)| format!("{:>2}. {:<10}", row * 4 + column + 1, word))
+                .collect();
+            sheet.push_str(cells.join("  ").trim_end());
+            sheet.push('\n');
+        }
+
+        sheet.push('\n');
+        sheet.push_str("Anyone holding this sheet controls the funds. Store it offline.\n");
+        Ok(Zeroizing::new(sheet.into_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EthereumAddress, Network};
+    use coins_bip32::enc::{MainnetEncoder, XKeyEncoder};
+    use coins_bip32::xkeys::Parent;
+    use ethers::signers::Signer;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn test_wallet() -> Wallet {
+        Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap()
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let registry = ExportRegistry::default();
+        assert_eq!(
+            registry.names(),
+            vec!["v3", "mew", "xpub", "addresses", "mnemonic", "private-key", "paper"]
+        );
+        assert!(registry.get("XPUB").is_ok());
+        assert!(registry.get("pem").is_err());
+        assert!(!registry.get("addresses").unwrap().contains_secrets());
+    }
+
+    #[test]
+    fn test_v3_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let wallet = test_wallet();
+        let context = ExportContext {
+            password: Some("export-password"),
+            ..ExportContext::default()
+        };
+
+        let keystore = V3Format { scrypt_log_n: 10 }.render(&wallet, &context).unwrap();
+        let path = temp_dir.path().join("keystore.json");
+        std::fs::write(&path, keystore.as_slice()).unwrap();
+
+        let decrypted = ethers::signers::LocalWallet::decrypt_keystore(&path, "export-password").unwrap();
+        assert_eq!(EthereumAddress::from(decrypted.address()), *wallet.address());
+        assert!(ethers::signers::LocalWallet::decrypt_keystore(&path, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_xpub_derives_same_addresses() {
+        let wallet = test_wallet();
+        let xpub_text = XpubFormat.render(&wallet, &ExportContext::default()).unwrap();
+        let xpub_text = std::str::from_utf8(&xpub_text).unwrap().trim();
+        assert!(xpub_text.starts_with("xpub"));
+
+        let xpub = MainnetEncoder::xpub_from_base58(xpub_text).unwrap();
+        let child = xpub.derive_path("m/0/1").unwrap();
+        let address = ethers::utils::public_key_to_address(child.as_ref());
+        assert_eq!(EthereumAddress::from(address), *wallet.derive_address(1).unwrap().address());
+    }
+
+    #[test]
+    fn test_addresses_csv() {
+        let wallet = test_wallet();
+        let context = ExportContext {
+            count: 2,
+            ..ExportContext::default()
+        };
+        let csv = AddressesFormat.render(&wallet, &context).unwrap();
+        let csv = std::str::from_utf8(&csv).unwrap();
+
+        assert_eq!(csv.lines().count(), 3);
+        assert_eq!(csv.lines().nth(1), Some("0,0x9858EfFD232B4033E47d90003D41EC34EcaEda94"));
+    }
+}
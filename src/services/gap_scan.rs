@@ -0,0 +1,135 @@
+//! # Gap Scan Service
+//!
+//! Discovers which receive addresses of a seed have been used. Addresses are
+//! derived in order and checked for a balance or sent transactions; the scan
+//! stops after a run of `gap` unused addresses (BIP44 gap limit, 20 by
+//! default), the same rule wallets use when restoring an account.
+
+use crate::errors::{CryptographicError, NetworkError, UserInputError, WalletResult};
+use ethers::providers::Middleware;
+use ethers::types::{Address, U256};
+
+/// Gap limit recommended by BIP44
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// Largest gap limit accepted (each address costs two RPC calls)
+pub const MAX_GAP_LIMIT: u32 = 1_000;
+
+/// On-chain activity of one derived address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressActivity {
+    /// Derivation index
+    pub index: u32,
+    /// Address at the index
+    pub address: String,
+    /// Current balance in wei
+    pub balance: U256,
+    /// Number of transactions sent
+    pub transactions: U256,
+}
+
+impl AddressActivity {
+    /// Whether the address has ever been used
+    pub fn is_used(&self) -> bool {
+        !self.balance.is_zero() || !self.transactions.is_zero()
+    }
+}
+
+/// Result of a gap scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GapScan {
+    /// Used addresses, in index order
+    pub used: Vec<AddressActivity>,
+    /// Number of addresses checked
+    pub scanned: u32,
+    /// First index after the last used address (the next fresh receive address)
+    pub next_index: u32,
+}
+
+/// Gap-limit address discovery
+pub struct GapScanService;
+
+impl GapScanService {
+    /// Check addresses from `start` until `gap` consecutive ones are unused
+    #[tracing::instrument(level = "debug", skip_all, fields(start = start, gap = gap))]
+    pub async fn scan<M: Middleware>(
+        provider: &M,
+        endpoint: &str,
+        derive: impl Fn(u32) -> WalletResult<String>,
+        start: u32,
+        gap: u32,
+    ) -> WalletResult<GapScan> {
+        if gap == 0 || gap > MAX_GAP_LIMIT {
+            return Err(UserInputError::ValueOutOfRange {
+                parameter: "gap".to_string(),
+                value: gap.to_string(),
+                range: format!("1-{}", MAX_GAP_LIMIT),
+            }
+            .into());
+        }
+
+        let rpc_error = |e: M::Error| NetworkError::ConnectivityFailure {
+            endpoint: endpoint.to_string(),
+            details: e.to_string(),
+        };
+
+        let mut result = GapScan { next_index: start, ..Default::default() };
+        let mut unused_run = 0;
+        let mut index = start;
+        while unused_run < gap {
+            let address = derive(index)?;
+            let parsed = address.parse::<Address>().map_err(|e| CryptographicError::AddressGenerationFailed {
+                details: e.to_string(),
+            })?;
+
+            let activity = AddressActivity {
+                index,
+                address,
+                balance: provider.get_balance(parsed, None).await.map_err(rpc_error)?,
+                transactions: provider.get_transaction_count(parsed, None).await.map_err(rpc_error)?,
+            };
+            result.scanned += 1;
+
+            if activity.is_used() {
+                unused_run = 0;
+                result.next_index = index.saturating_add(1);
+                result.used.push(activity);
+            } else {
+                unused_run += 1;
+            }
+
+            index = match index.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::Provider;
+
+    #[tokio::test]
+    async fn test_scan_stops_after_gap() {
+        let (provider, mock) = Provider::mocked();
+        // Index 0 and 2 used, then a gap of two; responses are served last-in first-out
+        for (balance, nonce) in [(0u64, 0u64), (0, 0), (0, 3), (0, 0), (5, 0)] {
+            mock.push(U256::from(nonce)).unwrap();
+            mock.push(U256::from(balance)).unwrap();
+        }
+
+        let derive = |index: u32| Ok(format!("0x{:040x}", index + 1));
+        let scan = GapScanService::scan(&provider, "mock", derive, 0, 2).await.unwrap();
+
+        assert_eq!(scan.scanned, 5);
+        assert_eq!(scan.used.iter().map(|a| a.index).collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(scan.used[1].transactions, U256::from(3));
+        assert_eq!(scan.next_index, 3);
+
+        assert!(GapScanService::scan(&provider, "mock", derive, 0, 0).await.is_err());
+    }
+}
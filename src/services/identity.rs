@@ -0,0 +1,140 @@
+//! # Identity Service
+//!
+//! One JSON document describing an account: address in both spellings, the
+//! public key, and an ownership proof over a challenge. dApps and back-office
+//! tools can check the whole bundle offline instead of collecting each piece
+//! by copy-paste.
+
+use crate::errors::{ValidationError, WalletResult};
+use crate::models::Network;
+use crate::services::ownership::{OwnershipProof, OwnershipService, OwnershipStatement, VerifyOptions};
+use crate::services::signer::KeySigner;
+use crate::services::WalletManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Bundle format version
+pub const IDENTITY_BUNDLE_VERSION: u32 = 1;
+
+/// Verifiable description of one account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdentityBundle {
+    /// Bundle format version
+    pub version: u32,
+    /// Lowercase address
+    pub address: String,
+    /// EIP-55 checksummed address
+    pub checksum_address: String,
+    /// Uncompressed public key, hex with `0x`
+    pub public_key: String,
+    /// Network the account is used on
+    pub network: String,
+    /// Signed ownership statement
+    pub proof: OwnershipProof,
+}
+
+/// Identity bundles
+pub struct IdentityService;
+
+impl IdentityService {
+    /// Build and sign a bundle for `signer`'s account
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = signer.backend()))]
+    pub fn export(
+        manager: &WalletManager,
+        signer: &dyn KeySigner,
+        network: &Network,
+        challenge: &str,
+        now: DateTime<Utc>,
+    ) -> WalletResult<IdentityBundle> {
+        let proof = OwnershipService::prove(manager, signer, challenge, now)?;
+        let checksum_address = signer.address();
+
+        Ok(IdentityBundle {
+            version: IDENTITY_BUNDLE_VERSION,
+            address: checksum_address.to_lowercase(),
+            checksum_address,
+            public_key: format!("0x{}", hex::encode(signer.public_key())),
+            network: network.to_string(),
+            proof,
+        })
+    }
+
+    /// Check every field of a bundle against the others and the proof signature
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn verify(
+        bundle: &IdentityBundle,
+        options: &VerifyOptions<'_>,
+        now: DateTime<Utc>,
+    ) -> WalletResult<OwnershipStatement> {
+        let failed = |details: &str| ValidationError::IntegrityCheckFailed {
+            data_type: "identity bundle".to_string(),
+            details: details.to_string(),
+        };
+
+        if bundle.version != IDENTITY_BUNDLE_VERSION {
+            return Err(failed(&format!("Unsupported bundle version {}", bundle.version)).into());
+        }
+
+        let address: ethers::types::Address = bundle
+            .checksum_address
+            .parse()
+            .map_err(|_| failed("Invalid checksum address"))?;
+        if ethers::utils::to_checksum(&address, None) != bundle.checksum_address {
+            return Err(failed("checksum_address is not EIP-55 checksummed").into());
+        }
+        if bundle.address != bundle.checksum_address.to_lowercase() {
+            return Err(failed("address and checksum_address differ").into());
+        }
+
+        let public_key = hex::decode(bundle.public_key.trim_start_matches("0x"))
+            .map_err(|_| failed("public_key is not hex"))?;
+        match public_key.split_first() {
+            Some((0x04, point)) if point.len() == 64 => {
+                if ethers::utils::keccak256(point)[12..] != address.0 {
+                    return Err(failed("public_key does not belong to address").into());
+                }
+            }
+            _ => return Err(failed("public_key is not an uncompressed secp256k1 key").into()),
+        }
+
+        if bundle.proof.address != bundle.checksum_address {
+            return Err(failed("Proof is for a different address").into());
+        }
+        OwnershipService::verify_proof(&bundle.proof, options, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Wallet;
+    use crate::services::LocalKeySigner;
+    use crate::WalletConfig;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_export_and_verify() {
+        let manager = WalletManager::new(WalletConfig::default());
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let signer = LocalKeySigner::from_wallet(&wallet, 0).unwrap();
+        let now = Utc::now();
+
+        let bundle = IdentityService::export(&manager, &signer, &Network::Mainnet, "kyc-42", now).unwrap();
+        assert_eq!(bundle.checksum_address, "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+        assert_eq!(bundle.address, "0x9858effd232b4033e47d90003d41ec34ecaeda94");
+        assert!(bundle.public_key.starts_with("0x04"));
+
+        let options = VerifyOptions { challenge: Some("kyc-42"), ..Default::default() };
+        IdentityService::verify(&bundle, &options, now).unwrap();
+
+        let mut wrong_key = bundle.clone();
+        let other = LocalKeySigner::from_wallet(&wallet, 1).unwrap();
+        wrong_key.public_key = format!("0x{}", hex::encode(other.public_key()));
+        assert!(IdentityService::verify(&wrong_key, &options, now).is_err());
+
+        let mut lowercase = bundle;
+        lowercase.checksum_address = lowercase.address.clone();
+        assert!(IdentityService::verify(&lowercase, &options, now).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! # KDF Benchmark
+//!
+//! Times Argon2id on the current machine to pick keystore parameters that
+//! take about as long as the caller is willing to wait. Memory is raised
+//! first, since it is what makes GPU and ASIC attacks expensive; the
+//! remaining time budget goes to extra passes. The result never drops
+//! below the minimum enforced for mainnet keystores, even on machines too
+//! slow to reach it within the target.
+
+use crate::config;
+use crate::errors::{FileSystemError, WalletResult};
+use crate::services::crypto::{KdfProfile, KdfSettings};
+use crate::services::CryptoService;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Memory sizes tried, in KiB (19 MiB to 1 GiB)
+pub const CANDIDATE_MEMORY: [u32; 7] = [19_456, 47_104, 65_536, 131_072, 262_144, 524_288, 1_048_576];
+
+/// Most passes recommended, however fast the machine
+pub const MAX_ITERATIONS: u32 = 10;
+
+/// One timed key derivation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfSample {
+    /// Parameters measured
+    pub settings: KdfSettings,
+    /// Time one derivation took
+    pub elapsed: Duration,
+}
+
+/// Benchmark outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KdfBenchReport {
+    /// Single-pass timings, smallest memory first
+    pub samples: Vec<KdfSample>,
+    /// Parameters to use, measured once more
+    pub recommended: KdfSample,
+    /// Whether the recommendation fits the target (false when the minimum is slower)
+    pub within_target: bool,
+}
+
+/// Argon2id benchmark
+pub struct KdfBenchService;
+
+impl KdfBenchService {
+    /// Find the costliest parameters that derive a key within `target`
+    ///
+    /// Memory above `max_memory` KiB, or above half the machine's RAM when
+    /// that is known, is not tried.
+    #[tracing::instrument(level = "debug", skip_all, fields(target_ms = target.as_millis() as u64))]
+    pub fn benchmark(target: Duration, max_memory: Option<u32>) -> WalletResult<KdfBenchReport> {
+        let half_ram = config::resources::total_memory_kb().map(|kb| u32::try_from(kb / 2).unwrap_or(u32::MAX));
+        let memory_cap = [max_memory, half_ram].into_iter().flatten().min().unwrap_or(u32::MAX);
+
+        let mut samples = Vec::new();
+        for memory in CANDIDATE_MEMORY.into_iter().filter(|&m| m <= memory_cap) {
+            let sample = Self::measure(KdfSettings {
+                memory,
+                iterations: 1,
+                parallelism: config::crypto::DEFAULT_ARGON2_PARALLELISM,
+            })?;
+            samples.push(sample);
+            if sample.elapsed > target {
+                break;
+            }
+        }
+
+        // Largest memory whose single pass fits, with as many passes as the budget allows
+        let fitting = samples.iter().rev().find(|s| s.elapsed <= target);
+        let mut settings = match fitting {
+            Some(sample) => {
+                let pass_ms = sample.elapsed.as_millis().max(1);
+                let passes = (target.as_millis() / pass_ms).clamp(1, MAX_ITERATIONS as u128) as u32;
+                KdfSettings { iterations: passes, ..sample.settings }
+            }
+            None => {
+                let (memory, iterations, parallelism) = config::get_argon2_config(true);
+                KdfSettings { memory, iterations, parallelism }
+            }
+        };
+        if !config::is_strong_argon2(settings.memory, settings.iterations) {
+            settings.iterations = settings.iterations.max(config::crypto::LOW_MEMORY_ARGON2_ITERATIONS);
+        }
+
+        let recommended = Self::measure(settings)?;
+        Ok(KdfBenchReport {
+            samples,
+            within_target: recommended.elapsed <= target,
+            recommended,
+        })
+    }
+
+    /// Time one Argon2id derivation
+    pub fn measure(settings: KdfSettings) -> WalletResult<KdfSample> {
+        let mut key = zeroize::Zeroizing::new([0u8; config::crypto::KEY_LENGTH]);
+        let salt = [0u8; config::crypto::SALT_LENGTH];
+
+        let started = Instant::now();
+        CryptoService::derive_key_argon2(
+            b"kdf-bench",
+            &salt,
+            settings.memory,
+            settings.iterations,
+            settings.parallelism,
+            &mut *key,
+        )?;
+        Ok(KdfSample { settings, elapsed: started.elapsed() })
+    }
+
+    /// Store `settings` as the `custom` KDF profile in a TOML config file
+    ///
+    /// Other settings, comments and formatting are kept; the file is
+    /// created if missing.
+    pub fn save_to_config(path: &Path, settings: KdfSettings) -> WalletResult<()> {
+        let invalid = |details: String| FileSystemError::InvalidFormat {
+            path: path.display().to_string(),
+            details,
+        };
+
+        let contents = if path.exists() { std::fs::read_to_string(path)? } else { String::new() };
+        let mut document: toml_edit::DocumentMut = contents.parse().map_err(|e: toml_edit::TomlError| invalid(e.to_string()))?;
+        document["kdf_profile"] = toml_edit::value(KdfProfile::Custom.name());
+        document["kdf_memory"] = toml_edit::value(i64::from(settings.memory));
+        document["kdf_iterations"] = toml_edit::value(i64::from(settings.iterations));
+        document["kdf_parallelism"] = toml_edit::value(i64::from(settings.parallelism));
+
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        crate::utils::write_atomic(path, document.to_string().as_bytes(), config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_to_config_keeps_other_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "# my wallets\nnetwork = \"sepolia\"\nkdf_memory = 1024\n\n[profile.work]\nnetwork = \"holesky\"\n").unwrap();
+
+        let settings = KdfSettings { memory: 131_072, iterations: 2, parallelism: 1 };
+        KdfBenchService::save_to_config(&path, settings).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# my wallets\n"));
+        let config = crate::WalletConfig::load(&path).unwrap();
+        assert_eq!(config.network, "sepolia");
+        assert_eq!(config.kdf_profile, KdfProfile::Custom);
+        assert_eq!(config.kdf_settings(), settings);
+        assert_eq!(config.with_profile("work").unwrap().network, "holesky");
+    }
+}
@@ -0,0 +1,674 @@
+//! # Keystore Index
+//!
+//! Metadata listing for the wallet directory. With the `sqlite-index`
+//! feature, metadata is cached in a SQLite database next to the keystores
+//! and refreshed incrementally: files whose size and modification time are
+//! unchanged are not re-read, and files whose content hash is unchanged are
+//! not re-parsed.
+
+use crate::config;
+use crate::errors::{UserInputError, WalletError, WalletResult};
+use crate::models::{Keystore, WatchOnlyWallet};
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// Index database file name inside the wallet directory
+pub const INDEX_FILE_NAME: &str = ".index.sqlite";
+
+/// Cached metadata for one keystore file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreSummary {
+    /// File name inside the wallet directory
+    pub filename: String,
+    /// Full path
+    pub path: PathBuf,
+    /// Primary Ethereum address
+    pub address: String,
+    /// Wallet alias
+    pub alias: Option<String>,
+    /// Target network
+    pub network: String,
+    /// Creation timestamp (ISO 8601)
+    pub created_at: String,
+    /// Keystore tags
+    pub tags: Vec<String>,
+    /// Keystore notes
+    pub notes: Option<String>,
+    /// SHA-256 of the file contents (hex encoded)
+    pub file_hash: String,
+    /// Public-only wallet without keys
+    pub watch_only: bool,
+}
+
+impl KeystoreSummary {
+    /// Parsed creation time, if the timestamp is valid RFC 3339
+    pub fn created(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.created_at).ok().map(|t| t.with_timezone(&Utc))
+    }
+}
+
+/// Criteria for narrowing a keystore listing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeystoreFilter {
+    /// Keep keystores carrying this tag (case-insensitive)
+    pub tag: Option<String>,
+    /// Keep keystores for this network
+    pub network: Option<String>,
+    /// Keep keystores whose alias contains this text (case-insensitive)
+    pub alias_contains: Option<String>,
+    /// Keep keystores whose address contains this hex (case-insensitive, so a prefix works)
+    pub address: Option<String>,
+    /// Keep keystores created at or after this time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Keep keystores created before this time
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl KeystoreFilter {
+    /// Check whether a keystore passes every criterion
+    pub fn matches(&self, summary: &KeystoreSummary) -> bool {
+        let tag_matches = self.tag.as_ref().map_or(true, |tag| {
+            summary.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+        });
+        let network_matches = self.network.as_ref().map_or(true, |network| {
+            summary.network.eq_ignore_ascii_case(network)
+        });
+        let alias_matches = self.alias_contains.as_ref().map_or(true, |text| {
+            summary.alias.as_ref().is_some_and(|alias| alias.to_lowercase().contains(&text.to_lowercase()))
+        });
+        let address_matches = self.address.as_ref().map_or(true, |address| {
+            summary.address.to_lowercase().contains(&address.to_lowercase())
+        });
+        // Keystores with an unreadable timestamp never pass a date bound
+        let created = summary.created();
+        let after_matches = self.created_after.map_or(true, |after| created.is_some_and(|c| c >= after));
+        let before_matches = self.created_before.map_or(true, |before| created.is_some_and(|c| c < before));
+
+        tag_matches && network_matches && alias_matches && address_matches && after_matches && before_matches
+    }
+}
+
+/// Order of a keystore listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeystoreSort {
+    /// Oldest first
+    Created,
+    /// Alphabetical by alias, keystores without one last
+    Alias,
+    /// By lowercase address
+    Address,
+}
+
+impl KeystoreSort {
+    /// Every sort order
+    pub const ALL: [KeystoreSort; 3] = [Self::Created, Self::Alias, Self::Address];
+
+    /// Name accepted by `--sort`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Alias => "alias",
+            Self::Address => "address",
+        }
+    }
+
+    /// Compare two keystores, falling back to the file name on ties
+    pub fn compare(self, a: &KeystoreSummary, b: &KeystoreSummary) -> Ordering {
+        let primary = match self {
+            // `None` sorts first, so undated keystores lead
+            Self::Created => a.created().cmp(&b.created()),
+            Self::Alias => match (&a.alias, &b.alias) {
+                (Some(x), Some(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            Self::Address => a.address.to_lowercase().cmp(&b.address.to_lowercase()),
+        };
+        primary.then_with(|| a.filename.cmp(&b.filename))
+    }
+}
+
+impl std::str::FromStr for KeystoreSort {
+    type Err = WalletError;
+
+    fn from_str(name: &str) -> WalletResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|sort| sort.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "sort".to_string(),
+                    value: name.to_string(),
+                    expected: Self::ALL.map(|sort| sort.name()).join(", "),
+                }
+                .into()
+            })
+    }
+}
+
+/// Filter, order and cap applied to a keystore listing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeystoreQuery {
+    /// Keystores to keep
+    pub filter: KeystoreFilter,
+    /// Order; file name order when unset
+    pub sort: Option<KeystoreSort>,
+    /// Keep at most this many, after sorting
+    pub limit: Option<usize>,
+}
+
+impl KeystoreQuery {
+    /// Run the query over a listing
+    pub fn apply(&self, summaries: Vec<KeystoreSummary>) -> Vec<KeystoreSummary> {
+        let mut matching: Vec<_> = summaries.into_iter().filter(|s| self.filter.matches(s)).collect();
+        if let Some(sort) = self.sort {
+            matching.sort_by(|a, b| sort.compare(a, b));
+        }
+        if let Some(limit) = self.limit {
+            matching.truncate(limit);
+        }
+        matching
+    }
+}
+
+/// Keystore files in the wallet directory, sorted by name
+fn keystore_files(wallet_dir: &Path) -> WalletResult<Vec<(String, std::fs::Metadata)>> {
+    let entries = std::fs::read_dir(wallet_dir).map_err(|e| {
+        crate::errors::FileSystemError::DirectoryNotAccessible {
+            path: wallet_dir.display().to_string(),
+            details: e.to_string(),
+        }
+    })?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_keystore = Path::new(&name).extension().and_then(|e| e.to_str()) == Some(config::KEYSTORE_EXTENSION);
+        if !is_keystore || name.starts_with('.') {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            files.push((name, metadata));
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(files)
+}
+
+/// Read and parse a keystore file, returning its hash and metadata if valid
+fn summarize(wallet_dir: &Path, filename: &str, size: u64) -> WalletResult<(String, Option<KeystoreSummary>)> {
+    use sha2::{Digest, Sha256};
+
+    let path = wallet_dir.join(filename);
+    let bytes = std::fs::read(&path)?;
+    let file_hash = hex::encode(Sha256::digest(&bytes));
+
+    if size > config::fs::MAX_KEYSTORE_SIZE {
+        return Ok((file_hash, None));
+    }
+
+    let Some(json) = std::str::from_utf8(&bytes).ok() else {
+        return Ok((file_hash, None));
+    };
+
+    let summary = if WatchOnlyWallet::is_watch_only(json) {
+        WatchOnlyWallet::from_json(json).ok().map(|wallet| KeystoreSummary {
+            filename: filename.to_string(),
+            path,
+            address: wallet.address().to_string(),
+            alias: wallet.alias().map(str::to_string),
+            network: wallet.network().to_string(),
+            created_at: wallet.created_at().to_rfc3339(),
+            tags: Vec::new(),
+            notes: None,
+            file_hash: file_hash.clone(),
+            watch_only: true,
+        })
+    } else {
+        Keystore::from_json(json).ok().map(|keystore| KeystoreSummary {
+            filename: filename.to_string(),
+            path,
+            address: keystore.metadata.address.to_string(),
+            alias: keystore.metadata.alias,
+            network: keystore.metadata.network.to_string(),
+            created_at: keystore.metadata.created_at,
+            tags: keystore.metadata.tags,
+            notes: keystore.metadata.notes,
+            file_hash: file_hash.clone(),
+            watch_only: false,
+        })
+    };
+
+    Ok((file_hash, summary))
+}
+
+/// Parse every keystore in the directory without caching
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn scan_directory(wallet_dir: &Path) -> WalletResult<Vec<KeystoreSummary>> {
+    let mut summaries = Vec::new();
+    for (filename, metadata) in keystore_files(wallet_dir)? {
+        if let (_, Some(summary)) = summarize(wallet_dir, &filename, metadata.len())? {
+            summaries.push(summary);
+        }
+    }
+    Ok(summaries)
+}
+
+/// List keystore metadata, using the index when available
+///
+/// `low_resource` opens the index with a small page cache.
+pub fn list_keystores(wallet_dir: &Path, low_resource: bool) -> WalletResult<Vec<KeystoreSummary>> {
+    #[cfg(feature = "sqlite-index")]
+    {
+        match KeystoreIndex::open(wallet_dir, low_resource).and_then(|mut index| {
+            index.refresh()?;
+            index.entries()
+        }) {
+            Ok(entries) => return Ok(entries),
+            Err(e) => tracing::debug!("Keystore index unavailable, scanning directly: {}", e),
+        }
+    }
+    #[cfg(not(feature = "sqlite-index"))]
+    let _ = low_resource;
+
+    scan_directory(wallet_dir)
+}
+
+#[cfg(feature = "sqlite-index")]
+pub use sqlite::{KeystoreIndex, RefreshStats};
+
+#[cfg(feature = "sqlite-index")]
+mod sqlite {
+    use super::*;
+    use crate::errors::{ValidationError, WalletError};
+    use rusqlite::{params, Connection, OptionalExtension};
+    use std::collections::HashSet;
+
+    /// Schema version stored in `PRAGMA user_version`
+    const SCHEMA_VERSION: i64 = 4;
+
+    /// Convert SQLite failures into wallet errors
+    fn index_error(e: rusqlite::Error) -> WalletError {
+        ValidationError::IntegrityCheckFailed {
+            data_type: "keystore_index".to_string(),
+            details: e.to_string(),
+        }
+        .into()
+    }
+
+    /// Modification time in nanoseconds since the Unix epoch
+    fn modified_nanos(metadata: &std::fs::Metadata) -> i64 {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as i64)
+            .unwrap_or(0)
+    }
+
+    /// Changes applied by a refresh
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct RefreshStats {
+        /// Files newly indexed
+        pub added: usize,
+        /// Files re-parsed because their contents changed
+        pub updated: usize,
+        /// Index rows dropped for deleted files
+        pub removed: usize,
+        /// Files skipped because nothing changed
+        pub unchanged: usize,
+    }
+
+    /// SQLite-backed keystore metadata cache
+    pub struct KeystoreIndex {
+        conn: Connection,
+        wallet_dir: PathBuf,
+    }
+
+    impl KeystoreIndex {
+        /// Open (or create) the index for a wallet directory, with a small page cache when `low_resource`
+        pub fn open(wallet_dir: &Path, low_resource: bool) -> WalletResult<Self> {
+            let path = wallet_dir.join(INDEX_FILE_NAME);
+            let conn = Connection::open(&path).map_err(index_error)?;
+            crate::security::permissions::restrict_to_owner(&path)?;
+            if low_resource {
+                // Negative cache_size is in KiB rather than pages
+                conn.execute_batch(&format!(
+                    "PRAGMA cache_size = -{};",
+                    crate::config::resources::LOW_RESOURCE_SQLITE_CACHE_KB
+                ))
+                .map_err(index_error)?;
+            }
+
+            let version: i64 = conn
+                .query_row("PRAGMA user_version", [], |row| row.get(0))
+                .map_err(index_error)?;
+            if version != SCHEMA_VERSION {
+                conn.execute_batch(&format!(
+                    "DROP TABLE IF EXISTS keystores;
+                     CREATE TABLE keystores (
+                         filename   TEXT PRIMARY KEY,
+                         size       INTEGER NOT NULL,
+                         modified   INTEGER NOT NULL,
+                         file_hash  TEXT NOT NULL,
+                         valid      INTEGER NOT NULL,
+                         address    TEXT,
+                         alias      TEXT,
+                         network    TEXT,
+                         created_at TEXT,
+                         tags       TEXT,
+                         notes      TEXT,
+                         watch_only INTEGER NOT NULL DEFAULT 0
+                     );
+                     PRAGMA user_version = {};",
+                    SCHEMA_VERSION
+                ))
+                .map_err(index_error)?;
+            }
+
+            Ok(Self {
+                conn,
+                wallet_dir: wallet_dir.to_path_buf(),
+            })
+        }
+
+        /// Bring the index in line with the files on disk
+        #[tracing::instrument(level = "debug", skip_all)]
+        pub fn refresh(&mut self) -> WalletResult<RefreshStats> {
+            let files = keystore_files(&self.wallet_dir)?;
+            let mut stats = RefreshStats::default();
+            let tx = self.conn.transaction().map_err(index_error)?;
+
+            for (filename, metadata) in &files {
+                let size = metadata.len() as i64;
+                let modified = modified_nanos(metadata);
+
+                let cached: Option<(i64, i64, String)> = tx
+                    .query_row(
+                        "SELECT size, modified, file_hash FROM keystores WHERE filename = ?1",
+                        params![filename],
+                        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                    )
+                    .optional()
+                    .map_err(index_error)?;
+
+                if let Some((cached_size, cached_modified, _)) = &cached {
+                    if *cached_size == size && *cached_modified == modified {
+                        stats.unchanged += 1;
+                        continue;
+                    }
+                }
+
+                let (file_hash, summary) = summarize(&self.wallet_dir, filename, metadata.len())?;
+
+                if let Some((_, _, cached_hash)) = &cached {
+                    if *cached_hash == file_hash {
+                        tx.execute(
+                            "UPDATE keystores SET size = ?2, modified = ?3 WHERE filename = ?1",
+                            params![filename, size, modified],
+                        )
+                        .map_err(index_error)?;
+                        stats.unchanged += 1;
+                        continue;
+                    }
+                }
+
+                tx.execute(
+                    "INSERT OR REPLACE INTO keystores
+                         (filename, size, modified, file_hash, valid, address, alias, network, created_at, tags, notes, watch_only)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        filename,
+                        size,
+                        modified,
+                        file_hash,
+                        summary.is_some(),
+                        summary.as_ref().map(|s| &s.address),
+                        summary.as_ref().and_then(|s| s.alias.as_ref()),
+                        summary.as_ref().map(|s| &s.network),
+                        summary.as_ref().map(|s| &s.created_at),
+                        summary.as_ref().map(|s| s.tags.join("\n")),
+                        summary.as_ref().and_then(|s| s.notes.as_ref()),
+                        summary.as_ref().is_some_and(|s| s.watch_only),
+                    ],
+                )
+                .map_err(index_error)?;
+
+                if cached.is_some() {
+                    stats.updated += 1;
+                } else {
+                    stats.added += 1;
+                }
+            }
+
+            let present: HashSet<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+            let indexed: Vec<String> = {
+                let mut stmt = tx.prepare("SELECT filename FROM keystores").map_err(index_error)?;
+                let rows = stmt.query_map([], |row| row.get(0)).map_err(index_error)?;
+                rows.collect::<Result<_, _>>().map_err(index_error)?
+            };
+            for filename in indexed.iter().filter(|f| !present.contains(f.as_str())) {
+                tx.execute("DELETE FROM keystores WHERE filename = ?1", params![filename])
+                    .map_err(index_error)?;
+                stats.removed += 1;
+            }
+
+            tx.commit().map_err(index_error)?;
+            Ok(stats)
+        }
+
+        /// All valid keystores, sorted by file name
+        pub fn entries(&self) -> WalletResult<Vec<KeystoreSummary>> {
+            self.query("", params![])
+        }
+
+        /// Keystores whose file name, address, or alias contains `query` (case-insensitive)
+        pub fn search(&self, query: &str) -> WalletResult<Vec<KeystoreSummary>> {
+            let pattern = format!(
+                "%{}%",
+                query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+            );
+            self.query(
+                "AND (filename LIKE ?1 ESCAPE '\\' OR address LIKE ?1 ESCAPE '\\' OR alias LIKE ?1 ESCAPE '\\')",
+                params![pattern],
+            )
+        }
+
+        /// Run a keystore query with an extra WHERE clause
+        fn query(&self, filter: &str, params: &[&dyn rusqlite::ToSql]) -> WalletResult<Vec<KeystoreSummary>> {
+            let sql = format!(
+                "SELECT filename, address, alias, network, created_at, tags, notes, file_hash, watch_only
+                 FROM keystores WHERE valid = 1 {} ORDER BY filename",
+                filter
+            );
+            let mut stmt = self.conn.prepare(&sql).map_err(index_error)?;
+            let rows = stmt
+                .query_map(params, |row| {
+                    let filename: String = row.get(0)?;
+                    Ok(KeystoreSummary {
+                        path: self.wallet_dir.join(&filename),
+                        filename,
+                        address: row.get(1)?,
+                        alias: row.get(2)?,
+                        network: row.get(3)?,
+                        created_at: row.get(4)?,
+                        tags: row
+                            .get::<_, Option<String>>(5)?
+                            .map(|tags| tags.lines().map(str::to_string).collect())
+                            .unwrap_or_default(),
+                        notes: row.get(6)?,
+                        file_hash: row.get(7)?,
+                        watch_only: row.get(8)?,
+                    })
+                })
+                .map_err(index_error)?;
+
+            rows.collect::<Result<_, _>>().map_err(index_error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+    use crate::models::keystore::KdfParams;
+    use tempfile::TempDir;
+
+    fn write_keystore(dir: &Path, name: &str, alias: &str) {
+        let keystore = Keystore::new(
+            Some(alias.to_string()),
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94".parse().unwrap(),
+            Network::Mainnet,
+            vec![1u8; 32],
+            vec![2u8; 32],
+            vec![3u8; 12],
+            vec![4u8; 32],
+            KdfParams::Pbkdf2 {
+                dklen: 32,
+                c: 100_000,
+                prf: "hmac-sha256".to_string(),
+                salt: hex::encode([2u8; 32]),
+            },
+        );
+        std::fs::write(dir.join(name), keystore.to_json().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_scan_skips_non_keystores() {
+        let temp_dir = TempDir::new().unwrap();
+        write_keystore(temp_dir.path(), "treasury.json", "treasury");
+        std::fs::write(temp_dir.path().join("chains.json"), "[]").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "hello").unwrap();
+
+        let summaries = scan_directory(temp_dir.path()).unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].alias.as_deref(), Some("treasury"));
+    }
+
+    #[test]
+    fn test_scan_includes_watch_only() {
+        let temp_dir = TempDir::new().unwrap();
+        write_keystore(temp_dir.path(), "treasury.json", "treasury");
+        WatchOnlyWallet::from_address("0x9858effd232b4033e47d90003d41ec34ecaeda94", &Network::Mainnet)
+            .unwrap()
+            .save(&temp_dir.path().join("watch.json"))
+            .unwrap();
+
+        let summaries = scan_directory(temp_dir.path()).unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert!(!summaries[0].watch_only);
+        assert!(summaries[1].watch_only);
+        assert_eq!(summaries[1].address, "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+    }
+
+    #[cfg(feature = "sqlite-index")]
+    #[test]
+    fn test_index_refreshes_incrementally() {
+        let temp_dir = TempDir::new().unwrap();
+        write_keystore(temp_dir.path(), "a.json", "alpha");
+        write_keystore(temp_dir.path(), "b.json", "beta");
+
+        let mut index = KeystoreIndex::open(temp_dir.path(), false).unwrap();
+        let stats = index.refresh().unwrap();
+        assert_eq!((stats.added, stats.unchanged), (2, 0));
+
+        let stats = index.refresh().unwrap();
+        assert_eq!((stats.added, stats.updated, stats.unchanged), (0, 0, 2));
+
+        write_keystore(temp_dir.path(), "b.json", "bravo");
+        std::fs::remove_file(temp_dir.path().join("a.json")).unwrap();
+        write_keystore(temp_dir.path(), "c.json", "charlie");
+
+        let stats = index.refresh().unwrap();
+        assert_eq!((stats.added, stats.updated, stats.removed), (1, 1, 1));
+
+        let aliases: Vec<_> = index.entries().unwrap().into_iter().filter_map(|e| e.alias).collect();
+        assert_eq!(aliases, vec!["bravo", "charlie"]);
+        assert_eq!(index.search("BRAV").unwrap().len(), 1);
+        assert_eq!(index.search("0x9858").unwrap().len(), 2);
+        assert!(index.search("%").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_keystores_matches_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        write_keystore(temp_dir.path(), "a.json", "alpha");
+        tag_keystore(temp_dir.path(), "a.json", &["treasury", "cold"]);
+
+        assert_eq!(
+            list_keystores(temp_dir.path(), false).unwrap(),
+            scan_directory(temp_dir.path()).unwrap()
+        );
+    }
+
+    fn tag_keystore(dir: &Path, name: &str, tags: &[&str]) {
+        let path = dir.join(name);
+        let mut keystore = Keystore::from_json(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        keystore.metadata.add_tags(&tags).unwrap();
+        std::fs::write(&path, keystore.to_json().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_filter_by_tag_and_network() {
+        let temp_dir = TempDir::new().unwrap();
+        write_keystore(temp_dir.path(), "a.json", "alpha");
+        write_keystore(temp_dir.path(), "b.json", "beta");
+        tag_keystore(temp_dir.path(), "a.json", &["treasury"]);
+
+        let summaries = list_keystores(temp_dir.path(), false).unwrap();
+        let matching = |filter: &KeystoreFilter| summaries.iter().filter(|s| filter.matches(s)).count();
+
+        assert_eq!(matching(&KeystoreFilter::default()), 2);
+        assert_eq!(
+            matching(&KeystoreFilter { tag: Some("Treasury".to_string()), network: None, ..Default::default() }),
+            1
+        );
+        assert_eq!(
+            matching(&KeystoreFilter {
+                tag: Some("treasury".to_string()),
+                network: Some("sepolia".to_string()),
+                ..Default::default()
+            }),
+            0
+        );
+    }
+
+    #[test]
+    fn test_query_filters_sorts_and_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        write_keystore(temp_dir.path(), "a.json", "Zulu");
+        write_keystore(temp_dir.path(), "b.json", "alpha");
+        write_keystore(temp_dir.path(), "c.json", "alphabet");
+
+        let mut summaries = list_keystores(temp_dir.path(), false).unwrap();
+        summaries[0].created_at = "2025-03-01T00:00:00Z".to_string();
+        summaries[1].created_at = "2025-01-01T00:00:00Z".to_string();
+        summaries[2].created_at = "2025-02-01T00:00:00+02:00".to_string();
+        let aliases = |query: &KeystoreQuery| -> Vec<String> {
+            query.apply(summaries.clone()).into_iter().filter_map(|s| s.alias).collect()
+        };
+
+        let by_alias = KeystoreQuery { sort: Some(KeystoreSort::Alias), ..Default::default() };
+        assert_eq!(aliases(&by_alias), ["alpha", "alphabet", "Zulu"]);
+        assert_eq!(aliases(&KeystoreQuery { sort: Some(KeystoreSort::Created), limit: Some(2), ..Default::default() }),
+            ["alpha", "alphabet"]);
+
+        let filter = KeystoreFilter {
+            alias_contains: Some("ALPHA".to_string()),
+            address: Some("0x9858eff".to_string()),
+            created_after: Some("2025-01-15T00:00:00Z".parse().unwrap()),
+            created_before: Some("2025-03-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(aliases(&KeystoreQuery { filter, ..Default::default() }), ["alphabet"]);
+        assert!("size".parse::<KeystoreSort>().is_err());
+    }
+}
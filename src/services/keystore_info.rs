@@ -0,0 +1,179 @@
+//! # Keystore Inspection
+//!
+//! Everything that can be learned about a wallet file without its
+//! password: file size, modification time and permissions, the schema
+//! version and metadata, and the cipher and KDF with their parameters.
+//! Unlike loading, inspection does not stop at the first problem. A file
+//! that is not JSON, does not match the schema or fails validation is
+//! still described as far as it parses, with each problem listed, so a
+//! keystore that will not open can be diagnosed.
+
+use crate::config;
+use crate::errors::{CryptographicError, FileSystemError, ValidationError, WalletError, WalletResult};
+use crate::models::{Keystore, WatchOnlyWallet};
+use crate::security::permissions::{self, PermissionStatus};
+use crate::services::CryptoService;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+/// What kind of file was inspected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletFileKind {
+    /// Encrypted wallet keystore
+    Keystore,
+    /// Public-only wallet without keys
+    WatchOnly,
+    /// Not recognisable as either
+    Unknown,
+}
+
+impl WalletFileKind {
+    /// Short name for display
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keystore => "keystore",
+            Self::WatchOnly => "watch-only",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Description of a wallet file
+#[derive(Debug, Clone)]
+pub struct KeystoreInfo {
+    /// Inspected path
+    pub path: PathBuf,
+    /// File size in bytes
+    pub file_size: u64,
+    /// Last modification time
+    pub modified: Option<DateTime<Utc>>,
+    /// Whether only the owner can access the file
+    pub permissions: PermissionStatus,
+    /// Kind of wallet file
+    pub kind: WalletFileKind,
+    /// Parsed keystore, even when it failed validation
+    pub keystore: Option<Keystore>,
+    /// Reasons the file would not open, in the order they were found
+    pub problems: Vec<String>,
+}
+
+impl KeystoreInfo {
+    /// Check if nothing stops the file from being decrypted
+    pub fn is_loadable(&self) -> bool {
+        self.kind == WalletFileKind::Keystore && self.problems.is_empty()
+    }
+}
+
+/// Inspects wallet files without decrypting them
+pub struct KeystoreInfoService;
+
+impl KeystoreInfoService {
+    /// Describe the wallet file at `path`
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    pub fn inspect(path: &Path) -> WalletResult<KeystoreInfo> {
+        let file = std::fs::metadata(path).map_err(|_| FileSystemError::FileNotFound {
+            path: path.display().to_string(),
+            directory: path.parent().map(|p| p.display().to_string()).unwrap_or_else(|| ".".to_string()),
+        })?;
+        let mut info = KeystoreInfo {
+            path: path.to_path_buf(),
+            file_size: file.len(),
+            modified: file.modified().ok().map(DateTime::<Utc>::from),
+            permissions: permissions::check(path)?,
+            kind: WalletFileKind::Unknown,
+            keystore: None,
+            problems: Vec::new(),
+        };
+
+        if info.file_size > config::fs::MAX_KEYSTORE_SIZE {
+            info.problems.push(format!(
+                "file is {} bytes, over the {} byte limit for keystores",
+                info.file_size,
+                config::fs::MAX_KEYSTORE_SIZE
+            ));
+            return Ok(info);
+        }
+
+        let contents = std::fs::read_to_string(path)?;
+        let json: serde_json::Value = match serde_json::from_str(&contents) {
+            Ok(json) => json,
+            Err(e) => {
+                info.problems.push(format!("not valid JSON: {}", e));
+                return Ok(info);
+            }
+        };
+        if WatchOnlyWallet::is_watch_only(&contents) {
+            info.kind = WalletFileKind::WatchOnly;
+            info.problems.push("watch-only wallet: it holds no keys to decrypt".to_string());
+            return Ok(info);
+        }
+
+        let keystore: Keystore = match serde_json::from_value(json) {
+            Ok(keystore) => keystore,
+            Err(e) => {
+                info.problems.push(format!("does not match the keystore schema: {}", e));
+                return Ok(info);
+            }
+        };
+        info.kind = WalletFileKind::Keystore;
+        for check in [keystore.validate(), CryptoService::check_kdf_strength(&keystore)] {
+            if let Err(e) = check {
+                info.problems.push(Self::describe(&e));
+            }
+        }
+        info.keystore = Some(keystore);
+
+        Ok(info)
+    }
+
+    /// Problem text, with the schema detail the error's message leaves out
+    fn describe(error: &WalletError) -> String {
+        match error {
+            WalletError::Validation(ValidationError::InvalidKeystoreSchema { error, .. }) => error.clone(),
+            WalletError::Cryptographic(CryptographicError::WeakKdfParameters { details, minimum }) => {
+                format!("KDF too weak for mainnet: {} (minimum {})", details, minimum)
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::crypto::SecureString;
+    use crate::models::keystore::KdfParams;
+    use crate::models::{Network, Wallet};
+
+    #[test]
+    fn test_inspect_reports_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let keystore = CryptoService::encrypt_wallet(&wallet, &SecureString::from("Passw0rd!Str0ng#1"), true).unwrap();
+        let path = dir.path().join("wallet.json");
+        std::fs::write(&path, keystore.to_json().unwrap()).unwrap();
+
+        let info = KeystoreInfoService::inspect(&path).unwrap();
+        assert!(info.is_loadable(), "{:?}", info.problems);
+        assert_eq!(info.keystore.as_ref().unwrap().metadata.address, *wallet.address());
+
+        // A bad cipher is reported while the rest of the file is still described
+        let mut broken = keystore.clone();
+        broken.crypto.cipher = "aes-128-ctr".to_string();
+        std::fs::write(&path, broken.to_json().unwrap()).unwrap();
+        let info = KeystoreInfoService::inspect(&path).unwrap();
+        assert!(!info.is_loadable());
+        assert_eq!(info.problems, ["Unsupported cipher: aes-128-ctr"]);
+        assert!(matches!(info.keystore.unwrap().crypto.kdfparams, KdfParams::Argon2 { .. }));
+
+        std::fs::write(&path, "{\"version\": ").unwrap();
+        let info = KeystoreInfoService::inspect(&path).unwrap();
+        assert_eq!(info.kind, WalletFileKind::Unknown);
+        assert!(info.problems[0].starts_with("not valid JSON"));
+    }
+}
@@ -0,0 +1,309 @@
+//! # Checksum Manifests
+//!
+//! `MANIFEST.json` records the SHA-256 hash, size and address of every
+//! keystore in a directory, and of backup archives written into it, so that
+//! files left in cold storage for years can be checked for bit rot or
+//! tampering without a password. Keystores are recorded as they are saved;
+//! `wallet verify-manifest` compares the directory against the manifest.
+//!
+//! The manifest is a plain file next to what it describes. Anyone able to
+//! rewrite the keystores can rewrite it too, so keep a printed copy of its
+//! hash (shown by `verify-manifest`) with the rest of the offline backup.
+
+use crate::config;
+use crate::errors::{ValidationError, WalletResult};
+use crate::models::EthereumAddress;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// What a manifest knows about one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// SHA-256 of the file contents (hex)
+    pub sha256: String,
+    /// File size in bytes
+    pub size: u64,
+    /// Address from the keystore metadata, when the file has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<EthereumAddress>,
+}
+
+impl ManifestEntry {
+    /// Hash and describe the file at `path`
+    pub fn of_file(path: &Path) -> WalletResult<Self> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self {
+            sha256: hex::encode(Sha256::digest(&bytes)),
+            size: bytes.len() as u64,
+            address: keystore_json(&bytes).and_then(|json| keystore_address(&json)),
+        })
+    }
+}
+
+/// Outcome of checking one file against the manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestStatus {
+    /// Contents match the recorded hash
+    Intact,
+    /// Contents differ from the recorded hash
+    Modified,
+    /// Recorded, but no longer present
+    Missing,
+    /// A keystore the manifest does not list
+    Unlisted,
+}
+
+/// One file's verification result
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestCheck {
+    /// File name inside the directory
+    pub name: String,
+    /// Verification outcome
+    pub status: ManifestStatus,
+    /// Recorded entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<ManifestEntry>,
+    /// Entry computed from the file as it is now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<ManifestEntry>,
+}
+
+impl ManifestCheck {
+    /// Whether this result means damage or tampering
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, ManifestStatus::Modified | ManifestStatus::Missing)
+    }
+}
+
+/// Hashes of the files in one directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumManifest {
+    /// Version of the tool that last wrote the manifest
+    pub version: String,
+    /// Last time an entry was added or changed
+    pub updated_at: DateTime<Utc>,
+    /// Entries by file name
+    pub files: BTreeMap<String, ManifestEntry>,
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+impl ChecksumManifest {
+    /// Load the manifest of `dir`, starting empty when none exists
+    pub fn load(dir: &Path) -> WalletResult<Self> {
+        let path = dir.join(config::backup::MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                updated_at: Utc::now(),
+                files: BTreeMap::new(),
+                dir: dir.to_path_buf(),
+            });
+        }
+
+        let json = std::fs::read_to_string(&path)?;
+        let mut manifest: Self = serde_json::from_str(&json).map_err(|e| ValidationError::IntegrityCheckFailed {
+            data_type: "manifest".to_string(),
+            details: e.to_string(),
+        })?;
+        manifest.dir = dir.to_path_buf();
+        Ok(manifest)
+    }
+
+    /// Persist the manifest
+    pub fn save(&self) -> WalletResult<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(&self.path(), json.as_bytes(), config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+
+    /// Location of the manifest file
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(config::backup::MANIFEST_FILE_NAME)
+    }
+
+    /// Record the current contents of `name`
+    pub fn record(&mut self, name: &str) -> WalletResult<()> {
+        let entry = ManifestEntry::of_file(&self.dir.join(name))?;
+        self.files.insert(name.to_string(), entry);
+        self.version = env!("CARGO_PKG_VERSION").to_string();
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Drop `name` from the manifest, returning whether it was listed
+    pub fn forget(&mut self, name: &str) -> bool {
+        self.files.remove(name).is_some()
+    }
+
+    /// Record keystores the manifest does not list yet, returning how many were added
+    ///
+    /// Listed files keep their recorded hashes, so damage that happened
+    /// since they were recorded is still reported rather than accepted.
+    pub fn track_keystores(&mut self) -> WalletResult<usize> {
+        let unlisted: Vec<String> = keystore_names(&self.dir)?
+            .into_iter()
+            .filter(|name| !self.files.contains_key(name))
+            .collect();
+        for name in &unlisted {
+            self.record(name)?;
+        }
+        Ok(unlisted.len())
+    }
+
+    /// Compare every listed file, and any unlisted keystore, against the manifest
+    pub fn verify(&self) -> WalletResult<Vec<ManifestCheck>> {
+        let mut checks = Vec::with_capacity(self.files.len());
+        for (name, expected) in &self.files {
+            let path = self.dir.join(name);
+            let (status, actual) = if !path.is_file() {
+                (ManifestStatus::Missing, None)
+            } else {
+                let actual = ManifestEntry::of_file(&path)?;
+                let intact = actual.sha256 == expected.sha256 && actual.size == expected.size;
+                (if intact { ManifestStatus::Intact } else { ManifestStatus::Modified }, Some(actual))
+            };
+            checks.push(ManifestCheck {
+                name: name.clone(),
+                status,
+                expected: Some(expected.clone()),
+                actual,
+            });
+        }
+
+        for name in keystore_names(&self.dir)? {
+            if !self.files.contains_key(&name) {
+                let actual = ManifestEntry::of_file(&self.dir.join(&name))?;
+                checks.push(ManifestCheck {
+                    name,
+                    status: ManifestStatus::Unlisted,
+                    expected: None,
+                    actual: Some(actual),
+                });
+            }
+        }
+        Ok(checks)
+    }
+
+    /// Record the file at `path` in the manifest of its directory
+    pub fn record_file(path: &Path) -> WalletResult<()> {
+        let (dir, name) = split_path(path);
+        let mut manifest = Self::load(dir)?;
+        manifest.record(&name)?;
+        manifest.save()
+    }
+
+    /// Drop the file at `path` from the manifest of its directory, if it has one
+    pub fn forget_file(path: &Path) -> WalletResult<()> {
+        let (dir, name) = split_path(path);
+        if !dir.join(config::backup::MANIFEST_FILE_NAME).exists() {
+            return Ok(());
+        }
+        let mut manifest = Self::load(dir)?;
+        if manifest.forget(&name) {
+            manifest.save()?;
+        }
+        Ok(())
+    }
+}
+
+fn split_path(path: &Path) -> (&Path, String) {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    (dir, name)
+}
+
+/// Parsed JSON of a keystore: any object with a `crypto` section (ours, v3, EIP-2335)
+fn keystore_json(bytes: &[u8]) -> Option<serde_json::Value> {
+    if bytes.len() as u64 > config::fs::MAX_KEYSTORE_SIZE {
+        return None;
+    }
+    serde_json::from_slice::<serde_json::Value>(bytes)
+        .ok()
+        .filter(|json| json.get("crypto").is_some_and(serde_json::Value::is_object))
+}
+
+/// Address of our keystores (`metadata.address`) or of v3 keystores (`address`)
+fn keystore_address(json: &serde_json::Value) -> Option<EthereumAddress> {
+    json.pointer("/metadata/address")
+        .or_else(|| json.get("address"))
+        .and_then(serde_json::Value::as_str)
+        .and_then(|address| address.parse().ok())
+}
+
+/// Names of the non-hidden keystore files directly inside `dir`
+fn keystore_names(dir: &Path) -> WalletResult<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') || !entry.file_type()?.is_file() {
+            continue;
+        }
+        if std::fs::read(entry.path()).ok().and_then(|bytes| keystore_json(&bytes)).is_some() {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const KEYSTORE: &str = r#"{"version":"1","metadata":{"address":"0x9858effd232b4033e47d90003d41ec34ecaeda94"},"crypto":{}}"#;
+    const V3_KEYSTORE: &str = r#"{"address":"9858effd232b4033e47d90003d41ec34ecaeda94","crypto":{},"version":3}"#;
+
+    #[test]
+    fn test_record_and_verify() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("alpha.json"), KEYSTORE).unwrap();
+        std::fs::write(dir.path().join("beta.json"), V3_KEYSTORE).unwrap();
+        std::fs::write(dir.path().join("usage.json"), "{}").unwrap();
+
+        let mut manifest = ChecksumManifest::load(dir.path()).unwrap();
+        assert_eq!(manifest.track_keystores().unwrap(), 2);
+        manifest.save().unwrap();
+        assert_eq!(manifest.track_keystores().unwrap(), 0);
+
+        let manifest = ChecksumManifest::load(dir.path()).unwrap();
+        let address: EthereumAddress = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94".parse().unwrap();
+        assert_eq!(manifest.files["alpha.json"].address, Some(address));
+        assert_eq!(manifest.files["beta.json"].address, Some(address));
+        assert_eq!(manifest.files["alpha.json"].size, KEYSTORE.len() as u64);
+        assert!(manifest.verify().unwrap().iter().all(|check| check.status == ManifestStatus::Intact));
+
+        // One flipped bit, one lost file and one keystore saved behind the manifest's back
+        let mut bytes = KEYSTORE.as_bytes().to_vec();
+        bytes[20] ^= 0x01;
+        std::fs::write(dir.path().join("alpha.json"), bytes).unwrap();
+        std::fs::remove_file(dir.path().join("beta.json")).unwrap();
+        std::fs::write(dir.path().join("gamma.json"), KEYSTORE).unwrap();
+
+        let statuses: Vec<_> = manifest.verify().unwrap().into_iter().map(|check| (check.name, check.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("alpha.json".to_string(), ManifestStatus::Modified),
+                ("beta.json".to_string(), ManifestStatus::Missing),
+                ("gamma.json".to_string(), ManifestStatus::Unlisted),
+            ]
+        );
+
+        // Tracking adds the new keystore but keeps the damaged file's original hash
+        let mut manifest = ChecksumManifest::load(dir.path()).unwrap();
+        assert_eq!(manifest.track_keystores().unwrap(), 1);
+        assert!(manifest.verify().unwrap()[0].is_failure());
+
+        ChecksumManifest::forget_file(&dir.path().join("beta.json")).unwrap();
+        ChecksumManifest::record_file(&dir.path().join("alpha.json")).unwrap();
+        let manifest = ChecksumManifest::load(dir.path()).unwrap();
+        assert_eq!(manifest.files.len(), 1);
+        assert!(!manifest.verify().unwrap().iter().any(ManifestCheck::is_failure));
+    }
+}
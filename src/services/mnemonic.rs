@@ -4,17 +4,40 @@
 
 use crate::config;
 use crate::errors::{CryptographicError, WalletResult};
+use crate::errors::UserInputError;
+use crate::models::Wallet;
+pub use crate::models::secret::SecureSeed;
 use bip39::{Language, Mnemonic};
 use rand::RngCore;
-use std::str::FromStr;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// `--language` names of the supported BIP39 wordlists
+const LANGUAGE_NAMES: &[(&str, Language)] = &[
+    ("english", Language::English),
+    ("japanese", Language::Japanese),
+    ("korean", Language::Korean),
+    ("spanish", Language::Spanish),
+    ("chinese-simplified", Language::SimplifiedChinese),
+    ("chinese-traditional", Language::TraditionalChinese),
+    ("french", Language::French),
+    ("italian", Language::Italian),
+    ("czech", Language::Czech),
+    ("portuguese", Language::Portuguese),
+];
 
 /// Mnemonic generation service
 pub struct MnemonicService;
 
 impl MnemonicService {
-    /// Generate a new random mnemonic phrase
+    /// Generate a new random English mnemonic phrase
     pub fn generate(word_count: u8) -> WalletResult<SecureMnemonic> {
+        Self::generate_in(Language::English, word_count)
+    }
+
+    /// Generate a new random mnemonic phrase from the `language` wordlist
+    #[tracing::instrument(level = "debug", skip_all, fields(word_count = word_count, language = %language))]
+    pub fn generate_in(language: Language, word_count: u8) -> WalletResult<SecureMnemonic> {
         // Validate word count
         if !config::is_supported_word_count(word_count) {
             return Err(CryptographicError::InvalidMnemonic {
@@ -41,7 +64,7 @@ impl MnemonicService {
         rand::thread_rng().fill_bytes(&mut entropy);
 
         // Create mnemonic from entropy
-        let mnemonic = Mnemonic::from_entropy(&entropy).map_err(|e| {
+        let mnemonic = Mnemonic::from_entropy_in(language, &entropy).map_err(|e| {
             CryptographicError::InvalidMnemonic {
                 details: e.to_string(),
                 suggestion: "Ensure system has adequate entropy sources".to_string(),
@@ -54,17 +77,53 @@ impl MnemonicService {
         Ok(SecureMnemonic::new(mnemonic.to_string()))
     }
 
-    /// Validate an existing mnemonic phrase
+    /// Validate an existing mnemonic phrase, detecting its language
+    #[tracing::instrument(level = "debug", skip_all)]
     pub fn validate(mnemonic_str: &str) -> WalletResult<SecureMnemonic> {
-        // Parse and validate mnemonic
-        let mnemonic = Mnemonic::from_str(mnemonic_str).map_err(|e| {
+        let mnemonic = Wallet::parse_mnemonic(mnemonic_str)?;
+        Self::check_word_count(mnemonic_str)?;
+        Ok(SecureMnemonic::new(mnemonic.to_string()))
+    }
+
+    /// Validate an existing mnemonic phrase against the `language` wordlist
+    #[tracing::instrument(level = "debug", skip_all, fields(language = %language))]
+    pub fn validate_in(language: Language, mnemonic_str: &str) -> WalletResult<SecureMnemonic> {
+        let mnemonic = Mnemonic::parse_in(language, mnemonic_str).map_err(|e| {
             CryptographicError::InvalidMnemonic {
                 details: e.to_string(),
-                suggestion: "Verify the mnemonic phrase has the correct number of words (12 or 24) and all words are from the BIP39 wordlist.".to_string(),
+                suggestion: format!("Verify every word is from the {} BIP39 wordlist", Self::language_name(language)),
             }
         })?;
+        Self::check_word_count(mnemonic_str)?;
+        Ok(SecureMnemonic::new(mnemonic.to_string()))
+    }
 
-        // Validate word count
+    /// Look up a wordlist by its `--language` name
+    pub fn parse_language(name: &str) -> WalletResult<Language> {
+        LANGUAGE_NAMES
+            .iter()
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name.trim()))
+            .map(|(_, language)| *language)
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "language".to_string(),
+                    value: name.to_string(),
+                    expected: LANGUAGE_NAMES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", "),
+                }
+                .into()
+            })
+    }
+
+    /// `--language` name of a wordlist
+    pub fn language_name(language: Language) -> &'static str {
+        LANGUAGE_NAMES
+            .iter()
+            .find(|(_, candidate)| *candidate == language)
+            .map(|(name, _)| *name)
+            .unwrap_or("english")
+    }
+
+    fn check_word_count(mnemonic_str: &str) -> WalletResult<()> {
         let word_count = mnemonic_str.split_whitespace().count();
         if !config::is_supported_word_count(word_count as u8) {
             return Err(CryptographicError::InvalidMnemonic {
@@ -74,17 +133,13 @@ impl MnemonicService {
             .into());
         }
 
-        Ok(SecureMnemonic::new(mnemonic.to_string()))
+        Ok(())
     }
 
     /// Generate seed from mnemonic with optional passphrase
+    #[tracing::instrument(level = "debug", skip_all, fields(with_passphrase = passphrase.is_some()))]
     pub fn generate_seed(mnemonic: &SecureMnemonic, passphrase: Option<&str>) -> WalletResult<SecureSeed> {
-        let bip39_mnemonic = Mnemonic::from_str(mnemonic.phrase()).map_err(|e| {
-            CryptographicError::InvalidMnemonic {
-                details: e.to_string(),
-                suggestion: "Ensure mnemonic is valid BIP39 format".to_string(),
-            }
-        })?;
+        let bip39_mnemonic = Wallet::parse_mnemonic(mnemonic.phrase())?;
 
         let passphrase = passphrase.unwrap_or("");
         let seed = bip39_mnemonic.to_seed(passphrase);
@@ -136,28 +191,55 @@ impl MnemonicService {
     }
 
     /// Get mnemonic word list for validation
-    pub fn get_word_list() -> &'static [&'static str] {
-        Language::English.word_list()
+    pub fn get_word_list(language: Language) -> &'static [&'static str] {
+        language.word_list()
     }
 
     /// Check if a word is in the BIP39 word list
-    pub fn is_valid_word(word: &str) -> bool {
-        Self::get_word_list().contains(&word)
+    pub fn is_valid_word(language: Language, word: &str) -> bool {
+        let word: String = word.nfkd().collect();
+        language.find_word(&word).is_some()
     }
 
     /// Get word suggestions for partial input
-    pub fn suggest_words(partial: &str) -> Vec<&'static str> {
+    pub fn suggest_words(language: Language, partial: &str) -> Vec<&'static str> {
         if partial.is_empty() {
             return Vec::new();
         }
 
-        Self::get_word_list()
+        // Wordlists are stored NFKD-normalized; match composed input too
+        let partial: String = partial.nfkd().collect();
+        Self::get_word_list(language)
             .iter()
-            .filter(|word| word.starts_with(partial))
+            .filter(|word| word.starts_with(&partial))
             .take(10) // Limit suggestions
             .copied()
             .collect()
     }
+
+    /// Pick `count` distinct word positions (0-based, ascending) to quiz a paper backup on
+    pub fn quiz_positions(word_count: usize, count: usize) -> WalletResult<Vec<usize>> {
+        if count == 0 || count > word_count {
+            return Err(UserInputError::ValueOutOfRange {
+                parameter: "words".to_string(),
+                value: count.to_string(),
+                range: format!("1-{}", word_count),
+            }
+            .into());
+        }
+
+        let mut positions = rand::seq::index::sample(&mut rand::thread_rng(), word_count, count).into_vec();
+        positions.sort_unstable();
+        Ok(positions)
+    }
+
+    /// Check a quiz answer against the word at `position`, ignoring case and Unicode composition
+    pub fn quiz_answer_matches(mnemonic: &SecureMnemonic, position: usize, answer: &str) -> bool {
+        let normalize = |text: &str| Zeroizing::new(text.trim().nfkd().collect::<String>().to_lowercase());
+        mnemonic
+            .word_at(position)
+            .is_some_and(|word| *normalize(word) == *normalize(answer))
+    }
 }
 
 /// Secure mnemonic phrase with automatic memory cleanup
@@ -199,34 +281,6 @@ impl SecureMnemonic {
     }
 }
 
-/// Secure seed with automatic memory cleanup
-#[derive(Debug, Clone, Zeroize, ZeroizeOnDrop)]
-pub struct SecureSeed {
-    bytes: Vec<u8>,
-}
-
-impl SecureSeed {
-    /// Create new secure seed
-    pub fn new(bytes: Vec<u8>) -> Self {
-        Self { bytes }
-    }
-
-    /// Get seed bytes (use carefully)
-    pub fn bytes(&self) -> &[u8] {
-        &self.bytes
-    }
-
-    /// Get seed length
-    pub fn len(&self) -> usize {
-        self.bytes.len()
-    }
-
-    /// Check if seed is empty
-    pub fn is_empty(&self) -> bool {
-        self.bytes.is_empty()
-    }
-}
-
 /// Mnemonic strength levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MnemonicStrength {
@@ -261,6 +315,7 @@ impl MnemonicStrength {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Network;
 
     #[test]
     fn test_mnemonic_generation() {
@@ -317,20 +372,46 @@ mod tests {
 
     #[test]
     fn test_word_validation() {
-        assert!(MnemonicService::is_valid_word("abandon"));
-        assert!(MnemonicService::is_valid_word("about"));
-        assert!(!MnemonicService::is_valid_word("invalid"));
-        assert!(!MnemonicService::is_valid_word(""));
+        assert!(MnemonicService::is_valid_word(Language::English, "abandon"));
+        assert!(MnemonicService::is_valid_word(Language::English, "about"));
+        assert!(!MnemonicService::is_valid_word(Language::English, "invalid"));
+        assert!(!MnemonicService::is_valid_word(Language::English, ""));
+        assert!(MnemonicService::is_valid_word(Language::Spanish, "\u{e1}baco"));
     }
 
     #[test]
     fn test_word_suggestions() {
-        let suggestions = MnemonicService::suggest_words("aba");
+        let suggestions = MnemonicService::suggest_words(Language::English, "aba");
         assert!(suggestions.contains(&"abandon"));
         assert!(suggestions.len() <= 10);
 
-        let empty_suggestions = MnemonicService::suggest_words("");
+        let empty_suggestions = MnemonicService::suggest_words(Language::English, "");
         assert!(empty_suggestions.is_empty());
+
+        // Composed "á" matches the decomposed wordlist entry
+        let spanish = MnemonicService::suggest_words(Language::Spanish, "\u{e1}ba");
+        assert_eq!(spanish.first().map(|w| w.nfc().collect::<String>()), Some("\u{e1}baco".to_string()));
+    }
+
+    #[test]
+    fn test_non_english_languages() {
+        let japanese = MnemonicService::generate_in(Language::Japanese, 12).unwrap();
+        assert_eq!(Mnemonic::language_of(japanese.phrase()).unwrap(), Language::Japanese);
+        assert!(MnemonicService::validate(japanese.phrase()).is_ok());
+        assert!(MnemonicService::validate_in(Language::English, japanese.phrase()).is_err());
+
+        // NFC input validates and derives the same wallet as the NFKD form
+        let spanish = Mnemonic::from_entropy_in(Language::Spanish, &[0u8; 16]).unwrap().to_string();
+        let composed: String = spanish.nfc().collect();
+        assert_ne!(composed, spanish);
+        let validated = MnemonicService::validate_in(Language::Spanish, &composed).unwrap();
+        assert_eq!(
+            Wallet::from_mnemonic(validated.phrase(), &Network::Mainnet, None).unwrap().address(),
+            Wallet::from_mnemonic(&spanish, &Network::Mainnet, None).unwrap().address()
+        );
+
+        assert_eq!(MnemonicService::parse_language("Chinese-Simplified").unwrap(), Language::SimplifiedChinese);
+        assert!(MnemonicService::parse_language("klingon").is_err());
     }
 
     #[test]
@@ -344,4 +425,21 @@ mod tests {
         assert_eq!(mnemonic.word_at(11), Some("about"));
         assert_eq!(mnemonic.word_at(12), None);
     }
+
+    #[test]
+    fn test_backup_quiz() {
+        let mnemonic = SecureMnemonic::new("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_string());
+
+        let positions = MnemonicService::quiz_positions(12, 3).unwrap();
+        assert_eq!(positions.len(), 3);
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(positions.iter().all(|&position| position < 12));
+        assert_eq!(MnemonicService::quiz_positions(12, 12).unwrap(), (0..12).collect::<Vec<_>>());
+        assert!(MnemonicService::quiz_positions(12, 0).is_err());
+        assert!(MnemonicService::quiz_positions(12, 13).is_err());
+
+        assert!(MnemonicService::quiz_answer_matches(&mnemonic, 11, " About "));
+        assert!(!MnemonicService::quiz_answer_matches(&mnemonic, 10, "about"));
+        assert!(!MnemonicService::quiz_answer_matches(&mnemonic, 12, "about"));
+    }
 }
\ No newline at end of file
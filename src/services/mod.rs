@@ -3,10 +3,115 @@
 //! Business logic and service layer for wallet operations.
 //! All services implement secure patterns with proper error handling.
 
+pub mod address_check;
+#[cfg(feature = "native")]
+pub mod agent;
+pub mod airgap;
+pub mod approvals;
+pub mod audit;
+pub mod backup;
+pub mod bip85;
+#[cfg(feature = "native")]
+pub mod blocking;
+pub mod bls;
+pub mod breach;
+#[cfg(feature = "btc")]
+pub mod btc;
+#[cfg(feature = "native")]
+pub mod chain_registry;
+pub mod config_check;
+pub mod contract_call;
 pub mod crypto;
+pub mod eth_encryption;
+pub mod events;
+pub mod export;
+#[cfg(feature = "native")]
+pub mod explorer;
+pub mod gap_scan;
+#[cfg(feature = "native")]
+pub mod identity;
+pub mod kdf_bench;
+pub mod keystore_index;
+pub mod keystore_info;
+pub mod manifest;
 pub mod mnemonic;
+pub mod nacl;
+pub mod naming;
+pub mod nft;
+#[cfg(feature = "native")]
+pub mod ownership;
+pub mod paper;
+pub mod psbt;
+pub mod qr;
+pub mod recovery;
+pub mod safe;
+pub mod signer;
+#[cfg(feature = "native")]
+pub mod signer_daemon;
+#[cfg(feature = "native")]
+pub mod siwe;
+#[cfg(feature = "solana")]
+pub mod solana;
+pub mod staking;
+pub mod stealth;
+pub mod storage;
+pub mod threshold;
+pub mod totp;
+pub mod ur;
+pub mod usage;
+pub mod validator_keystore;
+pub mod vanity;
+#[cfg(feature = "native")]
 pub mod wallet_manager;
 
 // Re-export main services
+pub use airgap::AirgapService;
+pub use approvals::ApprovalService;
+pub use audit::{AuditAction, AuditLog};
+pub use backup::{BackupLog, BackupService};
+pub use bip85::Bip85Service;
+#[cfg(feature = "native")]
+pub use blocking::BlockingWalletManager;
+#[cfg(feature = "btc")]
+pub use btc::BtcService;
+#[cfg(feature = "native")]
+pub use chain_registry::ChainRegistry;
+pub use config_check::ConfigCheckService;
+pub use contract_call::ContractCallService;
 pub use crypto::CryptoService;
-pub use wallet_manager::WalletManager;
\ No newline at end of file
+pub use eth_encryption::EthEncryptionService;
+pub use events::{WalletObserver, WalletOrigin};
+pub use export::{ExportContext, ExportFormat, ExportRegistry};
+#[cfg(feature = "native")]
+pub use explorer::ExplorerClient;
+pub use gap_scan::GapScanService;
+#[cfg(feature = "native")]
+pub use identity::IdentityService;
+pub use kdf_bench::KdfBenchService;
+pub use keystore_info::KeystoreInfoService;
+pub use manifest::ChecksumManifest;
+pub use naming::NamingService;
+pub use nft::NftService;
+#[cfg(feature = "native")]
+pub use ownership::OwnershipService;
+pub use paper::PaperWalletService;
+pub use psbt::PsbtService;
+pub use recovery::RecoveryService;
+pub use safe::SafeService;
+pub use signer::{KeySigner, LocalKeySigner};
+#[cfg(feature = "native")]
+pub use signer_daemon::SignerDaemon;
+#[cfg(feature = "native")]
+pub use siwe::SiweService;
+#[cfg(feature = "solana")]
+pub use solana::SolanaService;
+pub use staking::StakingService;
+pub use stealth::StealthService;
+pub use storage::{KeystoreStorage, MemoryStorage};
+pub use threshold::ThresholdService;
+pub use totp::TotpStore;
+pub use usage::UsageTracker;
+pub use validator_keystore::ValidatorKeystoreService;
+pub use vanity::VanityService;
+#[cfg(feature = "native")]
+pub use wallet_manager::{WalletManager, WalletOps};
\ No newline at end of file
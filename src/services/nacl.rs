@@ -0,0 +1,330 @@
+//! # NaCl Box
+//!
+//! `crypto_box` (X25519, XSalsa20, Poly1305) as specified by NaCl, which
+//! MetaMask's `eth_decrypt` scheme is built on. XSalsa20 and HSalsa20 come
+//! from the `salsa20` crate; X25519 follows TweetNaCl's constant-time
+//! field arithmetic and Poly1305 follows poly1305-donna's 26-bit limbs,
+//! both checked against the RFC 7748 and RFC 8439 test vectors.
+
+use salsa20::cipher::consts::U10;
+use salsa20::cipher::{KeyIvInit, StreamCipher};
+use salsa20::XSalsa20;
+use zeroize::Zeroizing;
+
+/// Bytes of a box nonce
+pub const NONCE_LENGTH: usize = 24;
+
+/// Bytes of the authentication tag prepended to box ciphertext
+pub const TAG_LENGTH: usize = 16;
+
+/// Field element of GF(2^255 - 19) as sixteen 16-bit limbs
+type Element = [i64; 16];
+
+const A24: Element = [0xdb41, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+const BASE_POINT: [u8; 32] = {
+    let mut point = [0; 32];
+    point[0] = 9;
+    point
+};
+
+/// X25519 public key of `secret`
+pub fn public_key(secret: &[u8; 32]) -> [u8; 32] {
+    scalar_mult(secret, &BASE_POINT)
+}
+
+/// Encrypt and authenticate `plaintext` from `secret` to `public` (`crypto_box`)
+pub fn seal(plaintext: &[u8], nonce: &[u8; NONCE_LENGTH], public: &[u8; 32], secret: &[u8; 32]) -> Vec<u8> {
+    let key = shared_key(public, secret);
+    let mut cipher = XSalsa20::new((&*key).into(), nonce.into());
+    let mut mac_key = Zeroizing::new([0u8; 32]);
+    cipher.apply_keystream(&mut *mac_key);
+
+    let mut sealed = vec![0; TAG_LENGTH];
+    sealed.extend_from_slice(plaintext);
+    cipher.apply_keystream(&mut sealed[TAG_LENGTH..]);
+    let tag = poly1305(&mac_key, &sealed[TAG_LENGTH..]);
+    sealed[..TAG_LENGTH].copy_from_slice(&tag);
+    sealed
+}
+
+/// Check and decrypt a box sealed to `secret` by `public` (`crypto_box_open`)
+pub fn open(sealed: &[u8], nonce: &[u8; NONCE_LENGTH], public: &[u8; 32], secret: &[u8; 32]) -> Option<Zeroizing<Vec<u8>>> {
+    if sealed.len() < TAG_LENGTH {
+        return None;
+    }
+    let (tag, ciphertext) = sealed.split_at(TAG_LENGTH);
+
+    let key = shared_key(public, secret);
+    let mut cipher = XSalsa20::new((&*key).into(), nonce.into());
+    let mut mac_key = Zeroizing::new([0u8; 32]);
+    cipher.apply_keystream(&mut *mac_key);
+
+    let expected = poly1305(&mac_key, ciphertext);
+    if expected.iter().zip(tag).fold(0, |diff, (a, b)| diff | (a ^ b)) != 0 {
+        return None;
+    }
+    let mut plaintext = Zeroizing::new(ciphertext.to_vec());
+    cipher.apply_keystream(&mut plaintext);
+    Some(plaintext)
+}
+
+/// Box key shared by `secret` and `public` (`crypto_box_beforenm`)
+fn shared_key(public: &[u8; 32], secret: &[u8; 32]) -> Zeroizing<[u8; 32]> {
+    let point = Zeroizing::new(scalar_mult(secret, public));
+    let key = salsa20::hsalsa::<U10>((&*point).into(), &Default::default());
+    Zeroizing::new(key.into())
+}
+
+/// X25519 scalar multiplication (RFC 7748)
+pub fn scalar_mult(scalar: &[u8; 32], point: &[u8; 32]) -> [u8; 32] {
+    let mut z = Zeroizing::new(*scalar);
+    z[31] = (z[31] & 127) | 64;
+    z[0] &= 248;
+
+    let x = unpack(point);
+    let (mut a, mut b, mut c, mut d) = ([0; 16], x, [0; 16], [0; 16]);
+    a[0] = 1;
+    d[0] = 1;
+    for i in (0..255).rev() {
+        let bit = i64::from((z[i >> 3] >> (i & 7)) & 1);
+        swap(&mut a, &mut b, bit);
+        swap(&mut c, &mut d, bit);
+        let mut e = add(&a, &c);
+        a = sub(&a, &c);
+        c = add(&b, &d);
+        b = sub(&b, &d);
+        d = mul(&e, &e);
+        let f = mul(&a, &a);
+        a = mul(&c, &a);
+        c = mul(&b, &e);
+        e = add(&a, &c);
+        a = sub(&a, &c);
+        b = mul(&a, &a);
+        c = sub(&d, &f);
+        a = mul(&c, &A24);
+        a = add(&a, &d);
+        c = mul(&c, &a);
+        a = mul(&d, &f);
+        d = mul(&b, &x);
+        b = mul(&e, &e);
+        swap(&mut a, &mut b, bit);
+        swap(&mut c, &mut d, bit);
+    }
+
+    pack(&mul(&a, &invert(&c)))
+}
+
+fn unpack(bytes: &[u8; 32]) -> Element {
+    let mut element = [0; 16];
+    for (limb, pair) in element.iter_mut().zip(bytes.chunks_exact(2)) {
+        *limb = i64::from(pair[0]) | (i64::from(pair[1]) << 8);
+    }
+    element[15] &= 0x7fff;
+    element
+}
+
+fn pack(element: &Element) -> [u8; 32] {
+    let mut t = *element;
+    carry(&mut t);
+    carry(&mut t);
+    carry(&mut t);
+    for _ in 0..2 {
+        let mut m = [0; 16];
+        m[0] = t[0] - 0xffed;
+        for i in 1..15 {
+            m[i] = t[i] - 0xffff - ((m[i - 1] >> 16) & 1);
+            m[i - 1] &= 0xffff;
+        }
+        m[15] = t[15] - 0x7fff - ((m[14] >> 16) & 1);
+        let borrow = (m[15] >> 16) & 1;
+        m[14] &= 0xffff;
+        swap(&mut t, &mut m, 1 - borrow);
+    }
+
+    let mut bytes = [0; 32];
+    for (pair, limb) in bytes.chunks_exact_mut(2).zip(t) {
+        pair[0] = limb as u8;
+        pair[1] = (limb >> 8) as u8;
+    }
+    bytes
+}
+
+fn carry(element: &mut Element) {
+    for i in 0..16 {
+        element[i] += 1 << 16;
+        let c = element[i] >> 16;
+        if i < 15 {
+            element[i + 1] += c - 1;
+        } else {
+            element[0] += 38 * (c - 1);
+        }
+        element[i] -= c << 16;
+    }
+}
+
+/// Swap `p` and `q` when `bit` is 1, without branching on it
+fn swap(p: &mut Element, q: &mut Element, bit: i64) {
+    let mask = !(bit - 1);
+    for (a, b) in p.iter_mut().zip(q.iter_mut()) {
+        let t = mask & (*a ^ *b);
+        *a ^= t;
+        *b ^= t;
+    }
+}
+
+fn add(a: &Element, b: &Element) -> Element {
+    std::array::from_fn(|i| a[i] + b[i])
+}
+
+fn sub(a: &Element, b: &Element) -> Element {
+    std::array::from_fn(|i| a[i] - b[i])
+}
+
+fn mul(a: &Element, b: &Element) -> Element {
+    let mut t = [0i64; 31];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            t[i + j] += x * y;
+        }
+    }
+    for i in 0..15 {
+        t[i] += 38 * t[i + 16];
+    }
+    let mut product: Element = std::array::from_fn(|i| t[i]);
+    carry(&mut product);
+    carry(&mut product);
+    product
+}
+
+/// `element^(p - 2)`, the inverse by Fermat's little theorem
+fn invert(element: &Element) -> Element {
+    let mut c = *element;
+    for bit in (0..254).rev() {
+        c = mul(&c, &c);
+        if bit != 2 && bit != 4 {
+            c = mul(&c, element);
+        }
+    }
+    c
+}
+
+/// Poly1305 one-time authenticator (RFC 8439)
+pub fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; 16] {
+    const MASK: u32 = 0x3ff_ffff;
+    let le = |bytes: &[u8]| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    let r = [
+        le(&key[0..]) & 0x3ff_ffff,
+        (le(&key[3..]) >> 2) & 0x3ff_ff03,
+        (le(&key[6..]) >> 4) & 0x3ff_c0ff,
+        (le(&key[9..]) >> 6) & 0x3f0_3fff,
+        (le(&key[12..]) >> 8) & 0x00f_ffff,
+    ];
+    let r = r.map(u64::from);
+    let s = [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+    let mut h = [0u32; 5];
+
+    for chunk in message.chunks(16) {
+        // A full block gets its high bit from byte 16, a short one from the padding byte
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+        h[0] += le(&block[0..]) & MASK;
+        h[1] += (le(&block[3..]) >> 2) & MASK;
+        h[2] += (le(&block[6..]) >> 4) & MASK;
+        h[3] += (le(&block[9..]) >> 6) & MASK;
+        h[4] += (le(&block[12..]) >> 8) | (u32::from(block[16]) << 24);
+
+        let h64 = h.map(u64::from);
+        let mut d = [
+            h64[0] * r[0] + h64[1] * s[3] + h64[2] * s[2] + h64[3] * s[1] + h64[4] * s[0],
+            h64[0] * r[1] + h64[1] * r[0] + h64[2] * s[3] + h64[3] * s[2] + h64[4] * s[1],
+            h64[0] * r[2] + h64[1] * r[1] + h64[2] * r[0] + h64[3] * s[3] + h64[4] * s[2],
+            h64[0] * r[3] + h64[1] * r[2] + h64[2] * r[1] + h64[3] * r[0] + h64[4] * s[3],
+            h64[0] * r[4] + h64[1] * r[3] + h64[2] * r[2] + h64[3] * r[1] + h64[4] * r[0],
+        ];
+        for i in 0..4 {
+            d[i + 1] += d[i] >> 26;
+            h[i] = (d[i] as u32) & MASK;
+        }
+        h[4] = (d[4] as u32) & MASK;
+        let h0 = u64::from(h[0]) + (d[4] >> 26) * 5;
+        h[0] = (h0 as u32) & MASK;
+        h[1] += (h0 >> 26) as u32;
+    }
+
+    // Fully carry h, then subtract p if h >= p
+    for i in 1..4 {
+        h[i + 1] += h[i] >> 26;
+        h[i] &= MASK;
+    }
+    h[0] += (h[4] >> 26) * 5;
+    h[4] &= MASK;
+    h[1] += h[0] >> 26;
+    h[0] &= MASK;
+
+    let mut g = [0u32; 5];
+    let mut c = 5;
+    for i in 0..5 {
+        g[i] = h[i].wrapping_add(c);
+        c = g[i] >> 26;
+        g[i] &= MASK;
+    }
+    g[4] = g[4].wrapping_sub(1 << 26);
+    let keep_g = (g[4] >> 31).wrapping_sub(1);
+    for i in 0..5 {
+        h[i] = (h[i] & !keep_g) | (g[i] & keep_g);
+    }
+
+    let words = [
+        h[0] | (h[1] << 26),
+        (h[1] >> 6) | (h[2] << 20),
+        (h[2] >> 12) | (h[3] << 14),
+        (h[3] >> 18) | (h[4] << 8),
+    ];
+    let mut tag = [0u8; 16];
+    let mut carry = 0u64;
+    for (i, word) in words.iter().enumerate() {
+        let sum = u64::from(*word) + u64::from(le(&key[16 + 4 * i..])) + carry;
+        tag[4 * i..4 * i + 4].copy_from_slice(&(sum as u32).to_le_bytes());
+        carry = sum >> 32;
+    }
+    tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes<const N: usize>(hex: &str) -> [u8; N] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_rfc_vectors() {
+        // RFC 7748 section 6.1
+        let alice: [u8; 32] = bytes("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob: [u8; 32] = bytes("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        assert_eq!(hex::encode(public_key(&alice)), "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+        assert_eq!(hex::encode(public_key(&bob)), "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+        assert_eq!(
+            hex::encode(scalar_mult(&alice, &public_key(&bob))),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+
+        // RFC 8439 section 2.5.2
+        let key: [u8; 32] = bytes("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+        assert_eq!(
+            hex::encode(poly1305(&key, b"Cryptographic Forum Research Group")),
+            "a8061dc1305136c6c22b8baf0c0127a9"
+        );
+
+        // A box sealed by one side opens on the other, and not after tampering
+        let nonce = [7u8; NONCE_LENGTH];
+        let mut sealed = seal(b"attack at dawn", &nonce, &public_key(&bob), &alice);
+        assert_eq!(&open(&sealed, &nonce, &public_key(&alice), &bob).unwrap()[..], b"attack at dawn");
+        sealed[TAG_LENGTH] ^= 1;
+        assert!(open(&sealed, &nonce, &public_key(&alice), &bob).is_none());
+    }
+}
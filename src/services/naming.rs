@@ -0,0 +1,141 @@
+//! # Naming Service
+//!
+//! Renders keystore filenames from templates such as
+//! `"{network}-{alias}-{fingerprint}"`, so saved wallets follow a fixed
+//! convention regardless of what the operator typed.
+
+use crate::errors::{UserInputError, WalletResult};
+use crate::models::Wallet;
+
+/// Placeholders understood by [`NamingService::render`]
+pub const PLACEHOLDERS: &[&str] = &[
+    "network",
+    "alias",
+    "fingerprint",
+    "address",
+    "short_address",
+    "date",
+];
+
+/// Keystore filename template renderer
+pub struct NamingService;
+
+impl NamingService {
+    /// Check that a template is well-formed and only uses known placeholders
+    pub fn validate_template(template: &str) -> WalletResult<()> {
+        Self::substitute(template, |_| Some(String::new()))?;
+        Ok(())
+    }
+
+    /// Render a filename (without extension) for `wallet`.
+    ///
+    /// `{alias}` uses the wallet alias, falling back to `fallback_alias`
+    /// (usually the name given to `--save`) and then to "wallet".
+    pub fn render(template: &str, wallet: &Wallet, fallback_alias: Option<&str>) -> WalletResult<String> {
+        let fingerprint = if template.contains("{fingerprint}") {
+            wallet.fingerprint()?
+        } else {
+            String::new()
+        };
+
+        let rendered = Self::substitute(template, |name| {
+            let value = match name {
+                "network" => wallet.network().to_string(),
+                "alias" => wallet.alias()
+                    .or(fallback_alias)
+                    .unwrap_or("wallet")
+                    .to_string(),
+                "fingerprint" => fingerprint.clone(),
+                "address" => wallet.address().to_lowercase(),
+                "short_address" => wallet.address().to_lowercase().chars().take(10).collect(),
+                "date" => wallet.created_at().format("%Y%m%d").to_string(),
+                _ => return None,
+            };
+            Some(crate::utils::sanitize_filename(&value))
+        })?;
+
+        if rendered.is_empty() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "save-template".to_string(),
+                value: template.to_string(),
+                expected: "a template that renders to a non-empty filename".to_string(),
+            }
+            .into());
+        }
+
+        Ok(rendered)
+    }
+
+    /// Replace `{name}` placeholders using `lookup`
+    fn substitute<F>(template: &str, mut lookup: F) -> WalletResult<String>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let invalid = |expected: String| UserInputError::InvalidParameters {
+            parameter: "save-template".to_string(),
+            value: template.to_string(),
+            expected,
+        };
+
+        let mut output = String::new();
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            let literal = &rest[..start];
+            if literal.contains('}') {
+                return Err(invalid("balanced braces".to_string()).into());
+            }
+            output.push_str(&crate::utils::sanitize_filename(literal));
+
+            let end = rest[start..].find('}')
+                .ok_or_else(|| invalid("balanced braces".to_string()))?;
+            let name = &rest[start + 1..start + end];
+
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(invalid(format!("placeholders from {{{}}}", PLACEHOLDERS.join("}, {"))).into());
+            }
+            output.push_str(&lookup(name).unwrap_or_default());
+            rest = &rest[start + end + 1..];
+        }
+
+        if rest.contains('}') {
+            return Err(invalid("balanced braces".to_string()).into());
+        }
+        output.push_str(&crate::utils::sanitize_filename(rest));
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_render_template() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Sepolia, None).unwrap();
+        let name = NamingService::render("{network}-{alias}-{fingerprint}", &wallet, Some("ops")).unwrap();
+
+        // BIP32 master fingerprint of the "abandon ... about" test vector
+        assert_eq!(name, "sepolia-ops-73c5da0a");
+    }
+
+    #[test]
+    fn test_render_sanitizes_values() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, Some("../team wallet".to_string())).unwrap();
+        let name = NamingService::render("{alias}_{short_address}", &wallet, None).unwrap();
+
+        assert_eq!(name, "teamwallet_0x9858effd");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(NamingService::validate_template("{network}-{alias}").is_ok());
+        assert!(NamingService::validate_template("{unknown}").is_err());
+        assert!(NamingService::validate_template("{network").is_err());
+        assert!(NamingService::validate_template("network}").is_err());
+    }
+}
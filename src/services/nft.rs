@@ -0,0 +1,312 @@
+//! # NFT Transfers
+//!
+//! Builds `safeTransferFrom` transactions for ERC-721 and ERC-1155 tokens.
+//! Before a transfer is handed to a signer it can be checked against the
+//! chain: the token standard is read with ERC-165 `supportsInterface`, the
+//! sender's ownership with `ownerOf` or `balanceOf`, and the transaction
+//! itself is simulated with `eth_call`, which also catches recipients that
+//! are contracts unable to receive the token.
+
+use crate::errors::{NetworkError, UserInputError, WalletError, WalletResult};
+use crate::models::EthereumAddress;
+use ethers::abi::{self, Token};
+use ethers::providers::{Middleware, MiddlewareError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Bytes, Eip1559TransactionRequest, TransactionRequest, U256};
+use ethers::utils::keccak256;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// ERC-165 interface ID of ERC-721
+pub const ERC721_INTERFACE_ID: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+
+/// ERC-165 interface ID of ERC-1155
+pub const ERC1155_INTERFACE_ID: [u8; 4] = [0xd9, 0xb6, 0x7a, 0x26];
+
+const ERC721_SAFE_TRANSFER: &str = "safeTransferFrom(address,address,uint256)";
+const ERC721_SAFE_TRANSFER_WITH_DATA: &str = "safeTransferFrom(address,address,uint256,bytes)";
+const ERC1155_SAFE_TRANSFER: &str = "safeTransferFrom(address,address,uint256,uint256,bytes)";
+const SUPPORTS_INTERFACE: &str = "supportsInterface(bytes4)";
+const OWNER_OF: &str = "ownerOf(uint256)";
+const BALANCE_OF: &str = "balanceOf(address,uint256)";
+
+/// Token standard of an NFT contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NftStandard {
+    /// Non-fungible tokens, one owner per token
+    Erc721,
+    /// Multi-token contracts with balances per token ID
+    Erc1155,
+}
+
+impl NftStandard {
+    /// Every standard, for listings
+    pub const ALL: [Self; 2] = [Self::Erc721, Self::Erc1155];
+
+    /// Name as accepted by `--standard`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Erc721 => "erc721",
+            Self::Erc1155 => "erc1155",
+        }
+    }
+}
+
+impl FromStr for NftStandard {
+    type Err = WalletError;
+
+    fn from_str(name: &str) -> WalletResult<Self> {
+        let normalized = name.replace('-', "");
+        Self::ALL
+            .into_iter()
+            .find(|standard| standard.name().eq_ignore_ascii_case(&normalized))
+            .ok_or_else(|| {
+                UserInputError::InvalidParameters {
+                    parameter: "standard".to_string(),
+                    value: name.to_string(),
+                    expected: Self::ALL.map(Self::name).join(", "),
+                }
+                .into()
+            })
+    }
+}
+
+impl fmt::Display for NftStandard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// One token transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NftTransfer {
+    /// Token contract
+    pub contract: EthereumAddress,
+    /// Standard the contract implements
+    pub standard: NftStandard,
+    /// Current holder, who signs the transfer
+    pub from: EthereumAddress,
+    /// Recipient
+    pub to: EthereumAddress,
+    /// Token ID
+    pub token_id: U256,
+    /// Number of tokens; always 1 for ERC-721
+    pub amount: U256,
+    /// Data passed to the recipient's `onERC721Received` / `onERC1155Received`
+    pub data: Bytes,
+}
+
+impl NftTransfer {
+    /// Calldata of `safeTransferFrom` on the contract
+    ///
+    /// ERC-721 transfers without data use the three-argument overload, the
+    /// one every wallet and marketplace calls.
+    pub fn calldata(&self) -> Bytes {
+        let mut tokens = vec![
+            Token::Address(self.from.into()),
+            Token::Address(self.to.into()),
+            Token::Uint(self.token_id),
+        ];
+        let signature = match self.standard {
+            NftStandard::Erc721 if self.data.is_empty() => ERC721_SAFE_TRANSFER,
+            NftStandard::Erc721 => {
+                tokens.push(Token::Bytes(self.data.to_vec()));
+                ERC721_SAFE_TRANSFER_WITH_DATA
+            }
+            NftStandard::Erc1155 => {
+                tokens.push(Token::Uint(self.amount));
+                tokens.push(Token::Bytes(self.data.to_vec()));
+                ERC1155_SAFE_TRANSFER
+            }
+        };
+        let mut calldata = keccak256(signature)[..4].to_vec();
+        calldata.extend(abi::encode(&tokens));
+        calldata.into()
+    }
+
+    /// Unsigned transaction of the transfer; nonce, gas and fees are left to fill
+    pub fn transaction(&self, chain_id: u64) -> Eip1559TransactionRequest {
+        Eip1559TransactionRequest::new()
+            .from(Address::from(self.from))
+            .to(Address::from(self.contract))
+            .value(0)
+            .data(self.calldata())
+            .chain_id(chain_id)
+    }
+}
+
+/// Build and check NFT transfers
+pub struct NftService;
+
+impl NftService {
+    /// Transfer of `amount` tokens (ERC-1155) or of the token itself (ERC-721, where `amount` must be 1)
+    pub fn transfer(
+        contract: EthereumAddress,
+        standard: NftStandard,
+        from: EthereumAddress,
+        to: EthereumAddress,
+        token_id: U256,
+        amount: U256,
+        data: Bytes,
+    ) -> WalletResult<NftTransfer> {
+        if amount.is_zero() || (standard == NftStandard::Erc721 && amount != U256::one()) {
+            return Err(UserInputError::ValueOutOfRange {
+                parameter: "amount".to_string(),
+                value: amount.to_string(),
+                range: match standard {
+                    NftStandard::Erc721 => "1 for ERC-721 tokens".to_string(),
+                    NftStandard::Erc1155 => "at least 1".to_string(),
+                },
+            }
+            .into());
+        }
+        if to == EthereumAddress::default() || to == contract {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "to".to_string(),
+                value: to.to_string(),
+                expected: "a recipient other than the zero address and the token contract".to_string(),
+            }
+            .into());
+        }
+        Ok(NftTransfer {
+            contract,
+            standard,
+            from,
+            to,
+            token_id,
+            amount,
+            data,
+        })
+    }
+
+    /// Standard of the contract at `contract`, from ERC-165 `supportsInterface`
+    pub async fn detect_standard<M: Middleware>(provider: &M, endpoint: &str, contract: EthereumAddress) -> WalletResult<NftStandard> {
+        for (standard, interface_id) in [(NftStandard::Erc721, ERC721_INTERFACE_ID), (NftStandard::Erc1155, ERC1155_INTERFACE_ID)] {
+            let result = view(provider, endpoint, contract, SUPPORTS_INTERFACE, &[Token::FixedBytes(interface_id.to_vec())]).await?;
+            if result.is_some_and(|word| !word.is_zero()) {
+                return Ok(standard);
+            }
+        }
+        Err(UserInputError::InvalidParameters {
+            parameter: "contract".to_string(),
+            value: contract.to_string(),
+            expected: "an ERC-721 or ERC-1155 contract (pass --standard if it lacks ERC-165)".to_string(),
+        }
+        .into())
+    }
+
+    /// Check that the sender holds what `transfer` moves, with `ownerOf` or `balanceOf`
+    ///
+    /// Returns `None` when it does, and otherwise what is wrong.
+    pub async fn check_ownership<M: Middleware>(
+        provider: &M,
+        endpoint: &str,
+        transfer: &NftTransfer,
+    ) -> WalletResult<Option<String>> {
+        let held = match transfer.standard {
+            NftStandard::Erc721 => view(provider, endpoint, transfer.contract, OWNER_OF, &[Token::Uint(transfer.token_id)])
+                .await?
+                .map(|word| {
+                    let owner = EthereumAddress::from(Address::from_slice(&<[u8; 32]>::from(word)[12..]));
+                    (owner == transfer.from, format!("owned by {}", owner))
+                }),
+            NftStandard::Erc1155 => view(
+                provider,
+                endpoint,
+                transfer.contract,
+                BALANCE_OF,
+                &[Token::Address(transfer.from.into()), Token::Uint(transfer.token_id)],
+            )
+            .await?
+            .map(|balance| (balance >= transfer.amount, format!("balance {}", balance))),
+        };
+
+        Ok(match held {
+            Some((true, _)) => None,
+            Some((false, holding)) => Some(format!("{} does not hold token {} ({})", transfer.from, transfer.token_id, holding)),
+            None => Some(format!("Token {} does not exist on {}", transfer.token_id, transfer.contract)),
+        })
+    }
+
+    /// Run `transaction` with `eth_call` against the latest block
+    ///
+    /// Returns `None` when it succeeds, and the node's revert message when it reverts.
+    pub async fn simulate<M: Middleware>(provider: &M, endpoint: &str, transaction: &TypedTransaction) -> WalletResult<Option<String>> {
+        match provider.call(transaction, None).await {
+            Ok(_) => Ok(None),
+            Err(e) => match e.as_error_response() {
+                Some(response) if response.is_revert() => Ok(Some(response.message.clone())),
+                _ => Err(NetworkError::ConnectivityFailure {
+                    endpoint: endpoint.to_string(),
+                    details: e.to_string(),
+                }
+                .into()),
+            },
+        }
+    }
+}
+
+/// First word returned by a view call, or `None` when the call reverts or returns nothing
+async fn view<M: Middleware>(
+    provider: &M,
+    endpoint: &str,
+    contract: EthereumAddress,
+    signature: &str,
+    tokens: &[Token],
+) -> WalletResult<Option<U256>> {
+    let mut data = keccak256(signature)[..4].to_vec();
+    data.extend(abi::encode(tokens));
+    let call: TypedTransaction = TransactionRequest::new().to(Address::from(contract)).data(data).into();
+
+    match provider.call(&call, None).await {
+        Ok(result) if result.len() >= 32 => Ok(Some(U256::from_big_endian(&result[..32]))),
+        Ok(_) => Ok(None),
+        Err(e) if e.as_error_response().is_some_and(|response| response.is_revert()) => Ok(None),
+        Err(e) => Err(NetworkError::ConnectivityFailure {
+            endpoint: endpoint.to_string(),
+            details: e.to_string(),
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D";
+    const FROM: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+    const TO: &str = "0x6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0";
+
+    fn word(hex: &str) -> String {
+        format!("{:0>64}", hex.trim_start_matches("0x").to_lowercase())
+    }
+
+    #[test]
+    fn test_transfer_calldata() {
+        let (contract, from, to) = (CONTRACT.parse().unwrap(), FROM.parse().unwrap(), TO.parse().unwrap());
+        let nft = NftService::transfer(contract, NftStandard::Erc721, from, to, 7.into(), 1.into(), Bytes::new()).unwrap();
+        assert_eq!(hex::encode(nft.calldata()), format!("42842e0e{}{}{}", word(FROM), word(TO), word("7")));
+
+        let with_data = NftTransfer { data: vec![0xab].into(), ..nft.clone() };
+        assert_eq!(
+            hex::encode(with_data.calldata()),
+            format!("b88d4fde{}{}{}{}{}{}", word(FROM), word(TO), word("7"), word("80"), word("1"), "ab".to_string() + &"0".repeat(62))
+        );
+
+        let multi = NftService::transfer(contract, NftStandard::Erc1155, from, to, 7.into(), 3.into(), Bytes::new()).unwrap();
+        assert_eq!(
+            hex::encode(multi.calldata()),
+            format!("f242432a{}{}{}{}{}{}", word(FROM), word(TO), word("7"), word("3"), word("a0"), word("0"))
+        );
+        assert_eq!(multi.transaction(1).to, Some(Address::from(contract).into()));
+
+        assert!(NftService::transfer(contract, NftStandard::Erc721, from, to, 7.into(), 2.into(), Bytes::new()).is_err());
+        assert!(NftService::transfer(contract, NftStandard::Erc1155, from, to, 7.into(), 0.into(), Bytes::new()).is_err());
+        assert!(NftService::transfer(contract, NftStandard::Erc721, from, contract, 7.into(), 1.into(), Bytes::new()).is_err());
+        assert_eq!("ERC-1155".parse::<NftStandard>().unwrap(), NftStandard::Erc1155);
+        assert!("erc20".parse::<NftStandard>().is_err());
+    }
+}
@@ -0,0 +1,400 @@
+//! # Ownership Service
+//!
+//! Signed statements proving control of an address, as requested by exchanges
+//! and OTC desks. The statement is a fixed English sentence binding the
+//! address, a timestamp, and the counterparty's challenge; it is signed as an
+//! EIP-191 personal message so any Ethereum tool can check it as well.
+//!
+//! Smart-contract wallets (Safe, Argent) have no key of their own; their
+//! statements are checked by asking the contract through ERC-1271
+//! `isValidSignature`, which needs an RPC endpoint.
+
+use crate::errors::{NetworkError, UserInputError, ValidationError, WalletResult};
+use crate::models::EthereumAddress;
+use crate::services::signer::KeySigner;
+use crate::services::WalletManager;
+use chrono::{DateTime, SecondsFormat, Utc};
+use ethers::abi::{self, Token};
+use ethers::providers::{Middleware, MiddlewareError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Signature, TransactionRequest, H256};
+use serde::{Deserialize, Serialize};
+
+/// Longest challenge accepted, in bytes
+pub const MAX_CHALLENGE_LEN: usize = 256;
+
+/// ERC-1271 `isValidSignature(bytes32,bytes)` selector, also the value returned for valid signatures
+pub const ERC1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// How a statement's signature was found valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureKind {
+    /// Recovered to the claimed address (an externally owned account)
+    Ecdsa,
+    /// Accepted by the contract wallet at the claimed address (ERC-1271)
+    Erc1271,
+}
+
+impl SignatureKind {
+    /// Short name for display
+    pub fn name(self) -> &'static str {
+        match self {
+            SignatureKind::Ecdsa => "ecdsa",
+            SignatureKind::Erc1271 => "erc1271",
+        }
+    }
+}
+
+/// The claim being signed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnershipStatement {
+    /// Checksummed address claimed
+    pub address: String,
+    /// Time of the claim, to the second
+    pub timestamp: DateTime<Utc>,
+    /// Counterparty challenge (nonce)
+    pub challenge: String,
+}
+
+impl OwnershipStatement {
+    /// Statement for `address` at `timestamp`, checking the challenge
+    pub fn new(address: &str, timestamp: DateTime<Utc>, challenge: &str) -> WalletResult<Self> {
+        Self::check_challenge(challenge)?;
+        let timestamp = DateTime::from_timestamp(timestamp.timestamp(), 0).unwrap_or(timestamp);
+
+        Ok(Self {
+            address: address.to_string(),
+            timestamp,
+            challenge: challenge.to_string(),
+        })
+    }
+
+    /// Standard message text that gets signed
+    pub fn message(&self) -> String {
+        format!(
+            "I control {} at {} for challenge {}",
+            self.address,
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.challenge
+        )
+    }
+
+    /// Parse the standard message text back into a statement
+    pub fn parse(message: &str) -> WalletResult<Self> {
+        let malformed = || ValidationError::IntegrityCheckFailed {
+            data_type: "ownership statement".to_string(),
+            details: "Expected \"I control <address> at <RFC 3339 time> for challenge <challenge>\"".to_string(),
+        };
+
+        let rest = message.strip_prefix("I control ").ok_or_else(malformed)?;
+        let (address, rest) = rest.split_once(" at ").ok_or_else(malformed)?;
+        let (timestamp, challenge) = rest.split_once(" for challenge ").ok_or_else(malformed)?;
+        crate::utils::validate_ethereum_address(address)?;
+        let timestamp = DateTime::parse_from_rfc3339(timestamp).map_err(|_| malformed())?;
+
+        Self::new(address, timestamp.with_timezone(&Utc), challenge)
+    }
+
+    fn check_challenge(challenge: &str) -> WalletResult<()> {
+        if challenge.trim().is_empty() || challenge.len() > MAX_CHALLENGE_LEN || challenge.contains(['\n', '\r']) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "challenge".to_string(),
+                value: challenge.to_string(),
+                expected: format!("a single-line challenge of 1 to {} bytes", MAX_CHALLENGE_LEN),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// A signed ownership statement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipProof {
+    /// Signed message text
+    pub message: String,
+    /// Claimed address
+    pub address: String,
+    /// Signing time (RFC 3339)
+    pub timestamp: DateTime<Utc>,
+    /// Counterparty challenge
+    pub challenge: String,
+    /// 65-byte EIP-191 signature, hex with `0x`
+    pub signature: String,
+}
+
+/// Expectations a verifier checks beyond the signature itself
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions<'a> {
+    /// Address the proof must be for
+    pub address: Option<&'a str>,
+    /// Challenge the verifier issued
+    pub challenge: Option<&'a str>,
+    /// Oldest acceptable proof
+    pub max_age: Option<chrono::Duration>,
+}
+
+/// Address ownership proofs
+pub struct OwnershipService;
+
+impl OwnershipService {
+    /// Sign an ownership statement for `signer`'s address
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = signer.backend()))]
+    pub fn prove(
+        manager: &WalletManager,
+        signer: &dyn KeySigner,
+        challenge: &str,
+        now: DateTime<Utc>,
+    ) -> WalletResult<OwnershipProof> {
+        let statement = OwnershipStatement::new(&signer.address(), now, challenge)?;
+        let message = statement.message();
+        let signature = manager.sign_message(signer, message.as_bytes())?;
+
+        Ok(OwnershipProof {
+            message,
+            address: statement.address,
+            timestamp: statement.timestamp,
+            challenge: statement.challenge,
+            signature: format!("0x{}", signature),
+        })
+    }
+
+    /// Check a signed message, returning the statement it proves
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn verify(
+        message: &str,
+        signature: &str,
+        options: &VerifyOptions<'_>,
+        now: DateTime<Utc>,
+    ) -> WalletResult<OwnershipStatement> {
+        let statement = OwnershipStatement::parse(message)?;
+        let claimed = claimed_address(&statement)?;
+
+        let signer = parse_signature(signature)?
+            .recover(message)
+            .map_err(|e| failed(format!("Signature does not recover: {}", e)))?;
+        if signer != claimed {
+            return Err(failed(format!("Signed by {}, not {}", EthereumAddress::from(signer), statement.address)).into());
+        }
+
+        Self::check_options(&statement, options, now)?;
+        Ok(statement)
+    }
+
+    /// Check a signed message like [`OwnershipService::verify`], also
+    /// accepting an ERC-1271 signature from a contract wallet at the claimed address
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn verify_with_contracts<M: Middleware>(
+        provider: &M,
+        endpoint: &str,
+        message: &str,
+        signature: &str,
+        options: &VerifyOptions<'_>,
+        now: DateTime<Utc>,
+    ) -> WalletResult<(OwnershipStatement, SignatureKind)> {
+        let statement = OwnershipStatement::parse(message)?;
+        let claimed = claimed_address(&statement)?;
+
+        let recovered = parse_signature(signature).ok().and_then(|parsed| parsed.recover(message).ok());
+        let kind = if recovered == Some(claimed) {
+            SignatureKind::Ecdsa
+        } else {
+            // Contract wallets may sign with anything, e.g. several owner signatures
+            let bytes = hex::decode(signature.trim().trim_start_matches("0x"))
+                .map_err(|e| failed(format!("Invalid signature: {}", e)))?;
+            let hash = ethers::utils::hash_message(message);
+            if !Self::is_valid_contract_signature(provider, endpoint, claimed, hash, &bytes).await? {
+                return Err(failed(format!(
+                    "Not signed by {}, and no contract wallet there accepts the signature (ERC-1271)",
+                    statement.address
+                ))
+                .into());
+            }
+            SignatureKind::Erc1271
+        };
+
+        Self::check_options(&statement, options, now)?;
+        Ok((statement, kind))
+    }
+
+    /// Ask the contract at `contract` whether it accepts `signature` over `hash` (ERC-1271)
+    ///
+    /// Addresses without code and contracts that revert count as rejecting
+    /// the signature, as Safe reverts on signatures it does not accept.
+    pub async fn is_valid_contract_signature<M: Middleware>(
+        provider: &M,
+        endpoint: &str,
+        contract: Address,
+        hash: H256,
+        signature: &[u8],
+    ) -> WalletResult<bool> {
+        let mut data = ERC1271_MAGIC_VALUE.to_vec();
+        data.extend(abi::encode(&[Token::FixedBytes(hash.0.to_vec()), Token::Bytes(signature.to_vec())]));
+        let call: TypedTransaction = TransactionRequest::new().to(contract).data(data).into();
+
+        match provider.call(&call, None).await {
+            Ok(result) => Ok(result.len() >= 4 && result[..4] == ERC1271_MAGIC_VALUE),
+            Err(e) if e.as_error_response().is_some_and(|response| response.is_revert()) => Ok(false),
+            Err(e) => Err(NetworkError::ConnectivityFailure {
+                endpoint: endpoint.to_string(),
+                details: e.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Check a proof document produced by [`OwnershipService::prove`]
+    pub fn verify_proof(
+        proof: &OwnershipProof,
+        options: &VerifyOptions<'_>,
+        now: DateTime<Utc>,
+    ) -> WalletResult<OwnershipStatement> {
+        let statement = Self::verify(&proof.message, &proof.signature, options, now)?;
+        Self::check_proof_fields(proof, &statement)?;
+        Ok(statement)
+    }
+
+    /// Check that a proof document's fields repeat the statement its message was verified as
+    pub fn check_proof_fields(proof: &OwnershipProof, statement: &OwnershipStatement) -> WalletResult<()> {
+        if !statement.address.eq_ignore_ascii_case(&proof.address)
+            || statement.timestamp != proof.timestamp
+            || statement.challenge != proof.challenge
+        {
+            return Err(failed("Proof fields do not match the signed message".to_string()).into());
+        }
+        Ok(())
+    }
+
+    fn check_options(statement: &OwnershipStatement, options: &VerifyOptions<'_>, now: DateTime<Utc>) -> WalletResult<()> {
+        if let Some(expected) = options.address {
+            if !expected.eq_ignore_ascii_case(&statement.address) {
+                return Err(failed(format!("Proof is for {}, expected {}", statement.address, expected)).into());
+            }
+        }
+        if let Some(expected) = options.challenge {
+            if expected != statement.challenge {
+                return Err(failed("Challenge does not match".to_string()).into());
+            }
+        }
+        if let Some(max_age) = options.max_age {
+            if now - statement.timestamp > max_age {
+                return Err(failed(format!("Proof from {} is older than allowed", statement.timestamp)).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn failed(details: String) -> ValidationError {
+    ValidationError::IntegrityCheckFailed {
+        data_type: "ownership proof".to_string(),
+        details,
+    }
+}
+
+fn claimed_address(statement: &OwnershipStatement) -> WalletResult<Address> {
+    Ok(statement
+        .address
+        .parse()
+        .map_err(|_| failed(format!("Invalid address {}", statement.address)))?)
+}
+
+fn parse_signature(signature: &str) -> WalletResult<Signature> {
+    Ok(signature
+        .trim()
+        .trim_start_matches("0x")
+        .parse()
+        .map_err(|e: ethers::types::SignatureError| failed(format!("Invalid signature: {}", e)))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Network, Wallet};
+    use crate::services::LocalKeySigner;
+    use crate::WalletConfig;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn proof(challenge: &str, now: DateTime<Utc>) -> OwnershipProof {
+        let manager = WalletManager::new(WalletConfig::default());
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let signer = LocalKeySigner::from_wallet(&wallet, 0).unwrap();
+        OwnershipService::prove(&manager, &signer, challenge, now).unwrap()
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:30:45.250Z").unwrap().with_timezone(&Utc);
+        let proof = proof("otc-8841", now);
+        assert_eq!(
+            proof.message,
+            "I control 0x9858EfFD232B4033E47d90003D41EC34EcaEda94 at 2024-05-01T12:30:45Z for challenge otc-8841"
+        );
+
+        let options = VerifyOptions {
+            address: Some("0x9858effd232b4033e47d90003d41ec34ecaeda94"),
+            challenge: Some("otc-8841"),
+            max_age: Some(chrono::Duration::minutes(10)),
+        };
+        let statement = OwnershipService::verify_proof(&proof, &options, now).unwrap();
+        assert_eq!(statement.challenge, "otc-8841");
+
+        let later = now + chrono::Duration::hours(1);
+        assert!(OwnershipService::verify_proof(&proof, &options, later).is_err());
+        let wrong_challenge = VerifyOptions { challenge: Some("other"), ..Default::default() };
+        assert!(OwnershipService::verify_proof(&proof, &wrong_challenge, now).is_err());
+    }
+
+    #[test]
+    fn test_tampered_proof_rejected() {
+        let now = Utc::now();
+        let proof = proof("nonce-1", now);
+        let options = VerifyOptions::default();
+
+        let tampered = proof.message.replace("nonce-1", "nonce-2");
+        assert!(OwnershipService::verify(&tampered, &proof.signature, &options, now).is_err());
+
+        let mut mismatched = proof.clone();
+        mismatched.challenge = "nonce-2".to_string();
+        assert!(OwnershipService::verify_proof(&mismatched, &options, now).is_err());
+
+        assert!(OwnershipStatement::new("0x9858EfFD232B4033E47d90003D41EC34EcaEda94", now, "two\nlines").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_contract_wallet_signature() {
+        use ethers::providers::{JsonRpcError, MockResponse, Provider};
+
+        let now = Utc::now();
+        let (provider, mock) = Provider::mocked();
+
+        // Key-signed proofs need no RPC call
+        let eoa = proof("nonce-1", now);
+        let (_, kind) = OwnershipService::verify_with_contracts(&provider, "mock", &eoa.message, &eoa.signature, &VerifyOptions::default(), now)
+            .await
+            .unwrap();
+        assert_eq!(kind, SignatureKind::Ecdsa);
+
+        // A Safe signs with its owners' signatures, which do not recover to the Safe itself
+        let message = OwnershipStatement::new("0x1111111111111111111111111111111111111111", now, "nonce-2").unwrap().message();
+        let verify = |signature: &'static str| {
+            let (provider, message) = (&provider, &message);
+            async move { OwnershipService::verify_with_contracts(provider, "mock", message, signature, &VerifyOptions::default(), now).await }
+        };
+
+        let mut accepted = ERC1271_MAGIC_VALUE.to_vec();
+        accepted.resize(32, 0);
+        mock.push::<ethers::types::Bytes, _>(ethers::types::Bytes::from(accepted)).unwrap();
+        assert_eq!(verify("0x1234").await.unwrap().1, SignatureKind::Erc1271);
+
+        mock.push::<ethers::types::Bytes, _>(ethers::types::Bytes::from(vec![0u8; 32])).unwrap();
+        assert!(matches!(verify("0x1234").await, Err(crate::WalletError::Validation(_))));
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted: GS026".to_string(),
+            data: None,
+        }));
+        assert!(matches!(verify("0x1234").await, Err(crate::WalletError::Validation(_))));
+    }
+}
@@ -0,0 +1,262 @@
+//! # Paper Wallets
+//!
+//! A one-page, printable PDF backup sheet: the receive address with its QR
+//! code, the derivation path, the network and the creation date, and, only
+//! when asked for, the mnemonic in numbered word boxes. The PDF is written
+//! by hand with the viewer's built-in fonts and vector QR modules, so
+//! nothing is fetched, embedded or rendered outside this process.
+
+use crate::errors::{UserInputError, WalletResult};
+use crate::models::Wallet;
+use crate::services::qr::{QrCode, QrEcc};
+use std::fmt::Write;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+/// A4 in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+
+/// Side of the printed QR code, quiet zone included
+const QR_SIZE: f32 = 160.0;
+const QR_QUIET_ZONE: usize = 4;
+
+const WORD_COLUMNS: usize = 4;
+const WORD_BOX_HEIGHT: f32 = 30.0;
+const WORD_BOX_GAP: f32 = 6.0;
+
+/// What goes on a backup sheet
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PaperOptions {
+    /// Account whose address and path are printed
+    pub index: u32,
+    /// Print the mnemonic in numbered word boxes
+    pub include_mnemonic: bool,
+}
+
+/// Printable backup sheets
+pub struct PaperWalletService;
+
+impl PaperWalletService {
+    /// Render a backup sheet for `wallet` as a PDF document
+    pub fn render_pdf(wallet: &Wallet, options: &PaperOptions) -> WalletResult<Zeroizing<Vec<u8>>> {
+        if options.include_mnemonic && !wallet.has_mnemonic() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "include-mnemonic".to_string(),
+                value: "true".to_string(),
+                expected: "an HD wallet with a mnemonic".to_string(),
+            }
+            .into());
+        }
+
+        let (address, path) = if wallet.is_hd() {
+            let derived = wallet.derive_address(options.index)?;
+            (*derived.address(), derived.derivation_path().to_string())
+        } else {
+            (*wallet.address(), "none (imported private key)".to_string())
+        };
+
+        let mut page = Page::default();
+        page.text(Font::Bold, 22.0, MARGIN, 780.0, "Web3 Wallet Backup")?;
+        page.line(MARGIN, 768.0, PAGE_WIDTH - MARGIN, 768.0);
+
+        let mut details = vec![
+            ("Address", address.to_string()),
+            ("Network", wallet.network().to_string()),
+            ("Derivation path", path),
+            ("Created", wallet.created_at().format("%Y-%m-%d").to_string()),
+        ];
+        if wallet.uses_passphrase() {
+            details.push(("Passphrase", "REQUIRED (not printed on this sheet)".to_string()));
+        }
+        let mut y = 742.0;
+        for (label, value) in &details {
+            page.text(Font::Regular, 10.0, MARGIN, y, label)?;
+            page.text(Font::Mono, 10.0, MARGIN + 100.0, y, value)?;
+            y -= 18.0;
+        }
+
+        let qr_top = y - 10.0;
+        page.qr(&QrCode::encode(&address.to_string(), QrEcc::Medium)?, MARGIN, qr_top - QR_SIZE);
+        page.text(Font::Regular, 9.0, MARGIN + 24.0, qr_top - QR_SIZE - 14.0, "Scan to receive funds")?;
+
+        let heading = qr_top - QR_SIZE - 52.0;
+        if options.include_mnemonic {
+            page.text(Font::Bold, 13.0, MARGIN, heading, "Recovery phrase")?;
+            let mnemonic = Zeroizing::new(wallet.mnemonic().nfc().collect::<String>());
+            let box_width = (PAGE_WIDTH - 2.0 * MARGIN - (WORD_COLUMNS - 1) as f32 * WORD_BOX_GAP) / WORD_COLUMNS as f32;
+            for (i, word) in mnemonic.split_whitespace().enumerate() {
+                let x = MARGIN + (i % WORD_COLUMNS) as f32 * (box_width + WORD_BOX_GAP);
+                let top = heading - 14.0 - (i / WORD_COLUMNS) as f32 * (WORD_BOX_HEIGHT + WORD_BOX_GAP);
+                page.stroke_rect(x, top - WORD_BOX_HEIGHT, box_width, WORD_BOX_HEIGHT);
+                page.text(Font::Regular, 7.0, x + 4.0, top - 9.0, &(i + 1).to_string())?;
+                page.text(Font::Mono, 12.0, x + 18.0, top - 20.0, word)?;
+            }
+        } else {
+            page.text(Font::Regular, 10.0, MARGIN, heading, "The recovery phrase is not printed on this sheet.")?;
+        }
+
+        page.line(MARGIN, 96.0, PAGE_WIDTH - MARGIN, 96.0);
+        let warning = if options.include_mnemonic {
+            "Anyone holding this sheet controls the funds. Store it offline, away from cameras."
+        } else {
+            "This sheet holds no secrets; keep the recovery phrase separately."
+        };
+        page.text(Font::Regular, 9.0, MARGIN, 80.0, warning)?;
+
+        Ok(page.into_pdf())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Self::Regular => "F1",
+            Self::Bold => "F2",
+            Self::Mono => "F3",
+        }
+    }
+}
+
+/// Content stream of a single page
+#[derive(Default)]
+struct Page {
+    content: Zeroizing<String>,
+}
+
+impl Page {
+    fn text(&mut self, font: Font, size: f32, x: f32, y: f32, text: &str) -> WalletResult<()> {
+        let literal = pdf_string(text)?;
+        let _ = writeln!(self.content, "BT /{} {} Tf {:.2} {:.2} Td {} Tj ET", font.resource(), size, x, y, &*literal);
+        Ok(())
+    }
+
+    fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let _ = writeln!(self.content, "0.5 w {:.2} {:.2} m {:.2} {:.2} l S", x1, y1, x2, y2);
+    }
+
+    fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(self.content, "0.5 w {:.2} {:.2} {:.2} {:.2} re S", x, y, width, height);
+    }
+
+    /// Dark modules as filled squares, with `(x, y)` the bottom-left corner of the quiet zone
+    fn qr(&mut self, code: &QrCode, x: f32, y: f32) {
+        let modules = code.size() + 2 * QR_QUIET_ZONE;
+        let module = QR_SIZE / modules as f32;
+        for row in 0..code.size() {
+            for column in (0..code.size()).filter(|&column| code.is_dark(column, row)) {
+                let left = x + (column + QR_QUIET_ZONE) as f32 * module;
+                let bottom = y + (modules - QR_QUIET_ZONE - row - 1) as f32 * module;
+                let _ = writeln!(self.content, "{:.3} {:.3} {:.3} {:.3} re", left, bottom, module, module);
+            }
+        }
+        self.content.push_str("f\n");
+    }
+
+    /// Wrap the page in a complete PDF 1.4 document
+    fn into_pdf(self) -> Zeroizing<Vec<u8>> {
+        let font = |name: &str| format!("<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>", name);
+        let objects = Zeroizing::new(vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 5 0 R /F2 6 0 R /F3 7 0 R >> >> /Contents 4 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT
+            ),
+            format!("<< /Length {} >>\nstream\n{}endstream", self.content.len(), &*self.content),
+            font("Helvetica"),
+            font("Helvetica-Bold"),
+            font("Courier"),
+        ]);
+
+        let mut pdf = Zeroizing::new(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec());
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for (number, object) in objects.iter().enumerate() {
+            let _ = writeln!(xref, "{:010} 00000 n ", pdf.len());
+            pdf.extend_from_slice(Zeroizing::new(format!("{} 0 obj\n{}\nendobj\n", number + 1, object)).as_bytes());
+        }
+
+        let _ = write!(xref, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, pdf.len());
+        pdf.extend_from_slice(xref.as_bytes());
+        pdf
+    }
+}
+
+/// PDF literal string in WinAnsi encoding, failing on characters the standard fonts lack
+fn pdf_string(text: &str) -> WalletResult<Zeroizing<String>> {
+    let mut literal = Zeroizing::new(String::with_capacity(text.len() + 2));
+    literal.push('(');
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            ' '..='~' => literal.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(literal, "\\{:03o}", c as u32);
+            }
+            _ => {
+                return Err(UserInputError::InvalidParameters {
+                    parameter: "text".to_string(),
+                    value: format!("U+{:04X}", c as u32),
+                    expected: "Latin-1 text; print other wordlists with `wallet export --format paper`".to_string(),
+                }
+                .into())
+            }
+        }
+    }
+    literal.push(')');
+    Ok(literal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_render_pdf() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let public = PaperWalletService::render_pdf(&wallet, &PaperOptions::default()).unwrap();
+        // The binary comment after the header is not UTF-8; keep byte offsets intact
+        let text: String = public.iter().map(|&b| if b.is_ascii() { b as char } else { '?' }).collect();
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("(0x9858EfFD232B4033E47d90003D41EC34EcaEda94) Tj"));
+        assert!(text.contains("(m/44'/60'/0'/0/0) Tj"));
+        assert!(!text.contains("abandon"));
+
+        // Every xref entry points at the start of its object
+        let xref = text.rfind("startxref\n").unwrap();
+        let start: usize = text[xref + 10..].lines().next().unwrap().parse().unwrap();
+        assert!(text[start..].starts_with("xref\n0 8\n"));
+        for (number, entry) in text[start..].lines().skip(3).take(7).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(text[offset..].starts_with(&format!("{} 0 obj\n", number + 1)));
+        }
+
+        let options = PaperOptions {
+            index: 1,
+            include_mnemonic: true,
+        };
+        let secret = PaperWalletService::render_pdf(&wallet, &options).unwrap();
+        let text: String = secret.iter().map(|&b| b as char).collect();
+        assert_eq!(text.matches("(abandon) Tj").count(), 11);
+        assert!(text.contains("(12) Tj"));
+        assert!(text.contains("(m/44'/60'/0'/0/1) Tj"));
+
+        assert_eq!(&*pdf_string("a(b)\\ é").unwrap(), "(a\\(b\\)\\\\ \\351)");
+        assert!(pdf_string("あ").is_err());
+    }
+}
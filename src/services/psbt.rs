@@ -0,0 +1,399 @@
+//! # Partially Signed Transactions
+//!
+//! A self-describing JSON envelope that carries a multisig transaction
+//! between keystore holders until enough of them have signed it, in the
+//! spirit of Bitcoin's PSBT. One holder creates it with the transaction
+//! and the signer policy; every holder signs a copy offline; the copies
+//! are combined, and once the threshold is met the envelope is finalized
+//! into the call that executes the transaction.
+//!
+//! ```json
+//! {
+//!   "format": "web3wallet-psbt-eth/1",
+//!   "chainId": 11155111,
+//!   "description": "Pay the auditors",
+//!   "payload": {
+//!     "type": "safe",
+//!     "safe": "0x1111111111111111111111111111111111111111",
+//!     "transaction": { "to": "0x…", "value": "0xde0b6b3a7640000", "data": "0x", "operation": "call", "…": "…" }
+//!   },
+//!   "digest": "0x…",
+//!   "policy": { "threshold": 2, "signers": ["0x…", "0x…", "0x…"] },
+//!   "signatures": {
+//!     "0x…": { "signature": "0x…", "label": "alice", "backend": "local", "signedAt": "2026-10-16T12:00:00Z" }
+//!   }
+//! }
+//! ```
+//!
+//! - `format` names this layout and its version; other values are refused.
+//! - `payload` is tagged by `type`. The only type so far is `safe`: the
+//!   `SafeTx` fields of a Safe multisig, as in `wallet safe` proposal files.
+//! - `digest` is the hash every signer signs (the Safe's EIP-712
+//!   `safeTxHash`). It is recomputed from the payload whenever an envelope
+//!   is read, so an edited field cannot keep earlier signatures.
+//! - `policy` lists the keys allowed to sign and how many must. Signatures
+//!   by other keys are refused, and finalizing below the threshold fails.
+//! - `signatures` maps each signer address to its 65-byte ECDSA signature
+//!   over `digest` (`v` of 27 or 28), with an optional holder label, the
+//!   kind of key that signed, and when.
+
+use crate::errors::{UserInputError, ValidationError, WalletResult};
+use crate::models::EthereumAddress;
+use crate::services::safe::{SafeProposal, SafeService, SafeTransaction};
+use crate::services::signer::KeySigner;
+use chrono::{DateTime, Utc};
+use ethers::types::{Bytes, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Value of the `format` field
+pub const PSBT_FORMAT: &str = "web3wallet-psbt-eth/1";
+
+/// What the signers authorize
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PsbtPayload {
+    /// A transaction executed by a Safe multisig
+    Safe {
+        /// Safe contract executing the transaction
+        safe: EthereumAddress,
+        /// `SafeTx` fields
+        transaction: SafeTransaction,
+    },
+}
+
+/// Who may sign, and how many must
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerPolicy {
+    /// Signatures needed to finalize
+    pub threshold: usize,
+    /// Keys allowed to sign
+    pub signers: Vec<EthereumAddress>,
+}
+
+/// One collected signature with its signer's metadata
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PsbtSignature {
+    /// 65-byte ECDSA signature over the digest, hex with `0x`
+    pub signature: String,
+    /// Name the holder gave themselves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Kind of key that signed (local, ledger, pkcs11, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    /// When the signature was made
+    pub signed_at: DateTime<Utc>,
+}
+
+/// A transaction with the signatures collected so far
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartiallySignedTransaction {
+    /// Always [`PSBT_FORMAT`]
+    pub format: String,
+    /// EIP-155 chain ID the transaction is for
+    pub chain_id: u64,
+    /// Free-form note for the signers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Transaction being authorized
+    pub payload: PsbtPayload,
+    /// Hash each signer signs
+    pub digest: H256,
+    /// Allowed signers and threshold
+    pub policy: SignerPolicy,
+    /// Signatures by signer address
+    #[serde(default)]
+    pub signatures: BTreeMap<EthereumAddress, PsbtSignature>,
+}
+
+impl PartiallySignedTransaction {
+    /// Whether enough signatures have been collected to finalize
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= self.policy.threshold
+    }
+
+    /// Allowed signers that have not signed yet
+    pub fn pending_signers(&self) -> Vec<EthereumAddress> {
+        self.policy.signers.iter().filter(|signer| !self.signatures.contains_key(signer)).copied().collect()
+    }
+
+    /// The same transaction as a Safe proposal, for hashing and signature checks
+    fn safe_proposal(&self) -> SafeProposal {
+        let PsbtPayload::Safe { safe, transaction } = &self.payload;
+        SafeProposal {
+            safe: *safe,
+            chain_id: self.chain_id,
+            transaction: transaction.clone(),
+            safe_tx_hash: self.digest,
+            signatures: self.signatures.iter().map(|(signer, entry)| (*signer, entry.signature.clone())).collect(),
+        }
+    }
+}
+
+/// The call that executes a fully signed transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalizedTransaction {
+    /// EIP-155 chain ID to submit on
+    pub chain_id: u64,
+    /// Contract to call
+    pub to: EthereumAddress,
+    /// Wei to send with the call
+    pub value: U256,
+    /// Calldata
+    pub data: Bytes,
+    /// Signers whose signatures went in, in the order they were packed
+    pub signers: Vec<EthereumAddress>,
+}
+
+/// Creating, signing, combining and finalizing partially signed transactions
+pub struct PsbtService;
+
+impl PsbtService {
+    /// Unsigned envelope for `payload` under `policy`
+    pub fn create(
+        chain_id: u64,
+        payload: PsbtPayload,
+        policy: SignerPolicy,
+        description: Option<String>,
+    ) -> WalletResult<PartiallySignedTransaction> {
+        let mut signers = policy.signers.clone();
+        signers.sort();
+        signers.dedup();
+        if signers.len() != policy.signers.len() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "signer".to_string(),
+                value: policy.signers.len().to_string(),
+                expected: "each signer listed once".to_string(),
+            }
+            .into());
+        }
+        if policy.threshold == 0 || policy.threshold > signers.len() {
+            return Err(UserInputError::ValueOutOfRange {
+                parameter: "threshold".to_string(),
+                value: policy.threshold.to_string(),
+                range: format!("1-{}", signers.len()),
+            }
+            .into());
+        }
+
+        let mut psbt = PartiallySignedTransaction {
+            format: PSBT_FORMAT.to_string(),
+            chain_id,
+            description,
+            payload,
+            digest: H256::zero(),
+            policy: SignerPolicy {
+                threshold: policy.threshold,
+                signers,
+            },
+            signatures: BTreeMap::new(),
+        };
+        psbt.digest = Self::digest(&psbt);
+        Ok(psbt)
+    }
+
+    /// Hash the signers sign, computed from the payload
+    pub fn digest(psbt: &PartiallySignedTransaction) -> H256 {
+        let PsbtPayload::Safe { safe, transaction } = &psbt.payload;
+        SafeService::transaction_hash(safe, psbt.chain_id, transaction)
+    }
+
+    /// Check the format, the digest, the policy and every signature
+    pub fn verify(psbt: &PartiallySignedTransaction) -> WalletResult<()> {
+        if psbt.format != PSBT_FORMAT {
+            return Err(invalid(format!("unsupported format '{}', expected {}", psbt.format, PSBT_FORMAT)).into());
+        }
+        let expected = Self::digest(psbt);
+        if psbt.digest != expected {
+            return Err(invalid(format!(
+                "digest {:?} does not match the payload (expected {:?})",
+                psbt.digest, expected
+            ))
+            .into());
+        }
+        if psbt.policy.threshold == 0 || psbt.policy.threshold > psbt.policy.signers.len() {
+            return Err(invalid(format!(
+                "threshold {} is not between 1 and the {} listed signers",
+                psbt.policy.threshold,
+                psbt.policy.signers.len()
+            ))
+            .into());
+        }
+        if let Some(signer) = psbt.signatures.keys().find(|signer| !psbt.policy.signers.contains(signer)) {
+            return Err(invalid(format!("{} signed but is not one of the allowed signers", signer)).into());
+        }
+        SafeService::verify(&psbt.safe_proposal())
+    }
+
+    /// Add `signer`'s signature, returning the signer address
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = signer.backend()))]
+    pub fn sign(
+        psbt: &mut PartiallySignedTransaction,
+        signer: &dyn KeySigner,
+        label: Option<String>,
+    ) -> WalletResult<EthereumAddress> {
+        Self::verify(psbt)?;
+        let address: EthereumAddress = signer.address().parse()?;
+        if !psbt.policy.signers.contains(&address) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "signer".to_string(),
+                value: address.to_string(),
+                expected: format!(
+                    "one of the allowed signers: {}",
+                    psbt.policy.signers.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+                ),
+            }
+            .into());
+        }
+
+        let signature = signer.sign_hash(&psbt.digest.0)?;
+        psbt.signatures.insert(
+            address,
+            PsbtSignature {
+                signature: format!("0x{}", signature),
+                label,
+                backend: Some(signer.backend().to_string()),
+                signed_at: Utc::now(),
+            },
+        );
+        Ok(address)
+    }
+
+    /// Combine copies of one envelope signed by different holders
+    pub fn combine(copies: &[PartiallySignedTransaction]) -> WalletResult<PartiallySignedTransaction> {
+        let (first, rest) = copies.split_first().ok_or_else(|| UserInputError::MissingParameter {
+            parameter: "psbt".to_string(),
+            hint: "Give at least one signed envelope".to_string(),
+        })?;
+        Self::verify(first)?;
+
+        let mut combined = first.clone();
+        for copy in rest {
+            Self::verify(copy)?;
+            if copy.digest != combined.digest || copy.policy != combined.policy {
+                return Err(invalid(format!(
+                    "envelopes are for different transactions or signer policies ({:?} and {:?})",
+                    combined.digest, copy.digest
+                ))
+                .into());
+            }
+            for (signer, signature) in &copy.signatures {
+                combined.signatures.entry(*signer).or_insert_with(|| signature.clone());
+            }
+        }
+        Ok(combined)
+    }
+
+    /// The executing call, once the threshold is met
+    pub fn finalize(psbt: &PartiallySignedTransaction) -> WalletResult<FinalizedTransaction> {
+        Self::verify(psbt)?;
+        if !psbt.is_complete() {
+            return Err(UserInputError::MissingParameter {
+                parameter: "signatures".to_string(),
+                hint: format!(
+                    "{} of {} required signatures collected; still possible: {}",
+                    psbt.signatures.len(),
+                    psbt.policy.threshold,
+                    psbt.pending_signers().iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+                ),
+            }
+            .into());
+        }
+
+        let PsbtPayload::Safe { safe, .. } = &psbt.payload;
+        Ok(FinalizedTransaction {
+            chain_id: psbt.chain_id,
+            to: *safe,
+            value: U256::zero(),
+            data: SafeService::exec_calldata(&psbt.safe_proposal())?,
+            signers: psbt.signatures.keys().copied().collect(),
+        })
+    }
+}
+
+fn invalid(details: String) -> ValidationError {
+    ValidationError::IntegrityCheckFailed {
+        data_type: "partially signed transaction".to_string(),
+        details,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Network, Wallet};
+    use crate::services::LocalKeySigner;
+
+    #[test]
+    fn test_signing_round() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let holders: Vec<_> = (0..3).map(|index| LocalKeySigner::from_wallet(&wallet, index).unwrap()).collect();
+        let addresses: Vec<EthereumAddress> = holders.iter().map(|holder| holder.address().parse().unwrap()).collect();
+
+        let payload = PsbtPayload::Safe {
+            safe: "0x1111111111111111111111111111111111111111".parse().unwrap(),
+            transaction: SafeTransaction {
+                to: "0x000000000000000000000000000000000000dEaD".parse().unwrap(),
+                value: U256::exp10(18),
+                nonce: U256::from(7),
+                ..SafeTransaction::default()
+            },
+        };
+        let policy = SignerPolicy {
+            threshold: 2,
+            signers: addresses.clone(),
+        };
+        let psbt = PsbtService::create(11_155_111, payload.clone(), policy.clone(), None).unwrap();
+        let PsbtPayload::Safe { safe, transaction } = &payload;
+        assert_eq!(psbt.digest, SafeService::transaction_hash(safe, 11_155_111, transaction));
+        assert!(PsbtService::create(1, payload.clone(), SignerPolicy { threshold: 4, ..policy.clone() }, None).is_err());
+        let twice = SignerPolicy {
+            threshold: 1,
+            signers: vec![addresses[0], addresses[0]],
+        };
+        assert!(PsbtService::create(1, payload.clone(), twice, None).is_err());
+
+        // Each holder signs a copy of the file
+        let copies: Vec<_> = [2, 0]
+            .into_iter()
+            .map(|holder| {
+                let mut copy: PartiallySignedTransaction = serde_json::from_str(&serde_json::to_string(&psbt).unwrap()).unwrap();
+                PsbtService::sign(&mut copy, &holders[holder], Some(format!("holder {}", holder))).unwrap();
+                copy
+            })
+            .collect();
+        assert!(!copies[0].is_complete());
+        assert!(PsbtService::finalize(&copies[0]).is_err());
+
+        let combined = PsbtService::combine(&copies).unwrap();
+        assert!(combined.is_complete());
+        assert_eq!(combined.pending_signers(), vec![addresses[1]]);
+        let finalized = PsbtService::finalize(&combined).unwrap();
+        assert_eq!(finalized.to, *safe);
+        assert_eq!(&finalized.data[..4], &[0x6a, 0x76, 0x12, 0x02]);
+        assert!(finalized.signers[0] < finalized.signers[1]);
+
+        // Keys outside the policy cannot sign, and edited payloads lose their signatures
+        let outsider = LocalKeySigner::from_wallet(&wallet, 3).unwrap();
+        assert!(PsbtService::sign(&mut combined.clone(), &outsider, None).is_err());
+        let mut tampered = combined.clone();
+        let PsbtPayload::Safe { transaction, .. } = &mut tampered.payload;
+        transaction.value = U256::exp10(19);
+        assert!(PsbtService::finalize(&tampered).is_err());
+        tampered.digest = PsbtService::digest(&tampered);
+        assert!(PsbtService::verify(&tampered).is_err());
+
+        let mut other = copies[1].clone();
+        other.policy.threshold = 1;
+        assert!(PsbtService::combine(&[combined, other]).is_err());
+    }
+}
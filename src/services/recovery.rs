@@ -0,0 +1,299 @@
+//! # Recovery Service
+//!
+//! Fills in unreadable words of a damaged mnemonic backup. Unknown positions
+//! (`?`) are enumerated over the whole wordlist on every available core. The
+//! BIP39 checksum discards most guesses; a known address or an on-chain
+//! activity scan then narrows the remainder down to the original phrase.
+
+use crate::config;
+use crate::errors::{CryptographicError, NetworkError, UserInputError, WalletResult};
+use crate::models::{EthereumAddress, Wallet};
+use bip39::Language;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::U256;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use unicode_normalization::UnicodeNormalization;
+
+/// Most unknown words a search will enumerate (2048^2 ≈ 4.2M phrases)
+pub const MAX_UNKNOWN_WORDS: usize = 2;
+
+/// Most checksum-valid candidates an on-chain scan will query
+pub const MAX_BALANCE_SCAN: usize = 1_000;
+
+/// Size of every BIP39 wordlist
+const WORDLIST_SIZE: u64 = 2048;
+
+/// A mnemonic with some words unknown
+#[derive(Debug, Clone)]
+pub struct RecoveryPattern {
+    language: Language,
+    words: Vec<Option<u16>>,
+}
+
+impl RecoveryPattern {
+    /// Parse a phrase where unknown words are written as `?`
+    pub fn parse(phrase: &str, language: Language) -> WalletResult<Self> {
+        let words = phrase
+            .split_whitespace()
+            .enumerate()
+            .map(|(position, word)| {
+                if word == "?" {
+                    return Ok(None);
+                }
+                let normalized: String = word.nfkd().collect();
+                language.find_word(&normalized).map(Some).ok_or_else(|| {
+                    UserInputError::InvalidParameters {
+                        parameter: format!("mnemonic word {}", position + 1),
+                        value: word.to_string(),
+                        expected: "a BIP39 word or ? for an unknown word".to_string(),
+                    }
+                    .into()
+                })
+            })
+            .collect::<WalletResult<Vec<_>>>()?;
+
+        if !config::is_supported_word_count(words.len() as u8) {
+            return Err(CryptographicError::InvalidMnemonic {
+                details: format!("Unsupported word count: {}", words.len()),
+                suggestion: "Write every position, using ? for unknown words (12 or 24 in total)".to_string(),
+            }
+            .into());
+        }
+
+        let pattern = Self { language, words };
+        let unknown = pattern.unknown_count();
+        if unknown == 0 || unknown > MAX_UNKNOWN_WORDS {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "mnemonic".to_string(),
+                value: format!("{} unknown word(s)", unknown),
+                expected: format!("between 1 and {} words marked with ?", MAX_UNKNOWN_WORDS),
+            }
+            .into());
+        }
+
+        Ok(pattern)
+    }
+
+    /// Number of unknown positions
+    pub fn unknown_count(&self) -> usize {
+        self.words.iter().filter(|word| word.is_none()).count()
+    }
+
+    /// Number of phrases the search enumerates
+    pub fn search_space(&self) -> u64 {
+        WORDLIST_SIZE.pow(self.unknown_count() as u32)
+    }
+
+    /// Fill the unknown positions with the base-2048 digits of `combination`
+    fn fill(&self, mut combination: u64, indices: &mut [u16]) {
+        for (slot, word) in indices.iter_mut().zip(&self.words) {
+            *slot = match word {
+                Some(index) => *index,
+                None => {
+                    let index = (combination % WORDLIST_SIZE) as u16;
+                    combination /= WORDLIST_SIZE;
+                    index
+                }
+            };
+        }
+    }
+
+    fn phrase(&self, indices: &[u16]) -> String {
+        let list = self.language.word_list();
+        indices.iter().map(|&i| list[i as usize]).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// A recovered phrase with on-chain activity
+#[derive(Debug, Clone)]
+pub struct ActiveCandidate {
+    /// Recovered mnemonic
+    pub mnemonic: String,
+    /// Primary address of the mnemonic
+    pub address: EthereumAddress,
+    /// Current balance in wei
+    pub balance: U256,
+    /// Number of transactions sent
+    pub transactions: U256,
+}
+
+/// Missing-word recovery
+pub struct RecoveryService;
+
+impl RecoveryService {
+    /// Every completion of `pattern` with a valid BIP39 checksum
+    #[tracing::instrument(level = "debug", skip_all, fields(unknown = pattern.unknown_count()))]
+    pub fn checksum_candidates(pattern: &RecoveryPattern, low_resource: bool) -> Vec<String> {
+        Self::search(pattern, false, low_resource, |indices| Some(pattern.phrase(indices)))
+    }
+
+    /// The completion of `pattern` whose primary address is `target`
+    #[tracing::instrument(level = "debug", skip_all, fields(unknown = pattern.unknown_count()))]
+    pub fn find_address(
+        pattern: &RecoveryPattern,
+        target: &str,
+        passphrase: &str,
+        low_resource: bool,
+    ) -> WalletResult<Option<String>> {
+        let target: EthereumAddress = target.parse()?;
+
+        let found = Self::search(pattern, true, low_resource, |indices| {
+            let phrase = pattern.phrase(indices);
+            let address = Wallet::primary_address(&phrase, passphrase).ok()?;
+            (address == target).then_some(phrase)
+        });
+        Ok(found.into_iter().next())
+    }
+
+    /// Query balance and nonce of each candidate's primary address, keeping the active ones
+    #[tracing::instrument(level = "debug", skip_all, fields(candidates = candidates.len()))]
+    pub async fn scan_balances(
+        candidates: &[String],
+        passphrase: &str,
+        rpc_url: &str,
+    ) -> WalletResult<Vec<ActiveCandidate>> {
+        if candidates.len() > MAX_BALANCE_SCAN {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "check-balance".to_string(),
+                value: format!("{} candidates", candidates.len()),
+                expected: format!("at most {} candidates; pass --address to narrow the search", MAX_BALANCE_SCAN),
+            }
+            .into());
+        }
+
+        let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| NetworkError::InvalidConfiguration {
+            key: "rpc_endpoints".to_string(),
+            details: e.to_string(),
+        })?;
+        let rpc_error = |e: ethers::providers::ProviderError| NetworkError::ConnectivityFailure {
+            endpoint: rpc_url.to_string(),
+            details: e.to_string(),
+        };
+
+        let mut active = Vec::new();
+        for mnemonic in candidates {
+            let address = Wallet::primary_address(mnemonic, passphrase)?;
+            let parsed = ethers::types::Address::from(address);
+
+            let balance = provider.get_balance(parsed, None).await.map_err(rpc_error)?;
+            let transactions = provider.get_transaction_count(parsed, None).await.map_err(rpc_error)?;
+            if !balance.is_zero() || !transactions.is_zero() {
+                active.push(ActiveCandidate {
+                    mnemonic: mnemonic.clone(),
+                    address,
+                    balance,
+                    transactions,
+                });
+            }
+        }
+
+        Ok(active)
+    }
+
+    /// Enumerate all completions on every core (one in low-resource mode), keeping checksum-valid ones accepted by `check`
+    fn search<T: Send>(
+        pattern: &RecoveryPattern,
+        stop_on_first: bool,
+        low_resource: bool,
+        check: impl Fn(&[u16]) -> Option<T> + Sync,
+    ) -> Vec<T> {
+        let total = pattern.search_space();
+        let threads = (config::resources::parallelism(usize::MAX, low_resource) as u64).min(total);
+        let chunk = (total + threads - 1) / threads;
+        let done = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|thread| {
+                    let (check, done) = (&check, &done);
+                    scope.spawn(move || {
+                        let mut indices = vec![0u16; pattern.words.len()];
+                        let mut found = Vec::new();
+                        for combination in thread * chunk..((thread + 1) * chunk).min(total) {
+                            if done.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            pattern.fill(combination, &mut indices);
+                            if !Self::checksum_matches(&indices) {
+                                continue;
+                            }
+                            if let Some(result) = check(&indices) {
+                                found.push(result);
+                                if stop_on_first {
+                                    done.store(true, Ordering::Relaxed);
+                                }
+                            }
+                        }
+                        found
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_default())
+                .collect()
+        })
+    }
+
+    /// Check the BIP39 checksum of a sequence of word indices
+    fn checksum_matches(indices: &[u16]) -> bool {
+        let checksum_bits = indices.len() / 3;
+        let entropy_bytes = checksum_bits * 4;
+
+        let mut bytes = vec![0u8; (indices.len() * 11 + 7) / 8];
+        for (position, &index) in indices.iter().enumerate() {
+            for bit in 0..11 {
+                if index & (1 << (10 - bit)) != 0 {
+                    let offset = position * 11 + bit;
+                    bytes[offset / 8] |= 0x80 >> (offset % 8);
+                }
+            }
+        }
+
+        let expected = Sha256::digest(&bytes[..entropy_bytes])[0] >> (8 - checksum_bits);
+        let actual = bytes[entropy_bytes] >> (8 - checksum_bits);
+        expected == actual
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_checksum_matches_bip39() {
+        let pattern = RecoveryPattern::parse(&TEST_MNEMONIC.replacen("abandon", "?", 1), Language::English).unwrap();
+        let candidates = RecoveryService::checksum_candidates(&pattern, false);
+
+        assert!(candidates.iter().any(|c| c == TEST_MNEMONIC));
+        for candidate in &candidates {
+            assert!(bip39::Mnemonic::parse_in(Language::English, candidate.as_str()).is_ok());
+        }
+        // A 4-bit checksum keeps roughly one in sixteen guesses
+        assert!(candidates.len() > 64 && candidates.len() < 256);
+    }
+
+    #[test]
+    fn test_find_address() {
+        let pattern = RecoveryPattern::parse(
+            "abandon abandon abandon abandon abandon ? abandon abandon abandon abandon abandon about",
+            Language::English,
+        )
+        .unwrap();
+
+        let found = RecoveryService::find_address(&pattern, "0x9858EfFD232B4033E47d90003D41EC34EcaEda94", "", false).unwrap();
+        assert_eq!(found.as_deref(), Some(TEST_MNEMONIC));
+    }
+
+    #[test]
+    fn test_pattern_limits() {
+        assert!(RecoveryPattern::parse(TEST_MNEMONIC, Language::English).is_err());
+        assert!(RecoveryPattern::parse("? ? ? abandon abandon abandon abandon abandon abandon abandon abandon about", Language::English).is_err());
+        assert!(RecoveryPattern::parse("? abandon abandon", Language::English).is_err());
+        assert!(RecoveryPattern::parse("? abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abuot", Language::English).is_err());
+    }
+}
@@ -0,0 +1,392 @@
+//! # Safe Multisig Transactions
+//!
+//! Offline signing ceremonies for Safe (formerly Gnosis Safe) multisig
+//! wallets, version 1.3.0 and later. A proposal file holds the Safe, its
+//! chain, the `SafeTx` fields and their EIP-712 hash. Each owner signs the
+//! file on their own machine and hands back a copy carrying their
+//! signature; the copies are merged, and whoever submits the transaction
+//! takes the packed signatures (or the full `execTransaction` calldata)
+//! from the merged file.
+//!
+//! The hash is recomputed from the fields whenever a file is loaded,
+//! signed or merged, so an edited field cannot ride along under another
+//! transaction's signatures. Signatures are plain ECDSA over the hash
+//! (`v` of 27 or 28), the form `execTransaction` checks with `ecrecover`.
+
+use crate::errors::{UserInputError, ValidationError, WalletResult};
+use crate::models::EthereumAddress;
+use crate::services::signer::KeySigner;
+use ethers::abi::{self, Token};
+use ethers::types::{Address, Bytes, Signature, H256, U256};
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// EIP-712 domain type of Safe 1.3.0 and later
+const DOMAIN_TYPE: &str = "EIP712Domain(uint256 chainId,address verifyingContract)";
+
+/// EIP-712 type of a Safe transaction
+const SAFE_TX_TYPE: &str = "SafeTx(address to,uint256 value,bytes data,uint8 operation,uint256 safeTxGas,uint256 baseGas,uint256 gasPrice,address gasToken,address refundReceiver,uint256 nonce)";
+
+/// Signature of the function that executes a signed Safe transaction
+const EXEC_TRANSACTION: &str = "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)";
+
+/// How the Safe invokes the target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafeOperation {
+    /// Ordinary call
+    #[default]
+    Call,
+    /// Delegate call: the target's code runs with the Safe's storage and funds
+    #[serde(rename = "delegatecall")]
+    DelegateCall,
+}
+
+impl SafeOperation {
+    /// Every operation
+    pub const ALL: [SafeOperation; 2] = [SafeOperation::Call, SafeOperation::DelegateCall];
+
+    /// Name as written on the command line and in proposal files
+    pub fn name(self) -> &'static str {
+        match self {
+            SafeOperation::Call => "call",
+            SafeOperation::DelegateCall => "delegatecall",
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            SafeOperation::Call => 0,
+            SafeOperation::DelegateCall => 1,
+        }
+    }
+}
+
+impl FromStr for SafeOperation {
+    type Err = crate::WalletError;
+
+    fn from_str(s: &str) -> WalletResult<Self> {
+        Self::ALL.into_iter().find(|operation| operation.name() == s).ok_or_else(|| {
+            UserInputError::InvalidParameters {
+                parameter: "operation".to_string(),
+                value: s.to_string(),
+                expected: "call or delegatecall".to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+/// The `SafeTx` fields owners sign
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeTransaction {
+    /// Target of the call
+    pub to: EthereumAddress,
+    /// Wei sent with the call
+    pub value: U256,
+    /// Calldata
+    pub data: Bytes,
+    /// Call or delegate call
+    pub operation: SafeOperation,
+    /// Gas reserved for the inner call (0 for all remaining gas)
+    pub safe_tx_gas: U256,
+    /// Gas refunded on top of the inner call
+    pub base_gas: U256,
+    /// Gas price used for the refund (0 for no refund)
+    pub gas_price: U256,
+    /// Token the refund is paid in (zero address for ETH)
+    pub gas_token: EthereumAddress,
+    /// Receiver of the refund (zero address for the submitter)
+    pub refund_receiver: EthereumAddress,
+    /// Safe nonce the transaction is for
+    pub nonce: U256,
+}
+
+/// A Safe transaction with the owner signatures collected so far
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeProposal {
+    /// Safe contract executing the transaction
+    pub safe: EthereumAddress,
+    /// EIP-155 chain ID of the Safe
+    pub chain_id: u64,
+    /// Transaction fields
+    pub transaction: SafeTransaction,
+    /// EIP-712 hash of the transaction, as shown by the Safe web interface
+    pub safe_tx_hash: H256,
+    /// 65-byte signatures by owner, hex with `0x`
+    #[serde(default)]
+    pub signatures: BTreeMap<EthereumAddress, String>,
+}
+
+impl SafeProposal {
+    /// Owners that have signed
+    pub fn signers(&self) -> Vec<EthereumAddress> {
+        self.signatures.keys().copied().collect()
+    }
+}
+
+/// Safe transaction hashing, signing and signature collection
+pub struct SafeService;
+
+impl SafeService {
+    /// EIP-712 hash of `transaction` for the Safe at `safe` on chain `chain_id`
+    pub fn transaction_hash(safe: &EthereumAddress, chain_id: u64, transaction: &SafeTransaction) -> H256 {
+        let domain_separator = keccak256(abi::encode(&[
+            Token::FixedBytes(keccak256(DOMAIN_TYPE).to_vec()),
+            Token::Uint(U256::from(chain_id)),
+            Token::Address(Address::from(*safe)),
+        ]));
+        let struct_hash = keccak256(abi::encode(&[
+            Token::FixedBytes(keccak256(SAFE_TX_TYPE).to_vec()),
+            Token::Address(Address::from(transaction.to)),
+            Token::Uint(transaction.value),
+            Token::FixedBytes(keccak256(&transaction.data).to_vec()),
+            Token::Uint(U256::from(transaction.operation.code())),
+            Token::Uint(transaction.safe_tx_gas),
+            Token::Uint(transaction.base_gas),
+            Token::Uint(transaction.gas_price),
+            Token::Address(Address::from(transaction.gas_token)),
+            Token::Address(Address::from(transaction.refund_receiver)),
+            Token::Uint(transaction.nonce),
+        ]));
+
+        let mut digest = Vec::with_capacity(66);
+        digest.extend_from_slice(&[0x19, 0x01]);
+        digest.extend_from_slice(&domain_separator);
+        digest.extend_from_slice(&struct_hash);
+        H256(keccak256(digest))
+    }
+
+    /// Unsigned proposal for `transaction`
+    pub fn propose(safe: EthereumAddress, chain_id: u64, transaction: SafeTransaction) -> SafeProposal {
+        let safe_tx_hash = Self::transaction_hash(&safe, chain_id, &transaction);
+        SafeProposal {
+            safe,
+            chain_id,
+            transaction,
+            safe_tx_hash,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// Check the stored hash and every signature of a proposal
+    pub fn verify(proposal: &SafeProposal) -> WalletResult<()> {
+        let expected = Self::transaction_hash(&proposal.safe, proposal.chain_id, &proposal.transaction);
+        if proposal.safe_tx_hash != expected {
+            return Err(invalid(format!(
+                "safeTxHash {:?} does not match the transaction fields (expected {:?})",
+                proposal.safe_tx_hash, expected
+            ))
+            .into());
+        }
+        for (owner, signature) in &proposal.signatures {
+            let recovered = recover(proposal.safe_tx_hash, signature)?;
+            if recovered != *owner {
+                return Err(invalid(format!("signature listed for {} was made by {}", owner, recovered)).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `signer`'s signature to a proposal, returning the owner address
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = signer.backend()))]
+    pub fn sign(proposal: &mut SafeProposal, signer: &dyn KeySigner) -> WalletResult<EthereumAddress> {
+        Self::verify(proposal)?;
+        let owner: EthereumAddress = signer.address().parse()?;
+        let signature = signer.sign_hash(&proposal.safe_tx_hash.0)?;
+        proposal.signatures.insert(owner, format!("0x{}", signature));
+        Ok(owner)
+    }
+
+    /// Combine copies of one proposal signed by different owners
+    pub fn merge(proposals: &[SafeProposal]) -> WalletResult<SafeProposal> {
+        let (first, rest) = proposals.split_first().ok_or_else(|| UserInputError::MissingParameter {
+            parameter: "proposal".to_string(),
+            hint: "Give at least one signed proposal file".to_string(),
+        })?;
+        Self::verify(first)?;
+
+        let mut merged = first.clone();
+        for proposal in rest {
+            Self::verify(proposal)?;
+            if proposal.safe_tx_hash != merged.safe_tx_hash {
+                return Err(invalid(format!(
+                    "proposals are for different transactions ({:?} and {:?})",
+                    merged.safe_tx_hash, proposal.safe_tx_hash
+                ))
+                .into());
+            }
+            merged.signatures.extend(proposal.signatures.clone());
+        }
+        Ok(merged)
+    }
+
+    /// Signatures packed as `execTransaction` expects them: sorted by owner, `r || s || v` each
+    pub fn packed_signatures(proposal: &SafeProposal) -> WalletResult<Bytes> {
+        Self::verify(proposal)?;
+        let mut packed = Vec::with_capacity(proposal.signatures.len() * 65);
+        // BTreeMap order is ascending by address, which the Safe requires
+        for signature in proposal.signatures.values() {
+            packed.extend_from_slice(&parse_signature(signature)?.to_vec());
+        }
+        Ok(packed.into())
+    }
+
+    /// Calldata of `execTransaction` on the Safe, with the collected signatures
+    pub fn exec_calldata(proposal: &SafeProposal) -> WalletResult<Bytes> {
+        let signatures = Self::packed_signatures(proposal)?;
+        let transaction = &proposal.transaction;
+
+        let mut calldata = keccak256(EXEC_TRANSACTION)[..4].to_vec();
+        calldata.extend(abi::encode(&[
+            Token::Address(Address::from(transaction.to)),
+            Token::Uint(transaction.value),
+            Token::Bytes(transaction.data.to_vec()),
+            Token::Uint(U256::from(transaction.operation.code())),
+            Token::Uint(transaction.safe_tx_gas),
+            Token::Uint(transaction.base_gas),
+            Token::Uint(transaction.gas_price),
+            Token::Address(Address::from(transaction.gas_token)),
+            Token::Address(Address::from(transaction.refund_receiver)),
+            Token::Bytes(signatures.to_vec()),
+        ]));
+        Ok(calldata.into())
+    }
+}
+
+fn invalid(details: String) -> ValidationError {
+    ValidationError::IntegrityCheckFailed {
+        data_type: "Safe proposal".to_string(),
+        details,
+    }
+}
+
+fn parse_signature(signature: &str) -> WalletResult<Signature> {
+    let parsed: Signature = signature
+        .trim()
+        .trim_start_matches("0x")
+        .parse()
+        .map_err(|e: ethers::types::SignatureError| invalid(format!("invalid signature {}: {}", signature, e)))?;
+    if parsed.v != 27 && parsed.v != 28 {
+        return Err(invalid(format!("signature {} has v = {}; only ECDSA signatures (27 or 28) are supported", signature, parsed.v)).into());
+    }
+    Ok(parsed)
+}
+
+fn recover(hash: H256, signature: &str) -> WalletResult<EthereumAddress> {
+    let recovered = parse_signature(signature)?
+        .recover(hash)
+        .map_err(|e| invalid(format!("signature {} does not recover: {}", signature, e)))?;
+    Ok(recovered.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Network, Wallet};
+    use crate::services::LocalKeySigner;
+    use ethers::types::transaction::eip712::{Eip712, TypedData};
+
+    fn transfer() -> SafeProposal {
+        let transaction = SafeTransaction {
+            to: "0x000000000000000000000000000000000000dEaD".parse().unwrap(),
+            value: U256::exp10(18),
+            data: Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]),
+            nonce: U256::from(7),
+            ..SafeTransaction::default()
+        };
+        SafeService::propose("0x1111111111111111111111111111111111111111".parse().unwrap(), 11_155_111, transaction)
+    }
+
+    #[test]
+    fn test_safe_tx_hash_is_eip712() {
+        let proposal = transfer();
+        let typed: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "SafeTx": [
+                    {"name": "to", "type": "address"},
+                    {"name": "value", "type": "uint256"},
+                    {"name": "data", "type": "bytes"},
+                    {"name": "operation", "type": "uint8"},
+                    {"name": "safeTxGas", "type": "uint256"},
+                    {"name": "baseGas", "type": "uint256"},
+                    {"name": "gasPrice", "type": "uint256"},
+                    {"name": "gasToken", "type": "address"},
+                    {"name": "refundReceiver", "type": "address"},
+                    {"name": "nonce", "type": "uint256"}
+                ]
+            },
+            "primaryType": "SafeTx",
+            "domain": {"chainId": 11155111, "verifyingContract": "0x1111111111111111111111111111111111111111"},
+            "message": {
+                "to": "0x000000000000000000000000000000000000dEaD",
+                "value": "1000000000000000000",
+                "data": "0xdeadbeef",
+                "operation": 0,
+                "safeTxGas": 0,
+                "baseGas": 0,
+                "gasPrice": 0,
+                "gasToken": "0x0000000000000000000000000000000000000000",
+                "refundReceiver": "0x0000000000000000000000000000000000000000",
+                "nonce": 7
+            }
+        }))
+        .unwrap();
+        assert_eq!(proposal.safe_tx_hash.0, typed.encode_eip712().unwrap());
+
+        let mut delegate = proposal.transaction.clone();
+        delegate.operation = "delegatecall".parse().unwrap();
+        assert_ne!(SafeService::transaction_hash(&proposal.safe, proposal.chain_id, &delegate), proposal.safe_tx_hash);
+        assert_ne!(SafeService::transaction_hash(&proposal.safe, 1, &proposal.transaction), proposal.safe_tx_hash);
+    }
+
+    #[test]
+    fn test_safe_signing_ceremony() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let proposal = transfer();
+
+        // Each owner signs their own copy of the proposal file
+        let mut copies = Vec::new();
+        for index in [1, 0] {
+            let mut copy: SafeProposal = serde_json::from_str(&serde_json::to_string(&proposal).unwrap()).unwrap();
+            SafeService::sign(&mut copy, &LocalKeySigner::from_wallet(&wallet, index).unwrap()).unwrap();
+            copies.push(copy);
+        }
+        let merged = SafeService::merge(&copies).unwrap();
+        let signers = merged.signers();
+        assert_eq!(signers.len(), 2);
+        assert!(signers[0] < signers[1]);
+
+        let packed = SafeService::packed_signatures(&merged).unwrap();
+        assert_eq!(packed.len(), 130);
+        assert_eq!(&packed[..65], &parse_signature(&merged.signatures[&signers[0]]).unwrap().to_vec()[..]);
+        let calldata = SafeService::exec_calldata(&merged).unwrap();
+        assert_eq!(&calldata[..4], &[0x6a, 0x76, 0x12, 0x02]);
+
+        // An edited field no longer matches the hash the owners signed
+        let mut tampered = merged.clone();
+        tampered.transaction.value = U256::from(2) * U256::exp10(18);
+        assert!(SafeService::verify(&tampered).is_err());
+        tampered.safe_tx_hash = SafeService::transaction_hash(&tampered.safe, tampered.chain_id, &tampered.transaction);
+        assert!(SafeService::verify(&tampered).is_err());
+
+        // Copies of another transaction do not merge
+        let mut other = transfer();
+        other.transaction.nonce = U256::from(8);
+        other = SafeService::propose(other.safe, other.chain_id, other.transaction);
+        assert!(SafeService::merge(&[merged, other]).is_err());
+    }
+}
@@ -0,0 +1,770 @@
+//! # Signer Service
+//!
+//! Backend-neutral signing. Callers hold a [`KeySigner`] and only ever ask
+//! it for signatures, so the same code path works whether the key was
+//! decrypted from a keystore or lives on a PKCS#11 token (HSM, smartcard)
+//! that never releases raw key material.
+
+use crate::errors::{CryptographicError, WalletResult};
+use crate::models::Wallet;
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Source of secp256k1 signatures for one account
+pub trait KeySigner: Send + Sync {
+    /// Backend name for display
+    fn backend(&self) -> &'static str;
+
+    /// Checksummed address of the signing key
+    fn address(&self) -> String;
+
+    /// Uncompressed SEC1 public key (65 bytes, `0x04` prefix)
+    fn public_key(&self) -> Vec<u8>;
+
+    /// Sign a 32-byte digest
+    fn sign_hash(&self, hash: &[u8; 32]) -> WalletResult<Signature>;
+
+    /// Sign a message with the EIP-191 personal-message prefix
+    fn sign_message(&self, message: &[u8]) -> WalletResult<Signature> {
+        self.sign_hash(&ethers::utils::hash_message(message).0)
+    }
+
+    /// Sign a transaction, with `v` encoded as its type expects (EIP-155 for legacy)
+    fn sign_transaction(&self, transaction: &TypedTransaction) -> WalletResult<Signature> {
+        let mut signature = self.sign_hash(&transaction.sighash().0)?;
+        let recovery_id = signature.v - 27;
+        signature.v = match (transaction, transaction.chain_id()) {
+            (TypedTransaction::Legacy(_), Some(chain_id)) => chain_id.as_u64() * 2 + 35 + recovery_id,
+            (TypedTransaction::Legacy(_), None) => 27 + recovery_id,
+            _ => recovery_id,
+        };
+        Ok(signature)
+    }
+}
+
+/// Signer backed by a key decrypted from a local keystore
+pub struct LocalKeySigner {
+    key: LocalWallet,
+}
+
+impl LocalKeySigner {
+    /// Signing key for account `index`; a private-key wallet only has account 0
+    pub fn from_wallet(wallet: &Wallet, index: u32) -> WalletResult<Self> {
+        Ok(Self {
+            key: wallet.signing_key(index)?,
+        })
+    }
+}
+
+impl KeySigner for LocalKeySigner {
+    fn backend(&self) -> &'static str {
+        "local"
+    }
+
+    fn address(&self) -> String {
+        ethers::utils::to_checksum(&self.key.address(), None)
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.key.signer().verifying_key().to_encoded_point(false).as_bytes().to_vec()
+    }
+
+    fn sign_hash(&self, hash: &[u8; 32]) -> WalletResult<Signature> {
+        self.key
+            .sign_hash(H256::from(*hash))
+            .map_err(|e| CryptographicError::SignatureFailed {
+                details: e.to_string(),
+            }
+            .into())
+    }
+}
+
+/// PKCS#11 token settings (`[pkcs11]` in the config file)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pkcs11Config {
+    /// Path to the vendor PKCS#11 module (e.g. `/usr/lib/softhsm/libsofthsm2.so`)
+    pub module: PathBuf,
+    /// Token label to open; the first token with a matching key is used when unset
+    pub token_label: Option<String>,
+    /// Label (CKA_LABEL) of the secp256k1 private key object
+    pub key_label: String,
+}
+
+#[cfg(feature = "native")]
+pub use pkcs11::Pkcs11Signer;
+
+/// PKCS#11 signing; the FFI is confined to this module
+#[cfg(feature = "native")]
+#[allow(unsafe_code)]
+mod pkcs11 {
+    use super::{KeySigner, Pkcs11Config};
+    use crate::errors::{AuthenticationError, CryptographicError, FileSystemError, WalletResult};
+    use ethers::core::k256;
+    use ethers::prelude::*;
+
+    /// Signer backed by a key held on a PKCS#11 token
+    ///
+    /// The private key never leaves the token: the module is asked for raw
+    /// ECDSA signatures (`CKM_ECDSA`) over the digest, which are normalized to
+    /// low-s and given the recovery id Ethereum expects. The public key is
+    /// read from the public key object with the same label.
+    pub struct Pkcs11Signer {
+        config: Pkcs11Config,
+        functions: *const ck::CK_FUNCTION_LIST,
+        session: ck::CK_SESSION_HANDLE,
+        key: ck::CK_OBJECT_HANDLE,
+        public_key: k256::ecdsa::VerifyingKey,
+        // Sign operations on one session must not interleave
+        lock: std::sync::Mutex<()>,
+        // Declared last so the module is unloaded after the session is closed
+        _module: Option<libloading::Library>,
+    }
+
+    // SAFETY: the module is initialized with CKF_OS_LOCKING_OK, which makes its
+    // functions safe to call from any thread, and the one session is only used
+    // under `lock`.
+    unsafe impl Send for Pkcs11Signer {}
+    unsafe impl Sync for Pkcs11Signer {}
+
+    impl Pkcs11Signer {
+        /// Open a session on the configured token, log in with `pin` and locate the signing key
+        pub fn open(config: &Pkcs11Config, pin: &str) -> WalletResult<Self> {
+            if !config.module.is_file() {
+                return Err(FileSystemError::FileNotFound {
+                    path: config.module.display().to_string(),
+                    directory: config.module.parent()
+                        .map(|dir| dir.display().to_string())
+                        .unwrap_or_default(),
+                }
+                .into());
+            }
+
+            // SAFETY: loading runs the module's initializers; the path is the
+            // vendor module the operator configured
+            let module = unsafe { libloading::Library::new(&config.module) }.map_err(|e| module_error(config, e))?;
+            let functions = unsafe {
+                let get_function_list = module
+                    .get::<unsafe extern "C" fn(*mut *const ck::CK_FUNCTION_LIST) -> ck::CK_RV>(b"C_GetFunctionList\0")
+                    .map_err(|e| module_error(config, e))?;
+                let mut functions = std::ptr::null();
+                check("C_GetFunctionList", get_function_list(&mut functions))?;
+                functions
+            };
+            if functions.is_null() {
+                return Err(module_error(config, "C_GetFunctionList returned no functions"));
+            }
+            // SAFETY: `functions` comes from the module and stays valid while it is loaded
+            unsafe { Self::with_functions(config, pin, functions, Some(module)) }
+        }
+
+        /// Token settings this signer was opened with
+        pub fn config(&self) -> &Pkcs11Config {
+            &self.config
+        }
+
+        /// Log in on the first token holding the key, through `functions`
+        ///
+        /// # Safety
+        ///
+        /// `functions` must point to a PKCS#11 function list that outlives the signer.
+        unsafe fn with_functions(
+            config: &Pkcs11Config,
+            pin: &str,
+            functions: *const ck::CK_FUNCTION_LIST,
+            module: Option<libloading::Library>,
+        ) -> WalletResult<Self> {
+            let f = &*functions;
+            let mut initialize = ck::CK_C_INITIALIZE_ARGS {
+                create_mutex: std::ptr::null_mut(),
+                destroy_mutex: std::ptr::null_mut(),
+                lock_mutex: std::ptr::null_mut(),
+                unlock_mutex: std::ptr::null_mut(),
+                flags: ck::CKF_OS_LOCKING_OK,
+                reserved: std::ptr::null_mut(),
+            };
+            match entry(f.C_Initialize, "C_Initialize")?(&mut initialize as *mut _ as *mut std::ffi::c_void) {
+                ck::CKR_CRYPTOKI_ALREADY_INITIALIZED => {}
+                rv => check("C_Initialize", rv)?,
+            }
+
+            let get_slot_list = entry(f.C_GetSlotList, "C_GetSlotList")?;
+            let mut count: ck::CK_ULONG = 0;
+            check("C_GetSlotList", get_slot_list(ck::CK_TRUE, std::ptr::null_mut(), &mut count))?;
+            let mut slots = vec![0; count as usize];
+            check("C_GetSlotList", get_slot_list(ck::CK_TRUE, slots.as_mut_ptr(), &mut count))?;
+            slots.truncate(count as usize);
+
+            for slot in slots {
+                if let Some(label) = &config.token_label {
+                    let mut info: ck::CK_TOKEN_INFO = std::mem::zeroed();
+                    check("C_GetTokenInfo", entry(f.C_GetTokenInfo, "C_GetTokenInfo")?(slot, &mut info))?;
+                    // Labels are blank-padded to 32 bytes
+                    let token_label = info.label;
+                    if String::from_utf8_lossy(&token_label).trim_end_matches([' ', '\0']) != label {
+                        continue;
+                    }
+                }
+
+                let mut session = 0;
+                check(
+                    "C_OpenSession",
+                    entry(f.C_OpenSession, "C_OpenSession")?(slot, ck::CKF_SERIAL_SESSION, std::ptr::null_mut(), std::ptr::null_mut(), &mut session),
+                )?;
+                match Self::find_key(f, session, config, pin) {
+                    Ok(Some((key, public_key))) => {
+                        return Ok(Self {
+                            config: config.clone(),
+                            functions,
+                            session,
+                            key,
+                            public_key,
+                            lock: std::sync::Mutex::new(()),
+                            _module: module,
+                        })
+                    }
+                    result => {
+                        if let Some(close) = f.C_CloseSession {
+                            close(session);
+                        }
+                        result?;
+                    }
+                }
+            }
+
+            Err(CryptographicError::KeyUnavailable {
+                details: format!(
+                    "No token{} holds a private key labelled '{}'",
+                    config.token_label.as_deref().map(|label| format!(" labelled '{}'", label)).unwrap_or_default(),
+                    config.key_label
+                ),
+                suggestion: "Check token_label and key_label in [pkcs11]".to_string(),
+            }
+            .into())
+        }
+
+        /// Log in on `session` and find the private key and its secp256k1 public key, or `None` when the token has no such key
+        unsafe fn find_key(
+            f: &ck::CK_FUNCTION_LIST,
+            session: ck::CK_SESSION_HANDLE,
+            config: &Pkcs11Config,
+            pin: &str,
+        ) -> WalletResult<Option<(ck::CK_OBJECT_HANDLE, k256::ecdsa::VerifyingKey)>> {
+            match entry(f.C_Login, "C_Login")?(session, ck::CKU_USER, pin.as_ptr(), pin.len() as ck::CK_ULONG) {
+                ck::CKR_USER_ALREADY_LOGGED_IN => {}
+                rv => check("C_Login", rv)?,
+            }
+
+            let Some(key) = find_object(f, session, ck::CKO_PRIVATE_KEY, &config.key_label)? else {
+                return Ok(None);
+            };
+            let public = find_object(f, session, ck::CKO_PUBLIC_KEY, &config.key_label)?.ok_or_else(|| CryptographicError::KeyUnavailable {
+                details: format!("The token has no public key object labelled '{}'", config.key_label),
+                suggestion: "Import the public key next to the private key with the same label".to_string(),
+            })?;
+
+            let not_secp256k1 = || CryptographicError::InvalidPrivateKey {
+                details: format!("Key '{}' is not a secp256k1 key", config.key_label),
+                expected: "an EC key on secp256k1".to_string(),
+            };
+            if attribute(f, session, public, ck::CKA_EC_PARAMS)? != ck::SECP256K1_OID {
+                return Err(not_secp256k1().into());
+            }
+            let point = attribute(f, session, public, ck::CKA_EC_POINT)?;
+            // CKA_EC_POINT is a DER OCTET STRING, though some modules return the bare point
+            let point = match point.as_slice() {
+                [0x04, 0x41, point @ ..] if point.len() == 65 => point,
+                point => point,
+            };
+            let public_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(point).map_err(|_| not_secp256k1())?;
+            Ok(Some((key, public_key)))
+        }
+    }
+
+    impl KeySigner for Pkcs11Signer {
+        fn backend(&self) -> &'static str {
+            "pkcs11"
+        }
+
+        fn address(&self) -> String {
+            ethers::utils::to_checksum(&ethers::utils::public_key_to_address(&self.public_key), None)
+        }
+
+        fn public_key(&self) -> Vec<u8> {
+            self.public_key.to_encoded_point(false).as_bytes().to_vec()
+        }
+
+        fn sign_hash(&self, hash: &[u8; 32]) -> WalletResult<Signature> {
+            use k256::ecdsa::{RecoveryId, VerifyingKey};
+
+            let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+            // SAFETY: the function list outlives `self`, and `lock` serializes use of the session
+            let raw = unsafe {
+                let f = &*self.functions;
+                let mut mechanism = ck::CK_MECHANISM {
+                    mechanism: ck::CKM_ECDSA,
+                    parameter: std::ptr::null_mut(),
+                    parameter_len: 0,
+                };
+                check("C_SignInit", entry(f.C_SignInit, "C_SignInit")?(self.session, &mut mechanism, self.key))?;
+                let mut raw = [0u8; 64];
+                let mut len = raw.len() as ck::CK_ULONG;
+                check(
+                    "C_Sign",
+                    entry(f.C_Sign, "C_Sign")?(self.session, hash.as_ptr(), hash.len() as ck::CK_ULONG, raw.as_mut_ptr(), &mut len),
+                )?;
+                if len as usize != raw.len() {
+                    return Err(CryptographicError::SignatureFailed {
+                        details: format!("C_Sign returned {} bytes; expected r || s (64)", len),
+                    }
+                    .into());
+                }
+                raw
+            };
+
+            let invalid = || CryptographicError::SignatureFailed {
+                details: "The token returned a signature that does not verify against its public key".to_string(),
+            };
+            let signature = k256::ecdsa::Signature::from_slice(&raw).map_err(|_| invalid())?;
+            // Tokens return whichever s the nonce gave; Ethereum only accepts the low one
+            let signature = signature.normalize_s().unwrap_or(signature);
+            let recovery_id = [0, 1]
+                .into_iter()
+                .filter_map(RecoveryId::from_byte)
+                .find(|id| VerifyingKey::recover_from_prehash(hash, &signature, *id).is_ok_and(|key| key == self.public_key))
+                .ok_or_else(invalid)?;
+
+            let (r, s) = signature.split_bytes();
+            Ok(Signature {
+                r: U256::from_big_endian(&r),
+                s: U256::from_big_endian(&s),
+                v: 27 + u64::from(recovery_id.to_byte()),
+            })
+        }
+    }
+
+    impl Drop for Pkcs11Signer {
+        fn drop(&mut self) {
+            // Closing the last session also logs out. The module is not
+            // finalized: other signers in this process may still use it.
+            // SAFETY: the function list outlives `self`
+            unsafe {
+                if let Some(close) = (*self.functions).C_CloseSession {
+                    close(self.session);
+                }
+            }
+        }
+    }
+
+    fn module_error(config: &Pkcs11Config, e: impl std::fmt::Display) -> crate::errors::WalletError {
+        CryptographicError::SignatureFailed {
+            details: format!("PKCS#11 module {}: {}", config.module.display(), e),
+        }
+        .into()
+    }
+
+    /// Error for a PKCS#11 return value other than CKR_OK
+    fn check(function: &str, rv: ck::CK_RV) -> WalletResult<()> {
+        match rv {
+            ck::CKR_OK => Ok(()),
+            ck::CKR_PIN_INCORRECT => Err(AuthenticationError::WrongPassword {
+                wallet_file: String::new(),
+                attempts_remaining: None,
+            }
+            .into()),
+            rv => Err(CryptographicError::SignatureFailed {
+                details: format!("{} failed with CKR 0x{:X}", function, rv),
+            }
+            .into()),
+        }
+    }
+
+    /// Entry point the module may leave out of its function list
+    fn entry<T>(function: Option<T>, name: &str) -> WalletResult<T> {
+        function.ok_or_else(|| {
+            CryptographicError::SignatureFailed {
+                details: format!("The PKCS#11 module does not provide {}", name),
+            }
+            .into()
+        })
+    }
+
+    /// First object of `class` labelled `label`
+    unsafe fn find_object(
+        f: &ck::CK_FUNCTION_LIST,
+        session: ck::CK_SESSION_HANDLE,
+        class: ck::CK_ULONG,
+        label: &str,
+    ) -> WalletResult<Option<ck::CK_OBJECT_HANDLE>> {
+        let mut class = class;
+        let mut template = [
+            ck::CK_ATTRIBUTE {
+                kind: ck::CKA_CLASS,
+                value: &mut class as *mut _ as *mut std::ffi::c_void,
+                value_len: std::mem::size_of::<ck::CK_ULONG>() as ck::CK_ULONG,
+            },
+            ck::CK_ATTRIBUTE {
+                kind: ck::CKA_LABEL,
+                value: label.as_ptr() as *mut std::ffi::c_void,
+                value_len: label.len() as ck::CK_ULONG,
+            },
+        ];
+        check(
+            "C_FindObjectsInit",
+            entry(f.C_FindObjectsInit, "C_FindObjectsInit")?(session, template.as_mut_ptr(), template.len() as ck::CK_ULONG),
+        )?;
+        let mut object = 0;
+        let mut found = 0;
+        let rv = entry(f.C_FindObjects, "C_FindObjects")?(session, &mut object, 1, &mut found);
+        check("C_FindObjectsFinal", entry(f.C_FindObjectsFinal, "C_FindObjectsFinal")?(session))?;
+        check("C_FindObjects", rv)?;
+        Ok((found > 0).then_some(object))
+    }
+
+    /// Value of attribute `kind` of `object`
+    unsafe fn attribute(
+        f: &ck::CK_FUNCTION_LIST,
+        session: ck::CK_SESSION_HANDLE,
+        object: ck::CK_OBJECT_HANDLE,
+        kind: ck::CK_ULONG,
+    ) -> WalletResult<Vec<u8>> {
+        let get_attribute_value = entry(f.C_GetAttributeValue, "C_GetAttributeValue")?;
+        let mut attribute = ck::CK_ATTRIBUTE {
+            kind,
+            value: std::ptr::null_mut(),
+            value_len: 0,
+        };
+        check("C_GetAttributeValue", get_attribute_value(session, object, &mut attribute, 1))?;
+        let mut value = vec![0u8; attribute.value_len as usize];
+        attribute.value = value.as_mut_ptr() as *mut std::ffi::c_void;
+        check("C_GetAttributeValue", get_attribute_value(session, object, &mut attribute, 1))?;
+        value.truncate(attribute.value_len as usize);
+        Ok(value)
+    }
+
+    /// The subset of the PKCS#11 (Cryptoki 2.40) interface the signer uses
+    ///
+    /// Windows builds of Cryptoki pack every structure to 1 byte; elsewhere
+    /// they have their natural C layout.
+    #[allow(non_camel_case_types, non_snake_case)]
+    mod ck {
+        use std::ffi::c_void;
+        use std::os::raw::c_ulong;
+
+        pub(super) type CK_ULONG = c_ulong;
+        pub(super) type CK_RV = CK_ULONG;
+        pub(super) type CK_SLOT_ID = CK_ULONG;
+        pub(super) type CK_SESSION_HANDLE = CK_ULONG;
+        pub(super) type CK_OBJECT_HANDLE = CK_ULONG;
+        pub(super) type CK_BBOOL = u8;
+
+        pub(super) const CK_TRUE: CK_BBOOL = 1;
+        pub(super) const CKR_OK: CK_RV = 0x000;
+        pub(super) const CKR_PIN_INCORRECT: CK_RV = 0x0A0;
+        pub(super) const CKR_USER_ALREADY_LOGGED_IN: CK_RV = 0x100;
+        pub(super) const CKR_CRYPTOKI_ALREADY_INITIALIZED: CK_RV = 0x191;
+        pub(super) const CKF_OS_LOCKING_OK: CK_ULONG = 0x2;
+        pub(super) const CKF_SERIAL_SESSION: CK_ULONG = 0x4;
+        pub(super) const CKU_USER: CK_ULONG = 1;
+        pub(super) const CKO_PUBLIC_KEY: CK_ULONG = 2;
+        pub(super) const CKO_PRIVATE_KEY: CK_ULONG = 3;
+        pub(super) const CKA_CLASS: CK_ULONG = 0x000;
+        pub(super) const CKA_LABEL: CK_ULONG = 0x003;
+        pub(super) const CKA_EC_PARAMS: CK_ULONG = 0x180;
+        pub(super) const CKA_EC_POINT: CK_ULONG = 0x181;
+        pub(super) const CKM_ECDSA: CK_ULONG = 0x1041;
+
+        /// DER encoding of the secp256k1 curve OID (1.3.132.0.10), as CKA_EC_PARAMS holds it
+        pub(super) const SECP256K1_OID: [u8; 7] = [0x06, 0x05, 0x2B, 0x81, 0x04, 0x00, 0x0A];
+
+        #[repr(C)]
+        #[cfg_attr(windows, repr(packed))]
+        pub(super) struct CK_C_INITIALIZE_ARGS {
+            pub(super) create_mutex: *mut c_void,
+            pub(super) destroy_mutex: *mut c_void,
+            pub(super) lock_mutex: *mut c_void,
+            pub(super) unlock_mutex: *mut c_void,
+            pub(super) flags: CK_ULONG,
+            pub(super) reserved: *mut c_void,
+        }
+
+        #[repr(C)]
+        #[cfg_attr(windows, repr(packed))]
+        pub(super) struct CK_TOKEN_INFO {
+            pub(super) label: [u8; 32],
+            pub(super) manufacturer_and_model: [u8; 64],
+            pub(super) flags: CK_ULONG,
+            pub(super) counters: [CK_ULONG; 10],
+            pub(super) versions: [u8; 4],
+            pub(super) utc_time: [u8; 16],
+        }
+
+        #[repr(C)]
+        #[cfg_attr(windows, repr(packed))]
+        pub(super) struct CK_ATTRIBUTE {
+            pub(super) kind: CK_ULONG,
+            pub(super) value: *mut c_void,
+            pub(super) value_len: CK_ULONG,
+        }
+
+        #[repr(C)]
+        #[cfg_attr(windows, repr(packed))]
+        pub(super) struct CK_MECHANISM {
+            pub(super) mechanism: CK_ULONG,
+            pub(super) parameter: *mut c_void,
+            pub(super) parameter_len: CK_ULONG,
+        }
+
+        /// Entry point the signer never calls
+        pub(super) type Unused = Option<unsafe extern "C" fn()>;
+
+        /// The leading entries of `CK_FUNCTION_LIST`, up to `C_Sign`
+        #[repr(C)]
+        #[cfg_attr(windows, repr(packed))]
+        pub(super) struct CK_FUNCTION_LIST {
+            pub(super) version: [u8; 2],
+            pub(super) C_Initialize: Option<unsafe extern "C" fn(*mut c_void) -> CK_RV>,
+            pub(super) C_Finalize: Unused,
+            pub(super) C_GetInfo: Unused,
+            pub(super) C_GetFunctionList: Unused,
+            pub(super) C_GetSlotList: Option<unsafe extern "C" fn(CK_BBOOL, *mut CK_SLOT_ID, *mut CK_ULONG) -> CK_RV>,
+            pub(super) C_GetSlotInfo: Unused,
+            pub(super) C_GetTokenInfo: Option<unsafe extern "C" fn(CK_SLOT_ID, *mut CK_TOKEN_INFO) -> CK_RV>,
+            /// C_GetMechanismList through C_SetPIN
+            pub(super) token_management: [Unused; 5],
+            pub(super) C_OpenSession:
+                Option<unsafe extern "C" fn(CK_SLOT_ID, CK_ULONG, *mut c_void, *mut c_void, *mut CK_SESSION_HANDLE) -> CK_RV>,
+            pub(super) C_CloseSession: Option<unsafe extern "C" fn(CK_SESSION_HANDLE) -> CK_RV>,
+            /// C_CloseAllSessions through C_SetOperationState
+            pub(super) session_management: [Unused; 4],
+            pub(super) C_Login: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, CK_ULONG, *const u8, CK_ULONG) -> CK_RV>,
+            pub(super) C_Logout: Unused,
+            /// C_CreateObject through C_GetObjectSize
+            pub(super) object_management: [Unused; 4],
+            pub(super) C_GetAttributeValue: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, CK_OBJECT_HANDLE, *mut CK_ATTRIBUTE, CK_ULONG) -> CK_RV>,
+            pub(super) C_SetAttributeValue: Unused,
+            pub(super) C_FindObjectsInit: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_ATTRIBUTE, CK_ULONG) -> CK_RV>,
+            pub(super) C_FindObjects: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_OBJECT_HANDLE, CK_ULONG, *mut CK_ULONG) -> CK_RV>,
+            pub(super) C_FindObjectsFinal: Option<unsafe extern "C" fn(CK_SESSION_HANDLE) -> CK_RV>,
+            /// C_EncryptInit through C_DigestFinal
+            pub(super) encryption_and_digests: [Unused; 13],
+            pub(super) C_SignInit: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_MECHANISM, CK_OBJECT_HANDLE) -> CK_RV>,
+            pub(super) C_Sign: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *const u8, CK_ULONG, *mut u8, *mut CK_ULONG) -> CK_RV>,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ethers::types::transaction::eip2718::TypedTransaction;
+        use std::path::PathBuf;
+
+        /// In-process stand-in for a PKCS#11 module holding the test key
+        mod fake_token {
+            use super::super::ck::*;
+            use ethers::core::k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey};
+            use std::ffi::c_void;
+            use std::sync::atomic::{AtomicU64, Ordering};
+
+            pub(super) const PIN: &str = "1234";
+            pub(super) const KEY: [u8; 32] = [0x42; 32];
+            const PRIVATE: CK_OBJECT_HANDLE = 10;
+            const PUBLIC: CK_OBJECT_HANDLE = 11;
+            static FOUND: AtomicU64 = AtomicU64::new(0);
+
+            pub(super) static FUNCTIONS: CK_FUNCTION_LIST = CK_FUNCTION_LIST {
+                version: [2, 40],
+                C_Initialize: Some(initialize),
+                C_Finalize: None,
+                C_GetInfo: None,
+                C_GetFunctionList: None,
+                C_GetSlotList: Some(get_slot_list),
+                C_GetSlotInfo: None,
+                C_GetTokenInfo: Some(get_token_info),
+                token_management: [None; 5],
+                C_OpenSession: Some(open_session),
+                C_CloseSession: Some(close_session),
+                session_management: [None; 4],
+                C_Login: Some(login),
+                C_Logout: None,
+                object_management: [None; 4],
+                C_GetAttributeValue: Some(get_attribute_value),
+                C_SetAttributeValue: None,
+                C_FindObjectsInit: Some(find_objects_init),
+                C_FindObjects: Some(find_objects),
+                C_FindObjectsFinal: Some(close_session),
+                encryption_and_digests: [None; 13],
+                C_SignInit: Some(sign_init),
+                C_Sign: Some(sign),
+            };
+
+            fn key() -> SigningKey {
+                SigningKey::from_bytes(&KEY.into()).unwrap()
+            }
+
+            unsafe extern "C" fn initialize(_: *mut c_void) -> CK_RV {
+                CKR_OK
+            }
+
+            unsafe extern "C" fn get_slot_list(_: CK_BBOOL, slots: *mut CK_SLOT_ID, count: *mut CK_ULONG) -> CK_RV {
+                if !slots.is_null() {
+                    *slots = 7;
+                }
+                *count = 1;
+                CKR_OK
+            }
+
+            unsafe extern "C" fn get_token_info(_: CK_SLOT_ID, info: *mut CK_TOKEN_INFO) -> CK_RV {
+                (*info).label = *b"signing token                   ";
+                CKR_OK
+            }
+
+            unsafe extern "C" fn open_session(_: CK_SLOT_ID, _: CK_ULONG, _: *mut c_void, _: *mut c_void, session: *mut CK_SESSION_HANDLE) -> CK_RV {
+                *session = 1;
+                CKR_OK
+            }
+
+            unsafe extern "C" fn close_session(_: CK_SESSION_HANDLE) -> CK_RV {
+                CKR_OK
+            }
+
+            unsafe extern "C" fn login(_: CK_SESSION_HANDLE, _: CK_ULONG, pin: *const u8, len: CK_ULONG) -> CK_RV {
+                match std::slice::from_raw_parts(pin, len as usize) == PIN.as_bytes() {
+                    true => CKR_OK,
+                    false => CKR_PIN_INCORRECT,
+                }
+            }
+
+            unsafe extern "C" fn find_objects_init(_: CK_SESSION_HANDLE, template: *mut CK_ATTRIBUTE, count: CK_ULONG) -> CK_RV {
+                let template = std::slice::from_raw_parts(template, count as usize);
+                let class = *(template[0].value as *const CK_ULONG);
+                let label = std::slice::from_raw_parts(template[1].value as *const u8, template[1].value_len as usize);
+                let found = match (class, label) {
+                    (CKO_PRIVATE_KEY, b"eth") => PRIVATE,
+                    (CKO_PUBLIC_KEY, b"eth") => PUBLIC,
+                    _ => 0,
+                };
+                FOUND.store(found, Ordering::SeqCst);
+                CKR_OK
+            }
+
+            unsafe extern "C" fn find_objects(_: CK_SESSION_HANDLE, objects: *mut CK_OBJECT_HANDLE, _: CK_ULONG, count: *mut CK_ULONG) -> CK_RV {
+                let found = FOUND.swap(0, Ordering::SeqCst);
+                *objects = found as CK_OBJECT_HANDLE;
+                *count = (found != 0) as CK_ULONG;
+                CKR_OK
+            }
+
+            unsafe extern "C" fn get_attribute_value(_: CK_SESSION_HANDLE, object: CK_OBJECT_HANDLE, attribute: *mut CK_ATTRIBUTE, _: CK_ULONG) -> CK_RV {
+                let value = match ((*attribute).kind, object) {
+                    (CKA_EC_PARAMS, PUBLIC) => SECP256K1_OID.to_vec(),
+                    (CKA_EC_POINT, PUBLIC) => [&[0x04, 0x41][..], key().verifying_key().to_encoded_point(false).as_bytes()].concat(),
+                    _ => return 0x12, // CKR_ATTRIBUTE_TYPE_INVALID
+                };
+                if !(*attribute).value.is_null() {
+                    std::ptr::copy_nonoverlapping(value.as_ptr(), (*attribute).value as *mut u8, value.len());
+                }
+                (*attribute).value_len = value.len() as CK_ULONG;
+                CKR_OK
+            }
+
+            unsafe extern "C" fn sign_init(_: CK_SESSION_HANDLE, mechanism: *mut CK_MECHANISM, key: CK_OBJECT_HANDLE) -> CK_RV {
+                match ((*mechanism).mechanism, key) {
+                    (CKM_ECDSA, PRIVATE) => CKR_OK,
+                    _ => 0x70, // CKR_MECHANISM_INVALID
+                }
+            }
+
+            /// Raw ECDSA like a token: always the high-s form, which Ethereum rejects until normalized
+            unsafe extern "C" fn sign(_: CK_SESSION_HANDLE, data: *const u8, len: CK_ULONG, out: *mut u8, out_len: *mut CK_ULONG) -> CK_RV {
+                let digest = std::slice::from_raw_parts(data, len as usize);
+                let signature: Signature = key().sign_prehash(digest).unwrap();
+                let (r, s) = signature.split_scalars();
+                let high = Signature::from_scalars(r, -*s.as_ref()).unwrap();
+                let high = match high.normalize_s() {
+                    Some(_) => high,
+                    None => signature,
+                };
+                std::ptr::copy_nonoverlapping(high.to_bytes().as_ptr(), out, 64);
+                *out_len = 64;
+                CKR_OK
+            }
+        }
+
+        #[test]
+        fn test_pkcs11_signer() {
+            let config = Pkcs11Config {
+                module: PathBuf::from("fake"),
+                token_label: Some("signing token".to_string()),
+                key_label: "eth".to_string(),
+            };
+            let open = |config: &Pkcs11Config, pin: &str| unsafe { Pkcs11Signer::with_functions(config, pin, &fake_token::FUNCTIONS, None) };
+            let signer = open(&config, fake_token::PIN).unwrap();
+            let expected = LocalWallet::from_bytes(&fake_token::KEY).unwrap();
+            assert_eq!(signer.address(), ethers::utils::to_checksum(&expected.address(), None));
+
+            // High-s token signatures come back low-s with a recoverable v
+            let transaction: TypedTransaction = Eip1559TransactionRequest::new().nonce(0).gas(21_000).chain_id(1).into();
+            let signature = signer.sign_transaction(&transaction).unwrap();
+            assert!(signature.s <= U256::from_big_endian(&hex::decode("7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0").unwrap()));
+            assert_eq!(signature.recover(transaction.sighash()).unwrap(), expected.address());
+            let signature = signer.sign_message(b"hello").unwrap();
+            assert_eq!(signature.recover(&b"hello"[..]).unwrap(), expected.address());
+
+            assert!(matches!(open(&config, "0000"), Err(crate::WalletError::Authentication(AuthenticationError::WrongPassword { .. }))));
+            let other_key = Pkcs11Config { key_label: "btc".to_string(), ..config.clone() };
+            assert!(matches!(open(&other_key, fake_token::PIN), Err(crate::WalletError::Cryptographic(CryptographicError::KeyUnavailable { .. }))));
+            let other_token = Pkcs11Config { token_label: Some("other".to_string()), ..config };
+            assert!(open(&other_token, fake_token::PIN).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_local_signer_signature_recovers() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let signer = LocalKeySigner::from_wallet(&wallet, 0).unwrap();
+        assert_eq!(signer.address(), "0x9858EfFD232B4033E47d90003D41EC34EcaEda94");
+
+        let signature = signer.sign_message(b"hello").unwrap();
+        let recovered = signature.recover(&b"hello"[..]).unwrap();
+        assert_eq!(ethers::utils::to_checksum(&recovered, None), signer.address());
+    }
+
+    #[test]
+    fn test_local_signer_transactions() {
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let signer = LocalKeySigner::from_wallet(&wallet, 0).unwrap();
+        let legacy: TypedTransaction = TransactionRequest::new().nonce(0).gas(21_000).gas_price(1).chain_id(1).into();
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new().nonce(0).gas(21_000).chain_id(1).into();
+
+        for (transaction, min_v) in [(legacy, 37), (eip1559, 0)] {
+            let signature = signer.sign_transaction(&transaction).unwrap();
+            assert!(signature.v >= min_v && signature.v <= min_v + 1);
+            let raw = transaction.rlp_signed(&signature);
+            let (_, decoded) = TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&raw)).unwrap();
+            let from = decoded.recover(transaction.sighash()).unwrap();
+            assert_eq!(ethers::utils::to_checksum(&from, None), signer.address());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "native")]
+    fn test_pkcs11_missing_module() {
+        let config = Pkcs11Config {
+            module: PathBuf::from("/nonexistent/libpkcs11.so"),
+            token_label: None,
+            key_label: "eth-signing".to_string(),
+        };
+
+        assert!(Pkcs11Signer::open(&config, "1234").is_err());
+    }
+}
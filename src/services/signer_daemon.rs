@@ -0,0 +1,605 @@
+//! # External Signer Daemon
+//!
+//! JSON-RPC server speaking the external-signer API of geth's clef, so
+//! geth (`--signer http://127.0.0.1:8550`), reth and dapp tooling can ask
+//! this wallet for signatures without ever holding its keys. The methods
+//! served are `account_version`, `account_list`, `account_signTransaction`
+//! and `account_signData` (`text/plain` and `data/typed` content).
+//!
+//! Nothing is listed or signed before an [`Approver`] has accepted the
+//! request; `wallet daemon` asks on its terminal. Requests are answered one
+//! at a time, so approvals never interleave.
+//!
+//! The server speaks plain HTTP and is meant for loopback. Requests that
+//! carry an `Origin` header (sent by browsers) or name a foreign `Host`
+//! (DNS rebinding) are refused, so web pages cannot drive it.
+
+use crate::errors::{CryptographicError, WalletResult};
+use crate::models::EthereumAddress;
+use crate::services::signer::KeySigner;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip2930::AccessList;
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{Address, Bytes, Eip1559TransactionRequest, Transaction, TransactionRequest, U256};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Address clef listens on, which signer clients default to
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:8550";
+
+/// External API version reported by `account_version`
+pub const API_VERSION: &str = "6.1.0";
+
+/// Largest request body accepted
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Largest request head (request line and headers) accepted
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+/// Time a client has to send its whole request
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// JSON-RPC error code clef answers rejected requests with
+const DENIED: i64 = -32000;
+
+/// A client request awaiting the user's decision
+#[derive(Debug, Clone)]
+pub enum ApprovalRequest {
+    /// Reveal the served accounts
+    ListAccounts {
+        /// Accounts that would be listed
+        accounts: Vec<EthereumAddress>,
+    },
+    /// Sign a transaction
+    SignTransaction {
+        /// Signing account
+        from: EthereumAddress,
+        /// Transaction to sign, with its chain ID set
+        transaction: Box<TypedTransaction>,
+    },
+    /// Sign a message or typed data
+    SignData {
+        /// Signing account
+        address: EthereumAddress,
+        /// Content type as requested (`text/plain`, `data/typed`)
+        content_type: String,
+        /// Printable rendering of what is signed
+        preview: String,
+    },
+}
+
+impl ApprovalRequest {
+    /// Check if approving this request produces a signature
+    pub fn is_signature(&self) -> bool {
+        !matches!(self, ApprovalRequest::ListAccounts { .. })
+    }
+
+    /// Account that would sign, if any
+    pub fn signer(&self) -> Option<&EthereumAddress> {
+        match self {
+            ApprovalRequest::ListAccounts { .. } => None,
+            ApprovalRequest::SignTransaction { from, .. } => Some(from),
+            ApprovalRequest::SignData { address, .. } => Some(address),
+        }
+    }
+}
+
+/// Summary shown to the user before approving
+impl fmt::Display for ApprovalRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApprovalRequest::ListAccounts { accounts } => {
+                writeln!(f, "List accounts")?;
+                for account in accounts {
+                    writeln!(f, "  {}", account)?;
+                }
+                Ok(())
+            }
+            ApprovalRequest::SignTransaction { from, transaction } => {
+                writeln!(f, "Sign transaction")?;
+                writeln!(f, "From:     {}", from)?;
+                match transaction.to_addr() {
+                    Some(to) => writeln!(f, "To:       {}", EthereumAddress::from(*to))?,
+                    None => writeln!(f, "To:       (contract creation)")?,
+                }
+                let value = transaction.value().copied().unwrap_or_default();
+                writeln!(f, "Value:    {} ETH", ethers::utils::format_ether(value))?;
+                writeln!(f, "Chain:    {}", transaction.chain_id().unwrap_or_default())?;
+                writeln!(f, "Nonce:    {}", transaction.nonce().copied().unwrap_or_default())?;
+                writeln!(f, "Gas:      {}", transaction.gas().copied().unwrap_or_default())?;
+                match &**transaction {
+                    TypedTransaction::Eip1559(tx) => writeln!(
+                        f,
+                        "Fees:     max {} wei, priority {} wei",
+                        tx.max_fee_per_gas.unwrap_or_default(),
+                        tx.max_priority_fee_per_gas.unwrap_or_default()
+                    )?,
+                    _ => writeln!(f, "Price:    {} wei", transaction.gas_price().unwrap_or_default())?,
+                }
+                let data = transaction.data().map(|data| data.as_ref()).unwrap_or_default();
+                if data.len() >= 4 {
+                    writeln!(f, "Data:     {} bytes, selector 0x{}", data.len(), hex::encode(&data[..4]))?;
+                } else if !data.is_empty() {
+                    writeln!(f, "Data:     0x{}", hex::encode(data))?;
+                }
+                Ok(())
+            }
+            ApprovalRequest::SignData { address, content_type, preview } => {
+                writeln!(f, "Sign {}", content_type)?;
+                writeln!(f, "Account:  {}", address)?;
+                writeln!(f, "Content:  {}", preview)
+            }
+        }
+    }
+}
+
+/// Decides whether a client request is carried out
+pub trait Approver: Send + Sync {
+    /// Approve or reject `request`; called on a blocking thread, one request at a time
+    fn approve(&self, request: &ApprovalRequest) -> bool;
+}
+
+/// Error answered to a JSON-RPC call
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self { code: -32602, message: message.into() }
+    }
+}
+
+impl From<crate::WalletError> for RpcError {
+    fn from(e: crate::WalletError) -> Self {
+        Self { code: DENIED, message: e.to_string() }
+    }
+}
+
+/// `account_signTransaction` arguments, as geth sends them
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionArgs {
+    from: Address,
+    to: Option<Address>,
+    gas: U256,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    value: Option<U256>,
+    nonce: U256,
+    data: Option<Bytes>,
+    input: Option<Bytes>,
+    chain_id: Option<U256>,
+    access_list: Option<AccessList>,
+}
+
+/// Accounts served over the external-signer API
+pub struct SignerDaemon {
+    accounts: Vec<(EthereumAddress, Box<dyn KeySigner>)>,
+    chain_id: u64,
+    approver: Arc<dyn Approver>,
+}
+
+impl SignerDaemon {
+    /// Daemon signing for chain `chain_id`, asking `approver` about every request
+    pub fn new(chain_id: u64, approver: Arc<dyn Approver>) -> Self {
+        Self {
+            accounts: Vec::new(),
+            chain_id,
+            approver,
+        }
+    }
+
+    /// Serve an account, returning its address
+    pub fn add_account(&mut self, signer: Box<dyn KeySigner>) -> WalletResult<EthereumAddress> {
+        let address: EthereumAddress = signer.address().parse()?;
+        if !self.accounts.iter().any(|(served, _)| *served == address) {
+            self.accounts.push((address, signer));
+        }
+        Ok(address)
+    }
+
+    /// Served accounts, in the order they were added
+    pub fn accounts(&self) -> Vec<EthereumAddress> {
+        self.accounts.iter().map(|(address, _)| *address).collect()
+    }
+
+    /// Answer HTTP requests on `listener` until the process is stopped
+    #[tracing::instrument(level = "debug", skip_all, fields(accounts = self.accounts.len(), chain_id = self.chain_id))]
+    pub async fn serve(self, listener: TcpListener) -> WalletResult<()> {
+        let daemon = Arc::new(self);
+        let local = listener.local_addr()?;
+        if !local.ip().is_loopback() {
+            tracing::warn!("Signer listening on {}, which is not loopback; requests travel unencrypted", local);
+        }
+        tracing::info!("Signer listening on http://{}", local);
+
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    tracing::debug!("accept failed: {}", e);
+                    continue;
+                }
+            };
+            if let Err(e) = daemon.answer(stream, local).await {
+                tracing::debug!("request from {} failed: {}", peer, e);
+            }
+        }
+    }
+
+    /// Read one HTTP request and write its response
+    async fn answer(self: &Arc<Self>, mut stream: TcpStream, local: std::net::SocketAddr) -> std::io::Result<()> {
+        let request = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream, local)).await;
+        let (status, body) = match request {
+            Err(_) => ("408 Request Timeout", json!({"error": "request timed out"}).to_string()),
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(Err(status))) => (status, json!({"error": status}).to_string()),
+            Ok(Ok(Ok(body))) => {
+                let daemon = Arc::clone(self);
+                let reply = tokio::task::spawn_blocking(move || daemon.handle(&body)).await;
+                ("200 OK", reply.unwrap_or_else(|e| rpc_error(Value::Null, -32603, &e.to_string()).to_string()))
+            }
+        };
+
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(body.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    /// Answer a JSON-RPC request body (a single call or a batch)
+    pub fn handle(&self, body: &[u8]) -> String {
+        let reply = match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Array(calls)) => Value::Array(calls.iter().map(|call| self.dispatch(call)).collect()),
+            Ok(call) => self.dispatch(&call),
+            Err(e) => rpc_error(Value::Null, -32700, &format!("parse error: {}", e)),
+        };
+        reply.to_string()
+    }
+
+    fn dispatch(&self, call: &Value) -> Value {
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        let method = match call.get("method").and_then(Value::as_str) {
+            Some(method) => method,
+            None => return rpc_error(id, -32600, "invalid request"),
+        };
+        let params = match call.get("params") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(params)) => params.clone(),
+            Some(_) => return rpc_error(id, -32602, "params must be an array"),
+        };
+
+        tracing::debug!(method, "signer request");
+        match self.call(method, &params) {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(e) => rpc_error(id, e.code, &e.message),
+        }
+    }
+
+    fn call(&self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "account_version" => Ok(json!(API_VERSION)),
+            "account_list" => {
+                let accounts = self.accounts();
+                self.ask(&ApprovalRequest::ListAccounts { accounts: accounts.clone() })?;
+                Ok(json!(accounts))
+            }
+            "account_signTransaction" => {
+                let args: TransactionArgs = param(params, 0)?;
+                let (from, signer) = self.account(args.from.into())?;
+                let transaction = self.transaction(args)?;
+                self.ask(&ApprovalRequest::SignTransaction { from, transaction: Box::new(transaction.clone()) })?;
+
+                let signature = signer.sign_transaction(&transaction)?;
+                let raw = transaction.rlp_signed(&signature);
+                let mut signed: Transaction = ethers::utils::rlp::decode(&raw)
+                    .map_err(|e| CryptographicError::SignatureFailed { details: e.to_string() })
+                    .map_err(crate::WalletError::from)?;
+                signed.from = from.into();
+                Ok(json!({"raw": raw, "tx": signed}))
+            }
+            "account_signData" => {
+                let content_type: String = param(params, 0)?;
+                let address: Address = param(params, 1)?;
+                let (address, signer) = self.account(address.into())?;
+                let data = params.get(2).ok_or_else(|| RpcError::invalid_params("missing data"))?;
+
+                let (hash, preview) = match content_type.as_str() {
+                    "text/plain" => {
+                        let message: Bytes = serde_json::from_value(data.clone())
+                            .map_err(|e| RpcError::invalid_params(format!("text/plain data must be hex: {}", e)))?;
+                        let preview = match std::str::from_utf8(&message) {
+                            Ok(text) => text.escape_debug().to_string(),
+                            Err(_) => format!("0x{}", hex::encode(&message)),
+                        };
+                        (ethers::utils::hash_message(message.as_ref()).0, preview)
+                    }
+                    "data/typed" => {
+                        let typed: TypedData = match data {
+                            Value::String(json) => serde_json::from_str(json),
+                            other => serde_json::from_value(other.clone()),
+                        }
+                        .map_err(|e| RpcError::invalid_params(format!("invalid typed data: {}", e)))?;
+                        let hash = typed.encode_eip712().map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                        let domain = typed.domain.name.as_deref().unwrap_or("unnamed domain");
+                        (hash, format!("{} for {}", typed.primary_type, domain).escape_debug().to_string())
+                    }
+                    other => {
+                        return Err(RpcError::invalid_params(format!(
+                            "unsupported content type '{}' (expected text/plain or data/typed)",
+                            other
+                        )))
+                    }
+                };
+                self.ask(&ApprovalRequest::SignData { address, content_type, preview })?;
+
+                let signature = signer.sign_hash(&hash)?;
+                Ok(json!(Bytes::from(signature.to_vec())))
+            }
+            _ => Err(RpcError { code: -32601, message: format!("the method {} does not exist/is not available", method) }),
+        }
+    }
+
+    fn ask(&self, request: &ApprovalRequest) -> Result<(), RpcError> {
+        if self.approver.approve(request) {
+            Ok(())
+        } else {
+            Err(RpcError { code: DENIED, message: "Request denied".to_string() })
+        }
+    }
+
+    fn account(&self, address: EthereumAddress) -> Result<(EthereumAddress, &dyn KeySigner), RpcError> {
+        self.accounts
+            .iter()
+            .find(|(served, _)| *served == address)
+            .map(|(served, signer)| (*served, signer.as_ref()))
+            .ok_or_else(|| RpcError::invalid_params(format!("account {} is not served by this signer", address)))
+    }
+
+    /// Transaction for `args`, refusing any other chain
+    fn transaction(&self, args: TransactionArgs) -> Result<TypedTransaction, RpcError> {
+        if let Some(chain_id) = args.chain_id {
+            if chain_id != U256::from(self.chain_id) {
+                return Err(RpcError::invalid_params(format!(
+                    "chain {} requested, but this signer is for chain {}",
+                    chain_id, self.chain_id
+                )));
+            }
+        }
+        let data = match (args.data, args.input) {
+            (Some(data), Some(input)) if data != input => {
+                return Err(RpcError::invalid_params("both data and input given, and they differ"))
+            }
+            (data, input) => input.or(data).unwrap_or_default(),
+        };
+        let value = args.value.unwrap_or_default();
+
+        let transaction: TypedTransaction = match (args.gas_price, args.max_fee_per_gas) {
+            (Some(_), Some(_)) => {
+                return Err(RpcError::invalid_params("both gasPrice and maxFeePerGas given"));
+            }
+            (None, Some(max_fee)) => {
+                let mut tx = Eip1559TransactionRequest::new()
+                    .from(args.from)
+                    .nonce(args.nonce)
+                    .gas(args.gas)
+                    .value(value)
+                    .data(data)
+                    .max_fee_per_gas(max_fee)
+                    .max_priority_fee_per_gas(args.max_priority_fee_per_gas.unwrap_or_default())
+                    .access_list(args.access_list.unwrap_or_default())
+                    .chain_id(self.chain_id);
+                tx.to = args.to.map(Into::into);
+                tx.into()
+            }
+            (Some(gas_price), None) => {
+                let mut tx = TransactionRequest::new()
+                    .from(args.from)
+                    .nonce(args.nonce)
+                    .gas(args.gas)
+                    .value(value)
+                    .data(data)
+                    .gas_price(gas_price)
+                    .chain_id(self.chain_id);
+                tx.to = args.to.map(Into::into);
+                match args.access_list {
+                    Some(access_list) => TypedTransaction::Eip2930(tx.with_access_list(access_list)),
+                    None => tx.into(),
+                }
+            }
+            (None, None) => return Err(RpcError::invalid_params("gasPrice or maxFeePerGas is required")),
+        };
+        Ok(transaction)
+    }
+}
+
+fn param<T: serde::de::DeserializeOwned>(params: &[Value], index: usize) -> Result<T, RpcError> {
+    let value = params.get(index).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value).map_err(|e| RpcError::invalid_params(format!("invalid argument {}: {}", index, e)))
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+/// Read a POST request, returning its body or the HTTP status to refuse it with
+async fn read_request(
+    stream: &mut TcpStream,
+    local: std::net::SocketAddr,
+) -> std::io::Result<Result<Vec<u8>, &'static str>> {
+    let mut reader = BufReader::new(stream);
+    let mut head_size = 0;
+    let mut request_line = String::new();
+    head_size += reader.read_line(&mut request_line).await?;
+
+    let mut content_length = None;
+    let mut host = None;
+    let mut has_origin = false;
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).await?;
+        head_size += read;
+        if read == 0 || head_size > MAX_HEAD_SIZE {
+            return Ok(Err("431 Request Header Fields Too Large"));
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse::<usize>().ok(),
+                "host" => host = Some(value.trim().to_string()),
+                "origin" => has_origin = true,
+                _ => {}
+            }
+        }
+    }
+
+    if !request_line.starts_with("POST ") {
+        return Ok(Err("405 Method Not Allowed"));
+    }
+    if has_origin || !host.as_deref().map_or(true, |host| is_local_host(host, local)) {
+        return Ok(Err("403 Forbidden"));
+    }
+    let length = match content_length {
+        Some(length) if length <= MAX_BODY_SIZE => length,
+        Some(_) => return Ok(Err("413 Payload Too Large")),
+        None => return Ok(Err("411 Length Required")),
+    };
+
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).await?;
+    Ok(Ok(body))
+}
+
+/// Check if a `Host` header names this machine rather than a rebound domain
+fn is_local_host(host: &str, local: std::net::SocketAddr) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback() || ip == local.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Network, Wallet};
+    use crate::services::LocalKeySigner;
+
+    /// Approves everything except listing, recording what it was asked
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<String>>);
+
+    impl Approver for Recorder {
+        fn approve(&self, request: &ApprovalRequest) -> bool {
+            self.0.lock().unwrap().push(request.to_string());
+            request.is_signature()
+        }
+    }
+
+    fn daemon(approver: Arc<dyn Approver>) -> SignerDaemon {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Sepolia,
+            None,
+        )
+        .unwrap();
+        let mut daemon = SignerDaemon::new(Network::Sepolia.chain_id(), approver);
+        daemon.add_account(Box::new(LocalKeySigner::from_wallet(&wallet, 0).unwrap())).unwrap();
+        daemon
+    }
+
+    fn call(daemon: &SignerDaemon, method: &str, params: Value) -> Value {
+        let body = json!({"jsonrpc": "2.0", "id": 7, "method": method, "params": params});
+        serde_json::from_str(&daemon.handle(body.to_string().as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn test_signer_api() {
+        let recorder = Arc::new(Recorder::default());
+        let daemon = daemon(recorder.clone());
+        let from = "0x9858effd232b4033e47d90003d41ec34ecaeda94";
+
+        assert_eq!(call(&daemon, "account_version", json!([]))["result"], API_VERSION);
+        // The recorder refuses listing
+        assert_eq!(call(&daemon, "account_list", json!([]))["error"]["code"], DENIED);
+
+        let reply = call(&daemon, "account_signTransaction", json!([{
+            "from": from,
+            "to": "0x000000000000000000000000000000000000dead",
+            "gas": "0x5208",
+            "maxFeePerGas": "0x3b9aca00",
+            "maxPriorityFeePerGas": "0x1",
+            "value": "0xde0b6b3a7640000",
+            "nonce": "0x3",
+            "chainId": "0xaa36a7"
+        }]));
+        let raw: Bytes = serde_json::from_value(reply["result"]["raw"].clone()).unwrap();
+        let (tx, signature) = TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&raw)).unwrap();
+        assert_eq!(EthereumAddress::from(signature.recover(tx.sighash()).unwrap()), from);
+        assert_eq!(reply["result"]["tx"]["nonce"], "0x3");
+        assert!(recorder.0.lock().unwrap().last().unwrap().contains("Value:    1.000000000000000000 ETH"));
+
+        // Another chain is refused before anyone is asked
+        let asked = recorder.0.lock().unwrap().len();
+        let reply = call(&daemon, "account_signTransaction", json!([{
+            "from": from, "gas": "0x5208", "gasPrice": "0x1", "nonce": "0x0", "chainId": "0x1"
+        }]));
+        assert_eq!(reply["error"]["code"], -32602);
+        assert_eq!(recorder.0.lock().unwrap().len(), asked);
+
+        let reply = call(&daemon, "account_signData", json!(["text/plain", from, "0x68656c6c6f"]));
+        let signature: ethers::types::Signature = reply["result"].as_str().unwrap().parse().unwrap();
+        assert_eq!(EthereumAddress::from(signature.recover("hello").unwrap()), from);
+
+        let unknown = call(&daemon, "account_signData", json!(["text/plain", "0x000000000000000000000000000000000000dead", "0x00"]));
+        assert_eq!(unknown["error"]["code"], -32602);
+        assert_eq!(call(&daemon, "eth_sendTransaction", json!([]))["error"]["code"], -32601);
+    }
+
+    #[tokio::test]
+    async fn test_signer_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(daemon(Arc::new(Recorder::default())).serve(listener));
+
+        let exchange = |extra: &'static str| async move {
+            let body = r#"{"jsonrpc":"2.0","id":1,"method":"account_version","params":[]}"#;
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            let request = format!(
+                "POST / HTTP/1.1\r\nHost: {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                addr,
+                extra,
+                body.len(),
+                body
+            );
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut reply = String::new();
+            stream.read_to_string(&mut reply).await.unwrap();
+            reply
+        };
+
+        let reply = exchange("").await;
+        assert!(reply.starts_with("HTTP/1.1 200 OK") && reply.contains(API_VERSION), "{}", reply);
+        // Browsers are refused
+        assert!(exchange("Origin: https://example.com\r\n").await.starts_with("HTTP/1.1 403"));
+        server.abort();
+    }
+}
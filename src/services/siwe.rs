@@ -0,0 +1,464 @@
+//! # Sign-In with Ethereum
+//!
+//! EIP-4361 messages: the text a dapp asks a wallet to sign to log in. A
+//! message is built from its fields and rendered in the exact layout the
+//! specification fixes, then signed as an EIP-191 personal message.
+//! Verification parses the text back, recovers the signer and checks the
+//! fields a backend cares about (domain, nonce, validity window), so
+//! service accounts can log in headlessly and backends can be tested
+//! against real signatures.
+
+use crate::errors::{UserInputError, ValidationError, WalletResult};
+use crate::models::EthereumAddress;
+use crate::services::signer::KeySigner;
+use crate::services::WalletManager;
+use chrono::{DateTime, SecondsFormat, Utc};
+use ethers::types::Signature;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Message version defined by EIP-4361
+pub const SIWE_VERSION: &str = "1";
+
+/// Shortest nonce the specification allows
+pub const MIN_NONCE_LEN: usize = 8;
+
+/// Length of nonces generated by [`SiweMessage::random_nonce`]
+const GENERATED_NONCE_LEN: usize = 17;
+
+const PREAMBLE: &str = " wants you to sign in with your Ethereum account:";
+
+/// An EIP-4361 message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiweMessage {
+    /// RFC 3986 authority requesting the sign-in (e.g. `app.example:443`)
+    pub domain: String,
+    /// Account signing in
+    pub address: EthereumAddress,
+    /// Human-readable assertion shown to the user
+    pub statement: Option<String>,
+    /// RFC 3986 URI the sign-in is for
+    pub uri: String,
+    /// Chain the account is on
+    pub chain_id: u64,
+    /// Server-issued nonce against replay (at least 8 alphanumerics)
+    pub nonce: String,
+    /// Time the message was created
+    pub issued_at: DateTime<Utc>,
+    /// Time after which the sign-in is no longer valid
+    pub expiration_time: Option<DateTime<Utc>>,
+    /// Time before which the sign-in is not yet valid
+    pub not_before: Option<DateTime<Utc>>,
+    /// System-specific request identifier
+    pub request_id: Option<String>,
+    /// URIs the user wishes to have resolved as part of the sign-in
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    /// Message with the required fields, checking them
+    pub fn new(
+        domain: &str,
+        address: EthereumAddress,
+        uri: &str,
+        chain_id: u64,
+        nonce: &str,
+        issued_at: DateTime<Utc>,
+    ) -> WalletResult<Self> {
+        let message = Self {
+            domain: domain.to_string(),
+            address,
+            statement: None,
+            uri: uri.to_string(),
+            chain_id,
+            nonce: nonce.to_string(),
+            issued_at,
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        };
+        message.validate()?;
+        Ok(message)
+    }
+
+    /// Random alphanumeric nonce, for clients that are not handed one
+    pub fn random_nonce() -> String {
+        rand::thread_rng()
+            .sample_iter(rand::distributions::Alphanumeric)
+            .take(GENERATED_NONCE_LEN)
+            .map(char::from)
+            .collect()
+    }
+
+    /// Check every field against the EIP-4361 grammar
+    pub fn validate(&self) -> WalletResult<()> {
+        let single_line = |value: &str| !value.contains(['\n', '\r']);
+
+        if self.domain.is_empty() || self.domain.contains(|c: char| c.is_whitespace() || "/?#".contains(c)) {
+            return Err(invalid_field("domain", &self.domain, "an RFC 3986 authority such as app.example or app.example:8443"));
+        }
+        if !is_uri(&self.uri) {
+            return Err(invalid_field("uri", &self.uri, "an RFC 3986 URI such as https://app.example/login"));
+        }
+        if self.nonce.len() < MIN_NONCE_LEN || !self.nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(invalid_field("nonce", &self.nonce, &format!("at least {} letters and digits", MIN_NONCE_LEN)));
+        }
+        if let Some(statement) = &self.statement {
+            if statement.is_empty() || !single_line(statement) {
+                return Err(invalid_field("statement", statement, "a single non-empty line"));
+            }
+        }
+        if let Some(request_id) = &self.request_id {
+            if !single_line(request_id) {
+                return Err(invalid_field("request-id", request_id, "a single line"));
+            }
+        }
+        if let Some(resource) = self.resources.iter().find(|resource| !is_uri(resource)) {
+            return Err(invalid_field("resource", resource, "an RFC 3986 URI"));
+        }
+        Ok(())
+    }
+
+    /// Parse message text in the EIP-4361 layout
+    pub fn parse(text: &str) -> WalletResult<Self> {
+        let mut lines = text.split('\n');
+        let mut next = |expected: &str| lines.next().ok_or_else(|| malformed(format!("Missing {}", expected)));
+
+        let domain = next("preamble")?
+            .strip_suffix(PREAMBLE)
+            .ok_or_else(|| malformed("Expected \"<domain> wants you to sign in with your Ethereum account:\"".to_string()))?
+            .to_string();
+        let address_line = next("address")?;
+        let address = address_line.parse().map_err(|_| malformed(format!("Invalid address {}", address_line)))?;
+        if EthereumAddress::to_checksum(&address) != address_line {
+            return Err(malformed(format!("Address {} is not EIP-55 checksummed", address_line)).into());
+        }
+        if !next("blank line")?.is_empty() {
+            return Err(malformed("Expected a blank line after the address".to_string()).into());
+        }
+        let mut line = next("URI")?;
+        let statement = if line.starts_with("URI: ") {
+            None
+        } else {
+            let statement = line.to_string();
+            if !next("blank line")?.is_empty() {
+                return Err(malformed("Expected a blank line after the statement".to_string()).into());
+            }
+            line = next("URI")?;
+            Some(statement)
+        };
+
+        let field = |line: &str, name: &str| -> WalletResult<String> {
+            line.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(": "))
+                .map(str::to_string)
+                .ok_or_else(|| malformed(format!("Expected \"{}: \"", name)).into())
+        };
+        let uri = field(line, "URI")?;
+        let version = field(next("version")?, "Version")?;
+        if version != SIWE_VERSION {
+            return Err(malformed(format!("Unsupported version {}", version)).into());
+        }
+        let chain_id = field(next("chain ID")?, "Chain ID")?;
+        let chain_id = chain_id.parse().map_err(|_| malformed(format!("Invalid chain ID {}", chain_id)))?;
+        let nonce = field(next("nonce")?, "Nonce")?;
+        let issued_at = parse_time(&field(next("issue time")?, "Issued At")?)?;
+
+        let mut message = Self {
+            domain,
+            address,
+            statement,
+            uri,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        };
+
+        // Optional fields follow in a fixed order
+        let mut rest = lines.peekable();
+        let mut optional = |name: &str| {
+            let value = rest.peek()?.strip_prefix(name)?.strip_prefix(": ")?.to_string();
+            rest.next();
+            Some(value)
+        };
+        message.expiration_time = optional("Expiration Time").as_deref().map(parse_time).transpose()?;
+        message.not_before = optional("Not Before").as_deref().map(parse_time).transpose()?;
+        message.request_id = optional("Request ID");
+        if rest.peek() == Some(&"Resources:") {
+            rest.next();
+            for line in rest.by_ref() {
+                let resource = line.strip_prefix("- ").ok_or_else(|| malformed("Expected \"- <resource URI>\"".to_string()))?;
+                message.resources.push(resource.to_string());
+            }
+        }
+        if let Some(line) = rest.next() {
+            return Err(malformed(format!("Unexpected line \"{}\"", line)).into());
+        }
+
+        message.validate()?;
+        Ok(message)
+    }
+
+    /// Whether the message is valid at `now`
+    pub fn is_current(&self, now: DateTime<Utc>) -> bool {
+        self.expiration_time.map_or(true, |expiry| now < expiry) && self.not_before.map_or(true, |start| now >= start)
+    }
+}
+
+impl std::fmt::Display for SiweMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}{}", self.domain, PREAMBLE)?;
+        writeln!(f, "{}", self.address.to_checksum())?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", SIWE_VERSION)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", format_time(&self.issued_at))?;
+        if let Some(expiry) = &self.expiration_time {
+            write!(f, "\nExpiration Time: {}", format_time(expiry))?;
+        }
+        if let Some(start) = &self.not_before {
+            write!(f, "\nNot Before: {}", format_time(start))?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, "\nRequest ID: {}", request_id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A signed sign-in message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiweSignature {
+    /// Signed message text
+    pub message: String,
+    /// 65-byte EIP-191 signature, hex with `0x`
+    pub signature: String,
+}
+
+/// What a backend expects of a sign-in beyond the signature
+#[derive(Debug, Clone, Default)]
+pub struct SiweVerifyOptions<'a> {
+    /// Domain the sign-in must be for
+    pub domain: Option<&'a str>,
+    /// Nonce the backend issued
+    pub nonce: Option<&'a str>,
+    /// Chain the account must be on
+    pub chain_id: Option<u64>,
+}
+
+/// Sign-In with Ethereum
+pub struct SiweService;
+
+impl SiweService {
+    /// Sign `message` with `signer`, whose address it must name
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = signer.backend()))]
+    pub fn sign(manager: &WalletManager, signer: &dyn KeySigner, message: &SiweMessage) -> WalletResult<SiweSignature> {
+        message.validate()?;
+        if message.address != signer.address().as_str() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "address".to_string(),
+                value: message.address.to_string(),
+                expected: format!("the signing account {}", signer.address()),
+            }
+            .into());
+        }
+
+        let text = message.to_string();
+        let signature = manager.sign_message(signer, text.as_bytes())?;
+        Ok(SiweSignature {
+            message: text,
+            signature: format!("0x{}", signature),
+        })
+    }
+
+    /// Check a signed sign-in message, returning the message it proves
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn verify(text: &str, signature: &str, options: &SiweVerifyOptions<'_>, now: DateTime<Utc>) -> WalletResult<SiweMessage> {
+        let message = SiweMessage::parse(text)?;
+
+        let signature: Signature = signature
+            .trim()
+            .trim_start_matches("0x")
+            .parse()
+            .map_err(|e: ethers::types::SignatureError| failed(format!("Invalid signature: {}", e)))?;
+        let signer = signature
+            .recover(text)
+            .map_err(|e| failed(format!("Signature does not recover: {}", e)))?;
+        if message.address != EthereumAddress::from(signer) {
+            return Err(failed(format!("Signed by {}, not {}", EthereumAddress::from(signer), message.address)).into());
+        }
+
+        if let Some(domain) = options.domain {
+            if !domain.eq_ignore_ascii_case(&message.domain) {
+                return Err(failed(format!("Sign-in is for {}, expected {}", message.domain, domain)).into());
+            }
+        }
+        if let Some(nonce) = options.nonce {
+            if nonce != message.nonce {
+                return Err(failed("Nonce does not match".to_string()).into());
+            }
+        }
+        if let Some(chain_id) = options.chain_id {
+            if chain_id != message.chain_id {
+                return Err(failed(format!("Sign-in is for chain {}, expected {}", message.chain_id, chain_id)).into());
+            }
+        }
+        if !message.is_current(now) {
+            return Err(failed("Sign-in is expired or not yet valid".to_string()).into());
+        }
+        Ok(message)
+    }
+}
+
+/// `scheme ":" rest` with an RFC 3986 scheme and no whitespace
+fn is_uri(value: &str) -> bool {
+    match value.split_once(':') {
+        Some((scheme, rest)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+                && !rest.is_empty()
+                && !value.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_time(value: &str) -> WalletResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| malformed(format!("Invalid RFC 3339 time {}", value)).into())
+}
+
+fn invalid_field(parameter: &str, value: &str, expected: &str) -> crate::errors::WalletError {
+    UserInputError::InvalidParameters {
+        parameter: parameter.to_string(),
+        value: value.to_string(),
+        expected: expected.to_string(),
+    }
+    .into()
+}
+
+fn malformed(details: String) -> ValidationError {
+    ValidationError::IntegrityCheckFailed {
+        data_type: "SIWE message".to_string(),
+        details,
+    }
+}
+
+fn failed(details: String) -> ValidationError {
+    ValidationError::IntegrityCheckFailed {
+        data_type: "SIWE signature".to_string(),
+        details,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Network, Wallet};
+    use crate::services::LocalKeySigner;
+    use crate::WalletConfig;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_message_layout() {
+        // Example message from EIP-4361
+        let text = "service.invalid wants you to sign in with your Ethereum account:\n\
+            0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2\n\
+            \n\
+            I accept the ServiceOrg Terms of Service: https://service.invalid/tos\n\
+            \n\
+            URI: https://service.invalid/login\n\
+            Version: 1\n\
+            Chain ID: 1\n\
+            Nonce: 32891756\n\
+            Issued At: 2021-09-30T16:25:24Z\n\
+            Resources:\n\
+            - ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq/\n\
+            - https://example.com/my-web2-claim.json";
+        let message = SiweMessage::parse(text).unwrap();
+        assert_eq!(message.domain, "service.invalid");
+        assert_eq!(message.nonce, "32891756");
+        assert_eq!(message.resources.len(), 2);
+        assert_eq!(message.to_string(), text);
+
+        // Without a statement the blank lines collapse as the grammar says
+        let mut bare = message.clone();
+        bare.statement = None;
+        bare.resources.clear();
+        bare.expiration_time = Some(bare.issued_at + chrono::Duration::minutes(5));
+        assert!(bare.to_string().contains("Cc2\n\nURI: "));
+        assert_eq!(SiweMessage::parse(&bare.to_string()).unwrap(), bare);
+
+        // Lowercase addresses, short nonces and extra lines are rejected
+        assert!(SiweMessage::parse(&text.replace("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2")).is_err());
+        assert!(SiweMessage::parse(&text.replace("32891756", "1234")).is_err());
+        assert!(SiweMessage::parse(&format!("{}\nextra", bare)).is_err());
+        assert!(SiweMessage::parse(&text.replace("Version: 1", "Version: 2")).is_err());
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let manager = WalletManager::new(WalletConfig::default());
+        let wallet = Wallet::from_mnemonic(TEST_MNEMONIC, &Network::Mainnet, None).unwrap();
+        let signer = LocalKeySigner::from_wallet(&wallet, 0).unwrap();
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:30:45Z").unwrap().with_timezone(&Utc);
+
+        let mut message = SiweMessage::new(
+            "app.example",
+            signer.address().parse().unwrap(),
+            "https://app.example/login",
+            1,
+            &SiweMessage::random_nonce(),
+            now,
+        )
+        .unwrap();
+        message.statement = Some("Sign in to app.example".to_string());
+        message.expiration_time = Some(now + chrono::Duration::minutes(10));
+        let signed = SiweService::sign(&manager, &signer, &message).unwrap();
+
+        let options = SiweVerifyOptions {
+            domain: Some("app.example"),
+            nonce: Some(&message.nonce),
+            chain_id: Some(1),
+        };
+        let verified = SiweService::verify(&signed.message, &signed.signature, &options, now).unwrap();
+        assert_eq!(verified, message);
+
+        // Wrong expectations, expiry and tampering all fail
+        let later = now + chrono::Duration::minutes(11);
+        assert!(SiweService::verify(&signed.message, &signed.signature, &options, later).is_err());
+        let other = SiweVerifyOptions { domain: Some("evil.example"), ..options.clone() };
+        assert!(SiweService::verify(&signed.message, &signed.signature, &other, now).is_err());
+        let other = SiweVerifyOptions { chain_id: Some(10), ..options.clone() };
+        assert!(SiweService::verify(&signed.message, &signed.signature, &other, now).is_err());
+        let tampered = signed.message.replace("Chain ID: 1", "Chain ID: 10");
+        assert!(SiweService::verify(&tampered, &signed.signature, &SiweVerifyOptions::default(), now).is_err());
+
+        // Only the named account may sign
+        let other = LocalKeySigner::from_wallet(&wallet, 1).unwrap();
+        assert!(SiweService::sign(&manager, &other, &message).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! # Solana Service
+//!
+//! Solana addresses controlled by a wallet's seed. Solana keys are ed25519,
+//! derived with SLIP-0010 rather than BIP32: every level is hardened and
+//! there are no public parents, so addresses always need the seed. Wallets
+//! such as Phantom and Solflare put account `x` at `m/44'/501'/x'/0'`; the
+//! address is the base58 public key. Only addresses are derived; nothing
+//! here signs Solana transactions. Compiled in with the `solana` feature.
+
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::{DerivationPath, Wallet};
+use hmac::{Hmac, Mac};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+/// SLIP-44 coin type of Solana
+pub const SOLANA_COIN_TYPE: u32 = 501;
+
+/// Largest number of addresses derived in one call
+pub const MAX_SOLANA_ADDRESSES: u32 = 1_000;
+
+/// HMAC key of the SLIP-0010 ed25519 master node
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// A derived Solana address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolanaAddress {
+    /// Account index
+    pub index: u32,
+    /// Base58 public key
+    pub address: String,
+    /// Full derivation path
+    pub derivation_path: DerivationPath,
+}
+
+/// SLIP-0010 ed25519 node: private key and chain code
+struct Ed25519Node {
+    key: Zeroizing<[u8; 32]>,
+    chain_code: Zeroizing<[u8; 32]>,
+}
+
+impl Ed25519Node {
+    /// Master node of a BIP39 seed
+    fn master(seed: &[u8]) -> Self {
+        Self::from_hmac(ED25519_SEED_KEY, &[seed])
+    }
+
+    /// Hardened child at `index` (ed25519 has no normal children)
+    fn child(&self, index: u32) -> Self {
+        let index = (index | 1 << 31).to_be_bytes();
+        Self::from_hmac(&self.chain_code[..], &[&[0u8], &self.key[..], &index])
+    }
+
+    fn from_hmac(key: &[u8], parts: &[&[u8]]) -> Self {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        let digest = Zeroizing::new(mac.finalize().into_bytes());
+
+        let mut node = Self {
+            key: Zeroizing::new([0u8; 32]),
+            chain_code: Zeroizing::new([0u8; 32]),
+        };
+        node.key.copy_from_slice(&digest[..32]);
+        node.chain_code.copy_from_slice(&digest[32..]);
+        node
+    }
+
+    /// Ed25519 public key of the node
+    fn public_key(&self) -> WalletResult<[u8; 32]> {
+        let pair = Ed25519KeyPair::from_seed_unchecked(&self.key[..]).map_err(|e| {
+            CryptographicError::AddressGenerationFailed {
+                details: e.to_string(),
+            }
+        })?;
+        let mut public = [0u8; 32];
+        public.copy_from_slice(pair.public_key().as_ref());
+        Ok(public)
+    }
+}
+
+/// Solana address derivation
+pub struct SolanaService;
+
+impl SolanaService {
+    /// Path of account `index`, `m/44'/501'/index'/0'`
+    pub fn account_path(index: u32) -> WalletResult<DerivationPath> {
+        DerivationPath::root()
+            .hardened_child(44)?
+            .hardened_child(SOLANA_COIN_TYPE)?
+            .hardened_child(index)?
+            .hardened_child(0)
+    }
+
+    /// Derive `count` account addresses from an HD wallet
+    pub fn derive_addresses(wallet: &Wallet, start: u32, count: u32) -> WalletResult<Vec<SolanaAddress>> {
+        Self::derive_from_seed(&wallet.seed()?, start, count)
+    }
+
+    /// Derive `count` account addresses from a BIP39 seed
+    #[tracing::instrument(level = "debug", skip_all, fields(start = start, count = count))]
+    pub fn derive_from_seed(seed: &[u8], start: u32, count: u32) -> WalletResult<Vec<SolanaAddress>> {
+        if count == 0 || count > MAX_SOLANA_ADDRESSES {
+            return Err(UserInputError::ValueOutOfRange {
+                parameter: "count".to_string(),
+                value: count.to_string(),
+                range: format!("1-{}", MAX_SOLANA_ADDRESSES),
+            }
+            .into());
+        }
+
+        let coin = Ed25519Node::master(seed).child(44).child(SOLANA_COIN_TYPE);
+        (start..start.saturating_add(count))
+            .map(|index| {
+                let derivation_path = Self::account_path(index)?;
+                let public = coin.child(index).child(0).public_key()?;
+                Ok(SolanaAddress {
+                    index,
+                    address: bs58::encode(public).into_string(),
+                    derivation_path,
+                })
+            })
+            .collect()
+    }
+
+    /// Ed25519 public key at a fully hardened `path` below a seed
+    pub fn public_key_at(seed: &[u8], path: &DerivationPath) -> WalletResult<[u8; 32]> {
+        let mut node = Ed25519Node::master(seed);
+        for component in path.components() {
+            if !component.is_hardened() {
+                return Err(CryptographicError::InvalidDerivationPath {
+                    path: path.to_string(),
+                    expected: "only hardened levels; ed25519 has no normal children".to_string(),
+                }
+                .into());
+            }
+            node = node.child(component.index());
+        }
+        node.public_key()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    #[test]
+    fn test_slip10_vector() {
+        // SLIP-0010 ed25519 test vector 1
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = Ed25519Node::master(&seed);
+        assert_eq!(hex::encode(&master.key[..]), "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7");
+        assert_eq!(hex::encode(&master.chain_code[..]), "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb");
+        assert_eq!(
+            hex::encode(master.public_key().unwrap()),
+            "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed"
+        );
+
+        let child = SolanaService::public_key_at(&seed, &"m/0'".parse().unwrap()).unwrap();
+        assert_eq!(hex::encode(child), "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c");
+        assert!(SolanaService::public_key_at(&seed, &"m/0".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_wallet_addresses() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Mainnet,
+            None,
+        )
+        .unwrap();
+
+        let addresses = SolanaService::derive_addresses(&wallet, 0, 2).unwrap();
+        assert_eq!(addresses[0].address, "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+        assert_eq!(addresses[1].derivation_path.to_string(), "m/44'/501'/1'/0'");
+        assert_ne!(addresses[0].address, addresses[1].address);
+
+        let seed = wallet.seed().unwrap();
+        let at_path = SolanaService::public_key_at(&seed, &addresses[1].derivation_path).unwrap();
+        assert_eq!(bs58::encode(at_path).into_string(), addresses[1].address);
+        assert!(SolanaService::derive_addresses(&wallet, 0, 0).is_err());
+    }
+}
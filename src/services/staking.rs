@@ -0,0 +1,326 @@
+//! # Staking Keys
+//!
+//! Validator keys for Ethereum proof-of-stake, derived from the wallet's
+//! BIP39 seed as EIP-2333 and EIP-2334 specify, so one mnemonic backs both
+//! the wallet's accounts and its validators. Deposit data is built field for
+//! field like the staking deposit CLI builds it, so the staking launchpad
+//! accepts it as is.
+
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::validator_keystore::BlsSecretKey;
+use crate::models::{DerivationPath, EthereumAddress, Wallet};
+use crate::services::bls;
+use ethers::core::k256::elliptic_curve::bigint::{NonZero, U384};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Deposit per validator, in gwei
+pub const DEPOSIT_AMOUNT_GWEI: u64 = 32_000_000_000;
+
+/// Deposit CLI version written into deposit data (the launchpad checks it)
+pub const DEPOSIT_CLI_VERSION: &str = "2.7.0";
+
+/// Networks the launchpad takes deposits for, with their genesis fork versions
+pub const DEPOSIT_NETWORKS: [(&str, [u8; 4]); 4] = [
+    ("mainnet", [0x00, 0x00, 0x00, 0x00]),
+    ("sepolia", [0x90, 0x00, 0x00, 0x69]),
+    ("holesky", [0x01, 0x01, 0x70, 0x00]),
+    ("hoodi", [0x10, 0x00, 0x09, 0x10]),
+];
+
+const DOMAIN_DEPOSIT: [u8; 4] = [0x03, 0x00, 0x00, 0x00];
+
+/// BLS12-381 group order r
+const CURVE_ORDER: U384 =
+    U384::from_be_hex("0000000000000000000000000000000073eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001");
+
+/// Launchpad deposit data entry, as the deposit CLI writes it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositData {
+    /// Validator public key (hex)
+    pub pubkey: String,
+    /// Withdrawal credentials (hex)
+    pub withdrawal_credentials: String,
+    /// Deposit in gwei
+    pub amount: u64,
+    /// Signature over the deposit message (hex)
+    pub signature: String,
+    /// hash_tree_root of the DepositMessage (hex)
+    pub deposit_message_root: String,
+    /// hash_tree_root of the DepositData (hex)
+    pub deposit_data_root: String,
+    /// Genesis fork version of the network (hex)
+    pub fork_version: String,
+    /// Network name
+    pub network_name: String,
+    /// Deposit CLI version the file is compatible with
+    pub deposit_cli_version: String,
+}
+
+/// A derived validator signing key
+pub struct ValidatorKey {
+    /// Validator index within the wallet
+    pub index: u32,
+    /// EIP-2334 signing key path
+    pub path: String,
+    /// Secret key
+    pub secret: BlsSecretKey,
+    /// Compressed public key
+    pub pubkey: [u8; bls::PUBLIC_KEY_LENGTH],
+}
+
+/// EIP-2333 key derivation and launchpad deposit data
+pub struct StakingService;
+
+impl StakingService {
+    /// EIP-2334 signing key path of validator `index`
+    pub fn signing_path(index: u32) -> String {
+        format!("m/12381/3600/{}/0/0", index)
+    }
+
+    /// EIP-2334 withdrawal key path of validator `index`
+    pub fn withdrawal_path(index: u32) -> String {
+        format!("m/12381/3600/{}/0", index)
+    }
+
+    /// Master secret key of a seed
+    pub fn derive_master_key(seed: &[u8]) -> WalletResult<BlsSecretKey> {
+        if seed.len() < 32 {
+            return Err(CryptographicError::InsufficientEntropy {
+                available: seed.len() as u32 * 8,
+                required: 256,
+                suggestion: "EIP-2333 needs a seed of at least 32 bytes".to_string(),
+            }
+            .into());
+        }
+        hkdf_mod_r(seed)
+    }
+
+    /// Child secret key at `index` below `parent`
+    pub fn derive_child_key(parent: &BlsSecretKey, index: u32) -> WalletResult<BlsSecretKey> {
+        hkdf_mod_r(&parent_to_lamport_public_key(parent, index))
+    }
+
+    /// Secret key at an EIP-2334 path such as `m/12381/3600/0/0/0`
+    pub fn derive_path(seed: &[u8], path: &str) -> WalletResult<BlsSecretKey> {
+        let parsed: DerivationPath = path.parse()?;
+        if parsed.components().iter().any(|child| child.is_hardened()) {
+            return Err(CryptographicError::InvalidDerivationPath {
+                path: path.to_string(),
+                expected: "EIP-2334 path without hardened components, e.g. m/12381/3600/0/0/0".to_string(),
+            }
+            .into());
+        }
+
+        parsed
+            .components()
+            .iter()
+            .try_fold(Self::derive_master_key(seed)?, |key, child| Self::derive_child_key(&key, child.index()))
+    }
+
+    /// Signing key of validator `index` of an HD wallet
+    pub fn validator_key(wallet: &Wallet, index: u32) -> WalletResult<ValidatorKey> {
+        let path = Self::signing_path(index);
+        let secret = Self::derive_path(&wallet.seed()?, &path)?;
+        Ok(ValidatorKey {
+            index,
+            pubkey: bls::public_key(&secret),
+            path,
+            secret,
+        })
+    }
+
+    /// Withdrawal credentials of validator `index`: an execution address (0x01) if one is
+    /// given, otherwise the validator's BLS withdrawal key (0x00)
+    pub fn withdrawal_credentials(
+        wallet: &Wallet,
+        index: u32,
+        address: Option<&EthereumAddress>,
+    ) -> WalletResult<[u8; 32]> {
+        let mut credentials = [0u8; 32];
+        match address {
+            Some(address) => {
+                credentials[0] = 0x01;
+                credentials[12..].copy_from_slice(ethers::types::Address::from(*address).as_bytes());
+            }
+            None => {
+                let withdrawal_key = Self::derive_path(&wallet.seed()?, &Self::withdrawal_path(index))?;
+                credentials.copy_from_slice(&Sha256::digest(bls::public_key(&withdrawal_key)));
+                credentials[0] = 0x00;
+            }
+        }
+        Ok(credentials)
+    }
+
+    /// Genesis fork version of a launchpad network
+    pub fn fork_version(network: &str) -> WalletResult<[u8; 4]> {
+        DEPOSIT_NETWORKS
+            .iter()
+            .find(|(name, _)| *name == network)
+            .map(|(_, version)| *version)
+            .ok_or_else(|| {
+                let names: Vec<_> = DEPOSIT_NETWORKS.iter().map(|(name, _)| *name).collect();
+                UserInputError::InvalidParameters {
+                    parameter: "chain".to_string(),
+                    value: network.to_string(),
+                    expected: names.join(", "),
+                }
+                .into()
+            })
+    }
+
+    /// Signed 32 ETH deposit of `key` on the named launchpad network
+    pub fn deposit_data(key: &ValidatorKey, withdrawal_credentials: &[u8; 32], network: &str) -> WalletResult<DepositData> {
+        let fork_version = Self::fork_version(network)?;
+
+        let pubkey_root = hash(&key.pubkey[..32], &chunk(&key.pubkey[32..]));
+        let amount = chunk(&DEPOSIT_AMOUNT_GWEI.to_le_bytes());
+        let message_root = hash(&hash(&pubkey_root, withdrawal_credentials), &hash(&amount, &[0; 32]));
+
+        // compute_domain with an all-zero genesis validators root, as deposits are valid on any fork
+        let fork_data_root = hash(&chunk(&fork_version), &[0; 32]);
+        let mut domain = [0u8; 32];
+        domain[..4].copy_from_slice(&DOMAIN_DEPOSIT);
+        domain[4..].copy_from_slice(&fork_data_root[..28]);
+        let signing_root = hash(&message_root, &domain);
+        let signature = bls::sign(&key.secret, &signing_root);
+        // The launchpad and the deposit contract's consumers check this too; never hand out a bad one
+        if !bls::verify(&key.pubkey, &signing_root, &signature) {
+            return Err(CryptographicError::SignatureFailed {
+                details: format!("deposit signature of validator {} does not verify", key.index),
+            }
+            .into());
+        }
+
+        let signature_root = hash(&hash(&signature[..32], &signature[32..64]), &hash(&signature[64..], &[0; 32]));
+        let data_root = hash(&hash(&pubkey_root, withdrawal_credentials), &hash(&amount, &signature_root));
+
+        Ok(DepositData {
+            pubkey: hex::encode(key.pubkey),
+            withdrawal_credentials: hex::encode(withdrawal_credentials),
+            amount: DEPOSIT_AMOUNT_GWEI,
+            signature: hex::encode(signature),
+            deposit_message_root: hex::encode(message_root),
+            deposit_data_root: hex::encode(data_root),
+            fork_version: hex::encode(fork_version),
+            network_name: network.to_string(),
+            deposit_cli_version: DEPOSIT_CLI_VERSION.to_string(),
+        })
+    }
+}
+
+/// HKDF_mod_r from EIP-2333
+fn hkdf_mod_r(ikm: &[u8]) -> WalletResult<BlsSecretKey> {
+    let mut salt: [u8; 32] = Sha256::digest(b"BLS-SIG-KEYGEN-SALT-").into();
+    let input = Zeroizing::new([ikm, &[0u8]].concat());
+    loop {
+        let mut okm = Zeroizing::new([0u8; 48]);
+        Hkdf::<Sha256>::new(Some(&salt), &input)
+            .expand(&48u16.to_be_bytes(), &mut *okm)
+            .expect("48 bytes is a valid HKDF length");
+
+        let scalar = U384::from_be_slice(&*okm).rem(&NonZero::new(CURVE_ORDER).expect("nonzero order"));
+        let mut bytes = Zeroizing::new(Vec::with_capacity(48));
+        for word in scalar.as_words().iter().rev() {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        if bytes.iter().any(|byte| *byte != 0) {
+            return BlsSecretKey::from_bytes(&bytes[16..]);
+        }
+        salt = Sha256::digest(salt).into();
+    }
+}
+
+/// Compressed Lamport public key of `parent` at `index` (EIP-2333)
+fn parent_to_lamport_public_key(parent: &BlsSecretKey, index: u32) -> [u8; 32] {
+    let salt = index.to_be_bytes();
+    let flipped = Zeroizing::new(parent.as_bytes().map(|byte| !byte));
+
+    let mut lamport_public_key = Sha256::new();
+    for ikm in [&parent.as_bytes()[..], &flipped[..]] {
+        let mut okm = Zeroizing::new(vec![0u8; 32 * 255]);
+        Hkdf::<Sha256>::new(Some(&salt), ikm)
+            .expand(&[], &mut okm)
+            .expect("8160 bytes is a valid HKDF length");
+        for chunk in okm.chunks(32) {
+            lamport_public_key.update(Sha256::digest(chunk));
+        }
+    }
+    lamport_public_key.finalize().into()
+}
+
+/// SHA-256 of two concatenated chunks, the SSZ Merkle node
+fn hash(left: &[u8], right: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update(left).chain_update(right).finalize().into()
+}
+
+/// Bytes right-padded with zeros to one 32-byte SSZ chunk
+fn chunk(bytes: &[u8]) -> [u8; 32] {
+    let mut chunk = [0u8; 32];
+    chunk[..bytes.len()].copy_from_slice(bytes);
+    chunk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(value: &str) -> String {
+        let mut bytes = [0u8; 32];
+        ethers::types::U256::from_dec_str(value).unwrap().to_big_endian(&mut bytes);
+        hex::encode(bytes)
+    }
+
+    #[test]
+    fn test_eip2333_vectors() {
+        // Test cases 0 and 1 from EIP-2333
+        let cases = [
+            (
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+                "6083874454709270928345386274498605044986640685124978867557563392430687146096",
+                0,
+                "20397789859736650942317412262472558107875392172444076792671091975210932703118",
+            ),
+            (
+                "3141592653589793238462643383279502884197169399375105820974944592",
+                "29757020647961307431480504535336562678282505419141012933316116377660817309383",
+                3141592653,
+                "25457201688850691947727629385191704516744796114925897962676248250929345014287",
+            ),
+        ];
+        for (seed, master, index, child) in cases {
+            let seed = hex::decode(seed).unwrap();
+            let master_key = StakingService::derive_master_key(&seed).unwrap();
+            assert_eq!(hex::encode(master_key.as_bytes()), decimal(master));
+            let child_key = StakingService::derive_child_key(&master_key, index).unwrap();
+            assert_eq!(hex::encode(child_key.as_bytes()), decimal(child));
+        }
+        let seed = hex::decode(cases[0].0).unwrap();
+        let key = StakingService::derive_path(&seed, "m/0").unwrap();
+        assert_eq!(hex::encode(key.as_bytes()), decimal(cases[0].3));
+        assert!(StakingService::derive_path(&[7; 32], "m/12381'/3600/0").is_err());
+        assert!(StakingService::derive_master_key(&[7; 16]).is_err());
+
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &crate::models::Network::Mainnet,
+            None,
+        )
+        .unwrap();
+        let key = StakingService::validator_key(&wallet, 0).unwrap();
+        assert_eq!(key.path, "m/12381/3600/0/0/0");
+        let credentials = StakingService::withdrawal_credentials(&wallet, 0, None).unwrap();
+        assert_eq!(credentials[0], 0x00);
+        let address: EthereumAddress = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94".parse().unwrap();
+        let credentials = StakingService::withdrawal_credentials(&wallet, 0, Some(&address)).unwrap();
+        assert_eq!(hex::encode(credentials), "0100000000000000000000009858effd232b4033e47d90003d41ec34ecaeda94");
+
+        let deposit = StakingService::deposit_data(&key, &credentials, "hoodi").unwrap();
+        assert_eq!(deposit.pubkey, hex::encode(key.pubkey));
+        assert_eq!(deposit.fork_version, "10000910");
+        assert_eq!(deposit.signature.len(), 192);
+        assert!(StakingService::deposit_data(&key, &credentials, "goerli").is_err());
+    }
+}
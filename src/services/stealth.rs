@@ -0,0 +1,361 @@
+//! # Stealth Addresses
+//!
+//! ERC-5564 stealth addresses on secp256k1 with view tags (scheme 1), and
+//! the ERC-6538 registry where recipients publish them. A recipient shares
+//! one stealth meta-address, `st:eth:0x<spending key><viewing key>`; a
+//! sender combines it with a one-time ephemeral key into a fresh address
+//! and announces the ephemeral key on chain. The viewing key finds those
+//! announcements without being able to spend, and the spending key claims
+//! them. No address is ever reused.
+//!
+//! Both keys come from the wallet's seed, at `m/5564'/<coin>'/<index>'/0`
+//! (spending) and `m/5564'/<coin>'/<index>'/1` (viewing), away from the
+//! wallet's ordinary receive addresses.
+
+use crate::errors::{CryptographicError, NetworkError, UserInputError, ValidationError, WalletResult};
+use crate::models::{DerivationPath, EthereumAddress, Wallet};
+use ethers::abi::{self, ParamType, Token};
+use ethers::core::k256::elliptic_curve::ops::Reduce;
+use ethers::core::k256::elliptic_curve::sec1::ToEncodedPoint;
+use ethers::core::k256::{ecdsa::VerifyingKey, NonZeroScalar, ProjectivePoint, PublicKey, Scalar, SecretKey, U256};
+use ethers::providers::Middleware;
+use ethers::types::{Address, BlockNumber, Bytes, Filter, H256};
+use ethers::utils::{keccak256, public_key_to_address};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+/// ERC-5564 scheme ID of secp256k1 with view tags
+pub const SCHEME_ID: u64 = 1;
+
+/// Prefix of an Ethereum stealth meta-address
+pub const META_ADDRESS_PREFIX: &str = "st:eth:0x";
+
+/// ERC-5564 announcer singleton (same address on every chain)
+pub const ANNOUNCER_ADDRESS: &str = "0x55649E01B5Df198D18D95b5cc5051630cfD45564";
+
+/// ERC-6538 registry singleton (same address on every chain)
+pub const REGISTRY_ADDRESS: &str = "0x6538E6bf4B0eBd30A8Ea093027Ac2422ce5d6538";
+
+/// Hardened purpose of the stealth key paths
+const PURPOSE: u32 = 5564;
+
+const ANNOUNCE: &str = "announce(uint256,address,bytes,bytes)";
+const REGISTER_KEYS: &str = "registerKeys(uint256,bytes)";
+const ANNOUNCEMENT_EVENT: &str = "Announcement(uint256,address,address,bytes,bytes)";
+
+/// Public half of a recipient's stealth keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StealthMetaAddress {
+    spending: PublicKey,
+    viewing: PublicKey,
+}
+
+impl StealthMetaAddress {
+    /// Compressed spending key followed by the compressed viewing key (66 bytes)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.spending.to_encoded_point(true).as_bytes().to_vec();
+        bytes.extend_from_slice(self.viewing.to_encoded_point(true).as_bytes());
+        bytes
+    }
+}
+
+impl fmt::Display for StealthMetaAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", META_ADDRESS_PREFIX, hex::encode(self.to_bytes()))
+    }
+}
+
+impl FromStr for StealthMetaAddress {
+    type Err = crate::errors::WalletError;
+
+    fn from_str(s: &str) -> WalletResult<Self> {
+        let invalid = || UserInputError::InvalidParameters {
+            parameter: "meta-address".to_string(),
+            value: s.to_string(),
+            expected: format!("{}<66 bytes of compressed spending and viewing keys>", META_ADDRESS_PREFIX),
+        };
+
+        let bytes = s
+            .strip_prefix(META_ADDRESS_PREFIX)
+            .and_then(|body| hex::decode(body).ok())
+            .filter(|bytes| bytes.len() == 66)
+            .ok_or_else(invalid)?;
+        Ok(Self {
+            spending: PublicKey::from_sec1_bytes(&bytes[..33]).map_err(|_| invalid())?,
+            viewing: PublicKey::from_sec1_bytes(&bytes[33..]).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// A fresh stealth address for a recipient, with what the sender announces
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StealthAddress {
+    /// Address to pay
+    pub stealth_address: EthereumAddress,
+    /// Compressed ephemeral public key to announce
+    pub ephemeral_public_key: Bytes,
+    /// First byte of the hashed shared secret, announced as metadata
+    pub view_tag: u8,
+}
+
+/// An `Announcement` event of the ERC-5564 announcer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Announcement {
+    /// Announced stealth address
+    pub stealth_address: EthereumAddress,
+    /// Sender's ephemeral public key
+    pub ephemeral_public_key: Bytes,
+    /// View tag, optionally followed by application data
+    #[serde(default)]
+    pub metadata: Bytes,
+    /// Block the announcement was made in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    /// Transaction that made the announcement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<H256>,
+}
+
+/// A recipient's spending and viewing keys
+pub struct StealthKeys {
+    spending: SecretKey,
+    viewing: SecretKey,
+}
+
+impl StealthKeys {
+    /// Stealth keys of account `index` of an HD wallet
+    pub fn from_wallet(wallet: &Wallet, index: u32) -> WalletResult<Self> {
+        let base = DerivationPath::root()
+            .hardened_child(PURPOSE)?
+            .hardened_child(wallet.coin_type())?
+            .hardened_child(index)?;
+        let key = |branch: u32| -> WalletResult<SecretKey> {
+            let signer = wallet.signing_key_at(&base.child(branch)?)?;
+            Ok(SecretKey::from(signer.signer().as_nonzero_scalar()))
+        };
+        Ok(Self {
+            spending: key(0)?,
+            viewing: key(1)?,
+        })
+    }
+
+    /// Stealth keys from raw spending and viewing private keys
+    pub fn from_secret_keys(spending: &[u8; 32], viewing: &[u8; 32]) -> WalletResult<Self> {
+        let parse = |bytes: &[u8; 32]| {
+            SecretKey::from_slice(bytes).map_err(|_| CryptographicError::InvalidPrivateKey {
+                details: "Stealth key is zero or not below the curve order".to_string(),
+                expected: "32-byte secp256k1 private key".to_string(),
+            })
+        };
+        Ok(Self {
+            spending: parse(spending)?,
+            viewing: parse(viewing)?,
+        })
+    }
+
+    /// Meta-address to publish
+    pub fn meta_address(&self) -> StealthMetaAddress {
+        StealthMetaAddress {
+            spending: self.spending.public_key(),
+            viewing: self.viewing.public_key(),
+        }
+    }
+
+    /// Whether an announcement pays these keys (needs only the viewing key)
+    pub fn matches(&self, announcement: &Announcement) -> bool {
+        let Ok(ephemeral) = PublicKey::from_sec1_bytes(&announcement.ephemeral_public_key) else {
+            return false;
+        };
+        let secret = shared_secret(&ephemeral, &self.viewing);
+        if announcement.metadata.first().is_some_and(|tag| *tag != secret[0]) {
+            return false;
+        }
+        stealth_public_key(&self.spending.public_key(), &secret)
+            .is_some_and(|public| address_of(&public) == announcement.stealth_address)
+    }
+
+    /// Private key of an announced stealth address
+    pub fn claim(&self, announcement: &Announcement) -> WalletResult<Zeroizing<[u8; 32]>> {
+        let ephemeral = PublicKey::from_sec1_bytes(&announcement.ephemeral_public_key).map_err(|_| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "stealth announcement".to_string(),
+                details: "Ephemeral public key is not a secp256k1 point".to_string(),
+            }
+        })?;
+        let secret = shared_secret(&ephemeral, &self.viewing);
+        let scalar = *self.spending.to_nonzero_scalar() + hashed_scalar(&secret);
+        let key = Option::<NonZeroScalar>::from(NonZeroScalar::new(scalar))
+            .map(SecretKey::from)
+            .filter(|key| address_of(&key.public_key()) == announcement.stealth_address)
+            .ok_or_else(|| CryptographicError::KeyUnavailable {
+                details: format!("{} was not sent to this wallet's stealth meta-address", announcement.stealth_address),
+                suggestion: "Check the account index and the announcement's ephemeral key".to_string(),
+            })?;
+        Ok(Zeroizing::new(key.to_bytes().into()))
+    }
+}
+
+/// ERC-5564 stealth address generation and scanning
+pub struct StealthService;
+
+impl StealthService {
+    /// Fresh stealth address for the owner of `meta_address`
+    pub fn generate(meta_address: &StealthMetaAddress) -> WalletResult<StealthAddress> {
+        Self::generate_with(meta_address, &SecretKey::random(&mut rand::thread_rng()))
+    }
+
+    /// Stealth address for `meta_address` under a chosen ephemeral key
+    pub fn generate_with(meta_address: &StealthMetaAddress, ephemeral: &SecretKey) -> WalletResult<StealthAddress> {
+        let secret = shared_secret(&meta_address.viewing, ephemeral);
+        let public = stealth_public_key(&meta_address.spending, &secret).ok_or_else(|| {
+            CryptographicError::AddressGenerationFailed {
+                details: "Stealth public key is the point at infinity".to_string(),
+            }
+        })?;
+        Ok(StealthAddress {
+            stealth_address: address_of(&public),
+            ephemeral_public_key: ephemeral.public_key().to_encoded_point(true).as_bytes().to_vec().into(),
+            view_tag: secret[0],
+        })
+    }
+
+    /// Calldata of `announce` on the announcer, with the view tag as metadata
+    pub fn announce_calldata(stealth: &StealthAddress) -> Bytes {
+        let mut calldata = keccak256(ANNOUNCE)[..4].to_vec();
+        calldata.extend(abi::encode(&[
+            Token::Uint(SCHEME_ID.into()),
+            Token::Address(Address::from(stealth.stealth_address)),
+            Token::Bytes(stealth.ephemeral_public_key.to_vec()),
+            Token::Bytes(vec![stealth.view_tag]),
+        ]));
+        calldata.into()
+    }
+
+    /// Calldata of `registerKeys` on the registry, publishing `meta_address` for the sender
+    pub fn register_calldata(meta_address: &StealthMetaAddress) -> Bytes {
+        let mut calldata = keccak256(REGISTER_KEYS)[..4].to_vec();
+        calldata.extend(abi::encode(&[Token::Uint(SCHEME_ID.into()), Token::Bytes(meta_address.to_bytes())]));
+        calldata.into()
+    }
+
+    /// Announcements among `announcements` that pay `keys`
+    pub fn scan<'a>(keys: &StealthKeys, announcements: &'a [Announcement]) -> Vec<&'a Announcement> {
+        announcements.iter().filter(|announcement| keys.matches(announcement)).collect()
+    }
+
+    /// Scheme 1 announcements made between `from_block` and `to_block` (default: latest)
+    #[tracing::instrument(level = "debug", skip_all, fields(from_block = from_block))]
+    pub async fn fetch_announcements<M: Middleware>(
+        provider: &M,
+        endpoint: &str,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> WalletResult<Vec<Announcement>> {
+        let announcer: Address = ANNOUNCER_ADDRESS.parse().expect("valid announcer address");
+        let filter = Filter::new()
+            .address(announcer)
+            .topic0(H256::from(keccak256(ANNOUNCEMENT_EVENT)))
+            .topic1(H256::from_low_u64_be(SCHEME_ID))
+            .from_block(from_block)
+            .to_block(to_block.map_or(BlockNumber::Latest, BlockNumber::from));
+        let logs = provider.get_logs(&filter).await.map_err(|e| NetworkError::ConnectivityFailure {
+            endpoint: endpoint.to_string(),
+            details: e.to_string(),
+        })?;
+
+        logs.into_iter()
+            .map(|log| {
+                let malformed = |details: &str| ValidationError::IntegrityCheckFailed {
+                    data_type: "stealth announcement".to_string(),
+                    details: format!("{} in transaction {:?}", details, log.transaction_hash.unwrap_or_default()),
+                };
+                let stealth_address = log.topics.get(2).ok_or_else(|| malformed("Missing stealth address topic"))?;
+                let mut fields = abi::decode(&[ParamType::Bytes, ParamType::Bytes], &log.data)
+                    .map_err(|_| malformed("Undecodable event data"))?
+                    .into_iter()
+                    .filter_map(Token::into_bytes);
+                let (Some(ephemeral_public_key), Some(metadata)) = (fields.next(), fields.next()) else {
+                    return Err(malformed("Undecodable event data").into());
+                };
+                Ok(Announcement {
+                    stealth_address: EthereumAddress::from(Address::from(*stealth_address)),
+                    ephemeral_public_key: ephemeral_public_key.into(),
+                    metadata: metadata.into(),
+                    block_number: log.block_number.map(|number| number.as_u64()),
+                    transaction_hash: log.transaction_hash,
+                })
+            })
+            .collect()
+    }
+}
+
+/// keccak256 of the compressed ECDH point between `public` and `secret`
+fn shared_secret(public: &PublicKey, secret: &SecretKey) -> [u8; 32] {
+    let point = (public.to_projective() * *secret.to_nonzero_scalar()).to_affine();
+    keccak256(point.to_encoded_point(true).as_bytes())
+}
+
+fn hashed_scalar(secret: &[u8; 32]) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(&(*secret).into())
+}
+
+/// `P_spend + G * s_h`, or `None` for the point at infinity
+fn stealth_public_key(spending: &PublicKey, secret: &[u8; 32]) -> Option<PublicKey> {
+    let point = spending.to_projective() + ProjectivePoint::GENERATOR * hashed_scalar(secret);
+    PublicKey::from_affine(point.to_affine()).ok()
+}
+
+fn address_of(public: &PublicKey) -> EthereumAddress {
+    EthereumAddress::from(public_key_to_address(&VerifyingKey::from(public)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_scan_and_claim() {
+        let keys = StealthKeys::from_secret_keys(&[0x11; 32], &[0x22; 32]).unwrap();
+        let meta = keys.meta_address();
+        assert!(meta.to_string().starts_with(META_ADDRESS_PREFIX));
+        assert_eq!(meta.to_string().len(), META_ADDRESS_PREFIX.len() + 132);
+        assert_eq!(meta.to_string().parse::<StealthMetaAddress>().unwrap(), meta);
+        assert!("st:eth:0x1234".parse::<StealthMetaAddress>().is_err());
+
+        let first = StealthService::generate(&meta).unwrap();
+        let second = StealthService::generate(&meta).unwrap();
+        assert_ne!(first.stealth_address, second.stealth_address);
+
+        let announce = |stealth: &StealthAddress| Announcement {
+            stealth_address: stealth.stealth_address,
+            ephemeral_public_key: stealth.ephemeral_public_key.clone(),
+            metadata: vec![stealth.view_tag].into(),
+            block_number: None,
+            transaction_hash: None,
+        };
+        let other = StealthKeys::from_secret_keys(&[0x33; 32], &[0x44; 32]).unwrap();
+        let foreign = announce(&StealthService::generate(&other.meta_address()).unwrap());
+        let announcements = vec![announce(&first), foreign.clone(), announce(&second)];
+        let found = StealthService::scan(&keys, &announcements);
+        assert_eq!(found.len(), 2);
+
+        // The claimed key controls the stealth address
+        let key = keys.claim(found[0]).unwrap();
+        let claimed = SecretKey::from_slice(&*key).unwrap();
+        assert_eq!(address_of(&claimed.public_key()), first.stealth_address);
+        assert_eq!(keys.claim(&foreign).unwrap_err().code(), "CRYPTO_012");
+
+        // A wrong view tag rules an announcement out before any point math
+        let mut tagged = announce(&first);
+        tagged.metadata = vec![first.view_tag.wrapping_add(1)].into();
+        assert!(!keys.matches(&tagged));
+
+        let calldata = StealthService::announce_calldata(&first);
+        assert_eq!(&calldata[..4], &keccak256(ANNOUNCE)[..4]);
+        assert_eq!(calldata.len(), 4 + 32 * 4 + 32 * 3 + 32 * 2);
+    }
+}
@@ -0,0 +1,150 @@
+//! # Keystore Storage
+//!
+//! Keystore persistence for hosts without a filesystem. The `wallet`
+//! binary keeps keystores in files through [`CryptoService`]; a WebAssembly
+//! host such as a browser extension implements [`KeystoreStorage`] over
+//! its own storage (`localStorage`, IndexedDB, `chrome.storage`) and gets
+//! the same validation and size limits. [`MemoryStorage`] keeps keystores
+//! in a map, for tests and for hosts that persist the whole set themselves.
+//!
+//! Only encrypted keystore JSON passes through storage; wallets are
+//! encrypted and decrypted with [`CryptoService`] as usual.
+//!
+//! [`CryptoService`]: crate::services::CryptoService
+
+use crate::config;
+use crate::errors::{FileSystemError, UserInputError, WalletResult};
+use crate::models::Keystore;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+/// Named keystore JSON documents
+pub trait KeystoreStorage: Send + Sync {
+    /// Keystore JSON stored under `name`, if any
+    fn read(&self, name: &str) -> WalletResult<Option<String>>;
+
+    /// Store keystore JSON under `name`, replacing any previous one
+    fn write(&self, name: &str, json: &str) -> WalletResult<()>;
+
+    /// Remove `name`, returning whether it was stored
+    fn remove(&self, name: &str) -> WalletResult<bool>;
+
+    /// Names of the stored keystores, sorted
+    fn names(&self) -> WalletResult<Vec<String>>;
+
+    /// Validate `keystore` and store it under `name`
+    fn save(&self, name: &str, keystore: &Keystore) -> WalletResult<()> {
+        if name.trim().is_empty() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "name".to_string(),
+                value: name.to_string(),
+                expected: "a non-empty keystore name".to_string(),
+            }
+            .into());
+        }
+        keystore.validate()?;
+        self.write(name, &keystore.to_json()?)
+    }
+
+    /// Load and validate the keystore stored under `name`
+    fn load(&self, name: &str) -> WalletResult<Keystore> {
+        let json = self.read(name)?.ok_or_else(|| FileSystemError::FileNotFound {
+            path: name.to_string(),
+            directory: "keystore storage".to_string(),
+        })?;
+        if json.len() > config::fs::MAX_KEYSTORE_SIZE as usize {
+            return Err(FileSystemError::InvalidFormat {
+                path: name.to_string(),
+                details: format!(
+                    "Keystore too large: {} bytes (max: {} bytes)",
+                    json.len(),
+                    config::fs::MAX_KEYSTORE_SIZE
+                ),
+            }
+            .into());
+        }
+        Keystore::from_json(&json)
+    }
+}
+
+/// Keystores held in memory
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    keystores: RwLock<BTreeMap<String, String>>,
+}
+
+impl MemoryStorage {
+    /// Empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Storage holding previously exported keystores
+    pub fn from_entries(entries: BTreeMap<String, String>) -> Self {
+        Self {
+            keystores: RwLock::new(entries),
+        }
+    }
+
+    /// Every stored keystore by name, for the host to persist
+    pub fn entries(&self) -> BTreeMap<String, String> {
+        self.keystores.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl KeystoreStorage for MemoryStorage {
+    fn read(&self, name: &str) -> WalletResult<Option<String>> {
+        Ok(self.keystores.read().unwrap_or_else(|e| e.into_inner()).get(name).cloned())
+    }
+
+    fn write(&self, name: &str, json: &str) -> WalletResult<()> {
+        self.keystores
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_string(), json.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) -> WalletResult<bool> {
+        Ok(self.keystores.write().unwrap_or_else(|e| e.into_inner()).remove(name).is_some())
+    }
+
+    fn names(&self) -> WalletResult<Vec<String>> {
+        Ok(self.keystores.read().unwrap_or_else(|e| e.into_inner()).keys().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Network, Wallet};
+    use crate::services::crypto::{CryptoService, KdfSettings, SecureString};
+
+    #[test]
+    fn test_memory_storage_round_trip() {
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Mainnet,
+            None,
+        )
+        .unwrap();
+        let password = SecureString::from("Passw0rd!Str0ng#1");
+        let settings = KdfSettings { memory: 1024, iterations: 1, parallelism: 1 };
+        let keystore = CryptoService::encrypt_wallet_with(&wallet, &password, settings).unwrap();
+
+        let storage = MemoryStorage::new();
+        storage.save("main", &keystore).unwrap();
+        assert!(storage.save(" ", &keystore).is_err());
+        assert_eq!(storage.names().unwrap(), vec!["main".to_string()]);
+
+        // A host persists the entries and restores them later
+        let restored = MemoryStorage::from_entries(storage.entries());
+        let loaded = CryptoService::decrypt_wallet(&restored.load("main").unwrap(), &password).unwrap();
+        assert_eq!(loaded.address(), wallet.address());
+
+        assert!(restored.remove("main").unwrap());
+        assert_eq!(restored.load("main").unwrap_err().code(), "FS_001");
+        restored.write("bad", "{}").unwrap();
+        assert!(restored.load("bad").is_err());
+    }
+}
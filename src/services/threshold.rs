@@ -0,0 +1,280 @@
+//! # Threshold Keystores
+//!
+//! Splits a keystore's data key among several passphrase holders so that
+//! any `k` of the `n` can open it, for example two of a personal, a
+//! spouse's and a lawyer's passphrase. The data key is a random 32-byte
+//! secret; the keystore is encrypted as usual with its hex encoding as the
+//! password, so KDF cost, hardware binding and re-encryption behave as for
+//! any other keystore. The key is split with Shamir's secret sharing and
+//! each share is wrapped with AES-256-GCM under a key derived from its
+//! holder's passphrase with Argon2id, authenticated together with the
+//! holder's label.
+//!
+//! Fewer than `k` shares reveal nothing about the data key. A wrong
+//! passphrase fails the share's authentication and counts toward the
+//! keystore's unlock lockout like a wrong password.
+
+use crate::config;
+use crate::errors::{AuthenticationError, CryptographicError, UserInputError, WalletResult};
+use crate::models::keystore::{KdfParams, ShareSet, WrappedShare};
+use crate::security::unlock_limit::UnlockAttempts;
+use crate::services::crypto::{CryptoService, KdfSettings};
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use rand::RngCore;
+use sharks::{Share, Sharks};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// AES-GCM nonce length
+const NONCE_LENGTH: usize = 12;
+
+/// Shamir splitting and passphrase wrapping of keystore data keys
+pub struct ThresholdService;
+
+impl ThresholdService {
+    /// Split a fresh data key among `holders`, any `threshold` of whom can recover it
+    ///
+    /// Returns the hex-encoded data key to encrypt the keystore with, and
+    /// the wrapped shares to store beside it.
+    #[tracing::instrument(level = "debug", skip_all, fields(threshold = threshold, holders = holders.len()))]
+    pub fn split(
+        threshold: u8,
+        holders: &[(String, Zeroizing<String>)],
+        settings: KdfSettings,
+    ) -> WalletResult<(Zeroizing<String>, ShareSet)> {
+        if holders.len() > config::crypto::MAX_KEY_SHARES || threshold == 0 || usize::from(threshold) > holders.len() {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "threshold".to_string(),
+                value: format!("{} of {}", threshold, holders.len()),
+                expected: format!("between 1 and the number of share holders (at most {})", config::crypto::MAX_KEY_SHARES),
+            }
+            .into());
+        }
+        let labels: std::collections::BTreeSet<_> = holders.iter().map(|(label, _)| label.as_str()).collect();
+        if labels.len() != holders.len() || labels.contains("") {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "share".to_string(),
+                value: holders.iter().map(|(label, _)| label.as_str()).collect::<Vec<_>>().join(", "),
+                expected: "a distinct, non-empty label for each holder".to_string(),
+            }
+            .into());
+        }
+
+        let mut data_key = Zeroizing::new([0u8; config::crypto::KEY_LENGTH]);
+        rand::thread_rng().fill_bytes(&mut *data_key);
+
+        let mut shares = Vec::with_capacity(holders.len());
+        for ((label, passphrase), share) in holders.iter().zip(Sharks(threshold).dealer(&*data_key)) {
+            let share = Zeroizing::new(Vec::from(&share));
+            shares.push(Self::wrap(label, threshold, passphrase, &share, settings)?);
+        }
+
+        let set = ShareSet { threshold, shares };
+        Ok((Zeroizing::new(hex::encode(data_key.as_slice())), set))
+    }
+
+    /// Decrypt one holder's share with their passphrase
+    pub fn open_share(share: &WrappedShare, threshold: u8, passphrase: &str) -> WalletResult<Zeroizing<Vec<u8>>> {
+        let key = Self::share_key(share, passphrase)?;
+        let nonce = hex::decode(&share.iv).map_err(|e| CryptographicError::DataCorruption {
+            details: format!("Share nonce is not hex: {}", e),
+        })?;
+        let ciphertext = hex::decode(&share.ciphertext).map_err(|e| CryptographicError::DataCorruption {
+            details: format!("Share ciphertext is not hex: {}", e),
+        })?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(CryptographicError::DataCorruption {
+                details: "Share nonce has the wrong length".to_string(),
+            }
+            .into());
+        }
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key));
+        let aad = Self::associated_data(&share.label, threshold);
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+            .map(Zeroizing::new)
+            .map_err(|_| {
+                AuthenticationError::WrongPassword {
+                    wallet_file: String::new(),
+                    attempts_remaining: None,
+                }
+                .into()
+            })
+    }
+
+    /// Recover the hex-encoded data key from at least `threshold` opened shares
+    pub fn combine(set: &ShareSet, opened: &[Zeroizing<Vec<u8>>]) -> WalletResult<Zeroizing<String>> {
+        let shares = opened
+            .iter()
+            .map(|bytes| Share::try_from(bytes.as_slice()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CryptographicError::DataCorruption {
+                details: format!("Malformed key share: {}", e),
+            })?;
+        let secret = Zeroizing::new(Sharks(set.threshold).recover(&shares).map_err(|e| {
+            CryptographicError::DataCorruption {
+                details: format!("Could not recover the data key: {}", e),
+            }
+        })?);
+        Ok(Zeroizing::new(hex::encode(&*secret)))
+    }
+
+    /// Ask holders for their passphrases until enough shares open, then recover the data key
+    ///
+    /// `passphrase_provider` is called once per share in order and may
+    /// return `None` to skip a holder who is not present. Each wrong
+    /// passphrase is recorded against the keystore at `path` and ends the
+    /// attempt. `reset` lets a keystore past the hard limit be tried again.
+    pub fn assemble<F>(path: &Path, set: &ShareSet, reset: bool, mut passphrase_provider: F) -> WalletResult<Zeroizing<String>>
+    where
+        F: FnMut(&WrappedShare) -> WalletResult<Option<Zeroizing<String>>>,
+    {
+        let mut attempts = UnlockAttempts::load(path)?;
+        attempts.check(chrono::Utc::now(), reset)?;
+
+        let mut opened = Vec::with_capacity(usize::from(set.threshold));
+        for share in &set.shares {
+            if opened.len() == usize::from(set.threshold) {
+                break;
+            }
+            let passphrase = match passphrase_provider(share)? {
+                Some(passphrase) => passphrase,
+                None => continue,
+            };
+            match Self::open_share(share, set.threshold, &passphrase) {
+                Ok(bytes) => opened.push(bytes),
+                Err(e) if e.code() == "AUTH_001" => {
+                    attempts.record_failure(chrono::Utc::now())?;
+                    return Err(AuthenticationError::WrongPassword {
+                        wallet_file: path.display().to_string(),
+                        attempts_remaining: Some(attempts.remaining()),
+                    }
+                    .into());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if opened.len() < usize::from(set.threshold) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "share passphrases".to_string(),
+                value: opened.len().to_string(),
+                expected: format!("{} of the {} holders", set.threshold, set.shares.len()),
+            }
+            .into());
+        }
+        Self::combine(set, &opened)
+    }
+
+    fn wrap(
+        label: &str,
+        threshold: u8,
+        passphrase: &str,
+        share: &[u8],
+        settings: KdfSettings,
+    ) -> WalletResult<WrappedShare> {
+        let mut salt = [0u8; config::crypto::SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let mut wrapped = WrappedShare {
+            label: label.to_string(),
+            kdfparams: KdfParams::Argon2 {
+                dklen: config::crypto::KEY_LENGTH as u32,
+                memory: settings.memory,
+                time: settings.iterations,
+                parallelism: settings.parallelism,
+                salt: hex::encode(salt),
+            },
+            iv: hex::encode(nonce),
+            ciphertext: String::new(),
+        };
+
+        let key = Self::share_key(&wrapped, passphrase)?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key));
+        let aad = Self::associated_data(label, threshold);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: share, aad: &aad })
+            .map_err(|e| CryptographicError::KdfFailed {
+                details: format!("Share encryption failed: {}", e),
+            })?;
+        wrapped.ciphertext = hex::encode(ciphertext);
+        Ok(wrapped)
+    }
+
+    /// Key wrapping one share, derived from its holder's passphrase
+    fn share_key(share: &WrappedShare, passphrase: &str) -> WalletResult<Zeroizing<[u8; config::crypto::KEY_LENGTH]>> {
+        let (memory, time, parallelism, salt) = match &share.kdfparams {
+            KdfParams::Argon2 { memory, time, parallelism, salt, .. } => (*memory, *time, *parallelism, salt),
+            KdfParams::Pbkdf2 { .. } => {
+                return Err(CryptographicError::DataCorruption {
+                    details: "Key shares must use Argon2id".to_string(),
+                }
+                .into())
+            }
+        };
+        let salt = hex::decode(salt).map_err(|e| CryptographicError::DataCorruption {
+            details: format!("Share salt is not hex: {}", e),
+        })?;
+
+        let mut key = Zeroizing::new([0u8; config::crypto::KEY_LENGTH]);
+        CryptoService::derive_key_argon2(passphrase.as_bytes(), &salt, memory, time, parallelism, &mut *key)?;
+        Ok(key)
+    }
+
+    /// Binds a share to its holder and threshold, so neither can be swapped in the file
+    fn associated_data(label: &str, threshold: u8) -> Vec<u8> {
+        let mut aad = format!("web3wallet key share v1:{}:", threshold).into_bytes();
+        aad.extend_from_slice(label.as_bytes());
+        aad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_of_three_shares() {
+        let settings = KdfSettings { memory: 1024, iterations: 1, parallelism: 1 };
+        let holders: Vec<(String, Zeroizing<String>)> = ["personal", "spouse", "lawyer"]
+            .iter()
+            .map(|label| (label.to_string(), Zeroizing::new(format!("{}-Passw0rd!", label))))
+            .collect();
+        let (data_key, set) = ThresholdService::split(2, &holders, settings).unwrap();
+        assert_eq!(data_key.len(), 64);
+        assert_eq!(set.shares.len(), 3);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.json");
+
+        // Any two holders recover the key; the first may be absent
+        let recovered = ThresholdService::assemble(&path, &set, false, |share| {
+            Ok((share.label != "personal").then(|| Zeroizing::new(format!("{}-Passw0rd!", share.label))))
+        })
+        .unwrap();
+        assert_eq!(recovered, data_key);
+
+        // One holder alone is not enough
+        let err = ThresholdService::assemble(&path, &set, false, |share| {
+            Ok((share.label == "lawyer").then(|| Zeroizing::new("lawyer-Passw0rd!".to_string())))
+        })
+        .unwrap_err();
+        assert_eq!(err.code(), "INPUT_001");
+
+        // A wrong passphrase is counted toward the lockout
+        let err = ThresholdService::assemble(&path, &set, false, |_| Ok(Some(Zeroizing::new("guess".to_string())))).unwrap_err();
+        assert_eq!(err.code(), "AUTH_001");
+        assert_eq!(UnlockAttempts::load(&path).unwrap().failures, 1);
+
+        // A share moved to another holder's label no longer opens
+        let mut swapped = set.shares[1].clone();
+        swapped.label = "personal".to_string();
+        assert!(ThresholdService::open_share(&swapped, 2, "spouse-Passw0rd!").is_err());
+    }
+}
@@ -0,0 +1,405 @@
+//! # TOTP Service
+//!
+//! Time-based one-time codes (RFC 6238: HMAC-SHA1, 30-second steps, six
+//! digits) as a second factor for signing. A keystore is enrolled once with
+//! any authenticator app; afterwards signing requests above the configured
+//! risk threshold need a current code in addition to the keystore password.
+//! Enrollments live in an index file inside the wallet directory, next to
+//! the usage counters the threshold is measured against. The shared secret
+//! is sealed with AES-256-GCM under an Argon2id key from the keystore
+//! password, so a copy of the directory alone cannot produce codes.
+
+use crate::config;
+use crate::errors::{AuthenticationError, CryptographicError, UserInputError, ValidationError, WalletResult};
+use crate::services::crypto::CryptoService;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// TOTP index file name inside the wallet directory
+pub const TOTP_FILE_NAME: &str = "totp.json";
+
+/// Length of one time step in seconds
+pub const TOTP_STEP_SECONDS: i64 = 30;
+
+/// Digits in a code
+pub const TOTP_DIGITS: usize = 6;
+
+/// Steps either side of the current one still accepted (clock drift)
+const ALLOWED_SKEW: i64 = 1;
+
+/// Shared secret length (160 bits, as recommended for HMAC-SHA1)
+const SECRET_BYTES: usize = 20;
+
+/// When signing requires a one-time code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TotpConfig {
+    /// Signatures per day a keystore may make before codes are required (0 = always)
+    pub after_daily: Option<u32>,
+}
+
+impl TotpConfig {
+    /// Whether the `count`-th signature of the day needs a code
+    pub fn required(&self, count: u32) -> bool {
+        self.after_daily.is_some_and(|limit| count > limit)
+    }
+}
+
+/// Shared TOTP secret
+#[derive(Clone)]
+pub struct TotpSecret(Zeroizing<Vec<u8>>);
+
+impl TotpSecret {
+    /// Fresh random secret
+    pub fn generate() -> Self {
+        let mut bytes = Zeroizing::new(vec![0u8; SECRET_BYTES]);
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// Secret from its base32 form, as shown by authenticator apps
+    pub fn from_base32(encoded: &str) -> WalletResult<Self> {
+        let normalized = Zeroizing::new(encoded.trim().trim_end_matches('=').to_ascii_uppercase().replace(' ', ""));
+        data_encoding::BASE32_NOPAD
+            .decode(normalized.as_bytes())
+            .map(|bytes| Self(Zeroizing::new(bytes)))
+            .map_err(|e| {
+                ValidationError::IntegrityCheckFailed {
+                    data_type: "TOTP secret".to_string(),
+                    details: e.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Unpadded base32 form for manual entry
+    pub fn to_base32(&self) -> Zeroizing<String> {
+        Zeroizing::new(data_encoding::BASE32_NOPAD.encode(&self.0))
+    }
+
+    /// `otpauth://` URI for QR enrollment
+    pub fn provisioning_uri(&self, account: &str, issuer: &str) -> Zeroizing<String> {
+        Zeroizing::new(format!(
+            "otpauth://totp/{issuer}:{account}?secret={}&issuer={issuer}&algorithm=SHA1&digits={}&period={}",
+            self.to_base32().as_str(),
+            TOTP_DIGITS,
+            TOTP_STEP_SECONDS,
+            issuer = issuer.replace(' ', "%20"),
+            account = account.replace(' ', "%20"),
+        ))
+    }
+
+    /// Code for the step containing `now`
+    pub fn code_at(&self, now: DateTime<Utc>) -> String {
+        self.code(Self::step(now))
+    }
+
+    /// Step matching `code` within the allowed drift, if any
+    pub fn matching_step(&self, code: &str, now: DateTime<Utc>) -> Option<u64> {
+        let current = Self::step(now) as i64;
+        (current - ALLOWED_SKEW..=current + ALLOWED_SKEW)
+            .filter(|&step| step >= 0)
+            .map(|step| step as u64)
+            .find(|&step| self.code(step) == code)
+    }
+
+    fn step(now: DateTime<Utc>) -> u64 {
+        now.timestamp().max(0) as u64 / TOTP_STEP_SECONDS as u64
+    }
+
+    /// RFC 4226 HOTP value for a counter
+    fn code(&self, counter: u64) -> String {
+        let mut mac = <Hmac<Sha1> as Mac>::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(&counter.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        let offset = (digest[19] & 0x0f) as usize;
+        let value = u32::from_be_bytes([digest[offset] & 0x7f, digest[offset + 1], digest[offset + 2], digest[offset + 3]]);
+        format!("{:0width$}", value % 10u32.pow(TOTP_DIGITS as u32), width = TOTP_DIGITS)
+    }
+}
+
+/// TOTP enrollment of one keystore
+#[derive(Clone, Serialize, Deserialize)]
+struct Enrollment {
+    /// Shared secret, sealed under the keystore password
+    sealed: SealedSecret,
+    /// Time of enrollment
+    enrolled_at: DateTime<Utc>,
+    /// Last step accepted, so a code cannot be replayed
+    last_step: Option<u64>,
+}
+
+/// Shared secret encrypted with AES-256-GCM under an Argon2id key
+///
+/// The keystore name is bound in as associated data, so a sealed secret
+/// cannot be moved to another keystore's enrollment.
+#[derive(Clone, Serialize, Deserialize)]
+struct SealedSecret {
+    memory: u32,
+    time: u32,
+    parallelism: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl SealedSecret {
+    fn seal(secret: &TotpSecret, keystore: &str, password: &str, low_resource: bool) -> WalletResult<Self> {
+        let (memory, time, parallelism) = config::get_argon2_config(low_resource);
+        let mut salt = [0u8; config::crypto::SALT_LENGTH];
+        let mut nonce = [0u8; config::crypto::NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let mut sealed = Self {
+            memory,
+            time,
+            parallelism,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: String::new(),
+        };
+        let ciphertext = sealed
+            .cipher(password)?
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &secret.0, aad: keystore.as_bytes() })
+            .map_err(|e| CryptographicError::KdfFailed {
+                details: format!("TOTP secret encryption failed: {}", e),
+            })?;
+        sealed.ciphertext = hex::encode(ciphertext);
+        Ok(sealed)
+    }
+
+    fn open(&self, keystore: &str, password: &str) -> WalletResult<TotpSecret> {
+        let corrupt = |details: &str| CryptographicError::DataCorruption {
+            details: format!("TOTP enrollment of {}: {}", keystore, details),
+        };
+        let nonce = hex::decode(&self.nonce).map_err(|_| corrupt("invalid nonce encoding"))?;
+        if nonce.len() != config::crypto::NONCE_LENGTH {
+            return Err(corrupt("invalid nonce length").into());
+        }
+        let ciphertext = hex::decode(&self.ciphertext).map_err(|_| corrupt("invalid ciphertext encoding"))?;
+
+        let plaintext = self
+            .cipher(password)?
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: keystore.as_bytes() })
+            .map_err(|_| AuthenticationError::WrongPassword {
+                wallet_file: keystore.to_string(),
+                attempts_remaining: None,
+            })?;
+        Ok(TotpSecret(Zeroizing::new(plaintext)))
+    }
+
+    fn cipher(&self, password: &str) -> WalletResult<Aes256Gcm> {
+        // The index is not authenticated until decryption, so bound the KDF work first
+        if !config::is_argon2_within_bounds(self.memory, self.time, self.parallelism) {
+            return Err(CryptographicError::DataCorruption {
+                details: "TOTP enrollment KDF parameters out of range".to_string(),
+            }
+            .into());
+        }
+        let salt = hex::decode(&self.salt).ok().filter(|salt| salt.len() == config::crypto::SALT_LENGTH).ok_or_else(|| {
+            CryptographicError::DataCorruption {
+                details: "TOTP enrollment has an invalid salt".to_string(),
+            }
+        })?;
+
+        let mut key = Zeroizing::new([0u8; config::crypto::KEY_LENGTH]);
+        CryptoService::derive_key_argon2(password.as_bytes(), &salt, self.memory, self.time, self.parallelism, &mut *key)?;
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key)))
+    }
+}
+
+/// TOTP enrollments for a wallet directory
+#[derive(Default, Serialize, Deserialize)]
+pub struct TotpStore {
+    keystores: BTreeMap<String, Enrollment>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl TotpStore {
+    /// Load the enrollment index, starting empty when none exists
+    pub fn load(wallet_dir: &Path) -> WalletResult<Self> {
+        let path = wallet_dir.join(TOTP_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self { keystores: BTreeMap::new(), path });
+        }
+
+        let json = Zeroizing::new(std::fs::read_to_string(&path)?);
+        let mut store: Self = serde_json::from_str(&json).map_err(|e| ValidationError::IntegrityCheckFailed {
+            data_type: "totp_index".to_string(),
+            details: e.to_string(),
+        })?;
+        store.path = path;
+        Ok(store)
+    }
+
+    /// Persist the enrollment index
+    pub fn save(&self) -> WalletResult<()> {
+        let json = Zeroizing::new(serde_json::to_string_pretty(self)?);
+        crate::utils::write_atomic(&self.path, json.as_bytes(), crate::config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+
+    /// Enroll `keystore` with `secret` sealed under its `password`, refusing to replace an existing enrollment
+    ///
+    /// `low_resource` picks the smaller Argon2id cost for the seal.
+    pub fn enroll(
+        &mut self,
+        keystore: &str,
+        secret: &TotpSecret,
+        password: &str,
+        now: DateTime<Utc>,
+        low_resource: bool,
+    ) -> WalletResult<()> {
+        if self.is_enrolled(keystore) {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "keystore".to_string(),
+                value: keystore.to_string(),
+                expected: "a keystore without TOTP enrollment; remove the existing one first".to_string(),
+            }
+            .into());
+        }
+
+        self.keystores.insert(
+            keystore.to_string(),
+            Enrollment { sealed: SealedSecret::seal(secret, keystore, password, low_resource)?, enrolled_at: now, last_step: None },
+        );
+        Ok(())
+    }
+
+    /// Drop the enrollment of `keystore`; returns false if it had none
+    pub fn remove(&mut self, keystore: &str) -> bool {
+        self.keystores.remove(keystore).is_some()
+    }
+
+    /// Whether `keystore` is enrolled
+    pub fn is_enrolled(&self, keystore: &str) -> bool {
+        self.keystores.contains_key(keystore)
+    }
+
+    /// Enrolled keystores with their enrollment time, sorted by name
+    pub fn entries(&self) -> impl Iterator<Item = (&String, DateTime<Utc>)> {
+        self.keystores.iter().map(|(keystore, enrollment)| (keystore, enrollment.enrolled_at))
+    }
+
+    /// Check a code for `keystore`, unsealing its secret with the keystore `password`, and consume its time step
+    pub fn verify(&mut self, keystore: &str, code: &str, password: &str, now: DateTime<Utc>) -> WalletResult<()> {
+        let failed = |details: &str| AuthenticationError::SecondFactorFailed {
+            keystore: keystore.to_string(),
+            details: details.to_string(),
+        };
+
+        let enrollment = self.keystores.get_mut(keystore).ok_or_else(|| failed("Keystore is not enrolled"))?;
+        let secret = enrollment.sealed.open(keystore, password)?;
+        let step = secret
+            .matching_step(code.trim(), now)
+            .ok_or_else(|| failed("Code is wrong or expired"))?;
+        if enrollment.last_step.is_some_and(|last| step <= last) {
+            return Err(failed("Code was already used").into());
+        }
+
+        enrollment.last_step = Some(step);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// RFC 6238 appendix B SHA1 seed
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+    const PASSWORD: &str = "TotpPass123!";
+
+    fn at(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn test_totp_config() {
+        let config: crate::WalletConfig = toml::from_str("[totp]\nafter_daily = 5\n").unwrap();
+        assert_eq!(config.totp.after_daily, Some(5));
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        let secret = TotpSecret(Zeroizing::new(RFC_SECRET.to_vec()));
+        // Last six digits of the eight-digit vectors
+        assert_eq!(secret.code_at(at(59)), "287082");
+        assert_eq!(secret.code_at(at(1_111_111_109)), "081804");
+        assert_eq!(secret.code_at(at(2_000_000_000)), "279037");
+
+        let round_trip = TotpSecret::from_base32(&secret.to_base32()).unwrap();
+        assert_eq!(round_trip.code_at(at(59)), "287082");
+        assert!(secret.provisioning_uri("hot.json", "Web3 Wallet").starts_with("otpauth://totp/Web3%20Wallet:hot.json?secret=GEZDGNBV"));
+    }
+
+    #[test]
+    fn test_verify_skew_and_replay() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TotpStore::load(temp_dir.path()).unwrap();
+        let secret = TotpSecret::generate();
+        store.enroll("hot.json", &secret, PASSWORD, at(0), false).unwrap();
+        assert!(store.enroll("hot.json", &secret, PASSWORD, at(0), false).is_err());
+
+        let now = at(1_700_000_000);
+        let previous = secret.code_at(now - chrono::Duration::seconds(TOTP_STEP_SECONDS));
+        store.verify("hot.json", &previous, PASSWORD, now).unwrap();
+        assert!(store.verify("hot.json", &previous, PASSWORD, now).is_err());
+        store.verify("hot.json", &secret.code_at(now), PASSWORD, now).unwrap();
+
+        let stale = secret.code_at(now - chrono::Duration::minutes(5));
+        assert!(store.verify("hot.json", &stale, PASSWORD, now).is_err());
+        assert!(store.verify("cold.json", &secret.code_at(now), PASSWORD, now).is_err());
+
+        store.save().unwrap();
+        let mut reloaded = TotpStore::load(temp_dir.path()).unwrap();
+        assert!(reloaded.verify("hot.json", &secret.code_at(now), PASSWORD, now).is_err());
+        assert!(reloaded.remove("hot.json"));
+    }
+
+    #[test]
+    fn test_secret_is_sealed_under_password() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = TotpStore::load(temp_dir.path()).unwrap();
+        let secret = TotpSecret::generate();
+        store.enroll("hot.json", &secret, PASSWORD, at(0), false).unwrap();
+        store.save().unwrap();
+
+        let index = std::fs::read_to_string(temp_dir.path().join(TOTP_FILE_NAME)).unwrap();
+        assert!(!index.contains(secret.to_base32().as_str()));
+        assert!(!index.to_lowercase().contains(&hex::encode(&*secret.0)));
+
+        let now = at(1_700_000_000);
+        let mut reloaded = TotpStore::load(temp_dir.path()).unwrap();
+        assert!(matches!(
+            reloaded.verify("hot.json", &secret.code_at(now), "WrongPass123!", now),
+            Err(crate::WalletError::Authentication(AuthenticationError::WrongPassword { .. }))
+        ));
+        // A sealed secret only opens for the keystore it was enrolled for
+        let sealed = reloaded.keystores["hot.json"].clone();
+        reloaded.keystores.insert("cold.json".to_string(), sealed);
+        assert!(reloaded.verify("cold.json", &secret.code_at(now), PASSWORD, now).is_err());
+        reloaded.verify("hot.json", &secret.code_at(now), PASSWORD, now).unwrap();
+    }
+
+    #[test]
+    fn test_risk_threshold() {
+        assert!(!TotpConfig::default().required(1_000));
+        assert!(TotpConfig { after_daily: Some(0) }.required(1));
+        assert!(!TotpConfig { after_daily: Some(5) }.required(5));
+        assert!(TotpConfig { after_daily: Some(5) }.required(6));
+    }
+}
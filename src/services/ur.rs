@@ -0,0 +1,711 @@
+//! # Uniform Resources
+//!
+//! Blockchain Commons UR encoding (BCR-2020-005), the transport that
+//! air-gapped wallets such as Keystone and AirGap Vault use for QR codes.
+//! A UR is a CBOR payload written as minimal bytewords with a CRC-32
+//! checksum: `ur:<type>/<bytewords>`. Payloads too large for one QR code
+//! are split by a fountain code into parts `ur:<type>/<seq>-<count>/...`.
+//! The first `count` parts carry the fragments in order; later parts XOR
+//! pseudo-randomly chosen fragments together, so a scanner that misses
+//! frames of an animated QR code can still finish from whichever parts it
+//! catches. Only the CBOR subset the Ethereum registry types need is
+//! supported: unsigned integers, byte and text strings, arrays, maps,
+//! tags and booleans.
+
+use crate::errors::{UserInputError, ValidationError, WalletResult};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Shortest fragment the encoder splits a payload into
+pub const MIN_FRAGMENT_LENGTH: usize = 10;
+
+/// Default longest fragment, sized so each part fits a medium QR code
+pub const DEFAULT_MAX_FRAGMENT_LENGTH: usize = 100;
+
+/// Bytewords, indexed by byte value
+const BYTEWORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald", "barn", "belt",
+    "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash", "cats", "chef", "city", "claw",
+    "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan", "dark", "data", "days", "deli", "dice", "diet",
+    "door", "down", "draw", "drop", "drum", "dull", "duty", "each", "easy", "echo", "edge", "epic", "even", "exam",
+    "exit", "eyes", "fact", "fair", "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free",
+    "frog", "fuel", "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope", "horn", "huts",
+    "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade", "jazz", "join", "jolt", "jowl",
+    "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept", "keys", "kick", "kiln", "king", "kite", "kiwi",
+    "knob", "lamb", "lava", "lazy", "leaf", "legs", "liar", "limp", "lion", "list", "logo", "loud", "love", "luau",
+    "luck", "lung", "main", "many", "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail",
+    "navy", "need", "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad", "quiz", "race",
+    "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs", "rust", "safe", "saga", "scar",
+    "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub", "surf", "swan", "taco", "task", "taxi", "tent",
+    "tied", "time", "tiny", "toil", "tomb", "toys", "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user",
+    "vast", "very", "veto", "vial", "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave",
+    "waxy", "webs", "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+/// CRC-32 (ISO-HDLC), the checksum URs use
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32fast::hash(data)
+}
+
+/// Minimal bytewords (first and last letter of each word) with a CRC-32 suffix
+pub fn bytewords_encode(data: &[u8]) -> String {
+    let checksum = crc32(data).to_be_bytes();
+    data.iter()
+        .chain(checksum.iter())
+        .flat_map(|&byte| {
+            let word = BYTEWORDS[usize::from(byte)].as_bytes();
+            [char::from(word[0]), char::from(word[3])]
+        })
+        .collect()
+}
+
+/// Decode minimal bytewords and verify their CRC-32 suffix
+pub fn bytewords_decode(text: &str) -> WalletResult<Vec<u8>> {
+    let invalid = |expected: &str| UserInputError::InvalidParameters {
+        parameter: "ur".to_string(),
+        value: truncate(text),
+        expected: expected.to_string(),
+    };
+
+    let text = text.to_ascii_lowercase();
+    if text.len() % 2 != 0 || text.len() < 10 || !text.is_ascii() {
+        return Err(invalid("minimal bytewords (letter pairs) with a 4-byte checksum").into());
+    }
+    let mut bytes = Vec::with_capacity(text.len() / 2);
+    for pair in text.as_bytes().chunks(2) {
+        let byte = BYTEWORDS
+            .iter()
+            .position(|word| word.as_bytes()[0] == pair[0] && word.as_bytes()[3] == pair[1])
+            .ok_or_else(|| invalid("minimal bytewords (letter pairs) with a 4-byte checksum"))?;
+        bytes.push(byte as u8);
+    }
+
+    let checksum = bytes.split_off(bytes.len() - 4);
+    if crc32(&bytes).to_be_bytes()[..] != checksum[..] {
+        return Err(ValidationError::IntegrityCheckFailed {
+            data_type: "UR".to_string(),
+            details: "bytewords checksum mismatch".to_string(),
+        }
+        .into());
+    }
+    Ok(bytes)
+}
+
+/// A CBOR data item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cbor {
+    /// Unsigned integer (major type 0)
+    Unsigned(u64),
+    /// Byte string (major type 2)
+    Bytes(Vec<u8>),
+    /// UTF-8 text string (major type 3)
+    Text(String),
+    /// Array (major type 4)
+    Array(Vec<Cbor>),
+    /// Map with entries in encoding order (major type 5)
+    Map(Vec<(Cbor, Cbor)>),
+    /// Tagged item (major type 6)
+    Tag(u64, Box<Cbor>),
+    /// `true` or `false`
+    Bool(bool),
+}
+
+impl Cbor {
+    /// Canonical encoding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write(&mut out);
+        out
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Unsigned(value) => write_header(out, 0, *value),
+            Self::Bytes(bytes) => {
+                write_header(out, 2, bytes.len() as u64);
+                out.extend_from_slice(bytes);
+            }
+            Self::Text(text) => {
+                write_header(out, 3, text.len() as u64);
+                out.extend_from_slice(text.as_bytes());
+            }
+            Self::Array(items) => {
+                write_header(out, 4, items.len() as u64);
+                for item in items {
+                    item.write(out);
+                }
+            }
+            Self::Map(entries) => {
+                write_header(out, 5, entries.len() as u64);
+                for (key, value) in entries {
+                    key.write(out);
+                    value.write(out);
+                }
+            }
+            Self::Tag(tag, item) => {
+                write_header(out, 6, *tag);
+                item.write(out);
+            }
+            Self::Bool(value) => out.push(if *value { 0xf5 } else { 0xf4 }),
+        }
+    }
+
+    /// Decode one item that spans all of `bytes`
+    pub fn from_bytes(bytes: &[u8]) -> WalletResult<Self> {
+        let mut offset = 0;
+        let item = Self::read(bytes, &mut offset, 0)?;
+        if offset != bytes.len() {
+            return Err(cbor_error("trailing bytes after the CBOR item"));
+        }
+        Ok(item)
+    }
+
+    fn read(bytes: &[u8], offset: &mut usize, depth: usize) -> WalletResult<Self> {
+        if depth > 16 {
+            return Err(cbor_error("nesting deeper than 16 levels"));
+        }
+        let initial = *bytes.get(*offset).ok_or_else(|| cbor_error("unexpected end of data"))?;
+        *offset += 1;
+        let (major, additional) = (initial >> 5, initial & 0x1f);
+        if major == 7 {
+            return match additional {
+                20 => Ok(Self::Bool(false)),
+                21 => Ok(Self::Bool(true)),
+                _ => Err(cbor_error("unsupported simple value or float")),
+            };
+        }
+
+        let argument = match additional {
+            0..=23 => u64::from(additional),
+            24..=27 => {
+                let width = 1usize << (additional - 24);
+                let field = bytes
+                    .get(*offset..*offset + width)
+                    .ok_or_else(|| cbor_error("unexpected end of data"))?;
+                *offset += width;
+                field.iter().fold(0u64, |value, &byte| value << 8 | u64::from(byte))
+            }
+            _ => return Err(cbor_error("indefinite lengths are not supported")),
+        };
+        let length = |argument: u64| -> WalletResult<usize> {
+            usize::try_from(argument)
+                .ok()
+                .filter(|&length| length <= bytes.len() - *offset)
+                .ok_or_else(|| cbor_error("length runs past the end of data"))
+        };
+
+        match major {
+            0 => Ok(Self::Unsigned(argument)),
+            2 | 3 => {
+                let length = length(argument)?;
+                let data = bytes[*offset..*offset + length].to_vec();
+                *offset += length;
+                if major == 2 {
+                    Ok(Self::Bytes(data))
+                } else {
+                    String::from_utf8(data).map(Self::Text).map_err(|_| cbor_error("text is not UTF-8"))
+                }
+            }
+            4 => {
+                let count = length(argument)?;
+                (0..count).map(|_| Self::read(bytes, offset, depth + 1)).collect::<WalletResult<_>>().map(Self::Array)
+            }
+            5 => {
+                let count = length(argument)?;
+                (0..count)
+                    .map(|_| Ok((Self::read(bytes, offset, depth + 1)?, Self::read(bytes, offset, depth + 1)?)))
+                    .collect::<WalletResult<_>>()
+                    .map(Self::Map)
+            }
+            6 => Ok(Self::Tag(argument, Box::new(Self::read(bytes, offset, depth + 1)?))),
+            _ => Err(cbor_error("negative integers are not supported")),
+        }
+    }
+
+    /// Value of an unsigned integer
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Contents of a byte string
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Value stored under integer `key` of a map
+    pub fn get(&self, key: u64) -> Option<&Cbor> {
+        match self {
+            Self::Map(entries) => entries.iter().find(|(k, _)| k.as_u64() == Some(key)).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Item inside `tag`, or the item itself when untagged
+    pub fn untag(&self, tag: u64) -> &Cbor {
+        match self {
+            Self::Tag(found, item) if *found == tag => item,
+            _ => self,
+        }
+    }
+}
+
+fn write_header(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => out.push(major | argument as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, argument as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(argument as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(argument as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&argument.to_be_bytes());
+        }
+    }
+}
+
+fn cbor_error(details: &str) -> crate::errors::WalletError {
+    ValidationError::IntegrityCheckFailed {
+        data_type: "UR payload".to_string(),
+        details: format!("invalid CBOR: {}", details),
+    }
+    .into()
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(40) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Xoshiro256** seeded from SHA-256, the generator fountain codes use to pick fragments
+struct Xoshiro256 {
+    s: [u64; 4],
+}
+
+impl Xoshiro256 {
+    fn from_seed(seed: &[u8]) -> Self {
+        let digest = Sha256::digest(seed);
+        let mut s = [0u64; 4];
+        for (word, chunk) in s.iter_mut().zip(digest.chunks(8)) {
+            *word = chunk.iter().fold(0u64, |value, &byte| value << 8 | u64::from(byte));
+        }
+        Self { s }
+    }
+
+    fn next(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next() as f64 / (u64::MAX as f64 + 1.0)
+    }
+
+    fn next_int(&mut self, low: u64, high: u64) -> u64 {
+        (self.next_double() * (high - low + 1) as f64) as u64 + low
+    }
+}
+
+/// Fragment indexes XORed together in part `seq_num` of `seq_len`
+fn choose_fragments(seq_num: u32, seq_len: usize, checksum: u32) -> BTreeSet<usize> {
+    if seq_num as usize <= seq_len {
+        return BTreeSet::from([seq_num as usize - 1]);
+    }
+
+    let mut seed = seq_num.to_be_bytes().to_vec();
+    seed.extend_from_slice(&checksum.to_be_bytes());
+    let mut rng = Xoshiro256::from_seed(&seed);
+
+    // Degree d is drawn with probability proportional to 1/d (Vose alias sampling)
+    let weights: Vec<f64> = (1..=seq_len).map(|degree| 1.0 / degree as f64).collect();
+    let degree = sample_alias(&weights, &mut rng) + 1;
+
+    let mut remaining: Vec<usize> = (0..seq_len).collect();
+    let mut chosen = BTreeSet::new();
+    while !remaining.is_empty() && chosen.len() < degree {
+        let index = rng.next_int(0, remaining.len() as u64 - 1) as usize;
+        chosen.insert(remaining.remove(index));
+    }
+    chosen
+}
+
+/// Draw an index with the given relative weights, as the reference sampler does
+fn sample_alias(weights: &[f64], rng: &mut Xoshiro256) -> usize {
+    let count = weights.len();
+    let sum: f64 = weights.iter().sum();
+    let mut scaled: Vec<f64> = weights.iter().map(|weight| weight * count as f64 / sum).collect();
+
+    let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..count).rev().partition(|&j| scaled[j] < 1.0);
+    let mut probabilities = vec![0.0; count];
+    let mut aliases = vec![0; count];
+    while let (Some(&a), Some(&g)) = (small.last(), large.last()) {
+        small.pop();
+        large.pop();
+        probabilities[a] = scaled[a];
+        aliases[a] = g;
+        scaled[g] += scaled[a] - 1.0;
+        if scaled[g] < 1.0 {
+            small.push(g);
+        } else {
+            large.push(g);
+        }
+    }
+    for index in large.into_iter().chain(small) {
+        probabilities[index] = 1.0;
+    }
+
+    let r1 = rng.next_double();
+    let r2 = rng.next_double();
+    let index = (count as f64 * r1) as usize;
+    if r2 < probabilities[index] {
+        index
+    } else {
+        aliases[index]
+    }
+}
+
+/// Header and payload of one part of a multi-part UR
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FountainPart {
+    seq_num: u32,
+    seq_len: usize,
+    message_len: usize,
+    checksum: u32,
+    data: Vec<u8>,
+}
+
+impl FountainPart {
+    fn to_cbor(&self) -> Cbor {
+        Cbor::Array(vec![
+            Cbor::Unsigned(u64::from(self.seq_num)),
+            Cbor::Unsigned(self.seq_len as u64),
+            Cbor::Unsigned(self.message_len as u64),
+            Cbor::Unsigned(u64::from(self.checksum)),
+            Cbor::Bytes(self.data.clone()),
+        ])
+    }
+
+    fn from_cbor(item: &Cbor) -> WalletResult<Self> {
+        let fields = match item {
+            Cbor::Array(fields) if fields.len() == 5 => fields,
+            _ => return Err(cbor_error("a multi-part UR must be a 5-element array")),
+        };
+        let number = |index: usize| fields[index].as_u64().ok_or_else(|| cbor_error("part header must be unsigned integers"));
+        let small = |value: u64| u32::try_from(value).map_err(|_| cbor_error("part header out of range"));
+
+        Ok(Self {
+            seq_num: small(number(0)?)?,
+            seq_len: small(number(1)?)? as usize,
+            message_len: small(number(2)?)? as usize,
+            checksum: small(number(3)?)?,
+            data: fields[4].as_bytes().ok_or_else(|| cbor_error("part data must be a byte string"))?.to_vec(),
+        })
+    }
+}
+
+/// Splits a UR into an endless sequence of parts
+#[derive(Debug, Clone)]
+pub struct UrEncoder {
+    ur_type: String,
+    message: Vec<u8>,
+    fragments: Vec<Vec<u8>>,
+    checksum: u32,
+    seq_num: u32,
+}
+
+impl UrEncoder {
+    /// Encoder for a CBOR payload of registry type `ur_type`, with fragments of at most `max_fragment_len` bytes
+    pub fn new(ur_type: &str, message: Vec<u8>, max_fragment_len: usize) -> WalletResult<Self> {
+        if !is_valid_type(ur_type) || message.is_empty() || max_fragment_len < MIN_FRAGMENT_LENGTH {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "ur".to_string(),
+                value: format!("{} ({} bytes, fragments of {})", ur_type, message.len(), max_fragment_len),
+                expected: format!("a lowercase type, a payload, and fragments of at least {} bytes", MIN_FRAGMENT_LENGTH),
+            }
+            .into());
+        }
+
+        // Fewest fragments of at most `max_fragment_len`, all the same length
+        let count = (message.len() + max_fragment_len - 1) / max_fragment_len;
+        let fragment_len = (message.len() + count - 1) / count;
+        let mut padded = message.clone();
+        padded.resize(fragment_len * count, 0);
+        let fragments = padded.chunks(fragment_len).map(<[u8]>::to_vec).collect();
+
+        Ok(Self {
+            ur_type: ur_type.to_string(),
+            checksum: crc32(&message),
+            message,
+            fragments,
+            seq_num: 0,
+        })
+    }
+
+    /// Check if the whole payload fits in one part
+    pub fn is_single_part(&self) -> bool {
+        self.fragments.len() == 1
+    }
+
+    /// Number of parts that carry every fragment once
+    pub fn fragment_count(&self) -> usize {
+        self.fragments.len()
+    }
+
+    /// Next part; a single-part UR returns the same string every time
+    pub fn next_part(&mut self) -> String {
+        if self.is_single_part() {
+            return format!("ur:{}/{}", self.ur_type, bytewords_encode(&self.message));
+        }
+
+        self.seq_num = self.seq_num.wrapping_add(1).max(1);
+        let indexes = choose_fragments(self.seq_num, self.fragments.len(), self.checksum);
+        let mut data = vec![0u8; self.fragments[0].len()];
+        for index in indexes {
+            xor_into(&mut data, &self.fragments[index]);
+        }
+        let part = FountainPart {
+            seq_num: self.seq_num,
+            seq_len: self.fragments.len(),
+            message_len: self.message.len(),
+            checksum: self.checksum,
+            data,
+        };
+        format!(
+            "ur:{}/{}-{}/{}",
+            self.ur_type,
+            part.seq_num,
+            part.seq_len,
+            bytewords_encode(&part.to_cbor().to_bytes())
+        )
+    }
+}
+
+/// Reassembles a UR from parts received in any order
+#[derive(Debug, Clone, Default)]
+pub struct UrDecoder {
+    ur_type: Option<String>,
+    header: Option<(usize, usize, u32, usize)>,
+    simple: BTreeMap<usize, Vec<u8>>,
+    mixed: Vec<(BTreeSet<usize>, Vec<u8>)>,
+    message: Option<Vec<u8>>,
+}
+
+impl UrDecoder {
+    /// Empty decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one part (`ur:type/...` in either case); returns true once the UR is complete
+    pub fn receive(&mut self, part: &str) -> WalletResult<bool> {
+        if self.message.is_some() {
+            return Ok(true);
+        }
+
+        let part = part.trim().to_ascii_lowercase();
+        let invalid = || UserInputError::InvalidParameters {
+            parameter: "ur".to_string(),
+            value: truncate(&part),
+            expected: "ur:<type>/<bytewords> or ur:<type>/<seq>-<count>/<bytewords>".to_string(),
+        };
+        let body = part.strip_prefix("ur:").ok_or_else(invalid)?;
+        let components: Vec<&str> = body.split('/').collect();
+        let ur_type = components[0];
+        if !is_valid_type(ur_type) {
+            return Err(invalid().into());
+        }
+        match &self.ur_type {
+            Some(expected) if expected != ur_type => {
+                return Err(UserInputError::InvalidParameters {
+                    parameter: "ur".to_string(),
+                    value: ur_type.to_string(),
+                    expected: format!("another part of the {} being decoded", expected),
+                }
+                .into());
+            }
+            _ => self.ur_type = Some(ur_type.to_string()),
+        }
+
+        match components[1..] {
+            [payload] => {
+                self.message = Some(bytewords_decode(payload)?);
+                Ok(true)
+            }
+            [sequence, payload] => {
+                let part = FountainPart::from_cbor(&Cbor::from_bytes(&bytewords_decode(payload)?)?)?;
+                if sequence != format!("{}-{}", part.seq_num, part.seq_len) {
+                    return Err(invalid().into());
+                }
+                self.receive_part(part)
+            }
+            _ => Err(invalid().into()),
+        }
+    }
+
+    fn receive_part(&mut self, part: FountainPart) -> WalletResult<bool> {
+        let header = (part.seq_len, part.message_len, part.checksum, part.data.len());
+        let consistent = part.seq_len > 0
+            && part.seq_num > 0
+            && part.data.len() * part.seq_len >= part.message_len
+            && part.message_len > part.data.len() * (part.seq_len - 1);
+        if !consistent || self.header.is_some_and(|expected| expected != header) {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: "UR".to_string(),
+                details: format!("part {}-{} does not match the parts before it", part.seq_num, part.seq_len),
+            }
+            .into());
+        }
+        self.header = Some(header);
+
+        // Peel known fragments off each part until it is a single fragment or cannot shrink further
+        let mut queue = vec![(choose_fragments(part.seq_num, part.seq_len, part.checksum), part.data)];
+        while let Some((mut indexes, mut data)) = queue.pop() {
+            for index in indexes.clone() {
+                if let Some(fragment) = self.simple.get(&index) {
+                    xor_into(&mut data, fragment);
+                    indexes.remove(&index);
+                }
+            }
+            match indexes.len() {
+                0 => {}
+                1 => {
+                    let index = *indexes.iter().next().expect("one index");
+                    self.simple.insert(index, data);
+                    let (solvable, rest) = std::mem::take(&mut self.mixed)
+                        .into_iter()
+                        .partition(|(mixed, _)| mixed.contains(&index));
+                    self.mixed = rest;
+                    queue.extend(solvable);
+                }
+                _ if self.mixed.iter().any(|(mixed, _)| *mixed == indexes) => {}
+                _ => self.mixed.push((indexes, data)),
+            }
+        }
+
+        if self.simple.len() < part.seq_len {
+            return Ok(false);
+        }
+        let mut message: Vec<u8> = self.simple.values().flatten().copied().collect();
+        message.truncate(part.message_len);
+        if crc32(&message) != part.checksum {
+            return Err(ValidationError::IntegrityCheckFailed {
+                data_type: "UR".to_string(),
+                details: "reassembled message checksum mismatch".to_string(),
+            }
+            .into());
+        }
+        self.message = Some(message);
+        Ok(true)
+    }
+
+    /// Fraction of fragments recovered so far
+    pub fn progress(&self) -> f64 {
+        match (self.message.is_some(), self.header) {
+            (true, _) => 1.0,
+            (false, Some((seq_len, ..))) => self.simple.len() as f64 / seq_len as f64,
+            (false, None) => 0.0,
+        }
+    }
+
+    /// Type and CBOR payload of the completed UR
+    pub fn result(&self) -> Option<(&str, &[u8])> {
+        Some((self.ur_type.as_deref()?, self.message.as_deref()?))
+    }
+}
+
+fn is_valid_type(ur_type: &str) -> bool {
+    !ur_type.is_empty() && ur_type.bytes().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-')
+}
+
+fn xor_into(target: &mut [u8], source: &[u8]) {
+    for (target, source) in target.iter_mut().zip(source) {
+        *target ^= source;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytewords_and_rng_vectors() {
+        // BCR-2020-012 minimal bytewords vector
+        assert_eq!(bytewords_encode(&[0, 1, 2, 128, 255]), "aeadaolazmjendeoti");
+        assert_eq!(bytewords_decode("AEADAOLAZMJENDEOTI").unwrap(), vec![0, 1, 2, 128, 255]);
+        assert!(bytewords_decode("aeadaolazmjendeota").is_err());
+
+        // BCR-2020-005 generator vector: Xoshiro256** seeded with SHA-256("Wolf")
+        let mut rng = Xoshiro256::from_seed(b"Wolf");
+        let numbers: Vec<u64> = (0..12).map(|_| rng.next() % 100).collect();
+        assert_eq!(numbers, [42, 81, 85, 8, 82, 84, 76, 73, 70, 88, 2, 74]);
+    }
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let item = Cbor::Map(vec![
+            (Cbor::Unsigned(1), Cbor::Tag(37, Box::new(Cbor::Bytes(vec![7; 16])))),
+            (Cbor::Unsigned(2), Cbor::Array(vec![Cbor::Unsigned(44), Cbor::Bool(true), Cbor::Unsigned(70_000)])),
+            (Cbor::Unsigned(3), Cbor::Text("origin".to_string())),
+        ]);
+        let bytes = item.to_bytes();
+        assert_eq!(&bytes[..3], &[0xa3, 0x01, 0xd8]);
+        assert_eq!(Cbor::from_bytes(&bytes).unwrap(), item);
+        assert_eq!(item.get(1).unwrap().untag(37).as_bytes(), Some(&[7u8; 16][..]));
+        assert!(Cbor::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_multi_part_round_trip() {
+        let message: Vec<u8> = (0..1000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let mut encoder = UrEncoder::new("bytes", Cbor::Bytes(message.clone()).to_bytes(), 60).unwrap();
+        assert!(!encoder.is_single_part());
+        let parts: Vec<String> = (0..60).map(|_| encoder.next_part()).collect();
+        assert!(parts[0].starts_with(&format!("ur:bytes/1-{}/", encoder.fragment_count())));
+
+        // Skip every third pure fragment so the mixed parts have to fill the gaps
+        let mut decoder = UrDecoder::new();
+        let mut done = false;
+        for (i, part) in parts.iter().enumerate() {
+            if i < encoder.fragment_count() && i % 3 == 0 {
+                continue;
+            }
+            done = decoder.receive(&part.to_uppercase()).unwrap();
+            if done {
+                break;
+            }
+        }
+        assert!(done);
+        let (ur_type, payload) = decoder.result().unwrap();
+        assert_eq!(ur_type, "bytes");
+        assert_eq!(Cbor::from_bytes(payload).unwrap(), Cbor::Bytes(message));
+
+        let mut single = UrEncoder::new("bytes", vec![0x41, 0x01], 100).unwrap();
+        assert!(single.is_single_part());
+        let mut decoder = UrDecoder::new();
+        assert!(decoder.receive(&single.next_part()).unwrap());
+        assert!(UrDecoder::new().receive("ur:bytes/1-3/aeadaolazmjendeoti").is_err());
+    }
+}
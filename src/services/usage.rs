@@ -0,0 +1,270 @@
+//! # Usage Service
+//!
+//! Per-keystore signing counters with daily quotas. Counters live in a small
+//! index file inside the wallet directory. Crossing a soft quota only warns;
+//! reaching a hard quota locks the keystore for signing until an operator
+//! runs `wallet usage reset`, so a runaway automated signer stays stopped
+//! instead of resuming at midnight.
+
+use crate::errors::{AuthenticationError, ValidationError, WalletResult};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Usage index file name inside the wallet directory
+pub const USAGE_FILE_NAME: &str = "usage.json";
+
+/// Daily signing limits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaLimits {
+    /// Signatures per day before warning
+    pub soft_daily: Option<u32>,
+    /// Signatures per day before refusing
+    pub hard_daily: Option<u32>,
+}
+
+/// Quota configuration with optional per-keystore overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    /// Limits applied to every keystore
+    #[serde(flatten)]
+    pub default: QuotaLimits,
+    /// Overrides keyed by keystore file name
+    pub keystores: BTreeMap<String, QuotaLimits>,
+}
+
+impl QuotaConfig {
+    /// Effective limits for a keystore
+    pub fn limits_for(&self, keystore: &str) -> QuotaLimits {
+        match self.keystores.get(keystore) {
+            Some(overrides) => QuotaLimits {
+                soft_daily: overrides.soft_daily.or(self.default.soft_daily),
+                hard_daily: overrides.hard_daily.or(self.default.hard_daily),
+            },
+            None => self.default,
+        }
+    }
+}
+
+/// Signing counters for one keystore
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyUsage {
+    /// Signatures since the counter was created
+    pub total: u64,
+    /// Day the daily counter refers to (UTC)
+    pub day: NaiveDate,
+    /// Signatures on `day`
+    pub today: u32,
+    /// Time of the most recent signature
+    pub last_used: Option<DateTime<Utc>>,
+    /// Set when the hard quota was reached; cleared by reset
+    pub locked: bool,
+}
+
+/// Outcome of recording a signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaStatus {
+    /// Within all limits
+    Within {
+        /// Signatures today, including this one
+        count: u32,
+    },
+    /// Soft quota crossed; signing continues with a warning
+    SoftLimitExceeded {
+        /// Signatures today, including this one
+        count: u32,
+        /// Configured soft limit
+        limit: u32,
+    },
+}
+
+/// Signing usage index for a wallet directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageTracker {
+    keystores: BTreeMap<String, KeyUsage>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl UsageTracker {
+    /// Load the usage index, starting empty when none exists
+    pub fn load(wallet_dir: &Path) -> WalletResult<Self> {
+        let path = wallet_dir.join(USAGE_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self { keystores: BTreeMap::new(), path });
+        }
+
+        let json = std::fs::read_to_string(&path)?;
+        let mut tracker: Self = serde_json::from_str(&json).map_err(|e| {
+            ValidationError::IntegrityCheckFailed {
+                data_type: "usage_index".to_string(),
+                details: e.to_string(),
+            }
+        })?;
+        tracker.path = path;
+        Ok(tracker)
+    }
+
+    /// Persist the usage index
+    pub fn save(&self) -> WalletResult<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        crate::utils::write_atomic(&self.path, json.as_bytes(), crate::config::fs::KEYSTORE_FILE_PERMISSIONS)
+    }
+
+    /// Count a signature for `keystore`, refusing once the hard quota is reached
+    pub fn record_signature(
+        &mut self,
+        keystore: &str,
+        limits: QuotaLimits,
+        now: DateTime<Utc>,
+    ) -> WalletResult<QuotaStatus> {
+        let today = now.date_naive();
+        let usage = self.keystores.entry(keystore.to_string()).or_insert(KeyUsage {
+            total: 0,
+            day: today,
+            today: 0,
+            last_used: None,
+            locked: false,
+        });
+
+        if usage.day != today {
+            usage.day = today;
+            usage.today = 0;
+        }
+
+        if let Some(limit) = limits.hard_daily {
+            if usage.locked || usage.today >= limit {
+                usage.locked = true;
+                return Err(AuthenticationError::QuotaExceeded {
+                    keystore: keystore.to_string(),
+                    limit,
+                }
+                .into());
+            }
+        }
+
+        usage.today += 1;
+        usage.total += 1;
+        usage.last_used = Some(now);
+
+        Ok(match limits.soft_daily {
+            Some(limit) if usage.today > limit => QuotaStatus::SoftLimitExceeded {
+                count: usage.today,
+                limit,
+            },
+            _ => QuotaStatus::Within { count: usage.today },
+        })
+    }
+
+    /// Clear the daily counter and lock for `keystore`; returns false if it was never used
+    pub fn reset(&mut self, keystore: &str) -> bool {
+        match self.keystores.get_mut(keystore) {
+            Some(usage) => {
+                usage.today = 0;
+                usage.locked = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Signatures `keystore` has made on the UTC day of `now`
+    pub fn count_today(&self, keystore: &str, now: DateTime<Utc>) -> u32 {
+        self.keystores
+            .get(keystore)
+            .filter(|usage| usage.day == now.date_naive())
+            .map_or(0, |usage| usage.today)
+    }
+
+    /// Usage for one keystore
+    pub fn usage(&self, keystore: &str) -> Option<&KeyUsage> {
+        self.keystores.get(keystore)
+    }
+
+    /// Usage for every tracked keystore, sorted by name
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &KeyUsage)> {
+        self.keystores.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_quota_config() {
+        let config: crate::WalletConfig =
+            toml::from_str("[quotas]\nsoft_daily = 10\n\n[quotas.keystores.\"hot.json\"]\nhard_daily = 20\n").unwrap();
+        assert_eq!(config.quotas.limits_for("hot.json").soft_daily, Some(10));
+        assert_eq!(config.quotas.limits_for("hot.json").hard_daily, Some(20));
+    }
+
+    #[test]
+    fn test_soft_and_hard_quotas() {
+        let mut tracker = UsageTracker::default();
+        let limits = QuotaLimits { soft_daily: Some(1), hard_daily: Some(2) };
+
+        assert_eq!(
+            tracker.record_signature("hot.json", limits, at(1, 9)).unwrap(),
+            QuotaStatus::Within { count: 1 }
+        );
+        assert_eq!(
+            tracker.record_signature("hot.json", limits, at(1, 10)).unwrap(),
+            QuotaStatus::SoftLimitExceeded { count: 2, limit: 1 }
+        );
+        assert!(tracker.record_signature("hot.json", limits, at(1, 11)).is_err());
+
+        // The lock survives the day rollover until reset
+        assert!(tracker.record_signature("hot.json", limits, at(2, 9)).is_err());
+        assert!(tracker.reset("hot.json"));
+        assert!(tracker.record_signature("hot.json", limits, at(2, 10)).is_ok());
+        assert_eq!(tracker.usage("hot.json").unwrap().total, 3);
+    }
+
+    #[test]
+    fn test_daily_counter_rolls_over() {
+        let mut tracker = UsageTracker::default();
+        let limits = QuotaLimits { soft_daily: None, hard_daily: Some(1) };
+
+        tracker.record_signature("bot.json", limits, at(1, 23)).unwrap();
+        tracker.record_signature("bot.json", limits, at(2, 0)).unwrap();
+        assert_eq!(tracker.usage("bot.json").unwrap().today, 1);
+        assert_eq!(tracker.count_today("bot.json", at(2, 12)), 1);
+        assert_eq!(tracker.count_today("bot.json", at(3, 0)), 0);
+    }
+
+    #[test]
+    fn test_quota_overrides() {
+        let mut config = QuotaConfig {
+            default: QuotaLimits { soft_daily: Some(100), hard_daily: Some(500) },
+            keystores: BTreeMap::new(),
+        };
+        config.keystores.insert(
+            "hot.json".to_string(),
+            QuotaLimits { soft_daily: None, hard_daily: Some(50) },
+        );
+
+        assert_eq!(config.limits_for("hot.json"), QuotaLimits { soft_daily: Some(100), hard_daily: Some(50) });
+        assert_eq!(config.limits_for("cold.json"), config.default);
+    }
+
+    #[test]
+    fn test_usage_persistence() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut tracker = UsageTracker::load(temp_dir.path()).unwrap();
+        tracker.record_signature("hot.json", QuotaLimits::default(), at(1, 9)).unwrap();
+        tracker.save().unwrap();
+
+        let reloaded = UsageTracker::load(temp_dir.path()).unwrap();
+        assert_eq!(reloaded.usage("hot.json"), tracker.usage("hot.json"));
+    }
+}
@@ -0,0 +1,329 @@
+//! # Validator Keystore Service
+//!
+//! Encrypts and decrypts EIP-2335 validator keystores. New keystores are
+//! written from a secret together with its public key: the one already
+//! recorded when changing a password, or [`bls::public_key`] for keys
+//! derived by the [`StakingService`](super::StakingService).
+//!
+//! [`bls::public_key`]: super::bls::public_key
+
+#[cfg(feature = "native")]
+use crate::config;
+#[cfg(feature = "native")]
+use crate::errors::FileSystemError;
+use crate::errors::{AuthenticationError, CryptographicError, UserInputError, WalletError, WalletResult};
+use crate::models::validator_keystore::{
+    BlsSecretKey, CryptoModule, EmptyParams, ValidatorCipherParams, ValidatorCrypto,
+    ValidatorKdfParams, ValidatorKeystore, EIP2335_VERSION,
+};
+use aes::cipher::{KeyIvInit, StreamCipher};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "native")]
+use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// KDF choice for new validator keystores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidatorKdf {
+    /// scrypt (n=2^18, r=8, p=1)
+    Scrypt,
+    /// PBKDF2-HMAC-SHA256 (c=2^18)
+    Pbkdf2,
+}
+
+impl std::str::FromStr for ValidatorKdf {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "scrypt" => Ok(Self::Scrypt),
+            "pbkdf2" => Ok(Self::Pbkdf2),
+            _ => Err(UserInputError::InvalidParameters {
+                parameter: "kdf".to_string(),
+                value: s.to_string(),
+                expected: "scrypt or pbkdf2".to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// EIP-2335 keystore operations
+pub struct ValidatorKeystoreService;
+
+impl ValidatorKeystoreService {
+    /// Read a keystore file
+    #[cfg(feature = "native")]
+    pub async fn load(path: &Path) -> WalletResult<ValidatorKeystore> {
+        crate::utils::validate_file_path(path)?;
+
+        let json = tokio::fs::read_to_string(path).await.map_err(|_| {
+            FileSystemError::FileNotFound {
+                path: path.display().to_string(),
+                directory: path.parent().map(|p| p.display().to_string()).unwrap_or_default(),
+            }
+        })?;
+
+        ValidatorKeystore::from_json(&json)
+    }
+
+    /// Write a keystore file, refusing to overwrite
+    #[cfg(feature = "native")]
+    pub async fn save(keystore: &ValidatorKeystore, path: &Path) -> WalletResult<()> {
+        crate::utils::validate_file_path(path)?;
+
+        if path.exists() {
+            return Err(FileSystemError::FileExists {
+                path: path.display().to_string(),
+                suggestion: "Choose a different output path".to_string(),
+            }
+            .into());
+        }
+
+        crate::utils::write_atomic(
+            path,
+            keystore.to_json()?.as_bytes(),
+            config::fs::KEYSTORE_FILE_PERMISSIONS,
+        )
+    }
+
+    /// Verify the password and return the secret key
+    #[tracing::instrument(level = "debug", skip_all, fields(kdf = keystore.kdf_name()))]
+    pub fn decrypt(keystore: &ValidatorKeystore, password: &str) -> WalletResult<BlsSecretKey> {
+        let decryption_key = Self::derive_key(&keystore.crypto.kdf.params, password)?;
+        let cipher_message = Self::decode_hex("cipher.message", &keystore.crypto.cipher.message)?;
+
+        let checksum = Self::checksum(&decryption_key, &cipher_message);
+        if hex::encode(checksum) != keystore.crypto.checksum.message.to_lowercase() {
+            return Err(AuthenticationError::WrongPassword {
+                wallet_file: String::new(),
+                attempts_remaining: None,
+            }
+            .into());
+        }
+
+        let iv = Self::decode_hex("cipher.params.iv", &keystore.crypto.cipher.params.iv)?;
+        let mut secret = Zeroizing::new(cipher_message);
+        Self::apply_cipher(&decryption_key, &iv, &mut secret)?;
+
+        BlsSecretKey::from_bytes(&secret)
+    }
+
+    /// Encrypt a secret key into a new keystore
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub fn encrypt(
+        secret: &BlsSecretKey,
+        pubkey: &str,
+        path: &str,
+        password: &str,
+        kdf: ValidatorKdf,
+    ) -> WalletResult<ValidatorKeystore> {
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut iv);
+
+        let kdf_params = match kdf {
+            ValidatorKdf::Scrypt => ValidatorKdfParams::Scrypt {
+                dklen: 32,
+                n: 262_144,
+                r: 8,
+                p: 1,
+                salt: hex::encode(salt),
+            },
+            ValidatorKdf::Pbkdf2 => ValidatorKdfParams::Pbkdf2 {
+                dklen: 32,
+                c: 262_144,
+                prf: "hmac-sha256".to_string(),
+                salt: hex::encode(salt),
+            },
+        };
+
+        let decryption_key = Self::derive_key(&kdf_params, password)?;
+        let mut cipher_message = secret.as_bytes().to_vec();
+        Self::apply_cipher(&decryption_key, &iv, &mut cipher_message)?;
+        let checksum = Self::checksum(&decryption_key, &cipher_message);
+
+        let keystore = ValidatorKeystore {
+            crypto: ValidatorCrypto {
+                kdf: CryptoModule {
+                    function: match kdf {
+                        ValidatorKdf::Scrypt => "scrypt".to_string(),
+                        ValidatorKdf::Pbkdf2 => "pbkdf2".to_string(),
+                    },
+                    params: kdf_params,
+                    message: String::new(),
+                },
+                checksum: CryptoModule {
+                    function: "sha256".to_string(),
+                    params: EmptyParams::default(),
+                    message: hex::encode(checksum),
+                },
+                cipher: CryptoModule {
+                    function: "aes-128-ctr".to_string(),
+                    params: ValidatorCipherParams { iv: hex::encode(iv) },
+                    message: hex::encode(cipher_message),
+                },
+            },
+            description: None,
+            pubkey: pubkey.trim_start_matches("0x").to_lowercase(),
+            path: path.to_string(),
+            uuid: crate::utils::random_uuid(),
+            version: EIP2335_VERSION,
+        };
+        keystore.validate()?;
+
+        Ok(keystore)
+    }
+
+    /// EIP-2335 password processing: NFKD, then strip control characters
+    fn normalize_password(password: &str) -> Zeroizing<String> {
+        Zeroizing::new(
+            password
+                .nfkd()
+                .filter(|c| !matches!(*c as u32, 0x00..=0x1f | 0x7f..=0x9f))
+                .collect(),
+        )
+    }
+
+    fn derive_key(params: &ValidatorKdfParams, password: &str) -> WalletResult<Zeroizing<[u8; 32]>> {
+        let password = Self::normalize_password(password);
+        let mut key = Zeroizing::new([0u8; 32]);
+        let kdf_error = |details: String| CryptographicError::KdfFailed { details };
+
+        match params {
+            ValidatorKdfParams::Scrypt { dklen, n, r, p, salt } => {
+                if *dklen != 32 || !n.is_power_of_two() {
+                    return Err(kdf_error(format!("unsupported scrypt parameters dklen={}, n={}", dklen, n)).into());
+                }
+                let salt = Self::decode_hex("kdf.params.salt", salt)?;
+                let params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p)
+                    .map_err(|e| kdf_error(e.to_string()))?;
+                scrypt::scrypt(password.as_bytes(), &salt, &params, key.as_mut())
+                    .map_err(|e| kdf_error(e.to_string()))?;
+            }
+            ValidatorKdfParams::Pbkdf2 { dklen, c, prf, salt } => {
+                if *dklen != 32 || prf != "hmac-sha256" {
+                    return Err(kdf_error(format!("unsupported pbkdf2 parameters dklen={}, prf={}", dklen, prf)).into());
+                }
+                let salt = Self::decode_hex("kdf.params.salt", salt)?;
+                pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, *c, key.as_mut());
+            }
+        }
+
+        Ok(key)
+    }
+
+    /// SHA-256 of the second half of the decryption key and the ciphertext
+    fn checksum(decryption_key: &[u8; 32], cipher_message: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(&decryption_key[16..]);
+        hasher.update(cipher_message);
+        hasher.finalize().into()
+    }
+
+    /// AES-128-CTR keyed with the first half of the decryption key
+    fn apply_cipher(decryption_key: &[u8; 32], iv: &[u8], data: &mut [u8]) -> WalletResult<()> {
+        let mut cipher = Aes128Ctr::new_from_slices(&decryption_key[..16], iv).map_err(|_| {
+            CryptographicError::DataCorruption {
+                details: format!("Invalid IV length {}", iv.len()),
+            }
+        })?;
+        cipher.apply_keystream(data);
+        Ok(())
+    }
+
+    fn decode_hex(field: &str, value: &str) -> WalletResult<Vec<u8>> {
+        hex::decode(value).map_err(|e| {
+            CryptographicError::DataCorruption {
+                details: format!("{}: {}", field, e),
+            }
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PBKDF2 test vector from EIP-2335
+    const PBKDF2_VECTOR: &str = r#"{
+        "crypto": {
+            "kdf": {
+                "function": "pbkdf2",
+                "params": {
+                    "dklen": 32,
+                    "c": 262144,
+                    "prf": "hmac-sha256",
+                    "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                },
+                "message": ""
+            },
+            "checksum": {
+                "function": "sha256",
+                "params": {},
+                "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+            },
+            "cipher": {
+                "function": "aes-128-ctr",
+                "params": {
+                    "iv": "264daa3f303d7259501c93d997d84fe6"
+                },
+                "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+            }
+        },
+        "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+        "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+        "path": "m/12381/60/0/0",
+        "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+        "version": 4
+    }"#;
+
+    const VECTOR_PASSWORD: &str = "\u{1d531}\u{1d522}\u{1d530}\u{1d531}\u{1d52d}\u{1d51e}\u{1d530}\u{1d530}\u{1d534}\u{1d52c}\u{1d52f}\u{1d521}\u{1f511}";
+    const VECTOR_SECRET: &str = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+
+    #[test]
+    fn test_decrypt_eip2335_vector() {
+        let keystore = ValidatorKeystore::from_json(PBKDF2_VECTOR).unwrap();
+        let secret = ValidatorKeystoreService::decrypt(&keystore, VECTOR_PASSWORD).unwrap();
+        assert_eq!(hex::encode(secret.as_bytes()), VECTOR_SECRET);
+
+        assert!(ValidatorKeystoreService::decrypt(&keystore, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_password_normalization() {
+        assert_eq!(
+            ValidatorKeystoreService::normalize_password(VECTOR_PASSWORD).as_str(),
+            "testpassword\u{1f511}"
+        );
+        assert_eq!(ValidatorKeystoreService::normalize_password("pass\u{7f}\nword").as_str(), "password");
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let keystore = ValidatorKeystore::from_json(PBKDF2_VECTOR).unwrap();
+        let secret = BlsSecretKey::from_bytes(&hex::decode(VECTOR_SECRET).unwrap()).unwrap();
+
+        let reencrypted = ValidatorKeystoreService::encrypt(
+            &secret,
+            &keystore.pubkey,
+            &keystore.path,
+            "NewPassword123!",
+            ValidatorKdf::Pbkdf2,
+        )
+        .unwrap();
+
+        let parsed = ValidatorKeystore::from_json(&reencrypted.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.pubkey, keystore.pubkey);
+        assert_eq!(parsed.uuid.len(), 36);
+
+        let decrypted = ValidatorKeystoreService::decrypt(&parsed, "NewPassword123!").unwrap();
+        assert_eq!(decrypted.as_bytes(), secret.as_bytes());
+    }
+}
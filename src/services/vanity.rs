@@ -0,0 +1,332 @@
+//! # Vanity Address Service
+//!
+//! Searches for an address with a chosen hex prefix and/or suffix, either
+//! among fresh random keys or along the derivation indices of an existing
+//! seed. Worker threads share one attempt counter, and the calling thread
+//! reports throughput while they run. Matching is case-insensitive by
+//! default; case-sensitive patterns are checked against the EIP-55
+//! checksummed form, which costs 2x per letter.
+
+use crate::errors::{CryptographicError, UserInputError, WalletResult};
+use crate::models::{DerivationPath, PathPreset, Wallet};
+use coins_bip32::xkeys::{Parent, XPriv};
+use ethers::core::k256::ecdsa::{SigningKey, VerifyingKey};
+use ethers::types::H160;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// How often the search reports progress
+pub const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Attempts a worker makes between updates of the shared counter
+const ATTEMPT_BATCH: u64 = 256;
+
+/// Address pattern to search for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VanityPattern {
+    prefix: String,
+    suffix: String,
+    lower_prefix: String,
+    lower_suffix: String,
+    case_sensitive: bool,
+}
+
+impl VanityPattern {
+    /// Pattern from hex prefix and suffix (a leading `0x` on the prefix is ignored)
+    pub fn new(prefix: Option<&str>, suffix: Option<&str>, case_sensitive: bool) -> WalletResult<Self> {
+        let prefix = prefix.map(|prefix| prefix.strip_prefix("0x").unwrap_or(prefix)).unwrap_or_default();
+        let suffix = suffix.unwrap_or_default();
+
+        for (parameter, value) in [("prefix", prefix), ("suffix", suffix)] {
+            if !value.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(UserInputError::InvalidParameters {
+                    parameter: parameter.to_string(),
+                    value: value.to_string(),
+                    expected: "hexadecimal characters (0-9, a-f)".to_string(),
+                }
+                .into());
+            }
+        }
+        if prefix.is_empty() && suffix.is_empty() || prefix.len() + suffix.len() > 40 {
+            return Err(UserInputError::InvalidParameters {
+                parameter: "prefix/suffix".to_string(),
+                value: format!("{}...{}", prefix, suffix),
+                expected: "between 1 and 40 hex characters in total".to_string(),
+            }
+            .into());
+        }
+
+        Ok(Self {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            lower_prefix: prefix.to_ascii_lowercase(),
+            lower_suffix: suffix.to_ascii_lowercase(),
+            case_sensitive,
+        })
+    }
+
+    /// Check if an address matches
+    pub fn matches(&self, address: &H160) -> bool {
+        let lower = hex::encode(address);
+        if !lower.starts_with(&self.lower_prefix) || !lower.ends_with(&self.lower_suffix) {
+            return false;
+        }
+        if !self.case_sensitive {
+            return true;
+        }
+
+        // The checksum costs another keccak, so only candidates get one
+        let checksummed = ethers::utils::to_checksum(address, None);
+        checksummed[2..].starts_with(&self.prefix) && checksummed.ends_with(&self.suffix)
+    }
+
+    /// Expected number of attempts to find a match
+    pub fn difficulty(&self) -> f64 {
+        self.prefix
+            .chars()
+            .chain(self.suffix.chars())
+            .map(|c| if self.case_sensitive && c.is_ascii_alphabetic() { 32.0 } else { 16.0 })
+            .product()
+    }
+}
+
+/// Search progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VanityProgress {
+    /// Addresses tried so far
+    pub attempts: u64,
+    /// Time since the search started
+    pub elapsed: Duration,
+}
+
+impl VanityProgress {
+    /// Addresses tried per second
+    pub fn rate(&self) -> f64 {
+        self.attempts as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// A matching address
+#[derive(Debug)]
+pub struct VanityMatch {
+    /// EIP-55 checksummed address
+    pub address: String,
+    /// Private key, for random searches
+    pub private_key: Option<Zeroizing<[u8; 32]>>,
+    /// Derivation path, for seed searches
+    pub derivation_path: Option<DerivationPath>,
+    /// Index the layout places the path at, for seed searches
+    pub index: Option<u32>,
+    /// Search progress when the match was found
+    pub progress: VanityProgress,
+}
+
+/// Vanity address search
+pub struct VanityService;
+
+impl VanityService {
+    /// Search random private keys until one matches
+    #[tracing::instrument(level = "debug", skip_all, fields(workers = workers))]
+    pub fn search_random(
+        pattern: &VanityPattern,
+        workers: usize,
+        on_progress: impl FnMut(&VanityProgress),
+    ) -> WalletResult<VanityMatch> {
+        Self::search(workers, on_progress, |_, stop, attempts, found| {
+            let mut rng = rand::thread_rng();
+            let mut batch = 0;
+            while !stop.load(Ordering::Relaxed) {
+                let key = SigningKey::random(&mut rng);
+                let address = Self::address_of(key.verifying_key());
+                if pattern.matches(&address) {
+                    let private_key = Zeroizing::new(<[u8; 32]>::from(key.to_bytes()));
+                    let _ = found.send(Candidate {
+                        address,
+                        private_key: Some(private_key),
+                        derivation_path: None,
+                        index: None,
+                    });
+                    break;
+                }
+                batch += 1;
+                if batch == ATTEMPT_BATCH {
+                    attempts.fetch_add(batch, Ordering::Relaxed);
+                    batch = 0;
+                }
+            }
+            attempts.fetch_add(batch, Ordering::Relaxed);
+            Ok(())
+        })
+    }
+
+    /// Search derivation indices of an HD wallet from `start` until one matches
+    #[tracing::instrument(level = "debug", skip_all, fields(base = %base, workers = workers))]
+    pub fn search_seed(
+        wallet: &Wallet,
+        layout: PathPreset,
+        base: &DerivationPath,
+        start: u32,
+        pattern: &VanityPattern,
+        workers: usize,
+        on_progress: impl FnMut(&VanityProgress),
+    ) -> WalletResult<VanityMatch> {
+        let key_error = |path: &DerivationPath| CryptographicError::InvalidDerivationPath {
+            path: path.to_string(),
+            expected: "a derivable BIP32 path".to_string(),
+        };
+        let base_key = wallet.master_key()?.derive_path(base.to_bip32()).map_err(|_| key_error(base))?;
+
+        Self::search(workers, on_progress, |worker, stop, attempts, found| {
+            let mut index = start.checked_add(worker as u32);
+            let mut batch = 0;
+            while let Some(current) = index {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                // Only the levels below the base vary between indices
+                let path = match layout.account_path(base, current) {
+                    Ok(path) => path,
+                    Err(_) => break,
+                };
+                let key = path.components()[base.depth()..]
+                    .iter()
+                    .try_fold(base_key.clone(), |key: XPriv, child| key.derive_child(child.to_bip32()))
+                    .map_err(|_| key_error(&path))?;
+                let signing_key: &SigningKey = key.as_ref();
+                let address = Self::address_of(signing_key.verifying_key());
+                if pattern.matches(&address) {
+                    let _ = found.send(Candidate {
+                        address,
+                        private_key: None,
+                        derivation_path: Some(path),
+                        index: Some(current),
+                    });
+                    break;
+                }
+
+                batch += 1;
+                if batch == ATTEMPT_BATCH {
+                    attempts.fetch_add(batch, Ordering::Relaxed);
+                    batch = 0;
+                }
+                index = current.checked_add(workers as u32);
+            }
+            attempts.fetch_add(batch, Ordering::Relaxed);
+            Ok(())
+        })
+    }
+
+    /// Run `worker` on `workers` threads and report progress until one finds a match
+    fn search<W>(
+        workers: usize,
+        mut on_progress: impl FnMut(&VanityProgress),
+        worker: W,
+    ) -> WalletResult<VanityMatch>
+    where
+        W: Fn(usize, &AtomicBool, &AtomicU64, mpsc::Sender<Candidate>) -> WalletResult<()> + Sync,
+    {
+        let workers = workers.max(1);
+        let stop = AtomicBool::new(false);
+        let attempts = AtomicU64::new(0);
+        let started = Instant::now();
+        let progress = || VanityProgress {
+            attempts: attempts.load(Ordering::Relaxed),
+            elapsed: started.elapsed(),
+        };
+
+        std::thread::scope(|scope| {
+            let (found, results) = mpsc::channel();
+            let handles: Vec<_> = (0..workers)
+                .map(|id| {
+                    let found = found.clone();
+                    let (worker, stop, attempts) = (&worker, &stop, &attempts);
+                    scope.spawn(move || worker(id, stop, attempts, found))
+                })
+                .collect();
+            drop(found);
+
+            let candidate = loop {
+                match results.recv_timeout(PROGRESS_INTERVAL) {
+                    Ok(candidate) => break Some(candidate),
+                    Err(mpsc::RecvTimeoutError::Timeout) => on_progress(&progress()),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break None,
+                }
+            };
+            stop.store(true, Ordering::Relaxed);
+
+            for handle in handles {
+                handle.join().expect("vanity worker panicked")?;
+            }
+
+            let candidate = candidate.ok_or_else(|| CryptographicError::AddressGenerationFailed {
+                details: "search space exhausted without a matching address".to_string(),
+            })?;
+            Ok(VanityMatch {
+                address: ethers::utils::to_checksum(&candidate.address, None),
+                private_key: candidate.private_key,
+                derivation_path: candidate.derivation_path,
+                index: candidate.index,
+                progress: progress(),
+            })
+        })
+    }
+
+    /// Ethereum address of a public key
+    fn address_of(key: &VerifyingKey) -> H160 {
+        let point = key.to_encoded_point(false);
+        H160::from_slice(&ethers::utils::keccak256(&point.as_bytes()[1..])[12..])
+    }
+}
+
+/// Match sent from a worker to the reporting thread
+struct Candidate {
+    address: H160,
+    private_key: Option<Zeroizing<[u8; 32]>>,
+    derivation_path: Option<DerivationPath>,
+    index: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Network;
+
+    #[test]
+    fn test_pattern_matching() {
+        let address: H160 = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse().unwrap();
+
+        assert!(VanityPattern::new(Some("0x5AAE"), Some("aed"), false).unwrap().matches(&address));
+        assert!(VanityPattern::new(Some("5aAeb"), None, true).unwrap().matches(&address));
+        assert!(!VanityPattern::new(Some("5aaeb"), None, true).unwrap().matches(&address));
+        assert!(!VanityPattern::new(None, Some("beef"), false).unwrap().matches(&address));
+
+        assert!(VanityPattern::new(Some("xyz"), None, false).is_err());
+        assert!(VanityPattern::new(None, None, false).is_err());
+        assert_eq!(VanityPattern::new(Some("a1"), None, false).unwrap().difficulty(), 256.0);
+        assert_eq!(VanityPattern::new(Some("a1"), None, true).unwrap().difficulty(), 512.0);
+    }
+
+    #[test]
+    fn test_search_finds_match() {
+        let pattern = VanityPattern::new(Some("a"), None, false).unwrap();
+        let found = VanityService::search_random(&pattern, 2, |_| {}).unwrap();
+        assert!(found.address.to_lowercase().starts_with("0xa"));
+        let key = SigningKey::from_bytes(found.private_key.as_ref().unwrap().as_ref().into()).unwrap();
+        assert_eq!(ethers::utils::to_checksum(&VanityService::address_of(key.verifying_key()), None), found.address);
+
+        let wallet = Wallet::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            &Network::Mainnet,
+            None,
+        )
+        .unwrap();
+        let base = PathPreset::Metamask.base_path();
+        let found = VanityService::search_seed(&wallet, PathPreset::Metamask, &base, 0, &pattern, 3, |_| {}).unwrap();
+        let derived = wallet.derive_at(found.derivation_path.as_ref().unwrap()).unwrap();
+        assert_eq!(derived.address().to_lowercase(), found.address.to_lowercase());
+        assert_eq!(found.derivation_path.unwrap(), base.child(found.index.unwrap()).unwrap());
+    }
+}
@@ -2,103 +2,546 @@
 //!
 //! High-level wallet management service that coordinates all wallet operations.
 
-use crate::errors::{WalletResult};
-use crate::models::{Address, Wallet};
-use crate::services::{crypto::CryptoService, mnemonic::MnemonicService};
+use crate::config;
+use crate::errors::{AuthenticationError, CryptographicError, UserInputError, WalletError, WalletResult};
+use crate::models::{Address, EthereumAddress, Keystore, Wallet};
+use crate::security::unlock_limit::UnlockAttempts;
+use crate::services::blocking::BlockingWalletManager;
+use crate::services::events::{WalletObserver, WalletOrigin};
+use crate::services::manifest::ChecksumManifest;
+use crate::services::signer::KeySigner;
+use crate::services::crypto::{CryptoService, HardwareBinding, HardwareTokens, SecretPassword, SecureString};
+use crate::services::mnemonic::MnemonicService;
+use crate::services::threshold::ThresholdService;
 use crate::WalletConfig;
-use std::path::Path;
+use async_trait::async_trait;
+use ethers::types::Signature;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use zeroize::Zeroizing;
+
+/// A keystore written by [`WalletManager::create_wallets`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreatedKeystore {
+    /// Position in the batch, from 1
+    pub index: usize,
+    /// Wallet address
+    pub address: EthereumAddress,
+    /// Keystore file
+    pub path: PathBuf,
+}
+
+/// Core wallet operations, for callers that want to swap in a fake
+///
+/// [`WalletManager`] is the real implementation. Code written against
+/// `&dyn WalletOps` can be tested with an in-memory stand-in that touches
+/// neither the filesystem nor the KDF.
+#[async_trait]
+pub trait WalletOps: Send + Sync {
+    /// Create a new wallet with `word_count` mnemonic words
+    async fn create_wallet(&self, word_count: u8) -> WalletResult<Wallet>;
+
+    /// Import a wallet from a BIP39 mnemonic
+    async fn import_from_mnemonic(&self, mnemonic: &str) -> WalletResult<Wallet>;
+
+    /// Import a wallet from a private key
+    async fn import_from_private_key(&self, private_key: &str) -> WalletResult<Wallet>;
+
+    /// Encrypt `wallet` with `password` into a new keystore at `path`
+    async fn save_wallet(&self, wallet: &Wallet, path: &Path, password: &dyn SecretPassword) -> WalletResult<()>;
+
+    /// Decrypt the keystore at `path`
+    async fn load_wallet(&self, path: &Path, password: &dyn SecretPassword) -> WalletResult<Wallet>;
+
+    /// Address at account `index` of `wallet`
+    async fn derive_address(&self, wallet: &Wallet, index: u32) -> WalletResult<Address>;
+}
 
 /// Main wallet management service
 pub struct WalletManager {
     config: WalletConfig,
+    observers: Vec<Arc<dyn WalletObserver>>,
+    hardware: HardwareTokens,
 }
 
 impl WalletManager {
     /// Create a new wallet manager
     pub fn new(config: WalletConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            observers: Vec::new(),
+            hardware: HardwareTokens::default(),
+        }
+    }
+
+    /// Blocking facade for callers without an async runtime
+    ///
+    /// Fails only if the facade's own runtime cannot be started.
+    pub fn blocking(self) -> WalletResult<BlockingWalletManager> {
+        BlockingWalletManager::new(self)
+    }
+
+    /// Answer hardware factors with `hardware` instead of the real devices
+    pub fn set_hardware(&mut self, hardware: HardwareTokens) {
+        self.hardware = hardware;
+    }
+
+    /// Register an observer for manager events
+    pub fn add_observer(&mut self, observer: Arc<dyn WalletObserver>) {
+        self.observers.push(observer);
+    }
+
+    fn notify(&self, event: impl Fn(&dyn WalletObserver)) {
+        for observer in &self.observers {
+            event(observer.as_ref());
+        }
+    }
+
+    /// Record a written keystore in its directory's checksum manifest, then tell observers
+    fn saved(&self, address: &EthereumAddress, path: &Path) {
+        // The keystore itself is safely on disk; a stale manifest only shows up in verify-manifest
+        if let Err(e) = ChecksumManifest::record_file(path) {
+            tracing::warn!("Could not update the checksum manifest: {}", e);
+        }
+        self.notify(|o| o.on_keystore_saved(address, path));
+    }
+
+    fn created(&self, wallet: WalletResult<Wallet>, origin: WalletOrigin) -> WalletResult<Wallet> {
+        if let Ok(wallet) = &wallet {
+            self.notify(|o| o.on_wallet_created(wallet.address(), wallet.network(), origin));
+        }
+        wallet
     }
 
     /// Create a new wallet with specified word count
     pub async fn create_wallet(&self, word_count: u8) -> WalletResult<Wallet> {
-        let mnemonic = MnemonicService::generate(word_count)?;
-        Wallet::from_mnemonic(
+        self.create_wallet_in(word_count, bip39::Language::English).await
+    }
+
+    /// Create a new wallet with a mnemonic from the `language` wordlist
+    #[tracing::instrument(level = "debug", skip_all, fields(word_count = word_count, language = %language))]
+    pub async fn create_wallet_in(&self, word_count: u8, language: bip39::Language) -> WalletResult<Wallet> {
+        let mnemonic = MnemonicService::generate_in(language, word_count)?;
+        let wallet = Wallet::from_mnemonic(
             mnemonic.phrase(),
             &self.config.network,
             None,
-        )
+        );
+        self.created(wallet, WalletOrigin::Generated)
     }
 
     /// Import wallet from mnemonic
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn import_from_mnemonic(&self, mnemonic_str: &str) -> WalletResult<Wallet> {
         let mnemonic = MnemonicService::validate(mnemonic_str)?;
-        Wallet::from_mnemonic(
+        let wallet = Wallet::from_mnemonic(
             mnemonic.phrase(),
             &self.config.network,
             None,
-        )
+        );
+        self.created(wallet, WalletOrigin::Mnemonic)
+    }
+
+    /// Import an HD wallet from a raw BIP39 seed (hex)
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub async fn import_from_seed(&self, seed_hex: &str) -> WalletResult<Wallet> {
+        let seed = Zeroizing::new(Self::decode_secret_hex(seed_hex, "seed")?);
+        let wallet = Wallet::from_seed(&seed, &self.config.network, None);
+        self.created(wallet, WalletOrigin::Seed)
+    }
+
+    /// Import wallet from BIP39 entropy (hex), regenerating the mnemonic in `language`
+    #[tracing::instrument(level = "debug", skip_all, fields(language = %language))]
+    pub async fn import_from_entropy(&self, entropy_hex: &str, language: bip39::Language) -> WalletResult<Wallet> {
+        let entropy = Zeroizing::new(Self::decode_secret_hex(entropy_hex, "entropy")?);
+        let wallet = Wallet::from_entropy(&entropy, language, &self.config.network, None);
+        self.created(wallet, WalletOrigin::Mnemonic)
+    }
+
+    /// Decode hex key material, with or without a `0x` prefix
+    fn decode_secret_hex(value: &str, parameter: &str) -> WalletResult<Vec<u8>> {
+        let value = value.trim();
+        hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| {
+            CryptographicError::InvalidPrivateKey {
+                details: format!("{} is not hex: {}", parameter, e),
+                expected: "hex bytes, optionally prefixed with 0x".to_string(),
+            }
+            .into()
+        })
     }
 
     /// Import wallet from private key
+    #[tracing::instrument(level = "debug", skip_all)]
     pub async fn import_from_private_key(&self, private_key: &str) -> WalletResult<Wallet> {
-        Wallet::from_private_key(
+        let wallet = Wallet::from_private_key(
             private_key,
             &self.config.network,
             None,
-        )
+        );
+        self.created(wallet, WalletOrigin::PrivateKey)
     }
 
     /// Save wallet to encrypted file
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
     pub async fn save_wallet(
         &self,
         wallet: &Wallet,
         path: &Path,
-        password: &str,
+        password: &impl SecretPassword,
+    ) -> WalletResult<()> {
+        self.save_wallet_bound(wallet, path, password, None).await
+    }
+
+    /// Save wallet to encrypted file with a plain string password
+    #[deprecated(since = "1.1.0", note = "pass the password as a `SecureString` to `save_wallet`")]
+    pub async fn save_wallet_str(&self, wallet: &Wallet, path: &Path, password: &str) -> WalletResult<()> {
+        self.save_wallet(wallet, path, &SecureString::from(password)).await
+    }
+
+    /// Save wallet to encrypted file that also needs `binding` to decrypt
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    pub async fn save_wallet_bound(
+        &self,
+        wallet: &Wallet,
+        path: &Path,
+        password: &impl SecretPassword,
+        binding: Option<HardwareBinding>,
     ) -> WalletResult<()> {
         // Validate password strength
-        CryptoService::validate_password(password)?;
+        CryptoService::validate_password(password.expose_password())?;
 
-        // Encrypt wallet data using Argon2id
-        let keystore = CryptoService::encrypt_wallet(wallet, password, true)?;
+        // Encrypt wallet data using Argon2id at the configured cost
+        let keystore = self.encrypt(wallet, password, binding)?;
 
         // Save keystore to file
-        CryptoService::save_keystore(&keystore, path).await
+        CryptoService::save_keystore(&keystore, path).await?;
+        self.saved(wallet.address(), path);
+        Ok(())
+    }
+
+    /// Save wallet to encrypted file that any `threshold` of `holders` can open together
+    ///
+    /// Each holder is a label and passphrase; the keystore itself is
+    /// encrypted with a random data key split among them.
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display(), threshold = threshold))]
+    pub async fn save_wallet_shared(
+        &self,
+        wallet: &Wallet,
+        path: &Path,
+        threshold: u8,
+        holders: &[(String, Zeroizing<String>)],
+        binding: Option<HardwareBinding>,
+    ) -> WalletResult<()> {
+        for (_, passphrase) in holders {
+            CryptoService::validate_password(passphrase)?;
+        }
+
+        let (data_key, shares) = ThresholdService::split(threshold, holders, self.config.kdf_settings())?;
+        let mut keystore = self.encrypt(wallet, &data_key, binding)?;
+        keystore.crypto.shares = Some(shares);
+
+        CryptoService::save_keystore(&keystore, path).await?;
+        self.saved(wallet.address(), path);
+        Ok(())
+    }
+
+    /// Encrypt at the configured cost, refusing weak KDF settings unless allowed
+    fn encrypt(&self, wallet: &Wallet, password: &impl SecretPassword, binding: Option<HardwareBinding>) -> WalletResult<Keystore> {
+        let settings = self.config.kdf_settings();
+        let keystore = match binding {
+            Some(binding) => CryptoService::encrypt_wallet_bound(wallet, password, settings, binding, &self.hardware)?,
+            None => CryptoService::encrypt_wallet_with(wallet, password, settings)?,
+        };
+        if !self.config.allow_weak_kdf {
+            CryptoService::check_kdf_strength(&keystore)?;
+        }
+        Ok(keystore)
+    }
+
+    /// Re-encrypt a wallet over its existing keystore file
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    pub async fn update_wallet(
+        &self,
+        wallet: &Wallet,
+        path: &Path,
+        password: &impl SecretPassword,
+    ) -> WalletResult<()> {
+        let existing = CryptoService::load_keystore(path).await?;
+        let binding = existing.crypto.hardware.as_ref().map(HardwareBinding::of);
+        self.reencrypt(wallet, path, password, existing, binding).await
+    }
+
+    /// Re-encrypt a wallet over its keystore file so it also needs `binding`, or the password alone when `None`
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    pub async fn set_hardware_binding(
+        &self,
+        wallet: &Wallet,
+        path: &Path,
+        password: &impl SecretPassword,
+        binding: Option<HardwareBinding>,
+    ) -> WalletResult<()> {
+        let existing = CryptoService::load_keystore(path).await?;
+        self.reencrypt(wallet, path, password, existing, binding).await
+    }
+
+    async fn reencrypt(
+        &self,
+        wallet: &Wallet,
+        path: &Path,
+        password: &impl SecretPassword,
+        existing: Keystore,
+        binding: Option<HardwareBinding>,
+    ) -> WalletResult<()> {
+        let mut keystore = self.encrypt(wallet, password, binding)?;
+
+        // Tags, notes and key shares live outside the ciphertext; carry them over
+        keystore.metadata.tags = existing.metadata.tags;
+        keystore.metadata.notes = existing.metadata.notes;
+        keystore.crypto.shares = existing.crypto.shares;
+
+        CryptoService::replace_keystore(&keystore, path).await?;
+        self.saved(wallet.address(), path);
+        Ok(())
+    }
+
+    /// Create `count` independent wallets, each saved to its own keystore in `dir`
+    ///
+    /// Keystores are named `NNNN-<address>.json` and all use `password`.
+    /// Encryption runs on the blocking pool with the same bound as
+    /// [`Self::load_wallets`]. The first failure stops the batch; keystores
+    /// already written stay in place.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = count, dir = %dir.display()))]
+    pub async fn create_wallets(
+        &self,
+        count: usize,
+        word_count: u8,
+        language: bip39::Language,
+        dir: &Path,
+        password: &impl SecretPassword,
+    ) -> WalletResult<Vec<CreatedKeystore>> {
+        if count == 0 || count > config::fs::MAX_BATCH_WALLETS {
+            return Err(UserInputError::ValueOutOfRange {
+                parameter: "count".to_string(),
+                value: count.to_string(),
+                range: format!("1-{}", config::fs::MAX_BATCH_WALLETS),
+            }
+            .into());
+        }
+        CryptoService::validate_password(password.expose_password())?;
+
+        let parallelism = config::resources::parallelism(config::crypto::MAX_CONCURRENT_UNLOCKS, self.config.is_low_resource());
+        let permits = Arc::new(Semaphore::new(parallelism));
+        let password = Arc::new(SecureString::from(password.expose_password()));
+        let check_kdf = !self.config.allow_weak_kdf;
+        let kdf = self.config.kdf_settings();
+
+        let mut pending = Vec::with_capacity(count);
+        for _ in 0..count {
+            let wallet = self.create_wallet_in(word_count, language).await?;
+            let permit = Arc::clone(&permits).acquire_owned().await.expect("encryption semaphore is never closed");
+            let password = Arc::clone(&password);
+            pending.push(tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                let keystore = CryptoService::encrypt_wallet_with(&wallet, &*password, kdf)?;
+                if check_kdf {
+                    CryptoService::check_kdf_strength(&keystore)?;
+                }
+                Ok::<_, WalletError>((*wallet.address(), keystore))
+            }));
+        }
+
+        let width = count.to_string().len().max(4);
+        let mut created = Vec::with_capacity(count);
+        for (position, task) in pending.into_iter().enumerate() {
+            let (address, keystore) = task.await.map_err(|e| CryptographicError::KdfFailed {
+                details: format!("Encryption task failed: {}", e),
+            })??;
+
+            let index = position + 1;
+            let path = dir.join(format!("{:0width$}-{}.{}", index, address.to_lowercase(), config::KEYSTORE_EXTENSION));
+            CryptoService::save_keystore(&keystore, &path).await?;
+            self.saved(&address, &path);
+            created.push(CreatedKeystore { index, address, path });
+        }
+        Ok(created)
     }
 
     /// Load wallet from encrypted file
-    pub async fn load_wallet(&self, path: &Path, password: &str) -> WalletResult<Wallet> {
+    #[tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))]
+    pub async fn load_wallet(&self, path: &Path, password: &impl SecretPassword) -> WalletResult<Wallet> {
         // Load keystore from file
         let keystore = CryptoService::load_keystore(path).await?;
 
-        // Decrypt and return wallet
-        CryptoService::decrypt_wallet(&keystore, password)
+        // Decrypt and return wallet, unless wrong passwords locked it out
+        let mut attempts = UnlockAttempts::load(path)?;
+        let wallet = attempts
+            .check(chrono::Utc::now(), self.config.reset_lockout)
+            .and_then(|()| CryptoService::decrypt_wallet_bound(&keystore, password, &self.hardware));
+        let wallet = Self::record_attempt(path, &mut attempts, wallet);
+        if let Err(e) = &wallet {
+            self.notify(|o| o.on_decrypt_failed(path, e));
+        }
+        wallet
+    }
+
+    /// Load wallet from encrypted file with a plain string password
+    #[deprecated(since = "1.1.0", note = "pass the password as a `SecureString` to `load_wallet`")]
+    pub async fn load_wallet_str(&self, path: &Path, password: &str) -> WalletResult<Wallet> {
+        self.load_wallet(path, &SecureString::from(password)).await
+    }
+
+    /// Count a wrong password, or clear the count after a successful unlock
+    ///
+    /// A wrong password comes back naming the file and the attempts left.
+    fn record_attempt(path: &Path, attempts: &mut UnlockAttempts, wallet: WalletResult<Wallet>) -> WalletResult<Wallet> {
+        let (recorded, wallet) = match wallet {
+            Ok(wallet) => (attempts.record_success(), Ok(wallet)),
+            Err(WalletError::Authentication(AuthenticationError::WrongPassword { .. })) => {
+                let recorded = attempts.record_failure(chrono::Utc::now());
+                let error = AuthenticationError::WrongPassword {
+                    wallet_file: path.display().to_string(),
+                    attempts_remaining: Some(attempts.remaining()),
+                };
+                (recorded, Err(error.into()))
+            }
+            Err(e) => (Ok(()), Err(e)),
+        };
+        if let Err(e) = recorded {
+            tracing::warn!("Could not update the failed-unlock count: {}", e);
+        }
+        wallet
+    }
+
+    /// Unlock several keystores, decrypting concurrently on the blocking pool
+    ///
+    /// `password_provider` is asked for each readable keystore in order, so it may
+    /// prompt. At most [`config::crypto::MAX_CONCURRENT_UNLOCKS`] decryptions
+    /// run at once, one in low-resource mode. Results come back in the order of `paths`; one file
+    /// failing does not stop the others.
+    #[tracing::instrument(level = "debug", skip_all, fields(count = paths.len()))]
+    pub async fn load_wallets<F>(&self, paths: &[PathBuf], mut password_provider: F) -> Vec<(PathBuf, WalletResult<Wallet>)>
+    where
+        F: FnMut(&Path) -> WalletResult<Zeroizing<String>>,
+    {
+        let parallelism = config::resources::parallelism(config::crypto::MAX_CONCURRENT_UNLOCKS, self.config.is_low_resource());
+        let permits = Arc::new(Semaphore::new(parallelism));
+
+        let mut pending = Vec::with_capacity(paths.len());
+        for path in paths {
+            let prepared = match CryptoService::load_keystore(path).await {
+                Ok(keystore) => UnlockAttempts::load(path).and_then(|attempts| {
+                    attempts.check(chrono::Utc::now(), self.config.reset_lockout)?;
+                    password_provider(path).map(|password| (keystore, password, attempts))
+                }),
+                Err(e) => Err(e),
+            };
+            let task = match prepared {
+                Ok((keystore, password, attempts)) => {
+                    let permit = Arc::clone(&permits).acquire_owned().await.expect("unlock semaphore is never closed");
+                    let hardware = self.hardware.clone();
+                    Ok((tokio::task::spawn_blocking(move || {
+                        let _permit = permit;
+                        CryptoService::decrypt_wallet_bound(&keystore, &password, &hardware)
+                    }), attempts))
+                }
+                Err(e) => Err(e),
+            };
+            pending.push((path.clone(), task));
+        }
+
+        let mut results = Vec::with_capacity(pending.len());
+        for (path, task) in pending {
+            let wallet = match task {
+                Ok((handle, mut attempts)) => {
+                    let wallet = handle.await.unwrap_or_else(|e| {
+                        Err(CryptographicError::DecryptionFailed {
+                            context: format!("Decryption task failed: {}", e),
+                        }
+                        .into())
+                    });
+                    Self::record_attempt(&path, &mut attempts, wallet)
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &wallet {
+                self.notify(|o| o.on_decrypt_failed(&path, e));
+            }
+            results.push((path, wallet));
+        }
+        results
+    }
+
+    /// Sign a digest with `signer`, notifying observers of the signature
+    #[tracing::instrument(level = "debug", skip_all, fields(backend = signer.backend()))]
+    pub fn sign_hash(&self, signer: &dyn KeySigner, hash: &[u8; 32]) -> WalletResult<Signature> {
+        let signature = signer.sign_hash(hash)?;
+        self.notify(|o| o.on_signature_produced(&signer.address(), hash));
+        Ok(signature)
+    }
+
+    /// Sign an EIP-191 personal message with `signer`, notifying observers of the signature
+    pub fn sign_message(&self, signer: &dyn KeySigner, message: &[u8]) -> WalletResult<Signature> {
+        self.sign_hash(signer, &ethers::utils::hash_message(message).0)
     }
 
     /// Derive address from wallet
+    #[tracing::instrument(level = "debug", skip_all, fields(index = index))]
     pub async fn derive_address(&self, wallet: &Wallet, index: u32) -> WalletResult<Address> {
         let derived = wallet.derive_address(index)?;
         Address::derived(
             derived.address().to_string(),
-            wallet.network().to_string(),
+            wallet.network().clone(),
             index,
-            derived.derivation_path().to_string(),
+            derived.derivation_path().clone(),
         )
     }
 }
 
+#[async_trait]
+impl WalletOps for WalletManager {
+    async fn create_wallet(&self, word_count: u8) -> WalletResult<Wallet> {
+        WalletManager::create_wallet(self, word_count).await
+    }
+
+    async fn import_from_mnemonic(&self, mnemonic: &str) -> WalletResult<Wallet> {
+        WalletManager::import_from_mnemonic(self, mnemonic).await
+    }
+
+    async fn import_from_private_key(&self, private_key: &str) -> WalletResult<Wallet> {
+        WalletManager::import_from_private_key(self, private_key).await
+    }
+
+    async fn save_wallet(&self, wallet: &Wallet, path: &Path, password: &dyn SecretPassword) -> WalletResult<()> {
+        WalletManager::save_wallet(self, wallet, path, &password).await
+    }
+
+    async fn load_wallet(&self, path: &Path, password: &dyn SecretPassword) -> WalletResult<Wallet> {
+        WalletManager::load_wallet(self, path, &password).await
+    }
+
+    async fn derive_address(&self, wallet: &Wallet, index: u32) -> WalletResult<Address> {
+        WalletManager::derive_address(self, wallet, index).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Network;
     use tempfile::TempDir;
 
     fn test_config() -> WalletConfig {
         let temp_dir = TempDir::new().unwrap();
         WalletConfig {
-            network: "mainnet".to_string(),
+            network: Network::Mainnet,
             wallet_dir: temp_dir.path().to_path_buf(),
             kdf_iterations: 1,
             kdf_memory: 1024,
             kdf_parallelism: 1,
+            allow_weak_kdf: true,
+            ..WalletConfig::default()
         }
     }
 
@@ -108,8 +551,8 @@ mod tests {
         let wallet = manager.create_wallet(12).await.unwrap();
 
         assert_eq!(wallet.mnemonic().split_whitespace().count(), 12);
-        assert!(wallet.address().starts_with("0x"));
-        assert_eq!(wallet.address().len(), 42);
+        assert!(wallet.address().to_string().starts_with("0x"));
+        assert_eq!(wallet.address().to_string().len(), 42);
     }
 
     #[tokio::test]
@@ -120,6 +563,199 @@ mod tests {
         let wallet = manager.import_from_mnemonic(test_mnemonic).await.unwrap();
 
         assert_eq!(wallet.mnemonic(), test_mnemonic);
-        assert!(wallet.address().starts_with("0x"));
+        assert!(wallet.address().to_string().starts_with("0x"));
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl WalletObserver for RecordingObserver {
+        fn on_wallet_created(&self, address: &EthereumAddress, _network: &Network, origin: WalletOrigin) {
+            self.events.lock().unwrap().push(format!("created {:?} {}", origin, address));
+        }
+
+        fn on_keystore_saved(&self, address: &EthereumAddress, _path: &Path) {
+            self.events.lock().unwrap().push(format!("saved {}", address));
+        }
+
+        fn on_decrypt_failed(&self, _path: &Path, _error: &crate::WalletError) {
+            self.events.lock().unwrap().push("decrypt failed".to_string());
+        }
+
+        fn on_signature_produced(&self, address: &str, _hash: &[u8; 32]) {
+            self.events.lock().unwrap().push(format!("signed {}", address));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observer_events() {
+        let config = test_config();
+        let path = config.wallet_dir.join("observed.json");
+        let observer = Arc::new(RecordingObserver::default());
+        let mut manager = WalletManager::new(config);
+        manager.add_observer(observer.clone());
+
+        let wallet = manager.import_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ).await.unwrap();
+        manager.save_wallet(&wallet, &path, &SecureString::from("Correct-Horse-42")).await.unwrap();
+        assert!(manager.load_wallet(&path, &SecureString::from("Wrong-Horse-42")).await.is_err());
+
+        let signer = crate::services::LocalKeySigner::from_wallet(&wallet, 0).unwrap();
+        manager.sign_message(&signer, b"hello").unwrap();
+
+        let address = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+        assert_eq!(*observer.events.lock().unwrap(), vec![
+            format!("created Mnemonic {}", address),
+            format!("saved {}", address),
+            "decrypt failed".to_string(),
+            format!("signed {}", address),
+        ]);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_accounts_round_trip() {
+        let config = test_config();
+        let path = config.wallet_dir.join("multi.json");
+        let manager = WalletManager::new(config);
+        let password = &SecureString::from("Correct-Horse-42");
+
+        let mut wallet = manager.import_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ).await.unwrap();
+        manager.save_wallet(&wallet, &path, password).await.unwrap();
+
+        wallet.add_account(3, Some("ops".to_string())).unwrap();
+        manager.update_wallet(&wallet, &path, password).await.unwrap();
+
+        let loaded = manager.load_wallet(&path, password).await.unwrap();
+        assert_eq!(loaded.accounts(), wallet.accounts());
+    }
+
+    #[tokio::test]
+    async fn test_load_wallets_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = WalletManager::new(test_config());
+        let paths: Vec<PathBuf> = ["a.json", "b.json", "c.json", "missing.json"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+
+        let mut addresses = Vec::new();
+        for (path, password) in paths[..3].iter().zip(["Password-A-123", "Password-B-123", "Password-C-123"]) {
+            let wallet = manager.create_wallet(12).await.unwrap();
+            manager.save_wallet(&wallet, path, &SecureString::from(password)).await.unwrap();
+            addresses.push(wallet.address().to_string());
+        }
+
+        // b.json gets the wrong password
+        let mut asked = Vec::new();
+        let results = manager
+            .load_wallets(&paths, |path| {
+                asked.push(path.to_path_buf());
+                let name = path.file_stem().unwrap().to_str().unwrap().to_uppercase();
+                let name = if name == "B" { "X".to_string() } else { name };
+                Ok(Zeroizing::new(format!("Password-{}-123", name)))
+            })
+            .await;
+
+        assert_eq!(asked, paths[..3]);
+        assert_eq!(results.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>(), paths);
+        assert_eq!(*results[0].1.as_ref().unwrap().address(), addresses[0]);
+        assert_eq!(
+            results[1].1.as_ref().unwrap_err(),
+            &WalletError::Authentication(AuthenticationError::WrongPassword {
+                wallet_file: paths[1].display().to_string(),
+                attempts_remaining: Some(config::unlock::HARD_LOCK_ATTEMPTS - 1),
+            })
+        );
+        assert_eq!(*results[2].1.as_ref().unwrap().address(), addresses[2]);
+        assert!(results[3].1.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_wallets_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = WalletManager::new(test_config());
+
+        let created = manager
+            .create_wallets(3, 12, bip39::Language::English, temp_dir.path(), &SecureString::from("Password-Batch-123"))
+            .await
+            .unwrap();
+        assert_eq!(created.iter().map(|c| c.index).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(created[0].path.file_name().unwrap().to_str().unwrap().starts_with("0001-0x"));
+        assert_ne!(created[0].address, created[1].address);
+
+        let wallet = manager.load_wallet(&created[2].path, &SecureString::from("Password-Batch-123")).await.unwrap();
+        assert_eq!(*wallet.address(), created[2].address);
+
+        assert!(manager.create_wallets(0, 12, bip39::Language::English, temp_dir.path(), &SecureString::from("Password-Batch-123")).await.is_err());
+    }
+
+    /// In-memory stand-in: keystores are plain wallets in a map, passwords compared as-is
+    #[derive(Default)]
+    struct FakeWallets(std::sync::Mutex<std::collections::HashMap<PathBuf, (String, Wallet)>>);
+
+    #[async_trait]
+    impl WalletOps for FakeWallets {
+        async fn create_wallet(&self, word_count: u8) -> WalletResult<Wallet> {
+            Wallet::generate(word_count, &Network::Mainnet, None)
+        }
+
+        async fn import_from_mnemonic(&self, mnemonic: &str) -> WalletResult<Wallet> {
+            Wallet::from_mnemonic(mnemonic, &Network::Mainnet, None)
+        }
+
+        async fn import_from_private_key(&self, private_key: &str) -> WalletResult<Wallet> {
+            Wallet::from_private_key(private_key, &Network::Mainnet, None)
+        }
+
+        async fn save_wallet(&self, wallet: &Wallet, path: &Path, password: &dyn SecretPassword) -> WalletResult<()> {
+            let entry = (password.expose_password().to_string(), wallet.clone());
+            self.0.lock().unwrap().insert(path.to_path_buf(), entry);
+            Ok(())
+        }
+
+        async fn load_wallet(&self, path: &Path, password: &dyn SecretPassword) -> WalletResult<Wallet> {
+            match self.0.lock().unwrap().get(path) {
+                Some((stored, wallet)) if stored == password.expose_password() => Ok(wallet.clone()),
+                _ => Err(AuthenticationError::WrongPassword {
+                    wallet_file: path.display().to_string(),
+                    attempts_remaining: None,
+                }
+                .into()),
+            }
+        }
+
+        async fn derive_address(&self, wallet: &Wallet, index: u32) -> WalletResult<Address> {
+            let derived = wallet.derive_address(index)?;
+            Address::derived(derived.address().to_string(), wallet.network().clone(), index, derived.derivation_path().clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wallet_ops_substitutable() {
+        // Application code written against the trait
+        async fn import_and_reopen(ops: &dyn WalletOps, path: &Path) -> WalletResult<String> {
+            let wallet = ops.import_from_mnemonic(
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+            ).await?;
+            let password = SecureString::from("Correct-Horse-42");
+            ops.save_wallet(&wallet, path, &password).await?;
+            assert!(ops.load_wallet(path, &SecureString::from("Wrong-Horse-42")).await.is_err());
+            let loaded = ops.load_wallet(path, &password).await?;
+            Ok(ops.derive_address(&loaded, 1).await?.address().to_string())
+        }
+
+        let config = test_config();
+        let path = config.wallet_dir.join("ops.json");
+        let real: Box<dyn WalletOps> = Box::new(WalletManager::new(config));
+        let fake: Box<dyn WalletOps> = Box::<FakeWallets>::default();
+
+        let from_real = import_and_reopen(real.as_ref(), &path).await.unwrap();
+        assert_eq!(import_and_reopen(fake.as_ref(), Path::new("nowhere.json")).await.unwrap(), from_real);
+        assert!(!Path::new("nowhere.json").exists());
+    }
+}
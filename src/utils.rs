@@ -3,8 +3,16 @@
 //! Common utility functions and helpers used throughout the application.
 //! All utilities follow security-first principles with proper validation.
 
-use crate::errors::{ValidationError, WalletResult};
-use std::path::Path;
+use crate::errors::{CryptographicError, FileSystemError, ValidationError, WalletResult};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Order `n` of the secp256k1 group, big-endian; valid private keys are `1..n`
+const SECP256K1_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
 
 /// Validate Ethereum address format
 pub fn validate_ethereum_address(address: &str) -> WalletResult<()> {
@@ -29,77 +37,109 @@ pub fn validate_ethereum_address(address: &str) -> WalletResult<()> {
         .into());
     }
 
+    // Mixed case claims an EIP-55 checksum, which must then be right
+    let mixed_case = addr.chars().any(|c| c.is_ascii_lowercase()) && addr.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case {
+        let parsed: ethers::types::H160 = addr.parse().map_err(|_| ValidationError::InvalidAddressFormat {
+            address: address.to_string(),
+            expected: "hexadecimal characters only".to_string(),
+        })?;
+        if ethers::utils::to_checksum(&parsed, None)[2..] != *addr {
+            return Err(ValidationError::InvalidAddressFormat {
+                address: address.to_string(),
+                expected: format!(
+                    "a valid EIP-55 checksum (mistyped character?), or an all-lowercase address; checksummed it reads {}",
+                    ethers::utils::to_checksum(&parsed, None)
+                ),
+            }
+            .into());
+        }
+    }
+
     Ok(())
 }
 
 /// Validate private key format
 pub fn validate_private_key(private_key: &str) -> WalletResult<()> {
-    // Remove 0x prefix if present
-    let key = private_key.strip_prefix("0x").unwrap_or(private_key);
+    decode_private_key(private_key).map(|_| ())
+}
 
-    // Check length (64 hex characters)
-    if key.len() != 64 {
-        return Err(ValidationError::InvalidAddressFormat {
-            address: private_key.to_string(),
-            expected: "64 hex characters (with or without 0x prefix)".to_string(),
-        }
-        .into());
-    }
+/// Decode a private key pasted in any supported encoding.
+///
+/// Accepts hex (with or without `0x`), a bracketed byte array such as
+/// `[12, 44, ...]`, WIF (Base58Check with the `0x80`/`0xef` version byte and
+/// optional compression flag), and standard or URL-safe base64.
+pub fn decode_private_key(input: &str) -> WalletResult<Zeroizing<[u8; 32]>> {
+    use base64::Engine;
 
-    // Check if all characters are valid hex
-    if !key.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Err(ValidationError::InvalidAddressFormat {
-            address: private_key.to_string(),
-            expected: "hexadecimal characters only".to_string(),
+    let input = input.trim();
+    let invalid = |details: String| CryptographicError::InvalidPrivateKey {
+        details,
+        expected: "32-byte key as hex, base64, WIF, or a [byte, ...] array".to_string(),
+    };
+
+    let bytes: Zeroizing<Vec<u8>> = if let Some(list) = input.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        let bytes: Result<Vec<u8>, _> = list.split(',').map(|b| b.trim().parse::<u8>()).collect();
+        Zeroizing::new(bytes.map_err(|e| invalid(format!("Invalid byte array: {}", e)))?)
+    } else if let Some(hex_key) = hex_key(input) {
+        Zeroizing::new(hex::decode(hex_key).map_err(|e| invalid(e.to_string()))?)
+    } else if let Ok(wif) = bs58::decode(input).with_check(None).into_vec() {
+        let wif = Zeroizing::new(wif);
+        match wif.as_slice() {
+            [0x80 | 0xef, key @ ..] if key.len() == 32 => Zeroizing::new(key.to_vec()),
+            [0x80 | 0xef, key @ .., 0x01] if key.len() == 32 => Zeroizing::new(key.to_vec()),
+            _ => return Err(invalid("Base58Check data is not a WIF private key".to_string()).into()),
         }
-        .into());
-    }
+    } else if let Ok(decoded) = base64::engine::general_purpose::STANDARD
+        .decode(input)
+        .or_else(|_| base64::engine::general_purpose::URL_SAFE.decode(input))
+    {
+        Zeroizing::new(decoded)
+    } else {
+        return Err(invalid("Unrecognized private key encoding".to_string()).into());
+    };
 
-    Ok(())
+    let key: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| invalid(format!("Expected 32 bytes, got {}", bytes.len())))?;
+    let key = Zeroizing::new(key);
+    check_scalar_range(&key)?;
+    Ok(key)
 }
 
-/// Validate HD derivation path format
-pub fn validate_derivation_path(path: &str) -> WalletResult<()> {
-    // Check if path starts with m/
-    if !path.starts_with("m/") {
-        return Err(ValidationError::InvalidAddressFormat {
-            address: path.to_string(),
-            expected: "path starting with 'm/'".to_string(),
+/// Reject scalars that are not valid secp256k1 private keys
+fn check_scalar_range(key: &[u8; 32]) -> WalletResult<()> {
+    let expected = "a scalar between 1 and the secp256k1 group order n - 1".to_string();
+    if key.iter().all(|&b| b == 0) {
+        return Err(CryptographicError::InvalidPrivateKey {
+            details: "Private key is zero, which has no public key".to_string(),
+            expected,
         }
         .into());
     }
-
-    // Split and validate each component
-    let components: Vec<&str> = path[2..].split('/').collect();
-    for component in components {
-        if component.is_empty() {
-            return Err(ValidationError::InvalidAddressFormat {
-                address: path.to_string(),
-                expected: "non-empty path components".to_string(),
-            }
-            .into());
-        }
-
-        // Check for hardened derivation (')
-        let (num_str, _) = if component.ends_with('\'') {
-            (&component[..component.len() - 1], true)
-        } else {
-            (component, false)
-        };
-
-        // Validate that component is a number
-        if num_str.parse::<u32>().is_err() {
-            return Err(ValidationError::InvalidAddressFormat {
-                address: path.to_string(),
-                expected: "numeric path components".to_string(),
-            }
-            .into());
+    // Big-endian byte arrays of equal length compare like the integers they encode
+    if key[..] >= SECP256K1_ORDER[..] {
+        return Err(CryptographicError::InvalidPrivateKey {
+            details: "Private key is not below the secp256k1 group order n (0xffff...d0364141)".to_string(),
+            expected,
         }
+        .into());
     }
-
     Ok(())
 }
 
+/// Hex digits of a hex-encoded key, if `input` looks like one
+fn hex_key(input: &str) -> Option<&str> {
+    let key = input.strip_prefix("0x").unwrap_or(input);
+    (key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit())).then_some(key)
+}
+
+/// Validate HD derivation path format
+pub fn validate_derivation_path(path: &str) -> WalletResult<()> {
+    path.parse::<crate::models::DerivationPath>().map(|_| ())
+}
+
 /// Validate file path for security (prevent path traversal)
 pub fn validate_file_path<P: AsRef<Path>>(path: P) -> WalletResult<()> {
     let path = path.as_ref();
@@ -117,6 +157,125 @@ pub fn validate_file_path<P: AsRef<Path>>(path: P) -> WalletResult<()> {
     Ok(())
 }
 
+/// Atomically write `data` to `path`.
+///
+/// The data is written to a temporary file in the same directory, flushed to
+/// disk, and then renamed over the destination, so a crash mid-write can never
+/// leave a truncated file behind. The temporary file is locked down before any
+/// data is written (`mode` on Unix, an owner-only DACL on Windows) and on Unix
+/// the parent directory is fsynced after the rename.
+pub fn write_atomic(path: &Path, data: &[u8], mode: u32) -> WalletResult<()> {
+    write_atomic_with(path, mode, |file| file.write_all(data))
+}
+
+/// Atomic write with a caller-supplied writer (separated for testing)
+fn write_atomic_with<F>(path: &Path, mode: u32, write: F) -> WalletResult<()>
+where
+    F: FnOnce(&mut std::fs::File) -> std::io::Result<()>,
+{
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| FileSystemError::InvalidFormat {
+            path: path.display().to_string(),
+            details: "Path has no file name".to_string(),
+        })?;
+
+    let temp_path = dir.join(format!(".{}.tmp-{:016x}", file_name, rand::random::<u64>()));
+
+    let result = (|| -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(mode);
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+
+        let mut file = options.open(&temp_path)?;
+        #[cfg(unix)]
+        {
+            // Apply the mode explicitly so the process umask can't widen it
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+        }
+        #[cfg(windows)]
+        crate::security::permissions::restrict_to_owner(&temp_path).map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::PermissionDenied, e.to_string())
+        })?;
+        write(&mut file)?;
+        file.sync_all()?;
+        drop(file);
+
+        std::fs::rename(&temp_path, path)?;
+
+        #[cfg(unix)]
+        std::fs::File::open(&dir)?.sync_all()?;
+
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(FileSystemError::PermissionDenied {
+            path: path.display().to_string(),
+            operation: format!("atomic write: {}", e),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Serialize a value as canonical JSON.
+///
+/// Object keys are sorted lexicographically at every level and the output is
+/// compact (no insignificant whitespace), so the same value always produces the
+/// same bytes regardless of struct field order or serde_json feature flags.
+pub fn to_canonical_json<T: serde::Serialize>(value: &T) -> serde_json::Result<String> {
+    let value = sort_json_keys(serde_json::to_value(value)?);
+    serde_json::to_string(&value)
+}
+
+/// Recursively rebuild JSON objects with sorted keys
+fn sort_json_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_json_keys(v)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(sort_json_keys).collect())
+        }
+        other => other,
+    }
+}
+
+/// Random RFC 4122 version 4 UUID
+pub fn random_uuid() -> String {
+    use rand::RngCore;
+
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
 /// Sanitize filename to prevent invalid characters
 pub fn sanitize_filename(filename: &str) -> String {
     // Remove path separators and collect only alphanumeric and safe characters
@@ -165,8 +324,13 @@ mod tests {
     #[test]
     fn test_validate_ethereum_address() {
         // Valid addresses
-        assert!(validate_ethereum_address("0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99").is_ok());
-        assert!(validate_ethereum_address("742d35Cc6634C0532925a3b8D57c2b9b3f0B9a99").is_ok());
+        assert!(validate_ethereum_address("0x742d35cc6634c0532925a3b8d57c2b9b3f0b9a99").is_ok());
+        assert!(validate_ethereum_address("742D35CC6634C0532925A3B8D57C2B9B3F0B9A99").is_ok());
+        assert!(validate_ethereum_address("0x9858EfFD232B4033E47d90003D41EC34EcaEda94").is_ok());
+
+        // Mixed case with a wrong checksum
+        assert!(validate_ethereum_address("0x9858EfFD232B4033E47d90003D41EC34EcaEda9A").is_err());
+        assert!(validate_ethereum_address("0x9858eFFD232B4033E47d90003D41EC34EcaEda94").is_err());
 
         // Invalid addresses
         assert!(validate_ethereum_address("0x742d35Cc6634C0532925a3b8D57c2b9b3f0B9a9").is_err()); // Too short
@@ -174,6 +338,46 @@ mod tests {
         assert!(validate_ethereum_address("").is_err()); // Empty
     }
 
+    #[test]
+    fn test_decode_private_key_encodings() {
+        let expected = [1u8; 32];
+        let hex_key = hex::encode(expected);
+        assert_eq!(*decode_private_key(&hex_key).unwrap(), expected);
+        assert_eq!(*decode_private_key(&format!("0x{}", hex_key)).unwrap(), expected);
+
+        let array = format!("[{}]", expected.iter().map(u8::to_string).collect::<Vec<_>>().join(", "));
+        assert_eq!(*decode_private_key(&array).unwrap(), expected);
+
+        use base64::Engine;
+        let b64 = base64::engine::general_purpose::STANDARD.encode(expected);
+        assert_eq!(*decode_private_key(&b64).unwrap(), expected);
+
+        // Compressed WIF for the key 0x0101..01
+        let mut wif = vec![0x80];
+        wif.extend_from_slice(&expected);
+        wif.push(0x01);
+        let wif = bs58::encode(wif).with_check().into_string();
+        assert!(wif.starts_with('K') || wif.starts_with('L'));
+        assert_eq!(*decode_private_key(&wif).unwrap(), expected);
+
+        assert!(decode_private_key("[1, 2, 3]").is_err());
+        assert!(decode_private_key("[1, 2, 300]").is_err());
+        assert!(decode_private_key("not a key").is_err());
+        assert!(decode_private_key(&hex_key[2..]).is_err());
+    }
+
+    #[test]
+    fn test_private_key_curve_order() {
+        let order = hex::encode(SECP256K1_ORDER);
+        let below = format!("{}40", &order[..62]);
+        assert!(validate_private_key(&below).is_ok());
+
+        for key in [hex::encode([0u8; 32]), order, hex::encode([0xffu8; 32])] {
+            let err = validate_private_key(&key).unwrap_err();
+            assert_eq!(err.code(), "CRYPTO_003");
+        }
+    }
+
     #[test]
     fn test_validate_derivation_path() {
         // Valid paths
@@ -194,6 +398,76 @@ mod tests {
         assert_eq!(sanitize_filename("../../../etc/passwd"), "etcpasswd");
     }
 
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("wallet.json");
+
+        write_atomic(&path, b"first", 0o600).unwrap();
+        write_atomic(&path, b"second", 0o600).unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_atomic_interrupted_write_keeps_original() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("wallet.json");
+        write_atomic(&path, b"original keystore", 0o600).unwrap();
+
+        // Simulate a crash halfway through writing the replacement
+        let result = write_atomic_with(&path, 0o600, |file| {
+            file.write_all(b"trunc")?;
+            Err(std::io::Error::new(std::io::ErrorKind::Interrupted, "simulated crash"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"original keystore");
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_atomic_interrupted_first_write_leaves_nothing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("wallet.json");
+
+        let result = write_atomic_with(&path, 0o600, |_| {
+            Err(std::io::Error::new(std::io::ErrorKind::Interrupted, "simulated crash"))
+        });
+
+        assert!(result.is_err());
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_sets_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("wallet.json");
+        write_atomic(&path, b"data", 0o600).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let value = serde_json::json!({
+            "zeta": 1,
+            "alpha": {"b": [ {"y": 1, "x": 2} ], "a": "text"},
+        });
+
+        let json = to_canonical_json(&value).unwrap();
+        assert_eq!(json, r#"{"alpha":{"a":"text","b":[{"x":2,"y":1}]},"zeta":1}"#);
+
+        let reparsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(to_canonical_json(&reparsed).unwrap(), json);
+    }
+
     #[test]
     fn test_format_bytes() {
         assert_eq!(format_bytes(512), "512 B");
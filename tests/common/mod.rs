@@ -29,11 +29,12 @@ impl TestConfig {
         })?;
 
         let config = WalletConfig {
-            network: "testnet".to_string(),
+            network: web3wallet_cli::models::Network::Sepolia,
             wallet_dir: temp_dir.path().to_path_buf(),
             kdf_iterations: 1, // Fast iterations for testing
             kdf_memory: 1024,  // Low memory usage for testing
             kdf_parallelism: 1,
+            ..WalletConfig::default()
         };
 
         Ok(Self { temp_dir, config })
@@ -108,6 +109,12 @@ pub struct MockRng {
     counter: std::cell::RefCell<u64>,
 }
 
+impl Default for MockRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockRng {
     pub fn new() -> Self {
         Self {
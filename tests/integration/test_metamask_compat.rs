@@ -18,6 +18,7 @@ async fn test_metamask_address_compatibility() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -42,6 +43,7 @@ async fn test_metamask_hd_derivation_compatibility() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -66,6 +68,7 @@ async fn test_metamask_keystore_format() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
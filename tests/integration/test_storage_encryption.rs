@@ -13,6 +13,7 @@ async fn test_storage_encryption_roundtrip() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -44,6 +45,7 @@ async fn test_wrong_password_error() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -23,6 +23,7 @@ async fn test_wallet_creation_flow() -> WalletResult<()> {
         kdf_iterations: 1, // Fast for testing
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -56,6 +57,7 @@ async fn test_wallet_creation_performance() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -79,6 +81,7 @@ async fn test_wallet_creation_with_save() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -17,6 +17,7 @@ async fn test_wallet_import_from_mnemonic() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -39,6 +40,7 @@ async fn test_wallet_import_from_private_key() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);
@@ -62,6 +64,7 @@ async fn test_wallet_import_performance() -> WalletResult<()> {
         kdf_iterations: 1,
         kdf_memory: 1024,
         kdf_parallelism: 1,
+        ..WalletConfig::default()
     };
 
     let manager = WalletManager::new(config);